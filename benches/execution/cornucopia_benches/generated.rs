@@ -19,7 +19,7 @@ pub mod queries {
             pub name: T1,
             pub hair_color: Option<T2>,
         }
-        #[derive(Debug, Clone, PartialEq)]
+        #[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
         pub struct User {
             pub id: i32,
             pub name: String,
@@ -45,7 +45,7 @@ pub mod queries {
                 }
             }
         }
-        #[derive(Debug, Clone, PartialEq)]
+        #[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
         pub struct Post {
             pub id: i32,
             pub user_id: i32,
@@ -75,7 +75,7 @@ pub mod queries {
                 }
             }
         }
-        #[derive(Debug, Clone, PartialEq)]
+        #[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
         pub struct Comment {
             pub id: i32,
             pub post_id: i32,
@@ -95,7 +95,7 @@ pub mod queries {
                 }
             }
         }
-        #[derive(Debug, Clone, PartialEq)]
+        #[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
         pub struct SelectComplex {
             pub myuser_id: i32,
             pub name: String,
@@ -138,13 +138,15 @@ pub mod queries {
             }
         }
         pub mod sync {
-            use postgres::{fallible_iterator::FallibleIterator, GenericClient};
+            use cornucopia_sync::GenericClient;
+            use postgres::fallible_iterator::FallibleIterator;
             pub struct UserQuery<'a, C: GenericClient, T, const N: usize> {
                 client: &'a mut C,
                 params: [&'a (dyn postgres_types::ToSql + Sync); N],
                 stmt: &'a mut cornucopia_sync::private::Stmt,
                 extractor: fn(&postgres::Row) -> super::UserBorrowed,
                 mapper: fn(super::UserBorrowed) -> T,
+                info: cornucopia_sync::QueryInfo,
             }
             impl<'a, C, T: 'a, const N: usize> UserQuery<'a, C, T, N>
             where
@@ -160,42 +162,135 @@ pub mod queries {
                         stmt: self.stmt,
                         extractor: self.extractor,
                         mapper,
+                        info: self.info,
                     }
                 }
                 pub fn one(self) -> Result<T, postgres::Error> {
-                    let stmt = self.stmt.prepare(self.client)?;
-                    let row = self.client.query_one(stmt, &self.params)?;
-                    Ok((self.mapper)((self.extractor)(&row)))
+                    let info = self.info;
+                    cornucopia_sync::private::observe(
+                        &info,
+                        |_| Some(1),
+                        move || {
+                            let row = self.stmt.run(self.client, |client, stmt| {
+                                let params: &[&(dyn postgres_types::ToSql + Sync)] = &self.params;
+                                client.query_one(stmt, params)
+                            })?;
+                            Ok((self.mapper)((self.extractor)(&row)))
+                        },
+                    )
                 }
                 pub fn all(self) -> Result<Vec<T>, postgres::Error> {
-                    self.iter()?.collect()
+                    let info = self.info;
+                    cornucopia_sync::private::observe(
+                        &info,
+                        |it: &Vec<T>| Some(it.len() as u64),
+                        move || self.raw_iter()?.collect(),
+                    )
                 }
                 pub fn opt(self) -> Result<Option<T>, postgres::Error> {
-                    let stmt = self.stmt.prepare(self.client)?;
-                    Ok(self
-                        .client
-                        .query_opt(stmt, &self.params)?
-                        .map(|row| (self.mapper)((self.extractor)(&row))))
+                    let info = self.info;
+                    cornucopia_sync::private::observe(
+                        &info,
+                        |it: &Option<T>| Some(it.is_some().into()),
+                        move || {
+                            Ok(self
+                                .stmt
+                                .run(self.client, |client, stmt| {
+                                    let params: &[&(dyn postgres_types::ToSql + Sync)] =
+                                        &self.params;
+                                    client.query_opt(stmt, params)
+                                })?
+                                .map(|row| (self.mapper)((self.extractor)(&row))))
+                        },
+                    )
                 }
                 pub fn iter(
                     self,
                 ) -> Result<impl Iterator<Item = Result<T, postgres::Error>> + 'a, postgres::Error>
                 {
-                    let stmt = self.stmt.prepare(self.client)?;
+                    let info = self.info;
+                    cornucopia_sync::private::observe(&info, |_| None, move || self.raw_iter())
+                }
+                /// Passes each row to `visitor` as it is read, without building owned values,
+                /// returning the number of rows.
+                pub fn for_each(
+                    self,
+                    mut visitor: impl FnMut(super::UserBorrowed),
+                ) -> Result<u64, postgres::Error> {
+                    let info = self.info;
+                    cornucopia_sync::private::observe(
+                        &info,
+                        |it: &u64| Some(*it),
+                        move || {
+                            let rows = self.stmt.run_borrowed(
+                                self.client,
+                                &self.params,
+                                |client, stmt, params| {
+                                    client.query_raw(
+                                        stmt,
+                                        cornucopia_sync::private::slice_iter(params),
+                                    )
+                                },
+                            )?;
+                            let mut count = 0;
+                            let mut rows = rows;
+                            while let Some(row) = rows.next()? {
+                                visitor((self.extractor)(&row));
+                                count += 1;
+                            }
+                            Ok(count)
+                        },
+                    )
+                }
+                fn raw_iter(
+                    self,
+                ) -> Result<impl Iterator<Item = Result<T, postgres::Error>> + 'a, postgres::Error>
+                {
                     let it = self
-                        .client
-                        .query_raw(stmt, cornucopia_sync::private::slice_iter(&self.params))?
+                        .stmt
+                        .run_borrowed(self.client, &self.params, |client, stmt, params| {
+                            client.query_raw(stmt, cornucopia_sync::private::slice_iter(params))
+                        })?
                         .iterator()
                         .map(move |res| res.map(|row| (self.mapper)((self.extractor)(&row))));
                     Ok(it)
                 }
             }
+            impl<'a, C, T: 'a, const N: usize> UserQuery<'a, C, T, N>
+            where
+                C: cornucopia_sync::CursorClient,
+            {
+                /// Fetch rows through a portal, `batch_size` rows at a time.
+                pub fn cursor(
+                    self,
+                    batch_size: i32,
+                ) -> Result<impl Iterator<Item = Result<T, postgres::Error>> + 'a, postgres::Error>
+                {
+                    let info = self.info;
+                    cornucopia_sync::private::observe(
+                        &info,
+                        |_| None,
+                        move || {
+                            let stmt = self.stmt.prepare(self.client)?;
+                            let it = cornucopia_sync::private::cursor(
+                                self.client,
+                                stmt,
+                                &self.params,
+                                batch_size,
+                            )?
+                            .map(move |res| res.map(|row| (self.mapper)((self.extractor)(&row))));
+                            Ok(it)
+                        },
+                    )
+                }
+            }
             pub struct PostQuery<'a, C: GenericClient, T, const N: usize> {
                 client: &'a mut C,
                 params: [&'a (dyn postgres_types::ToSql + Sync); N],
                 stmt: &'a mut cornucopia_sync::private::Stmt,
                 extractor: fn(&postgres::Row) -> super::PostBorrowed,
                 mapper: fn(super::PostBorrowed) -> T,
+                info: cornucopia_sync::QueryInfo,
             }
             impl<'a, C, T: 'a, const N: usize> PostQuery<'a, C, T, N>
             where
@@ -211,42 +306,135 @@ pub mod queries {
                         stmt: self.stmt,
                         extractor: self.extractor,
                         mapper,
+                        info: self.info,
                     }
                 }
                 pub fn one(self) -> Result<T, postgres::Error> {
-                    let stmt = self.stmt.prepare(self.client)?;
-                    let row = self.client.query_one(stmt, &self.params)?;
-                    Ok((self.mapper)((self.extractor)(&row)))
+                    let info = self.info;
+                    cornucopia_sync::private::observe(
+                        &info,
+                        |_| Some(1),
+                        move || {
+                            let row = self.stmt.run(self.client, |client, stmt| {
+                                let params: &[&(dyn postgres_types::ToSql + Sync)] = &self.params;
+                                client.query_one(stmt, params)
+                            })?;
+                            Ok((self.mapper)((self.extractor)(&row)))
+                        },
+                    )
                 }
                 pub fn all(self) -> Result<Vec<T>, postgres::Error> {
-                    self.iter()?.collect()
+                    let info = self.info;
+                    cornucopia_sync::private::observe(
+                        &info,
+                        |it: &Vec<T>| Some(it.len() as u64),
+                        move || self.raw_iter()?.collect(),
+                    )
                 }
                 pub fn opt(self) -> Result<Option<T>, postgres::Error> {
-                    let stmt = self.stmt.prepare(self.client)?;
-                    Ok(self
-                        .client
-                        .query_opt(stmt, &self.params)?
-                        .map(|row| (self.mapper)((self.extractor)(&row))))
+                    let info = self.info;
+                    cornucopia_sync::private::observe(
+                        &info,
+                        |it: &Option<T>| Some(it.is_some().into()),
+                        move || {
+                            Ok(self
+                                .stmt
+                                .run(self.client, |client, stmt| {
+                                    let params: &[&(dyn postgres_types::ToSql + Sync)] =
+                                        &self.params;
+                                    client.query_opt(stmt, params)
+                                })?
+                                .map(|row| (self.mapper)((self.extractor)(&row))))
+                        },
+                    )
                 }
                 pub fn iter(
                     self,
                 ) -> Result<impl Iterator<Item = Result<T, postgres::Error>> + 'a, postgres::Error>
                 {
-                    let stmt = self.stmt.prepare(self.client)?;
+                    let info = self.info;
+                    cornucopia_sync::private::observe(&info, |_| None, move || self.raw_iter())
+                }
+                /// Passes each row to `visitor` as it is read, without building owned values,
+                /// returning the number of rows.
+                pub fn for_each(
+                    self,
+                    mut visitor: impl FnMut(super::PostBorrowed),
+                ) -> Result<u64, postgres::Error> {
+                    let info = self.info;
+                    cornucopia_sync::private::observe(
+                        &info,
+                        |it: &u64| Some(*it),
+                        move || {
+                            let rows = self.stmt.run_borrowed(
+                                self.client,
+                                &self.params,
+                                |client, stmt, params| {
+                                    client.query_raw(
+                                        stmt,
+                                        cornucopia_sync::private::slice_iter(params),
+                                    )
+                                },
+                            )?;
+                            let mut count = 0;
+                            let mut rows = rows;
+                            while let Some(row) = rows.next()? {
+                                visitor((self.extractor)(&row));
+                                count += 1;
+                            }
+                            Ok(count)
+                        },
+                    )
+                }
+                fn raw_iter(
+                    self,
+                ) -> Result<impl Iterator<Item = Result<T, postgres::Error>> + 'a, postgres::Error>
+                {
                     let it = self
-                        .client
-                        .query_raw(stmt, cornucopia_sync::private::slice_iter(&self.params))?
+                        .stmt
+                        .run_borrowed(self.client, &self.params, |client, stmt, params| {
+                            client.query_raw(stmt, cornucopia_sync::private::slice_iter(params))
+                        })?
                         .iterator()
                         .map(move |res| res.map(|row| (self.mapper)((self.extractor)(&row))));
                     Ok(it)
                 }
             }
+            impl<'a, C, T: 'a, const N: usize> PostQuery<'a, C, T, N>
+            where
+                C: cornucopia_sync::CursorClient,
+            {
+                /// Fetch rows through a portal, `batch_size` rows at a time.
+                pub fn cursor(
+                    self,
+                    batch_size: i32,
+                ) -> Result<impl Iterator<Item = Result<T, postgres::Error>> + 'a, postgres::Error>
+                {
+                    let info = self.info;
+                    cornucopia_sync::private::observe(
+                        &info,
+                        |_| None,
+                        move || {
+                            let stmt = self.stmt.prepare(self.client)?;
+                            let it = cornucopia_sync::private::cursor(
+                                self.client,
+                                stmt,
+                                &self.params,
+                                batch_size,
+                            )?
+                            .map(move |res| res.map(|row| (self.mapper)((self.extractor)(&row))));
+                            Ok(it)
+                        },
+                    )
+                }
+            }
             pub struct CommentQuery<'a, C: GenericClient, T, const N: usize> {
                 client: &'a mut C,
                 params: [&'a (dyn postgres_types::ToSql + Sync); N],
                 stmt: &'a mut cornucopia_sync::private::Stmt,
                 extractor: fn(&postgres::Row) -> super::CommentBorrowed,
                 mapper: fn(super::CommentBorrowed) -> T,
+                info: cornucopia_sync::QueryInfo,
             }
             impl<'a, C, T: 'a, const N: usize> CommentQuery<'a, C, T, N>
             where
@@ -262,42 +450,135 @@ pub mod queries {
                         stmt: self.stmt,
                         extractor: self.extractor,
                         mapper,
+                        info: self.info,
                     }
                 }
                 pub fn one(self) -> Result<T, postgres::Error> {
-                    let stmt = self.stmt.prepare(self.client)?;
-                    let row = self.client.query_one(stmt, &self.params)?;
-                    Ok((self.mapper)((self.extractor)(&row)))
+                    let info = self.info;
+                    cornucopia_sync::private::observe(
+                        &info,
+                        |_| Some(1),
+                        move || {
+                            let row = self.stmt.run(self.client, |client, stmt| {
+                                let params: &[&(dyn postgres_types::ToSql + Sync)] = &self.params;
+                                client.query_one(stmt, params)
+                            })?;
+                            Ok((self.mapper)((self.extractor)(&row)))
+                        },
+                    )
                 }
                 pub fn all(self) -> Result<Vec<T>, postgres::Error> {
-                    self.iter()?.collect()
+                    let info = self.info;
+                    cornucopia_sync::private::observe(
+                        &info,
+                        |it: &Vec<T>| Some(it.len() as u64),
+                        move || self.raw_iter()?.collect(),
+                    )
                 }
                 pub fn opt(self) -> Result<Option<T>, postgres::Error> {
-                    let stmt = self.stmt.prepare(self.client)?;
-                    Ok(self
-                        .client
-                        .query_opt(stmt, &self.params)?
-                        .map(|row| (self.mapper)((self.extractor)(&row))))
+                    let info = self.info;
+                    cornucopia_sync::private::observe(
+                        &info,
+                        |it: &Option<T>| Some(it.is_some().into()),
+                        move || {
+                            Ok(self
+                                .stmt
+                                .run(self.client, |client, stmt| {
+                                    let params: &[&(dyn postgres_types::ToSql + Sync)] =
+                                        &self.params;
+                                    client.query_opt(stmt, params)
+                                })?
+                                .map(|row| (self.mapper)((self.extractor)(&row))))
+                        },
+                    )
                 }
                 pub fn iter(
                     self,
                 ) -> Result<impl Iterator<Item = Result<T, postgres::Error>> + 'a, postgres::Error>
                 {
-                    let stmt = self.stmt.prepare(self.client)?;
+                    let info = self.info;
+                    cornucopia_sync::private::observe(&info, |_| None, move || self.raw_iter())
+                }
+                /// Passes each row to `visitor` as it is read, without building owned values,
+                /// returning the number of rows.
+                pub fn for_each(
+                    self,
+                    mut visitor: impl FnMut(super::CommentBorrowed),
+                ) -> Result<u64, postgres::Error> {
+                    let info = self.info;
+                    cornucopia_sync::private::observe(
+                        &info,
+                        |it: &u64| Some(*it),
+                        move || {
+                            let rows = self.stmt.run_borrowed(
+                                self.client,
+                                &self.params,
+                                |client, stmt, params| {
+                                    client.query_raw(
+                                        stmt,
+                                        cornucopia_sync::private::slice_iter(params),
+                                    )
+                                },
+                            )?;
+                            let mut count = 0;
+                            let mut rows = rows;
+                            while let Some(row) = rows.next()? {
+                                visitor((self.extractor)(&row));
+                                count += 1;
+                            }
+                            Ok(count)
+                        },
+                    )
+                }
+                fn raw_iter(
+                    self,
+                ) -> Result<impl Iterator<Item = Result<T, postgres::Error>> + 'a, postgres::Error>
+                {
                     let it = self
-                        .client
-                        .query_raw(stmt, cornucopia_sync::private::slice_iter(&self.params))?
+                        .stmt
+                        .run_borrowed(self.client, &self.params, |client, stmt, params| {
+                            client.query_raw(stmt, cornucopia_sync::private::slice_iter(params))
+                        })?
                         .iterator()
                         .map(move |res| res.map(|row| (self.mapper)((self.extractor)(&row))));
                     Ok(it)
                 }
             }
+            impl<'a, C, T: 'a, const N: usize> CommentQuery<'a, C, T, N>
+            where
+                C: cornucopia_sync::CursorClient,
+            {
+                /// Fetch rows through a portal, `batch_size` rows at a time.
+                pub fn cursor(
+                    self,
+                    batch_size: i32,
+                ) -> Result<impl Iterator<Item = Result<T, postgres::Error>> + 'a, postgres::Error>
+                {
+                    let info = self.info;
+                    cornucopia_sync::private::observe(
+                        &info,
+                        |_| None,
+                        move || {
+                            let stmt = self.stmt.prepare(self.client)?;
+                            let it = cornucopia_sync::private::cursor(
+                                self.client,
+                                stmt,
+                                &self.params,
+                                batch_size,
+                            )?
+                            .map(move |res| res.map(|row| (self.mapper)((self.extractor)(&row))));
+                            Ok(it)
+                        },
+                    )
+                }
+            }
             pub struct SelectComplexQuery<'a, C: GenericClient, T, const N: usize> {
                 client: &'a mut C,
                 params: [&'a (dyn postgres_types::ToSql + Sync); N],
                 stmt: &'a mut cornucopia_sync::private::Stmt,
                 extractor: fn(&postgres::Row) -> super::SelectComplexBorrowed,
                 mapper: fn(super::SelectComplexBorrowed) -> T,
+                info: cornucopia_sync::QueryInfo,
             }
             impl<'a, C, T: 'a, const N: usize> SelectComplexQuery<'a, C, T, N>
             where
@@ -313,41 +594,138 @@ pub mod queries {
                         stmt: self.stmt,
                         extractor: self.extractor,
                         mapper,
+                        info: self.info,
                     }
                 }
                 pub fn one(self) -> Result<T, postgres::Error> {
-                    let stmt = self.stmt.prepare(self.client)?;
-                    let row = self.client.query_one(stmt, &self.params)?;
-                    Ok((self.mapper)((self.extractor)(&row)))
+                    let info = self.info;
+                    cornucopia_sync::private::observe(
+                        &info,
+                        |_| Some(1),
+                        move || {
+                            let row = self.stmt.run(self.client, |client, stmt| {
+                                let params: &[&(dyn postgres_types::ToSql + Sync)] = &self.params;
+                                client.query_one(stmt, params)
+                            })?;
+                            Ok((self.mapper)((self.extractor)(&row)))
+                        },
+                    )
                 }
                 pub fn all(self) -> Result<Vec<T>, postgres::Error> {
-                    self.iter()?.collect()
+                    let info = self.info;
+                    cornucopia_sync::private::observe(
+                        &info,
+                        |it: &Vec<T>| Some(it.len() as u64),
+                        move || self.raw_iter()?.collect(),
+                    )
                 }
                 pub fn opt(self) -> Result<Option<T>, postgres::Error> {
-                    let stmt = self.stmt.prepare(self.client)?;
-                    Ok(self
-                        .client
-                        .query_opt(stmt, &self.params)?
-                        .map(|row| (self.mapper)((self.extractor)(&row))))
+                    let info = self.info;
+                    cornucopia_sync::private::observe(
+                        &info,
+                        |it: &Option<T>| Some(it.is_some().into()),
+                        move || {
+                            Ok(self
+                                .stmt
+                                .run(self.client, |client, stmt| {
+                                    let params: &[&(dyn postgres_types::ToSql + Sync)] =
+                                        &self.params;
+                                    client.query_opt(stmt, params)
+                                })?
+                                .map(|row| (self.mapper)((self.extractor)(&row))))
+                        },
+                    )
                 }
                 pub fn iter(
                     self,
                 ) -> Result<impl Iterator<Item = Result<T, postgres::Error>> + 'a, postgres::Error>
                 {
-                    let stmt = self.stmt.prepare(self.client)?;
+                    let info = self.info;
+                    cornucopia_sync::private::observe(&info, |_| None, move || self.raw_iter())
+                }
+                /// Passes each row to `visitor` as it is read, without building owned values,
+                /// returning the number of rows.
+                pub fn for_each(
+                    self,
+                    mut visitor: impl FnMut(super::SelectComplexBorrowed),
+                ) -> Result<u64, postgres::Error> {
+                    let info = self.info;
+                    cornucopia_sync::private::observe(
+                        &info,
+                        |it: &u64| Some(*it),
+                        move || {
+                            let rows = self.stmt.run_borrowed(
+                                self.client,
+                                &self.params,
+                                |client, stmt, params| {
+                                    client.query_raw(
+                                        stmt,
+                                        cornucopia_sync::private::slice_iter(params),
+                                    )
+                                },
+                            )?;
+                            let mut count = 0;
+                            let mut rows = rows;
+                            while let Some(row) = rows.next()? {
+                                visitor((self.extractor)(&row));
+                                count += 1;
+                            }
+                            Ok(count)
+                        },
+                    )
+                }
+                fn raw_iter(
+                    self,
+                ) -> Result<impl Iterator<Item = Result<T, postgres::Error>> + 'a, postgres::Error>
+                {
                     let it = self
-                        .client
-                        .query_raw(stmt, cornucopia_sync::private::slice_iter(&self.params))?
+                        .stmt
+                        .run_borrowed(self.client, &self.params, |client, stmt, params| {
+                            client.query_raw(stmt, cornucopia_sync::private::slice_iter(params))
+                        })?
                         .iterator()
                         .map(move |res| res.map(|row| (self.mapper)((self.extractor)(&row))));
                     Ok(it)
                 }
             }
+            impl<'a, C, T: 'a, const N: usize> SelectComplexQuery<'a, C, T, N>
+            where
+                C: cornucopia_sync::CursorClient,
+            {
+                /// Fetch rows through a portal, `batch_size` rows at a time.
+                pub fn cursor(
+                    self,
+                    batch_size: i32,
+                ) -> Result<impl Iterator<Item = Result<T, postgres::Error>> + 'a, postgres::Error>
+                {
+                    let info = self.info;
+                    cornucopia_sync::private::observe(
+                        &info,
+                        |_| None,
+                        move || {
+                            let stmt = self.stmt.prepare(self.client)?;
+                            let it = cornucopia_sync::private::cursor(
+                                self.client,
+                                stmt,
+                                &self.params,
+                                batch_size,
+                            )?
+                            .map(move |res| res.map(|row| (self.mapper)((self.extractor)(&row))));
+                            Ok(it)
+                        },
+                    )
+                }
+            }
             pub fn users() -> UsersStmt {
                 UsersStmt(cornucopia_sync::private::Stmt::new("SELECT * FROM users"))
             }
             pub struct UsersStmt(cornucopia_sync::private::Stmt);
             impl UsersStmt {
+                /// Uses `sql`, e.g. composed at runtime, instead of the annotated query.
+                /// It is prepared as is, so it must take the same parameters and return the same columns.
+                pub fn with_sql(sql: impl Into<std::borrow::Cow<'static, str>>) -> Self {
+                    Self(cornucopia_sync::private::Stmt::new(sql))
+                }
                 pub fn bind<'a, C: GenericClient>(
                     &'a mut self,
                     client: &'a mut C,
@@ -362,6 +740,10 @@ pub mod queries {
                             hair_color: row.get(2),
                         },
                         mapper: |it| <super::User>::from(it),
+                        info: cornucopia_sync::QueryInfo {
+                            module: "bench",
+                            query: "users",
+                        },
                     }
                 }
             }
@@ -372,6 +754,11 @@ pub mod queries {
             }
             pub struct InsertUserStmt(cornucopia_sync::private::Stmt);
             impl InsertUserStmt {
+                /// Uses `sql`, e.g. composed at runtime, instead of the annotated query.
+                /// It is prepared as is, so it must take the same parameters and return the same columns.
+                pub fn with_sql(sql: impl Into<std::borrow::Cow<'static, str>>) -> Self {
+                    Self(cornucopia_sync::private::Stmt::new(sql))
+                }
                 pub fn bind<
                     'a,
                     C: GenericClient,
@@ -383,8 +770,20 @@ pub mod queries {
                     name: &'a T1,
                     hair_color: &'a Option<T2>,
                 ) -> Result<u64, postgres::Error> {
-                    let stmt = self.0.prepare(client)?;
-                    client.execute(stmt, &[name, hair_color])
+                    cornucopia_sync::private::observe(
+                        &cornucopia_sync::QueryInfo {
+                            module: "bench",
+                            query: "insert_user",
+                        },
+                        |rows| Some(*rows),
+                        move || {
+                            self.0.run(client, |client, stmt| {
+                                let params: &[&(dyn postgres_types::ToSql + Sync)] =
+                                    &[name, hair_color];
+                                client.execute(stmt, params)
+                            })
+                        },
+                    )
                 }
             }
             impl<
@@ -413,6 +812,11 @@ pub mod queries {
             }
             pub struct PostsStmt(cornucopia_sync::private::Stmt);
             impl PostsStmt {
+                /// Uses `sql`, e.g. composed at runtime, instead of the annotated query.
+                /// It is prepared as is, so it must take the same parameters and return the same columns.
+                pub fn with_sql(sql: impl Into<std::borrow::Cow<'static, str>>) -> Self {
+                    Self(cornucopia_sync::private::Stmt::new(sql))
+                }
                 pub fn bind<'a, C: GenericClient>(
                     &'a mut self,
                     client: &'a mut C,
@@ -428,6 +832,10 @@ pub mod queries {
                             body: row.get(3),
                         },
                         mapper: |it| <super::Post>::from(it),
+                        info: cornucopia_sync::QueryInfo {
+                            module: "bench",
+                            query: "posts",
+                        },
                     }
                 }
             }
@@ -438,6 +846,11 @@ pub mod queries {
             }
             pub struct PostByUserIdsStmt(cornucopia_sync::private::Stmt);
             impl PostByUserIdsStmt {
+                /// Uses `sql`, e.g. composed at runtime, instead of the annotated query.
+                /// It is prepared as is, so it must take the same parameters and return the same columns.
+                pub fn with_sql(sql: impl Into<std::borrow::Cow<'static, str>>) -> Self {
+                    Self(cornucopia_sync::private::Stmt::new(sql))
+                }
                 pub fn bind<'a, C: GenericClient, T1: cornucopia_sync::ArraySql<Item = i32>>(
                     &'a mut self,
                     client: &'a mut C,
@@ -454,6 +867,10 @@ pub mod queries {
                             body: row.get(3),
                         },
                         mapper: |it| <super::Post>::from(it),
+                        info: cornucopia_sync::QueryInfo {
+                            module: "bench",
+                            query: "post_by_user_ids",
+                        },
                     }
                 }
             }
@@ -464,6 +881,11 @@ pub mod queries {
             }
             pub struct CommentsStmt(cornucopia_sync::private::Stmt);
             impl CommentsStmt {
+                /// Uses `sql`, e.g. composed at runtime, instead of the annotated query.
+                /// It is prepared as is, so it must take the same parameters and return the same columns.
+                pub fn with_sql(sql: impl Into<std::borrow::Cow<'static, str>>) -> Self {
+                    Self(cornucopia_sync::private::Stmt::new(sql))
+                }
                 pub fn bind<'a, C: GenericClient>(
                     &'a mut self,
                     client: &'a mut C,
@@ -478,6 +900,10 @@ pub mod queries {
                             text: row.get(2),
                         },
                         mapper: |it| <super::Comment>::from(it),
+                        info: cornucopia_sync::QueryInfo {
+                            module: "bench",
+                            query: "comments",
+                        },
                     }
                 }
             }
@@ -488,6 +914,11 @@ pub mod queries {
             }
             pub struct CommentsByPostIdStmt(cornucopia_sync::private::Stmt);
             impl CommentsByPostIdStmt {
+                /// Uses `sql`, e.g. composed at runtime, instead of the annotated query.
+                /// It is prepared as is, so it must take the same parameters and return the same columns.
+                pub fn with_sql(sql: impl Into<std::borrow::Cow<'static, str>>) -> Self {
+                    Self(cornucopia_sync::private::Stmt::new(sql))
+                }
                 pub fn bind<'a, C: GenericClient, T1: cornucopia_sync::ArraySql<Item = i32>>(
                     &'a mut self,
                     client: &'a mut C,
@@ -503,14 +934,27 @@ pub mod queries {
                             text: row.get(2),
                         },
                         mapper: |it| <super::Comment>::from(it),
+                        info: cornucopia_sync::QueryInfo {
+                            module: "bench",
+                            query: "comments_by_post_id",
+                        },
                     }
                 }
             }
             pub fn select_complex() -> SelectComplexStmt {
-                SelectComplexStmt(cornucopia_sync :: private :: Stmt :: new("SELECT u.id as myuser_id, u.name, u.hair_color, p.id as post_id, p.user_id, p.title, p.body FROM users as u LEFT JOIN posts as p on u.id = p.user_id"))
+                SelectComplexStmt(
+                    cornucopia_sync::private::Stmt::new(
+                        "SELECT u.id as myuser_id, u.name, u.hair_color, p.id as post_id, p.user_id, p.title, p.body FROM users as u LEFT JOIN posts as p on u.id = p.user_id",
+                    ),
+                )
             }
             pub struct SelectComplexStmt(cornucopia_sync::private::Stmt);
             impl SelectComplexStmt {
+                /// Uses `sql`, e.g. composed at runtime, instead of the annotated query.
+                /// It is prepared as is, so it must take the same parameters and return the same columns.
+                pub fn with_sql(sql: impl Into<std::borrow::Cow<'static, str>>) -> Self {
+                    Self(cornucopia_sync::private::Stmt::new(sql))
+                }
                 pub fn bind<'a, C: GenericClient>(
                     &'a mut self,
                     client: &'a mut C,
@@ -529,6 +973,10 @@ pub mod queries {
                             body: row.get(6),
                         },
                         mapper: |it| <super::SelectComplex>::from(it),
+                        info: cornucopia_sync::QueryInfo {
+                            module: "bench",
+                            query: "select_complex",
+                        },
                     }
                 }
             }
@@ -543,6 +991,8 @@ pub mod queries {
                 stmt: &'a mut cornucopia_async::private::Stmt,
                 extractor: fn(&tokio_postgres::Row) -> super::UserBorrowed,
                 mapper: fn(super::UserBorrowed) -> T,
+                info: cornucopia_async::QueryInfo,
+                timeout: Option<(std::time::Duration, tokio_postgres::CancelToken)>,
             }
             impl<'a, C, T: 'a, const N: usize> UserQuery<'a, C, T, N>
             where
@@ -558,23 +1008,65 @@ pub mod queries {
                         stmt: self.stmt,
                         extractor: self.extractor,
                         mapper,
+                        info: self.info,
+                        timeout: self.timeout,
                     }
                 }
                 pub async fn one(self) -> Result<T, tokio_postgres::Error> {
-                    let stmt = self.stmt.prepare(self.client).await?;
-                    let row = self.client.query_one(stmt, &self.params).await?;
-                    Ok((self.mapper)((self.extractor)(&row)))
+                    let info = self.info;
+                    cornucopia_async::private::observe(&info, |_| Some(1), async move {
+                        cornucopia_async::private::timeout(self.timeout.clone(), async move {
+                            let row = self
+                                .stmt
+                                .run(self.client, |client, stmt| async move {
+                                    let stmt = &stmt;
+                                    let params: &[&(dyn postgres_types::ToSql + Sync)] =
+                                        &self.params;
+                                    client.query_one(stmt, params).await
+                                })
+                                .await?;
+                            Ok((self.mapper)((self.extractor)(&row)))
+                        })
+                        .await
+                    })
+                    .await
                 }
                 pub async fn all(self) -> Result<Vec<T>, tokio_postgres::Error> {
-                    self.iter().await?.try_collect().await
+                    let info = self.info;
+                    cornucopia_async::private::observe(
+                        &info,
+                        |it: &Vec<T>| Some(it.len() as u64),
+                        async move {
+                            cornucopia_async::private::timeout(self.timeout.clone(), async move {
+                                self.raw_iter().await?.try_collect().await
+                            })
+                            .await
+                        },
+                    )
+                    .await
                 }
                 pub async fn opt(self) -> Result<Option<T>, tokio_postgres::Error> {
-                    let stmt = self.stmt.prepare(self.client).await?;
-                    Ok(self
-                        .client
-                        .query_opt(stmt, &self.params)
-                        .await?
-                        .map(|row| (self.mapper)((self.extractor)(&row))))
+                    let info = self.info;
+                    cornucopia_async::private::observe(
+                        &info,
+                        |it: &Option<T>| Some(it.is_some().into()),
+                        async move {
+                            cornucopia_async::private::timeout(self.timeout.clone(), async move {
+                                Ok(self
+                                    .stmt
+                                    .run(self.client, |client, stmt| async move {
+                                        let stmt = &stmt;
+                                        let params: &[&(dyn postgres_types::ToSql + Sync)] =
+                                            &self.params;
+                                        client.query_opt(stmt, params).await
+                                    })
+                                    .await?
+                                    .map(|row| (self.mapper)((self.extractor)(&row))))
+                            })
+                            .await
+                        },
+                    )
+                    .await
                 }
                 pub async fn iter(
                     self,
@@ -582,22 +1074,122 @@ pub mod queries {
                     impl futures::Stream<Item = Result<T, tokio_postgres::Error>> + 'a,
                     tokio_postgres::Error,
                 > {
-                    let stmt = self.stmt.prepare(self.client).await?;
+                    let info = self.info;
+                    cornucopia_async::private::observe(&info, |_| None, async move {
+                        cornucopia_async::private::timeout(self.timeout.clone(), async move {
+                            self.raw_iter().await
+                        })
+                        .await
+                    })
+                    .await
+                }
+                /// Passes each row to `visitor` as it is read, without building owned values,
+                /// returning the number of rows.
+                pub async fn for_each(
+                    self,
+                    mut visitor: impl FnMut(super::UserBorrowed),
+                ) -> Result<u64, tokio_postgres::Error> {
+                    let info = self.info;
+                    cornucopia_async::private::observe(&info, |it: &u64| Some(*it), async move {
+                        cornucopia_async::private::timeout(self.timeout.clone(), async move {
+                            let rows = self
+                                .stmt
+                                .run(self.client, |client, stmt| async move {
+                                    let stmt = &stmt;
+                                    let params: &[&(dyn postgres_types::ToSql + Sync)] =
+                                        &self.params;
+                                    client
+                                        .query_raw(
+                                            stmt,
+                                            cornucopia_async::private::slice_iter(params),
+                                        )
+                                        .await
+                                })
+                                .await?;
+                            let mut count = 0;
+                            let mut rows = std::pin::pin!(rows);
+                            while let Some(row) = rows.try_next().await? {
+                                visitor((self.extractor)(&row));
+                                count += 1;
+                            }
+                            Ok(count)
+                        })
+                        .await
+                    })
+                    .await
+                }
+                async fn raw_iter(
+                    self,
+                ) -> Result<
+                    impl futures::Stream<Item = Result<T, tokio_postgres::Error>> + 'a,
+                    tokio_postgres::Error,
+                > {
                     let it = self
-                        .client
-                        .query_raw(stmt, cornucopia_async::private::slice_iter(&self.params))
+                        .stmt
+                        .run(self.client, |client, stmt| async move {
+                            let stmt = &stmt;
+                            let params: &[&(dyn postgres_types::ToSql + Sync)] = &self.params;
+                            client
+                                .query_raw(stmt, cornucopia_async::private::slice_iter(params))
+                                .await
+                        })
                         .await?
                         .map(move |res| res.map(|row| (self.mapper)((self.extractor)(&row))))
                         .into_stream();
                     Ok(it)
                 }
             }
+            impl<'a, C, T: 'a, const N: usize> UserQuery<'a, C, T, N>
+            where
+                C: cornucopia_async::CancelClient,
+            {
+                /// Cancels the query on the server if it runs longer than `timeout`, failing with
+                /// the cancellation error. `iter` and `cursor` only bound starting the query, not
+                /// reading its rows.
+                pub fn timeout(mut self, timeout: std::time::Duration) -> Self {
+                    self.timeout = Some((timeout, self.client.cancel_token()));
+                    self
+                }
+            }
+            impl<'a, C, T: 'a, const N: usize> UserQuery<'a, C, T, N>
+            where
+                C: cornucopia_async::CursorClient,
+            {
+                /// Fetch rows through a portal, `batch_size` rows at a time.
+                pub async fn cursor(
+                    self,
+                    batch_size: i32,
+                ) -> Result<
+                    impl futures::Stream<Item = Result<T, tokio_postgres::Error>> + 'a,
+                    tokio_postgres::Error,
+                > {
+                    let info = self.info;
+                    cornucopia_async::private::observe(&info, |_| None, async move {
+                        cornucopia_async::private::timeout(self.timeout.clone(), async move {
+                            let stmt = self.stmt.prepare(self.client).await?;
+                            let it = cornucopia_async::private::cursor(
+                                self.client,
+                                stmt,
+                                &self.params,
+                                batch_size,
+                            )
+                            .await?
+                            .map(move |res| res.map(|row| (self.mapper)((self.extractor)(&row))));
+                            Ok(it)
+                        })
+                        .await
+                    })
+                    .await
+                }
+            }
             pub struct PostQuery<'a, C: GenericClient, T, const N: usize> {
                 client: &'a C,
                 params: [&'a (dyn postgres_types::ToSql + Sync); N],
                 stmt: &'a mut cornucopia_async::private::Stmt,
                 extractor: fn(&tokio_postgres::Row) -> super::PostBorrowed,
                 mapper: fn(super::PostBorrowed) -> T,
+                info: cornucopia_async::QueryInfo,
+                timeout: Option<(std::time::Duration, tokio_postgres::CancelToken)>,
             }
             impl<'a, C, T: 'a, const N: usize> PostQuery<'a, C, T, N>
             where
@@ -613,23 +1205,65 @@ pub mod queries {
                         stmt: self.stmt,
                         extractor: self.extractor,
                         mapper,
+                        info: self.info,
+                        timeout: self.timeout,
                     }
                 }
                 pub async fn one(self) -> Result<T, tokio_postgres::Error> {
-                    let stmt = self.stmt.prepare(self.client).await?;
-                    let row = self.client.query_one(stmt, &self.params).await?;
-                    Ok((self.mapper)((self.extractor)(&row)))
+                    let info = self.info;
+                    cornucopia_async::private::observe(&info, |_| Some(1), async move {
+                        cornucopia_async::private::timeout(self.timeout.clone(), async move {
+                            let row = self
+                                .stmt
+                                .run(self.client, |client, stmt| async move {
+                                    let stmt = &stmt;
+                                    let params: &[&(dyn postgres_types::ToSql + Sync)] =
+                                        &self.params;
+                                    client.query_one(stmt, params).await
+                                })
+                                .await?;
+                            Ok((self.mapper)((self.extractor)(&row)))
+                        })
+                        .await
+                    })
+                    .await
                 }
                 pub async fn all(self) -> Result<Vec<T>, tokio_postgres::Error> {
-                    self.iter().await?.try_collect().await
+                    let info = self.info;
+                    cornucopia_async::private::observe(
+                        &info,
+                        |it: &Vec<T>| Some(it.len() as u64),
+                        async move {
+                            cornucopia_async::private::timeout(self.timeout.clone(), async move {
+                                self.raw_iter().await?.try_collect().await
+                            })
+                            .await
+                        },
+                    )
+                    .await
                 }
                 pub async fn opt(self) -> Result<Option<T>, tokio_postgres::Error> {
-                    let stmt = self.stmt.prepare(self.client).await?;
-                    Ok(self
-                        .client
-                        .query_opt(stmt, &self.params)
-                        .await?
-                        .map(|row| (self.mapper)((self.extractor)(&row))))
+                    let info = self.info;
+                    cornucopia_async::private::observe(
+                        &info,
+                        |it: &Option<T>| Some(it.is_some().into()),
+                        async move {
+                            cornucopia_async::private::timeout(self.timeout.clone(), async move {
+                                Ok(self
+                                    .stmt
+                                    .run(self.client, |client, stmt| async move {
+                                        let stmt = &stmt;
+                                        let params: &[&(dyn postgres_types::ToSql + Sync)] =
+                                            &self.params;
+                                        client.query_opt(stmt, params).await
+                                    })
+                                    .await?
+                                    .map(|row| (self.mapper)((self.extractor)(&row))))
+                            })
+                            .await
+                        },
+                    )
+                    .await
                 }
                 pub async fn iter(
                     self,
@@ -637,22 +1271,122 @@ pub mod queries {
                     impl futures::Stream<Item = Result<T, tokio_postgres::Error>> + 'a,
                     tokio_postgres::Error,
                 > {
-                    let stmt = self.stmt.prepare(self.client).await?;
+                    let info = self.info;
+                    cornucopia_async::private::observe(&info, |_| None, async move {
+                        cornucopia_async::private::timeout(self.timeout.clone(), async move {
+                            self.raw_iter().await
+                        })
+                        .await
+                    })
+                    .await
+                }
+                /// Passes each row to `visitor` as it is read, without building owned values,
+                /// returning the number of rows.
+                pub async fn for_each(
+                    self,
+                    mut visitor: impl FnMut(super::PostBorrowed),
+                ) -> Result<u64, tokio_postgres::Error> {
+                    let info = self.info;
+                    cornucopia_async::private::observe(&info, |it: &u64| Some(*it), async move {
+                        cornucopia_async::private::timeout(self.timeout.clone(), async move {
+                            let rows = self
+                                .stmt
+                                .run(self.client, |client, stmt| async move {
+                                    let stmt = &stmt;
+                                    let params: &[&(dyn postgres_types::ToSql + Sync)] =
+                                        &self.params;
+                                    client
+                                        .query_raw(
+                                            stmt,
+                                            cornucopia_async::private::slice_iter(params),
+                                        )
+                                        .await
+                                })
+                                .await?;
+                            let mut count = 0;
+                            let mut rows = std::pin::pin!(rows);
+                            while let Some(row) = rows.try_next().await? {
+                                visitor((self.extractor)(&row));
+                                count += 1;
+                            }
+                            Ok(count)
+                        })
+                        .await
+                    })
+                    .await
+                }
+                async fn raw_iter(
+                    self,
+                ) -> Result<
+                    impl futures::Stream<Item = Result<T, tokio_postgres::Error>> + 'a,
+                    tokio_postgres::Error,
+                > {
                     let it = self
-                        .client
-                        .query_raw(stmt, cornucopia_async::private::slice_iter(&self.params))
+                        .stmt
+                        .run(self.client, |client, stmt| async move {
+                            let stmt = &stmt;
+                            let params: &[&(dyn postgres_types::ToSql + Sync)] = &self.params;
+                            client
+                                .query_raw(stmt, cornucopia_async::private::slice_iter(params))
+                                .await
+                        })
                         .await?
                         .map(move |res| res.map(|row| (self.mapper)((self.extractor)(&row))))
                         .into_stream();
                     Ok(it)
                 }
             }
+            impl<'a, C, T: 'a, const N: usize> PostQuery<'a, C, T, N>
+            where
+                C: cornucopia_async::CancelClient,
+            {
+                /// Cancels the query on the server if it runs longer than `timeout`, failing with
+                /// the cancellation error. `iter` and `cursor` only bound starting the query, not
+                /// reading its rows.
+                pub fn timeout(mut self, timeout: std::time::Duration) -> Self {
+                    self.timeout = Some((timeout, self.client.cancel_token()));
+                    self
+                }
+            }
+            impl<'a, C, T: 'a, const N: usize> PostQuery<'a, C, T, N>
+            where
+                C: cornucopia_async::CursorClient,
+            {
+                /// Fetch rows through a portal, `batch_size` rows at a time.
+                pub async fn cursor(
+                    self,
+                    batch_size: i32,
+                ) -> Result<
+                    impl futures::Stream<Item = Result<T, tokio_postgres::Error>> + 'a,
+                    tokio_postgres::Error,
+                > {
+                    let info = self.info;
+                    cornucopia_async::private::observe(&info, |_| None, async move {
+                        cornucopia_async::private::timeout(self.timeout.clone(), async move {
+                            let stmt = self.stmt.prepare(self.client).await?;
+                            let it = cornucopia_async::private::cursor(
+                                self.client,
+                                stmt,
+                                &self.params,
+                                batch_size,
+                            )
+                            .await?
+                            .map(move |res| res.map(|row| (self.mapper)((self.extractor)(&row))));
+                            Ok(it)
+                        })
+                        .await
+                    })
+                    .await
+                }
+            }
             pub struct CommentQuery<'a, C: GenericClient, T, const N: usize> {
                 client: &'a C,
                 params: [&'a (dyn postgres_types::ToSql + Sync); N],
                 stmt: &'a mut cornucopia_async::private::Stmt,
                 extractor: fn(&tokio_postgres::Row) -> super::CommentBorrowed,
                 mapper: fn(super::CommentBorrowed) -> T,
+                info: cornucopia_async::QueryInfo,
+                timeout: Option<(std::time::Duration, tokio_postgres::CancelToken)>,
             }
             impl<'a, C, T: 'a, const N: usize> CommentQuery<'a, C, T, N>
             where
@@ -668,23 +1402,65 @@ pub mod queries {
                         stmt: self.stmt,
                         extractor: self.extractor,
                         mapper,
+                        info: self.info,
+                        timeout: self.timeout,
                     }
                 }
                 pub async fn one(self) -> Result<T, tokio_postgres::Error> {
-                    let stmt = self.stmt.prepare(self.client).await?;
-                    let row = self.client.query_one(stmt, &self.params).await?;
-                    Ok((self.mapper)((self.extractor)(&row)))
+                    let info = self.info;
+                    cornucopia_async::private::observe(&info, |_| Some(1), async move {
+                        cornucopia_async::private::timeout(self.timeout.clone(), async move {
+                            let row = self
+                                .stmt
+                                .run(self.client, |client, stmt| async move {
+                                    let stmt = &stmt;
+                                    let params: &[&(dyn postgres_types::ToSql + Sync)] =
+                                        &self.params;
+                                    client.query_one(stmt, params).await
+                                })
+                                .await?;
+                            Ok((self.mapper)((self.extractor)(&row)))
+                        })
+                        .await
+                    })
+                    .await
                 }
                 pub async fn all(self) -> Result<Vec<T>, tokio_postgres::Error> {
-                    self.iter().await?.try_collect().await
+                    let info = self.info;
+                    cornucopia_async::private::observe(
+                        &info,
+                        |it: &Vec<T>| Some(it.len() as u64),
+                        async move {
+                            cornucopia_async::private::timeout(self.timeout.clone(), async move {
+                                self.raw_iter().await?.try_collect().await
+                            })
+                            .await
+                        },
+                    )
+                    .await
                 }
                 pub async fn opt(self) -> Result<Option<T>, tokio_postgres::Error> {
-                    let stmt = self.stmt.prepare(self.client).await?;
-                    Ok(self
-                        .client
-                        .query_opt(stmt, &self.params)
-                        .await?
-                        .map(|row| (self.mapper)((self.extractor)(&row))))
+                    let info = self.info;
+                    cornucopia_async::private::observe(
+                        &info,
+                        |it: &Option<T>| Some(it.is_some().into()),
+                        async move {
+                            cornucopia_async::private::timeout(self.timeout.clone(), async move {
+                                Ok(self
+                                    .stmt
+                                    .run(self.client, |client, stmt| async move {
+                                        let stmt = &stmt;
+                                        let params: &[&(dyn postgres_types::ToSql + Sync)] =
+                                            &self.params;
+                                        client.query_opt(stmt, params).await
+                                    })
+                                    .await?
+                                    .map(|row| (self.mapper)((self.extractor)(&row))))
+                            })
+                            .await
+                        },
+                    )
+                    .await
                 }
                 pub async fn iter(
                     self,
@@ -692,22 +1468,122 @@ pub mod queries {
                     impl futures::Stream<Item = Result<T, tokio_postgres::Error>> + 'a,
                     tokio_postgres::Error,
                 > {
-                    let stmt = self.stmt.prepare(self.client).await?;
+                    let info = self.info;
+                    cornucopia_async::private::observe(&info, |_| None, async move {
+                        cornucopia_async::private::timeout(self.timeout.clone(), async move {
+                            self.raw_iter().await
+                        })
+                        .await
+                    })
+                    .await
+                }
+                /// Passes each row to `visitor` as it is read, without building owned values,
+                /// returning the number of rows.
+                pub async fn for_each(
+                    self,
+                    mut visitor: impl FnMut(super::CommentBorrowed),
+                ) -> Result<u64, tokio_postgres::Error> {
+                    let info = self.info;
+                    cornucopia_async::private::observe(&info, |it: &u64| Some(*it), async move {
+                        cornucopia_async::private::timeout(self.timeout.clone(), async move {
+                            let rows = self
+                                .stmt
+                                .run(self.client, |client, stmt| async move {
+                                    let stmt = &stmt;
+                                    let params: &[&(dyn postgres_types::ToSql + Sync)] =
+                                        &self.params;
+                                    client
+                                        .query_raw(
+                                            stmt,
+                                            cornucopia_async::private::slice_iter(params),
+                                        )
+                                        .await
+                                })
+                                .await?;
+                            let mut count = 0;
+                            let mut rows = std::pin::pin!(rows);
+                            while let Some(row) = rows.try_next().await? {
+                                visitor((self.extractor)(&row));
+                                count += 1;
+                            }
+                            Ok(count)
+                        })
+                        .await
+                    })
+                    .await
+                }
+                async fn raw_iter(
+                    self,
+                ) -> Result<
+                    impl futures::Stream<Item = Result<T, tokio_postgres::Error>> + 'a,
+                    tokio_postgres::Error,
+                > {
                     let it = self
-                        .client
-                        .query_raw(stmt, cornucopia_async::private::slice_iter(&self.params))
+                        .stmt
+                        .run(self.client, |client, stmt| async move {
+                            let stmt = &stmt;
+                            let params: &[&(dyn postgres_types::ToSql + Sync)] = &self.params;
+                            client
+                                .query_raw(stmt, cornucopia_async::private::slice_iter(params))
+                                .await
+                        })
                         .await?
                         .map(move |res| res.map(|row| (self.mapper)((self.extractor)(&row))))
                         .into_stream();
                     Ok(it)
                 }
             }
+            impl<'a, C, T: 'a, const N: usize> CommentQuery<'a, C, T, N>
+            where
+                C: cornucopia_async::CancelClient,
+            {
+                /// Cancels the query on the server if it runs longer than `timeout`, failing with
+                /// the cancellation error. `iter` and `cursor` only bound starting the query, not
+                /// reading its rows.
+                pub fn timeout(mut self, timeout: std::time::Duration) -> Self {
+                    self.timeout = Some((timeout, self.client.cancel_token()));
+                    self
+                }
+            }
+            impl<'a, C, T: 'a, const N: usize> CommentQuery<'a, C, T, N>
+            where
+                C: cornucopia_async::CursorClient,
+            {
+                /// Fetch rows through a portal, `batch_size` rows at a time.
+                pub async fn cursor(
+                    self,
+                    batch_size: i32,
+                ) -> Result<
+                    impl futures::Stream<Item = Result<T, tokio_postgres::Error>> + 'a,
+                    tokio_postgres::Error,
+                > {
+                    let info = self.info;
+                    cornucopia_async::private::observe(&info, |_| None, async move {
+                        cornucopia_async::private::timeout(self.timeout.clone(), async move {
+                            let stmt = self.stmt.prepare(self.client).await?;
+                            let it = cornucopia_async::private::cursor(
+                                self.client,
+                                stmt,
+                                &self.params,
+                                batch_size,
+                            )
+                            .await?
+                            .map(move |res| res.map(|row| (self.mapper)((self.extractor)(&row))));
+                            Ok(it)
+                        })
+                        .await
+                    })
+                    .await
+                }
+            }
             pub struct SelectComplexQuery<'a, C: GenericClient, T, const N: usize> {
                 client: &'a C,
                 params: [&'a (dyn postgres_types::ToSql + Sync); N],
                 stmt: &'a mut cornucopia_async::private::Stmt,
                 extractor: fn(&tokio_postgres::Row) -> super::SelectComplexBorrowed,
                 mapper: fn(super::SelectComplexBorrowed) -> T,
+                info: cornucopia_async::QueryInfo,
+                timeout: Option<(std::time::Duration, tokio_postgres::CancelToken)>,
             }
             impl<'a, C, T: 'a, const N: usize> SelectComplexQuery<'a, C, T, N>
             where
@@ -723,23 +1599,65 @@ pub mod queries {
                         stmt: self.stmt,
                         extractor: self.extractor,
                         mapper,
+                        info: self.info,
+                        timeout: self.timeout,
                     }
                 }
                 pub async fn one(self) -> Result<T, tokio_postgres::Error> {
-                    let stmt = self.stmt.prepare(self.client).await?;
-                    let row = self.client.query_one(stmt, &self.params).await?;
-                    Ok((self.mapper)((self.extractor)(&row)))
+                    let info = self.info;
+                    cornucopia_async::private::observe(&info, |_| Some(1), async move {
+                        cornucopia_async::private::timeout(self.timeout.clone(), async move {
+                            let row = self
+                                .stmt
+                                .run(self.client, |client, stmt| async move {
+                                    let stmt = &stmt;
+                                    let params: &[&(dyn postgres_types::ToSql + Sync)] =
+                                        &self.params;
+                                    client.query_one(stmt, params).await
+                                })
+                                .await?;
+                            Ok((self.mapper)((self.extractor)(&row)))
+                        })
+                        .await
+                    })
+                    .await
                 }
                 pub async fn all(self) -> Result<Vec<T>, tokio_postgres::Error> {
-                    self.iter().await?.try_collect().await
+                    let info = self.info;
+                    cornucopia_async::private::observe(
+                        &info,
+                        |it: &Vec<T>| Some(it.len() as u64),
+                        async move {
+                            cornucopia_async::private::timeout(self.timeout.clone(), async move {
+                                self.raw_iter().await?.try_collect().await
+                            })
+                            .await
+                        },
+                    )
+                    .await
                 }
                 pub async fn opt(self) -> Result<Option<T>, tokio_postgres::Error> {
-                    let stmt = self.stmt.prepare(self.client).await?;
-                    Ok(self
-                        .client
-                        .query_opt(stmt, &self.params)
-                        .await?
-                        .map(|row| (self.mapper)((self.extractor)(&row))))
+                    let info = self.info;
+                    cornucopia_async::private::observe(
+                        &info,
+                        |it: &Option<T>| Some(it.is_some().into()),
+                        async move {
+                            cornucopia_async::private::timeout(self.timeout.clone(), async move {
+                                Ok(self
+                                    .stmt
+                                    .run(self.client, |client, stmt| async move {
+                                        let stmt = &stmt;
+                                        let params: &[&(dyn postgres_types::ToSql + Sync)] =
+                                            &self.params;
+                                        client.query_opt(stmt, params).await
+                                    })
+                                    .await?
+                                    .map(|row| (self.mapper)((self.extractor)(&row))))
+                            })
+                            .await
+                        },
+                    )
+                    .await
                 }
                 pub async fn iter(
                     self,
@@ -747,21 +1665,124 @@ pub mod queries {
                     impl futures::Stream<Item = Result<T, tokio_postgres::Error>> + 'a,
                     tokio_postgres::Error,
                 > {
-                    let stmt = self.stmt.prepare(self.client).await?;
+                    let info = self.info;
+                    cornucopia_async::private::observe(&info, |_| None, async move {
+                        cornucopia_async::private::timeout(self.timeout.clone(), async move {
+                            self.raw_iter().await
+                        })
+                        .await
+                    })
+                    .await
+                }
+                /// Passes each row to `visitor` as it is read, without building owned values,
+                /// returning the number of rows.
+                pub async fn for_each(
+                    self,
+                    mut visitor: impl FnMut(super::SelectComplexBorrowed),
+                ) -> Result<u64, tokio_postgres::Error> {
+                    let info = self.info;
+                    cornucopia_async::private::observe(&info, |it: &u64| Some(*it), async move {
+                        cornucopia_async::private::timeout(self.timeout.clone(), async move {
+                            let rows = self
+                                .stmt
+                                .run(self.client, |client, stmt| async move {
+                                    let stmt = &stmt;
+                                    let params: &[&(dyn postgres_types::ToSql + Sync)] =
+                                        &self.params;
+                                    client
+                                        .query_raw(
+                                            stmt,
+                                            cornucopia_async::private::slice_iter(params),
+                                        )
+                                        .await
+                                })
+                                .await?;
+                            let mut count = 0;
+                            let mut rows = std::pin::pin!(rows);
+                            while let Some(row) = rows.try_next().await? {
+                                visitor((self.extractor)(&row));
+                                count += 1;
+                            }
+                            Ok(count)
+                        })
+                        .await
+                    })
+                    .await
+                }
+                async fn raw_iter(
+                    self,
+                ) -> Result<
+                    impl futures::Stream<Item = Result<T, tokio_postgres::Error>> + 'a,
+                    tokio_postgres::Error,
+                > {
                     let it = self
-                        .client
-                        .query_raw(stmt, cornucopia_async::private::slice_iter(&self.params))
+                        .stmt
+                        .run(self.client, |client, stmt| async move {
+                            let stmt = &stmt;
+                            let params: &[&(dyn postgres_types::ToSql + Sync)] = &self.params;
+                            client
+                                .query_raw(stmt, cornucopia_async::private::slice_iter(params))
+                                .await
+                        })
                         .await?
                         .map(move |res| res.map(|row| (self.mapper)((self.extractor)(&row))))
                         .into_stream();
                     Ok(it)
                 }
             }
+            impl<'a, C, T: 'a, const N: usize> SelectComplexQuery<'a, C, T, N>
+            where
+                C: cornucopia_async::CancelClient,
+            {
+                /// Cancels the query on the server if it runs longer than `timeout`, failing with
+                /// the cancellation error. `iter` and `cursor` only bound starting the query, not
+                /// reading its rows.
+                pub fn timeout(mut self, timeout: std::time::Duration) -> Self {
+                    self.timeout = Some((timeout, self.client.cancel_token()));
+                    self
+                }
+            }
+            impl<'a, C, T: 'a, const N: usize> SelectComplexQuery<'a, C, T, N>
+            where
+                C: cornucopia_async::CursorClient,
+            {
+                /// Fetch rows through a portal, `batch_size` rows at a time.
+                pub async fn cursor(
+                    self,
+                    batch_size: i32,
+                ) -> Result<
+                    impl futures::Stream<Item = Result<T, tokio_postgres::Error>> + 'a,
+                    tokio_postgres::Error,
+                > {
+                    let info = self.info;
+                    cornucopia_async::private::observe(&info, |_| None, async move {
+                        cornucopia_async::private::timeout(self.timeout.clone(), async move {
+                            let stmt = self.stmt.prepare(self.client).await?;
+                            let it = cornucopia_async::private::cursor(
+                                self.client,
+                                stmt,
+                                &self.params,
+                                batch_size,
+                            )
+                            .await?
+                            .map(move |res| res.map(|row| (self.mapper)((self.extractor)(&row))));
+                            Ok(it)
+                        })
+                        .await
+                    })
+                    .await
+                }
+            }
             pub fn users() -> UsersStmt {
                 UsersStmt(cornucopia_async::private::Stmt::new("SELECT * FROM users"))
             }
             pub struct UsersStmt(cornucopia_async::private::Stmt);
             impl UsersStmt {
+                /// Uses `sql`, e.g. composed at runtime, instead of the annotated query.
+                /// It is prepared as is, so it must take the same parameters and return the same columns.
+                pub fn with_sql(sql: impl Into<std::borrow::Cow<'static, str>>) -> Self {
+                    Self(cornucopia_async::private::Stmt::new(sql))
+                }
                 pub fn bind<'a, C: GenericClient>(
                     &'a mut self,
                     client: &'a C,
@@ -776,6 +1797,11 @@ pub mod queries {
                             hair_color: row.get(2),
                         },
                         mapper: |it| <super::User>::from(it),
+                        info: cornucopia_async::QueryInfo {
+                            module: "bench",
+                            query: "users",
+                        },
+                        timeout: None,
                     }
                 }
             }
@@ -786,6 +1812,11 @@ pub mod queries {
             }
             pub struct InsertUserStmt(cornucopia_async::private::Stmt);
             impl InsertUserStmt {
+                /// Uses `sql`, e.g. composed at runtime, instead of the annotated query.
+                /// It is prepared as is, so it must take the same parameters and return the same columns.
+                pub fn with_sql(sql: impl Into<std::borrow::Cow<'static, str>>) -> Self {
+                    Self(cornucopia_async::private::Stmt::new(sql))
+                }
                 pub async fn bind<
                     'a,
                     C: GenericClient,
@@ -797,8 +1828,24 @@ pub mod queries {
                     name: &'a T1,
                     hair_color: &'a Option<T2>,
                 ) -> Result<u64, tokio_postgres::Error> {
-                    let stmt = self.0.prepare(client).await?;
-                    client.execute(stmt, &[name, hair_color]).await
+                    cornucopia_async::private::observe(
+                        &cornucopia_async::QueryInfo {
+                            module: "bench",
+                            query: "insert_user",
+                        },
+                        |rows| Some(*rows),
+                        async move {
+                            self.0
+                                .run(client, |client, stmt| async move {
+                                    let stmt = &stmt;
+                                    let params: &[&(dyn postgres_types::ToSql + Sync)] =
+                                        &[name, hair_color];
+                                    client.execute(stmt, params).await
+                                })
+                                .await
+                        },
+                    )
+                    .await
                 }
             }
             impl<
@@ -839,6 +1886,11 @@ pub mod queries {
             }
             pub struct PostsStmt(cornucopia_async::private::Stmt);
             impl PostsStmt {
+                /// Uses `sql`, e.g. composed at runtime, instead of the annotated query.
+                /// It is prepared as is, so it must take the same parameters and return the same columns.
+                pub fn with_sql(sql: impl Into<std::borrow::Cow<'static, str>>) -> Self {
+                    Self(cornucopia_async::private::Stmt::new(sql))
+                }
                 pub fn bind<'a, C: GenericClient>(
                     &'a mut self,
                     client: &'a C,
@@ -854,6 +1906,11 @@ pub mod queries {
                             body: row.get(3),
                         },
                         mapper: |it| <super::Post>::from(it),
+                        info: cornucopia_async::QueryInfo {
+                            module: "bench",
+                            query: "posts",
+                        },
+                        timeout: None,
                     }
                 }
             }
@@ -864,6 +1921,11 @@ pub mod queries {
             }
             pub struct PostByUserIdsStmt(cornucopia_async::private::Stmt);
             impl PostByUserIdsStmt {
+                /// Uses `sql`, e.g. composed at runtime, instead of the annotated query.
+                /// It is prepared as is, so it must take the same parameters and return the same columns.
+                pub fn with_sql(sql: impl Into<std::borrow::Cow<'static, str>>) -> Self {
+                    Self(cornucopia_async::private::Stmt::new(sql))
+                }
                 pub fn bind<'a, C: GenericClient, T1: cornucopia_async::ArraySql<Item = i32>>(
                     &'a mut self,
                     client: &'a C,
@@ -880,6 +1942,11 @@ pub mod queries {
                             body: row.get(3),
                         },
                         mapper: |it| <super::Post>::from(it),
+                        info: cornucopia_async::QueryInfo {
+                            module: "bench",
+                            query: "post_by_user_ids",
+                        },
+                        timeout: None,
                     }
                 }
             }
@@ -890,6 +1957,11 @@ pub mod queries {
             }
             pub struct CommentsStmt(cornucopia_async::private::Stmt);
             impl CommentsStmt {
+                /// Uses `sql`, e.g. composed at runtime, instead of the annotated query.
+                /// It is prepared as is, so it must take the same parameters and return the same columns.
+                pub fn with_sql(sql: impl Into<std::borrow::Cow<'static, str>>) -> Self {
+                    Self(cornucopia_async::private::Stmt::new(sql))
+                }
                 pub fn bind<'a, C: GenericClient>(
                     &'a mut self,
                     client: &'a C,
@@ -904,6 +1976,11 @@ pub mod queries {
                             text: row.get(2),
                         },
                         mapper: |it| <super::Comment>::from(it),
+                        info: cornucopia_async::QueryInfo {
+                            module: "bench",
+                            query: "comments",
+                        },
+                        timeout: None,
                     }
                 }
             }
@@ -914,6 +1991,11 @@ pub mod queries {
             }
             pub struct CommentsByPostIdStmt(cornucopia_async::private::Stmt);
             impl CommentsByPostIdStmt {
+                /// Uses `sql`, e.g. composed at runtime, instead of the annotated query.
+                /// It is prepared as is, so it must take the same parameters and return the same columns.
+                pub fn with_sql(sql: impl Into<std::borrow::Cow<'static, str>>) -> Self {
+                    Self(cornucopia_async::private::Stmt::new(sql))
+                }
                 pub fn bind<'a, C: GenericClient, T1: cornucopia_async::ArraySql<Item = i32>>(
                     &'a mut self,
                     client: &'a C,
@@ -929,14 +2011,28 @@ pub mod queries {
                             text: row.get(2),
                         },
                         mapper: |it| <super::Comment>::from(it),
+                        info: cornucopia_async::QueryInfo {
+                            module: "bench",
+                            query: "comments_by_post_id",
+                        },
+                        timeout: None,
                     }
                 }
             }
             pub fn select_complex() -> SelectComplexStmt {
-                SelectComplexStmt(cornucopia_async :: private :: Stmt :: new("SELECT u.id as myuser_id, u.name, u.hair_color, p.id as post_id, p.user_id, p.title, p.body FROM users as u LEFT JOIN posts as p on u.id = p.user_id"))
+                SelectComplexStmt(
+                    cornucopia_async::private::Stmt::new(
+                        "SELECT u.id as myuser_id, u.name, u.hair_color, p.id as post_id, p.user_id, p.title, p.body FROM users as u LEFT JOIN posts as p on u.id = p.user_id",
+                    ),
+                )
             }
             pub struct SelectComplexStmt(cornucopia_async::private::Stmt);
             impl SelectComplexStmt {
+                /// Uses `sql`, e.g. composed at runtime, instead of the annotated query.
+                /// It is prepared as is, so it must take the same parameters and return the same columns.
+                pub fn with_sql(sql: impl Into<std::borrow::Cow<'static, str>>) -> Self {
+                    Self(cornucopia_async::private::Stmt::new(sql))
+                }
                 pub fn bind<'a, C: GenericClient>(
                     &'a mut self,
                     client: &'a C,
@@ -955,9 +2051,25 @@ pub mod queries {
                             body: row.get(6),
                         },
                         mapper: |it| <super::SelectComplex>::from(it),
+                        info: cornucopia_async::QueryInfo {
+                            module: "bench",
+                            query: "select_complex",
+                        },
+                        timeout: None,
                     }
                 }
             }
         }
     }
 }
+/// Flat re-exports of the generated items, to import them all with `use prelude::*`
+#[allow(unused_imports)]
+pub mod prelude {
+    pub use super::queries::bench::Comment;
+    pub use super::queries::bench::InsertUserParams;
+    pub use super::queries::bench::Post;
+    pub use super::queries::bench::SelectComplex;
+    pub use super::queries::bench::User;
+    pub use cornucopia_async::{GenericClient, Params as _};
+    pub use cornucopia_sync::Params as _;
+}
@@ -0,0 +1,84 @@
+use std::{
+    future::Future,
+    io,
+    pin::{pin, Pin},
+    task::{Context, Poll},
+};
+
+use futures_util::{future, task::noop_waker_ref};
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use tokio_postgres::{
+    tls::{ChannelBinding, TlsConnect, TlsStream},
+    CancelToken,
+};
+
+/// Identifies the connection of `token` by its backend key data, the process ID and secret key
+/// which the server gave it when it started. They are read back from the cancel request the
+/// token writes, without sending it anywhere.
+pub(crate) fn backend_key(token: &CancelToken) -> Option<usize> {
+    let mut stream = Capture(Vec::new());
+    {
+        let sent = pin!(token.cancel_query_raw(&mut stream, Clear));
+        // Writing in memory never waits
+        match sent.poll(&mut Context::from_waker(noop_waker_ref())) {
+            Poll::Ready(Ok(())) => {}
+            _ => return None,
+        }
+    }
+    // The cancel request ends with the process ID and the secret key, after an SSL request
+    // when the token would negotiate TLS
+    let key = stream.0.get(stream.0.len().checked_sub(8)?..)?;
+    Some(u64::from_be_bytes(key.try_into().ok()?) as usize)
+}
+
+/// Stream keeping what is written to it, and accepting to negotiate TLS
+struct Capture(Vec<u8>);
+
+impl AsyncRead for Capture {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        _: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        buf.put_slice(b"S");
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl AsyncWrite for Capture {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        _: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        self.get_mut().0.extend_from_slice(buf);
+        Poll::Ready(Ok(buf.len()))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl TlsStream for &mut Capture {
+    fn channel_binding(&self) -> ChannelBinding {
+        ChannelBinding::none()
+    }
+}
+
+/// TLS "handshake" keeping the stream as is, so that the cancel request is written in clear
+struct Clear;
+
+impl<'a> TlsConnect<&'a mut Capture> for Clear {
+    type Stream = &'a mut Capture;
+    type Error = io::Error;
+    type Future = future::Ready<io::Result<Self::Stream>>;
+
+    fn connect(self, stream: &'a mut Capture) -> Self::Future {
+        future::ready(Ok(stream))
+    }
+}
@@ -0,0 +1,78 @@
+use async_trait::async_trait;
+use bb8::{ManageConnection, PooledConnection};
+use tokio_postgres::{
+    types::BorrowToSql, Client as PgClient, Error, RowStream, Statement, ToStatement,
+};
+
+use crate::{backend_key::backend_key, generic_client::GenericClient};
+
+/// Connections checked out of a `bb8` pool of `tokio_postgres` clients, such as
+/// the ones managed by `bb8_postgres::PostgresConnectionManager`.
+#[async_trait]
+impl<M> GenericClient for PooledConnection<'_, M>
+where
+    M: ManageConnection<Connection = PgClient>,
+{
+    async fn prepare(&self, query: &str) -> Result<Statement, Error> {
+        PgClient::prepare(self, query).await
+    }
+
+    /// Checked out connections move around, unlike the backend key data of their connection
+    fn cache_key(&self) -> Option<usize> {
+        backend_key(&PgClient::cancel_token(self))
+    }
+
+    async fn execute<T>(
+        &self,
+        query: &T,
+        params: &[&(dyn tokio_postgres::types::ToSql + Sync)],
+    ) -> Result<u64, Error>
+    where
+        T: ?Sized + tokio_postgres::ToStatement + Sync + Send,
+    {
+        PgClient::execute(self, query, params).await
+    }
+
+    async fn query_one<T>(
+        &self,
+        statement: &T,
+        params: &[&(dyn tokio_postgres::types::ToSql + Sync)],
+    ) -> Result<tokio_postgres::Row, Error>
+    where
+        T: ?Sized + tokio_postgres::ToStatement + Sync + Send,
+    {
+        PgClient::query_one(self, statement, params).await
+    }
+
+    async fn query_opt<T>(
+        &self,
+        statement: &T,
+        params: &[&(dyn tokio_postgres::types::ToSql + Sync)],
+    ) -> Result<Option<tokio_postgres::Row>, Error>
+    where
+        T: ?Sized + tokio_postgres::ToStatement + Sync + Send,
+    {
+        PgClient::query_opt(self, statement, params).await
+    }
+
+    async fn query<T>(
+        &self,
+        query: &T,
+        params: &[&(dyn tokio_postgres::types::ToSql + Sync)],
+    ) -> Result<Vec<tokio_postgres::Row>, Error>
+    where
+        T: ?Sized + tokio_postgres::ToStatement + Sync + Send,
+    {
+        PgClient::query(self, query, params).await
+    }
+
+    async fn query_raw<T, P, I>(&self, statement: &T, params: I) -> Result<RowStream, Error>
+    where
+        T: ?Sized + ToStatement + Sync + Send,
+        P: BorrowToSql,
+        I: IntoIterator<Item = P> + Sync + Send,
+        I::IntoIter: ExactSizeIterator,
+    {
+        PgClient::query_raw(self, statement, params).await
+    }
+}
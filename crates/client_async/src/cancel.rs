@@ -0,0 +1,52 @@
+use tokio_postgres::{CancelToken, Client, Transaction};
+
+use crate::generic_client::GenericClient;
+
+/// Clients able to cancel the query running on their connection, e.g. once it timed out.
+pub trait CancelClient: GenericClient {
+    /// Token cancelling the query running on the connection of this client
+    fn cancel_token(&self) -> CancelToken;
+}
+
+impl CancelClient for Client {
+    fn cancel_token(&self) -> CancelToken {
+        Client::cancel_token(self)
+    }
+}
+
+impl CancelClient for Transaction<'_> {
+    fn cancel_token(&self) -> CancelToken {
+        Transaction::cancel_token(self)
+    }
+}
+
+#[cfg(feature = "deadpool")]
+impl CancelClient for deadpool_postgres::Client {
+    fn cancel_token(&self) -> CancelToken {
+        Client::cancel_token(self)
+    }
+}
+
+#[cfg(feature = "deadpool")]
+impl CancelClient for deadpool_postgres::ClientWrapper {
+    fn cancel_token(&self) -> CancelToken {
+        Client::cancel_token(self)
+    }
+}
+
+#[cfg(feature = "deadpool")]
+impl CancelClient for deadpool_postgres::Transaction<'_> {
+    fn cancel_token(&self) -> CancelToken {
+        Transaction::cancel_token(self)
+    }
+}
+
+#[cfg(feature = "bb8")]
+impl<M> CancelClient for bb8::PooledConnection<'_, M>
+where
+    M: bb8::ManageConnection<Connection = Client>,
+{
+    fn cancel_token(&self) -> CancelToken {
+        Client::cancel_token(self)
+    }
+}
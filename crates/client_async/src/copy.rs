@@ -0,0 +1,133 @@
+use async_trait::async_trait;
+use bytes::Buf;
+use tokio_postgres::{Client, CopyInSink, CopyOutStream, Error, ToStatement, Transaction};
+
+use crate::generic_client::GenericClient;
+
+/// Clients able to copy rows in and out of the database with `COPY`.
+#[async_trait]
+pub trait CopyClient: GenericClient {
+    async fn copy_in<T, U>(&self, statement: &T) -> Result<CopyInSink<U>, Error>
+    where
+        T: ?Sized + ToStatement + Sync + Send,
+        U: Buf + 'static + Send;
+
+    async fn copy_out<T>(&self, statement: &T) -> Result<CopyOutStream, Error>
+    where
+        T: ?Sized + ToStatement + Sync + Send;
+}
+
+#[async_trait]
+impl CopyClient for Client {
+    async fn copy_in<T, U>(&self, statement: &T) -> Result<CopyInSink<U>, Error>
+    where
+        T: ?Sized + ToStatement + Sync + Send,
+        U: Buf + 'static + Send,
+    {
+        Client::copy_in(self, statement).await
+    }
+
+    async fn copy_out<T>(&self, statement: &T) -> Result<CopyOutStream, Error>
+    where
+        T: ?Sized + ToStatement + Sync + Send,
+    {
+        Client::copy_out(self, statement).await
+    }
+}
+
+#[async_trait]
+impl CopyClient for Transaction<'_> {
+    async fn copy_in<T, U>(&self, statement: &T) -> Result<CopyInSink<U>, Error>
+    where
+        T: ?Sized + ToStatement + Sync + Send,
+        U: Buf + 'static + Send,
+    {
+        Transaction::copy_in(self, statement).await
+    }
+
+    async fn copy_out<T>(&self, statement: &T) -> Result<CopyOutStream, Error>
+    where
+        T: ?Sized + ToStatement + Sync + Send,
+    {
+        Transaction::copy_out(self, statement).await
+    }
+}
+
+#[cfg(feature = "deadpool")]
+#[async_trait]
+impl CopyClient for deadpool_postgres::Client {
+    async fn copy_in<T, U>(&self, statement: &T) -> Result<CopyInSink<U>, Error>
+    where
+        T: ?Sized + ToStatement + Sync + Send,
+        U: Buf + 'static + Send,
+    {
+        Client::copy_in(self, statement).await
+    }
+
+    async fn copy_out<T>(&self, statement: &T) -> Result<CopyOutStream, Error>
+    where
+        T: ?Sized + ToStatement + Sync + Send,
+    {
+        Client::copy_out(self, statement).await
+    }
+}
+
+#[cfg(feature = "deadpool")]
+#[async_trait]
+impl CopyClient for deadpool_postgres::ClientWrapper {
+    async fn copy_in<T, U>(&self, statement: &T) -> Result<CopyInSink<U>, Error>
+    where
+        T: ?Sized + ToStatement + Sync + Send,
+        U: Buf + 'static + Send,
+    {
+        Client::copy_in(self, statement).await
+    }
+
+    async fn copy_out<T>(&self, statement: &T) -> Result<CopyOutStream, Error>
+    where
+        T: ?Sized + ToStatement + Sync + Send,
+    {
+        Client::copy_out(self, statement).await
+    }
+}
+
+#[cfg(feature = "deadpool")]
+#[async_trait]
+impl CopyClient for deadpool_postgres::Transaction<'_> {
+    async fn copy_in<T, U>(&self, statement: &T) -> Result<CopyInSink<U>, Error>
+    where
+        T: ?Sized + ToStatement + Sync + Send,
+        U: Buf + 'static + Send,
+    {
+        Transaction::copy_in(self, statement).await
+    }
+
+    async fn copy_out<T>(&self, statement: &T) -> Result<CopyOutStream, Error>
+    where
+        T: ?Sized + ToStatement + Sync + Send,
+    {
+        Transaction::copy_out(self, statement).await
+    }
+}
+
+#[cfg(feature = "bb8")]
+#[async_trait]
+impl<M> CopyClient for bb8::PooledConnection<'_, M>
+where
+    M: bb8::ManageConnection<Connection = Client>,
+{
+    async fn copy_in<T, U>(&self, statement: &T) -> Result<CopyInSink<U>, Error>
+    where
+        T: ?Sized + ToStatement + Sync + Send,
+        U: Buf + 'static + Send,
+    {
+        Client::copy_in(self, statement).await
+    }
+
+    async fn copy_out<T>(&self, statement: &T) -> Result<CopyOutStream, Error>
+    where
+        T: ?Sized + ToStatement + Sync + Send,
+    {
+        Client::copy_out(self, statement).await
+    }
+}
@@ -0,0 +1,70 @@
+use async_trait::async_trait;
+use futures_util::{stream, Stream, TryStreamExt};
+use tokio_postgres::{types::ToSql, Error, Portal, Row, Statement, Transaction};
+
+use crate::generic_client::GenericClient;
+
+/// Clients able to fetch rows in batches through a portal. Portals only live in transactions.
+#[async_trait]
+pub trait CursorClient: GenericClient {
+    async fn bind(
+        &self,
+        statement: &Statement,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<Portal, Error>;
+    async fn query_portal(&self, portal: &Portal, max_rows: i32) -> Result<Vec<Row>, Error>;
+}
+
+#[async_trait]
+impl CursorClient for Transaction<'_> {
+    async fn bind(
+        &self,
+        statement: &Statement,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<Portal, Error> {
+        Transaction::bind(self, statement, params).await
+    }
+
+    async fn query_portal(&self, portal: &Portal, max_rows: i32) -> Result<Vec<Row>, Error> {
+        Transaction::query_portal(self, portal, max_rows).await
+    }
+}
+
+#[cfg(feature = "deadpool")]
+#[async_trait]
+impl CursorClient for deadpool_postgres::Transaction<'_> {
+    async fn bind(
+        &self,
+        statement: &Statement,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<Portal, Error> {
+        Transaction::bind(self, statement, params).await
+    }
+
+    async fn query_portal(&self, portal: &Portal, max_rows: i32) -> Result<Vec<Row>, Error> {
+        Transaction::query_portal(self, portal, max_rows).await
+    }
+}
+
+/// Streams the rows of a statement, fetching `batch_size` rows at a time
+pub async fn cursor<'a, C: CursorClient>(
+    client: &'a C,
+    statement: &Statement,
+    params: &[&(dyn ToSql + Sync)],
+    batch_size: i32,
+) -> Result<impl Stream<Item = Result<Row, Error>> + 'a, Error> {
+    let portal = client.bind(statement, params).await?;
+    let batches = stream::try_unfold((portal, false), move |(portal, done)| async move {
+        if done {
+            return Ok(None);
+        }
+        let rows = client.query_portal(&portal, batch_size).await?;
+        // A short batch means the portal is exhausted
+        let done = rows.len() < batch_size as usize;
+        Ok(Some((
+            stream::iter(rows.into_iter().map(Ok)),
+            (portal, done),
+        )))
+    });
+    Ok(batches.try_flatten())
+}
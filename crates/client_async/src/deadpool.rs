@@ -15,6 +15,77 @@ impl GenericClient for DeadpoolClient {
         ClientWrapper::prepare_cached(self, query).await
     }
 
+    fn cache_key(&self) -> Option<usize> {
+        // Statements are cached per connection by deadpool
+        None
+    }
+
+    async fn execute<T>(
+        &self,
+        query: &T,
+        params: &[&(dyn tokio_postgres::types::ToSql + Sync)],
+    ) -> Result<u64, Error>
+    where
+        T: ?Sized + tokio_postgres::ToStatement + Sync + Send,
+    {
+        PgClient::execute(self, query, params).await
+    }
+
+    async fn query_one<T>(
+        &self,
+        statement: &T,
+        params: &[&(dyn tokio_postgres::types::ToSql + Sync)],
+    ) -> Result<tokio_postgres::Row, Error>
+    where
+        T: ?Sized + tokio_postgres::ToStatement + Sync + Send,
+    {
+        PgClient::query_one(self, statement, params).await
+    }
+
+    async fn query_opt<T>(
+        &self,
+        statement: &T,
+        params: &[&(dyn tokio_postgres::types::ToSql + Sync)],
+    ) -> Result<Option<tokio_postgres::Row>, Error>
+    where
+        T: ?Sized + tokio_postgres::ToStatement + Sync + Send,
+    {
+        PgClient::query_opt(self, statement, params).await
+    }
+
+    async fn query<T>(
+        &self,
+        query: &T,
+        params: &[&(dyn tokio_postgres::types::ToSql + Sync)],
+    ) -> Result<Vec<tokio_postgres::Row>, Error>
+    where
+        T: ?Sized + tokio_postgres::ToStatement + Sync + Send,
+    {
+        PgClient::query(self, query, params).await
+    }
+
+    async fn query_raw<T, P, I>(&self, statement: &T, params: I) -> Result<RowStream, Error>
+    where
+        T: ?Sized + ToStatement + Sync + Send,
+        P: BorrowToSql,
+        I: IntoIterator<Item = P> + Sync + Send,
+        I::IntoIter: ExactSizeIterator,
+    {
+        PgClient::query_raw(self, statement, params).await
+    }
+}
+
+#[async_trait]
+impl GenericClient for ClientWrapper {
+    async fn prepare(&self, query: &str) -> Result<Statement, Error> {
+        ClientWrapper::prepare_cached(self, query).await
+    }
+
+    fn cache_key(&self) -> Option<usize> {
+        // Statements are cached per connection by deadpool
+        None
+    }
+
     async fn execute<T>(
         &self,
         query: &T,
@@ -76,6 +147,11 @@ impl GenericClient for DeadpoolTransaction<'_> {
         DeadpoolTransaction::prepare_cached(self, query).await
     }
 
+    fn cache_key(&self) -> Option<usize> {
+        // Statements are cached per connection by deadpool
+        None
+    }
+
     async fn execute<T>(
         &self,
         query: &T,
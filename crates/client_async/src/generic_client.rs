@@ -3,14 +3,24 @@ use tokio_postgres::{
     types::BorrowToSql, Client, Error, RowStream, Statement, ToStatement, Transaction,
 };
 
+use crate::backend_key::backend_key;
+
 /// Abstraction over multiple types of asynchronous clients.
 /// This allows you to use tokio_postgres clients and transactions interchangeably.
 ///
 /// In addition, when the `deadpool` feature is enabled (default), this trait also
-/// abstracts over deadpool clients and transactions
+/// abstracts over deadpool clients and transactions. When the `bb8` feature is enabled,
+/// it also abstracts over connections checked out of a `bb8` pool (e.g. using `bb8_postgres`).
 #[async_trait]
 pub trait GenericClient: Send + Sync {
     async fn prepare(&self, query: &str) -> Result<Statement, Error>;
+    /// Identifies the connection of this client, so that statements are only reused on the
+    /// connection they were prepared on. Defaults to the client's address, which a later
+    /// connection can reuse: statements its connection doesn't know are then prepared again.
+    /// `None` opts out of caching, e.g. for clients that have their own statement cache.
+    fn cache_key(&self) -> Option<usize> {
+        Some((self as *const Self).cast::<()>() as usize)
+    }
     async fn execute<T>(
         &self,
         query: &T,
@@ -54,6 +64,11 @@ impl GenericClient for Transaction<'_> {
         Transaction::prepare(self, query).await
     }
 
+    /// The connection of the client it borrows, sharing its statements
+    fn cache_key(&self) -> Option<usize> {
+        backend_key(&Transaction::cancel_token(self))
+    }
+
     async fn execute<T>(
         &self,
         query: &T,
@@ -115,6 +130,11 @@ impl GenericClient for Client {
         Client::prepare(self, query).await
     }
 
+    /// The backend key data of its connection, which stays the same wherever the client moves
+    fn cache_key(&self) -> Option<usize> {
+        backend_key(&Client::cancel_token(self))
+    }
+
     async fn execute<T>(
         &self,
         query: &T,
@@ -0,0 +1,106 @@
+use std::io::SeekFrom;
+
+use cornucopia_client_core::{lo_seek_args, LargeObjectMode};
+use futures_util::{pin_mut, stream, Stream, StreamExt};
+use tokio_postgres::{types::Oid, Error};
+
+use crate::generic_client::GenericClient;
+
+/// Large object opened with `lo_open`, read and written in chunks of bytes.
+/// Its descriptor only lives in the transaction it was opened in.
+pub struct LargeObject<'a, C: GenericClient> {
+    client: &'a C,
+    fd: i32,
+}
+
+impl<'a, C: GenericClient> LargeObject<'a, C> {
+    /// Creates an empty large object, returning its oid
+    pub async fn create(client: &C) -> Result<Oid, Error> {
+        client
+            .query_one("SELECT lo_create(0)", &[])
+            .await?
+            .try_get(0)
+    }
+
+    /// Deletes the large object `oid`
+    pub async fn unlink(client: &C, oid: Oid) -> Result<(), Error> {
+        client.execute("SELECT lo_unlink($1)", &[&oid]).await?;
+        Ok(())
+    }
+
+    /// Opens the large object `oid`, in a transaction
+    pub async fn open(client: &'a C, oid: Oid, mode: LargeObjectMode) -> Result<Self, Error> {
+        let fd = client
+            .query_one("SELECT lo_open($1, $2)", &[&oid, &mode.flags()])
+            .await?
+            .try_get(0)?;
+        Ok(Self { client, fd })
+    }
+
+    /// Reads up to `len` bytes from the current position, none at the end of the object
+    pub async fn read(&mut self, len: usize) -> Result<Vec<u8>, Error> {
+        let len = i32::try_from(len).unwrap_or(i32::MAX);
+        self.client
+            .query_one("SELECT loread($1, $2)", &[&self.fd, &len])
+            .await?
+            .try_get(0)
+    }
+
+    /// Writes `data` at the current position
+    pub async fn write(&mut self, data: &[u8]) -> Result<(), Error> {
+        self.client
+            .execute("SELECT lowrite($1, $2)", &[&self.fd, &data])
+            .await?;
+        Ok(())
+    }
+
+    /// Moves the current position, returning the new one
+    pub async fn seek(&mut self, pos: SeekFrom) -> Result<u64, Error> {
+        let (offset, whence) = lo_seek_args(pos);
+        let pos: i64 = self
+            .client
+            .query_one(
+                "SELECT lo_lseek64($1, $2, $3)",
+                &[&self.fd, &offset, &whence],
+            )
+            .await?
+            .try_get(0)?;
+        Ok(pos as u64)
+    }
+
+    /// Truncates or extends the large object to `len` bytes
+    pub async fn truncate(&mut self, len: i64) -> Result<(), Error> {
+        self.client
+            .execute("SELECT lo_truncate64($1, $2)", &[&self.fd, &len])
+            .await?;
+        Ok(())
+    }
+
+    /// Closes the descriptor, which would otherwise be closed at the end of the transaction
+    pub async fn close(self) -> Result<(), Error> {
+        self.client
+            .execute("SELECT lo_close($1)", &[&self.fd])
+            .await?;
+        Ok(())
+    }
+
+    /// Streams the rest of the large object, in chunks of up to `chunk_size` bytes
+    pub fn into_stream(self, chunk_size: usize) -> impl Stream<Item = Result<Vec<u8>, Error>> + 'a {
+        stream::try_unfold(self, move |mut object| async move {
+            let chunk = object.read(chunk_size).await?;
+            Ok((!chunk.is_empty()).then_some((chunk, object)))
+        })
+    }
+
+    /// Writes every chunk of `chunks` at the current position
+    pub async fn write_stream<B: AsRef<[u8]>>(
+        &mut self,
+        chunks: impl Stream<Item = B>,
+    ) -> Result<(), Error> {
+        pin_mut!(chunks);
+        while let Some(chunk) = chunks.next().await {
+            self.write(chunk.as_ref()).await?;
+        }
+        Ok(())
+    }
+}
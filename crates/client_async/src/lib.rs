@@ -1,15 +1,41 @@
 #[doc(hidden)]
 pub mod private;
 
+pub use crate::cancel::CancelClient;
+pub use crate::copy::CopyClient;
+pub use crate::cursor::CursorClient;
 pub use crate::generic_client::GenericClient;
-pub use cornucopia_client_core::{ArrayIterator, ArraySql, BytesSql, IterSql, StringSql};
+pub use crate::large_object::LargeObject;
+pub use crate::script::ScriptClient;
+pub use cornucopia_client_core::{
+    set_auditor, set_observer, ArrayIterator, ArraySql, AuditRecord, Auditor, BitString,
+    BoundingBox, BudgetExceeded, BytesSql, Circle, Interval, IterSql, LargeObjectMode, Lexeme,
+    LexemePosition, Line, LineSegment, MatrixIterator, Money, MultirangeIterator, Page, Path,
+    Point, Polygon, QueryInfo, QueryObserver, QueryOutcome, Range, Record, StringSql, TsQuery,
+    TsVector, ValidationError, Weight,
+};
 
 #[cfg(feature = "with-serde_json-1")]
 pub use cornucopia_client_core::JsonSql;
 
+#[cfg(feature = "with-time-0_3")]
+pub use cornucopia_client_core::ExactInterval;
+#[cfg(feature = "hstore")]
+pub use cornucopia_client_core::{HstoreIterator, HstoreSql};
+
+mod backend_key;
+#[cfg(feature = "bb8")]
+mod bb8;
+mod cancel;
+mod copy;
+mod cursor;
 #[cfg(feature = "deadpool")]
 mod deadpool;
 mod generic_client;
+mod large_object;
+mod script;
+#[cfg(feature = "tracing")]
+mod trace;
 
 /// This trait allows you to bind parameters to a query using a single
 /// struct, rather than passing each bind parameter as a function parameter.
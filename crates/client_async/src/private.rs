@@ -1,32 +1,137 @@
-pub use cornucopia_client_core::{slice_iter, Domain, DomainArray};
+pub use crate::cursor::cursor;
+pub use cornucopia_client_core::{
+    audit, check_budget_future as check_budget, observe_future as observe, slice_iter, Domain,
+    DomainArray, Multirange,
+};
+#[cfg(feature = "tracing")]
+pub use {crate::trace::traced, tracing};
+
+use std::{borrow::Cow, future::Future, sync::Arc, time::Duration};
 
 use crate::generic_client::GenericClient;
-use tokio_postgres::{Error, Statement};
+use tokio_postgres::{error::SqlState, CancelToken, Error, NoTls, Statement};
+
+/// Number of clients a statement stays prepared for
+const CACHE_SIZE: usize = 8;
+
+/// Statements shared by the clones of a generated `Queries` facade
+pub type StmtCache = cornucopia_client_core::StmtCache<Statement>;
 
-/// Cached statement
+/// Statement prepared lazily, and cached per client
 pub struct Stmt {
-    query: &'static str,
-    cached: Option<Statement>,
+    query: Cow<'static, str>,
+    /// Prepared statements keyed by client, most recently used first
+    cached: Vec<(Option<usize>, Statement)>,
+    /// Statements shared with other instances of this statement, e.g. on other threads
+    shared: Option<Arc<StmtCache>>,
 }
 
 impl Stmt {
     #[must_use]
-    pub fn new(query: &'static str) -> Self {
+    pub fn new(query: impl Into<Cow<'static, str>>) -> Self {
         Self {
-            query,
-            cached: None,
+            query: query.into(),
+            cached: Vec::new(),
+            shared: None,
         }
     }
 
+    /// Shares the statements prepared on each connection through `cache`
+    #[must_use]
+    pub fn with_cache(mut self, cache: &Arc<StmtCache>) -> Self {
+        self.shared = Some(cache.clone());
+        self
+    }
+
+    /// Query of the statement
+    #[must_use]
+    pub fn query(&self) -> &str {
+        &self.query
+    }
+
     pub async fn prepare<'a, C: GenericClient>(
         &'a mut self,
         client: &C,
     ) -> Result<&'a Statement, Error> {
-        if self.cached.is_none() {
-            let stmt = client.prepare(self.query).await?;
-            self.cached = Some(stmt);
+        let key = client.cache_key();
+        match self
+            .cached
+            .iter()
+            .position(|(k, _)| key.is_some() && *k == key)
+        {
+            Some(idx) => self.cached[..=idx].rotate_right(1),
+            None => {
+                let shared = self.shared.as_ref().zip(key);
+                let stmt = match shared.and_then(|(cache, key)| cache.get(&self.query, key)) {
+                    Some(stmt) => stmt,
+                    None => {
+                        let stmt = client.prepare(&self.query).await?;
+                        if let Some((cache, key)) = shared {
+                            cache.insert(self.query.clone(), key, stmt.clone());
+                        }
+                        stmt
+                    }
+                };
+                if key.is_none() {
+                    // Never reused, so only keep the last one
+                    self.cached.retain(|(k, _)| k.is_some());
+                }
+                self.cached.insert(0, (key, stmt));
+                self.cached.truncate(CACHE_SIZE);
+            }
+        }
+        Ok(&self.cached[0].1)
+    }
+
+    /// Runs `run` with the statement prepared on `client`. A later connection can reuse the key
+    /// a statement was cached for, e.g. the address of a client, so when the connection doesn't
+    /// know the statement, it is prepared again and `run` retried once.
+    pub async fn run<'c, C: GenericClient, T, F: Future<Output = Result<T, Error>>>(
+        &mut self,
+        client: &'c C,
+        mut run: impl FnMut(&'c C, Statement) -> F,
+    ) -> Result<T, Error> {
+        let stmt = self.prepare(client).await?.clone();
+        match run(client, stmt).await {
+            Err(err) if self.forget_unknown(client, &err) => {
+                let stmt = self.prepare(client).await?.clone();
+                run(client, stmt).await
+            }
+            res => res,
+        }
+    }
+
+    /// Forgets the statement cached for `client` if `err` reports that its connection doesn't
+    /// know it, returning whether it did
+    fn forget_unknown<C: GenericClient>(&mut self, client: &C, err: &Error) -> bool {
+        let key = client.cache_key();
+        if key.is_none() || err.code() != Some(&SqlState::INVALID_SQL_STATEMENT_NAME) {
+            return false;
+        }
+        self.cached.retain(|(k, _)| *k != key);
+        if let (Some(cache), Some(key)) = (&self.shared, key) {
+            cache.remove(&self.query, key);
+        }
+        true
+    }
+}
+
+/// Runs `fut`, cancelling its query with the token of `timeout` if it takes longer than its
+/// duration. It then fails with the cancellation error of the database, or with the error
+/// sending the cancellation, e.g. to servers only accepting TLS connections.
+pub async fn timeout<T>(
+    timeout: Option<(Duration, CancelToken)>,
+    fut: impl Future<Output = Result<T, Error>>,
+) -> Result<T, Error> {
+    let Some((timeout, token)) = timeout else {
+        return fut.await;
+    };
+    let mut fut = std::pin::pin!(fut);
+    match tokio::time::timeout(timeout, &mut fut).await {
+        Ok(res) => res,
+        Err(_) => {
+            token.cancel_query(NoTls).await?;
+            fut.await
         }
-        // the statement is always prepared at this point
-        Ok(unsafe { self.cached.as_ref().unwrap_unchecked() })
     }
 }
@@ -0,0 +1,60 @@
+use async_trait::async_trait;
+use tokio_postgres::{Client, Error, Transaction};
+
+use crate::generic_client::GenericClient;
+
+/// Clients able to run scripts, made of several statements without parameters.
+#[async_trait]
+pub trait ScriptClient: GenericClient {
+    /// Runs the `;` separated statements of `query`, without parameters
+    async fn batch_execute(&self, query: &str) -> Result<(), Error>;
+}
+
+#[async_trait]
+impl ScriptClient for Client {
+    async fn batch_execute(&self, query: &str) -> Result<(), Error> {
+        Client::batch_execute(self, query).await
+    }
+}
+
+#[async_trait]
+impl ScriptClient for Transaction<'_> {
+    async fn batch_execute(&self, query: &str) -> Result<(), Error> {
+        Transaction::batch_execute(self, query).await
+    }
+}
+
+#[cfg(feature = "deadpool")]
+#[async_trait]
+impl ScriptClient for deadpool_postgres::Client {
+    async fn batch_execute(&self, query: &str) -> Result<(), Error> {
+        Client::batch_execute(self, query).await
+    }
+}
+
+#[cfg(feature = "deadpool")]
+#[async_trait]
+impl ScriptClient for deadpool_postgres::ClientWrapper {
+    async fn batch_execute(&self, query: &str) -> Result<(), Error> {
+        Client::batch_execute(self, query).await
+    }
+}
+
+#[cfg(feature = "deadpool")]
+#[async_trait]
+impl ScriptClient for deadpool_postgres::Transaction<'_> {
+    async fn batch_execute(&self, query: &str) -> Result<(), Error> {
+        Transaction::batch_execute(self, query).await
+    }
+}
+
+#[cfg(feature = "bb8")]
+#[async_trait]
+impl<M> ScriptClient for bb8::PooledConnection<'_, M>
+where
+    M: bb8::ManageConnection<Connection = Client>,
+{
+    async fn batch_execute(&self, query: &str) -> Result<(), Error> {
+        Client::batch_execute(self, query).await
+    }
+}
@@ -0,0 +1,18 @@
+use std::{future::Future, time::Instant};
+
+use tracing::{Instrument, Span};
+
+/// Runs `fut` inside `span`, recording its duration and the number of rows given by `rows`
+pub async fn traced<T, E>(
+    span: Span,
+    rows: impl FnOnce(&T) -> Option<u64>,
+    fut: impl Future<Output = Result<T, E>>,
+) -> Result<T, E> {
+    let start = Instant::now();
+    let res = fut.instrument(span.clone()).await;
+    span.record("duration_ms", start.elapsed().as_secs_f64() * 1000.);
+    if let Some(rows) = res.as_ref().ok().and_then(rows) {
+        span.record("rows", rows);
+    }
+    res
+}
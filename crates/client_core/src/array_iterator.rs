@@ -46,8 +46,9 @@ impl<'a, T: FromSql<'a>> FromSql<'a> for ArrayIterator<'a, T> {
         };
 
         let array = array_from_sql(raw)?;
-        if array.dimensions().count()? > 1 {
-            return Err("array contains too many dimensions".into());
+        let dims = array.dimensions().count()?;
+        if dims > 1 {
+            return Err(format!("expected an array of 1 dimension, got {dims}").into());
         }
 
         Ok(ArrayIterator {
@@ -64,3 +65,79 @@ impl<'a, T: FromSql<'a>> FromSql<'a> for ArrayIterator<'a, T> {
         }
     }
 }
+
+/// Iterator over the rows of a two dimensional PostgreSQL array, e.g. an `int[][]`, collected
+/// in a `Vec` each. Empty arrays have no rows, and arrays of other dimensions fail to decode.
+pub struct MatrixIterator<'a, T: FromSql<'a>> {
+    values: ArrayValues<'a>,
+    ty: Type,
+    rows: usize,
+    cols: usize,
+    _type: PhantomData<T>,
+}
+
+impl<'a, T: FromSql<'a>> Debug for MatrixIterator<'a, T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MatrixIterator")
+            .field("values", &"[[T]]")
+            .field("ty", &self.ty)
+            .field("rows", &self.rows)
+            .field("cols", &self.cols)
+            .finish()
+    }
+}
+
+impl<'a, T: FromSql<'a>> Iterator for MatrixIterator<'a, T> {
+    type Item = Vec<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.rows == 0 {
+            return None;
+        }
+        self.rows -= 1;
+        let row = (0..self.cols)
+            .map(|_| {
+                let raw = self.values.next().unwrap().unwrap();
+                T::from_sql_nullable(&self.ty, raw).unwrap()
+            })
+            .collect();
+        Some(row)
+    }
+}
+
+impl<'a, T: FromSql<'a>> FromSql<'a> for MatrixIterator<'a, T> {
+    fn from_sql(
+        ty: &Type,
+        raw: &'a [u8],
+    ) -> Result<MatrixIterator<'a, T>, Box<dyn std::error::Error + Sync + Send>> {
+        let member_type = match *escape_domain(ty).kind() {
+            Kind::Array(ref member) => escape_domain(member),
+            _ => panic!("expected array type got {ty}"),
+        };
+
+        let array = array_from_sql(raw)?;
+        let dims: Vec<_> = array.dimensions().collect()?;
+        let (rows, cols) = match dims[..] {
+            [] => (0, 0),
+            [rows, cols] => (usize::try_from(rows.len)?, usize::try_from(cols.len)?),
+            _ => {
+                return Err(format!("expected an array of 2 dimensions, got {}", dims.len()).into())
+            }
+        };
+
+        Ok(MatrixIterator {
+            ty: member_type.clone(),
+            values: array.values(),
+            rows,
+            cols,
+            _type: PhantomData,
+        })
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        match *ty.kind() {
+            Kind::Array(ref inner) => T::accepts(escape_domain(inner)),
+            _ => false,
+        }
+    }
+}
@@ -0,0 +1,41 @@
+use std::{fmt::Debug, sync::OnceLock};
+
+static AUDITOR: OnceLock<Box<dyn Auditor>> = OnceLock::new();
+
+/// Record of a run of a query annotated with `--# audit`
+#[derive(Debug)]
+pub struct AuditRecord<'a> {
+    /// Name of the query module
+    pub module: &'static str,
+    /// Name of the query
+    pub query: &'static str,
+    /// Value of the parameter named by `--# audit(actor = param)`
+    pub actor: Option<&'a dyn Debug>,
+    /// Number of rows affected by the query
+    pub rows: u64,
+}
+
+/// Receives the records of audited queries, e.g. to centralize audit logging.
+pub trait Auditor: Send + Sync {
+    /// Called after each run of an audited query
+    fn record(&self, record: &AuditRecord<'_>);
+}
+
+impl<F: Fn(&AuditRecord<'_>) + Send + Sync> Auditor for F {
+    fn record(&self, record: &AuditRecord<'_>) {
+        self(record);
+    }
+}
+
+/// Sets the auditor receiving the records of audited queries.
+/// It can only be set once, the auditor is given back if one was already set.
+pub fn set_auditor<A: Auditor + 'static>(auditor: A) -> Result<(), Box<dyn Auditor>> {
+    AUDITOR.set(Box::new(auditor))
+}
+
+/// Sends a record to the auditor, if any
+pub fn audit(record: &AuditRecord<'_>) {
+    if let Some(auditor) = AUDITOR.get() {
+        auditor.record(record);
+    }
+}
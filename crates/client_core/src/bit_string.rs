@@ -0,0 +1,122 @@
+use postgres_types::{private::BytesMut, to_sql_checked, FromSql, IsNull, ToSql, Type};
+use std::{error::Error, fmt::Display};
+
+/// Value of a `bit(n)` or a `bit varying`, its bits packed in bytes as Postgres does: the
+/// first bit is the most significant one of the first byte, and the unused bits of the last
+/// byte are zero.
+#[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct BitString {
+    len: usize,
+    bytes: Vec<u8>,
+}
+
+impl BitString {
+    /// The first `len` bits of `bytes`, or `None` if it doesn't have exactly the bytes they fill
+    pub fn from_bytes(mut bytes: Vec<u8>, len: usize) -> Option<Self> {
+        if bytes.len() != len.div_ceil(8) {
+            return None;
+        }
+        let unused = bytes.len() * 8 - len;
+        if let Some(last) = bytes.last_mut() {
+            *last &= 0xff << unused;
+        }
+        Some(Self { len, bytes })
+    }
+
+    /// Number of bits
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Bits packed in bytes, the first one being the most significant bit of the first byte
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// Bit at `idx`, if there are that many
+    pub fn get(&self, idx: usize) -> Option<bool> {
+        (idx < self.len).then(|| self.bytes[idx / 8] & (0x80 >> (idx % 8)) != 0)
+    }
+
+    pub fn iter(&self) -> impl ExactSizeIterator<Item = bool> + '_ {
+        (0..self.len).map(|idx| self.bytes[idx / 8] & (0x80 >> (idx % 8)) != 0)
+    }
+}
+
+impl FromIterator<bool> for BitString {
+    fn from_iter<I: IntoIterator<Item = bool>>(iter: I) -> Self {
+        let mut bits = Self::default();
+        for bit in iter {
+            if bits.len % 8 == 0 {
+                bits.bytes.push(0);
+            }
+            if bit {
+                *bits.bytes.last_mut().unwrap() |= 0x80 >> (bits.len % 8);
+            }
+            bits.len += 1;
+        }
+        bits
+    }
+}
+
+impl From<&[bool]> for BitString {
+    fn from(bits: &[bool]) -> Self {
+        bits.iter().copied().collect()
+    }
+}
+
+impl From<Vec<bool>> for BitString {
+    fn from(bits: Vec<bool>) -> Self {
+        bits.into_iter().collect()
+    }
+}
+
+impl From<BitString> for Vec<bool> {
+    fn from(bits: BitString) -> Self {
+        bits.iter().collect()
+    }
+}
+
+/// Written as Postgres does, e.g. `1010`
+impl Display for BitString {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for bit in self.iter() {
+            f.write_str(if bit { "1" } else { "0" })?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a> FromSql<'a> for BitString {
+    fn from_sql(_: &Type, raw: &'a [u8]) -> Result<Self, Box<dyn Error + Sync + Send>> {
+        let (len, bytes) = raw
+            .split_first_chunk::<4>()
+            .ok_or("invalid bit string message size")?;
+        let len = usize::try_from(i32::from_be_bytes(*len))?;
+        Self::from_bytes(bytes.to_vec(), len)
+            .ok_or_else(|| "invalid bit string message size".into())
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        matches!(*ty, Type::BIT | Type::VARBIT)
+    }
+}
+
+impl ToSql for BitString {
+    fn to_sql(&self, _: &Type, out: &mut BytesMut) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
+        out.extend_from_slice(&i32::try_from(self.len)?.to_be_bytes());
+        out.extend_from_slice(&self.bytes);
+        Ok(IsNull::No)
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        matches!(*ty, Type::BIT | Type::VARBIT)
+    }
+
+    to_sql_checked!();
+}
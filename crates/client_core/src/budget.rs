@@ -0,0 +1,74 @@
+use std::{
+    error::Error,
+    fmt::Display,
+    future::Future,
+    time::{Duration, Instant},
+};
+
+use crate::QueryInfo;
+
+/// Query taking longer than the latency budget of its `--# budget` attribute
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BudgetExceeded {
+    /// Name of the query module
+    pub module: &'static str,
+    /// Name of the query
+    pub query: &'static str,
+    /// Latency budget of the query
+    pub budget: Duration,
+    /// Time the query took
+    pub elapsed: Duration,
+}
+
+impl Display for BudgetExceeded {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "query `{}::{}` took {:?}, over its latency budget of {:?}",
+            self.module, self.query, self.elapsed, self.budget
+        )
+    }
+}
+
+impl Error for BudgetExceeded {}
+
+fn check<T>(
+    query: &QueryInfo,
+    budget: Duration,
+    start: Instant,
+    res: T,
+) -> Result<T, BudgetExceeded> {
+    let elapsed = start.elapsed();
+    if elapsed <= budget {
+        Ok(res)
+    } else {
+        Err(BudgetExceeded {
+            module: query.module,
+            query: query.query,
+            budget,
+            elapsed,
+        })
+    }
+}
+
+/// Runs the query `f`, failing if it takes longer than its latency `budget`
+pub fn check_budget<T>(
+    query: &QueryInfo,
+    budget: Duration,
+    f: impl FnOnce() -> T,
+) -> Result<T, BudgetExceeded> {
+    let start = Instant::now();
+    let res = f();
+    check(query, budget, start, res)
+}
+
+/// Runs the query `fut`, failing if it takes longer than its latency `budget`
+pub async fn check_budget_future<T>(
+    query: &QueryInfo,
+    budget: Duration,
+    fut: impl Future<Output = T>,
+) -> Result<T, BudgetExceeded> {
+    let start = Instant::now();
+    let res = fut.await;
+    check(query, budget, start, res)
+}
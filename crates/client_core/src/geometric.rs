@@ -0,0 +1,255 @@
+use postgres_types::{private::BytesMut, to_sql_checked, FromSql, IsNull, ToSql, Type};
+use std::error::Error;
+
+/// Value of a `point`
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Point {
+    pub x: f64,
+    pub y: f64,
+}
+
+impl Point {
+    pub fn new(x: f64, y: f64) -> Self {
+        Self { x, y }
+    }
+}
+
+/// Value of a `line`, the points verifying `a * x + b * y + c = 0`
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Line {
+    pub a: f64,
+    pub b: f64,
+    pub c: f64,
+}
+
+/// Value of a `lseg`, the line segment between two points
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct LineSegment {
+    pub start: Point,
+    pub end: Point,
+}
+
+/// Value of a `box`, given by its upper right and lower left corners. Postgres swaps the
+/// coordinates of the corners it stores as needed, so `high` is above and right of `low`.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct BoundingBox {
+    pub high: Point,
+    pub low: Point,
+}
+
+/// Value of a `path`, open or closed
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Path {
+    pub closed: bool,
+    pub points: Vec<Point>,
+}
+
+/// Value of a `polygon`, given by its vertices
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Polygon {
+    pub points: Vec<Point>,
+}
+
+/// Value of a `circle`
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Circle {
+    pub center: Point,
+    pub radius: f64,
+}
+
+/// Splits the `N` floats starting `raw` off it
+fn take_f64s<const N: usize>(raw: &mut &[u8]) -> Result<[f64; N], Box<dyn Error + Sync + Send>> {
+    let mut floats = [0.; N];
+    for float in &mut floats {
+        let (taken, rest) = raw
+            .split_first_chunk::<8>()
+            .ok_or("invalid geometric message size")?;
+        *float = f64::from_be_bytes(*taken);
+        *raw = rest;
+    }
+    Ok(floats)
+}
+
+/// Reads the count of points starting `raw`, then the points
+fn read_points(raw: &mut &[u8]) -> Result<Vec<Point>, Box<dyn Error + Sync + Send>> {
+    let (len, rest) = raw
+        .split_first_chunk::<4>()
+        .ok_or("invalid geometric message size")?;
+    *raw = rest;
+    let len = usize::try_from(i32::from_be_bytes(*len))?;
+    (0..len).map(|_| Point::read(raw)).collect()
+}
+
+/// Writes the count of `points`, then the points
+fn write_points(points: &[Point], out: &mut BytesMut) -> Result<(), Box<dyn Error + Sync + Send>> {
+    out.extend_from_slice(&i32::try_from(points.len())?.to_be_bytes());
+    write_f64s(points.iter().flat_map(|it| [it.x, it.y]), out);
+    Ok(())
+}
+
+fn write_f64s(floats: impl IntoIterator<Item = f64>, out: &mut BytesMut) {
+    for float in floats {
+        out.extend_from_slice(&float.to_be_bytes());
+    }
+}
+
+/// Geometric type, read and written as the fields of its binary format
+trait Geometric: Sized {
+    const TYPE: Type;
+
+    fn read(raw: &mut &[u8]) -> Result<Self, Box<dyn Error + Sync + Send>>;
+
+    fn write(&self, out: &mut BytesMut) -> Result<(), Box<dyn Error + Sync + Send>>;
+}
+
+impl Geometric for Point {
+    const TYPE: Type = Type::POINT;
+
+    fn read(raw: &mut &[u8]) -> Result<Self, Box<dyn Error + Sync + Send>> {
+        let [x, y] = take_f64s(raw)?;
+        Ok(Self { x, y })
+    }
+
+    fn write(&self, out: &mut BytesMut) -> Result<(), Box<dyn Error + Sync + Send>> {
+        write_f64s([self.x, self.y], out);
+        Ok(())
+    }
+}
+
+impl Geometric for Line {
+    const TYPE: Type = Type::LINE;
+
+    fn read(raw: &mut &[u8]) -> Result<Self, Box<dyn Error + Sync + Send>> {
+        let [a, b, c] = take_f64s(raw)?;
+        Ok(Self { a, b, c })
+    }
+
+    fn write(&self, out: &mut BytesMut) -> Result<(), Box<dyn Error + Sync + Send>> {
+        write_f64s([self.a, self.b, self.c], out);
+        Ok(())
+    }
+}
+
+impl Geometric for LineSegment {
+    const TYPE: Type = Type::LSEG;
+
+    fn read(raw: &mut &[u8]) -> Result<Self, Box<dyn Error + Sync + Send>> {
+        Ok(Self {
+            start: Point::read(raw)?,
+            end: Point::read(raw)?,
+        })
+    }
+
+    fn write(&self, out: &mut BytesMut) -> Result<(), Box<dyn Error + Sync + Send>> {
+        self.start.write(out)?;
+        self.end.write(out)
+    }
+}
+
+impl Geometric for BoundingBox {
+    const TYPE: Type = Type::BOX;
+
+    fn read(raw: &mut &[u8]) -> Result<Self, Box<dyn Error + Sync + Send>> {
+        Ok(Self {
+            high: Point::read(raw)?,
+            low: Point::read(raw)?,
+        })
+    }
+
+    fn write(&self, out: &mut BytesMut) -> Result<(), Box<dyn Error + Sync + Send>> {
+        self.high.write(out)?;
+        self.low.write(out)
+    }
+}
+
+impl Geometric for Path {
+    const TYPE: Type = Type::PATH;
+
+    fn read(raw: &mut &[u8]) -> Result<Self, Box<dyn Error + Sync + Send>> {
+        let (closed, rest) = raw.split_first().ok_or("invalid geometric message size")?;
+        *raw = rest;
+        Ok(Self {
+            closed: *closed != 0,
+            points: read_points(raw)?,
+        })
+    }
+
+    fn write(&self, out: &mut BytesMut) -> Result<(), Box<dyn Error + Sync + Send>> {
+        out.extend_from_slice(&[u8::from(self.closed)]);
+        write_points(&self.points, out)
+    }
+}
+
+impl Geometric for Polygon {
+    const TYPE: Type = Type::POLYGON;
+
+    fn read(raw: &mut &[u8]) -> Result<Self, Box<dyn Error + Sync + Send>> {
+        Ok(Self {
+            points: read_points(raw)?,
+        })
+    }
+
+    fn write(&self, out: &mut BytesMut) -> Result<(), Box<dyn Error + Sync + Send>> {
+        write_points(&self.points, out)
+    }
+}
+
+impl Geometric for Circle {
+    const TYPE: Type = Type::CIRCLE;
+
+    fn read(raw: &mut &[u8]) -> Result<Self, Box<dyn Error + Sync + Send>> {
+        Ok(Self {
+            center: Point::read(raw)?,
+            radius: take_f64s::<1>(raw)?[0],
+        })
+    }
+
+    fn write(&self, out: &mut BytesMut) -> Result<(), Box<dyn Error + Sync + Send>> {
+        self.center.write(out)?;
+        write_f64s([self.radius], out);
+        Ok(())
+    }
+}
+
+/// Implements `FromSql` and `ToSql` for geometric types, which must read their whole message
+macro_rules! geometric_sql {
+    ($($ty:ident),*) => {
+        $(
+            impl<'a> FromSql<'a> for $ty {
+                fn from_sql(_: &Type, mut raw: &'a [u8]) -> Result<Self, Box<dyn Error + Sync + Send>> {
+                    let value = Self::read(&mut raw)?;
+                    if !raw.is_empty() {
+                        return Err("invalid geometric message size".into());
+                    }
+                    Ok(value)
+                }
+
+                fn accepts(ty: &Type) -> bool {
+                    *ty == <Self as Geometric>::TYPE
+                }
+            }
+
+            impl ToSql for $ty {
+                fn to_sql(&self, _: &Type, out: &mut BytesMut) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
+                    self.write(out)?;
+                    Ok(IsNull::No)
+                }
+
+                fn accepts(ty: &Type) -> bool {
+                    *ty == <Self as Geometric>::TYPE
+                }
+
+                to_sql_checked!();
+            }
+        )*
+    };
+}
+
+geometric_sql!(Point, Line, LineSegment, BoundingBox, Path, Polygon, Circle);
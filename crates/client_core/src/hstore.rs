@@ -0,0 +1,47 @@
+use fallible_iterator::FallibleIterator;
+use postgres_protocol::types::{hstore_from_sql, HstoreEntries};
+use postgres_types::{FromSql, Type};
+use std::{collections::HashMap, error::Error, fmt::Debug, hash::BuildHasher};
+
+/// Iterator over the entries of an `hstore` value, borrowing its keys and values. You only need
+/// this if you are working with custom zero-cost type mapping of rows containing `hstore`s.
+pub struct HstoreIterator<'a> {
+    entries: HstoreEntries<'a>,
+}
+
+impl Debug for HstoreIterator<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("HstoreIterator")
+            .field("entries", &"[(&str, Option<&str>)]")
+            .finish()
+    }
+}
+
+impl<'a> Iterator for HstoreIterator<'a> {
+    type Item = (&'a str, Option<&'a str>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.entries.next().unwrap()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.entries.size_hint()
+    }
+}
+
+impl<'a> FromSql<'a> for HstoreIterator<'a> {
+    fn from_sql(_: &Type, raw: &'a [u8]) -> Result<Self, Box<dyn Error + Sync + Send>> {
+        Ok(HstoreIterator {
+            entries: hstore_from_sql(raw)?,
+        })
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        ty.name() == "hstore"
+    }
+}
+
+/// `hstore` parameter
+pub trait HstoreSql: Debug + postgres_types::ToSql + Sync {}
+impl<T: HstoreSql> HstoreSql for &T {}
+impl<S: BuildHasher + Debug + Sync> HstoreSql for HashMap<String, Option<String>, S> {}
@@ -0,0 +1,113 @@
+use postgres_types::{private::BytesMut, to_sql_checked, FromSql, IsNull, ToSql, Type};
+use std::error::Error;
+
+/// Value of an `interval`, with its months, days and microseconds kept apart as Postgres
+/// does, since months and days have no fixed duration.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Interval {
+    pub months: i32,
+    pub days: i32,
+    pub microseconds: i64,
+}
+
+impl Interval {
+    pub fn new(months: i32, days: i32, microseconds: i64) -> Self {
+        Self {
+            months,
+            days,
+            microseconds,
+        }
+    }
+}
+
+impl<'a> FromSql<'a> for Interval {
+    fn from_sql(_: &Type, raw: &'a [u8]) -> Result<Self, Box<dyn Error + Sync + Send>> {
+        let raw = <[u8; 16]>::try_from(raw).map_err(|_| "invalid interval message size")?;
+        let (microseconds, rest) = raw.split_at(8);
+        let (days, months) = rest.split_at(4);
+        Ok(Self {
+            months: i32::from_be_bytes(months.try_into()?),
+            days: i32::from_be_bytes(days.try_into()?),
+            microseconds: i64::from_be_bytes(microseconds.try_into()?),
+        })
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        *ty == Type::INTERVAL
+    }
+}
+
+impl ToSql for Interval {
+    fn to_sql(&self, _: &Type, out: &mut BytesMut) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
+        out.extend_from_slice(&self.microseconds.to_be_bytes());
+        out.extend_from_slice(&self.days.to_be_bytes());
+        out.extend_from_slice(&self.months.to_be_bytes());
+        Ok(IsNull::No)
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        *ty == Type::INTERVAL
+    }
+
+    to_sql_checked!();
+}
+
+/// Value of an `interval` without months, whose days are counted as 24 hours. Decoding an
+/// interval with months fails, as they have no exact duration.
+#[cfg(feature = "with-time-0_3")]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ExactInterval(pub time::Duration);
+
+#[cfg(feature = "with-time-0_3")]
+impl From<time::Duration> for ExactInterval {
+    fn from(duration: time::Duration) -> Self {
+        Self(duration)
+    }
+}
+
+#[cfg(feature = "with-time-0_3")]
+impl From<ExactInterval> for time::Duration {
+    fn from(interval: ExactInterval) -> Self {
+        interval.0
+    }
+}
+
+#[cfg(feature = "with-time-0_3")]
+impl<'a> FromSql<'a> for ExactInterval {
+    fn from_sql(ty: &Type, raw: &'a [u8]) -> Result<Self, Box<dyn Error + Sync + Send>> {
+        let Interval {
+            months,
+            days,
+            microseconds,
+        } = Interval::from_sql(ty, raw)?;
+        if months != 0 {
+            return Err(format!("interval of {months} months has no exact duration").into());
+        }
+        Ok(Self(
+            time::Duration::days(days.into()) + time::Duration::microseconds(microseconds),
+        ))
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        *ty == Type::INTERVAL
+    }
+}
+
+#[cfg(feature = "with-time-0_3")]
+impl ToSql for ExactInterval {
+    fn to_sql(
+        &self,
+        ty: &Type,
+        out: &mut BytesMut,
+    ) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
+        let microseconds = i64::try_from(self.0.whole_microseconds())?;
+        Interval::new(0, 0, microseconds).to_sql(ty, out)
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        *ty == Type::INTERVAL
+    }
+
+    to_sql_checked!();
+}
@@ -0,0 +1,33 @@
+use std::io::SeekFrom;
+
+/// Access mode of a large object opened with `lo_open`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LargeObjectMode {
+    Read,
+    Write,
+    ReadWrite,
+}
+
+impl LargeObjectMode {
+    /// Flags of `lo_open`, as defined by `libpq/libpq-fs.h`
+    #[must_use]
+    pub fn flags(self) -> i32 {
+        const INV_WRITE: i32 = 0x0002_0000;
+        const INV_READ: i32 = 0x0004_0000;
+        match self {
+            LargeObjectMode::Read => INV_READ,
+            LargeObjectMode::Write => INV_WRITE,
+            LargeObjectMode::ReadWrite => INV_READ | INV_WRITE,
+        }
+    }
+}
+
+/// Offset and whence arguments of `lo_lseek64` seeking to `pos`
+#[must_use]
+pub fn lo_seek_args(pos: SeekFrom) -> (i64, i32) {
+    match pos {
+        SeekFrom::Start(offset) => (offset as i64, 0),
+        SeekFrom::Current(offset) => (offset, 1),
+        SeekFrom::End(offset) => (offset, 2),
+    }
+}
@@ -1,13 +1,49 @@
 mod array_iterator;
+mod audit;
+mod bit_string;
+mod budget;
 mod domain;
+mod geometric;
+#[cfg(feature = "hstore")]
+mod hstore;
+mod interval;
+mod large_object;
+mod money;
+mod multirange;
+mod observer;
+mod page;
+mod range;
+mod record;
+mod stmt_cache;
+mod text_search;
 mod type_traits;
 mod utils;
+mod validate;
 
-pub use array_iterator::ArrayIterator;
+pub use array_iterator::{ArrayIterator, MatrixIterator};
+pub use audit::{audit, set_auditor, AuditRecord, Auditor};
+pub use bit_string::BitString;
+pub use budget::{check_budget, check_budget_future, BudgetExceeded};
 pub use domain::{Domain, DomainArray};
+pub use geometric::{BoundingBox, Circle, Line, LineSegment, Path, Point, Polygon};
+#[cfg(feature = "hstore")]
+pub use hstore::{HstoreIterator, HstoreSql};
+#[cfg(feature = "with-time-0_3")]
+pub use interval::ExactInterval;
+pub use interval::Interval;
+pub use large_object::{lo_seek_args, LargeObjectMode};
+pub use money::Money;
+pub use multirange::{Multirange, MultirangeIterator, MultirangeSql};
+pub use observer::{observe, observe_future, set_observer, QueryInfo, QueryObserver, QueryOutcome};
+pub use page::Page;
+pub use range::Range;
+pub use record::Record;
+pub use stmt_cache::StmtCache;
+pub use text_search::{Lexeme, LexemePosition, TsQuery, TsVector, Weight};
 pub use type_traits::{ArraySql, BytesSql, IterSql, StringSql};
 
 #[cfg(feature = "with-serde_json-1")]
 pub use type_traits::JsonSql;
 
 pub use utils::slice_iter;
+pub use validate::ValidationError;
@@ -0,0 +1,69 @@
+use postgres_types::{private::BytesMut, to_sql_checked, FromSql, IsNull, ToSql, Type};
+use std::error::Error;
+
+/// Value of a `money`, as its amount in the smallest unit of the currency, e.g. cents.
+///
+/// Postgres stores it with the number of fraction digits of its `lc_monetary` locale, which
+/// is 2 for most currencies, so `12.34::money` is read as `Money(1234)`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Money(pub i64);
+
+impl From<i64> for Money {
+    fn from(cents: i64) -> Self {
+        Self(cents)
+    }
+}
+
+impl From<Money> for i64 {
+    fn from(money: Money) -> Self {
+        money.0
+    }
+}
+
+#[cfg(feature = "with-rust_decimal-1")]
+impl From<Money> for rust_decimal::Decimal {
+    fn from(money: Money) -> Self {
+        Self::new(money.0, 2)
+    }
+}
+
+#[cfg(feature = "with-rust_decimal-1")]
+impl TryFrom<rust_decimal::Decimal> for Money {
+    type Error = Box<dyn Error + Sync + Send>;
+
+    /// Fails if the amount has more than 2 fraction digits, or doesn't fit a `money`
+    fn try_from(amount: rust_decimal::Decimal) -> Result<Self, Self::Error> {
+        use rust_decimal::prelude::ToPrimitive;
+        let cents = amount
+            .checked_mul(rust_decimal::Decimal::ONE_HUNDRED)
+            .filter(|it| it.fract().is_zero())
+            .and_then(|it| it.to_i64())
+            .ok_or_else(|| format!("{amount} isn't a valid amount of money"))?;
+        Ok(Self(cents))
+    }
+}
+
+impl<'a> FromSql<'a> for Money {
+    fn from_sql(_: &Type, raw: &'a [u8]) -> Result<Self, Box<dyn Error + Sync + Send>> {
+        let raw = <[u8; 8]>::try_from(raw).map_err(|_| "invalid money message size")?;
+        Ok(Self(i64::from_be_bytes(raw)))
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        *ty == Type::MONEY
+    }
+}
+
+impl ToSql for Money {
+    fn to_sql(&self, _: &Type, out: &mut BytesMut) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
+        out.extend_from_slice(&self.0.to_be_bytes());
+        Ok(IsNull::No)
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        *ty == Type::MONEY
+    }
+
+    to_sql_checked!();
+}
@@ -0,0 +1,153 @@
+use postgres_types::{private::BytesMut, to_sql_checked, FromSql, IsNull, Kind, ToSql, Type};
+use std::{error::Error, fmt::Debug, marker::PhantomData};
+
+use crate::Range;
+
+/// Iterator over the ranges of a PostgreSQL multirange, e.g. an `int4multirange`. You only need
+/// this if you are working with custom zero-cost type mapping of rows containing multiranges.
+pub struct MultirangeIterator<'a, T: FromSql<'a>> {
+    remaining: &'a [u8],
+    len: usize,
+    /// Type of the bounds of the ranges
+    ty: Type,
+    _type: PhantomData<T>,
+}
+
+impl<'a, T: FromSql<'a>> Debug for MultirangeIterator<'a, T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MultirangeIterator")
+            .field("ranges", &"[Range<T>]")
+            .field("len", &self.len)
+            .field("ty", &self.ty)
+            .finish()
+    }
+}
+
+/// Type of the bounds of the ranges of the multirange type `ty`
+fn bound_type(ty: &Type) -> &Type {
+    match *ty.kind() {
+        Kind::Multirange(ref inner) => inner,
+        _ => panic!("expected multirange type got {ty}"),
+    }
+}
+
+/// Reads the big endian integer starting `raw`
+fn read_i32(raw: &mut &[u8]) -> Result<i32, Box<dyn Error + Sync + Send>> {
+    let (int, rest) = raw
+        .split_first_chunk::<4>()
+        .ok_or("invalid multirange message size")?;
+    *raw = rest;
+    Ok(i32::from_be_bytes(*int))
+}
+
+impl<'a, T: FromSql<'a>> Iterator for MultirangeIterator<'a, T> {
+    type Item = Range<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        self.len -= 1;
+        let size = read_i32(&mut self.remaining).unwrap() as usize;
+        let (raw, rest) = self.remaining.split_at(size);
+        self.remaining = rest;
+        Some(Range::read(&self.ty, raw).unwrap())
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, T: FromSql<'a>> ExactSizeIterator for MultirangeIterator<'a, T> {}
+
+impl<'a, T: FromSql<'a>> FromSql<'a> for MultirangeIterator<'a, T> {
+    fn from_sql(ty: &Type, mut raw: &'a [u8]) -> Result<Self, Box<dyn Error + Sync + Send>> {
+        let len = read_i32(&mut raw)?;
+        Ok(MultirangeIterator {
+            remaining: raw,
+            len: usize::try_from(len)?,
+            ty: bound_type(ty).clone(),
+            _type: PhantomData,
+        })
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        match *ty.kind() {
+            Kind::Multirange(ref inner) => T::accepts(inner),
+            _ => false,
+        }
+    }
+}
+
+/// Value written as a multirange: the slice of its ranges, or an optional one when nullable
+pub trait MultirangeSql: Debug {
+    fn write(&self, ty: &Type, out: &mut BytesMut) -> Result<IsNull, Box<dyn Error + Sync + Send>>;
+
+    fn accepts(ty: &Type) -> bool;
+}
+
+impl<T: ToSql> MultirangeSql for &[Range<T>] {
+    fn write(&self, ty: &Type, out: &mut BytesMut) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
+        let ty = bound_type(ty);
+        out.extend_from_slice(&i32::try_from(self.len())?.to_be_bytes());
+        for range in self.iter() {
+            let base = out.len();
+            out.extend_from_slice(&[0; 4]);
+            range.write(ty, out)?;
+            let size = i32::try_from(out.len() - base - 4)?;
+            out[base..base + 4].copy_from_slice(&size.to_be_bytes());
+        }
+        Ok(IsNull::No)
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        match *ty.kind() {
+            Kind::Multirange(ref inner) => T::accepts(inner),
+            _ => false,
+        }
+    }
+}
+
+impl<M: MultirangeSql> MultirangeSql for &M {
+    fn write(&self, ty: &Type, out: &mut BytesMut) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
+        M::write(self, ty, out)
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        M::accepts(ty)
+    }
+}
+
+impl<M: MultirangeSql> MultirangeSql for Option<M> {
+    fn write(&self, ty: &Type, out: &mut BytesMut) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
+        match self {
+            Some(it) => it.write(ty, out),
+            None => Ok(IsNull::Yes),
+        }
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        M::accepts(ty)
+    }
+}
+
+/// Multirange parameter, written from its ranges
+#[derive(Debug)]
+pub struct Multirange<M: MultirangeSql>(pub M);
+
+impl<M: MultirangeSql> ToSql for Multirange<M> {
+    fn to_sql(
+        &self,
+        ty: &Type,
+        out: &mut BytesMut,
+    ) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
+        self.0.write(ty, out)
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        M::accepts(ty)
+    }
+
+    to_sql_checked!();
+}
@@ -0,0 +1,83 @@
+use std::{
+    error::Error,
+    future::Future,
+    sync::OnceLock,
+    time::{Duration, Instant},
+};
+
+static OBSERVER: OnceLock<Box<dyn QueryObserver>> = OnceLock::new();
+
+/// Query run by the generated code
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QueryInfo {
+    /// Name of the query module
+    pub module: &'static str,
+    /// Name of the query
+    pub query: &'static str,
+}
+
+/// Outcome of a run of a query
+#[derive(Debug, Clone, Copy)]
+pub enum QueryOutcome<'a> {
+    /// The query succeeded, with the number of rows it returned or affected when known
+    Success { rows: Option<u64> },
+    /// The query failed
+    Failure(&'a (dyn Error + 'static)),
+}
+
+/// Observes the runs of the generated queries, e.g. to collect metrics or log slow statements.
+pub trait QueryObserver: Send + Sync {
+    /// Called before each run of a query
+    fn on_start(&self, _query: &QueryInfo) {}
+
+    /// Called after each run of a query, which took `elapsed`
+    fn on_end(&self, query: &QueryInfo, elapsed: Duration, outcome: QueryOutcome<'_>);
+}
+
+/// Sets the observer of the runs of queries.
+/// It can only be set once, the observer is given back if one was already set.
+pub fn set_observer<O: QueryObserver + 'static>(observer: O) -> Result<(), Box<dyn QueryObserver>> {
+    OBSERVER.set(Box::new(observer))
+}
+
+fn outcome<'a, T, E: Error + 'static>(
+    res: &'a Result<T, E>,
+    rows: impl FnOnce(&T) -> Option<u64>,
+) -> QueryOutcome<'a> {
+    match res {
+        Ok(it) => QueryOutcome::Success { rows: rows(it) },
+        Err(err) => QueryOutcome::Failure(err),
+    }
+}
+
+/// Runs the query `f`, reporting it to the observer if any
+pub fn observe<T, E: Error + 'static>(
+    query: &QueryInfo,
+    rows: impl FnOnce(&T) -> Option<u64>,
+    f: impl FnOnce() -> Result<T, E>,
+) -> Result<T, E> {
+    let Some(observer) = OBSERVER.get() else {
+        return f();
+    };
+    observer.on_start(query);
+    let start = Instant::now();
+    let res = f();
+    observer.on_end(query, start.elapsed(), outcome(&res, rows));
+    res
+}
+
+/// Runs the query `fut`, reporting it to the observer if any
+pub async fn observe_future<T, E: Error + 'static>(
+    query: &QueryInfo,
+    rows: impl FnOnce(&T) -> Option<u64>,
+    fut: impl Future<Output = Result<T, E>>,
+) -> Result<T, E> {
+    let Some(observer) = OBSERVER.get() else {
+        return fut.await;
+    };
+    observer.on_start(query);
+    let start = Instant::now();
+    let res = fut.await;
+    observer.on_end(query, start.elapsed(), outcome(&res, rows));
+    res
+}
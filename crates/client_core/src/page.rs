@@ -0,0 +1,8 @@
+/// Page of rows of a query annotated with `--# paginate`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Page<T, C> {
+    /// Rows of the page, in order
+    pub rows: Vec<T>,
+    /// Cursor to fetch the next page from, if this page was full
+    pub next: Option<C>,
+}
@@ -0,0 +1,174 @@
+use postgres_protocol::types::{
+    self, empty_range_to_sql, range_from_sql, range_to_sql, RangeBound,
+};
+use postgres_types::{private::BytesMut, to_sql_checked, FromSql, IsNull, Kind, ToSql, Type};
+use std::{error::Error, ops::Bound};
+
+/// Value of a range type, e.g. `int4range`, `daterange` or `tstzrange`.
+///
+/// Postgres normalizes the ranges of discrete types, so `[1,5]::int4range` is read back as
+/// `[1,6)`, with an exclusive upper bound.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum Range<T> {
+    /// Range holding no value, like `'empty'::int4range`
+    Empty,
+    /// Range going from its lower bound to its upper bound
+    Nonempty(Bound<T>, Bound<T>),
+}
+
+impl<T> Range<T> {
+    /// Range going from `lower` to `upper`
+    pub fn new(lower: Bound<T>, upper: Bound<T>) -> Self {
+        Self::Nonempty(lower, upper)
+    }
+
+    /// Does this range hold no value
+    pub fn is_empty(&self) -> bool {
+        matches!(self, Self::Empty)
+    }
+
+    /// Lower and upper bounds of this range, unless it is empty
+    pub fn bounds(&self) -> Option<(Bound<&T>, Bound<&T>)> {
+        match self {
+            Self::Empty => None,
+            Self::Nonempty(lower, upper) => Some((lower.as_ref(), upper.as_ref())),
+        }
+    }
+
+    /// Is `value` between the bounds of this range
+    pub fn contains(&self, value: &T) -> bool
+    where
+        T: PartialOrd,
+    {
+        let Some((lower, upper)) = self.bounds() else {
+            return false;
+        };
+        let above = match lower {
+            Bound::Included(it) => it <= value,
+            Bound::Excluded(it) => it < value,
+            Bound::Unbounded => true,
+        };
+        let below = match upper {
+            Bound::Included(it) => value <= it,
+            Bound::Excluded(it) => value < it,
+            Bound::Unbounded => true,
+        };
+        above && below
+    }
+
+    /// Range with the bounds of this one mapped by `f`
+    pub fn map<U>(self, mut f: impl FnMut(T) -> U) -> Range<U> {
+        let mut bound = |bound: Bound<T>| match bound {
+            Bound::Included(it) => Bound::Included(f(it)),
+            Bound::Excluded(it) => Bound::Excluded(f(it)),
+            Bound::Unbounded => Bound::Unbounded,
+        };
+        match self {
+            Self::Empty => Range::Empty,
+            Self::Nonempty(lower, upper) => {
+                let lower = bound(lower);
+                Range::Nonempty(lower, bound(upper))
+            }
+        }
+    }
+}
+
+/// Type of the bounds of the range type `ty`
+fn bound_type(ty: &Type) -> &Type {
+    match *ty.kind() {
+        Kind::Range(ref inner) => inner,
+        _ => panic!("expected range type got {ty}"),
+    }
+}
+
+fn bound_from_sql<'a, T: FromSql<'a>>(
+    ty: &Type,
+    bound: RangeBound<Option<&'a [u8]>>,
+) -> Result<Bound<T>, Box<dyn Error + Sync + Send>> {
+    Ok(match bound {
+        RangeBound::Inclusive(raw) => Bound::Included(T::from_sql_nullable(ty, raw)?),
+        RangeBound::Exclusive(raw) => Bound::Excluded(T::from_sql_nullable(ty, raw)?),
+        RangeBound::Unbounded => Bound::Unbounded,
+    })
+}
+
+fn bound_to_sql<T: ToSql>(
+    ty: &Type,
+    bound: &Bound<T>,
+    out: &mut BytesMut,
+) -> Result<RangeBound<postgres_protocol::IsNull>, Box<dyn Error + Sync + Send>> {
+    let value = |it: &T, out: &mut BytesMut| match it.to_sql(ty, out)? {
+        IsNull::No => Ok::<_, Box<dyn Error + Sync + Send>>(postgres_protocol::IsNull::No),
+        IsNull::Yes => Ok(postgres_protocol::IsNull::Yes),
+    };
+    Ok(match bound {
+        Bound::Included(it) => RangeBound::Inclusive(value(it, out)?),
+        Bound::Excluded(it) => RangeBound::Exclusive(value(it, out)?),
+        Bound::Unbounded => RangeBound::Unbounded,
+    })
+}
+
+impl<'a, T: FromSql<'a>> Range<T> {
+    /// Reads a range whose bounds are of type `ty`
+    pub(crate) fn read(ty: &Type, raw: &'a [u8]) -> Result<Self, Box<dyn Error + Sync + Send>> {
+        Ok(match range_from_sql(raw)? {
+            types::Range::Empty => Self::Empty,
+            types::Range::Nonempty(lower, upper) => {
+                Self::Nonempty(bound_from_sql(ty, lower)?, bound_from_sql(ty, upper)?)
+            }
+        })
+    }
+}
+
+impl<T: ToSql> Range<T> {
+    /// Writes a range whose bounds are of type `ty`
+    pub(crate) fn write(
+        &self,
+        ty: &Type,
+        out: &mut BytesMut,
+    ) -> Result<(), Box<dyn Error + Sync + Send>> {
+        match self {
+            Self::Empty => empty_range_to_sql(out),
+            Self::Nonempty(lower, upper) => range_to_sql(
+                |out| bound_to_sql(ty, lower, out),
+                |out| bound_to_sql(ty, upper, out),
+                out,
+            )?,
+        }
+        Ok(())
+    }
+}
+
+impl<'a, T: FromSql<'a>> FromSql<'a> for Range<T> {
+    fn from_sql(ty: &Type, raw: &'a [u8]) -> Result<Self, Box<dyn Error + Sync + Send>> {
+        Self::read(bound_type(ty), raw)
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        match *ty.kind() {
+            Kind::Range(ref inner) => T::accepts(inner),
+            _ => false,
+        }
+    }
+}
+
+impl<T: ToSql> ToSql for Range<T> {
+    fn to_sql(
+        &self,
+        ty: &Type,
+        out: &mut BytesMut,
+    ) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
+        self.write(bound_type(ty), out)?;
+        Ok(IsNull::No)
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        match *ty.kind() {
+            Kind::Range(ref inner) => T::accepts(inner),
+            _ => false,
+        }
+    }
+
+    to_sql_checked!();
+}
@@ -0,0 +1,82 @@
+use postgres_types::{FromSql, Type};
+use std::error::Error;
+
+/// Value of an anonymous `record`, e.g. built by `ROW(a, b)`, decoded as the tuple `T` of the
+/// Rust types of its fields. Only fields of the built-in types can be decoded, as the types of
+/// the others aren't known by the client.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Record<T>(pub T);
+
+/// Reads the built-in type and the value of the next field of a record
+fn read_field<'a, T: FromSql<'a>>(
+    raw: &mut &'a [u8],
+    idx: usize,
+) -> Result<T, Box<dyn Error + Sync + Send>> {
+    let (oid, rest) = raw
+        .split_first_chunk::<4>()
+        .ok_or("invalid record message size")?;
+    let (len, rest) = rest
+        .split_first_chunk::<4>()
+        .ok_or("invalid record message size")?;
+    let oid = u32::from_be_bytes(*oid);
+    let ty = Type::from_oid(oid)
+        .ok_or_else(|| format!("field {idx} of the record has the unknown type {oid}"))?;
+    if !T::accepts(&ty) {
+        return Err(format!(
+            "field {idx} of the record of type {ty} can't be decoded as a {}",
+            std::any::type_name::<T>()
+        )
+        .into());
+    }
+    let len = i32::from_be_bytes(*len);
+    if len < 0 {
+        *raw = rest;
+        return T::from_sql_null(&ty);
+    }
+    let len = usize::try_from(len)?;
+    if rest.len() < len {
+        return Err("invalid record message size".into());
+    }
+    let (value, rest) = rest.split_at(len);
+    *raw = rest;
+    T::from_sql(&ty, value)
+}
+
+macro_rules! record_tuple {
+    ($len:literal, $($field:ident),+) => {
+        impl<'a, $($field: FromSql<'a>),+> FromSql<'a> for Record<($($field,)+)> {
+            fn from_sql(_: &Type, mut raw: &'a [u8]) -> Result<Self, Box<dyn Error + Sync + Send>> {
+                let (len, rest) = raw
+                    .split_first_chunk::<4>()
+                    .ok_or("invalid record message size")?;
+                raw = rest;
+                let len = i32::from_be_bytes(*len);
+                if len != $len {
+                    return Err(format!("expected a record of {} fields, got {len}", $len).into());
+                }
+                let mut idx = 0;
+                let record = ($({
+                    idx += 1;
+                    read_field::<$field>(&mut raw, idx)?
+                },)+);
+                if !raw.is_empty() {
+                    return Err("invalid record message size".into());
+                }
+                Ok(Self(record))
+            }
+
+            fn accepts(ty: &Type) -> bool {
+                *ty == Type::RECORD
+            }
+        }
+    };
+}
+
+record_tuple!(1, A);
+record_tuple!(2, A, B);
+record_tuple!(3, A, B, C);
+record_tuple!(4, A, B, C, D);
+record_tuple!(5, A, B, C, D, E);
+record_tuple!(6, A, B, C, D, E, F);
+record_tuple!(7, A, B, C, D, E, F, G);
+record_tuple!(8, A, B, C, D, E, F, G, H);
@@ -0,0 +1,63 @@
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    sync::{Mutex, MutexGuard, PoisonError},
+};
+
+/// Number of connections a shared statement stays prepared for
+const SHARED_CACHE_SIZE: usize = 64;
+
+/// Prepared statements of each query, most recently used connection first
+type Cached<S> = HashMap<Cow<'static, str>, Vec<(usize, S)>>;
+
+/// Statements of `S` shared between threads, e.g. by the clones of a generated `Queries`
+/// facade, keyed by query and connection. They are prepared lazily, once per connection.
+#[derive(Debug)]
+pub struct StmtCache<S> {
+    cached: Mutex<Cached<S>>,
+}
+
+impl<S> Default for StmtCache<S> {
+    fn default() -> Self {
+        Self {
+            cached: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+impl<S: Clone> StmtCache<S> {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Statement of `query` prepared on the connection identified by `key`
+    pub fn get(&self, query: &str, key: usize) -> Option<S> {
+        let mut cached = self.lock();
+        let stmts = cached.get_mut(query)?;
+        let idx = stmts.iter().position(|(k, _)| *k == key)?;
+        stmts[..=idx].rotate_right(1);
+        Some(stmts[0].1.clone())
+    }
+
+    /// Keeps `stmt`, the statement of `query` prepared on the connection identified by `key`
+    pub fn insert(&self, query: Cow<'static, str>, key: usize, stmt: S) {
+        let mut cached = self.lock();
+        let stmts = cached.entry(query).or_default();
+        stmts.retain(|(k, _)| *k != key);
+        stmts.insert(0, (key, stmt));
+        stmts.truncate(SHARED_CACHE_SIZE);
+    }
+
+    /// Forgets the statement of `query` prepared on the connection identified by `key`
+    pub fn remove(&self, query: &str, key: usize) {
+        if let Some(stmts) = self.lock().get_mut(query) {
+            stmts.retain(|(k, _)| *k != key);
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Cached<S>> {
+        // Statements are inserted whole, so a panic while locked leaves them consistent
+        self.cached.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
@@ -0,0 +1,264 @@
+use postgres_types::{private::BytesMut, to_sql_checked, FromSql, IsNull, ToSql, Type};
+use std::{error::Error, fmt::Display};
+
+/// Weight of a lexeme position, from the most important `A` to the default `D`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum Weight {
+    A,
+    B,
+    C,
+    D,
+}
+
+/// Position of a lexeme in its document, starting at 1, and its weight
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct LexemePosition {
+    pub position: u16,
+    pub weight: Weight,
+}
+
+/// Normalized word of a `tsvector`, along with its positions in the document, if any
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Lexeme {
+    pub word: String,
+    pub positions: Vec<LexemePosition>,
+}
+
+/// Value of a `tsvector`, the lexemes of a document sorted by word, e.g. as returned by
+/// `to_tsvector('english', 'The fat rats')`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct TsVector(pub Vec<Lexeme>);
+
+impl TsVector {
+    /// Does this document contain the lexeme `word`
+    pub fn contains(&self, word: &str) -> bool {
+        self.0
+            .binary_search_by(|it| it.word.as_str().cmp(word))
+            .is_ok()
+    }
+}
+
+/// Splits the first `N` bytes off `raw`
+fn take<'a, const N: usize>(
+    raw: &mut &'a [u8],
+) -> Result<&'a [u8; N], Box<dyn Error + Sync + Send>> {
+    let (taken, rest) = raw
+        .split_first_chunk::<N>()
+        .ok_or("invalid text search message size")?;
+    *raw = rest;
+    Ok(taken)
+}
+
+/// Splits the null terminated string starting `raw` off it
+fn take_str<'a>(raw: &mut &'a [u8]) -> Result<&'a str, Box<dyn Error + Sync + Send>> {
+    let end = raw
+        .iter()
+        .position(|it| *it == 0)
+        .ok_or("unterminated text search string")?;
+    let str = std::str::from_utf8(&raw[..end])?;
+    *raw = &raw[end + 1..];
+    Ok(str)
+}
+
+impl<'a> FromSql<'a> for TsVector {
+    fn from_sql(_: &Type, mut raw: &'a [u8]) -> Result<Self, Box<dyn Error + Sync + Send>> {
+        let len = i32::from_be_bytes(*take(&mut raw)?);
+        let mut lexemes = Vec::with_capacity(usize::try_from(len)?);
+        for _ in 0..len {
+            let word = take_str(&mut raw)?.to_string();
+            let count = u16::from_be_bytes(*take(&mut raw)?);
+            let positions = (0..count)
+                .map(|_| {
+                    let pos = u16::from_be_bytes(*take(&mut raw)?);
+                    let weight = match pos >> 14 {
+                        3 => Weight::A,
+                        2 => Weight::B,
+                        1 => Weight::C,
+                        _ => Weight::D,
+                    };
+                    Ok(LexemePosition {
+                        position: pos & 0x3fff,
+                        weight,
+                    })
+                })
+                .collect::<Result<_, Box<dyn Error + Sync + Send>>>()?;
+            lexemes.push(Lexeme { word, positions });
+        }
+        if !raw.is_empty() {
+            return Err("invalid tsvector message size".into());
+        }
+        Ok(Self(lexemes))
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        *ty == Type::TS_VECTOR
+    }
+}
+
+impl ToSql for TsVector {
+    fn to_sql(&self, _: &Type, out: &mut BytesMut) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
+        out.extend_from_slice(&i32::try_from(self.0.len())?.to_be_bytes());
+        for Lexeme { word, positions } in &self.0 {
+            if word.contains('\0') {
+                return Err("lexemes can't contain null characters".into());
+            }
+            out.extend_from_slice(word.as_bytes());
+            out.extend_from_slice(&[0]);
+            out.extend_from_slice(&u16::try_from(positions.len())?.to_be_bytes());
+            for LexemePosition { position, weight } in positions {
+                let weight: u16 = match weight {
+                    Weight::A => 3,
+                    Weight::B => 2,
+                    Weight::C => 1,
+                    Weight::D => 0,
+                };
+                out.extend_from_slice(&(weight << 14 | position & 0x3fff).to_be_bytes());
+            }
+        }
+        Ok(IsNull::No)
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        *ty == Type::TS_VECTOR
+    }
+
+    to_sql_checked!();
+}
+
+/// Value of a `tsquery`, written in the syntax read by Postgres, e.g. `'fat' & ( 'rat' | 'cat' )`.
+/// Parameters of this type are bound from text instead, parsed and normalized by `to_tsquery`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct TsQuery(pub String);
+
+impl Display for TsQuery {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// Operator of a `tsquery`, with the priority Postgres gives it
+#[derive(Clone, Copy, PartialEq)]
+enum Operator {
+    Not,
+    And,
+    Or,
+    Phrase(i16),
+}
+
+impl Operator {
+    fn priority(self) -> u8 {
+        match self {
+            Operator::Or => 1,
+            Operator::And => 2,
+            Operator::Phrase(_) => 3,
+            Operator::Not => 4,
+        }
+    }
+}
+
+/// Writes the `tsquery` node starting `raw`, given in prefix order, to `out`. Returns the
+/// priority of its operator, if any.
+fn write_tsquery(
+    raw: &mut &[u8],
+    out: &mut String,
+    depth: usize,
+) -> Result<Option<u8>, Box<dyn Error + Sync + Send>> {
+    if depth > 1024 {
+        return Err("tsquery too deeply nested".into());
+    }
+    match take::<1>(raw)?[0] {
+        // Operand
+        1 => {
+            let [weight] = *take(raw)?;
+            let [prefix] = *take(raw)?;
+            let word = take_str(raw)?;
+            out.push('\'');
+            for c in word.chars() {
+                if c == '\'' || c == '\\' {
+                    out.push(c);
+                }
+                out.push(c);
+            }
+            out.push('\'');
+            if prefix != 0 || weight != 0 {
+                out.push(':');
+            }
+            if prefix != 0 {
+                out.push('*');
+            }
+            for (bit, c) in [(3, 'A'), (2, 'B'), (1, 'C'), (0, 'D')] {
+                if weight & 1 << bit != 0 {
+                    out.push(c);
+                }
+            }
+            Ok(None)
+        }
+        // Operator
+        2 => {
+            let operator = match take::<1>(raw)?[0] {
+                1 => Operator::Not,
+                2 => Operator::And,
+                3 => Operator::Or,
+                4 => Operator::Phrase(i16::from_be_bytes(*take(raw)?)),
+                _ => return Err("invalid tsquery operator".into()),
+            };
+            let priority = operator.priority();
+            // Operands are parenthesized when their operator binds less tightly, or when
+            // phrases are nested, as their distances don't associate
+            let operand = |raw: &mut &[u8], out: &mut String| {
+                let mut operand = String::new();
+                match write_tsquery(raw, &mut operand, depth + 1)? {
+                    Some(inner) if inner < priority || (inner == 3 && priority == 3) => {
+                        out.push_str("( ");
+                        out.push_str(&operand);
+                        out.push_str(" )");
+                    }
+                    _ => out.push_str(&operand),
+                }
+                Ok::<_, Box<dyn Error + Sync + Send>>(())
+            };
+            if operator == Operator::Not {
+                out.push('!');
+                operand(raw, out)?;
+                return Ok(Some(priority));
+            }
+            // The right operand comes first
+            let mut right = String::new();
+            operand(raw, &mut right)?;
+            operand(raw, out)?;
+            match operator {
+                Operator::And => out.push_str(" & "),
+                Operator::Or => out.push_str(" | "),
+                Operator::Phrase(1) => out.push_str(" <-> "),
+                Operator::Phrase(distance) => out.push_str(&format!(" <{distance}> ")),
+                Operator::Not => unreachable!("written above"),
+            }
+            out.push_str(&right);
+            Ok(Some(priority))
+        }
+        _ => Err("invalid tsquery item".into()),
+    }
+}
+
+impl<'a> FromSql<'a> for TsQuery {
+    fn from_sql(_: &Type, mut raw: &'a [u8]) -> Result<Self, Box<dyn Error + Sync + Send>> {
+        let len = i32::from_be_bytes(*take(&mut raw)?);
+        let mut query = String::new();
+        if len > 0 {
+            write_tsquery(&mut raw, &mut query, 0)?;
+        }
+        if !raw.is_empty() {
+            return Err("invalid tsquery message size".into());
+        }
+        Ok(Self(query))
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        *ty == Type::TSQUERY
+    }
+}
@@ -4,17 +4,55 @@ use crate::domain::escape_domain_to_sql;
 use postgres_protocol::types::{self, ArrayDimension};
 use postgres_types::{private::BytesMut, to_sql_checked, IsNull, Kind, ToSql, Type};
 
-pub trait StringSql: std::fmt::Debug + ToSql + Sync {}
-impl<T: StringSql> StringSql for &T {}
-impl StringSql for String {}
-impl StringSql for &str {}
-impl StringSql for Cow<'_, str> {}
-impl StringSql for Box<str> {}
-
-pub trait BytesSql: std::fmt::Debug + ToSql + Send + Sync {}
-impl<T: BytesSql> BytesSql for &T {}
-impl BytesSql for Vec<u8> {}
-impl BytesSql for &[u8] {}
+pub trait StringSql: std::fmt::Debug + ToSql + Sync {
+    /// Text bound, read by the checks of the `validate` attribute
+    fn as_str(&self) -> &str;
+}
+impl<T: StringSql> StringSql for &T {
+    fn as_str(&self) -> &str {
+        T::as_str(self)
+    }
+}
+impl StringSql for String {
+    fn as_str(&self) -> &str {
+        self
+    }
+}
+impl StringSql for &str {
+    fn as_str(&self) -> &str {
+        self
+    }
+}
+impl StringSql for Cow<'_, str> {
+    fn as_str(&self) -> &str {
+        self
+    }
+}
+impl StringSql for Box<str> {
+    fn as_str(&self) -> &str {
+        self
+    }
+}
+
+pub trait BytesSql: std::fmt::Debug + ToSql + Send + Sync {
+    /// Bytes bound, read by the checks of the `validate` attribute
+    fn as_bytes(&self) -> &[u8];
+}
+impl<T: BytesSql> BytesSql for &T {
+    fn as_bytes(&self) -> &[u8] {
+        T::as_bytes(self)
+    }
+}
+impl BytesSql for Vec<u8> {
+    fn as_bytes(&self) -> &[u8] {
+        self
+    }
+}
+impl BytesSql for &[u8] {
+    fn as_bytes(&self) -> &[u8] {
+        self
+    }
+}
 
 #[cfg(feature = "with-serde_json-1")]
 pub trait JsonSql: std::fmt::Debug + ToSql + Sync + Send {}
@@ -25,6 +63,9 @@ impl JsonSql for serde_json::value::Value {}
 #[cfg(feature = "with-serde_json-1")]
 impl<T: serde::ser::Serialize + std::fmt::Debug + Sync + Send> JsonSql for postgres_types::Json<T> {}
 
+/// Array parameter, e.g. the list of an `= ANY(:ids)` comparison. Implemented for vectors,
+/// slices and boxed slices of any parameter type, including enums and composites, and for
+/// [`IterSql`] to bind an iterator without allocating.
 pub trait ArraySql: std::fmt::Debug + ToSql + Send + Sync {
     type Item;
     fn escape_domain_to_sql(
@@ -68,6 +109,18 @@ impl<T: std::fmt::Debug + ToSql + Sync> ArraySql for &[T] {
     }
 }
 
+impl<T: std::fmt::Debug + ToSql + Send + Sync> ArraySql for Box<[T]> {
+    type Item = T;
+
+    fn escape_domain_to_sql(
+        &self,
+        ty: &Type,
+        w: &mut BytesMut,
+    ) -> Result<IsNull, Box<dyn std::error::Error + Sync + Send>> {
+        escape_domain_to_sql(ty, w, self.iter())
+    }
+}
+
 impl<
         T: std::fmt::Debug + ToSql + Send + Sync,
         I: Iterator<Item = T> + ExactSizeIterator,
@@ -85,6 +138,8 @@ impl<
     }
 }
 
+/// Array parameter produced by an iterator, e.g. `IterSql(|| map.values())`. The closure is
+/// called each time the parameter is written, so nothing is collected beforehand.
 pub struct IterSql<T: ToSql, I: Iterator<Item = T> + ExactSizeIterator, F: Fn() -> I + Sync>(pub F);
 
 impl<T: ToSql, I: Iterator<Item = T> + ExactSizeIterator, F: Fn() -> I + Sync> std::fmt::Debug
@@ -0,0 +1,27 @@
+use std::{error::Error, fmt::Display};
+
+/// Parameter failing a check of the `--# validate` attribute of its query, reported before
+/// running it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ValidationError {
+    /// Name of the query module
+    pub module: &'static str,
+    /// Name of the query
+    pub query: &'static str,
+    /// Name of the parameter
+    pub param: &'static str,
+    /// Failed check, as written in the attribute, e.g. `len(email) <= 255`
+    pub check: &'static str,
+}
+
+impl Display for ValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "parameter `{}` of query `{}::{}` fails `{}`",
+            self.param, self.module, self.query, self.check
+        )
+    }
+}
+
+impl Error for ValidationError {}
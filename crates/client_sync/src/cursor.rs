@@ -0,0 +1,58 @@
+use postgres::{types::ToSql, Error, Portal, Row, Statement, Transaction};
+
+use crate::generic_client::GenericClient;
+
+/// Clients able to fetch rows in batches through a portal. Portals only live in transactions.
+pub trait CursorClient: GenericClient {
+    fn bind(
+        &mut self,
+        statement: &Statement,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<Portal, Error>;
+    fn query_portal(&mut self, portal: &Portal, max_rows: i32) -> Result<Vec<Row>, Error>;
+}
+
+impl CursorClient for Transaction<'_> {
+    fn bind(
+        &mut self,
+        statement: &Statement,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<Portal, Error> {
+        Transaction::bind(self, statement, params)
+    }
+
+    fn query_portal(&mut self, portal: &Portal, max_rows: i32) -> Result<Vec<Row>, Error> {
+        Transaction::query_portal(self, portal, max_rows)
+    }
+}
+
+/// Iterates over the rows of a statement, fetching `batch_size` rows at a time
+pub fn cursor<'a, C: CursorClient>(
+    client: &'a mut C,
+    statement: &Statement,
+    params: &[&(dyn ToSql + Sync)],
+    batch_size: i32,
+) -> Result<impl Iterator<Item = Result<Row, Error>> + 'a, Error> {
+    let portal = client.bind(statement, params)?;
+    let mut batch = Vec::new().into_iter();
+    let mut done = false;
+    Ok(std::iter::from_fn(move || loop {
+        if let Some(row) = batch.next() {
+            return Some(Ok(row));
+        }
+        if done {
+            return None;
+        }
+        match client.query_portal(&portal, batch_size) {
+            Ok(rows) => {
+                // A short batch means the portal is exhausted
+                done = rows.len() < batch_size as usize;
+                batch = rows.into_iter();
+            }
+            Err(err) => {
+                done = true;
+                return Some(Err(err));
+            }
+        }
+    }))
+}
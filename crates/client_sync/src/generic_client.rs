@@ -0,0 +1,26 @@
+use postgres::{Client, Transaction};
+
+/// Abstraction over `postgres` clients and transactions, identifying their connection so that
+/// the statements cached by the generated queries are only reused on the connection they were
+/// prepared on.
+pub trait GenericClient: postgres::GenericClient {
+    /// Identifies the connection of this client, or `None` to prepare statements on each use.
+    fn cache_key(&self) -> Option<usize>;
+}
+
+impl GenericClient for Client {
+    /// The address of the client, which a later connection can reuse: statements its connection
+    /// doesn't know are then prepared again.
+    fn cache_key(&self) -> Option<usize> {
+        Some((self as *const Self).cast::<()>() as usize)
+    }
+}
+
+impl GenericClient for Transaction<'_> {
+    /// Transactions don't tell the connection they borrow, and their own address is soon reused
+    /// by one on another connection, where a failed statement aborts them, leaving nothing to
+    /// retry.
+    fn cache_key(&self) -> Option<usize> {
+        None
+    }
+}
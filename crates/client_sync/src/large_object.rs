@@ -0,0 +1,88 @@
+use std::io::{self, Read, Seek, SeekFrom, Write};
+
+use cornucopia_client_core::{lo_seek_args, LargeObjectMode};
+use postgres::{types::Oid, Error, GenericClient};
+
+/// Large object opened with `lo_open`, read and written as a stream of bytes.
+/// Its descriptor only lives in the transaction it was opened in.
+pub struct LargeObject<'a, C: GenericClient> {
+    client: &'a mut C,
+    fd: i32,
+}
+
+impl<'a, C: GenericClient> LargeObject<'a, C> {
+    /// Creates an empty large object, returning its oid
+    pub fn create(client: &mut C) -> Result<Oid, Error> {
+        client.query_one("SELECT lo_create(0)", &[])?.try_get(0)
+    }
+
+    /// Deletes the large object `oid`
+    pub fn unlink(client: &mut C, oid: Oid) -> Result<(), Error> {
+        client.execute("SELECT lo_unlink($1)", &[&oid])?;
+        Ok(())
+    }
+
+    /// Opens the large object `oid`, in a transaction
+    pub fn open(client: &'a mut C, oid: Oid, mode: LargeObjectMode) -> Result<Self, Error> {
+        let fd = client
+            .query_one("SELECT lo_open($1, $2)", &[&oid, &mode.flags()])?
+            .try_get(0)?;
+        Ok(Self { client, fd })
+    }
+
+    /// Truncates or extends the large object to `len` bytes
+    pub fn truncate(&mut self, len: i64) -> Result<(), Error> {
+        self.client
+            .execute("SELECT lo_truncate64($1, $2)", &[&self.fd, &len])?;
+        Ok(())
+    }
+
+    /// Closes the descriptor, which would otherwise be closed at the end of the transaction
+    pub fn close(self) -> Result<(), Error> {
+        self.client.execute("SELECT lo_close($1)", &[&self.fd])?;
+        Ok(())
+    }
+}
+
+impl<C: GenericClient> Read for LargeObject<'_, C> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let len = i32::try_from(buf.len()).unwrap_or(i32::MAX);
+        let data: Vec<u8> = self
+            .client
+            .query_one("SELECT loread($1, $2)", &[&self.fd, &len])
+            .and_then(|row| row.try_get(0))
+            .map_err(io::Error::other)?;
+        buf[..data.len()].copy_from_slice(&data);
+        Ok(data.len())
+    }
+}
+
+impl<C: GenericClient> Write for LargeObject<'_, C> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written: i32 = self
+            .client
+            .query_one("SELECT lowrite($1, $2)", &[&self.fd, &buf])
+            .and_then(|row| row.try_get(0))
+            .map_err(io::Error::other)?;
+        Ok(written as usize)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl<C: GenericClient> Seek for LargeObject<'_, C> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let (offset, whence) = lo_seek_args(pos);
+        let pos: i64 = self
+            .client
+            .query_one(
+                "SELECT lo_lseek64($1, $2, $3)",
+                &[&self.fd, &offset, &whence],
+            )
+            .and_then(|row| row.try_get(0))
+            .map_err(io::Error::other)?;
+        Ok(pos as u64)
+    }
+}
@@ -1,31 +1,141 @@
-pub use cornucopia_client_core::{slice_iter, Domain, DomainArray};
+pub use crate::cursor::cursor;
+pub use cornucopia_client_core::{
+    audit, check_budget, observe, slice_iter, Domain, DomainArray, Multirange,
+};
+#[cfg(feature = "tracing")]
+pub use {crate::trace::traced, tracing};
 
-use postgres::Statement;
+use std::{borrow::Cow, sync::Arc};
 
-/// Cached statement
+use postgres::{error::SqlState, Error, Statement};
+
+use crate::generic_client::GenericClient;
+
+/// Parameters of a statement
+type Params<'p> = [&'p (dyn postgres::types::ToSql + Sync)];
+
+/// Number of clients a statement stays prepared for
+const CACHE_SIZE: usize = 8;
+
+/// Statements shared by the clones of a generated `Queries` facade
+pub type StmtCache = cornucopia_client_core::StmtCache<Statement>;
+
+/// Statement prepared lazily, and cached per client
 pub struct Stmt {
-    query: &'static str,
-    cached: Option<Statement>,
+    query: Cow<'static, str>,
+    /// Prepared statements keyed by client, most recently used first
+    cached: Vec<(Option<usize>, Statement)>,
+    /// Statements shared with other instances of this statement, e.g. on other threads
+    shared: Option<Arc<StmtCache>>,
 }
 
 impl Stmt {
     #[must_use]
-    pub fn new(query: &'static str) -> Self {
+    pub fn new(query: impl Into<Cow<'static, str>>) -> Self {
         Self {
-            query,
-            cached: None,
+            query: query.into(),
+            cached: Vec::new(),
+            shared: None,
         }
     }
 
-    pub fn prepare<'a, C: postgres::GenericClient>(
+    /// Shares the statements prepared on each connection through `cache`
+    #[must_use]
+    pub fn with_cache(mut self, cache: &Arc<StmtCache>) -> Self {
+        self.shared = Some(cache.clone());
+        self
+    }
+
+    /// Query of the statement
+    #[must_use]
+    pub fn query(&self) -> &str {
+        &self.query
+    }
+
+    pub fn prepare<'a, C: GenericClient>(
         &'a mut self,
         client: &mut C,
-    ) -> Result<&'a Statement, postgres::Error> {
-        if self.cached.is_none() {
-            let stmt = client.prepare(self.query)?;
-            self.cached = Some(stmt);
+    ) -> Result<&'a Statement, Error> {
+        let key = client.cache_key();
+        match self
+            .cached
+            .iter()
+            .position(|(k, _)| key.is_some() && *k == key)
+        {
+            Some(idx) => self.cached[..=idx].rotate_right(1),
+            None => {
+                let shared = self.shared.as_ref().zip(key);
+                let stmt = match shared.and_then(|(cache, key)| cache.get(&self.query, key)) {
+                    Some(stmt) => stmt,
+                    None => {
+                        let stmt = client.prepare(&self.query)?;
+                        if let Some((cache, key)) = shared {
+                            cache.insert(self.query.clone(), key, stmt.clone());
+                        }
+                        stmt
+                    }
+                };
+                if key.is_none() {
+                    // Never reused, so only keep the last one
+                    self.cached.retain(|(k, _)| k.is_some());
+                }
+                self.cached.insert(0, (key, stmt));
+                self.cached.truncate(CACHE_SIZE);
+            }
+        }
+        Ok(&self.cached[0].1)
+    }
+
+    /// Runs `run` with the statement prepared on `client`. A later connection can live at the
+    /// address a statement was cached for, so when the connection doesn't know the statement,
+    /// it is prepared again and `run` retried once.
+    pub fn run<C: GenericClient, T>(
+        &mut self,
+        client: &mut C,
+        mut run: impl FnMut(&mut C, &Statement) -> Result<T, Error>,
+    ) -> Result<T, Error> {
+        let stmt = self.prepare(client)?;
+        match run(client, stmt) {
+            Err(err) if self.forget_unknown(client, &err) => {
+                let stmt = self.prepare(client)?;
+                run(client, stmt)
+            }
+            res => res,
+        }
+    }
+
+    /// Like [`Stmt::run`], for `run` returning values borrowing `client`, e.g. row iterators
+    pub fn run_borrowed<'c, C: GenericClient, T: 'c>(
+        &mut self,
+        client: &'c mut C,
+        params: &Params,
+        run: fn(&'c mut C, &Statement, &Params) -> Result<T, Error>,
+    ) -> Result<T, Error> {
+        let client: *mut C = client;
+        // SAFETY: `client` is borrowed again only once the first borrow is over. On failure
+        // `run` returns an error that doesn't borrow it, and being a function pointer, `run`
+        // can't have kept it anywhere else.
+        let stmt = self.prepare(unsafe { &mut *client })?;
+        match run(unsafe { &mut *client }, stmt, params) {
+            Err(err) if self.forget_unknown(unsafe { &*client }, &err) => {
+                let stmt = self.prepare(unsafe { &mut *client })?;
+                run(unsafe { &mut *client }, stmt, params)
+            }
+            res => res,
+        }
+    }
+
+    /// Forgets the statement cached for `client` if `err` reports that its connection doesn't
+    /// know it, returning whether it did
+    fn forget_unknown<C: GenericClient>(&mut self, client: &C, err: &Error) -> bool {
+        let key = client.cache_key();
+        if key.is_none() || err.code() != Some(&SqlState::INVALID_SQL_STATEMENT_NAME) {
+            return false;
+        }
+        self.cached.retain(|(k, _)| *k != key);
+        if let (Some(cache), Some(key)) = (&self.shared, key) {
+            cache.remove(&self.query, key);
         }
-        // the statement is always prepared at this point
-        Ok(unsafe { self.cached.as_ref().unwrap_unchecked() })
+        true
     }
 }
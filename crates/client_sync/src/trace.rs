@@ -0,0 +1,19 @@
+use std::time::Instant;
+
+use tracing::Span;
+
+/// Runs `f` inside `span`, recording its duration and the number of rows given by `rows`
+pub fn traced<T, E>(
+    span: &Span,
+    rows: impl FnOnce(&T) -> Option<u64>,
+    f: impl FnOnce() -> Result<T, E>,
+) -> Result<T, E> {
+    let _entered = span.enter();
+    let start = Instant::now();
+    let res = f();
+    span.record("duration_ms", start.elapsed().as_secs_f64() * 1000.);
+    if let Some(rows) = res.as_ref().ok().and_then(rows) {
+        span.record("rows", rows);
+    }
+    res
+}
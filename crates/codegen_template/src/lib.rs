@@ -137,6 +137,13 @@ fn gen_disp(s: &mut String, out: &str, ident: &str) {
     s.push_str(")).unwrap();\n");
 }
 
+/// Generate code iterating over a copy of `ident`, leaving it usable afterward
+fn gen_into_iter(s: &mut String, ident: &str) {
+    s.push_str("::std::iter::IntoIterator::into_iter(");
+    s.push_str(ident);
+    s.push_str(".clone())");
+}
+
 /// Generate code to write interpolation patterns in `scan` into `out`
 fn gen_recursive(scan: &mut Scanner, s: &mut String, out: &str) {
     loop {
@@ -160,12 +167,11 @@ fn gen_recursive(scan: &mut Scanner, s: &mut String, out: &str) {
                     let idents = ident_in_iterator(&mut scan);
                     let mut iter = idents.iter();
                     s.push_str("{\nlet iter = ");
-                    s.push_str(iter.next().unwrap());
-                    s.push_str(".clone()");
+                    gen_into_iter(s, iter.next().unwrap());
                     for item in iter {
                         s.push_str(".zip(");
-                        s.push_str(item);
-                        s.push_str(".clone())");
+                        gen_into_iter(s, item);
+                        s.push(')');
                     }
                     s.push_str(";\n");
                     s.push_str("for ");
@@ -207,8 +213,8 @@ fn gen_recursive(scan: &mut Scanner, s: &mut String, out: &str) {
 ///
 /// Repetition is done using `$(...)`. This iterates through the elements of any variable
 /// interpolated within the repetition and inserts a copy of the repetition body
-/// for each one. The variables in an interpolation must implement the [`Iterator`] and the
-/// [`Clone`] traits.
+/// for each one. The variables in an interpolation must implement the [`IntoIterator`] and the
+/// [`Clone`] traits, so iterators as well as references to collections can be used.
 ///
 /// - `$($var)` — simple repetition
 /// - `$( struct ${var}; )` — the repetition can contain other tokens
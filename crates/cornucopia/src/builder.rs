@@ -0,0 +1,597 @@
+use std::{
+    fmt::{self, Display, Formatter},
+    str::FromStr,
+};
+
+use proc_macro2::{Ident, Span, TokenStream};
+use quote::{quote, ToTokens};
+
+/// Prints `tokens` as an item laid out by `prettyplease`, so that doc attributes read as
+/// comments again, or as is when they do not parse as items on their own
+fn unparse(tokens: TokenStream, f: &mut Formatter<'_>) -> fmt::Result {
+    match syn::parse2::<syn::File>(tokens.clone()) {
+        Ok(file) => f.write_str(&prettyplease::unparse(&file)),
+        Err(_) => writeln!(f, "{tokens}"),
+    }
+}
+
+/// Rust code given to the builders, either as tokens or as source to lex
+pub(crate) trait IntoTokens {
+    fn into_tokens(self) -> TokenStream;
+}
+
+impl IntoTokens for TokenStream {
+    fn into_tokens(self) -> TokenStream {
+        self
+    }
+}
+
+impl IntoTokens for &str {
+    fn into_tokens(self) -> TokenStream {
+        TokenStream::from_str(self)
+            .unwrap_or_else(|err| panic!("generated invalid tokens `{self}`: {err}"))
+    }
+}
+
+impl IntoTokens for String {
+    fn into_tokens(self) -> TokenStream {
+        self.as_str().into_tokens()
+    }
+}
+
+impl IntoTokens for &String {
+    fn into_tokens(self) -> TokenStream {
+        self.as_str().into_tokens()
+    }
+}
+
+impl IntoTokens for FnBuilder {
+    fn into_tokens(self) -> TokenStream {
+        self.into_token_stream()
+    }
+}
+
+/// Identifier named `name`, e.g. of a function or struct, which can be raw like `r#type`
+fn ident(name: impl Display) -> Ident {
+    let name = name.to_string();
+    match name.strip_prefix("r#") {
+        Some(raw) => Ident::new_raw(raw, Span::call_site()),
+        None => Ident::new(&name, Span::call_site()),
+    }
+}
+
+/// Outer doc attributes of `lines`, printed as `///` comments once formatted
+fn docs(lines: &[String]) -> TokenStream {
+    let lines = lines.iter().map(|it| format!(" {it}"));
+    quote!(#(#[doc = #lines])*)
+}
+
+/// `<...>` holding `generics`, or nothing when there are none
+fn generics(generics: &[TokenStream]) -> TokenStream {
+    if generics.is_empty() {
+        TokenStream::new()
+    } else {
+        quote!(<#(#generics),*>)
+    }
+}
+
+/// Field of a struct built with [`StructBuilder`]
+#[derive(Debug, Clone)]
+struct Field {
+    attributes: Vec<TokenStream>,
+    vis: TokenStream,
+    name: Option<Ident>,
+    ty: TokenStream,
+}
+
+/// Builds the definition of a Rust struct with named fields, e.g. to add a generated item
+/// whose derives, attributes and generics depend on the code generation settings.
+#[derive(Debug, Clone)]
+pub(crate) struct StructBuilder {
+    name: Ident,
+    docs: Vec<String>,
+    derives: Vec<TokenStream>,
+    attributes: Vec<TokenStream>,
+    generics: Vec<TokenStream>,
+    fields: Vec<Field>,
+}
+
+impl StructBuilder {
+    /// Starts a public struct named `name`
+    pub(crate) fn new(name: impl Display) -> Self {
+        Self {
+            name: ident(name),
+            docs: Vec::new(),
+            derives: Vec::new(),
+            attributes: Vec::new(),
+            generics: Vec::new(),
+            fields: Vec::new(),
+        }
+    }
+
+    /// Adds a line of documentation
+    #[must_use]
+    pub(crate) fn doc(mut self, line: impl Display) -> Self {
+        self.docs.push(line.to_string());
+        self
+    }
+
+    /// Derives the trait at `path`
+    #[must_use]
+    pub(crate) fn derive(self, path: impl IntoTokens) -> Self {
+        self.derive_if(true, path)
+    }
+
+    /// Derives the trait at `path` if `cond` holds
+    #[must_use]
+    pub(crate) fn derive_if(self, cond: bool, path: impl IntoTokens) -> Self {
+        self.derives_if(cond, [path])
+    }
+
+    /// Derives all the traits at `paths`
+    #[must_use]
+    pub(crate) fn derives<I: IntoIterator<Item = D>, D: IntoTokens>(self, paths: I) -> Self {
+        self.derives_if(true, paths)
+    }
+
+    /// Derives all the traits at `paths` if `cond` holds
+    #[must_use]
+    pub(crate) fn derives_if<I: IntoIterator<Item = D>, D: IntoTokens>(
+        mut self,
+        cond: bool,
+        paths: I,
+    ) -> Self {
+        if cond {
+            self.derives
+                .extend(paths.into_iter().map(IntoTokens::into_tokens));
+        }
+        self
+    }
+
+    /// Adds an outer attribute, written without its `#[...]`
+    #[must_use]
+    pub(crate) fn attribute(mut self, attribute: impl IntoTokens) -> Self {
+        self.attributes.push(attribute.into_tokens());
+        self
+    }
+
+    /// Adds a generic parameter, e.g. `'a` or `T: Trait`
+    #[must_use]
+    pub(crate) fn generic(mut self, param: impl IntoTokens) -> Self {
+        self.generics.push(param.into_tokens());
+        self
+    }
+
+    /// Adds all the generic parameters of `params`
+    #[must_use]
+    pub(crate) fn generics<I: IntoIterator<Item = D>, D: IntoTokens>(mut self, params: I) -> Self {
+        self.generics
+            .extend(params.into_iter().map(IntoTokens::into_tokens));
+        self
+    }
+
+    /// Adds a public field
+    #[must_use]
+    pub(crate) fn field(self, name: impl Display, ty: impl IntoTokens) -> Self {
+        self.restricted_field("pub", name, ty)
+    }
+
+    /// Adds a public field with an outer attribute, written without its `#[...]`
+    #[must_use]
+    pub(crate) fn attributed_field(
+        self,
+        attribute: impl IntoTokens,
+        name: impl Display,
+        ty: impl IntoTokens,
+    ) -> Self {
+        self.push_field(vec![attribute.into_tokens()], "pub", Some(ident(name)), ty)
+    }
+
+    /// Adds a private field
+    #[must_use]
+    pub(crate) fn private_field(self, name: impl Display, ty: impl IntoTokens) -> Self {
+        self.restricted_field("", name, ty)
+    }
+
+    /// Adds a field with the visibility `vis`, e.g. `pub(crate)`
+    #[must_use]
+    pub(crate) fn restricted_field(
+        self,
+        vis: impl IntoTokens,
+        name: impl Display,
+        ty: impl IntoTokens,
+    ) -> Self {
+        self.push_field(Vec::new(), vis, Some(ident(name)), ty)
+    }
+
+    /// Adds an unnamed field with the visibility `vis`, making it a tuple struct. Its fields
+    /// must then all be unnamed.
+    #[must_use]
+    pub(crate) fn tuple_field(self, vis: impl IntoTokens, ty: impl IntoTokens) -> Self {
+        self.push_field(Vec::new(), vis, None, ty)
+    }
+
+    fn push_field(
+        mut self,
+        attributes: Vec<TokenStream>,
+        vis: impl IntoTokens,
+        name: Option<Ident>,
+        ty: impl IntoTokens,
+    ) -> Self {
+        self.fields.push(Field {
+            attributes,
+            vis: vis.into_tokens(),
+            name,
+            ty: ty.into_tokens(),
+        });
+        self
+    }
+}
+
+impl ToTokens for StructBuilder {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let docs = docs(&self.docs);
+        let derives = &self.derives;
+        let derive = (!derives.is_empty()).then(|| quote!(#[derive(#(#derives),*)]));
+        let attributes = &self.attributes;
+        let name = &self.name;
+        let generics = generics(&self.generics);
+        let fields = self.fields.iter().map(|field| {
+            let Field {
+                attributes,
+                vis,
+                name,
+                ty,
+            } = field;
+            match name {
+                Some(name) => quote!(#(#[#attributes])* #vis #name: #ty),
+                None => quote!(#(#[#attributes])* #vis #ty),
+            }
+        });
+        let body = if self.fields.first().is_some_and(|it| it.name.is_none()) {
+            quote!((#(#fields),*);)
+        } else {
+            quote!({ #(#fields,)* })
+        };
+        tokens.extend(quote! {
+            #docs
+            #derive
+            #(#[#attributes])*
+            pub struct #name #generics #body
+        });
+    }
+}
+
+impl Display for StructBuilder {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        unparse(self.to_token_stream(), f)
+    }
+}
+
+/// Builds a Rust function, or a method when given a receiver, e.g. to add a generated function
+/// whose signature depends on the parameters of a query.
+#[derive(Debug, Clone)]
+pub(crate) struct FnBuilder {
+    name: Ident,
+    docs: Vec<String>,
+    attributes: Vec<TokenStream>,
+    vis: TokenStream,
+    is_async: bool,
+    generics: Vec<TokenStream>,
+    receiver: Option<TokenStream>,
+    params: Vec<(TokenStream, TokenStream)>,
+    ret: Option<TokenStream>,
+    body: TokenStream,
+}
+
+impl FnBuilder {
+    /// Starts a public function named `name`
+    pub(crate) fn new(name: impl Display) -> Self {
+        Self {
+            name: ident(name),
+            docs: Vec::new(),
+            attributes: Vec::new(),
+            vis: quote!(pub),
+            is_async: false,
+            generics: Vec::new(),
+            receiver: None,
+            params: Vec::new(),
+            ret: None,
+            body: TokenStream::new(),
+        }
+    }
+
+    /// Adds a line of documentation
+    #[must_use]
+    pub(crate) fn doc(mut self, line: impl Display) -> Self {
+        self.docs.push(line.to_string());
+        self
+    }
+
+    /// Adds an outer attribute, written without its `#[...]`
+    #[must_use]
+    pub(crate) fn attribute(self, attribute: impl IntoTokens) -> Self {
+        self.attributes([attribute])
+    }
+
+    /// Adds all the outer attributes of `attributes`, written without their `#[...]`
+    #[must_use]
+    pub(crate) fn attributes<I: IntoIterator<Item = D>, D: IntoTokens>(
+        mut self,
+        attributes: I,
+    ) -> Self {
+        self.attributes
+            .extend(attributes.into_iter().map(IntoTokens::into_tokens));
+        self
+    }
+
+    /// Gives the function the visibility `vis`, e.g. `pub(crate)`, or none when empty
+    #[must_use]
+    pub(crate) fn vis(mut self, vis: impl IntoTokens) -> Self {
+        self.vis = vis.into_tokens();
+        self
+    }
+
+    /// Makes the function `async` if `cond` holds
+    #[must_use]
+    pub(crate) fn async_if(mut self, cond: bool) -> Self {
+        self.is_async = cond;
+        self
+    }
+
+    /// Adds a generic parameter, e.g. `'a` or `T: Trait`
+    #[must_use]
+    pub(crate) fn generic(self, param: impl IntoTokens) -> Self {
+        self.generics([param])
+    }
+
+    /// Adds all the generic parameters of `params`
+    #[must_use]
+    pub(crate) fn generics<I: IntoIterator<Item = D>, D: IntoTokens>(mut self, params: I) -> Self {
+        self.generics
+            .extend(params.into_iter().map(IntoTokens::into_tokens));
+        self
+    }
+
+    /// Makes the function a method taking `receiver`, e.g. `&mut self`
+    #[must_use]
+    pub(crate) fn receiver(mut self, receiver: impl IntoTokens) -> Self {
+        self.receiver = Some(receiver.into_tokens());
+        self
+    }
+
+    /// Adds a parameter
+    #[must_use]
+    pub(crate) fn param(self, name: impl Display, ty: impl IntoTokens) -> Self {
+        self.params([(name, ty)])
+    }
+
+    /// Adds all the parameters of `params`, as patterns, e.g. `mut name`, and types
+    #[must_use]
+    pub(crate) fn params<I: IntoIterator<Item = (N, T)>, N: Display, T: IntoTokens>(
+        mut self,
+        params: I,
+    ) -> Self {
+        self.params.extend(
+            params
+                .into_iter()
+                .map(|(name, ty)| (name.to_string().into_tokens(), ty.into_tokens())),
+        );
+        self
+    }
+
+    /// Sets the return type
+    #[must_use]
+    pub(crate) fn returns(mut self, ty: impl IntoTokens) -> Self {
+        self.ret = Some(ty.into_tokens());
+        self
+    }
+
+    /// Sets the code of the body, without its braces
+    #[must_use]
+    pub(crate) fn body(mut self, code: impl IntoTokens) -> Self {
+        self.body = code.into_tokens();
+        self
+    }
+}
+
+impl ToTokens for FnBuilder {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let docs = docs(&self.docs);
+        let attributes = &self.attributes;
+        let vis = &self.vis;
+        let asyncness = self.is_async.then(|| quote!(async));
+        let name = &self.name;
+        let generics = generics(&self.generics);
+        let params = self
+            .receiver
+            .iter()
+            .cloned()
+            .chain(self.params.iter().map(|(name, ty)| quote!(#name: #ty)));
+        let ret = self.ret.as_ref().map(|ret| quote!(-> #ret));
+        let body = &self.body;
+        tokens.extend(quote! {
+            #docs
+            #(#[#attributes])*
+            #vis #asyncness fn #name #generics(#(#params),*) #ret {
+                #body
+            }
+        });
+    }
+}
+
+impl Display for FnBuilder {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        unparse(self.to_token_stream(), f)
+    }
+}
+
+/// Builds an `impl` block, inherent or of a trait, e.g. to add the methods of a generated item.
+#[derive(Debug, Clone)]
+pub(crate) struct ImplBuilder {
+    target: TokenStream,
+    trait_path: Option<TokenStream>,
+    generics: Vec<TokenStream>,
+    bounds: Vec<TokenStream>,
+    items: Vec<TokenStream>,
+}
+
+impl ImplBuilder {
+    /// Starts an inherent `impl` block of `target`
+    pub(crate) fn new(target: impl IntoTokens) -> Self {
+        Self {
+            target: target.into_tokens(),
+            trait_path: None,
+            generics: Vec::new(),
+            bounds: Vec::new(),
+            items: Vec::new(),
+        }
+    }
+
+    /// Implements the trait at `path` instead
+    #[must_use]
+    pub(crate) fn of_trait(mut self, path: impl IntoTokens) -> Self {
+        self.trait_path = Some(path.into_tokens());
+        self
+    }
+
+    /// Adds all the generic parameters of `params`
+    #[must_use]
+    pub(crate) fn generics<I: IntoIterator<Item = D>, D: IntoTokens>(mut self, params: I) -> Self {
+        self.generics
+            .extend(params.into_iter().map(IntoTokens::into_tokens));
+        self
+    }
+
+    /// Adds a predicate to the `where` clause, e.g. `T: Trait`
+    #[must_use]
+    pub(crate) fn bound(mut self, predicate: impl IntoTokens) -> Self {
+        self.bounds.push(predicate.into_tokens());
+        self
+    }
+
+    /// Adds an item, e.g. a method built with [`FnBuilder`] or an associated constant
+    #[must_use]
+    pub(crate) fn item(self, item: impl IntoTokens) -> Self {
+        self.items([item])
+    }
+
+    /// Adds all the items of `items`
+    #[must_use]
+    pub(crate) fn items<I: IntoIterator<Item = D>, D: IntoTokens>(mut self, items: I) -> Self {
+        self.items
+            .extend(items.into_iter().map(IntoTokens::into_tokens));
+        self
+    }
+}
+
+impl ToTokens for ImplBuilder {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let generics = generics(&self.generics);
+        let target = &self.target;
+        let target = match &self.trait_path {
+            Some(path) => quote!(#path for #target),
+            None => target.clone(),
+        };
+        let bounds = &self.bounds;
+        let bounds = (!bounds.is_empty()).then(|| quote!(where #(#bounds),*));
+        let items = &self.items;
+        tokens.extend(quote! {
+            impl #generics #target #bounds {
+                #(#items)*
+            }
+        });
+    }
+}
+
+impl Display for ImplBuilder {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        unparse(self.to_token_stream(), f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use quote::quote;
+
+    use super::{FnBuilder, ImplBuilder, StructBuilder};
+
+    #[test]
+    fn structs() {
+        let item = StructBuilder::new("Author")
+            .doc("Author of books")
+            .derives(["Debug", "Clone"])
+            .derive_if(false, "Copy")
+            .derive_if(true, "serde::Serialize")
+            .generic("'a")
+            .field("name", "&'a str")
+            .private_field("id", "i32");
+        assert_eq!(
+            item.to_string(),
+            "/// Author of books
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct Author<'a> {
+    pub name: &'a str,
+    id: i32,
+}
+"
+        );
+        let item =
+            StructBuilder::new("AuthorsStmt").tuple_field("", "cornucopia_async::private::Stmt");
+        assert_eq!(
+            item.to_string(),
+            "pub struct AuthorsStmt(cornucopia_async::private::Stmt);\n"
+        );
+    }
+
+    #[test]
+    fn fns() {
+        let item = FnBuilder::new("count")
+            .doc("Number of authors")
+            .async_if(true)
+            .generic("C: GenericClient")
+            .receiver("&mut self")
+            .param("client", "&C")
+            .returns(quote!(Result<i64, Error>))
+            .body("self.0.count(client).await");
+        assert_eq!(
+            item.to_string(),
+            "/// Number of authors
+pub async fn count<C: GenericClient>(&mut self, client: &C) -> Result<i64, Error> {
+    self.0.count(client).await
+}
+"
+        );
+        let item = FnBuilder::new("new").vis("").returns("Self").body("Self");
+        assert_eq!(item.to_string(), "fn new() -> Self {\n    Self\n}\n");
+    }
+
+    #[test]
+    fn impls() {
+        let item = ImplBuilder::new("Authors<'_, C>")
+            .of_trait("Iterator")
+            .generics(["C"])
+            .bound("C: GenericClient")
+            .item("type Item = i32;")
+            .item(
+                FnBuilder::new("next")
+                    .vis("")
+                    .receiver("&mut self")
+                    .returns("Option<i32>")
+                    .body("None"),
+            );
+        assert_eq!(
+            item.to_string(),
+            "impl<C> Iterator for Authors<'_, C>
+where
+    C: GenericClient,
+{
+    type Item = i32;
+    fn next(&mut self) -> Option<i32> {
+        None
+    }
+}
+"
+        );
+    }
+}
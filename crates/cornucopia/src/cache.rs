@@ -0,0 +1,240 @@
+use std::{
+    collections::{hash_map::DefaultHasher, BTreeMap},
+    hash::{Hash, Hasher},
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+
+use postgres::Client;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    dump::USER_SCHEMAS,
+    log,
+    parser::Module,
+    prepare_queries::PreparedModule,
+    sql_analysis,
+    type_registrar::{CornucopiaType, TypeRegistrar},
+    CodegenSettings,
+};
+
+/// Preparations of the query modules of the previous generation, reused for the modules whose
+/// content, settings and schema didn't change since
+#[derive(Debug)]
+pub(crate) struct ModuleCache {
+    path: PathBuf,
+    previous: BTreeMap<String, CachedModule>,
+    current: BTreeMap<String, CachedModule>,
+}
+
+/// Preparation of a query module, along with what it depends on
+#[derive(Debug, Serialize, Deserialize)]
+struct CachedModule {
+    /// Hash of the parsed module, of the settings of its preparation and of cornucopia's version
+    key: String,
+    /// Names used by its queries, which could refer to relations or functions
+    names: Vec<String>,
+    /// User types it uses, as `schema.name` paths
+    types: Vec<String>,
+    /// Hash of the definitions of its names and types in the database catalog
+    schema: String,
+    /// Types it registered or looked up, in the order of their registration
+    registered: Vec<((String, String), Rc<CornucopiaType>)>,
+    newtypes: Vec<(String, Rc<CornucopiaType>)>,
+    prepared: PreparedModule,
+}
+
+impl ModuleCache {
+    /// Reads the cache at `path`. A missing or unreadable cache, e.g. written by another version
+    /// of cornucopia, is empty.
+    pub(crate) fn load(path: &Path) -> Self {
+        let previous = std::fs::read_to_string(path)
+            .ok()
+            .and_then(|it| serde_json::from_str(&it).ok())
+            .unwrap_or_default();
+        Self {
+            path: path.to_owned(),
+            previous,
+            current: BTreeMap::new(),
+        }
+    }
+
+    /// Key of `module`, changing with its content or the settings of its preparation
+    pub(crate) fn key(module: &Module, settings: &CodegenSettings) -> String {
+        let mut hasher = DefaultHasher::new();
+        env!("CARGO_PKG_VERSION").hash(&mut hasher);
+        format!("{module:?}").hash(&mut hasher);
+        format!(
+            "{:?}",
+            (
+                &settings.extension_types,
+                &settings.codecs,
+                settings.money,
+                settings.interval,
+                settings.bit,
+                &settings.newtypes,
+                &settings.inline_columns,
+                settings.upstream_compat,
+                settings.validate_hints,
+            )
+        )
+        .hash(&mut hasher);
+        format!("{:016x}", hasher.finish())
+    }
+
+    /// Preparation of the module named `name` if its `key` and schema didn't change, registering
+    /// the types it uses as its preparation would have
+    pub(crate) fn reuse(
+        &mut self,
+        client: &mut Client,
+        name: &str,
+        key: &str,
+        registrar: &mut TypeRegistrar,
+    ) -> Result<Option<PreparedModule>, postgres::Error> {
+        let Some(cached) = self.previous.remove(name) else {
+            return Ok(None);
+        };
+        if cached.key != key || schema_hash(client, &cached.names, &cached.types)? != cached.schema
+        {
+            return Ok(None);
+        }
+        log::info!("Reusing the preparation of the module `{name}`");
+        for (key, ty) in &cached.registered {
+            registrar
+                .types
+                .entry(key.clone())
+                .or_insert_with(|| ty.clone());
+        }
+        for (name, ty) in &cached.newtypes {
+            registrar
+                .newtypes
+                .entry(name.clone())
+                .or_insert_with(|| ty.clone());
+        }
+        let prepared = cached.prepared.clone();
+        self.current.insert(name.to_string(), cached);
+        Ok(Some(prepared))
+    }
+
+    /// Caches `prepared` under `key`, along with the types it used since `registrar` was
+    /// last cleared
+    pub(crate) fn store(
+        &mut self,
+        client: &mut Client,
+        key: String,
+        prepared: &PreparedModule,
+        registrar: &TypeRegistrar,
+    ) -> Result<(), postgres::Error> {
+        let mut names = Vec::new();
+        for query in prepared.queries.values() {
+            let sqls = std::iter::once(&query.sql).chain(&query.sql_variants);
+            for name in sqls.flat_map(|it| sql_analysis::names(it)) {
+                if !names.contains(&name) {
+                    names.push(name);
+                }
+            }
+        }
+        let mut registered = Vec::new();
+        let mut types = Vec::new();
+        for key in &registrar.used {
+            if registered.iter().any(|(it, _)| it == key) {
+                continue;
+            }
+            if key.0 != "pg_catalog" {
+                types.push(format!(
+                    "{}.{}",
+                    sql_analysis::quote_ident(&key.0),
+                    sql_analysis::quote_ident(&key.1)
+                ));
+            }
+            registered.push((key.clone(), registrar.types[key].clone()));
+        }
+        let mut newtypes = Vec::new();
+        for name in &registrar.used_newtypes {
+            if newtypes.iter().all(|(it, _)| it != name) {
+                newtypes.push((name.clone(), registrar.newtypes[name].clone()));
+            }
+        }
+        let schema = schema_hash(client, &names, &types)?;
+        self.current.insert(
+            prepared.info.name.clone(),
+            CachedModule {
+                key,
+                names,
+                types,
+                schema,
+                registered,
+                newtypes,
+                prepared: prepared.clone(),
+            },
+        );
+        Ok(())
+    }
+
+    /// Writes the cache, only holding the modules of this generation. The generation doesn't
+    /// fail if it can't be written, it is only slower next time.
+    pub(crate) fn save(self) {
+        log::debug!("Writing the cache `{}`", self.path.display());
+        let json = serde_json::to_string(&self.current).expect("caches serialize");
+        if let Err(err) = std::fs::write(&self.path, json) {
+            eprintln!(
+                "Couldn't write the cache `{}`, the modules will be prepared again: {err}",
+                self.path.display()
+            );
+        }
+    }
+}
+
+/// Hash of the definitions of the relations and functions called `names` and of the `types`,
+/// those of the system schemas being left out
+fn schema_hash(
+    client: &mut Client,
+    names: &[String],
+    types: &[String],
+) -> Result<String, postgres::Error> {
+    let row = client.query_one(
+        &format!(
+            "SELECT md5(coalesce(string_agg(def, E'\\n' ORDER BY def), '')) FROM (
+                SELECT c.oid::regclass::text || ' (' || coalesce((
+                    SELECT string_agg(a.attname || ' ' || format_type(a.atttypid, a.atttypmod)
+                        || CASE WHEN a.attnotnull THEN ' NOT NULL' ELSE '' END,
+                        ', ' ORDER BY a.attnum)
+                    FROM pg_attribute a
+                    WHERE a.attrelid = c.oid AND a.attnum > 0 AND NOT a.attisdropped
+                ), '') || ') ' || coalesce((
+                    SELECT string_agg(co.conname || ' ' || pg_get_constraintdef(co.oid),
+                        ', ' ORDER BY co.conname)
+                    FROM pg_constraint co WHERE co.conrelid = c.oid
+                ), '') || coalesce(pg_get_viewdef(c.oid), '')
+                FROM unnest($1::text[]) AS it(name)
+                JOIN pg_class c ON c.oid = to_regclass(it.name)
+                JOIN pg_namespace n ON n.oid = c.relnamespace
+                WHERE {USER_SCHEMAS}
+                UNION ALL
+                SELECT p.oid::regprocedure::text || ' ' || pg_get_function_result(p.oid)
+                FROM pg_proc p
+                JOIN pg_namespace n ON n.oid = p.pronamespace
+                WHERE p.proname IN (
+                    SELECT (parse_ident(it.name))[array_length(parse_ident(it.name), 1)]
+                    FROM unnest($1::text[]) AS it(name)
+                ) AND {USER_SCHEMAS}
+                UNION ALL
+                SELECT t.oid::regtype::text || ' ' || concat_ws(' ',
+                    (SELECT string_agg(e.enumlabel, ', ' ORDER BY e.enumsortorder)
+                        FROM pg_enum e WHERE e.enumtypid = t.oid),
+                    (SELECT string_agg(a.attname || ' ' || format_type(a.atttypid, a.atttypmod),
+                        ', ' ORDER BY a.attnum)
+                        FROM pg_attribute a
+                        WHERE a.attrelid = t.typrelid AND a.attnum > 0 AND NOT a.attisdropped),
+                    CASE WHEN t.typtype = 'd' THEN format_type(t.typbasetype, t.typtypmod)
+                        || CASE WHEN t.typnotnull THEN ' NOT NULL' ELSE '' END END,
+                    (SELECT string_agg(pg_get_constraintdef(co.oid), ', ' ORDER BY co.conname)
+                        FROM pg_constraint co WHERE co.contypid = t.oid))
+                FROM unnest($2::text[]) AS it(name)
+                JOIN pg_type t ON t.oid = to_regtype(it.name)
+            ) AS defs(def)"
+        ),
+        &[&names, &types],
+    )?;
+    Ok(row.get(0))
+}
@@ -0,0 +1,81 @@
+use std::{fmt::Write, path::Path};
+
+use self::error::Error;
+
+/// Maximum number of changed lines of each side shown by the summary
+const MAX_SHOWN_LINES: usize = 8;
+
+/// Checks the code at `destination` is exactly `generated_code`, without touching it
+pub(crate) fn check(destination: &Path, generated_code: &str) -> Result<(), Error> {
+    let path = destination.to_string_lossy().into_owned();
+    let current = std::fs::read_to_string(destination).map_err(|err| Error::Io {
+        path: path.clone(),
+        err,
+    })?;
+    match diff_summary(&current, generated_code) {
+        Some(summary) => Err(Error::OutOfDate { path, summary }),
+        None => Ok(()),
+    }
+}
+
+/// Summarizes the lines of `current` between those it shares at its start and end with
+/// `generated`, as the hunk of a unified diff showing the first of them
+fn diff_summary(current: &str, generated: &str) -> Option<String> {
+    if current == generated {
+        return None;
+    }
+    let current: Vec<_> = current.lines().collect();
+    let generated: Vec<_> = generated.lines().collect();
+    let prefix = current
+        .iter()
+        .zip(&generated)
+        .take_while(|(a, b)| a == b)
+        .count();
+    let suffix = current[prefix..]
+        .iter()
+        .rev()
+        .zip(generated[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let removed = &current[prefix..current.len() - suffix];
+    let added = &generated[prefix..generated.len() - suffix];
+    if removed.is_empty() && added.is_empty() {
+        return Some("only their line endings differ".to_string());
+    }
+    let mut summary = format!(
+        "@@ -{},{} +{},{} @@",
+        prefix + 1,
+        removed.len(),
+        prefix + 1,
+        added.len()
+    );
+    for (lines, sign) in [(removed, '-'), (added, '+')] {
+        for line in lines.iter().take(MAX_SHOWN_LINES) {
+            write!(summary, "\n{sign}{line}").unwrap();
+        }
+        if lines.len() > MAX_SHOWN_LINES {
+            write!(
+                summary,
+                "\n{sign}... {} more lines",
+                lines.len() - MAX_SHOWN_LINES
+            )
+            .unwrap();
+        }
+    }
+    Some(summary)
+}
+
+pub(crate) mod error {
+    use miette::Diagnostic;
+    use thiserror::Error as ThisError;
+
+    #[derive(Debug, ThisError, Diagnostic)]
+    pub enum Error {
+        #[error("Could not read the generated code at `{path}`: ({err})")]
+        #[diagnostic(help("generate it by running the same command without `check`"))]
+        Io { path: String, err: std::io::Error },
+        #[error("The generated code at `{path}` is out of date:\n{summary}")]
+        #[diagnostic(help("regenerate it by running the same command without `check`"))]
+        OutOfDate { path: String, summary: String },
+    }
+}
@@ -2,21 +2,61 @@ use std::path::PathBuf;
 
 use clap::{Parser, Subcommand};
 
-use crate::{conn, container, error::Error, generate_live, generate_managed, CodegenSettings};
+use crate::{
+    check::check,
+    config::{expand_env, Config, LintLevel},
+    conn, container,
+    diagnostics::{json_record, json_records, MessageFormat},
+    diff::{diff, signatures},
+    doctor::doctor,
+    dump::{dump_schema, restore_schema, DumpFormat},
+    error::Error,
+    generate_databases, generate_live, generate_managed,
+    init::init,
+    lint::{error::Error as LintError, lint},
+    list::{list, ListFormat},
+    load_schema, log,
+    read_queries::STDIN_PATH,
+    watch::watch,
+    CodegenSettings,
+};
 
 /// Command line interface to interact with Cornucopia SQL.
 #[derive(Parser, Debug)]
 #[clap(version)]
 struct Args {
-    /// Use `podman` instead of `docker`
+    /// Use `podman` instead of `docker`, which is done anyway when `docker` isn't installed
     #[clap(short, long)]
     podman: bool,
-    /// Folder containing the queries
+    /// Folder containing the queries, or `-` to read a single query module from stdin
+    /// and write the generated code to stdout. Given several times, the other folders are
+    /// read along with the first one, like the `extra_queries` of the configuration file
     #[clap(short, long, default_value = "queries/")]
-    queries_path: PathBuf,
+    queries_path: Vec<PathBuf>,
+    /// Don't format the generated code, which is slow on big outputs
+    #[clap(long)]
+    no_fmt: bool,
+    /// File caching the preparation of the query modules, so that the next generations only
+    /// prepare the modules which changed, overriding the `cache` of the configuration file
+    #[clap(long)]
+    cache: Option<PathBuf>,
+    /// Glob of the query files read, relative to their folder, e.g. `prod_*.sql`. Can be given
+    /// several times, along with the `include_queries` of the configuration file
+    #[clap(long)]
+    include: Vec<String>,
+    /// Glob of the query files skipped even when included, e.g. `*_draft.sql`. Can be given
+    /// several times, along with the `exclude_queries` of the configuration file
+    #[clap(long)]
+    exclude: Vec<String>,
     /// Destination folder for generated modules
     #[clap(short, long, default_value = "src/cornucopia.rs")]
     destination: PathBuf,
+    /// Write the generated code to stdout instead of the destination
+    #[clap(long)]
+    stdout: bool,
+    /// Generate the code without writing it anywhere, only reporting the errors
+    #[clap(long, conflicts_with = "stdout")]
+    dry_run: bool,
     #[clap(subcommand)]
     action: Action,
     /// Generate synchronous rust code
@@ -28,58 +68,478 @@ struct Args {
     /// Derive serde's `Serialize` trait for generated types.
     #[clap(long)]
     serialize: bool,
+    /// Generate a `Queries` facade sharing statements prepared once per connection, e.g. to store in a web framework's state.
+    #[clap(long, conflicts_with = "upstream_compat")]
+    facade: bool,
+    /// Generate a `Statements` bundle whose `prepare_all` method prepares every statement up front
+    #[clap(long)]
+    prepare_all: bool,
+    /// Instrument queries with `tracing` spans, configured with `--# trace(level = "..", target = "..")`.
+    /// Requires the `tracing` feature of the client crate
+    #[clap(long, conflicts_with = "upstream_compat")]
+    tracing: bool,
+    /// Generate a `QueryError` enum classifying database errors, e.g. unique or foreign key violations
+    #[clap(long)]
+    db_errors: bool,
+    /// Generate a `fuzz` module decoding the composite types from arbitrary bytes, to call from `cargo fuzz` targets
+    #[clap(long)]
+    fuzz: bool,
+    /// Generate code compatible with the upstream `cornucopia_async` and `cornucopia_sync` crates,
+    /// rejecting the queries and types needing this fork's
+    #[clap(long)]
+    upstream_compat: bool,
+    /// Don't generate the `iter` and `cursor` methods streaming the rows of queries, which
+    /// modules can still opt into with their `iter` setting
+    #[clap(long)]
+    no_iter: bool,
+    /// Configuration file, ignored if missing unless explicitly given [default: cornucopia.toml]
+    #[clap(short, long)]
+    config: Option<PathBuf>,
+    /// Write what is being done on stderr, timed: `-v` for the phases of the generation, `-vv`
+    /// for their steps too, e.g. the files read or the statements prepared
+    #[clap(short, long, action = clap::ArgAction::Count)]
+    verbose: u8,
+    /// Format of the errors, which can also be written on stdout as JSON records for other tools
+    #[clap(long, value_enum, default_value_t = MessageFormat::Human)]
+    message_format: MessageFormat,
 }
 
 #[derive(Debug, Subcommand)]
 enum Action {
     /// Generate your modules against your own db
     Live {
-        /// Postgres url to the database
-        url: String,
+        /// Postgres url to the database, in which `${VAR}` is replaced by an environment variable.
+        /// Read from `DATABASE_URL` or the `PG*` variables if missing, e.g. set in a `.env` file
+        url: Option<String>,
     },
     /// Generate your modules against schema files
     Schema {
         /// SQL files containing the database schema
         schema_files: Vec<PathBuf>,
     },
+    /// Generate the modules of the databases declared in the configuration file, each against
+    /// its own url or schema files
+    Databases,
+    /// List the query modules, their queries, params and rows, without a database
+    List {
+        /// Output format
+        #[clap(long, value_enum, default_value_t = ListFormat::Text)]
+        format: ListFormat,
+    },
+    /// Lint the query files without a database, warning about or denying risky patterns as
+    /// configured in the `[lint]` section of the configuration file: `SELECT *`, unbound
+    /// declared params, unused named types, duplicate query names, queries returning rows
+    /// without a cardinality and output columns shadowing each other
+    Lint,
+    /// Generate your modules, then regenerate them whenever the queries, the schema files or the
+    /// configuration file change, until interrupted
+    Watch {
+        #[clap(subcommand)]
+        against: Against,
+    },
+    /// Generate your modules in memory, failing with a summary of the differences if they aren't
+    /// exactly the destination file, which is left untouched. Give the same options as when
+    /// generating it, e.g. `--no-fmt`
+    Check {
+        #[clap(subcommand)]
+        against: Against,
+    },
+    /// Write the schema of the database on stdout, as introspected by cornucopia: its relations
+    /// with their columns, enums, composite types and domains, with their OIDs and nullability
+    Dump {
+        /// Output format
+        #[clap(long, value_enum, default_value_t = DumpFormat::Json)]
+        format: DumpFormat,
+        #[clap(subcommand)]
+        against: Against,
+    },
+    /// Compare the generated signatures of the queries and types, as prepared against the current
+    /// database, with those they would have against a target database, e.g. before migrating it.
+    /// Fails if some would change
+    Diff {
+        /// Postgres url to the target database, in which `${VAR}` is replaced by an environment
+        /// variable
+        #[clap(
+            long,
+            required_unless_present = "snapshot",
+            conflicts_with = "snapshot"
+        )]
+        to: Option<String>,
+        /// Schema dump written by `cornucopia dump --format json`, restored in Cornucopia's
+        /// container as the target database. Its functions and constraints aren't dumped
+        #[clap(long)]
+        snapshot: Option<PathBuf>,
+        /// Current database
+        #[clap(subcommand)]
+        against: Against,
+    },
+    /// Create a starter layout in the current folder: a schema, a query module and a commented
+    /// configuration file, keeping the existing files
+    Init {
+        /// Also create a build script generating the queries at the destination
+        #[clap(long)]
+        build_rs: bool,
+    },
+    /// Check the setup, reporting how to fix what is wrong: the container engine and its
+    /// Postgres image, the queries, the configuration file and the live database
+    Doctor {
+        /// Postgres url to the database, in which `${VAR}` is replaced by an environment variable.
+        /// Read from `DATABASE_URL` or the `PG*` variables if missing, the check being skipped
+        /// if they aren't set either
+        url: Option<String>,
+    },
+    /// Work with the configuration file
+    Config {
+        #[clap(subcommand)]
+        action: ConfigAction,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum Against {
+    /// Generate your modules against your own db
+    Live {
+        /// Postgres url to the database, in which `${VAR}` is replaced by an environment variable.
+        /// Read from `DATABASE_URL` or the `PG*` variables if missing, e.g. set in a `.env` file
+        url: Option<String>,
+    },
+    /// Generate your modules against schema files, loaded in Cornucopia's container. The
+    /// container is left running when interrupted, and replaced by the next watch
+    Schema {
+        /// SQL files containing the database schema
+        schema_files: Vec<PathBuf>,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum ConfigAction {
+    /// Validate the configuration file, rejecting unknown settings, invalid type names and conflicting options
+    Check,
 }
 
 // Main entrypoint of the CLI. Parses the args and calls the appropriate routines.
 pub fn run() -> Result<(), Error> {
+    let args = Args::parse();
+    let message_format = args.message_format;
+    let result = execute(args);
+    if let Err(err) = &result {
+        report_json(err, message_format);
+    }
+    result
+}
+
+/// Connects to the database at `url`, expanding its `${VAR}` references to environment
+/// variables, or else to the database given by the environment
+fn connect(url: Option<&str>) -> Result<postgres::Client, Error> {
+    log::info!("Connecting to the database");
+    Ok(match url {
+        Some(url) => conn::from_url(&expand_env("url", url)?)?,
+        None => conn::from_env()?,
+    })
+}
+
+/// Whether `action` generates against a live database managed by the user
+fn generates_live(action: &Action, settings: &CodegenSettings) -> bool {
+    match action {
+        Action::Live { .. }
+        | Action::Check {
+            against: Against::Live { .. },
+        }
+        | Action::Watch {
+            against: Against::Live { .. },
+        } => true,
+        Action::Databases => settings.databases.values().any(|it| it.url.is_some()),
+        _ => false,
+    }
+}
+
+/// Writes the JSON records of `err` on stdout, if requested
+fn report_json(err: &Error, message_format: MessageFormat) {
+    if message_format == MessageFormat::Json {
+        print!("{}", json_records(err));
+    }
+}
+
+fn execute(args: Args) -> Result<(), Error> {
     let Args {
         podman,
         queries_path,
+        no_fmt,
+        cache,
+        include,
+        exclude,
         destination,
+        stdout,
+        dry_run,
         action,
         sync,
         r#async,
         serialize,
-    } = Args::parse();
+        facade,
+        prepare_all,
+        tracing,
+        db_errors,
+        fuzz,
+        upstream_compat,
+        no_iter,
+        config,
+        message_format,
+        verbose,
+    } = args;
+    log::set_verbosity(verbose);
+    let mut queries_paths = queries_path.into_iter();
+    let queries_path = queries_paths
+        .next()
+        .expect("the queries path has a default");
+    let extra_queries: Vec<_> = queries_paths.collect();
 
-    let settings = CodegenSettings {
-        gen_async: r#async || !sync,
-        gen_sync: sync,
-        derive_ser: serialize,
+    if let Action::Init { build_rs } = action {
+        let created = init(&queries_path, &destination, build_rs)?;
+        if created.is_empty() {
+            println!("The files of the starter layout already exist");
+        }
+        for path in created {
+            println!("Created `{}`", path.display());
+        }
+        return Ok(());
+    }
+
+    if let Action::Doctor { url } = &action {
+        doctor(podman, &queries_path, config.as_deref(), || {
+            connect(url.as_deref())
+        })?;
+        println!("Everything is ready to generate the queries");
+        return Ok(());
+    }
+
+    if let Action::Config {
+        action: ConfigAction::Check,
+    } = action
+    {
+        let path = config.unwrap_or_else(|| PathBuf::from("cornucopia.toml"));
+        Config::check(&path)?;
+        println!("`{}` is valid", path.display());
+        return Ok(());
+    }
+
+    // Watching reloads the configuration file whenever it changes
+    let load_settings = || -> Result<CodegenSettings, Error> {
+        let config = match &config {
+            Some(path) => Config::from_file(path, true)?,
+            None => Config::from_file("cornucopia.toml".as_ref(), false)?,
+        };
+        let mut settings = CodegenSettings {
+            gen_async: r#async || !sync,
+            gen_sync: sync,
+            derive_ser: serialize,
+            gen_facade: facade,
+            gen_prepare_all: prepare_all,
+            gen_tracing: tracing,
+            gen_db_errors: db_errors,
+            gen_fuzz: fuzz,
+            upstream_compat,
+            gen_iter: !no_iter,
+            format: !no_fmt,
+            ..Default::default()
+        }
+        .with_config(config);
+        settings.cache = cache.clone().or(settings.cache);
+        settings.extra_queries.extend(extra_queries.iter().cloned());
+        settings.include_queries.extend(include.iter().cloned());
+        settings.exclude_queries.extend(exclude.iter().cloned());
+        Ok(settings)
     };
+    let settings = load_settings()?;
+    if settings.verify_nullability && generates_live(&action, &settings) {
+        eprintln!(
+            "warning: `verify_nullability` runs the parameterless selects against the live database, reading up to 10,000 rows each"
+        );
+    }
 
-    match action {
+    if let Action::Check { against } = &action {
+        let generated_code = match against {
+            Against::Live { url } => {
+                let mut client = connect(url.as_deref())?;
+                generate_live(&mut client, &queries_path, None, settings)?
+            }
+            Against::Schema { schema_files } => {
+                let schema_files: Vec<_> = schema_files.iter().collect();
+                match generate_managed(&queries_path, &schema_files, None, podman, settings) {
+                    Ok(generated_code) => generated_code,
+                    Err(e) => {
+                        container::cleanup(podman).ok();
+                        return Err(e);
+                    }
+                }
+            }
+        };
+        check(&destination, &generated_code)?;
+        println!("`{}` is up to date", destination.display());
+        return Ok(());
+    }
+
+    // Queries read from stdin are generated to stdout
+    let to_stdout = (stdout || queries_path.as_os_str() == STDIN_PATH) && !dry_run;
+    let destination = (!to_stdout && !dry_run).then_some(destination);
+
+    let generated_code = match action {
         Action::Live { url } => {
-            let mut client = conn::from_url(&url)?;
-            generate_live(&mut client, &queries_path, Some(&destination), settings)?;
+            let mut client = connect(url.as_deref())?;
+            generate_live(&mut client, queries_path, destination, settings)?
         }
         Action::Schema { schema_files } => {
             // Run the generate command. If the command is unsuccessful, cleanup Cornucopia's container
-            if let Err(e) = generate_managed(
-                queries_path,
-                &schema_files,
-                Some(destination),
-                podman,
-                settings,
-            ) {
+            match generate_managed(queries_path, &schema_files, destination, podman, settings) {
+                Ok(generated_code) => generated_code,
+                Err(e) => {
+                    container::cleanup(podman).ok();
+                    return Err(e);
+                }
+            }
+        }
+        Action::Databases => {
+            // Cornucopia's container is only used by some databases, but cleaning it up is harmless
+            match generate_databases(destination, podman, settings) {
+                Ok(generated_code) => generated_code,
+                Err(e) => {
+                    container::cleanup(podman).ok();
+                    return Err(e);
+                }
+            }
+        }
+        Action::List { format } => {
+            print!("{}", list(&queries_path, &settings, format)?);
+            return Ok(());
+        }
+        Action::Lint => {
+            let (denied, warned): (Vec<_>, Vec<_>) = lint(&queries_path, &settings)?
+                .into_iter()
+                .partition(|it| it.level == LintLevel::Deny);
+            let warnings = warned.len();
+            for finding in warned {
+                if message_format == MessageFormat::Json {
+                    print!("{}", json_record(&finding));
+                }
+                eprintln!("{:?}", miette::Report::new(finding));
+            }
+            if !denied.is_empty() {
+                return Err(LintError::Denied { denied }.into());
+            }
+            match warnings {
+                0 => println!("No lint found in the queries"),
+                1 => println!("1 lint found in the queries"),
+                n => println!("{n} lints found in the queries"),
+            }
+            return Ok(());
+        }
+        Action::Dump { format, against } => {
+            let dump = match against {
+                Against::Live { url } => dump_schema(&mut connect(url.as_deref())?, format)?,
+                Against::Schema { schema_files } => {
+                    let dump = container::setup(podman)
+                        .map_err(Error::from)
+                        .and_then(|()| {
+                            let mut client = conn::cornucopia_conn()?;
+                            load_schema(&mut client, &schema_files)?;
+                            Ok(dump_schema(&mut client, format)?)
+                        });
+                    container::cleanup(podman).ok();
+                    dump?
+                }
+            };
+            print!("{dump}");
+            return Ok(());
+        }
+        Action::Diff {
+            to,
+            snapshot,
+            against,
+        } => {
+            let managed = snapshot.is_some() || matches!(against, Against::Schema { .. });
+            if managed {
+                container::setup(podman)?;
+            }
+            let signatures = || -> Result<_, Error> {
+                let mut client = match &against {
+                    Against::Live { url } => connect(url.as_deref())?,
+                    Against::Schema { schema_files } => {
+                        let mut client = conn::cornucopia_database("cornucopia_diff_current")?;
+                        load_schema(&mut client, schema_files)?;
+                        client
+                    }
+                };
+                let current = signatures(&mut client, &queries_path, &settings)?;
+                let mut client = match &snapshot {
+                    Some(path) => {
+                        let mut client = conn::cornucopia_database("cornucopia_diff_target")?;
+                        restore_schema(&mut client, path)?;
+                        client
+                    }
+                    None => connect(to.as_deref())?,
+                };
+                let target = signatures(&mut client, &queries_path, &settings)?;
+                Ok((current, target))
+            };
+            let signatures = signatures();
+            if managed {
                 container::cleanup(podman).ok();
-                return Err(e);
             }
+            let (current, target) = signatures?;
+            diff(&current, &target)?;
+            println!("None of the generated signatures would change");
+            return Ok(());
+        }
+        Action::Watch { against } => {
+            let mut paths = vec![
+                queries_path.clone(),
+                config
+                    .clone()
+                    .unwrap_or_else(|| PathBuf::from("cornucopia.toml")),
+            ];
+            paths.extend(settings.extra_queries.iter().cloned());
+            let generate = |client: &mut postgres::Client| -> Result<(), Error> {
+                generate_live(
+                    client,
+                    &queries_path,
+                    destination.as_ref(),
+                    load_settings()?,
+                )?;
+                Ok(())
+            };
+            match against {
+                Against::Live { url } => {
+                    watch(
+                        &paths,
+                        || generate(&mut connect(url.as_deref())?),
+                        |err| report_json(err, message_format),
+                    );
+                }
+                Against::Schema { schema_files } => {
+                    paths.extend(schema_files.iter().cloned());
+                    // Replace the container left running by a previous watch
+                    container::cleanup(podman).ok();
+                    container::setup(podman)?;
+                    watch(
+                        &paths,
+                        || {
+                            let mut client = conn::cornucopia_database("cornucopia_watch")?;
+                            load_schema(&mut client, &schema_files)?;
+                            generate(&mut client)
+                        },
+                        |err| report_json(err, message_format),
+                    );
+                }
+            }
+        }
+        Action::Check { .. } => unreachable!("handled before choosing the destination"),
+        Action::Init { .. } | Action::Doctor { .. } | Action::Config { .. } => {
+            unreachable!("handled before loading the configuration")
         }
     };
+    if to_stdout {
+        print!("{generated_code}");
+    } else if dry_run {
+        println!("The queries are valid, nothing was written");
+    }
     Ok(())
 }
@@ -1,15 +1,26 @@
 use core::str;
-use std::fmt::{Display, Write};
+use std::{
+    fmt::{Display, Write},
+    sync::Arc,
+};
 
 use codegen_template::code;
+use heck::ToUpperCamelCase;
 use indexmap::IndexMap;
+use postgres_types::{Kind, Type};
+use proc_macro2::{Literal, TokenStream};
+use quote::{format_ident, quote, ToTokens};
 
 use crate::{
+    builder::{FnBuilder, ImplBuilder, IntoTokens, StructBuilder},
+    plugin::{FieldInfo, ItemInfo, ItemKind, Plugin, TypeContent, TypeInfo},
     prepare_queries::{
-        Ident, Preparation, PreparedContent, PreparedField, PreparedItem, PreparedModule,
-        PreparedQuery, PreparedType,
+        Cardinality, CopyStatement, Ident, Preparation, PreparedContent, PreparedField,
+        PreparedItem, PreparedModule, PreparedQuery, PreparedType,
     },
-    CodegenSettings,
+    read_queries::{materialized_view_name, MATERIALIZED_VIEWS_MODULE},
+    type_registrar::{custom_ty_path, CheckedKind, CornucopiaType},
+    CodegenSettings, Visibility,
 };
 
 pub struct GenCtx {
@@ -89,6 +100,16 @@ impl PreparedField {
         )
     }
 
+    /// Code to extract this field from a row at `idx`. `void` columns carry no value
+    /// that could be decoded, so they are never read.
+    pub fn row_get(&self, idx: usize) -> String {
+        if self.is_void() {
+            "()".to_string()
+        } else {
+            format!("row.get({idx})")
+        }
+    }
+
     pub fn owning_assign(&self) -> String {
         let call = self.owning_call(None);
         if call == self.ident.rs {
@@ -272,9 +293,11 @@ fn composite_fromsql(
     fields: &[PreparedField],
     name: &str,
     schema: &str,
+    ctx: &GenCtx,
 ) {
     let field_names = fields.iter().map(|p| &p.ident.rs);
     let read_idx = 0..fields.len();
+    let marker = row_marker(fields, ctx).1;
     code!(w =>
         impl<'a> postgres_types::FromSql<'a> for ${struct_name}Borrowed<'a> {
             fn from_sql(ty: &postgres_types::Type, out: &'a [u8]) ->
@@ -282,6 +305,7 @@ fn composite_fromsql(
             {
                 let fields = match *ty.kind() {
                     postgres_types::Kind::Composite(ref fields) => fields,
+                    postgres_types::Kind::Domain(ref inner) => return Self::from_sql(inner, out),
                     _ => unreachable!(),
                 };
                 let mut out = out;
@@ -294,80 +318,174 @@ fn composite_fromsql(
                     let _oid = postgres_types::private::read_be_i32(&mut out)?;
                     let $field_names = postgres_types::private::read_value(fields[$read_idx].type_(), &mut out)?;
                 )
-                Ok(${struct_name}Borrowed { $($field_names,) })
+                Ok(${struct_name}Borrowed { $($field_names,) $marker })
             }
 
             fn accepts(ty: &postgres_types::Type) -> bool {
-                ty.name() == "$name" && ty.schema() == "$schema"
+                match *ty.kind() {
+                    postgres_types::Kind::Domain(ref inner) => Self::accepts(inner),
+                    _ => ty.name() == "$name" && ty.schema() == "$schema",
+                }
             }
         }
     );
 }
 
-fn gen_params_struct(w: &mut impl Write, params: &PreparedItem, ctx: &GenCtx) {
+impl PreparedField {
+    fn info(&self, ty: String) -> FieldInfo<'_> {
+        FieldInfo {
+            db_name: &self.ident.db,
+            name: &self.ident.rs,
+            ty,
+            is_nullable: self.is_nullable,
+        }
+    }
+}
+
+/// `#[deprecated]` attribute with the note of a `deprecated` annotation, if any
+fn deprecated_attribute(deprecated: &Option<String>) -> String {
+    deprecated_meta(deprecated)
+        .map(|meta| format!("#[{meta}]"))
+        .unwrap_or_default()
+}
+
+/// `deprecated` attribute of a deprecated item, written without its `#[...]`
+fn deprecated_meta(deprecated: &Option<String>) -> Option<String> {
+    match deprecated.as_deref() {
+        None => None,
+        Some("") => Some("deprecated".to_string()),
+        Some(note) => Some(format!("deprecated(note = {note:?})")),
+    }
+}
+
+/// Appends the code of the plugins for a query struct
+fn gen_plugin_item(w: &mut impl Write, plugins: &[Arc<dyn Plugin>], item: &ItemInfo) {
+    for code in plugins.iter().filter_map(|it| it.item(item)) {
+        w.write_str(&code).unwrap();
+    }
+}
+
+fn gen_params_struct(
+    w: &mut impl Write,
+    module: &str,
+    params: &PreparedItem,
+    ctx: &GenCtx,
+    plugins: &[Arc<dyn Plugin>],
+) {
     let PreparedItem {
         name,
         fields,
         is_copy,
         is_named,
         is_ref,
+        imported,
+        deprecated,
         ..
     } = params;
+    if let Some(from) = imported {
+        let from = &from.value;
+        code!(w => pub use super::$from::$name;);
+        return;
+    }
     if *is_named {
         let traits = &mut Vec::new();
 
-        let copy = if *is_copy { "Clone,Copy," } else { "" };
-        let lifetime = if *is_ref { "'a," } else { "" };
         let fields_ty = fields
             .iter()
             .map(|p| p.param_ergo_ty(traits, ctx))
             .collect::<Vec<_>>();
-        let fields_name = fields.iter().map(|p| &p.ident.rs);
-        let traits_idx = (1..=traits.len()).map(idx_char);
-        code!(w =>
-            #[derive($copy Debug)]
-            pub struct $name<$lifetime $($traits_idx: $traits,)> {
-                $(pub $fields_name: $fields_ty,)
-            }
+        let lifetime = is_ref.then(|| "'a".to_string());
+        let bounds = (1..=traits.len())
+            .zip(traits.iter())
+            .map(|(idx, bound)| format!("{}: {bound}", idx_char(idx)));
+        let generics: Vec<_> = lifetime.into_iter().chain(bounds).collect();
+        let item = fields.iter().zip(&fields_ty).fold(
+            StructBuilder::new(name)
+                .derives_if(*is_copy, ["Clone", "Copy"])
+                .derive("Debug")
+                .generics(&generics),
+            |item, (p, ty)| item.field(&p.ident.rs, ty),
         );
+        let deprecated = deprecated_attribute(deprecated);
+        code!(w => $deprecated $item);
+        let info = ItemInfo {
+            module,
+            name: &name.value,
+            kind: ItemKind::Params,
+            generics: generics.join(", "),
+            fields: fields
+                .iter()
+                .zip(fields_ty)
+                .map(|(p, ty)| p.info(ty))
+                .collect(),
+        };
+        gen_plugin_item(w, plugins, &info);
     }
 }
 
-fn gen_row_structs(w: &mut impl Write, row: &PreparedItem, ctx: &GenCtx) {
+fn gen_row_structs(
+    w: &mut impl Write,
+    module: &str,
+    row: &PreparedItem,
+    derives: &[String],
+    ctx: &GenCtx,
+    plugins: &[Arc<dyn Plugin>],
+) {
     let PreparedItem {
         name,
         fields,
         is_copy,
         is_named,
+        imported,
+        deprecated,
+        derives: row_derives,
         ..
     } = row;
+    if let Some(from) = imported {
+        let from = &from.value;
+        if *is_copy {
+            code!(w => pub use super::$from::$name;);
+        } else {
+            code!(w => pub use super::$from::{$name, ${name}Borrowed};);
+        }
+        return;
+    }
     if *is_named {
         // Generate row struct
-        let fields_name = fields.iter().map(|p| &p.ident.rs);
-        let fields_ty = fields.iter().map(|p| p.own_struct(ctx));
-        let copy = if *is_copy { "Copy" } else { "" };
-        let ser_str = if ctx.gen_derive {
-            "serde::Serialize,"
-        } else {
-            ""
-        };
-        code!(w =>
-            #[derive($ser_str Debug, Clone, PartialEq,$copy)]
-            pub struct $name {
-                $(pub $fields_name : $fields_ty,)
+        let mut traits = vec!["Debug", "Clone", "PartialEq"];
+        if *is_copy {
+            traits.push("Copy");
+        }
+        if fields.iter().all(|f| f.ty.is_ord()) {
+            traits.extend(["Eq", "Hash", "PartialOrd", "Ord"]);
+        }
+        // Extra derives of the module and of the queries, unless already derived
+        let mut extra: Vec<_> = Vec::new();
+        for derive in derives.iter().chain(row_derives) {
+            if !traits.contains(&derive.as_str()) && !extra.contains(&derive) {
+                extra.push(derive);
             }
-        );
+        }
+        let item = StructBuilder::new(name)
+            .derive_if(ctx.gen_derive, "serde::Serialize")
+            .derives(traits)
+            .derives(extra);
+        let item = fields
+            .iter()
+            .fold(item, |item, p| item.field(&p.ident.rs, p.own_struct(ctx)));
+        let deprecated = deprecated_attribute(deprecated);
+        code!(w => $deprecated $item);
 
         if !is_copy {
             let fields_name = fields.iter().map(|p| &p.ident.rs);
-            let fields_ty = fields.iter().map(|p| p.brw_ty(true, ctx));
             let from_own_assign = fields.iter().map(|f| f.owning_assign());
+            let borrowed = borrowed_struct(format!("{name}Borrowed"), fields, ctx);
+            let rest = row_marker(fields, ctx).0.map_or("", |_| "..");
             code!(w =>
-                pub struct ${name}Borrowed<'a> {
-                    $(pub $fields_name : $fields_ty,)
-                }
+                $deprecated
+                $borrowed
                 impl<'a> From<${name}Borrowed<'a>> for $name {
-                    fn from(${name}Borrowed { $($fields_name,) }: ${name}Borrowed<'a>) -> Self {
+                    fn from(${name}Borrowed { $($fields_name,) $rest }: ${name}Borrowed<'a>) -> Self {
                         Self {
                             $($from_own_assign,)
                         }
@@ -375,10 +493,23 @@ fn gen_row_structs(w: &mut impl Write, row: &PreparedItem, ctx: &GenCtx) {
                 }
             );
         };
+        let info = ItemInfo {
+            module,
+            name: &name.value,
+            kind: ItemKind::Row,
+            generics: String::new(),
+            fields: fields.iter().map(|p| p.info(p.own_struct(ctx))).collect(),
+        };
+        gen_plugin_item(w, plugins, &info);
     }
 }
 
-fn gen_row_query(w: &mut impl Write, row: &PreparedItem, ctx: &GenCtx) {
+fn gen_row_query<W: Write>(
+    w: &mut W,
+    row: &PreparedItem,
+    ctx: &GenCtx,
+    settings: &CodegenSettings,
+) {
     let PreparedItem {
         name,
         fields,
@@ -388,11 +519,10 @@ fn gen_row_query(w: &mut impl Write, row: &PreparedItem, ctx: &GenCtx) {
     } = row;
     // Generate query struct
     let borrowed_str = if *is_copy { "" } else { "Borrowed" };
-    let (client_mut, fn_async, fn_await, backend, collect, raw_type, raw_pre, raw_post, client) =
+    let (client_mut, fn_await, backend, collect, raw_type, raw_pre, raw_post, client) =
         if ctx.is_async {
             (
                 "",
-                "async",
                 ".await",
                 "tokio_postgres",
                 "try_collect().await",
@@ -405,7 +535,6 @@ fn gen_row_query(w: &mut impl Write, row: &PreparedItem, ctx: &GenCtx) {
             (
                 "mut",
                 "",
-                "",
                 "postgres",
                 "collect()",
                 "Iterator",
@@ -421,278 +550,1414 @@ fn gen_row_query(w: &mut impl Write, row: &PreparedItem, ctx: &GenCtx) {
         fields[0].brw_ty(false, ctx)
     };
 
-    code!(w =>
-    pub struct ${name}Query<'a, C: GenericClient, T, const N: usize> {
-        client: &'a $client_mut C,
-        params: [&'a (dyn postgres_types::ToSql + Sync); N],
-        stmt: &'a mut $client::private::Stmt,
-        extractor: fn(&$backend::Row) -> $row_struct,
-        mapper: fn($row_struct) -> T,
-    }
-    impl<'a, C, T:'a, const N: usize> ${name}Query<'a, C, T, N> where C: GenericClient {
-        pub fn map<R>(self, mapper: fn($row_struct) -> R) -> ${name}Query<'a,C,R,N> {
-            ${name}Query {
-                client: self.client,
-                params: self.params,
-                stmt: self.stmt,
-                extractor: self.extractor,
-                mapper,
-            }
-        }
-
-        pub $fn_async fn one(self) -> Result<T, $backend::Error> {
-            let stmt = self.stmt.prepare(self.client)$fn_await?;
-            let row = self.client.query_one(stmt, &self.params)$fn_await?;
-            Ok((self.mapper)((self.extractor)(&row)))
-        }
-
-        pub $fn_async fn all(self) -> Result<Vec<T>, $backend::Error> {
-            self.iter()$fn_await?.$collect
-        }
-
-        pub $fn_async fn opt(self) -> Result<Option<T>, $backend::Error> {
-            let stmt = self.stmt.prepare(self.client)$fn_await?;
-            Ok(self
-                .client
-                .query_opt(stmt, &self.params)
-                $fn_await?
-                .map(|row| (self.mapper)((self.extractor)(&row))))
-        }
-
-        pub $fn_async fn iter(
-            self,
-        ) -> Result<impl $raw_type<Item = Result<T, $backend::Error>> + 'a, $backend::Error> {
-            let stmt = self.stmt.prepare(self.client)$fn_await?;
-            let it = self
-                .client
-                .query_raw(stmt, $client::private::slice_iter(&self.params))
-                $fn_await?
-                $raw_pre
-                .map(move |res| res.map(|row| (self.mapper)((self.extractor)(&row))))
-                $raw_post;
-            Ok(it)
+    // Run the body of a method for the query observer, and inside the query's span,
+    // reporting the number of rows given by `rows`
+    let observed = |rows: &str, body: &str| {
+        // The upstream client crates have no query observer
+        if settings.upstream_compat {
+            return body.to_string();
         }
-    });
+        let (closure, span_ref) = if ctx.is_async {
+            ("async move", "")
+        } else {
+            ("move ||", "&")
+        };
+        let mut body = body.to_string();
+        if ctx.is_async {
+            body = format!(
+                "{client}::private::timeout(self.timeout.clone(), async move {{ {body} }}).await"
+            );
+        }
+        if settings.gen_tracing {
+            body = format!(
+                "let span = self.span.clone(); {client}::private::traced({span_ref}span, {rows}, {closure} {{ {body} }}){fn_await}"
+            );
+        }
+        format!("let info = self.info; {client}::private::observe(&info, {rows}, {closure} {{ {body} }}){fn_await}")
+    };
+    let query_name = format!("{name}Query");
+    let mut item = StructBuilder::new(&query_name)
+        .generics(["'a", "C: GenericClient", "T", "const N: usize"])
+        .private_field("client", format!("&'a {client_mut} C"))
+        .private_field("params", "[&'a (dyn postgres_types::ToSql + Sync); N]")
+        .private_field("stmt", format!("&'a mut {client}::private::Stmt"))
+        .private_field("extractor", format!("fn(&{backend}::Row) -> {row_struct}"))
+        .private_field("mapper", format!("fn({row_struct}) -> T"));
+    // Fields describing the query, kept by `map`
+    let mut described = Vec::new();
+    if !settings.upstream_compat {
+        item = item.private_field("info", format!("{client}::QueryInfo"));
+        described.push("info");
+    }
+    if settings.gen_tracing {
+        item = item.private_field("span", format!("{client}::private::tracing::Span"));
+        described.push("span");
+    }
+    let is_timed = ctx.is_async && !settings.upstream_compat;
+    if is_timed {
+        item = item.private_field(
+            "timeout",
+            format!("Option<(std::time::Duration, {backend}::CancelToken)>"),
+        );
+        described.push("timeout");
+    }
+    let described = described
+        .iter()
+        .map(|it| format!("{it}: self.{it},"))
+        .collect::<String>();
+    // Methods consuming the query
+    let method = |name: &str| FnBuilder::new(name).async_if(ctx.is_async).receiver("self");
+    let iter_ty =
+        code!(Result<impl $raw_type<Item = Result<T, $backend::Error>> + 'a, $backend::Error>);
+    let run = |call: &str, borrowed: bool| {
+        run_stmt(
+            ("self.stmt", "self.client", "&self.params"),
+            call,
+            borrowed,
+            ctx,
+            settings,
+        )
+    };
+    let run_one = run("query_one(stmt, params)", false);
+    let run_opt = run("query_opt(stmt, params)", false);
+    let run_raw = run(
+        &format!("query_raw(stmt, {client}::private::slice_iter(params))"),
+        true,
+    );
+    // Without streaming, rows are all read before being mapped
+    let all_rows = if settings.gen_iter {
+        code!(self.raw_iter()$fn_await?.$collect)
+    } else {
+        let run_all = run("query(stmt, params)", false);
+        code!(
+            Ok($run_all?
+                .iter()
+                .map(|row| (self.mapper)((self.extractor)(row)))
+                .collect())
+        )
+    };
+    let each_rows = if ctx.is_async {
+        "let mut rows = std::pin::pin!(rows); while let Some(row) = rows.try_next().await?"
+    } else {
+        "let mut rows = rows; while let Some(row) = rows.next()?"
+    };
+
+    let mut methods = vec![FnBuilder::new("map")
+        .generic("R")
+        .receiver("self")
+        .param("mapper", format!("fn({row_struct}) -> R"))
+        .returns(format!("{query_name}<'a,C,R,N>"))
+        .body(format!(
+            "{query_name} {{ client: self.client, params: self.params, stmt: self.stmt, extractor: self.extractor, mapper, {described} }}"
+        ))];
+    methods.push(
+        method("one")
+            .returns(format!("Result<T, {backend}::Error>"))
+            .body(observed(
+                "|_| Some(1)",
+                &format!("let row = {run_one}?; Ok((self.mapper)((self.extractor)(&row)))"),
+            )),
+    );
+    methods.push(
+        method("all")
+            .returns(format!("Result<Vec<T>, {backend}::Error>"))
+            .body(observed("|it: &Vec<T>| Some(it.len() as u64)", &all_rows)),
+    );
+    methods.push(
+        method("opt")
+            .returns(format!("Result<Option<T>, {backend}::Error>"))
+            .body(observed(
+                "|it: &Option<T>| Some(it.is_some().into())",
+                &format!("Ok({run_opt}?.map(|row| (self.mapper)((self.extractor)(&row))))"),
+            )),
+    );
+    if settings.gen_iter {
+        methods.push(
+            method("iter")
+                .returns(&iter_ty)
+                .body(observed("|_| None", &format!("self.raw_iter(){fn_await}"))),
+        );
+    }
+    methods.push(
+        method("for_each")
+            .doc("Passes each row to `visitor` as it is read, without building owned values,")
+            .doc("returning the number of rows.")
+            .param("mut visitor", format!("impl FnMut({row_struct})"))
+            .returns(format!("Result<u64, {backend}::Error>"))
+            .body(observed(
+                "|it: &u64| Some(*it)",
+                &format!(
+                    "let rows = {run_raw}?; let mut count = 0; {each_rows} {{ visitor((self.extractor)(&row)); count += 1; }} Ok(count)"
+                ),
+            )),
+    );
+    // The rows counted by `all` can't be reported by `iter` too, so both run this one
+    if settings.gen_iter {
+        methods.push(
+            method("raw_iter")
+                .vis("")
+                .returns(&iter_ty)
+                .body(format!(
+                    "let it = {run_raw}?{raw_pre}.map(move |res| res.map(|row| (self.mapper)((self.extractor)(&row)))){raw_post}; Ok(it)"
+                )),
+        );
+    }
+    let query_impl = |bound: String| {
+        ImplBuilder::new(format!("{query_name}<'a, C, T, N>"))
+            .generics(["'a", "C", "T:'a", "const N: usize"])
+            .bound(bound)
+    };
+    let methods = query_impl("C: GenericClient".to_string()).items(methods);
+    code!(w =>
+        $item
+        $methods
+    );
+    // Timeouts cancel the query through the client
+    if is_timed {
+        let timeout = FnBuilder::new("timeout")
+            .doc("Cancels the query on the server if it runs longer than `timeout`, failing with")
+            .doc("the cancellation error. `iter` and `cursor` only bound starting the query, not")
+            .doc("reading its rows.")
+            .receiver("mut self")
+            .param("timeout", "std::time::Duration")
+            .returns("Self")
+            .body("self.timeout = Some((timeout, self.client.cancel_token())); self");
+        let item = query_impl(format!("C: {client}::CancelClient")).item(timeout);
+        code!(w => $item);
+    }
+    // Cursors stream their rows too
+    if !settings.upstream_compat && settings.gen_iter {
+        let cursor = method("cursor")
+            .doc("Fetch rows through a portal, `batch_size` rows at a time.")
+            .param("batch_size", "i32")
+            .returns(&iter_ty)
+            .body(observed(
+                "|_| None",
+                &format!(
+                    "let stmt = self.stmt.prepare(self.client){fn_await}?; let it = {client}::private::cursor(self.client, stmt, &self.params, batch_size){fn_await}?.map(move |res| res.map(|row| (self.mapper)((self.extractor)(&row)))); Ok(it)"
+                ),
+            ));
+        let item = query_impl(format!("C: {client}::CursorClient")).item(cursor);
+        code!(w => $item);
+    }
+}
+
+/// Marker field of a borrowed struct whose `fields` are all decoded as owned values, which
+/// still carries the lifetime of the row, with the code initializing it
+fn row_marker(fields: &[PreparedField], ctx: &GenCtx) -> (Option<&'static str>, &'static str) {
+    if fields
+        .iter()
+        .any(|f| f.brw_ty(true, ctx) != f.brw_ty(false, ctx))
+    {
+        (None, "")
+    } else {
+        (
+            Some("std::marker::PhantomData<&'a ()>"),
+            "_row: std::marker::PhantomData,",
+        )
+    }
+}
+
+/// Borrowed version of a struct with `fields`, borrowing from the row they are decoded from
+fn borrowed_struct(name: String, fields: &[PreparedField], ctx: &GenCtx) -> StructBuilder {
+    let item = fields
+        .iter()
+        .fold(StructBuilder::new(name).generic("'a"), |item, p| {
+            item.field(&p.ident.rs, p.brw_ty(true, ctx))
+        });
+    match row_marker(fields, ctx).0 {
+        Some(marker) => item.field("_row", marker),
+        None => item,
+    }
+}
+
+/// Type of the rows of `item`, with the code extracting one from `row` and the code owning the extracted `it`
+fn row_mapping(item: &PreparedItem, index: &[usize], ctx: &GenCtx) -> (String, String, String) {
+    if item.is_named {
+        let path = item.path(ctx);
+        let post = if item.is_copy { "" } else { "Borrowed" };
+        let fields_name = item.fields.iter().map(|p| &p.ident.rs);
+        let fields_get = item
+            .fields
+            .iter()
+            .enumerate()
+            .map(|(i, f)| f.row_get(index[i]));
+        let marker = if item.is_copy {
+            ""
+        } else {
+            row_marker(&item.fields, ctx).1
+        };
+        let extract = code!($path$post { $($fields_name: $fields_get,) $marker });
+        let mapper = code!(<$path>::from(it));
+        (path, extract, mapper)
+    } else {
+        let field = &item.fields[0];
+        (
+            field.own_struct(ctx),
+            field.row_get(0),
+            field.owning_call(Some("it")),
+        )
+    }
 }
 
 pub fn idx_char(idx: usize) -> String {
     format!("T{idx}")
 }
 
-fn gen_query_fn<W: Write>(w: &mut W, module: &PreparedModule, query: &PreparedQuery, ctx: &GenCtx) {
-    let PreparedQuery {
-        ident,
-        row,
-        sql,
-        param,
-    } = query;
+/// Escapes `sql` for a Rust string literal, so that the database receives it byte for byte
+/// as written in the query file
+fn escape_sql(sql: &str) -> String {
+    sql.replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\r', "\\r")
+}
 
-    let (client_mut, fn_async, fn_await, backend, client) = if ctx.is_async {
-        ("", "async", ".await", "tokio_postgres", "cornucopia_async")
+/// Code running `call`, a method of the client taking the statement `stmt` prepared on `client`
+/// as `stmt` and the parameters `params` as `params`. Statements are prepared again on
+/// connections that don't know them, except by the upstream client crates. `borrowed` calls
+/// return values borrowing the client.
+fn run_stmt(
+    (stmt, client, params): (&str, &str, &str),
+    call: &str,
+    borrowed: bool,
+    ctx: &GenCtx,
+    settings: &CodegenSettings,
+) -> String {
+    let let_params = format!("let params: &[&(dyn postgres_types::ToSql + Sync)] = {params};");
+    if settings.upstream_compat {
+        let fn_await = if ctx.is_async { ".await" } else { "" };
+        format!("{{ let stmt = {stmt}.prepare({client}){fn_await}?; {let_params} {client}.{call}{fn_await} }}")
+    } else if ctx.is_async {
+        format!("{stmt}.run({client}, |client, stmt| async move {{ let stmt = &stmt; {let_params} client.{call}.await }}).await")
+    } else if borrowed {
+        format!("{stmt}.run_borrowed({client}, {params}, |client, stmt, params| client.{call})")
     } else {
-        ("mut", "", "", "postgres", "cornucopia_sync")
-    };
+        format!("{stmt}.run({client}, |client, stmt| {{ {let_params} client.{call} }})")
+    }
+}
+
+/// Builds the statement of a query and the items binding it, from the query's parameters and
+/// the code generation settings
+struct QueryGen<'a> {
+    module: &'a PreparedModule,
+    query: &'a PreparedQuery,
+    ctx: &'a GenCtx,
+    settings: &'a CodegenSettings,
+    struct_name: String,
+    param: Option<&'a PreparedItem>,
+    param_field: &'a [PreparedField],
+    order: &'a [usize],
+    /// Generic parameters of the traits the parameters are taken as, e.g. `T1: StringSql`
+    traits: Vec<String>,
+    /// Parameters, as names and borrowed types
+    params: Vec<(String, String)>,
+    /// Expression describing the query to its observer
+    info: String,
+    /// Expression entering the span of the query
+    span: String,
+    /// Fields of the query struct describing the query
+    query_init: String,
+}
 
-    let struct_name = ident.type_ident();
-    let (param, param_field, order) = match param {
-        Some((idx, order)) => {
-            let it = module.params.get_index(*idx).unwrap().1;
-            (Some(it), it.fields.as_slice(), order.as_slice())
+impl<'a> QueryGen<'a> {
+    fn new(
+        module: &'a PreparedModule,
+        query: &'a PreparedQuery,
+        ctx: &'a GenCtx,
+        settings: &'a CodegenSettings,
+    ) -> Self {
+        let PreparedQuery {
+            ident,
+            sql,
+            param,
+            trace,
+            operation,
+            table,
+            ..
+        } = query;
+        let client = ctx.client_name();
+        let (param, param_field, order) = match param {
+            Some((idx, order)) => {
+                let it = module.params.get_index(*idx).unwrap().1;
+                (Some(it), it.fields.as_slice(), order.as_slice())
+            }
+            None => (None, [].as_slice(), [].as_slice()),
+        };
+        let mut traits = Vec::new();
+        let params = order
+            .iter()
+            .map(|idx| {
+                let p = &param_field[*idx];
+                let ty = p.param_ergo_ty(&mut traits, ctx);
+                (p.ident.rs.clone(), format!("&'a {ty}"))
+            })
+            .collect();
+        let span = {
+            let target = trace
+                .target
+                .as_ref()
+                .map(|target| format!("target: \"{target}\", "))
+                .unwrap_or_default();
+            // OpenTelemetry semantic conventions
+            let mut attributes = "db.system = \"postgresql\"".to_string();
+            if let Some(operation) = operation {
+                write!(attributes, ", db.operation = \"{operation}\"").unwrap();
+            }
+            if let Some(table) = table {
+                write!(attributes, ", db.sql.table = \"{table}\"").unwrap();
+            }
+            if settings.trace_statement {
+                let sql = escape_sql(sql);
+                write!(attributes, ", db.statement = \"{sql}\"").unwrap();
+            }
+            // Recorded once the query ran
+            let tracing = format!("{client}::private::tracing");
+            write!(
+                attributes,
+                ", rows = {tracing}::field::Empty, duration_ms = {tracing}::field::Empty"
+            )
+            .unwrap();
+            format!(
+                "{tracing}::{}_span!({target}\"{}\", module = \"{}\", {attributes})",
+                trace.level, ident.db, module.info.name
+            )
+        };
+        let info = format!(
+            "{client}::QueryInfo {{ module: \"{}\", query: \"{}\" }}",
+            module.info.name, ident.db
+        );
+        let mut query_init = if settings.upstream_compat {
+            String::new()
+        } else {
+            format!("info: {info},")
+        };
+        if settings.gen_tracing {
+            write!(query_init, " span: {span},").unwrap();
         }
-        None => (None, [].as_slice(), [].as_slice()),
-    };
-    let traits = &mut Vec::new();
-    let params_ty: Vec<_> = order
-        .iter()
-        .map(|idx| param_field[*idx].param_ergo_ty(traits, ctx))
-        .collect();
-    let params_name = order.iter().map(|idx| &param_field[*idx].ident.rs);
-    let traits_idx = (1..=traits.len()).map(idx_char);
-    let lazy_impl = |w: &mut W| {
-        if let Some((idx, index)) = row {
-            let item = module.rows.get_index(*idx).unwrap().1;
-            let PreparedItem {
-                name: row_name,
-                fields,
-                is_copy,
-                is_named,
-                ..
-            } = &item;
-            // Query fn
-            let nb_params = param_field.len();
-
-            // TODO find a way to clean this mess
-            #[allow(clippy::type_complexity)]
-            let (row_struct_name, extractor, mapper): (_, Box<dyn Fn(&mut W)>, _) = if *is_named {
-                let path = item.path(ctx);
+        if ctx.is_async && !settings.upstream_compat {
+            query_init.push_str("timeout: None,");
+        }
+        Self {
+            module,
+            query,
+            ctx,
+            settings,
+            struct_name: ident.type_ident(),
+            param,
+            param_field,
+            order,
+            traits: trait_generics(&traits),
+            params,
+            info,
+            span,
+            query_init,
+        }
+    }
+
+    fn client(&self) -> &'static str {
+        self.ctx.client_name()
+    }
+
+    fn backend(&self) -> &'static str {
+        if self.ctx.is_async {
+            "tokio_postgres"
+        } else {
+            "postgres"
+        }
+    }
+
+    /// Mutability of the borrowed clients
+    fn client_mut(&self) -> &'static str {
+        if self.ctx.is_async {
+            ""
+        } else {
+            "mut"
+        }
+    }
+
+    fn fn_await(&self) -> &'static str {
+        if self.ctx.is_async {
+            ".await"
+        } else {
+            ""
+        }
+    }
+
+    /// Runs the statement `body` for the query observer, and inside the query's span,
+    /// reporting the number of rows given by `rows`
+    fn observed(&self, rows: &str, body: &str) -> String {
+        if self.settings.upstream_compat {
+            return body.to_string();
+        }
+        let (client, fn_await) = (self.client(), self.fn_await());
+        let (closure, span_ref) = if self.ctx.is_async {
+            ("async move", "")
+        } else {
+            ("move ||", "&")
+        };
+        let mut body = body.to_string();
+        if self.settings.gen_tracing {
+            body = format!(
+                "let span = {}; {client}::private::traced({span_ref}span, {rows}, {closure} {{ {body} }}){fn_await}",
+                self.span
+            );
+        }
+        format!(
+            "{client}::private::observe(&{}, {rows}, {closure} {{ {body} }}){fn_await}",
+            self.info
+        )
+    }
+
+    /// Method binding the parameters of the query, taking them after the client
+    fn bound_method(&self, name: &str) -> FnBuilder {
+        FnBuilder::new(name)
+            .async_if(self.ctx.is_async)
+            .generics(["'a", "C: GenericClient"])
+            .generics(&self.traits)
+            .receiver("&'a mut self")
+            .param("client", format!("&'a {} C", self.client_mut()))
+            .params(self.params.iter().map(|(name, ty)| (name, ty)))
+    }
+
+    /// Query struct of the row at `idx`, with the parameters `params`
+    fn query_literal(&self, idx: usize, index: &[usize], params: &str) -> String {
+        let item = self.module.rows.get_index(idx).unwrap().1;
+        let (_, extractor, mapper) = row_mapping(item, index, self.ctx);
+        format!(
+            "{}Query {{ client, params: [{params}], stmt: &mut self.0, extractor: |row| {{ {extractor} }}, mapper: |it| {{ {mapper} }}, {} }}",
+            item.name, self.query_init
+        )
+    }
+
+    /// Owned type of the rows of the row at `idx`
+    fn row_struct(&self, idx: usize) -> String {
+        let item = self.module.rows.get_index(idx).unwrap().1;
+        if item.is_named {
+            item.path(self.ctx)
+        } else {
+            item.fields[0].own_struct(self.ctx)
+        }
+    }
+
+    /// Copying, running or binding the statement, depending on the kind of the query
+    fn bind_fn(&self) -> FnBuilder {
+        let PreparedQuery {
+            ident,
+            row,
+            audit,
+            copy,
+            cardinality,
+            is_script,
+            ..
+        } = self.query;
+        let (client, backend, client_mut, fn_await) = (
+            self.client(),
+            self.backend(),
+            self.client_mut(),
+            self.fn_await(),
+        );
+        let (generic, lifetime, writer_lifetime) = if self.ctx.is_async {
+            (None, "", "")
+        } else {
+            (Some("'w"), "'w ", "<'w>")
+        };
+        // Copies run on the client, outside of the statement. The synchronous clients all copy.
+        let bound = if self.ctx.is_async {
+            format!("{client}::CopyClient")
+        } else {
+            "GenericClient".to_string()
+        };
+        let copy_fn = |name: &str| {
+            FnBuilder::new(name)
+                .async_if(self.ctx.is_async)
+                .generics(generic)
+                .generic(format!("C: {bound}"))
+                .receiver("&mut self")
+                .param("client", format!("&{lifetime}{client_mut} C"))
+        };
+        if let Some(CopyStatement::Out { sql: copy, csv }) = copy {
+            let copy = escape_sql(copy);
+            if *csv {
+                let reader = if self.ctx.is_async {
+                    "tokio_postgres::CopyOutStream"
+                } else {
+                    "postgres::CopyOutReader<'w>"
+                };
+                return copy_fn("reader")
+                    .doc("Streams the copied rows as csv, starting with a header line")
+                    .returns(format!("Result<{reader}, {backend}::Error>"))
+                    .body(format!("client.copy_out(\"{copy}\"){fn_await}"));
+            }
+            let (idx, index) = row.as_ref().unwrap();
+            let item = self.module.rows.get_index(*idx).unwrap().1;
+            let (row_ty, extract, mapper) = row_mapping(item, index, self.ctx);
+            let (raw_type, pre, raw_pre, bound) = if self.ctx.is_async {
                 (
-                    path.clone(),
-                    Box::new(|w: _| {
-                        let path = item.path(ctx);
-                        let post = if *is_copy { "" } else { "Borrowed" };
-                        let fields_name = fields.iter().map(|p| &p.ident.rs);
-                        let fields_idx = (0..fields.len()).map(|i| index[i]);
-                        code!(w => $path$post {
-                            $($fields_name: row.get($fields_idx),)
-                        })
-                    }),
-                    code!(<$path>::from(it)),
+                    "futures::Stream",
+                    "",
+                    "tokio_postgres::binary_copy::BinaryCopyOutStream",
+                    "",
                 )
             } else {
-                let field = &fields[0];
                 (
-                    field.own_struct(ctx),
-                    Box::new(|w: _| code!(w => row.get(0))),
-                    field.owning_call(Some("it")),
+                    "Iterator",
+                    ".iterator()",
+                    "postgres::binary_copy::BinaryCopyOutIter",
+                    " + 'w",
                 )
             };
-            code!(w =>
-                pub fn bind<'a, C: GenericClient,$($traits_idx: $traits,)>(&'a mut self, client: &'a $client_mut C, $($params_name: &'a $params_ty,) ) -> ${row_name}Query<'a,C, $row_struct_name, $nb_params> {
-                    ${row_name}Query {
-                        client,
-                        params: [$($params_name,)],
-                        stmt: &mut self.0,
-                        extractor: |row| { $!extractor },
-                        mapper: |it| { $mapper },
-                    }
+            copy_fn("reader")
+                .doc("Streams the copied rows, decoded from the binary format")
+                .returns(format!(
+                    "Result<impl {raw_type}<Item = Result<{row_ty}, {backend}::Error>>{bound}, {backend}::Error>"
+                ))
+                .body(format!(
+                    "let types: Vec<_> = self.0.prepare(client){fn_await}?.columns().iter().map(|it| it.type_().clone()).collect(); \
+                     let stream = client.copy_out(\"{copy}\"){fn_await}?; \
+                     Ok({raw_pre}::new(stream, &types){pre}.map(|res| res.map(|row| {{ let it = {extract}; {mapper} }})))"
+                ))
+        } else if let Some(CopyStatement::In(copy)) = copy {
+            let copy = escape_sql(copy);
+            let (pre, post) = if self.ctx.is_async {
+                ("Box::pin(", ")")
+            } else {
+                ("", "")
+            };
+            let writer = format!("{}Writer", self.struct_name);
+            copy_fn("writer")
+                .doc("Starts copying rows, which are only committed once the writer is finished")
+                .returns(format!("Result<{writer}{writer_lifetime}, {backend}::Error>"))
+                .body(format!(
+                    "let types = self.0.prepare(client){fn_await}?.params().to_vec(); \
+                     let sink = client.copy_in(\"{copy}\"){fn_await}?; \
+                     Ok({writer}({pre}{backend}::binary_copy::BinaryCopyInWriter::new(sink, &types){post}))"
+                ))
+        } else if *is_script {
+            // The synchronous clients all run scripts
+            let bound = if self.ctx.is_async {
+                format!("{client}::ScriptClient")
+            } else {
+                "GenericClient".to_string()
+            };
+            FnBuilder::new("bind")
+                .doc("Runs the statements of the script")
+                .async_if(self.ctx.is_async)
+                .generics(["'a".to_string(), format!("C: {bound}")])
+                .receiver("&'a mut self")
+                .param("client", format!("&'a {client_mut} C"))
+                .returns(format!("Result<(), {backend}::Error>"))
+                .body(self.observed(
+                    "|_| None",
+                    &format!("client.batch_execute(self.0.query()){fn_await}"),
+                ))
+        } else if let Some((idx, index)) = row {
+            let params = self.params.iter().map(|it| format!("{},", it.0));
+            let literal = self.query_literal(*idx, index, &params.collect::<String>());
+            let row_struct_name =
+                row_mapping(self.module.rows.get_index(*idx).unwrap().1, index, self.ctx).0;
+            match cardinality {
+                Some(cardinality) => self
+                    .bound_method("bind")
+                    .returns(format!(
+                        "Result<{}, {backend}::Error>",
+                        cardinality.ty(&row_struct_name)
+                    ))
+                    .body(format!("{literal}.{}(){fn_await}", cardinality.method())),
+                None => {
+                    let row_name = &self.module.rows.get_index(*idx).unwrap().1.name;
+                    self.bound_method("bind")
+                        .async_if(false)
+                        .returns(format!(
+                            "{row_name}Query<'a,C, {row_struct_name}, {}>",
+                            self.param_field.len()
+                        ))
+                        .body(literal)
                 }
-            );
+            }
         } else {
-            // Execute fn
-            let params_wrap = order.iter().map(|idx| {
-                let p = &param_field[*idx];
-                p.ty.sql_wrapped(&p.ident.rs, ctx)
+            let params_wrap = self.order.iter().map(|idx| {
+                let p = &self.param_field[*idx];
+                format!("{},", p.ty.sql_wrapped(&p.ident.rs, self.ctx))
             });
-            code!(w =>
-                pub $fn_async fn bind<'a, C: GenericClient,$($traits_idx: $traits,)>(&'a mut self, client: &'a $client_mut C, $($params_name: &'a $params_ty,)) -> Result<u64, $backend::Error> {
-                    let stmt = self.0.prepare(client)$fn_await?;
-                    client.execute(stmt, &[ $($params_wrap,) ])$fn_await
-                }
+            let params = format!("&[ {} ]", params_wrap.collect::<String>());
+            let execute = run_stmt(
+                ("self.0", "client", &params),
+                "execute(stmt, params)",
+                false,
+                self.ctx,
+                self.settings,
             );
+            let run = match audit {
+                Some(audit) => {
+                    let actor = audit
+                        .actor
+                        .as_ref()
+                        .map_or("None".to_string(), |actor| format!("Some({actor})"));
+                    let (module, query) = (&self.module.info.name, &ident.db);
+                    format!(
+                        "let rows = {execute}?; \
+                         {client}::private::audit(&{client}::AuditRecord {{ module: \"{module}\", query: \"{query}\", actor: {actor}, rows, }}); \
+                         Ok(rows)"
+                    )
+                }
+                None => execute,
+            };
+            self.bound_method("bind")
+                .returns(format!("Result<u64, {backend}::Error>"))
+                .body(self.observed("|rows| Some(*rows)", &run))
         }
-    };
-    // Gen statement struct
-    {
-        let sql = sql.replace('"', "\\\""); // Rust string format escaping
-        let name = &ident.rs;
-        code!(w =>
-            pub fn $name() -> ${struct_name}Stmt {
-                ${struct_name}Stmt($client::private::Stmt::new("$sql"))
-            }
-            pub struct ${struct_name}Stmt($client::private::Stmt);
-            impl ${struct_name}Stmt {
-                $!lazy_impl
-            }
-        );
     }
 
-    // Param impl
-    if let Some(param) = param {
-        if param.is_named {
-            let param_path = &param.path(ctx);
-            let lifetime = if param.is_copy || !param.is_ref {
-                ""
-            } else {
-                "'a,"
-            };
-            if let Some((idx, _)) = row {
-                let prepared_row = &module.rows.get_index(*idx).unwrap().1;
-                let query_row_struct = if prepared_row.is_named {
-                    prepared_row.path(ctx)
-                } else {
-                    prepared_row.fields[0].own_struct(ctx)
-                };
-                let name = &module.rows.get_index(*idx).unwrap().1.name;
-                let nb_params = param_field.len();
-                code!(w =>
-                    impl <'a, C: GenericClient,$($traits_idx: $traits,)> $client::Params<'a, $param_path<$lifetime $($traits_idx,)>, ${name}Query<'a, C, $query_row_struct, $nb_params>, C> for ${struct_name}Stmt {
-                        fn params(&'a mut self, client: &'a $client_mut C, params: &'a $param_path<$lifetime $($traits_idx,)>) -> ${name}Query<'a, C, $query_row_struct, $nb_params> {
-                            self.bind(client, $(&params.$params_name,))
-                        }
+    /// Splits the `= ANY(...)` array parameter into chunks
+    fn bind_chunked_fn(&self) -> Option<FnBuilder> {
+        let (Some(chunk_size), Some(any_idx)) =
+            (self.settings.any_chunk_size, self.query.any_param)
+        else {
+            return None;
+        };
+        let chunked = &self.param_field[self.order[any_idx]];
+        let CornucopiaType::Array { inner } = chunked.ty.as_ref() else {
+            return None;
+        };
+        if chunked.is_nullable {
+            return None;
+        }
+        let traits = &mut Vec::new();
+        let params: Vec<_> = self
+            .order
+            .iter()
+            .map(|idx| {
+                let p = &self.param_field[*idx];
+                let ty = if p == chunked {
+                    let item = inner.param_ergo_ty(p.is_inner_nullable, traits, self.ctx);
+                    if p.is_inner_nullable {
+                        format!("[Option<{item}>]")
+                    } else {
+                        format!("[{item}]")
                     }
-                );
-            } else {
-                let (send_sync, pre_ty, post_ty_lf, pre, post) = if ctx.is_async {
-                    (
-                        "+ Send + Sync",
-                        "std::pin::Pin<Box<dyn futures::Future<Output = Result",
-                        "> + Send + 'a>>",
-                        "Box::pin(self",
-                        ")",
-                    )
                 } else {
-                    ("", "Result", "", "self", "")
+                    p.param_ergo_ty(traits, self.ctx)
                 };
-                code!(w =>
-                    impl <'a, C: GenericClient $send_sync, $($traits_idx: $traits,)> $client::Params<'a, $param_path<$lifetime $($traits_idx,)>, $pre_ty<u64, $backend::Error>$post_ty_lf, C> for ${struct_name}Stmt {
-                        fn params(&'a mut self, client: &'a $client_mut C, params: &'a $param_path<$lifetime $($traits_idx,)>) -> $pre_ty<u64, $backend::Error>$post_ty_lf {
-                            $pre.bind(client, $(&params.$params_name,))$post
-                        }
-                    }
-                );
+                (&p.ident.rs, format!("&'a {ty}"))
+            })
+            .collect();
+        let args = self.order.iter().map(|idx| {
+            let p = &self.param_field[*idx];
+            if p == chunked {
+                "&chunk,".to_string()
+            } else {
+                format!("{},", p.ident.rs)
             }
-        }
+        });
+        let args = args.collect::<String>();
+        let (backend, fn_await) = (self.backend(), self.fn_await());
+        let chunked_name = &chunked.ident.rs;
+        let method = FnBuilder::new("bind_chunked")
+            .async_if(self.ctx.is_async)
+            .generics(["'a", "C: GenericClient"])
+            .generics(trait_generics(traits))
+            .receiver("&'a mut self")
+            .param("client", format!("&'a {} C", self.client_mut()))
+            .params(params);
+        Some(if let Some((idx, _)) = self.query.row {
+            // Rows of queries with a single row cardinality can't be merged
+            let fetch = match self.query.cardinality {
+                None => format!(".all(){fn_await}"),
+                Some(Cardinality::All) => fn_await.to_string(),
+                Some(Cardinality::One | Cardinality::Opt) => return None,
+            };
+            method
+                .returns(format!(
+                    "Result<Vec<{}>, {backend}::Error>",
+                    self.row_struct(idx)
+                ))
+                .body(format!(
+                    "let mut rows = Vec::new(); \
+                     for chunk in {chunked_name}.chunks({chunk_size}) {{ rows.extend(self.bind(client, {args}){fetch}?); }} \
+                     Ok(rows)"
+                ))
+        } else {
+            method
+                .returns(format!("Result<u64, {backend}::Error>"))
+                .body(format!(
+                    "let mut count = 0; \
+                     for chunk in {chunked_name}.chunks({chunk_size}) {{ count += self.bind(client, {args}){fn_await}?; }} \
+                     Ok(count)"
+                ))
+        })
     }
-}
 
-/// Generates type definitions for custom user types. This includes domains, composites and enums.
-/// If the type is not `Copy`, then a Borrowed version will be generated.
-fn gen_custom_type(w: &mut impl Write, schema: &str, prepared: &PreparedType, ctx: &GenCtx) {
-    let PreparedType {
-        struct_name,
-        content,
-        is_copy,
-        is_params,
-        name,
-    } = prepared;
-    let copy = if *is_copy { "Copy," } else { "" };
-    let ser_str = if ctx.gen_derive {
-        "serde::Serialize,"
-    } else {
-        ""
-    };
-    match content {
-        PreparedContent::Enum(variants) => {
-            let variants_ident = variants.iter().map(|v| &v.rs);
-            code!(w =>
-                #[derive($ser_str Debug, Clone, Copy, PartialEq, Eq)]
-                #[allow(non_camel_case_types)]
-                pub enum $struct_name {
-                    $($variants_ident,)
+    /// Checks of the `validate` attribute, run before binding the parameters
+    fn validate_fn(&self) -> Option<FnBuilder> {
+        if self.query.validate.is_empty() {
+            return None;
+        }
+        let client = self.client();
+        let checks = self.query.validate.iter().map(|check| {
+            let field = self
+                .param_field
+                .iter()
+                .find(|it| it.ident.db == check.param)
+                .expect("checked parameters exist");
+            let name = &field.ident.rs;
+            let (op, mut value) = (&check.op, check.value.clone());
+            let cond = match field.ty.checked_kind() {
+                Some(CheckedKind::Text) => format!("{name}.as_str().chars().count() {op} {value}"),
+                Some(CheckedKind::Bytes) => format!("{name}.as_bytes().len() {op} {value}"),
+                Some(CheckedKind::Float) if !value.contains('.') => {
+                    value.push_str(".0");
+                    format!("*{name} {op} {value}")
                 }
+                _ => format!("*{name} {op} {value}"),
+            };
+            let cond = if field.is_nullable {
+                format!("{name}.as_ref().map_or(true, |{name}| {cond})")
+            } else {
+                cond
+            };
+            let (module, query, param, text) = (
+                &self.module.info.name,
+                &self.query.ident.db,
+                &check.param,
+                &check.text,
             );
-            enum_sql(w, name, struct_name, variants);
-        }
-        PreparedContent::Composite(fields) => {
-            let fields_original_name = fields.iter().map(|p| &p.ident.db);
-            let fields_name = fields.iter().map(|p| &p.ident.rs);
-            {
-                let fields_ty = fields.iter().map(|p| p.own_struct(ctx));
-                code!(w =>
-                    #[derive($ser_str Debug,postgres_types::FromSql,$copy Clone, PartialEq)]
-                    #[postgres(name = "$name")]
-                    pub struct $struct_name {
-                        $(
-                            #[postgres(name = "$fields_original_name")]
-                            pub $fields_name: $fields_ty,
-                        )
-                    }
-                );
+            format!(
+                "if !({cond}) {{ return Err({client}::ValidationError {{ module: \"{module}\", query: \"{query}\", param: \"{param}\", check: \"{text}\", }}); }}"
+            )
+        });
+        Some(
+            FnBuilder::new("validate")
+                .doc("Checks the parameters against the `validate` attribute of the query, before binding them")
+                .generic("'a")
+                .generics(&self.traits)
+                .receiver("&self")
+                .params(self.params.iter().map(|(name, ty)| (name, ty)))
+                .returns(format!("Result<(), {client}::ValidationError>"))
+                .body(format!("{} Ok(())", checks.collect::<String>())),
+        )
+    }
+
+    /// Latency budget, checked by tests against a seeded database
+    fn budget_items(&self) -> Vec<TokenStream> {
+        let Some(budget) = self.query.budget else {
+            return Vec::new();
+        };
+        let duration = if budget.subsec_nanos() == 0 {
+            let secs = Literal::u64_unsuffixed(budget.as_secs());
+            quote!(from_secs(#secs))
+        } else if budget.subsec_micros() % 1000 == 0 {
+            let millis = Literal::u128_unsuffixed(budget.as_millis());
+            quote!(from_millis(#millis))
+        } else {
+            let micros = Literal::u128_unsuffixed(budget.as_micros());
+            quote!(from_micros(#micros))
+        };
+        // Only compiled in the tests of crates without the configured feature
+        let cfg = match &self.settings.budget_feature {
+            Some(feature) => quote!(cfg(any(test, feature = #feature))),
+            None => quote!(cfg(test)),
+        };
+        let client = format_ident!("{}", self.client());
+        let info = self.info.as_str().into_tokens();
+        let check = FnBuilder::new("check_budget")
+            .attribute(cfg)
+            .async_if(self.ctx.is_async)
+            .generic(quote!(T))
+            .returns(quote!(Result<T, #client::BudgetExceeded>));
+        let check = if self.ctx.is_async {
+            check
+                .doc("Runs `fut`, e.g. this query bound to a seeded database, and fails if it takes longer than the budget of the query")
+                .param("fut", quote!(impl std::future::Future<Output = T>))
+                .body(quote!(#client::private::check_budget(&#info, Self::BUDGET, fut).await))
+        } else {
+            check
+                .doc("Runs `f`, e.g. this query bound to a seeded database, and fails if it takes longer than the budget of the query")
+                .param("f", quote!(impl FnOnce() -> T))
+                .body(quote!(#client::private::check_budget(&#info, Self::BUDGET, f)))
+        };
+        vec![
+            quote! {
+                #[doc = " Latency budget of the query"]
+                pub const BUDGET: std::time::Duration = std::time::Duration::#duration;
+            },
+            check.into_token_stream(),
+        ]
+    }
+
+    /// Function creating the statement of the query, with its struct and methods
+    fn gen_stmt<W: Write>(&self, w: &mut W) {
+        let client = self.client();
+        let stmt = format!("{}Stmt", self.struct_name);
+        let sql = escape_sql(&self.query.sql);
+        let new = FnBuilder::new(&self.query.ident.rs)
+            .attributes(deprecated_meta(&self.query.deprecated))
+            .returns(&stmt)
+            .body(format!("{stmt}({client}::private::Stmt::new(\"{sql}\"))"));
+        let item = StructBuilder::new(&stmt).tuple_field("", format!("{client}::private::Stmt"));
+        let mut methods = ImplBuilder::new(&stmt);
+        // The statements of the upstream client crates only hold static queries
+        if !self.settings.upstream_compat {
+            methods = methods.item(
+                FnBuilder::new("with_sql")
+                    .doc("Uses `sql`, e.g. composed at runtime, instead of the annotated query.")
+                    .doc("It is prepared as is, so it must take the same parameters and return the same columns.")
+                    .param("sql", "impl Into<std::borrow::Cow<'static, str>>")
+                    .returns("Self")
+                    .body(format!("Self({client}::private::Stmt::new(sql))")),
+            );
+        }
+        // The facade prefers the async statements when both are generated
+        if self.settings.gen_facade && (self.ctx.is_async || !self.settings.gen_async) {
+            methods = methods.item(
+                FnBuilder::new("with_cache")
+                    .doc("Shares the statements prepared on each connection through `cache`")
+                    .vis("pub(crate)")
+                    .receiver("self")
+                    .param(
+                        "cache",
+                        format!("&std::sync::Arc<{client}::private::StmtCache>"),
+                    )
+                    .returns("Self")
+                    .body("Self(self.0.with_cache(cache))"),
+            );
+        }
+        let methods = methods
+            .item(self.bind_fn())
+            .items(self.bind_chunked_fn())
+            .items(self.validate_fn())
+            .items(self.budget_items());
+        code!(w =>
+            $new
+            $item
+            $methods
+        );
+    }
+
+    /// Keyset paginated statement
+    fn gen_paginated_stmt<W: Write>(&self, w: &mut W) {
+        let (Some(paginate), Some((idx, index))) = (&self.query.paginate, &self.query.row) else {
+            return;
+        };
+        let (client, struct_name, ident) = (self.client(), &self.struct_name, &self.query.ident);
+        let item = self.module.rows.get_index(*idx).unwrap().1;
+        let (row_struct_name, _, _) = row_mapping(item, index, self.ctx);
+        let by: Vec<_> = paginate
+            .by
+            .iter()
+            .map(|col| {
+                let field = if item.is_named {
+                    &item.fields[index.iter().position(|it| it == col).unwrap()]
+                } else {
+                    &item.fields[0]
+                };
+                let value = if item.is_named {
+                    format!("it.{}.clone()", field.ident.rs)
+                } else {
+                    "it.clone()".to_string()
+                };
+                (&field.ident.rs, field.own_struct(self.ctx), value)
+            })
+            .collect();
+        let cursor_name = format!("{struct_name}Cursor");
+        let cursor = by.iter().fold(
+            StructBuilder::new(&cursor_name)
+                .doc(format!(
+                    "Position after the last row of a page of [`{}_page`]",
+                    ident.rs
+                ))
+                .derives(["Debug", "Clone", "PartialEq"]),
+            |item, (name, ty, _)| item.private_field(name, ty),
+        );
+        let stmt = format!("{struct_name}PageStmt");
+        let sql = escape_sql(&paginate.sql);
+        let new = FnBuilder::new(format!("{}_page", ident.rs))
+            .attributes(deprecated_meta(&self.query.deprecated))
+            .returns(&stmt)
+            .body(format!("{stmt}({client}::private::Stmt::new(\"{sql}\"))"));
+        let item = StructBuilder::new(&stmt).tuple_field("", format!("{client}::private::Stmt"));
+        let after = by
+            .iter()
+            .map(|it| format!("let {0} = after.map(|it| &it.{0});", it.0))
+            .collect::<String>();
+        let params = self
+            .params
+            .iter()
+            .map(|it| format!("{},", it.0))
+            .chain(by.iter().map(|it| format!("&{},", it.0)))
+            .collect::<String>();
+        let next = by
+            .iter()
+            .map(|(name, _, value)| format!("{name}: {value},"))
+            .collect::<String>();
+        let page = self
+            .bound_method("page")
+            .doc("Fetches up to `limit` rows ordered after the `after` cursor, or from the start without one")
+            .param("after", format!("Option<&'a {cursor_name}>"))
+            .param("limit", "i64")
+            .returns(format!(
+                "Result<{client}::Page<{row_struct_name}, {cursor_name}>, {}::Error>",
+                self.backend()
+            ))
+            .body(format!(
+                "{after} let query = {}; \
+                 let rows = query.all(){}?; \
+                 let next = if rows.len() as i64 == limit {{ rows.last().map(|it| {cursor_name} {{ {next} }}) }} else {{ None }}; \
+                 Ok({client}::Page {{ rows, next }})",
+                self.query_literal(*idx, index, &format!("{params} &limit")),
+                self.fn_await()
+            ));
+        let methods = ImplBuilder::new(&stmt).item(page);
+        code!(w =>
+            $new
+            $cursor
+            $item
+            $methods
+        );
+    }
+
+    /// Writer of the rows copied in
+    fn gen_copy_writer<W: Write>(&self, w: &mut W) {
+        let (backend, fn_await) = (self.backend(), self.fn_await());
+        let writer = format!("{}Writer", self.struct_name);
+        let params_wrap = self.order.iter().map(|idx| {
+            let p = &self.param_field[*idx];
+            format!("{},", p.ty.sql_wrapped(&p.ident.rs, self.ctx))
+        });
+        let params_wrap = params_wrap.collect::<String>();
+        let method = |name: &str| FnBuilder::new(name).async_if(self.ctx.is_async);
+        let (item, methods, write) = if self.ctx.is_async {
+            (
+                StructBuilder::new(&writer).tuple_field(
+                    "",
+                    "std::pin::Pin<Box<tokio_postgres::binary_copy::BinaryCopyInWriter>>",
+                ),
+                ImplBuilder::new(&writer),
+                format!("self.0.as_mut().write(&[{params_wrap}]).await"),
+            )
+        } else {
+            (
+                StructBuilder::new(&writer)
+                    .generic("'w")
+                    .tuple_field("", "postgres::binary_copy::BinaryCopyInWriter<'w>"),
+                ImplBuilder::new(format!("{writer}<'_>")),
+                format!("self.0.write(&[{params_wrap}])"),
+            )
+        };
+        let mut methods = methods.item(
+            method("write")
+                .generic("'a")
+                .generics(&self.traits)
+                .receiver("&mut self")
+                .params(self.params.iter().map(|(name, ty)| (name, ty)))
+                .returns(format!("Result<(), {backend}::Error>"))
+                .body(write),
+        );
+        if let Some(param) = self.param.filter(|it| it.is_named) {
+            let args = self
+                .params
+                .iter()
+                .map(|it| format!("&params.{},", it.0))
+                .collect::<String>();
+            methods = methods.item(
+                method("write_params")
+                    .generic("'a")
+                    .generics(&self.traits)
+                    .receiver("&mut self")
+                    .param("params", format!("&'a {}", self.param_ty(param)))
+                    .returns(format!("Result<(), {backend}::Error>"))
+                    .body(format!("self.write({args}){fn_await}")),
+            );
+        }
+        let (receiver, finish) = if self.ctx.is_async {
+            ("mut self", "self.0.as_mut().finish().await")
+        } else {
+            ("self", "self.0.finish()")
+        };
+        let methods = methods.item(
+            method("finish")
+                .doc("Completes the copy, returning the number of copied rows")
+                .receiver(receiver)
+                .returns(format!("Result<u64, {backend}::Error>"))
+                .body(finish),
+        );
+        code!(w =>
+            $item
+            $methods
+        );
+    }
+
+    /// Type of the named parameters struct `param`, generic over the traits of its fields
+    fn param_ty(&self, param: &PreparedItem) -> String {
+        let lifetime = if param.is_copy || !param.is_ref {
+            ""
+        } else {
+            "'a,"
+        };
+        let traits_idx = (1..=self.traits.len())
+            .map(|it| format!("{},", idx_char(it)))
+            .collect::<String>();
+        format!("{}<{lifetime}{traits_idx}>", param.path(self.ctx))
+    }
+
+    /// Binding of the named parameters struct, as an implementation of `Params`
+    fn gen_params_impl<W: Write>(&self, w: &mut W) {
+        let Some(param) = self.param.filter(|it| it.is_named) else {
+            return;
+        };
+        let PreparedQuery {
+            row, cardinality, ..
+        } = self.query;
+        let (client, backend, client_mut) = (self.client(), self.backend(), self.client_mut());
+        let param_ty = self.param_ty(param);
+        let args = self
+            .params
+            .iter()
+            .map(|it| format!("&params.{},", it.0))
+            .collect::<String>();
+        let (send_sync, ty, body) = if let (Some((idx, _)), None) = (row, cardinality) {
+            let name = &self.module.rows.get_index(*idx).unwrap().1.name;
+            (
+                "",
+                format!(
+                    "{name}Query<'a, C, {}, {}>",
+                    self.row_struct(*idx),
+                    self.param_field.len()
+                ),
+                format!("self.bind(client, {args})"),
+            )
+        } else {
+            // Rows fetched when bound, or the number of affected rows
+            let ty = match (row, cardinality) {
+                (Some((idx, _)), Some(cardinality)) => cardinality.ty(&self.row_struct(*idx)),
+                _ => "u64".to_string(),
+            };
+            if self.ctx.is_async {
+                (
+                    "+ Send + Sync",
+                    format!("std::pin::Pin<Box<dyn futures::Future<Output = Result<{ty}, {backend}::Error>> + Send + 'a>>"),
+                    format!("Box::pin(self.bind(client, {args}))"),
+                )
+            } else {
+                (
+                    "",
+                    format!("Result<{ty}, {backend}::Error>"),
+                    format!("self.bind(client, {args})"),
+                )
+            }
+        };
+        let item = ImplBuilder::new(format!("{}Stmt", self.struct_name))
+            .of_trait(format!("{client}::Params<'a, {param_ty}, {ty}, C>"))
+            .generics(["'a".to_string(), format!("C: GenericClient {send_sync}")])
+            .generics(&self.traits)
+            .item(
+                FnBuilder::new("params")
+                    .vis("")
+                    .receiver("&'a mut self")
+                    .param("client", format!("&'a {client_mut} C"))
+                    .param("params", format!("&'a {param_ty}"))
+                    .returns(ty)
+                    .body(body),
+            );
+        code!(w => $item);
+    }
+}
+
+/// Generic parameters of the traits at `traits`, named after their position
+fn trait_generics(traits: &[String]) -> Vec<String> {
+    traits
+        .iter()
+        .enumerate()
+        .map(|(idx, it)| format!("{}: {it}", idx_char(idx + 1)))
+        .collect()
+}
+
+fn gen_query_fn<W: Write>(
+    w: &mut W,
+    module: &PreparedModule,
+    query: &PreparedQuery,
+    ctx: &GenCtx,
+    settings: &CodegenSettings,
+) {
+    let gen = QueryGen::new(module, query, ctx, settings);
+    gen.gen_stmt(w);
+    if !query.slots.is_empty() {
+        gen_variants_fn(w, query, ctx);
+    }
+    gen.gen_paginated_stmt(w);
+    if let Some(CopyStatement::In(_)) = &query.copy {
+        gen.gen_copy_writer(w);
+        return;
+    }
+    gen.gen_params_impl(w);
+}
+
+/// Generates deprecated aliases of the rows renamed by their queries
+fn gen_renamed_rows(w: &mut impl Write, module: &PreparedModule) {
+    for query in module.queries.values() {
+        let (Some(old), Some((idx, _))) = (
+            query.renamed.as_ref().and_then(|it| it.row.as_ref()),
+            &query.row,
+        ) else {
+            continue;
+        };
+        let row = module.rows.get_index(*idx).unwrap().1;
+        let name = &row.name;
+        let deprecated = format!("#[deprecated(note = \"renamed to `{name}`\")]");
+        code!(w =>
+            $deprecated
+            pub type $old = $name;
+        );
+        if !row.is_copy {
+            code!(w =>
+                $deprecated
+                pub type ${old}Borrowed<'a> = ${name}Borrowed<'a>;
+            );
+        }
+    }
+}
+
+/// Generates the function creating the statement of the chosen variants of a query
+fn gen_variants_fn(w: &mut impl Write, query: &PreparedQuery, ctx: &GenCtx) {
+    let name = &query.ident.rs;
+    let struct_name = query.ident.type_ident();
+    let client = ctx.client_name();
+    let enums: Vec<_> = query
+        .slots
+        .iter()
+        .map(|slot| ctx.path(ctx.depth - 2, &slot.enum_name))
+        .collect();
+    let args = query.slots.iter().map(|slot| &slot.arg);
+    let args_ty = enums.iter();
+    let matched = query.slots.iter().map(|slot| &slot.arg);
+    // Combinations are ordered like the SQL variants, the last slot changing first
+    let mut combinations = vec![String::new()];
+    for (slot, path) in query.slots.iter().zip(&enums) {
+        combinations = combinations
+            .iter()
+            .flat_map(|prefix| {
+                slot.variants
+                    .iter()
+                    .map(move |variant| format!("{prefix}{path}::{variant},"))
+            })
+            .collect();
+    }
+    let arms = combinations
+        .iter()
+        .zip(&query.sql_variants)
+        .map(|(combination, sql)| format!("({combination}) => \"{}\",", escape_sql(sql)));
+    let deprecated = deprecated_attribute(&query.deprecated);
+    code!(w =>
+        /// Creates the statement of the given variants of the query
+        $deprecated
+        pub fn ${name}_with($($args: $args_ty,)) -> ${struct_name}Stmt {
+            ${struct_name}Stmt($client::private::Stmt::new(match ($($matched,)) {
+                $($arms)
+            }))
+        }
+    );
+}
+
+/// Generates the enums choosing the variants of the slots of the queries
+fn gen_slot_enums(w: &mut impl Write, module: &PreparedModule) {
+    for query in module.queries.values() {
+        for slot in &query.slots {
+            let enum_name = &slot.enum_name;
+            let default = &slot.variants[0];
+            let others = &slot.variants[1..];
+            writeln!(
+                w,
+                "/// Variants of the `{}` slot of the `{}` query",
+                slot.arg, query.ident.db
+            )
+            .unwrap();
+            code!(w =>
+                #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+                pub enum $enum_name {
+                    #[default]
+                    $default,
+                    $($others,)
+                }
+            );
+        }
+    }
+}
+
+/// Generates the error enum decoding the violations of the constraints declared by the
+/// `constraints` attribute of a module
+fn gen_constraint_errors<W: Write>(w: &mut W, module: &PreparedModule, settings: &CodegenSettings) {
+    if module.constraints.is_empty() {
+        return;
+    }
+    let backend = if settings.gen_async {
+        "tokio_postgres"
+    } else {
+        "postgres"
+    };
+    let variants = module.constraints.iter().map(|it| {
+        let kind = it
+            .code
+            .trim_end_matches("_VIOLATION")
+            .to_lowercase()
+            .replace('_', " ");
+        move |w: &mut W| {
+            writeln!(w, "/// Violation of the {kind} constraint `{}`", it.name).unwrap();
+            let variant = &it.variant;
+            code!(w => $variant($backend::Error),)
+        }
+    });
+    let conversions = module.constraints.iter().map(|it| {
+        let (name, variant, code) = (&it.name, &it.variant, &it.code);
+        move |w: &mut W| {
+            code!(w =>
+                if *code == SqlState::$code && constraint == Some("$name") {
+                    return Self::$variant(source);
+                }
+            )
+        }
+    });
+    let sources = module.constraints.iter().map(|it| &it.variant);
+    code!(w =>
+        /// Error of the queries of this module, decoding the violations of its constraints.
+        /// Converts from the client's error, e.g. with `?` or `map_err(ConstraintError::from)`.
+        #[derive(Debug)]
+        pub enum ConstraintError {
+            $($!variants)
+            /// Any other error
+            Other($backend::Error),
+        }
+
+        impl ConstraintError {
+            /// Underlying error of the client
+            pub fn inner(&self) -> &$backend::Error {
+                match self {
+                    $(Self::$sources(source) => source,)
+                    Self::Other(source) => source,
+                }
             }
+        }
+
+        impl From<$backend::Error> for ConstraintError {
+            fn from(source: $backend::Error) -> Self {
+                use $backend::error::SqlState;
+                let Some(db) = source.as_db_error() else {
+                    return Self::Other(source);
+                };
+                let (code, constraint) = (db.code(), db.constraint());
+                $($!conversions)
+                Self::Other(source)
+            }
+        }
+
+        impl std::fmt::Display for ConstraintError {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                std::fmt::Display::fmt(self.inner(), f)
+            }
+        }
+
+        impl std::error::Error for ConstraintError {
+            fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+                Some(self.inner())
+            }
+        }
+    );
+}
+
+/// Generates deprecated aliases of the renamed queries, and of the query structs of their
+/// renamed rows
+fn gen_renamed_queries(w: &mut impl Write, module: &PreparedModule) {
+    for query in module.queries.values() {
+        let Some(renamed) = &query.renamed else {
+            continue;
+        };
+        if let Some(old) = &renamed.query {
+            let name = &query.ident.rs;
+            let struct_name = query.ident.type_ident();
+            let old_struct_name = old.value.to_upper_camel_case();
+            let deprecated = format!("#[deprecated(note = \"renamed to `{name}`\")]");
+            code!(w =>
+                $deprecated
+                pub fn $old() -> ${struct_name}Stmt {
+                    $name()
+                }
+                $deprecated
+                pub type ${old_struct_name}Stmt = ${struct_name}Stmt;
+            );
+        }
+        if let (Some(old), Some((idx, _))) = (&renamed.row, &query.row) {
+            let name = &module.rows.get_index(*idx).unwrap().1.name;
+            let deprecated = format!("#[deprecated(note = \"renamed to `{name}Query`\")]");
+            code!(w =>
+                $deprecated
+                pub type ${old}Query<'a, C, T, const N: usize> = ${name}Query<'a, C, T, N>;
+            );
+        }
+    }
+}
+
+/// Generates type definitions for custom user types. This includes domains, composites and enums.
+/// If the type is not `Copy`, then a Borrowed version will be generated.
+fn gen_custom_type(
+    w: &mut impl Write,
+    schema: &str,
+    prepared: &PreparedType,
+    ctx: &GenCtx,
+    plugins: &[Arc<dyn Plugin>],
+) {
+    let PreparedType {
+        struct_name,
+        content,
+        is_copy,
+        is_params,
+        name,
+        ..
+    } = prepared;
+    let copy = if *is_copy { "Copy," } else { "" };
+    let ser_str = if ctx.gen_derive {
+        "serde::Serialize,"
+    } else {
+        ""
+    };
+    match content {
+        PreparedContent::Enum(variants) => {
+            let variants_ident = variants.iter().map(|v| &v.rs);
+            code!(w =>
+                #[derive($ser_str Debug, Clone, Copy, PartialEq, Eq)]
+                #[allow(non_camel_case_types)]
+                pub enum $struct_name {
+                    $($variants_ident,)
+                }
+            );
+            enum_sql(w, name, struct_name, variants);
+        }
+        PreparedContent::Composite(fields) => {
+            let fields_name = fields.iter().map(|p| &p.ident.rs);
+            let item = StructBuilder::new(struct_name)
+                .derive_if(ctx.gen_derive, "serde::Serialize")
+                .derives(["Debug", "postgres_types::FromSql"])
+                .derive_if(*is_copy, "Copy")
+                .derives(["Clone", "PartialEq"])
+                .attribute(format!("postgres(name = \"{name}\")"));
+            let item = fields.iter().fold(item, |item, p| {
+                item.attributed_field(
+                    format!("postgres(name = \"{}\")", p.ident.db),
+                    &p.ident.rs,
+                    p.own_struct(ctx),
+                )
+            });
+            code!(w => $item);
             if *is_copy {
                 struct_tosql(w, struct_name, fields, name, false, *is_params, ctx);
             } else {
                 let fields_owning = fields.iter().map(|p| p.owning_assign());
-                let fields_brw = fields.iter().map(|p| p.brw_ty(true, ctx));
+                let borrowed =
+                    borrowed_struct(format!("{struct_name}Borrowed"), fields, ctx).derive("Debug");
+                let rest = row_marker(fields, ctx).0.map_or("", |_| "..");
                 code!(w =>
-                    #[derive(Debug)]
-                    pub struct ${struct_name}Borrowed<'a> {
-                        $(pub $fields_name: $fields_brw,)
-                    }
+                    $borrowed
                     impl<'a> From<${struct_name}Borrowed<'a>> for $struct_name {
                         fn from(
                             ${struct_name}Borrowed {
-                            $($fields_name,)
+                            $($fields_name,) $rest
                             }: ${struct_name}Borrowed<'a>,
                         ) -> Self {
                             Self {
@@ -701,20 +1966,243 @@ fn gen_custom_type(w: &mut impl Write, schema: &str, prepared: &PreparedType, ct
                         }
                     }
                 );
-                composite_fromsql(w, struct_name, fields, name, schema);
+                composite_fromsql(w, struct_name, fields, name, schema, ctx);
                 if !is_params {
-                    let fields_ty = fields.iter().map(|p| p.param_ty(ctx));
-                    let derive = if *is_copy { ",Copy,Clone" } else { "" };
-                    code!(w =>
-                        #[derive(Debug $derive)]
-                        pub struct ${struct_name}Params<'a> {
-                            $(pub $fields_name: $fields_ty,)
-                        }
+                    let params = fields.iter().fold(
+                        StructBuilder::new(format!("{struct_name}Params"))
+                            .derive("Debug")
+                            .derives_if(*is_copy, ["Copy", "Clone"])
+                            .generic("'a"),
+                        |item, p| item.field(&p.ident.rs, p.param_ty(ctx)),
                     );
+                    code!(w => $params);
                 }
                 struct_tosql(w, struct_name, fields, name, true, *is_params, ctx);
             }
         }
+        PreparedContent::Newtype(inner) => {
+            let inner_own = inner.own_ty(false, ctx);
+            let ord = if inner.is_ord() {
+                "Eq, Hash, PartialOrd, Ord,"
+            } else {
+                ""
+            };
+            code!(w =>
+                #[derive($ser_str Debug, Clone, $copy PartialEq, $ord)]
+                pub struct $struct_name(pub $inner_own);
+                impl<'a> postgres_types::FromSql<'a> for $struct_name {
+                    fn from_sql(
+                        ty: &postgres_types::Type,
+                        raw: &'a [u8],
+                    ) -> Result<$struct_name, Box<dyn std::error::Error + Sync + Send>> {
+                        <$inner_own as postgres_types::FromSql>::from_sql(ty, raw).map($struct_name)
+                    }
+                    fn accepts(ty: &postgres_types::Type) -> bool {
+                        <$inner_own as postgres_types::FromSql>::accepts(ty)
+                    }
+                }
+                impl postgres_types::ToSql for $struct_name {
+                    fn to_sql(
+                        &self,
+                        ty: &postgres_types::Type,
+                        out: &mut postgres_types::private::BytesMut,
+                    ) -> Result<postgres_types::IsNull, Box<dyn std::error::Error + Sync + Send>> {
+                        postgres_types::ToSql::to_sql(&self.0, ty, out)
+                    }
+                    fn accepts(ty: &postgres_types::Type) -> bool {
+                        <$inner_own as postgres_types::ToSql>::accepts(ty)
+                    }
+                    fn to_sql_checked(
+                        &self,
+                        ty: &postgres_types::Type,
+                        out: &mut postgres_types::private::BytesMut,
+                    ) -> Result<postgres_types::IsNull, Box<dyn std::error::Error + Sync + Send>> {
+                        postgres_types::__to_sql_checked(self, ty, out)
+                    }
+                }
+            );
+            if !is_copy {
+                let inner_brw = inner.brw_ty(false, true, ctx);
+                code!(w =>
+                    impl<'a> From<$inner_brw> for $struct_name {
+                        fn from(v: $inner_brw) -> Self {
+                            Self(v.into())
+                        }
+                    }
+                );
+            }
+        }
+    }
+    let info = TypeInfo {
+        schema,
+        name,
+        struct_name,
+        content: match content {
+            PreparedContent::Enum(variants) => {
+                TypeContent::Enum(variants.iter().map(|v| v.rs.as_str()).collect())
+            }
+            PreparedContent::Composite(fields) => {
+                TypeContent::Composite(fields.iter().map(|p| p.info(p.own_struct(ctx))).collect())
+            }
+            PreparedContent::Newtype(inner) => TypeContent::Newtype(inner.own_ty(false, ctx)),
+        },
+    };
+    for code in plugins.iter().filter_map(|it| it.custom_type(&info)) {
+        w.write_str(&code).unwrap();
+    }
+}
+
+/// Generates a module's facade, sharing the statements it returns between its clones
+fn gen_module_facade<W: Write>(w: &mut W, module: &PreparedModule, settings: &CodegenSettings) {
+    // Web frameworks are asynchronous, so prefer the async statements when both are generated
+    let prefix = if settings.gen_async && settings.gen_sync {
+        "async_::"
+    } else {
+        ""
+    };
+    let client = if settings.gen_async {
+        "cornucopia_async"
+    } else {
+        "cornucopia_sync"
+    };
+    let methods = module.queries.values().map(|query| {
+        move |w: &mut W| {
+            let name = &query.ident.rs;
+            let struct_name = query.ident.type_ident();
+            let deprecated = deprecated_attribute(&query.deprecated);
+            code!(w =>
+                $deprecated
+                pub fn $name(&self) -> ${prefix}${struct_name}Stmt {
+                    $prefix$name().with_cache(&self.cache)
+                }
+            )
+        }
+    });
+    code!(w =>
+        /// Statements of this module, prepared lazily once per connection and shared by the
+        /// clones of `Queries`, which are cheap
+        #[derive(Debug, Clone, Default)]
+        pub struct Queries {
+            cache: std::sync::Arc<$client::private::StmtCache>,
+        }
+        impl Queries {
+            $($!methods)
+        }
+    );
+}
+
+/// Generates a module's bundle owning all its statements, which can be prepared up front
+fn gen_module_statements<W: Write>(w: &mut W, module: &PreparedModule, ctx: &GenCtx) {
+    let (client_mut, fn_async, backend) = if ctx.is_async {
+        ("", "async", "tokio_postgres")
+    } else {
+        ("mut", "", "postgres")
+    };
+    let names: Vec<_> = module.queries.values().map(|it| &it.ident.rs).collect();
+    let types = module
+        .queries
+        .values()
+        .map(|it| format!("{}Stmt", it.ident.type_ident()));
+    let prepare = |w: &mut W| {
+        // Scripts are never prepared
+        let names = module
+            .queries
+            .values()
+            .filter(|it| !it.is_script)
+            .map(|it| &it.ident.rs)
+            .collect::<Vec<_>>();
+        if !ctx.is_async {
+            code!(w => $(self.$names.0.prepare(client)?;))
+        } else if !names.is_empty() {
+            // Concurrent statements are pipelined by tokio_postgres
+            code!(w => futures::future::try_join_all([$(self.$names.0.prepare(client),)]).await?;)
+        }
+    };
+    let item = names.iter().zip(types).fold(
+        StructBuilder::new("Statements")
+            .doc("Every statement of this module. Statements are prepared on the connection")
+            .doc("they are first run on, so keep one bundle per connection."),
+        |item, (name, ty)| item.field(name, ty),
+    );
+    let (new_name, new_fn) = (names.iter(), names.iter());
+    code!(w =>
+        $item
+        impl Statements {
+            pub fn new() -> Self {
+                Self {
+                    $($new_name: $new_fn(),)
+                }
+            }
+
+            /// Prepares every statement on `client`
+            pub $fn_async fn prepare_all<C: GenericClient>(&mut self, client: &$client_mut C) -> Result<(), $backend::Error> {
+                $!prepare
+                Ok(())
+            }
+        }
+    );
+}
+
+/// Generates the helper applying the session settings of a module
+fn gen_configure_fn<W: Write>(w: &mut W, module: &PreparedModule, ctx: &GenCtx) {
+    if module.session.is_empty() {
+        return;
+    }
+    let (client_mut, fn_async, fn_await, backend) = if ctx.is_async {
+        ("", "async", ".await", "tokio_postgres")
+    } else {
+        ("mut", "", "", "postgres")
+    };
+    let listed = module
+        .session
+        .iter()
+        .map(|(name, value)| format!("`{name} = {value}`"))
+        .collect::<Vec<_>>()
+        .join(", ");
+    writeln!(
+        w,
+        "/// Applies the session settings the queries of this module rely on: {listed}"
+    )
+    .unwrap();
+    let settings = module
+        .session
+        .iter()
+        .map(|(name, value)| format!("({name:?}, {value:?})"));
+    let sql = "SELECT set_config($1, $2, false)";
+    code!(w =>
+        pub $fn_async fn configure<C: GenericClient>(client: &$client_mut C) -> Result<(), $backend::Error> {
+            for (name, value) in [$($settings,)] {
+                client.execute("$sql", &[&name, &value])$fn_await?;
+            }
+            Ok(())
+        }
+    );
+}
+
+/// Generates the refresh helpers of the materialized views
+fn gen_refresh_fns<W: Write>(w: &mut W, views: &[String], ctx: &GenCtx) {
+    let (client_mut, fn_async, fn_await, backend) = if ctx.is_async {
+        ("", "async", ".await", "tokio_postgres")
+    } else {
+        ("mut", "", "", "postgres")
+    };
+    for view in views {
+        let name = Ident::new(materialized_view_name(view).to_string());
+        let name = &name.rs;
+        let escaped = view.replace('"', "\\\"");
+        code!(w =>
+            /// Refreshes the `$escaped` materialized view. A concurrent refresh doesn't block
+            /// reads of the view, but requires a unique index on it.
+            pub $fn_async fn refresh_$name<C: GenericClient>(client: &$client_mut C, concurrently: bool) -> Result<(), $backend::Error> {
+                let sql = if concurrently {
+                    "REFRESH MATERIALIZED VIEW CONCURRENTLY $escaped"
+                } else {
+                    "REFRESH MATERIALIZED VIEW $escaped"
+                };
+                client.execute(sql, &[])$fn_await?;
+                Ok(())
+            }
+        );
     }
 }
 
@@ -722,12 +2210,13 @@ fn gen_type_modules<W: Write>(
     w: &mut W,
     prepared: &IndexMap<String, Vec<PreparedType>>,
     ctx: &GenCtx,
+    plugins: &[Arc<dyn Plugin>],
 ) {
     let modules = prepared.iter().map(|(schema, types)| {
         move |w: &mut W| {
             let lazy = |w: &mut W| {
                 for ty in types {
-                    gen_custom_type(w, schema, ty, ctx)
+                    gen_custom_type(w, schema, ty, ctx, plugins)
                 }
             };
 
@@ -748,28 +2237,312 @@ fn gen_type_modules<W: Write>(
     );
 }
 
-pub(crate) fn generate(preparation: Preparation, settings: CodegenSettings) -> String {
-    let mut buff = "// This file was generated with `cornucopia`. Do not modify.\n\n".to_string();
+/// Generates a module re-exporting the custom types, the params and row structs and the client
+/// traits. Names generated more than once are left out, as they would be ambiguous.
+fn gen_prelude(w: &mut String, preparation: &Preparation, settings: &CodegenSettings) {
+    let types = preparation.types.iter().flat_map(|(schema, types)| {
+        types
+            .iter()
+            .map(move |ty| ("pub", format!("types::{schema}"), &ty.struct_name))
+    });
+    let items = preparation
+        .modules
+        .iter()
+        // Modules only visible to `queries` can't be re-exported
+        .filter(|module| module_visibility(settings, &module.info.name) != "pub(super)")
+        .flat_map(|module| {
+            let name = &module.info.name;
+            let vis = module_visibility(settings, name);
+            module
+                .params
+                .values()
+                .chain(module.rows.values())
+                // Imported structs are re-exported from their own module, and deprecated ones
+                // are left out of the flat imports
+                .filter(|it| it.is_named && it.imported.is_none() && it.deprecated.is_none())
+                .map(move |it| (vis, format!("queries::{name}"), &it.name.value))
+        });
+    let items: Vec<_> = types.chain(items).collect();
+    let uses = items
+        .iter()
+        .filter(|(_, _, name)| items.iter().filter(|it| it.2 == *name).count() == 1)
+        .map(|(vis, path, name)| format!("{vis} use super::{path}::{name};"));
+    let mut traits = Vec::new();
+    if settings.gen_async {
+        traits.push("pub use cornucopia_async::{GenericClient, Params as _};");
+    }
+    if settings.gen_sync {
+        traits.push(if settings.gen_async {
+            "pub use cornucopia_sync::Params as _;"
+        } else {
+            "pub use cornucopia_sync::{GenericClient, Params as _};"
+        });
+    }
+    code!(w =>
+        /// Flat re-exports of the generated items, to import them all with `use prelude::*`
+        #[allow(unused_imports)]
+        pub mod prelude {
+            $($traits)
+            $($uses)
+        }
+    );
+}
+
+/// Visibility of the query module `name`
+fn module_visibility(settings: &CodegenSettings, name: &str) -> &'static str {
+    settings
+        .modules
+        .get(name)
+        .and_then(|it| it.visibility)
+        .map_or("pub", Visibility::as_str)
+}
+
+/// Generates an error enum classifying the errors of the database by their SQLSTATE
+fn gen_db_errors(w: &mut String, settings: &CodegenSettings) {
+    let backend = if settings.gen_async {
+        "tokio_postgres"
+    } else {
+        "postgres"
+    };
+    code!(w =>
+        #[allow(clippy::all, clippy::pedantic)]
+        #[allow(dead_code)]
+        pub mod errors {
+            use $backend::error::{Error, SqlState};
+
+            /// Error of a query, classified by the SQLSTATE code returned by the database.
+            /// Converts from the client's error, e.g. with `?` or `map_err(QueryError::from)`.
+            #[derive(Debug)]
+            pub enum QueryError {
+                /// A unique constraint or index would be violated (23505)
+                UniqueViolation { constraint: Option<String>, source: Error },
+                /// A foreign key would be violated (23503)
+                ForeignKeyViolation { constraint: Option<String>, source: Error },
+                /// A check constraint would be violated (23514)
+                CheckViolation { constraint: Option<String>, source: Error },
+                /// An exclusion constraint would be violated (23P01)
+                ExclusionViolation { constraint: Option<String>, source: Error },
+                /// A null value would be stored in a non-nullable column (23502)
+                NotNullViolation { column: Option<String>, source: Error },
+                /// The transaction could not be serialized and may be retried (40001)
+                SerializationFailure(Error),
+                /// The transaction was part of a deadlock and may be retried (40P01)
+                DeadlockDetected(Error),
+                /// Any other error
+                Other(Error),
+            }
+
+            impl QueryError {
+                /// Name of the violated constraint, if any
+                pub fn constraint(&self) -> Option<&str> {
+                    match self {
+                        Self::UniqueViolation { constraint, .. }
+                        | Self::ForeignKeyViolation { constraint, .. }
+                        | Self::CheckViolation { constraint, .. }
+                        | Self::ExclusionViolation { constraint, .. } => constraint.as_deref(),
+                        _ => None,
+                    }
+                }
+
+                /// Whether running the transaction again may succeed
+                pub fn is_retryable(&self) -> bool {
+                    matches!(self, Self::SerializationFailure(_) | Self::DeadlockDetected(_))
+                }
+
+                /// Underlying error of the client
+                pub fn inner(&self) -> &Error {
+                    match self {
+                        Self::UniqueViolation { source, .. }
+                        | Self::ForeignKeyViolation { source, .. }
+                        | Self::CheckViolation { source, .. }
+                        | Self::ExclusionViolation { source, .. }
+                        | Self::NotNullViolation { source, .. }
+                        | Self::SerializationFailure(source)
+                        | Self::DeadlockDetected(source)
+                        | Self::Other(source) => source,
+                    }
+                }
+            }
+
+            impl From<Error> for QueryError {
+                fn from(source: Error) -> Self {
+                    let Some(db) = source.as_db_error() else {
+                        return Self::Other(source);
+                    };
+                    let code = db.code();
+                    let constraint = db.constraint().map(str::to_string);
+                    if *code == SqlState::UNIQUE_VIOLATION {
+                        Self::UniqueViolation { constraint, source }
+                    } else if *code == SqlState::FOREIGN_KEY_VIOLATION {
+                        Self::ForeignKeyViolation { constraint, source }
+                    } else if *code == SqlState::CHECK_VIOLATION {
+                        Self::CheckViolation { constraint, source }
+                    } else if *code == SqlState::EXCLUSION_VIOLATION {
+                        Self::ExclusionViolation { constraint, source }
+                    } else if *code == SqlState::NOT_NULL_VIOLATION {
+                        let column = db.column().map(str::to_string);
+                        Self::NotNullViolation { column, source }
+                    } else if *code == SqlState::T_R_SERIALIZATION_FAILURE {
+                        Self::SerializationFailure(source)
+                    } else if *code == SqlState::T_R_DEADLOCK_DETECTED {
+                        Self::DeadlockDetected(source)
+                    } else {
+                        Self::Other(source)
+                    }
+                }
+            }
+
+            impl std::fmt::Display for QueryError {
+                fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                    std::fmt::Display::fmt(self.inner(), f)
+                }
+            }
+
+            impl std::error::Error for QueryError {
+                fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+                    Some(self.inner())
+                }
+            }
+        }
+    );
+}
+
+/// Expression building the postgres type `ty`, down to the types of its fields and elements
+fn pg_type_expr(ty: &Type) -> String {
+    if Type::from_oid(ty.oid()).is_some() {
+        return format!("postgres_types::Type::from_oid({}).unwrap()", ty.oid());
+    }
+    let kind = match ty.kind() {
+        Kind::Enum(variants) => {
+            let variants = variants.iter().map(|it| format!("{it:?}.to_string()"));
+            format!("Enum(vec![{}])", variants.collect::<Vec<_>>().join(", "))
+        }
+        Kind::Array(inner) => format!("Array({})", pg_type_expr(inner)),
+        Kind::Range(inner) => format!("Range({})", pg_type_expr(inner)),
+        Kind::Multirange(inner) => format!("Multirange({})", pg_type_expr(inner)),
+        Kind::Domain(inner) => format!("Domain({})", pg_type_expr(inner)),
+        Kind::Composite(fields) => {
+            let fields = fields.iter().map(|it| {
+                let ty = pg_type_expr(it.type_());
+                format!(
+                    "postgres_types::Field::new({:?}.to_string(), {ty})",
+                    it.name()
+                )
+            });
+            format!("Composite(vec![{}])", fields.collect::<Vec<_>>().join(", "))
+        }
+        _ => "Simple".to_string(),
+    };
+    // The oids of custom types differ between databases and aren't needed to decode them
+    format!(
+        "postgres_types::Type::new({:?}.to_string(), 0, postgres_types::Kind::{kind}, {:?}.to_string())",
+        ty.name(),
+        ty.schema()
+    )
+}
+
+/// Generates entry points for `cargo fuzz` targets, decoding the composite types from arbitrary bytes
+fn gen_fuzz(w: &mut String, types: &IndexMap<String, Vec<PreparedType>>, ctx: &GenCtx) {
+    let modules = types.iter().map(|(schema, types)| {
+        let composites = types
+            .iter()
+            .filter(|it| matches!(it.content, PreparedContent::Composite(_)));
+        move |w: &mut String| {
+            let fns = composites.map(|ty| {
+                move |w: &mut String| {
+                    let PreparedType {
+                        name,
+                        struct_name,
+                        pg_ty,
+                        is_copy,
+                        ..
+                    } = ty;
+                    let path = custom_ty_path(schema, struct_name, ctx);
+                    let brw = if *is_copy {
+                        path
+                    } else {
+                        format!("{path}Borrowed")
+                    };
+                    let ty = pg_type_expr(pg_ty);
+                    let fn_name = Ident::new(name.clone()).rs;
+                    code!(w =>
+                        /// Decodes `data` as a `$schema.$name`, which must fail without panicking
+                        /// when `data` isn't a valid value.
+                        pub fn $fn_name(data: &[u8]) {
+                            let ty = $ty;
+                            let _ = <$brw as postgres_types::FromSql>::from_sql(&ty, data);
+                        }
+                    );
+                }
+            });
+            code!(w =>
+                pub mod $schema {
+                    $($!fns)
+                }
+            );
+        }
+    });
+    code!(w =>
+        /// Entry points of `cargo fuzz` targets exercising the decoders of the composite types
+        /// with arbitrary bytes, e.g. `fuzz_target!(|data: &[u8]| fuzz::public::point(data));`
+        #[allow(clippy::all, clippy::pedantic)]
+        #[allow(dead_code)]
+        pub mod fuzz {
+            $($!modules)
+        }
+    );
+}
+
+/// First lines of the generated code
+pub(crate) const HEADER: &str = "// This file was generated with `cornucopia`. Do not modify.\n\n";
+
+/// Top of the generated file: the configured header, e.g. a license notice or `#![allow(...)]`
+/// attributes, followed by cornucopia's notice
+pub(crate) fn header(settings: &CodegenSettings) -> String {
+    let custom = settings.header.trim_end();
+    if custom.is_empty() {
+        HEADER.to_string()
+    } else {
+        format!("{custom}\n{HEADER}")
+    }
+}
+
+pub(crate) fn generate(preparation: Preparation, settings: &CodegenSettings) -> String {
+    // The upstream client crates can't instrument queries, nor share statements
+    let settings = &CodegenSettings {
+        gen_tracing: settings.gen_tracing && !settings.upstream_compat,
+        gen_facade: settings.gen_facade && !settings.upstream_compat,
+        ..settings.clone()
+    };
+    let mut buff = header(settings);
     let w = &mut buff;
     // Generate database type
     gen_type_modules(
         w,
         &preparation.types,
         &GenCtx::new(1, settings.gen_async, settings.derive_ser),
+        &settings.plugins,
     );
     // Generate queries
     let query_modules = preparation.modules.iter().map(|module| {
         move |w: &mut String| {
             let name = &module.info.name;
+            let settings = &settings.for_module(name);
+            let vis = module_visibility(settings, name);
+            let derives = settings.modules.get(name).map_or(&[][..], |it| &it.derives);
             let ctx = GenCtx::new(2, settings.gen_async, settings.derive_ser);
             let params_string = module
                 .params
                 .values()
-                .map(|params| |w: &mut String| gen_params_struct(w, params,  &ctx));
+                .map(|params| |w: &mut String| gen_params_struct(w, name, params, &ctx, &settings.plugins));
             let rows_struct_string = module
                 .rows
                 .values()
-                .map(|row| |w: &mut String| gen_row_structs(w, row,  &ctx));
+                .map(|row| |w: &mut String| gen_row_structs(w, name, row, derives, &ctx, &settings.plugins));
+
+            let renamed_rows = |w: &mut String| gen_renamed_rows(w, module);
+            let slot_enums = |w: &mut String| gen_slot_enums(w, module);
+            let constraint_errors = |w: &mut String| gen_constraint_errors(w, module, settings);
 
             let sync_specific = |w: &mut String| {
                 let gen_specific = |depth: u8, is_async: bool| {
@@ -778,19 +2551,35 @@ pub(crate) fn generate(preparation: Preparation, settings: CodegenSettings) -> S
                         let import = if is_async {
                             "use futures::{StreamExt, TryStreamExt};use futures; use cornucopia_async::GenericClient;"
                         } else {
-                            "use postgres::{fallible_iterator::FallibleIterator,GenericClient};"
+                            "use postgres::fallible_iterator::FallibleIterator; use cornucopia_sync::GenericClient;"
                         };
                         let rows_query_string = module
                             .rows
                             .values()
-                            .map(|row| |w: &mut String| gen_row_query(w, row, &ctx));
+                            .map(|row| |w: &mut String| gen_row_query(w, row, &ctx, settings));
                         let queries_string = module.queries.values().map(|query| {
-                            |w: &mut String| gen_query_fn(w, module, query, &ctx)
+                            |w: &mut String| gen_query_fn(w, module, query, &ctx, settings)
                         });
+                        let renamed = |w: &mut String| gen_renamed_queries(w, module);
+                        let statements = |w: &mut String| {
+                            if settings.gen_prepare_all {
+                                gen_module_statements(w, module, &ctx)
+                            }
+                        };
+                        let configure = |w: &mut String| gen_configure_fn(w, module, &ctx);
+                        let refresh = |w: &mut String| {
+                            if name == MATERIALIZED_VIEWS_MODULE {
+                                gen_refresh_fns(w, &settings.materialized_views, &ctx)
+                            }
+                        };
                         code!(w =>
                             $import
                             $($!rows_query_string)
                             $($!queries_string)
+                            $!renamed
+                            $!configure
+                            $!refresh
+                            $!statements
                         )
                     }
                 };
@@ -818,23 +2607,166 @@ pub(crate) fn generate(preparation: Preparation, settings: CodegenSettings) -> S
                 }
             };
 
+            let facade = |w: &mut String| {
+                if settings.gen_facade {
+                    gen_module_facade(w, module, settings)
+                }
+            };
+
             code!(w =>
-                pub mod $name {
+                $vis mod $name {
                     $($!params_string)
                     $($!rows_struct_string)
+                    $!renamed_rows
+                    $!slot_enums
+                    $!constraint_errors
                     $!sync_specific
+                    $!facade
                 }
             );
         }
     });
+    let facade = |w: &mut String| {
+        if settings.gen_facade {
+            let fields = preparation.modules.iter().map(|module| {
+                move |w: &mut String| {
+                    let name = &module.info.name;
+                    code!(w => $name: $name::Queries,)
+                }
+            });
+            let methods = preparation.modules.iter().map(|module| {
+                move |w: &mut String| {
+                    let name = &module.info.name;
+                    let vis = module_visibility(settings, name);
+                    code!(w =>
+                        $vis fn $name(&self) -> $name::Queries {
+                            self.$name.clone()
+                        }
+                    )
+                }
+            });
+            code!(w =>
+                /// Statements of every module, to share between threads (e.g. as axum's `State`
+                /// or actix's `Data`). They are prepared lazily once per connection, and shared by
+                /// the clones of `Queries`, which are cheap. The clients with their own statement
+                /// cache, like deadpool's, use it instead.
+                #[derive(Debug, Clone, Default)]
+                pub struct Queries {
+                    $($!fields)
+                }
+                impl Queries {
+                    $($!methods)
+                }
+            );
+        }
+    };
+    let statements = |w: &mut String| {
+        if settings.gen_prepare_all {
+            // Prefer the async statements when both are generated, like the facade
+            let (fn_async, client, backend) = if settings.gen_async {
+                ("async", "&C", "tokio_postgres")
+            } else {
+                ("", "&mut C", "postgres")
+            };
+            let generic_client = if settings.gen_async {
+                "cornucopia_async::GenericClient"
+            } else {
+                "cornucopia_sync::GenericClient"
+            };
+            // Modules which don't generate this flavour are left out
+            let modules: Vec<_> = preparation
+                .modules
+                .iter()
+                .map(|it| (&it.info.name, settings.for_module(&it.info.name)))
+                .filter(|(_, module)| {
+                    if settings.gen_async {
+                        module.gen_async
+                    } else {
+                        module.gen_sync
+                    }
+                })
+                .collect();
+            let names: Vec<_> = modules.iter().map(|(name, _)| *name).collect();
+            let prepare = |w: &mut String| {
+                let names = names.iter();
+                if !settings.gen_async {
+                    code!(w => $(self.$names.prepare_all(client)?;))
+                } else if names.len() > 0 {
+                    code!(w => futures::try_join!($(self.$names.prepare_all(client),))?;)
+                }
+            };
+            let paths: Vec<_> = modules
+                .iter()
+                .map(|(name, module)| {
+                    let prefix = if settings.gen_async && module.gen_sync {
+                        "async_::"
+                    } else {
+                        ""
+                    };
+                    format!("{name}::{prefix}Statements")
+                })
+                .collect();
+            let vis = modules
+                .iter()
+                .map(|(name, _)| module_visibility(settings, name));
+            let item = names.iter().zip(&paths).zip(vis).fold(
+                StructBuilder::new("Statements")
+                    .doc("Every statement, to prepare them all up front with `prepare_all`. This gives a predictable")
+                    .doc("latency on first use and surfaces schema drift at startup. Prepared statements belong to")
+                    .doc("the connection they were prepared on, so keep one bundle per connection."),
+                |item, ((name, ty), vis)| item.restricted_field(vis, name, ty),
+            );
+            let (new_name, new_ty) = (names.iter(), paths.iter());
+            code!(w =>
+                $item
+                impl Statements {
+                    pub fn new() -> Self {
+                        Self {
+                            $($new_name: $new_ty::new(),)
+                        }
+                    }
+
+                    /// Prepares every statement on `client`
+                    pub $fn_async fn prepare_all<C: $generic_client>(&mut self, client: $client) -> Result<(), $backend::Error> {
+                        $!prepare
+                        Ok(())
+                    }
+                }
+            );
+        }
+    };
+    // The generated code uses the deprecated queries and structs, only their users are warned
+    let is_deprecated = preparation
+        .modules
+        .iter()
+        .any(|module| module.queries.values().any(|it| it.deprecated.is_some()));
+    let allow_deprecated = if is_deprecated {
+        "#[allow(deprecated)]"
+    } else {
+        ""
+    };
     code!(w =>
         #[allow(clippy::all, clippy::pedantic)]
         #[allow(unused_variables)]
         #[allow(unused_imports)]
         #[allow(dead_code)]
+        $allow_deprecated
         pub mod queries {
             $($!query_modules)
+            $!facade
+            $!statements
         }
     );
+    if settings.gen_db_errors {
+        gen_db_errors(w, settings);
+    }
+    if settings.gen_fuzz {
+        gen_fuzz(
+            w,
+            &preparation.types,
+            &GenCtx::new(2, settings.gen_async, settings.derive_ser),
+        );
+    }
+    gen_prelude(w, &preparation, settings);
     buff
 }
@@ -0,0 +1,844 @@
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+use miette::{NamedSource, SourceSpan};
+use serde::{de::IgnoredAny, Deserialize};
+use toml::Spanned;
+
+use self::error::Error;
+
+/// Settings read from a `cornucopia.toml` configuration file.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// Folders of queries read along with the main one, e.g. next to the features owning them.
+    /// Their modules named like a previous one are named after their folder too, e.g.
+    /// `billing_invoices` for `crates/billing/queries/invoices.sql`.
+    pub extra_queries: Vec<PathBuf>,
+    /// Lines written at the top of the generated file, before cornucopia's notice, e.g. a
+    /// mandatory license header, `#![allow(...)]` attributes, `//!` module docs or `use`
+    /// preludes, usually as a multi-line string.
+    pub header: String,
+    /// File caching the preparation of the query modules, e.g. `target/cornucopia-cache.json`.
+    /// Only the modules whose content, settings, or the relations and types they use, changed
+    /// since the previous generation are then prepared again. Unused when `verify_nullability`
+    /// is set, as it reads the data.
+    pub cache: Option<PathBuf>,
+    /// Globs of the query files read, relative to their folder, e.g. `prod_*.sql`. `*` doesn't
+    /// match `/`, unlike `**`. All the files are read if empty.
+    pub include_queries: Vec<String>,
+    /// Globs of the query files skipped even when included, e.g. `*_draft.sql`, so that
+    /// experiments and fixtures living next to the queries aren't generated.
+    pub exclude_queries: Vec<String>,
+    /// Columns wrapped in a generated newtype, keyed by their `schema.table.column` path.
+    /// Columns sharing the same newtype name share the same generated type.
+    pub newtypes: BTreeMap<String, String>,
+    /// Short text or `bytea` columns whose owned row fields hold their value inline, as an
+    /// `arrayvec::ArrayString<N>` or a `[u8; N]`, keyed by their `schema.table.column` path.
+    /// Reading rows then doesn't allocate for them.
+    pub inline_columns: BTreeMap<String, String>,
+    /// Types of extensions, e.g. `citext` or `ltree`, keyed by their `schema.name` path. They are
+    /// decoded as `String`, borrowed as `&str`, or as the given Rust type implementing `FromSql`
+    /// and `ToSql`, e.g. `crate::Isbn`.
+    pub extension_types: BTreeMap<String, String>,
+    /// Rust types implementing `FromSql` and `ToSql` which decode and encode the values of some
+    /// Postgres types, e.g. those of PostGIS or pgvector, keyed by their `schema.name` path in
+    /// `[codecs."schema.name"]` sections. They take precedence over the built-in mappings.
+    /// Keyed by a `schema.table.column` path instead, they only apply to that column, e.g. a
+    /// `timestamptz` column decoded as `time::OffsetDateTime` while the others stay as they are.
+    pub codecs: BTreeMap<String, Codec>,
+    /// Rust type of the `money` columns and parameters.
+    pub money: MoneyType,
+    /// Rust type of the `interval` columns and parameters.
+    pub interval: IntervalType,
+    /// Rust type of the `bit(n)` and `bit varying` columns and parameters.
+    pub bit: BitType,
+    /// Maximum number of elements sent at once by the generated `bind_chunked` methods, which
+    /// split the array parameter of queries using `= ANY(...)`. No such methods are generated if unset.
+    pub any_chunk_size: Option<usize>,
+    /// Cargo feature of the crate including the generated code, e.g. `budget`, enabling the
+    /// `check_budget` methods of the queries with a `--# budget` attribute outside of its tests.
+    /// They are only compiled in tests if unset.
+    pub budget_feature: Option<String>,
+    /// Record the SQL of queries in the `db.statement` attribute of their tracing span.
+    pub trace_statement: bool,
+    /// Materialized views, optionally schema-qualified, for which typed rows and
+    /// `refresh_<view>` helpers are generated in the `materialized_views` module.
+    pub materialized_views: Vec<String>,
+    /// Tables for which row structs holding all their columns, along with queries selecting
+    /// their rows, are generated in the `tables` module.
+    pub tables: TablesConfig,
+    /// Execute the parameterless selects against the database's data when generating, failing
+    /// if their first 10,000 rows have null in columns that aren't declared nullable. Better
+    /// left unset against a live production database, whose data it reads.
+    pub verify_nullability: bool,
+    /// Check that the `/*+ ... */` plan hints of queries start them, where pg_hint_plan reads
+    /// them, and only use its hints.
+    pub validate_hints: bool,
+    /// Levels of the lints of `cornucopia lint`, keyed by lint name in the `[lint]` section.
+    pub lint: LintConfig,
+    /// Overrides of the code generation settings for some query modules, keyed by module name
+    /// in `[module."name"]` sections.
+    #[serde(rename = "module")]
+    pub modules: BTreeMap<String, ModuleConfig>,
+    /// Databases generated into their own module by `cornucopia databases`, keyed by module name
+    /// in `[database."name"]` sections. The other settings apply to each of them.
+    #[serde(rename = "database")]
+    pub databases: BTreeMap<String, DatabaseConfig>,
+}
+
+/// Database whose queries are generated into their own module, alongside other databases.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DatabaseConfig {
+    /// Postgres url to a live database, used instead of schema files.
+    pub url: Option<String>,
+    /// SQL files containing the database schema, loaded in a database of Cornucopia's container.
+    pub schema: Vec<PathBuf>,
+    /// Folder containing the queries of the database [default: queries/<name>]
+    pub queries: Option<PathBuf>,
+}
+
+impl DatabaseConfig {
+    /// Folder containing the queries of the database `name`
+    pub(crate) fn queries_path(&self, name: &str) -> PathBuf {
+        self.queries
+            .clone()
+            .unwrap_or_else(|| Path::new("queries").join(name))
+    }
+}
+
+/// Tables whose row structs are generated in the `tables` module. None are by default.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TablesConfig {
+    /// Schemas whose tables all get a row struct.
+    pub schemas: Vec<String>,
+    /// Tables getting a row struct, by their `schema.table` path.
+    pub include: Vec<String>,
+    /// Tables of `schemas` not getting one, by their `schema.table` path.
+    pub exclude: Vec<String>,
+}
+
+impl TablesConfig {
+    pub(crate) fn is_empty(&self) -> bool {
+        self.schemas.is_empty() && self.include.is_empty()
+    }
+}
+
+/// Level of a lint of `cornucopia lint`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LintLevel {
+    /// Not reported
+    Allow,
+    /// Reported, without failing
+    Warn,
+    /// Reported, failing the lint
+    Deny,
+}
+
+/// Levels of the lints of `cornucopia lint`, all warning by default but `missing_cardinality`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LintConfig {
+    /// Queries selecting or returning `*`, whose rows change along with the schema.
+    pub select_star: LintLevel,
+    /// Declared params which the SQL of their query doesn't bind.
+    pub unused_param: LintLevel,
+    /// Named params and row types which no query uses.
+    pub unused_type: LintLevel,
+    /// Queries named like a query of another module.
+    pub duplicate_query: LintLevel,
+    /// Queries returning rows without a `cardinality` attribute.
+    pub missing_cardinality: LintLevel,
+    /// Output columns named like a previous one, which the row can't hold both.
+    pub shadowed_column: LintLevel,
+}
+
+impl Default for LintConfig {
+    fn default() -> Self {
+        Self {
+            select_star: LintLevel::Warn,
+            unused_param: LintLevel::Warn,
+            unused_type: LintLevel::Warn,
+            duplicate_query: LintLevel::Warn,
+            missing_cardinality: LintLevel::Allow,
+            shadowed_column: LintLevel::Warn,
+        }
+    }
+}
+
+/// User types decoding and encoding the values of a Postgres type, passed through untouched
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Codec {
+    /// Path of the type holding the values, e.g. `crate::Vector`, implementing `FromSql` and
+    /// `ToSql`. Parameters are bound from it.
+    pub owned: String,
+    /// Path of a type borrowing the values from the rows, e.g. `crate::VectorRef`, taking their
+    /// lifetime as its only generic parameter. It implements `FromSql` and converts into the
+    /// owned type with `From`. Rows decode the owned type if unset.
+    pub borrowed: Option<String>,
+}
+
+/// Code generation settings of a query module, overriding the global ones when set.
+/// Custom types are shared by all the modules, so they always follow the global settings.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ModuleConfig {
+    /// Derive serde's `Serialize` trait for the row structs.
+    pub serialize: Option<bool>,
+    /// Generate synchronous code.
+    pub sync: Option<bool>,
+    /// Generate asynchronous code, which is still generated if neither flavour is.
+    pub r#async: Option<bool>,
+    /// Generate the `iter` and `cursor` methods streaming the rows of the queries.
+    pub iter: Option<bool>,
+    /// Extra traits derived by the row structs, e.g. `Hash` or `Eq`.
+    pub derives: Vec<String>,
+    /// Visibility of the generated module.
+    pub visibility: Option<Visibility>,
+}
+
+/// Rust type of the `money` values, holding their amount in cents. Parameters are always
+/// bound from the `Money` type of the client crates, which converts from the other two.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+pub enum MoneyType {
+    /// The `Money` newtype of the client crates
+    #[default]
+    #[serde(rename = "newtype")]
+    Newtype,
+    /// `i64` cents
+    #[serde(rename = "cents")]
+    Cents,
+    /// `rust_decimal::Decimal`, needing the `with-rust_decimal-1` feature of the client crates
+    #[serde(rename = "decimal")]
+    Decimal,
+}
+
+/// Rust type of the `interval` values
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+pub enum IntervalType {
+    /// The `Interval` struct of the client crates, with its months, days and microseconds
+    #[default]
+    #[serde(rename = "struct")]
+    Struct,
+    /// `time::Duration`, needing the `with-time-0_3` feature of the client crates. Decoding
+    /// intervals with months fails, and days are counted as 24 hours.
+    #[serde(rename = "duration")]
+    Duration,
+}
+
+/// Rust type of the `bit(n)` and `bit varying` values. Parameters are bound from the
+/// `BitString` type of the client crates, which converts from `Vec<bool>`, except for `bit_vec`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+pub enum BitType {
+    /// The `BitString` type of the client crates, holding the bits packed in bytes
+    #[default]
+    #[serde(rename = "bit_string")]
+    BitString,
+    /// `Vec<bool>`, one element per bit
+    #[serde(rename = "bools")]
+    Bools,
+    /// `bit_vec::BitVec`, needing the `with-bit-vec-0_6` feature of the `postgres` or
+    /// `tokio-postgres` crate
+    #[serde(rename = "bit_vec")]
+    BitVec,
+}
+
+/// Visibility of a generated module
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub enum Visibility {
+    #[serde(rename = "pub")]
+    Public,
+    #[serde(rename = "pub(crate)")]
+    Crate,
+    #[serde(rename = "pub(super)")]
+    Super,
+}
+
+impl Visibility {
+    pub(crate) fn as_str(self) -> &'static str {
+        match self {
+            Self::Public => "pub",
+            Self::Crate => "pub(crate)",
+            Self::Super => "pub(super)",
+        }
+    }
+}
+
+/// Type holding the value of an inline column in owned rows
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum InlineType {
+    /// `arrayvec::ArrayString<N>`, holding up to `N` bytes of text
+    String(usize),
+    /// `[u8; N]`, holding exactly `N` bytes
+    Bytes(usize),
+}
+
+impl InlineType {
+    /// Parses `ArrayString<N>` or `[u8; N]`, with a non-zero capacity
+    pub(crate) fn parse(ty: &str) -> Option<Self> {
+        let ty: String = ty.chars().filter(|c| !c.is_whitespace()).collect();
+        let inline = if let Some(capacity) = ty
+            .strip_prefix("ArrayString<")
+            .and_then(|it| it.strip_suffix('>'))
+        {
+            Self::String(capacity.parse().ok()?)
+        } else {
+            let capacity = ty.strip_prefix("[u8;")?.strip_suffix(']')?;
+            Self::Bytes(capacity.parse().ok()?)
+        };
+        (inline.capacity() > 0).then_some(inline)
+    }
+
+    pub(crate) fn capacity(self) -> usize {
+        match self {
+            Self::String(capacity) | Self::Bytes(capacity) => capacity,
+        }
+    }
+}
+
+/// Settings of the configuration file
+const KEYS: [&str; 22] = [
+    "extra_queries",
+    "header",
+    "cache",
+    "include_queries",
+    "exclude_queries",
+    "newtypes",
+    "inline_columns",
+    "extension_types",
+    "codecs",
+    "money",
+    "interval",
+    "bit",
+    "any_chunk_size",
+    "budget_feature",
+    "trace_statement",
+    "materialized_views",
+    "tables",
+    "verify_nullability",
+    "validate_hints",
+    "lint",
+    "module",
+    "database",
+];
+
+/// Positions of the settings validated by [`Config::check`]
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct Spans {
+    newtypes: BTreeMap<Spanned<String>, Spanned<String>>,
+    inline_columns: BTreeMap<Spanned<String>, Spanned<String>>,
+    extension_types: BTreeMap<Spanned<String>, Spanned<String>>,
+    codecs: BTreeMap<Spanned<String>, CodecSpans>,
+    any_chunk_size: Option<Spanned<usize>>,
+    tables: TablesSpans,
+    module: BTreeMap<Spanned<String>, ModuleSpans>,
+    database: BTreeMap<Spanned<String>, DatabaseSpans>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CodecSpans {
+    owned: Spanned<String>,
+    borrowed: Option<Spanned<String>>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct TablesSpans {
+    include: Vec<Spanned<String>>,
+    exclude: Vec<Spanned<String>>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct ModuleSpans {
+    sync: Option<Spanned<bool>>,
+    r#async: Option<Spanned<bool>>,
+    derives: Vec<Spanned<String>>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct DatabaseSpans {
+    url: Option<Spanned<String>>,
+    schema: Vec<Spanned<String>>,
+}
+
+fn span<T>(spanned: &Spanned<T>) -> SourceSpan {
+    spanned.span().into()
+}
+
+fn is_ident(s: &str) -> bool {
+    let mut chars = s.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+impl Config {
+    /// Reads a configuration file. If `required` is false, a missing file yields the default configuration.
+    pub fn from_file(path: &Path, required: bool) -> Result<Self, Error> {
+        let Some(content) = Self::read(path, required)? else {
+            return Ok(Self::default());
+        };
+        Self::parse(path, content)
+    }
+
+    /// Reads a configuration file, rejecting the unknown settings, the invalid type names
+    /// and the conflicting options that [`Config::from_file`] lets through.
+    pub fn check(path: &Path) -> Result<Self, Error> {
+        let content = Self::read(path, true)?.expect("the file is required");
+        let src = || NamedSource::new(path.to_string_lossy(), content.clone());
+        let config = Self::parse(path, content.clone())?;
+        // Unknown settings would otherwise be ignored
+        let keys: BTreeMap<Spanned<String>, IgnoredAny> =
+            toml::from_str(&content).expect("parsed as a config");
+        if let Some(key) = keys
+            .keys()
+            .find(|it| !KEYS.contains(&it.get_ref().as_str()))
+        {
+            return Err(Error::UnknownKey {
+                key: key.get_ref().clone(),
+                src: src(),
+                span: span(key),
+                help: format!("use one of those settings: `{}`", KEYS.join("`, `")),
+            });
+        }
+        let spans: Spans = toml::from_str(&content).expect("parsed as a config");
+        let invalid = |spanned: SourceSpan, msg: &str, help: &str| Error::InvalidValue {
+            msg: msg.to_string(),
+            src: src(),
+            span: spanned,
+            help: help.to_string(),
+        };
+        let is_column_path = |column: &Spanned<String>| {
+            let parts: Vec<_> = column.get_ref().split('.').collect();
+            parts.len() == 3 && parts.iter().all(|it| !it.is_empty())
+        };
+        for (column, ty) in &spans.newtypes {
+            if !is_column_path(column) {
+                return Err(invalid(
+                    span(column),
+                    "expected a `schema.table.column` path",
+                    "wrap columns using their schema qualified path, e.g. `public.author.id`",
+                ));
+            }
+            if !is_ident(ty.get_ref()) {
+                return Err(invalid(
+                    span(ty),
+                    "expected the name of a Rust type",
+                    "name the generated newtype with an identifier, e.g. `AuthorId`",
+                ));
+            }
+        }
+        for (column, ty) in &spans.inline_columns {
+            if !is_column_path(column) {
+                return Err(invalid(
+                    span(column),
+                    "expected a `schema.table.column` path",
+                    "inline columns using their schema qualified path, e.g. `public.book.isbn`",
+                ));
+            }
+            if InlineType::parse(ty.get_ref()).is_none() {
+                return Err(invalid(
+                    span(ty),
+                    "expected `ArrayString<N>` or `[u8; N]`",
+                    "hold the values inline in `ArrayString<N>` for text or `[u8; N]` for fixed size bytes, e.g. `ArrayString<16>`",
+                ));
+            }
+        }
+        let is_type_path = |name: &Spanned<String>| {
+            let parts: Vec<_> = name.get_ref().split('.').collect();
+            parts.len() == 2 && parts.iter().all(|it| !it.is_empty())
+        };
+        let is_rust_path = |ty: &Spanned<String>| ty.get_ref().split("::").all(is_ident);
+        for (name, ty) in &spans.extension_types {
+            if !is_type_path(name) {
+                return Err(invalid(
+                    span(name),
+                    "expected a `schema.name` path",
+                    "declare extension types using their schema qualified name, e.g. `public.citext`",
+                ));
+            }
+            if !is_rust_path(ty) {
+                return Err(invalid(
+                    span(ty),
+                    "expected the path of a Rust type",
+                    "decode extension types as `String` or a type implementing `FromSql` and `ToSql`, e.g. `crate::Isbn`",
+                ));
+            }
+        }
+        for (name, codec) in &spans.codecs {
+            if !is_type_path(name) && !is_column_path(name) {
+                return Err(invalid(
+                    span(name),
+                    "expected a `schema.name` or `schema.table.column` path",
+                    "declare codecs using the schema qualified name of their type, e.g. `public.vector`, or the path of a single column, e.g. `public.author.born`",
+                ));
+            }
+            if spans.newtypes.contains_key(name) || spans.inline_columns.contains_key(name) {
+                return Err(invalid(
+                    span(name),
+                    "the column is also a newtype or held inline",
+                    "decode the column either with a codec or in a generated type, not both",
+                ));
+            }
+            if spans.extension_types.contains_key(name) {
+                return Err(invalid(
+                    span(name),
+                    "the type is also an extension type",
+                    "decode the type either with a codec or as an extension type, not both",
+                ));
+            }
+            if let Some(ty) = std::iter::once(&codec.owned)
+                .chain(&codec.borrowed)
+                .find(|it| !is_rust_path(it))
+            {
+                return Err(invalid(
+                    span(ty),
+                    "expected the path of a Rust type",
+                    "name the types of codecs by their path, without generics, e.g. `crate::Vector`",
+                ));
+            }
+        }
+        if let Some(table) = spans
+            .tables
+            .include
+            .iter()
+            .chain(&spans.tables.exclude)
+            .find(|it| !is_type_path(it))
+        {
+            return Err(invalid(
+                span(table),
+                "expected a `schema.table` path",
+                "name tables using their schema qualified path, e.g. `public.author`",
+            ));
+        }
+        if let Some(size) = spans
+            .any_chunk_size
+            .as_ref()
+            .filter(|it| *it.get_ref() == 0)
+        {
+            return Err(invalid(
+                span(size),
+                "chunks must hold at least one element",
+                "remove `any_chunk_size` to send arrays at once",
+            ));
+        }
+        for (name, module) in &spans.module {
+            if let Some(derive) = module
+                .derives
+                .iter()
+                .find(|it| !it.get_ref().split("::").all(is_ident))
+            {
+                return Err(invalid(
+                    span(derive),
+                    "expected the path of a trait",
+                    "derive traits by name or path, e.g. `Hash` or `serde::Deserialize`",
+                ));
+            }
+            if let (Some(sync), Some(r#async)) = (&module.sync, &module.r#async) {
+                if !sync.get_ref() && !r#async.get_ref() {
+                    return Err(Error::Conflict {
+                        name: name.get_ref().clone(),
+                        src: src(),
+                        first: span(sync),
+                        second: span(r#async),
+                    });
+                }
+            }
+        }
+        for (name, database) in &spans.database {
+            if !is_ident(name.get_ref()) {
+                return Err(invalid(
+                    span(name),
+                    "expected the name of a Rust module",
+                    "name databases with an identifier, e.g. `billing`",
+                ));
+            }
+            match (&database.url, database.schema.first()) {
+                (Some(url), Some(_)) => {
+                    return Err(invalid(
+                        span(url),
+                        "the database also has schema files",
+                        "generate against either a live database or schema files, not both",
+                    ))
+                }
+                (None, None) => {
+                    return Err(invalid(
+                        span(name),
+                        "the database has neither a url nor schema files",
+                        "set the `url` of a live database or its `schema` files",
+                    ))
+                }
+                _ => {}
+            }
+        }
+        Ok(config)
+    }
+
+    /// Content of the file at `path`, if any
+    fn read(path: &Path, required: bool) -> Result<Option<String>, Error> {
+        match std::fs::read_to_string(path) {
+            Ok(content) => Ok(Some(content)),
+            Err(err) if !required && err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(Error::Io {
+                path: path.to_string_lossy().to_string(),
+                err,
+            }),
+        }
+    }
+
+    fn parse(path: &Path, content: String) -> Result<Self, Error> {
+        Self::parse_with_env(path, content, |var| std::env::var(var).ok())
+    }
+
+    /// Parses `content`, expanding the `${VAR}` references of its strings with `env`
+    fn parse_with_env(
+        path: &Path,
+        content: String,
+        env: impl Fn(&str) -> Option<String>,
+    ) -> Result<Self, Error> {
+        let name = path.to_string_lossy();
+        let parse_err = |err: toml::de::Error| Error::Parse {
+            msg: err.message().to_string(),
+            err_span: err.span().map(Into::into),
+            src: NamedSource::new(&name, content.clone()),
+        };
+        let mut value = toml::Value::Table(toml::from_str(&content).map_err(parse_err)?);
+        // Expanded once parsed, so that the values of the variables are never read as TOML
+        expand_strings(&mut value, &env).map_err(|var| Error::MissingEnvVar {
+            span: reference_span(&content, &var),
+            var,
+            src: NamedSource::new(&name, content.clone()),
+        })?;
+        value.try_into().map_err(|err| {
+            // The expanded values have no location, unlike the same error in the file as is
+            parse_err(toml::from_str::<Self>(&content).err().unwrap_or(err))
+        })
+    }
+}
+
+/// Expands the `${VAR}` references to environment variables of `value`, named `name` in
+/// errors, `$${` standing for a literal `${`.
+pub(crate) fn expand_env(name: &str, value: &str) -> Result<String, Error> {
+    expand_vars(value, |var| std::env::var(var).ok()).map_err(|var| Error::MissingEnvVar {
+        span: reference_span(value, &var),
+        var,
+        src: NamedSource::new(name, value.to_string()),
+    })
+}
+
+/// Expands the `${VAR}` references of the strings in `value` with `env`, failing with the first
+/// variable it doesn't know
+fn expand_strings(
+    value: &mut toml::Value,
+    env: &impl Fn(&str) -> Option<String>,
+) -> Result<(), String> {
+    match value {
+        toml::Value::String(it) => *it = expand_vars(it, env)?,
+        toml::Value::Array(items) => {
+            for item in items {
+                expand_strings(item, env)?;
+            }
+        }
+        toml::Value::Table(table) => {
+            for (_, item) in table.iter_mut() {
+                expand_strings(item, env)?;
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Expands the `${VAR}` references of `value` with `env`, `$${` standing for a literal `${`,
+/// failing with the first variable it doesn't know
+fn expand_vars(value: &str, env: impl Fn(&str) -> Option<String>) -> Result<String, String> {
+    let mut expanded = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(start) = rest.find("${") {
+        if rest[..start].ends_with('$') {
+            expanded.push_str(&rest[..start]);
+            expanded.push('{');
+            rest = &rest[start + 2..];
+            continue;
+        }
+        let Some(len) = rest[start + 2..].find('}') else {
+            break;
+        };
+        let var = &rest[start + 2..start + 2 + len];
+        let value = env(var).ok_or_else(|| var.to_string())?;
+        expanded.push_str(&rest[..start]);
+        expanded.push_str(&value);
+        rest = &rest[start + len + 3..];
+    }
+    expanded.push_str(rest);
+    Ok(expanded)
+}
+
+/// Span of the first reference to `var` in `src` outside of comments
+fn reference_span(src: &str, var: &str) -> SourceSpan {
+    let reference = format!("${{{var}}}");
+    let mut offset = 0;
+    for line in src.split_inclusive('\n') {
+        if !line.trim_start().starts_with('#') {
+            let found = line
+                .match_indices(&reference)
+                .find(|(idx, _)| !line[..*idx].ends_with('$'));
+            if let Some((idx, _)) = found {
+                return (offset + idx, reference.len()).into();
+            }
+        }
+        offset += line.len();
+    }
+    (0, 0).into()
+}
+
+pub(crate) mod error {
+    use miette::{Diagnostic, NamedSource, SourceSpan};
+    use thiserror::Error as ThisError;
+
+    #[derive(Debug, ThisError, Diagnostic)]
+    pub enum Error {
+        #[error("Could not read config file `{path}`: ({err})")]
+        Io { path: String, err: std::io::Error },
+        #[error("Could not parse config file: {msg}")]
+        Parse {
+            msg: String,
+            #[source_code]
+            src: NamedSource,
+            #[label("error occurs near this location")]
+            err_span: Option<SourceSpan>,
+        },
+        #[error("the environment variable `{var}` isn't set")]
+        #[diagnostic(help("set the variable, or write `$${{` for a literal `${{`"))]
+        MissingEnvVar {
+            var: String,
+            #[source_code]
+            src: NamedSource,
+            #[label("referenced here")]
+            span: SourceSpan,
+        },
+        #[error("Config file overrides the settings of the unknown module `{name}`")]
+        UnknownModule {
+            name: String,
+            #[help]
+            help: String,
+        },
+        #[error("unknown setting `{key}`")]
+        UnknownKey {
+            key: String,
+            #[source_code]
+            src: NamedSource,
+            #[label("this setting is ignored")]
+            span: SourceSpan,
+            #[help]
+            help: String,
+        },
+        #[error("invalid setting")]
+        InvalidValue {
+            msg: String,
+            #[source_code]
+            src: NamedSource,
+            #[label("{msg}")]
+            span: SourceSpan,
+            #[help]
+            help: String,
+        },
+        #[error("conflicting settings for the module `{name}`")]
+        #[diagnostic(help(
+            "async code is generated when neither flavour is, remove `async = false`"
+        ))]
+        Conflict {
+            name: String,
+            #[source_code]
+            src: NamedSource,
+            #[label("no sync code")]
+            first: SourceSpan,
+            #[label("and no async code")]
+            second: SourceSpan,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, path::Path};
+
+    use super::{error::Error, expand_vars, Config};
+
+    fn env(var: &str) -> Option<String> {
+        HashMap::from([("HOST", "db.local"), ("QUOTED", r#"a"b\c"#)])
+            .get(var)
+            .map(|it| it.to_string())
+    }
+
+    fn parse(content: &str) -> Result<Config, Error> {
+        Config::parse_with_env(Path::new("cornucopia.toml"), content.to_string(), env)
+    }
+
+    #[test]
+    fn expand() {
+        let cases = [
+            ("postgres://${HOST}/app", Ok("postgres://db.local/app")),
+            ("${HOST}:${HOST}", Ok("db.local:db.local")),
+            // `$${` stands for a literal `${`
+            ("$${HOST}", Ok("${HOST}")),
+            ("$$${HOST}", Ok("$${HOST}")),
+            ("$${MISSING}", Ok("${MISSING}")),
+            // Not references
+            ("${HOST", Ok("${HOST")),
+            ("$HOST", Ok("$HOST")),
+            ("", Ok("")),
+            ("postgres://${MISSING}/app", Err("MISSING")),
+        ];
+        for (value, expected) in cases {
+            assert_eq!(
+                expand_vars(value, env),
+                expected.map(String::from).map_err(String::from),
+                "`{value}`"
+            );
+        }
+    }
+
+    #[test]
+    fn expand_strings_once_parsed() {
+        let config = parse(
+            r#"
+            # url = "${MISSING}"
+            header = "// ${QUOTED} $${HOST}"
+            [database."${HOST}"]
+            url = "postgres://${HOST}/${QUOTED}"
+            "#,
+        )
+        .unwrap();
+        // The values of the variables aren't read as TOML, nor are keys expanded
+        assert_eq!(config.header, r#"// a"b\c ${HOST}"#);
+        assert_eq!(
+            config.databases["${HOST}"].url.as_deref(),
+            Some(r#"postgres://db.local/a"b\c"#)
+        );
+    }
+
+    #[test]
+    fn missing_env_var() {
+        let content = "# header = \"${MISSING}\"\nheader = \"$${MISSING} ${MISSING}\"\n";
+        let Err(Error::MissingEnvVar { var, span, .. }) = parse(content) else {
+            panic!("expected a missing variable");
+        };
+        assert_eq!(var, "MISSING");
+        assert_eq!(
+            &content[span.offset()..span.offset() + span.len()],
+            "${MISSING}"
+        );
+        assert_eq!(span.offset(), content.rfind("${MISSING}").unwrap());
+    }
+}
@@ -1,3 +1,5 @@
+use std::{collections::BTreeMap, path::Path};
+
 use postgres::{Client, Config, NoTls};
 
 use self::error::Error;
@@ -7,21 +9,239 @@ pub(crate) fn from_url(url: &str) -> Result<Client, Error> {
     Ok(Client::connect(url, NoTls)?)
 }
 
+/// Creates a non-TLS connection from the environment, like sqlx and diesel do: to the url of
+/// `DATABASE_URL`, or else to the database described by the `PG*` variables of libpq. The
+/// variables missing from the environment are read from the `.env` file, if any.
+pub(crate) fn from_env() -> Result<Client, Error> {
+    let dotenv = read_dotenv(Path::new(".env"));
+    let config = env_config(|name| std::env::var(name).ok(), &dotenv)?;
+    Ok(config.connect(NoTls)?)
+}
+
+/// Config of the connection described by the variables of `env`, or else of `dotenv`
+fn env_config(
+    env: impl Fn(&str) -> Option<String>,
+    dotenv: &BTreeMap<String, String>,
+) -> Result<Config, Error> {
+    let var = |name: &str| env(name).or_else(|| dotenv.get(name).cloned());
+    if let Some(url) = var("DATABASE_URL") {
+        return Ok(url.parse()?);
+    }
+    let vars = ["PGHOST", "PGPORT", "PGUSER", "PGPASSWORD", "PGDATABASE"];
+    if vars.iter().all(|it| var(it).is_none()) {
+        return Err(Error::MissingUrl);
+    }
+    let mut config = Config::new();
+    config.host(&var("PGHOST").unwrap_or_else(|| "localhost".to_string()));
+    if let Some(port) = var("PGPORT") {
+        config.port(port.parse().map_err(|_| Error::InvalidPort(port))?);
+    }
+    if let Some(user) = var("PGUSER").or_else(|| var("USER")) {
+        config.user(&user);
+    }
+    if let Some(password) = var("PGPASSWORD") {
+        config.password(password);
+    }
+    if let Some(dbname) = var("PGDATABASE") {
+        config.dbname(&dbname);
+    }
+    Ok(config)
+}
+
+/// Variables of the `.env` file at `path`, written as `KEY=value` lines
+fn read_dotenv(path: &Path) -> BTreeMap<String, String> {
+    let Ok(content) = std::fs::read_to_string(path) else {
+        return BTreeMap::new();
+    };
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|it| !it.starts_with('#'))
+        .filter_map(|line| {
+            let line = line.strip_prefix("export ").unwrap_or(line);
+            let (key, value) = line.split_once('=')?;
+            let value = value.trim();
+            let value = [('"', '"'), ('\'', '\'')]
+                .iter()
+                .find_map(|(start, end)| value.strip_prefix(*start)?.strip_suffix(*end))
+                .unwrap_or(value);
+            Some((key.trim().to_string(), value.to_string()))
+        })
+        .collect()
+}
+
 /// Create a non-TLS connection to the container managed by Cornucopia.
 pub fn cornucopia_conn() -> Result<Client, Error> {
-    Ok(Config::new()
+    Ok(cornucopia_config().dbname("postgres").connect(NoTls)?)
+}
+
+/// Creates the empty database `name` in the container managed by Cornucopia, replacing any
+/// previous one, and returns a non-TLS connection to it.
+pub(crate) fn cornucopia_database(name: &str) -> Result<Client, Error> {
+    let mut client = cornucopia_conn()?;
+    client.execute(
+        &format!("DROP DATABASE IF EXISTS \"{name}\" WITH (FORCE)"),
+        &[],
+    )?;
+    client.execute(&format!("CREATE DATABASE \"{name}\""), &[])?;
+    Ok(cornucopia_config().dbname(name).connect(NoTls)?)
+}
+
+fn cornucopia_config() -> Config {
+    let mut config = Config::new();
+    config
         .user("postgres")
         .password("postgres")
         .host("127.0.0.1")
-        .port(5435)
-        .dbname("postgres")
-        .connect(NoTls)?)
+        .port(5435);
+    config
 }
 
 pub(crate) mod error {
     use miette::Diagnostic;
 
     #[derive(Debug, thiserror::Error, Diagnostic)]
-    #[error("Couldn't establish a connection with the database.")]
-    pub struct Error(#[from] pub postgres::Error);
+    pub enum Error {
+        #[error("Couldn't establish a connection with the database.")]
+        Connect(#[from] postgres::Error),
+        #[error("No database url was given")]
+        #[diagnostic(help(
+            "pass the url of the database, or set `DATABASE_URL` or the `PG*` variables, e.g. in a `.env` file"
+        ))]
+        MissingUrl,
+        #[error("`PGPORT` isn't a port: `{0}`")]
+        InvalidPort(String),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{BTreeMap, HashMap};
+
+    use postgres::config::Host;
+
+    use super::{env_config, error::Error, read_dotenv};
+
+    fn vars(vars: &[(&str, &str)]) -> BTreeMap<String, String> {
+        vars.iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect()
+    }
+
+    /// Host, port, user, password and database of the config described by `env` and `dotenv`
+    fn target(
+        env: &[(&str, &str)],
+        dotenv: &[(&str, &str)],
+    ) -> Result<(String, u16, String, String, String), Error> {
+        let env: HashMap<_, _> = env.iter().copied().collect();
+        let config = env_config(|name| env.get(name).map(|it| it.to_string()), &vars(dotenv))?;
+        let Some(Host::Tcp(host)) = config.get_hosts().first() else {
+            panic!("expected a TCP host");
+        };
+        Ok((
+            host.clone(),
+            config.get_ports().first().copied().unwrap_or(5432),
+            config.get_user().unwrap_or_default().to_string(),
+            String::from_utf8_lossy(config.get_password().unwrap_or_default()).into_owned(),
+            config.get_dbname().unwrap_or_default().to_string(),
+        ))
+    }
+
+    fn expected(
+        host: &str,
+        port: u16,
+        user: &str,
+        password: &str,
+        dbname: &str,
+    ) -> (String, u16, String, String, String) {
+        (
+            host.to_string(),
+            port,
+            user.to_string(),
+            password.to_string(),
+            dbname.to_string(),
+        )
+    }
+
+    #[test]
+    fn database_url_first() {
+        let url = ("DATABASE_URL", "postgres://app:secret@db:5433/app");
+        let pg = [("PGHOST", "pg"), ("PGDATABASE", "other")];
+        let from_url = expected("db", 5433, "app", "secret", "app");
+        // Over the `PG*` variables, wherever they are set
+        assert_eq!(target(&[url], &pg).unwrap(), from_url);
+        assert_eq!(target(&[url, pg[0], pg[1]], &[]).unwrap(), from_url);
+        assert_eq!(target(&pg, &[url]).unwrap(), from_url);
+        // The environment over `.env`
+        let dotenv_url = ("DATABASE_URL", "postgres://dotenv@elsewhere/dotenv");
+        assert_eq!(target(&[url], &[dotenv_url]).unwrap(), from_url);
+    }
+
+    #[test]
+    fn pg_variables() {
+        let env = [("PGHOST", "env"), ("PGUSER", "env_user")];
+        let dotenv = [
+            ("PGHOST", "dotenv"),
+            ("PGPORT", "5434"),
+            ("PGPASSWORD", "secret"),
+            ("PGDATABASE", "app"),
+        ];
+        // Each variable is read from the environment, or else from `.env`
+        assert_eq!(
+            target(&env, &dotenv).unwrap(),
+            expected("env", 5434, "env_user", "secret", "app")
+        );
+        // Defaults
+        assert_eq!(
+            target(&[("PGDATABASE", "app"), ("USER", "me")], &[]).unwrap(),
+            expected("localhost", 5432, "me", "", "app")
+        );
+    }
+
+    #[test]
+    fn invalid_variables() {
+        assert!(matches!(target(&[], &[]), Err(Error::MissingUrl)));
+        // Unrelated variables don't describe a database
+        assert!(matches!(
+            target(&[("USER", "me")], &[("HOME", "/")]),
+            Err(Error::MissingUrl)
+        ));
+        assert!(matches!(
+            target(&[("PGPORT", "port")], &[]),
+            Err(Error::InvalidPort(port)) if port == "port"
+        ));
+        assert!(matches!(
+            target(&[("DATABASE_URL", "not a url")], &[]),
+            Err(Error::Connect(_))
+        ));
+    }
+
+    #[test]
+    fn dotenv() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(".env");
+        assert!(read_dotenv(&path).is_empty());
+        std::fs::write(
+            &path,
+            "# DATABASE_URL=postgres://commented\n\
+             \n\
+             PGHOST=db\n\
+             export PGUSER = app \n\
+             \x20 PGPASSWORD=\"se=cr#et\"\n\
+             PGDATABASE='app'\n\
+             QUOTE=\"unterminated\n\
+             no separator\n",
+        )
+        .unwrap();
+        assert_eq!(
+            read_dotenv(&path),
+            vars(&[
+                ("PGHOST", "db"),
+                ("PGUSER", "app"),
+                ("PGPASSWORD", "se=cr#et"),
+                ("PGDATABASE", "app"),
+                ("QUOTE", "\"unterminated"),
+            ])
+        );
+    }
 }
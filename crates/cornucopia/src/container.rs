@@ -1,21 +1,90 @@
 use std::process::{Command, Stdio};
 
+use crate::log;
+
 use self::error::Error;
 
+/// Image of Cornucopia's database container
+const IMAGE: &str = "docker.io/library/postgres:latest";
+
 /// Starts Cornucopia's database container and wait until it reports healthy.
 pub fn setup(podman: bool) -> Result<(), Error> {
-    spawn_container(podman)?;
-    healthcheck(podman, 120, 50)?;
+    let use_podman = find_engine(podman)?;
+    if use_podman && !podman {
+        eprintln!("`docker` isn't installed, using `podman` instead");
+    }
+    log::info!("Starting Cornucopia's container");
+    spawn_container(use_podman)?;
+    healthcheck(use_podman, 120, 50)?;
+    log::info!("Cornucopia's container is healthy");
     Ok(())
 }
 
+/// Name of the container engine used, `docker` or `podman`, checking it is installed
+pub(crate) fn engine(podman: bool) -> Result<&'static str, Error> {
+    Ok(if find_engine(podman)? {
+        "podman"
+    } else {
+        "docker"
+    })
+}
+
+/// Pulls the image of Cornucopia's container unless it is already there. Returns whether it
+/// was pulled.
+pub(crate) fn pull_image(podman: bool) -> Result<bool, Error> {
+    let use_podman = find_engine(podman)?;
+    if cmd(
+        use_podman,
+        &["image", "inspect", IMAGE],
+        "inspect the image",
+    )
+    .is_ok()
+    {
+        return Ok(false);
+    }
+    cmd(use_podman, &["pull", IMAGE], "pull the image")?;
+    Ok(true)
+}
+
 /// Stop and remove a container and its volume.
 pub fn cleanup(podman: bool) -> Result<(), Error> {
-    stop_container(podman)?;
-    remove_container(podman)?;
+    let use_podman = find_engine(podman)?;
+    log::info!("Removing Cornucopia's container");
+    stop_container(use_podman)?;
+    remove_container(use_podman)?;
     Ok(())
 }
 
+/// Checks that the requested container engine is installed, falling back to `podman`
+/// when `docker` is missing. Returns whether `podman` is used.
+fn find_engine(podman: bool) -> Result<bool, Error> {
+    let installed = |command: &str| {
+        Command::new(command)
+            .arg("--version")
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .is_ok_and(|status| status.success())
+    };
+    if podman {
+        installed("podman")
+            .then_some(true)
+            .ok_or(Error::MissingEngine {
+                engines: "`podman`",
+                install: "podman",
+            })
+    } else if installed("docker") {
+        Ok(false)
+    } else if installed("podman") {
+        Ok(true)
+    } else {
+        Err(Error::MissingEngine {
+            engines: "`docker` or `podman`",
+            install: "docker or podman",
+        })
+    }
+}
+
 /// Starts Cornucopia's database container.
 fn spawn_container(podman: bool) -> Result<(), Error> {
     cmd(
@@ -29,7 +98,7 @@ fn spawn_container(podman: bool) -> Result<(), Error> {
             "5435:5432",
             "-e",
             "POSTGRES_PASSWORD=postgres",
-            "docker.io/library/postgres:latest",
+            IMAGE,
         ],
         "spawn container",
     )
@@ -60,7 +129,7 @@ fn healthcheck(podman: bool, max_retries: u64, ms_per_retry: u64) -> Result<(),
         nb_retries += 1;
 
         if nb_retries % slow_threshold == 0 {
-            println!("Container startup slower than expected ({nb_retries} retries out of {max_retries})");
+            eprintln!("Container startup slower than expected ({nb_retries} retries out of {max_retries})");
         }
     }
     // Just for extra safety...
@@ -84,6 +153,7 @@ fn remove_container(podman: bool) -> Result<(), Error> {
 
 fn cmd(podman: bool, args: &[&'static str], action: &'static str) -> Result<(), Error> {
     let command = if podman { "podman" } else { "docker" };
+    log::debug!("Running `{command} {}`", args.join(" "));
     let output = Command::new(command)
         .args(args)
         .stderr(Stdio::piped())
@@ -108,11 +178,21 @@ pub(crate) mod error {
     use thiserror::Error as ThisError;
 
     #[derive(Debug, ThisError, Diagnostic)]
-    #[error("{msg}")]
-    pub struct Error {
-        msg: String,
-        #[help]
-        pub help: Option<String>,
+    pub enum Error {
+        #[error("{msg}")]
+        Command {
+            msg: String,
+            #[help]
+            help: Option<String>,
+        },
+        #[error("Couldn't find {engines} to run Cornucopia's database container")]
+        #[diagnostic(help(
+            "Install {install}, or generate your queries against your own database with `cornucopia live <url>`."
+        ))]
+        MissingEngine {
+            engines: &'static str,
+            install: &'static str,
+        },
     }
 
     impl Error {
@@ -122,7 +202,7 @@ pub(crate) mod error {
             } else {
                 "First, check that the docker daemon is up-and-running. Then, make sure that port 5435 is usable and that no container named `cornucopia_postgres` already exists."
             };
-            Error {
+            Error::Command {
                 msg,
                 help: Some(String::from(help)),
             }
@@ -131,7 +211,7 @@ pub(crate) mod error {
 
     impl From<std::io::Error> for Error {
         fn from(e: std::io::Error) -> Self {
-            Self {
+            Self::Command {
                 msg: format!("{e:#}"),
                 help: None,
             }
@@ -0,0 +1,109 @@
+use clap::ValueEnum;
+use miette::{Diagnostic, Severity};
+use serde::Serialize;
+
+use crate::error::Error;
+
+/// Format of the errors reported by the CLI
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub(crate) enum MessageFormat {
+    /// Rendered for humans on stderr
+    Human,
+    /// Also written on stdout as JSON records, one per line
+    Json,
+}
+
+/// Diagnostic, as a machine-readable record
+#[derive(Debug, Serialize)]
+struct Record {
+    severity: &'static str,
+    /// Names of the nested variants of the error, e.g. `PrepareQueries::Db`
+    code: Option<String>,
+    message: String,
+    help: Option<String>,
+    /// Name of the file the spans point into
+    file: Option<String>,
+    spans: Vec<SpanRecord>,
+}
+
+/// Labeled span of a diagnostic, its line and column starting at 1
+#[derive(Debug, Serialize)]
+struct SpanRecord {
+    offset: usize,
+    length: usize,
+    line: usize,
+    column: usize,
+    label: Option<String>,
+}
+
+/// JSON records of `err` and of its related diagnostics, one per line
+pub(crate) fn json_records(err: &Error) -> String {
+    let mut records = vec![record(err, Some(error_code(err)))];
+    if let Some(related) = err.related() {
+        records.extend(related.map(|it| record(it, None)));
+    }
+    records
+        .iter()
+        .map(|it| serde_json::to_string(it).expect("records serialize") + "\n")
+        .collect()
+}
+
+/// JSON record of a single `diagnostic`, on its own line
+pub(crate) fn json_record(diagnostic: &dyn Diagnostic) -> String {
+    serde_json::to_string(&record(diagnostic, None)).expect("records serialize") + "\n"
+}
+
+fn record(diagnostic: &dyn Diagnostic, code: Option<String>) -> Record {
+    let severity = match diagnostic.severity().unwrap_or(Severity::Error) {
+        Severity::Error => "error",
+        Severity::Warning => "warning",
+        Severity::Advice => "advice",
+    };
+    let mut file = None;
+    let spans = diagnostic
+        .labels()
+        .into_iter()
+        .flatten()
+        .map(|label| {
+            let contents = diagnostic
+                .source_code()
+                .and_then(|src| src.read_span(label.inner(), 0, 0).ok());
+            if let Some(name) = contents.as_ref().and_then(|it| it.name()) {
+                file = Some(name.to_string());
+            }
+            SpanRecord {
+                offset: label.offset(),
+                length: label.len(),
+                line: contents.as_ref().map_or(0, |it| it.line()) + 1,
+                column: contents.as_ref().map_or(0, |it| it.column()) + 1,
+                label: label.label().map(ToString::to_string),
+            }
+        })
+        .collect();
+    Record {
+        severity,
+        code: code.or_else(|| diagnostic.code().map(|it| it.to_string())),
+        message: diagnostic.to_string(),
+        help: diagnostic.help().map(|it| it.to_string()),
+        file,
+        spans,
+    }
+}
+
+/// Names of the nested variants of `err`, read from its debug representation
+fn error_code(err: &Error) -> String {
+    let debug = format!("{err:?}");
+    let mut names = Vec::new();
+    let mut rest = debug.as_str();
+    loop {
+        let end = rest
+            .find(|c: char| !c.is_alphanumeric() && c != '_')
+            .unwrap_or(rest.len());
+        names.push(&rest[..end]);
+        match rest[end..].strip_prefix('(') {
+            Some(inner) => rest = inner,
+            None => break,
+        }
+    }
+    names.join("::")
+}
@@ -0,0 +1,205 @@
+use std::{collections::BTreeMap, path::Path};
+
+use postgres::Client;
+
+use crate::{
+    add_tables_module,
+    codegen::GenCtx,
+    error::Error,
+    parser::Module,
+    prepare_queries::{
+        prepare_available, PreparedContent, PreparedField, PreparedModule, PreparedQuery,
+    },
+    read_modules, CodegenSettings,
+};
+
+use self::error::Error as DiffError;
+
+/// Generated signatures of the queries and the types, as prepared against a database
+#[derive(Debug, Default)]
+pub(crate) struct Signatures {
+    /// Signatures of the queries, or the error preparing them, keyed by their `module::query`
+    /// path
+    queries: BTreeMap<String, Result<String, String>>,
+    /// Definitions of the types, keyed by their `schema::Name` path
+    types: BTreeMap<String, String>,
+}
+
+/// Prepares the queries at `queries_path` against the database of `client`, keeping the
+/// signatures of what would be generated. The queries which fail to prepare keep their error.
+pub(crate) fn signatures(
+    client: &mut Client,
+    queries_path: &Path,
+    settings: &CodegenSettings,
+) -> Result<Signatures, Error> {
+    // Only the signatures matter, whatever the data
+    let settings = CodegenSettings {
+        cache: None,
+        verify_nullability: false,
+        ..settings.clone()
+    };
+    let mut modules = read_modules(queries_path, &settings)?;
+    add_tables_module(client, &mut modules, &settings)?;
+    // Each query is prepared in its own module named after its path, so that a failing query
+    // doesn't hide the others of its module
+    let mut split = Vec::new();
+    for module in modules {
+        for query in module.queries {
+            let mut info = module.info.clone();
+            info.name = format!("{}::{}", module.info.name, query.name.value);
+            split.push(Module {
+                info,
+                types: module.types.clone(),
+                queries: vec![query],
+                fragments: module.fragments.clone(),
+                attributes: module.attributes.clone(),
+            });
+        }
+    }
+    let (preparation, failed) = prepare_available(client, split, &settings)?;
+    let ctx = GenCtx::new(0, settings.gen_async, false);
+    let mut signatures = Signatures::default();
+    for module in &preparation.modules {
+        for query in module.queries.values() {
+            let signature = query_signature(module, query, &ctx);
+            signatures
+                .queries
+                .insert(module.info.name.clone(), Ok(signature));
+        }
+    }
+    for (path, err) in failed {
+        let message = Error::from(err).to_string();
+        signatures.queries.insert(path, Err(message));
+    }
+    for (schema, types) in &preparation.types {
+        for ty in types {
+            let definition = match &ty.content {
+                PreparedContent::Enum(variants) => {
+                    let variants: Vec<_> = variants.iter().map(|it| it.rs.as_str()).collect();
+                    format!("enum {} {{ {} }}", ty.struct_name, variants.join(", "))
+                }
+                PreparedContent::Composite(fields) => {
+                    format!(
+                        "struct {} {{ {} }}",
+                        ty.struct_name,
+                        fields_signature(fields, &ctx)
+                    )
+                }
+                PreparedContent::Newtype(inner) => {
+                    format!("struct {}({})", ty.struct_name, inner.own_ty(false, &ctx))
+                }
+            };
+            signatures
+                .types
+                .insert(format!("{schema}::{}", ty.struct_name), definition);
+        }
+    }
+    Ok(signatures)
+}
+
+/// Signature of the generated query, e.g. `author_by_id(id: i32) -> Author { name: String }`
+fn query_signature(module: &PreparedModule, query: &PreparedQuery, ctx: &GenCtx) -> String {
+    let params = query
+        .param
+        .as_ref()
+        .map(|(idx, _)| fields_signature(&module.params[*idx].fields, ctx))
+        .unwrap_or_default();
+    let mut signature = format!("{}({params})", query.ident.rs);
+    if let Some((idx, _)) = &query.row {
+        let row = &module.rows[*idx];
+        if row.is_named {
+            signature.push_str(&format!(
+                " -> {} {{ {} }}",
+                row.name.value,
+                fields_signature(&row.fields, ctx)
+            ));
+        } else {
+            signature.push_str(&format!(" -> {}", field_type(&row.fields[0], ctx)));
+        }
+    }
+    signature
+}
+
+fn fields_signature(fields: &[PreparedField], ctx: &GenCtx) -> String {
+    let fields: Vec<_> = fields
+        .iter()
+        .map(|it| format!("{}: {}", it.ident.rs, field_type(it, ctx)))
+        .collect();
+    fields.join(", ")
+}
+
+fn field_type(field: &PreparedField, ctx: &GenCtx) -> String {
+    let ty = field.ty.own_ty(field.is_inner_nullable, ctx);
+    if field.is_nullable {
+        format!("Option<{ty}>")
+    } else {
+        ty
+    }
+}
+
+/// Writes on stdout the generated signatures which differ from `current` to `target`, failing
+/// if any does
+pub(crate) fn diff(current: &Signatures, target: &Signatures) -> Result<(), DiffError> {
+    let mut changed = 0;
+    let mut change = |path: &str, before: Option<&str>, after: Option<&str>| {
+        if before == after {
+            return;
+        }
+        changed += 1;
+        println!("{path}");
+        if let Some(before) = before {
+            println!("  - {before}");
+        }
+        if let Some(after) = after {
+            println!("  + {after}");
+        }
+    };
+    let describe = |it: Option<&Result<String, String>>| {
+        it.map(|it| match it {
+            Ok(signature) => signature.clone(),
+            Err(err) => format!("doesn't prepare: {err}"),
+        })
+    };
+    let mut queries: Vec<_> = current
+        .queries
+        .keys()
+        .chain(target.queries.keys())
+        .collect();
+    queries.sort_unstable();
+    queries.dedup();
+    for query in queries {
+        change(
+            query,
+            describe(current.queries.get(query)).as_deref(),
+            describe(target.queries.get(query)).as_deref(),
+        );
+    }
+    let mut types: Vec<_> = current.types.keys().chain(target.types.keys()).collect();
+    types.sort_unstable();
+    types.dedup();
+    for ty in types {
+        change(
+            &format!("types::{ty}"),
+            current.types.get(ty).map(String::as_str),
+            target.types.get(ty).map(String::as_str),
+        );
+    }
+    if changed > 0 {
+        return Err(DiffError::Changed { changed });
+    }
+    Ok(())
+}
+
+pub(crate) mod error {
+    use miette::Diagnostic;
+    use thiserror::Error as ThisError;
+
+    #[derive(Debug, ThisError, Diagnostic)]
+    pub enum Error {
+        #[error("{changed} of the generated signatures would change")]
+        #[diagnostic(help(
+            "update the code using them along with the migration, or keep the schema compatible"
+        ))]
+        Changed { changed: usize },
+    }
+}
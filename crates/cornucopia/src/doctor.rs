@@ -0,0 +1,136 @@
+use std::path::Path;
+
+use postgres::Client;
+
+use crate::{
+    config::Config, conn::error::Error as ConnError, container, read_queries::read_query_modules,
+};
+
+use self::error::Error;
+
+/// Failed check, with the fix to apply
+struct Failure {
+    problem: String,
+    fix: &'static str,
+}
+
+/// Message of `err` followed by those of its sources, on a single line
+fn describe(err: &dyn std::error::Error) -> String {
+    let mut message = err.to_string().trim().to_string();
+    let mut source = err.source();
+    while let Some(err) = source {
+        let part = err.to_string();
+        // Some errors already end with the message of their source
+        if !message.ends_with(part.trim()) {
+            message = format!("{message} {}", part.trim());
+        }
+        source = err.source();
+    }
+    message
+}
+
+/// Checks what generating the queries relies on, writing the outcome of each check on stdout
+/// along with how to fix the failed ones: the container engine and its Postgres image, the
+/// queries, the configuration file and the live database, skipped if no url is known.
+pub(crate) fn doctor(
+    podman: bool,
+    queries_path: &Path,
+    config_path: Option<&Path>,
+    connect: impl FnOnce() -> Result<Client, crate::Error>,
+) -> Result<(), Error> {
+    let mut failed = 0;
+    let mut report = |check: &str, outcome: Result<String, Failure>| match outcome {
+        Ok(detail) => println!("✔ {check}: {detail}"),
+        Err(Failure { problem, fix }) => {
+            failed += 1;
+            println!("✘ {check}: {problem}\n  fix: {fix}");
+        }
+    };
+
+    let engine = container::engine(podman).map_err(|err| Failure {
+        problem: describe(&err),
+        fix: "install docker or podman, or generate against your own database with `cornucopia live <url>`",
+    });
+    let has_engine = engine.is_ok();
+    report("container engine", engine.map(|it| format!("using `{it}`")));
+    if has_engine {
+        let image = container::pull_image(podman)
+            .map(|pulled| {
+                if pulled {
+                    "pulled the Postgres image".to_string()
+                } else {
+                    "the Postgres image is there".to_string()
+                }
+            })
+            .map_err(|err| Failure {
+                problem: describe(&err),
+                fix: "check that the container engine is running and can reach docker.io",
+            });
+        report("container image", image);
+    }
+
+    let queries = read_query_modules(queries_path)
+        .map(|modules| {
+            format!(
+                "found {} query module{} in `{}`",
+                modules.len(),
+                if modules.len() == 1 { "" } else { "s" },
+                queries_path.display()
+            )
+        })
+        .map_err(|err| Failure {
+            problem: describe(&err),
+            fix: "create the folder of the queries, or give it with `--queries-path`",
+        });
+    report("queries", queries);
+
+    let default_path = Path::new("cornucopia.toml");
+    let config_path = config_path.unwrap_or(default_path);
+    let config = if config_path.exists() || config_path != default_path {
+        Config::check(config_path)
+            .map(|_| format!("`{}` is valid", config_path.display()))
+            .map_err(|err| Failure {
+                problem: describe(&err),
+                fix: "run `cornucopia config check` to see where the configuration file is wrong",
+            })
+    } else {
+        Ok("no configuration file, using the default settings".to_string())
+    };
+    report("configuration", config);
+
+    let database = connect().and_then(|mut client| {
+        let row = client
+            .query_one("SELECT version()", &[])
+            .map_err(ConnError::from)?;
+        Ok(format!("connected to {}", row.get::<_, &str>(0)))
+    });
+    match database {
+        Err(crate::Error::Connection(ConnError::MissingUrl)) => {
+            println!("- database: skipped, no url was given nor found in the environment");
+        }
+        database => report(
+            "database",
+            database.map_err(|err| Failure {
+                problem: describe(&err),
+                fix: "check that the database is up and that its url, user and password are right",
+            }),
+        ),
+    }
+
+    if failed > 0 {
+        return Err(Error::Failed { failed });
+    }
+    Ok(())
+}
+
+pub(crate) mod error {
+    use miette::Diagnostic;
+    use thiserror::Error as ThisError;
+
+    #[derive(Debug, ThisError, Diagnostic)]
+    pub enum Error {
+        #[error("{failed} of the checks failed")]
+        #[diagnostic(help("apply the fixes given with the failed checks"))]
+        Failed { failed: usize },
+    }
+}
@@ -0,0 +1,347 @@
+use std::{collections::BTreeMap, path::Path};
+
+use clap::ValueEnum;
+use postgres::Client;
+use serde::{Deserialize, Serialize};
+
+use crate::sql_analysis::quote_ident;
+
+use self::error::Error;
+
+/// Format of the schema dump
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub(crate) enum DumpFormat {
+    Json,
+    Toml,
+}
+
+/// Schema of the database, as introspected from its catalog. The system schemas are left out.
+#[derive(Debug, Serialize, Deserialize)]
+struct SchemaDump {
+    /// Extensions installed in the database, some of which could provide the types of columns
+    #[serde(default)]
+    extensions: Vec<String>,
+    relations: Vec<Relation>,
+    enums: Vec<Enum>,
+    composites: Vec<Composite>,
+    domains: Vec<Domain>,
+}
+
+/// Table, view or materialized view
+#[derive(Debug, Serialize, Deserialize)]
+struct Relation {
+    oid: u32,
+    schema: String,
+    name: String,
+    kind: String,
+    columns: Vec<Column>,
+}
+
+/// Column of a relation or field of a composite type
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Column {
+    name: String,
+    /// Type, as written in SQL, e.g. `character varying(16)` or `integer[]`
+    #[serde(rename = "type")]
+    ty: String,
+    type_oid: u32,
+    nullable: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Enum {
+    oid: u32,
+    schema: String,
+    name: String,
+    variants: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Composite {
+    oid: u32,
+    schema: String,
+    name: String,
+    fields: Vec<Column>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Domain {
+    oid: u32,
+    schema: String,
+    name: String,
+    /// Type the domain is based on, as written in SQL
+    #[serde(rename = "type")]
+    ty: String,
+    type_oid: u32,
+    nullable: bool,
+}
+
+/// Filters out the system schemas, given the alias of `pg_namespace`
+pub(crate) const USER_SCHEMAS: &str = "n.nspname NOT IN ('pg_catalog', 'information_schema')
+    AND n.nspname NOT LIKE 'pg\\_toast%' AND n.nspname NOT LIKE 'pg\\_temp%'";
+
+/// Dumps the schema of the database in `format`
+pub(crate) fn dump_schema(client: &mut Client, format: DumpFormat) -> Result<String, Error> {
+    let dump = read_schema(client)?;
+    Ok(match format {
+        DumpFormat::Json => serde_json::to_string_pretty(&dump).expect("dumps serialize") + "\n",
+        DumpFormat::Toml => toml::to_string_pretty(&dump).expect("dumps serialize"),
+    })
+}
+
+fn read_schema(client: &mut Client) -> Result<SchemaDump, Error> {
+    let relations = client.query(
+        &format!(
+            "SELECT c.oid, n.nspname, c.relname, c.relkind::text FROM pg_class c
+            JOIN pg_namespace n ON n.oid = c.relnamespace
+            WHERE c.relkind IN ('r', 'p', 'v', 'm', 'f') AND {USER_SCHEMAS}
+            ORDER BY n.nspname, c.relname"
+        ),
+        &[],
+    )?;
+    let composites = client.query(
+        &format!(
+            "SELECT t.oid, n.nspname, t.typname, t.typrelid FROM pg_type t
+            JOIN pg_namespace n ON n.oid = t.typnamespace
+            JOIN pg_class c ON c.oid = t.typrelid
+            WHERE t.typtype = 'c' AND c.relkind = 'c' AND {USER_SCHEMAS}
+            ORDER BY n.nspname, t.typname"
+        ),
+        &[],
+    )?;
+    // Columns of the relations and fields of the composite types, keyed by their relation
+    let relids: Vec<u32> = relations
+        .iter()
+        .map(|it| it.get(0))
+        .chain(composites.iter().map(|it| it.get(3)))
+        .collect();
+    let mut columns: BTreeMap<u32, Vec<Column>> = BTreeMap::new();
+    for row in client.query(
+        "SELECT a.attrelid, a.attname, format_type(a.atttypid, a.atttypmod), a.atttypid,
+            NOT a.attnotnull
+        FROM pg_attribute a
+        WHERE a.attrelid = ANY($1) AND a.attnum > 0 AND NOT a.attisdropped
+        ORDER BY a.attrelid, a.attnum",
+        &[&relids],
+    )? {
+        columns.entry(row.get(0)).or_default().push(Column {
+            name: row.get(1),
+            ty: row.get(2),
+            type_oid: row.get(3),
+            nullable: row.get(4),
+        });
+    }
+    let mut columns_of = |relid: u32| columns.remove(&relid).unwrap_or_default();
+    let relations = relations
+        .iter()
+        .map(|row| Relation {
+            oid: row.get(0),
+            schema: row.get(1),
+            name: row.get(2),
+            kind: match row.get::<_, &str>(3) {
+                "v" => "view",
+                "m" => "materialized view",
+                "f" => "foreign table",
+                _ => "table",
+            }
+            .to_string(),
+            columns: columns_of(row.get(0)),
+        })
+        .collect();
+    let composites = composites
+        .iter()
+        .map(|row| Composite {
+            oid: row.get(0),
+            schema: row.get(1),
+            name: row.get(2),
+            fields: columns_of(row.get(3)),
+        })
+        .collect();
+    let enums = client
+        .query(
+            &format!(
+                "SELECT t.oid, n.nspname, t.typname,
+                    array_agg(e.enumlabel::text ORDER BY e.enumsortorder)
+                FROM pg_type t
+                JOIN pg_namespace n ON n.oid = t.typnamespace
+                JOIN pg_enum e ON e.enumtypid = t.oid
+                WHERE {USER_SCHEMAS}
+                GROUP BY t.oid, n.nspname, t.typname
+                ORDER BY n.nspname, t.typname"
+            ),
+            &[],
+        )?
+        .iter()
+        .map(|row| Enum {
+            oid: row.get(0),
+            schema: row.get(1),
+            name: row.get(2),
+            variants: row.get(3),
+        })
+        .collect();
+    let domains = client
+        .query(
+            &format!(
+                "SELECT t.oid, n.nspname, t.typname, format_type(t.typbasetype, t.typtypmod),
+                    t.typbasetype, NOT t.typnotnull
+                FROM pg_type t
+                JOIN pg_namespace n ON n.oid = t.typnamespace
+                WHERE t.typtype = 'd' AND {USER_SCHEMAS}
+                ORDER BY n.nspname, t.typname"
+            ),
+            &[],
+        )?
+        .iter()
+        .map(|row| Domain {
+            oid: row.get(0),
+            schema: row.get(1),
+            name: row.get(2),
+            ty: row.get(3),
+            type_oid: row.get(4),
+            nullable: row.get(5),
+        })
+        .collect();
+    let extensions = client
+        .query(
+            "SELECT extname::text FROM pg_extension WHERE extname <> 'plpgsql' ORDER BY extname",
+            &[],
+        )?
+        .iter()
+        .map(|row| row.get(0))
+        .collect();
+    Ok(SchemaDump {
+        extensions,
+        relations,
+        enums,
+        composites,
+        domains,
+    })
+}
+
+/// Restores the schema of the JSON dump at `path` in the empty database of `client`, its
+/// relations becoming tables. The functions and constraints aren't dumped, so the queries
+/// relying on them can't be prepared against it.
+pub(crate) fn restore_schema(client: &mut Client, path: &Path) -> Result<(), Error> {
+    let path_str = || path.to_string_lossy().into_owned();
+    let json = std::fs::read_to_string(path).map_err(|err| Error::Read {
+        path: path_str(),
+        err,
+    })?;
+    let dump: SchemaDump = serde_json::from_str(&json).map_err(|err| Error::Parse {
+        path: path_str(),
+        err,
+    })?;
+    let mut pending = restore_statements(&dump);
+    // Types and relations depend on each other, so the statements are run until all of them
+    // succeed or none does
+    while !pending.is_empty() {
+        let mut failed = Vec::new();
+        for statement in &pending {
+            if let Err(err) = client.batch_execute(statement) {
+                failed.push((statement.clone(), err));
+            }
+        }
+        if failed.len() == pending.len() {
+            let (statement, err) = failed.swap_remove(0);
+            return Err(Error::Restore { statement, err });
+        }
+        pending = failed.into_iter().map(|(statement, _)| statement).collect();
+    }
+    Ok(())
+}
+
+/// Statements creating the schema of `dump`
+fn restore_statements(dump: &SchemaDump) -> Vec<String> {
+    let path = |schema: &str, name: &str| format!("{}.{}", quote_ident(schema), quote_ident(name));
+    let columns = |columns: &[Column], with_nullability: bool| {
+        let columns: Vec<_> = columns
+            .iter()
+            .map(|it| {
+                let not_null = if with_nullability && !it.nullable {
+                    " NOT NULL"
+                } else {
+                    ""
+                };
+                format!("{} {}{not_null}", quote_ident(&it.name), it.ty)
+            })
+            .collect();
+        columns.join(", ")
+    };
+    let mut schemas: Vec<&str> = (dump.relations.iter().map(|it| it.schema.as_str()))
+        .chain(dump.enums.iter().map(|it| it.schema.as_str()))
+        .chain(dump.composites.iter().map(|it| it.schema.as_str()))
+        .chain(dump.domains.iter().map(|it| it.schema.as_str()))
+        .filter(|it| *it != "public")
+        .collect();
+    schemas.sort_unstable();
+    schemas.dedup();
+    let mut statements: Vec<String> = schemas
+        .iter()
+        .map(|it| format!("CREATE SCHEMA IF NOT EXISTS {}", quote_ident(it)))
+        .collect();
+    statements.extend(
+        dump.extensions
+            .iter()
+            .map(|it| format!("CREATE EXTENSION IF NOT EXISTS {}", quote_ident(it))),
+    );
+    statements.extend(dump.enums.iter().map(|it| {
+        let variants: Vec<_> = it
+            .variants
+            .iter()
+            .map(|it| format!("'{}'", it.replace('\'', "''")))
+            .collect();
+        format!(
+            "CREATE TYPE {} AS ENUM ({})",
+            path(&it.schema, &it.name),
+            variants.join(", ")
+        )
+    }));
+    statements.extend(dump.composites.iter().map(|it| {
+        format!(
+            "CREATE TYPE {} AS ({})",
+            path(&it.schema, &it.name),
+            columns(&it.fields, false)
+        )
+    }));
+    statements.extend(dump.domains.iter().map(|it| {
+        let not_null = if it.nullable { "" } else { " NOT NULL" };
+        format!(
+            "CREATE DOMAIN {} AS {}{not_null}",
+            path(&it.schema, &it.name),
+            it.ty
+        )
+    }));
+    statements.extend(dump.relations.iter().map(|it| {
+        format!(
+            "CREATE TABLE {} ({})",
+            path(&it.schema, &it.name),
+            columns(&it.columns, true)
+        )
+    }));
+    statements
+}
+
+pub(crate) mod error {
+    use miette::Diagnostic;
+    use thiserror::Error as ThisError;
+
+    #[derive(Debug, ThisError, Diagnostic)]
+    pub enum Error {
+        #[error("Couldn't read the schema of the database: {0}")]
+        Db(#[from] postgres::Error),
+        #[error("Could not read the schema dump `{path}`: ({err})")]
+        Read { path: String, err: std::io::Error },
+        #[error("`{path}` isn't a JSON schema dump: {err}")]
+        #[diagnostic(help("write it with `cornucopia dump --format json`"))]
+        Parse {
+            path: String,
+            err: serde_json::Error,
+        },
+        #[error("Couldn't restore the schema dump: {err:#}")]
+        #[diagnostic(help("failed to run `{statement}`"))]
+        Restore {
+            statement: String,
+            err: postgres::Error,
+        },
+    }
+}
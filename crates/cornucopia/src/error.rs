@@ -10,10 +10,18 @@ use thiserror::Error as ThisError;
 pub enum Error {
     /// An error while trying to connect to a database.
     Connection(#[from] crate::conn::error::Error),
+    /// An error while trying to read the configuration file.
+    Config(#[from] crate::config::error::Error),
     /// An error while trying to read PostgreSQL query files.
     ReadQueries(#[from] crate::read_queries::error::Error),
+    /// An error while trying to annotate PostgreSQL query files with their sidecar file.
+    Sidecar(#[from] crate::sidecar::error::Error),
     /// An error while trying to parse PostgreSQL query files.
     ParseQueries(#[from] crate::parser::error::Error),
+    /// An error while trying to include SQL fragments in queries.
+    IncludeFragments(#[from] crate::fragments::error::Error),
+    /// An error while trying to import row and params structs from other query modules.
+    ImportStructs(#[from] crate::imports::error::Error),
     /// An error while trying to validate PostgreSQL query files.
     ValidateQueries(#[from] Box<crate::validation::error::Error>),
     /// An error while manipulating a container managed by Cornucopia.
@@ -24,6 +32,18 @@ pub enum Error {
     LoadSchema(#[from] crate::load_schema::error::Error),
     /// An error while trying to write the generated code to its destination file.
     WriteCodeGenFile(#[from] WriteOutputError),
+    /// An error while dumping the schema of a database.
+    Dump(#[from] crate::dump::error::Error),
+    /// A failed check of the setup.
+    Doctor(#[from] crate::doctor::error::Error),
+    /// A change of the generated signatures between two databases.
+    Diff(#[from] crate::diff::error::Error),
+    /// A denied lint of the query files.
+    Lint(#[from] crate::lint::error::Error),
+    /// An error while creating the starter layout.
+    Init(#[from] crate::init::error::Error),
+    /// An error while checking the generated code is up to date.
+    Check(#[from] crate::check::error::Error),
 }
 
 impl Error {
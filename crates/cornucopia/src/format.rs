@@ -0,0 +1,32 @@
+use crate::log;
+
+/// Formats `code` with `prettyplease`, keeping its leading `header` as is since the non-doc
+/// comments are lost. `code` is returned as is with a warning when it can't be parsed.
+pub(crate) fn format(header: &str, code: String) -> String {
+    log::info!("Formatting the generated code");
+    let body = code.strip_prefix(header).unwrap_or(&code);
+    match syn::parse_file(body) {
+        Ok(file) => format!("{header}{}", prettyplease::unparse(&file)),
+        Err(err) => {
+            eprintln!("Couldn't format the generated code, leaving it as is: {err}");
+            code
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::format;
+
+    #[test]
+    fn keeps_header() {
+        let header = "// Generated\n\n#![allow(clippy::all)]\n";
+        let code = format!("{header}pub mod a{{pub fn b( )->i32{{1}}}}");
+        assert_eq!(
+            format(header, code),
+            format!("{header}pub mod a {{\n    pub fn b() -> i32 {{\n        1\n    }}\n}}\n")
+        );
+        // Left as is when it isn't Rust
+        assert_eq!(format(header, "fn (".to_string()), "fn (");
+    }
+}
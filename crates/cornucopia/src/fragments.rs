@@ -0,0 +1,441 @@
+use std::ops::Range;
+
+use chumsky::prelude::*;
+use miette::SourceSpan;
+
+use crate::{
+    parser::{Attribute, Fragment, Module, Query, Slot, Span},
+    validation,
+};
+
+use self::error::Error;
+
+/// Directive including a fragment, e.g. `--: include(book_columns)`
+const INCLUDE_PREFIX: &str = "--:";
+
+/// Fragment referenced by an include directive
+struct Include {
+    /// Module declaring the fragment, the including one if unqualified
+    module: Option<Span<String>>,
+    name: Span<String>,
+    /// Position of the directive in the SQL of the query, up to the end of its line
+    range: Range<usize>,
+}
+
+/// Parses the include directive at `range` in `sql`, if any. The rest of its line is ignored,
+/// as it would otherwise be part of the comment.
+fn parse_include(sql: &str, range: Range<usize>) -> Option<Result<Include, ()>> {
+    let line = &sql[range.clone()];
+    let rest = line[INCLUDE_PREFIX.len()..].trim_start_matches([' ', '\t']);
+    if !rest.starts_with("include") {
+        return None;
+    }
+    let ident = || {
+        filter(|c: &char| c.is_ascii_alphanumeric() || *c == '_')
+            .repeated()
+            .at_least(1)
+            .collect::<String>()
+            .map_with_span(|value, span: Range<usize>| Span {
+                value,
+                span: span.into(),
+            })
+    };
+    let space = || filter(|c: &char| *c == ' ' || *c == '\t').repeated();
+    let offset = range.start + line.len() - rest.len();
+    let directive = just::<_, _, Simple<char>>("include")
+        .ignore_then(space())
+        .ignore_then(
+            ident()
+                .then(just('.').ignore_then(ident()).or_not())
+                .padded_by(space())
+                .delimited_by(just('('), just(')')),
+        )
+        .then_ignore(none_of('\n').repeated());
+    Some(
+        directive
+            .parse(rest)
+            .map(|(first, second)| {
+                let (module, name) = match second {
+                    Some(name) => (Some(shift(first, offset)), name),
+                    None => (None, first),
+                };
+                Include {
+                    module,
+                    name: shift(name, offset),
+                    range,
+                }
+            })
+            .map_err(|_| ()),
+    )
+}
+
+/// Include directives of `sql`, whose fragment names are positioned in the file
+fn includes(sql: &str, sql_offset: usize, module: &Module) -> Result<Vec<Include>, Error> {
+    let mut includes = Vec::new();
+    for (start, _) in sql.match_indices(INCLUDE_PREFIX) {
+        let end = sql[start..].find('\n').map_or(sql.len(), |it| start + it);
+        match parse_include(sql, start..end) {
+            None => {}
+            Some(Ok(include)) => includes.push(Include {
+                module: include.module.map(|it| shift(it, sql_offset)),
+                name: shift(include.name, sql_offset),
+                range: include.range,
+            }),
+            Some(Err(())) => {
+                return Err(Error::InvalidInclude {
+                    src: (&module.info).into(),
+                    pos: (sql_offset + start, end - start).into(),
+                })
+            }
+        }
+    }
+    Ok(includes)
+}
+
+fn shift(span: Span<String>, offset: usize) -> Span<String> {
+    Span {
+        span: (offset + span.span.offset(), span.span.len()).into(),
+        value: span.value,
+    }
+}
+
+/// Ranges of the included fragments in an expanded SQL, with their directive
+type Included = Vec<(Range<usize>, Range<usize>)>;
+
+/// Splices the fragments included by the queries of `modules` into their SQL, before they are prepared.
+/// Unqualified fragments are declared by the including module, while `module.fragment`
+/// refers to the fragment of another module, e.g. a file shared by several modules.
+///
+/// The slots of queries with variants are included the same way, each combination of
+/// their variants giving a SQL of its own.
+pub(crate) fn include_fragments(modules: &mut [Module]) -> Result<(), Error> {
+    for module in modules.iter() {
+        check_fragments(module)?;
+    }
+    // Fragments are cloned so that the queries of their module can be modified
+    let fragments: Vec<(String, Vec<Fragment>)> = modules
+        .iter()
+        .map(|it| (it.info.name.clone(), it.fragments.clone()))
+        .collect();
+    for module in modules.iter_mut() {
+        let content = module.info.content.clone();
+        let mut spliced = Vec::new();
+        for (idx, query) in module.queries.iter().enumerate() {
+            let variants = Attribute::find(&query.attributes, "variants");
+            let slots = match variants {
+                Some(attribute) => {
+                    validation::variants_attribute(&module.info, attribute)?;
+                    Slot::from_attribute(attribute)
+                }
+                None => Vec::new(),
+            };
+            // The SQL of the query as written, without its final `;`
+            let start = query.sql_span.offset();
+            let sql = &content[start..start + query.sql_span.len() - 1];
+            let includes = includes(sql, start, module)?;
+            if let Some(slot) = slots.iter().find(|slot| {
+                includes
+                    .iter()
+                    .all(|it| it.module.is_some() || it.name != slot.name)
+            }) {
+                return Err(Error::UnusedSlot {
+                    src: (&module.info).into(),
+                    name: slot.name.value.clone(),
+                    pos: slot.name.span,
+                });
+            }
+            if includes.is_empty() {
+                continue;
+            }
+            let expanded = combinations(&slots)
+                .iter()
+                .map(|combination| expand(sql, &includes, module, &fragments, &slots, combination))
+                .collect::<Result<Vec<_>, _>>()?;
+            spliced.push((idx, slots, expanded));
+        }
+        for (idx, slots, expanded) in spliced {
+            let query = &mut module.queries[idx];
+            let mut expanded = expanded.into_iter();
+            let (sql, included) = expanded.next().expect("at least one combination");
+            query.set_sql(sql);
+            // Bindings are reported at their position in the file, the ones of a fragment at its directive
+            for bind in query
+                .bind_params
+                .iter_mut()
+                .chain(query.nullable_bind_params.iter_mut())
+            {
+                bind.span = source_span(bind.span, &included);
+            }
+            if slots.is_empty() {
+                continue;
+            }
+            // The variants are bound like the default SQL
+            query.sql_variants.push(query.sql_str.clone());
+            for (sql, _) in expanded {
+                let (sql, bind_params, nullable_bind_params) = Query::normalize(sql);
+                if bind_params != query.bind_params
+                    || nullable_bind_params != query.nullable_bind_params
+                {
+                    return Err(Error::VariantParams {
+                        src: (&module.info).into(),
+                        name: query.name.value.clone(),
+                        pos: slots[0].span,
+                    });
+                }
+                query.sql_variants.push(sql);
+            }
+            query.slots = slots;
+        }
+    }
+    Ok(())
+}
+
+/// Indices of the variants of each combination of the variants of `slots`, the
+/// default combination first
+fn combinations(slots: &[Slot]) -> Vec<Vec<usize>> {
+    slots.iter().fold(vec![Vec::new()], |combinations, slot| {
+        combinations
+            .into_iter()
+            .flat_map(|combination| {
+                (0..slot.variants.len()).map(move |idx| {
+                    let mut combination = combination.clone();
+                    combination.push(idx);
+                    combination
+                })
+            })
+            .collect()
+    })
+}
+
+/// `sql` with its includes replaced by their fragment, or by the variant of their slot
+/// in `combination`
+fn expand(
+    sql: &str,
+    includes: &[Include],
+    module: &Module,
+    fragments: &[(String, Vec<Fragment>)],
+    slots: &[Slot],
+    combination: &[usize],
+) -> Result<(String, Included), Error> {
+    let mut expanded = String::new();
+    let mut included = Vec::new();
+    let mut last = 0;
+    for include in includes {
+        let slot = slots
+            .iter()
+            .zip(combination)
+            .find(|(slot, _)| include.module.is_none() && slot.name == include.name);
+        let text = match slot {
+            Some((slot, idx)) => variant(slot, &slot.variants[*idx], module, fragments)?,
+            None => resolve(include, module, fragments)?.sql.as_str(),
+        };
+        expanded.push_str(&sql[last..include.range.start]);
+        let from = expanded.len();
+        expanded.push_str(text);
+        included.push((from..expanded.len(), include.range.clone()));
+        last = include.range.end;
+    }
+    expanded.push_str(&sql[last..]);
+    Ok((expanded, included))
+}
+
+/// SQL of the `variant` of `slot`: its fragment if any, or the identifier itself
+fn variant<'a>(
+    slot: &Slot,
+    variant: &'a str,
+    module: &Module,
+    fragments: &'a [(String, Vec<Fragment>)],
+) -> Result<&'a str, Error> {
+    let spanned = |value: &str| Span {
+        span: slot.span,
+        value: value.to_string(),
+    };
+    let include = match variant.split_once('.') {
+        Some((module, name)) => Include {
+            module: Some(spanned(module)),
+            name: spanned(name),
+            range: 0..0,
+        },
+        None => Include {
+            module: None,
+            name: spanned(variant),
+            range: 0..0,
+        },
+    };
+    let declared = fragments
+        .iter()
+        .find(|(name, _)| *name == module.info.name)
+        .map_or(&[][..], |(_, it)| it.as_slice());
+    if include.module.is_none() && declared.iter().all(|it| it.name != include.name) {
+        return Ok(variant);
+    }
+    resolve(&include, module, fragments).map(|it| it.sql.as_str())
+}
+
+/// Position in the query as written of `span`, relative to its expanded SQL
+fn source_span(span: SourceSpan, included: &[(Range<usize>, Range<usize>)]) -> SourceSpan {
+    let pos = span.offset();
+    if let Some((_, directive)) = included
+        .iter()
+        .find(|(expanded, _)| expanded.contains(&pos))
+    {
+        // Binding spans don't include their colon
+        return (directive.start + 1, directive.len() - 1).into();
+    }
+    let offset = included
+        .iter()
+        .filter(|(expanded, _)| expanded.end <= pos)
+        .fold(pos, |pos, (expanded, directive)| {
+            pos - expanded.len() + directive.len()
+        });
+    (offset, span.len()).into()
+}
+
+/// Fragment included by `include`
+fn resolve<'a>(
+    include: &Include,
+    module: &Module,
+    fragments: &'a [(String, Vec<Fragment>)],
+) -> Result<&'a Fragment, Error> {
+    let src = || (&module.info).into();
+    let module_name = include
+        .module
+        .as_ref()
+        .map_or(module.info.name.as_str(), |it| it.value.as_str());
+    let Some((_, declared)) = fragments.iter().find(|(name, _)| name == module_name) else {
+        let module_span = include
+            .module
+            .as_ref()
+            .expect("the including module exists");
+        return Err(Error::UnknownModule {
+            src: src(),
+            name: module_name.to_string(),
+            pos: module_span.span,
+        });
+    };
+    declared
+        .iter()
+        .find(|it| it.name == include.name)
+        .ok_or_else(|| {
+            let known: Vec<_> = declared.iter().map(|it| it.name.value.as_str()).collect();
+            Error::UnknownFragment {
+                src: src(),
+                name: include.name.value.clone(),
+                pos: include.name.span,
+                help: if known.is_empty() {
+                    format!(
+                        "declare it in `{module_name}` with `--: fragment {}`",
+                        include.name
+                    )
+                } else {
+                    format!("use one of those fragments: `{}`", known.join("`, `"))
+                },
+            }
+        })
+}
+
+/// Fragments must have distinct names, and can't include other fragments
+fn check_fragments(module: &Module) -> Result<(), Error> {
+    for (i, fragment) in module.fragments.iter().enumerate() {
+        if let Some(first) = module.fragments[..i]
+            .iter()
+            .find(|it| it.name == fragment.name)
+        {
+            return Err(Error::DuplicateFragment {
+                src: (&module.info).into(),
+                name: fragment.name.value.clone(),
+                first: first.name.span,
+                second: fragment.name.span,
+            });
+        }
+        let start = fragment.sql_span.offset();
+        let sql = &module.info.content[start..start + fragment.sql_span.len()];
+        if let Some(include) = includes(sql, start, module)?.first() {
+            return Err(Error::NestedInclude {
+                src: (&module.info).into(),
+                pos: (start + include.range.start, include.range.len()).into(),
+            });
+        }
+    }
+    Ok(())
+}
+
+pub(crate) mod error {
+    use miette::{Diagnostic, NamedSource, SourceSpan};
+    use thiserror::Error as ThisError;
+
+    use crate::validation::error::Error as ValidationError;
+
+    #[derive(Debug, ThisError, Diagnostic)]
+    pub enum Error {
+        #[error("the fragment `{name}` is defined multiple time")]
+        #[diagnostic(help("use a different name for one of those"))]
+        DuplicateFragment {
+            #[source_code]
+            src: NamedSource,
+            name: String,
+            #[label("previous definition here")]
+            first: SourceSpan,
+            #[label("redefined here")]
+            second: SourceSpan,
+        },
+        #[error("invalid include")]
+        #[diagnostic(help("use `--: include(fragment)` or `--: include(module.fragment)`"))]
+        InvalidInclude {
+            #[source_code]
+            src: NamedSource,
+            #[label("expected the name of a fragment")]
+            pos: SourceSpan,
+        },
+        #[error("reference to an unknown module `{name}`")]
+        #[diagnostic(help("include fragments of the query modules"))]
+        UnknownModule {
+            #[source_code]
+            src: NamedSource,
+            name: String,
+            #[label("unknown module")]
+            pos: SourceSpan,
+        },
+        #[error("reference to an unknown fragment `{name}`")]
+        UnknownFragment {
+            #[source_code]
+            src: NamedSource,
+            name: String,
+            #[label("unknown fragment")]
+            pos: SourceSpan,
+            #[help]
+            help: String,
+        },
+        #[error("the slot `{name}` is never included")]
+        #[diagnostic(help(
+            "include it where its variants are substituted with `--: include({name})`"
+        ))]
+        UnusedSlot {
+            #[source_code]
+            src: NamedSource,
+            name: String,
+            #[label("declared here")]
+            pos: SourceSpan,
+        },
+        #[error("the variants of `{name}` bind different parameters")]
+        #[diagnostic(help("bind the same parameters in each variant"))]
+        VariantParams {
+            #[source_code]
+            src: NamedSource,
+            name: String,
+            #[label("some of these variants bind other parameters")]
+            pos: SourceSpan,
+        },
+        #[error(transparent)]
+        #[diagnostic(transparent)]
+        Validation(#[from] Box<ValidationError>),
+        #[error("fragments can't include other fragments")]
+        #[diagnostic(help("include each fragment in the queries instead"))]
+        NestedInclude {
+            #[source_code]
+            src: NamedSource,
+            #[label("included here")]
+            pos: SourceSpan,
+        },
+    }
+}
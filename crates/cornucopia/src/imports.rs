@@ -0,0 +1,102 @@
+use crate::parser::{Module, NullableIdent, Span};
+
+use self::error::Error;
+
+/// Resolves the row and params structs imported with `--: Name from module`, giving them the
+/// fields declared in their module. Their code is only generated in that module, and re-exported
+/// by the importing ones.
+pub(crate) fn resolve_imports(modules: &mut [Module]) -> Result<(), Error> {
+    let mut resolved = Vec::new();
+    for (module_idx, module) in modules.iter().enumerate() {
+        for (ty_idx, ty) in module.types.iter().enumerate() {
+            let Some(from) = &ty.from else {
+                continue;
+            };
+            let Some(source) = modules
+                .iter()
+                .find(|it| it.info.name == from.value && it.info.name != module.info.name)
+            else {
+                return Err(Error::UnknownModule {
+                    src: (&module.info).into(),
+                    name: from.value.clone(),
+                    pos: from.span,
+                });
+            };
+            if source
+                .types
+                .iter()
+                .any(|it| it.name == ty.name && it.from.is_some())
+            {
+                return Err(Error::NestedImport {
+                    src: (&module.info).into(),
+                    name: ty.name.value.clone(),
+                    module: from.value.clone(),
+                    pos: from.span,
+                });
+            }
+            let Some(fields) = declared_fields(source, &ty.name) else {
+                return Err(Error::UnknownStruct {
+                    src: (&module.info).into(),
+                    name: ty.name.value.clone(),
+                    module: from.value.clone(),
+                    pos: ty.name.span,
+                });
+            };
+            resolved.push((module_idx, ty_idx, fields));
+        }
+    }
+    for (module_idx, ty_idx, fields) in resolved {
+        modules[module_idx].types[ty_idx].fields = fields;
+    }
+    Ok(())
+}
+
+/// Fields of the row or params struct `name` of the queries of `module`
+fn declared_fields(module: &Module, name: &Span<String>) -> Option<Vec<NullableIdent>> {
+    module.queries.iter().find_map(|query| {
+        [(&query.param, Some("Params")), (&query.row, None)]
+            .into_iter()
+            .find_map(|(it, suffix)| {
+                let (fields, struct_name) = it.name_and_fields(&module.types, &query.name, suffix);
+                (struct_name.value == name.value).then(|| fields.to_vec())
+            })
+    })
+}
+
+pub(crate) mod error {
+    use miette::{Diagnostic, NamedSource, SourceSpan};
+    use thiserror::Error as ThisError;
+
+    #[derive(Debug, ThisError, Diagnostic)]
+    pub enum Error {
+        #[error("import from an unknown module `{name}`")]
+        #[diagnostic(help("import the structs of the other query modules"))]
+        UnknownModule {
+            #[source_code]
+            src: NamedSource,
+            name: String,
+            #[label("unknown module")]
+            pos: SourceSpan,
+        },
+        #[error("the module `{module}` has no `{name}` struct")]
+        #[diagnostic(help("import the row or params struct of one of its queries"))]
+        UnknownStruct {
+            #[source_code]
+            src: NamedSource,
+            name: String,
+            module: String,
+            #[label("not found in `{module}`")]
+            pos: SourceSpan,
+        },
+        #[error("`{name}` is imported in `{module}` too")]
+        #[diagnostic(help("import it from the module declaring it"))]
+        NestedImport {
+            #[source_code]
+            src: NamedSource,
+            name: String,
+            module: String,
+            #[label("imported from here")]
+            pos: SourceSpan,
+        },
+    }
+}
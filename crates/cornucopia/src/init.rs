@@ -0,0 +1,116 @@
+use std::path::{Path, PathBuf};
+
+use self::error::Error;
+
+const SCHEMA: &str = "CREATE TABLE author (
+    id SERIAL PRIMARY KEY,
+    name TEXT NOT NULL,
+    country TEXT
+);
+";
+
+const QUERIES: &str = "--! insert_author (country?)
+INSERT INTO author (name, country) VALUES (:name, :country) RETURNING id;
+
+--! authors : (country?)
+SELECT id, name, country FROM author ORDER BY id;
+
+--! author_by_id : (country?)
+SELECT id, name, country FROM author WHERE id = :id;
+";
+
+const CONFIG: &str = r#"# Settings of the code generation, all optional. Validate them with `cornucopia config check`.
+
+# Rust type of the `money` columns: "newtype", "cents" or "decimal"
+# money = "newtype"
+
+# Columns wrapped in a generated newtype, keyed by their `schema.table.column` path
+# [newtypes]
+# "public.author.id" = "AuthorId"
+
+# Overrides of the settings of a query module
+# [module.authors]
+# derives = ["Hash"]
+"#;
+
+/// Build script regenerating the queries into `destination` when they change
+fn build_script(queries_path: &Path, destination: &Path) -> String {
+    format!(
+        r#"use cornucopia::{{CodegenSettings, Config, Error}};
+
+// Regenerates the queries whenever the schema, the queries or the configuration change,
+// against the schema loaded in a container managed by cornucopia.
+fn main() -> Result<(), Error> {{
+    let queries_path = "{}";
+    let schema_file = "schema.sql";
+    let config_file = "cornucopia.toml";
+    let destination = "{}";
+    let settings = CodegenSettings::default()
+        .with_config(Config::from_file(config_file.as_ref(), false)?);
+
+    println!("cargo:rerun-if-changed={{queries_path}}");
+    println!("cargo:rerun-if-changed={{schema_file}}");
+    println!("cargo:rerun-if-changed={{config_file}}");
+    cornucopia::generate_managed(
+        queries_path,
+        &[schema_file],
+        Some(destination),
+        false,
+        settings,
+    )?;
+
+    Ok(())
+}}
+"#,
+        queries_path.display(),
+        destination.display()
+    )
+}
+
+/// Creates a starter layout: a schema, a query module, a commented configuration file and
+/// optionally a build script generating the queries into `destination`. Existing files are
+/// left untouched. Returns the created files.
+pub(crate) fn init(
+    queries_path: &Path,
+    destination: &Path,
+    build_rs: bool,
+) -> Result<Vec<PathBuf>, Error> {
+    let mut files = vec![
+        (PathBuf::from("schema.sql"), SCHEMA.to_string()),
+        (queries_path.join("authors.sql"), QUERIES.to_string()),
+        (PathBuf::from("cornucopia.toml"), CONFIG.to_string()),
+    ];
+    if build_rs {
+        files.push((
+            PathBuf::from("build.rs"),
+            build_script(queries_path, destination),
+        ));
+    }
+    let mut created = Vec::new();
+    for (path, content) in files {
+        if path.exists() {
+            continue;
+        }
+        let io = |err| Error::Io {
+            path: path.to_string_lossy().into_owned(),
+            err,
+        };
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(io)?;
+        }
+        std::fs::write(&path, content).map_err(io)?;
+        created.push(path);
+    }
+    Ok(created)
+}
+
+pub(crate) mod error {
+    use miette::Diagnostic;
+    use thiserror::Error as ThisError;
+
+    #[derive(Debug, ThisError, Diagnostic)]
+    pub enum Error {
+        #[error("Could not create `{path}`: ({err})")]
+        Io { path: String, err: std::io::Error },
+    }
+}
@@ -1,41 +1,247 @@
+mod builder;
+mod cache;
+mod check;
 mod cli;
 mod codegen;
+mod config;
+mod diagnostics;
+mod diff;
+mod doctor;
+mod dump;
 mod error;
+mod format;
+mod fragments;
+mod imports;
+mod init;
+mod lint;
+mod list;
 mod load_schema;
+mod log;
 mod parser;
 mod prepare_queries;
 mod read_queries;
+mod serialize;
+mod sidecar;
+mod sql_analysis;
 mod type_registrar;
 mod utils;
 mod validation;
+mod watch;
 
 /// Helpers to establish connections to database instances.
 pub mod conn;
 /// High-level interfaces to work with Cornucopia's container manager.
 pub mod container;
+/// Extension point to append code to the generated items.
+pub mod plugin;
 
-use std::path::Path;
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 
 use postgres::Client;
 
-use codegen::generate as generate_internal;
+use codegen::{generate as generate_internal, header};
 use error::WriteOutputError;
-use parser::parse_query_module;
-use prepare_queries::prepare;
-use read_queries::read_query_modules;
+use format::format;
+use fragments::include_fragments;
+use imports::resolve_imports;
+use parser::{parse_query_module, Module};
+use plugin::Plugin;
+use prepare_queries::{prepare as prepare_internal, resolve_tables};
+use read_queries::{
+    materialized_views_module, merge_query_folders, read_query_modules, tables_module,
+    MATERIALIZED_VIEWS_MODULE, STDIN_PATH, TABLES_MODULE,
+};
+use sidecar::apply_sidecar;
+use utils::glob_match;
 
 #[doc(hidden)]
 pub use cli::run;
 
+pub use config::{
+    BitType, Codec, Config, DatabaseConfig, IntervalType, LintConfig, LintLevel, ModuleConfig,
+    MoneyType, TablesConfig, Visibility,
+};
 pub use error::Error;
 pub use load_schema::load_schema;
+pub use prepare_queries::Preparation;
 
 /// Struct containing the settings for code generation.
-#[derive(Clone, Copy)]
+#[derive(Debug, Clone)]
 pub struct CodegenSettings {
     pub gen_async: bool,
     pub gen_sync: bool,
     pub derive_ser: bool,
+    /// Generate a `Queries` facade sharing statements prepared once per connection between its
+    /// clones. Ignored with `upstream_compat`.
+    pub gen_facade: bool,
+    /// Generate a `Statements` bundle owning all the statements, with a `prepare_all` method.
+    pub gen_prepare_all: bool,
+    /// Instrument the generated queries with `tracing` spans recording their row count and duration,
+    /// behind the `tracing` feature of the client crates.
+    pub gen_tracing: bool,
+    /// Generate a `QueryError` enum classifying the errors of the database by their SQLSTATE.
+    pub gen_db_errors: bool,
+    /// Generate a `fuzz` module whose functions decode the composite types from arbitrary bytes,
+    /// as entry points of `cargo fuzz` targets.
+    pub gen_fuzz: bool,
+    /// Generate code compiling against the upstream `cornucopia_async` and `cornucopia_sync`
+    /// crates, without the query observers, timeouts, cursors and tracing spans needing this
+    /// fork's. Queries and types needing them fail to prepare.
+    pub upstream_compat: bool,
+    /// Generate the `iter` and `cursor` methods of the queries, streaming their rows. Without
+    /// them, `all` reads every row before mapping them.
+    pub gen_iter: bool,
+    /// Format the generated code with `prettyplease`.
+    pub format: bool,
+    /// Lines written at the top of the generated file, before cornucopia's notice.
+    pub header: String,
+    /// File caching the preparation of the query modules, reused by the next generations for
+    /// the modules whose content, settings and schema didn't change.
+    pub cache: Option<PathBuf>,
+    /// Record the SQL of queries in the `db.statement` attribute of their tracing span.
+    pub trace_statement: bool,
+    /// Folders of queries read along with the main one.
+    pub extra_queries: Vec<PathBuf>,
+    /// Globs of the query files read, relative to their folder. All the files are read if empty.
+    pub include_queries: Vec<String>,
+    /// Globs of the query files skipped even when included.
+    pub exclude_queries: Vec<String>,
+    /// Columns wrapped in a generated newtype, keyed by their `schema.table.column` path.
+    pub newtypes: BTreeMap<String, String>,
+    /// Columns whose owned row fields are `arrayvec::ArrayString<N>` or `[u8; N]`, keyed by their
+    /// `schema.table.column` path.
+    pub inline_columns: BTreeMap<String, String>,
+    /// Types of extensions decoded as `String` or as the given Rust type, keyed by their `schema.name` path.
+    pub extension_types: BTreeMap<String, String>,
+    /// User types decoding and encoding the values of some Postgres types, keyed by their `schema.name` path,
+    /// or of a single column, keyed by its `schema.table.column` path.
+    pub codecs: BTreeMap<String, Codec>,
+    /// Rust type of the `money` columns.
+    pub money: MoneyType,
+    /// Rust type of the `interval` columns.
+    pub interval: IntervalType,
+    /// Rust type of the `bit(n)` and `bit varying` columns.
+    pub bit: BitType,
+    /// Generate `bind_chunked` methods splitting `= ANY(...)` array parameters into chunks of this size.
+    pub any_chunk_size: Option<usize>,
+    /// Cargo feature enabling the latency budget checks outside of tests, which are only
+    /// compiled in tests if unset.
+    pub budget_feature: Option<String>,
+    /// Materialized views for which typed rows and `refresh_<view>` helpers are generated.
+    pub materialized_views: Vec<String>,
+    /// Tables for which row structs and queries selecting their rows are generated.
+    pub tables: TablesConfig,
+    /// Execute parameterless selects against the database, failing if their first rows have null in non-nullable columns.
+    pub verify_nullability: bool,
+    /// Check that the plan hints of queries are read by pg_hint_plan and only use its hints.
+    pub validate_hints: bool,
+    /// Levels of the lints of `cornucopia lint`.
+    pub lint: LintConfig,
+    /// Plugins appending extra code to the generated items.
+    pub plugins: Vec<Arc<dyn Plugin>>,
+    /// Settings overriding these ones for some query modules, keyed by module name.
+    pub modules: BTreeMap<String, ModuleConfig>,
+    /// Databases generated into their own module by [`generate_databases`], keyed by module name.
+    pub databases: BTreeMap<String, DatabaseConfig>,
+}
+
+impl Default for CodegenSettings {
+    fn default() -> Self {
+        Self {
+            gen_async: true,
+            gen_sync: false,
+            derive_ser: false,
+            gen_facade: false,
+            gen_prepare_all: false,
+            gen_tracing: false,
+            gen_db_errors: false,
+            gen_fuzz: false,
+            upstream_compat: false,
+            gen_iter: true,
+            format: false,
+            header: String::new(),
+            cache: None,
+            trace_statement: false,
+            extra_queries: Vec::new(),
+            include_queries: Vec::new(),
+            exclude_queries: Vec::new(),
+            newtypes: BTreeMap::new(),
+            inline_columns: BTreeMap::new(),
+            extension_types: BTreeMap::new(),
+            codecs: BTreeMap::new(),
+            money: MoneyType::Newtype,
+            interval: IntervalType::Struct,
+            bit: BitType::BitString,
+            any_chunk_size: None,
+            budget_feature: None,
+            materialized_views: Vec::new(),
+            tables: TablesConfig::default(),
+            verify_nullability: false,
+            validate_hints: false,
+            lint: LintConfig::default(),
+            plugins: Vec::new(),
+            modules: BTreeMap::new(),
+            databases: BTreeMap::new(),
+        }
+    }
+}
+
+impl CodegenSettings {
+    /// Applies the settings of a configuration file.
+    #[must_use]
+    pub fn with_config(self, config: Config) -> Self {
+        Self {
+            extra_queries: config.extra_queries,
+            header: config.header,
+            cache: config.cache.or(self.cache),
+            include_queries: config.include_queries,
+            exclude_queries: config.exclude_queries,
+            newtypes: config.newtypes,
+            inline_columns: config.inline_columns,
+            extension_types: config.extension_types,
+            codecs: config.codecs,
+            money: config.money,
+            interval: config.interval,
+            bit: config.bit,
+            any_chunk_size: config.any_chunk_size,
+            budget_feature: config.budget_feature,
+            trace_statement: config.trace_statement,
+            materialized_views: config.materialized_views,
+            tables: config.tables,
+            verify_nullability: config.verify_nullability,
+            validate_hints: config.validate_hints,
+            lint: config.lint,
+            modules: config.modules,
+            databases: config.databases,
+            ..self
+        }
+    }
+
+    /// Whether the query file at `path`, relative to its folder, is read
+    pub(crate) fn reads_query_file(&self, path: &str) -> bool {
+        (self.include_queries.is_empty()
+            || self.include_queries.iter().any(|it| glob_match(it, path)))
+            && !self.exclude_queries.iter().any(|it| glob_match(it, path))
+    }
+
+    /// Settings of the query module `name`, with its overrides applied
+    pub(crate) fn for_module(&self, name: &str) -> Self {
+        let Some(module) = self.modules.get(name) else {
+            return self.clone();
+        };
+        let gen_sync = module.sync.unwrap_or(self.gen_sync);
+        Self {
+            gen_sync,
+            gen_async: module.r#async.unwrap_or(self.gen_async) || !gen_sync,
+            derive_ser: module.serialize.unwrap_or(self.derive_ser),
+            gen_iter: module.iter.unwrap_or(self.gen_iter),
+            ..self.clone()
+        }
+    }
 }
 
 /// Generates Rust queries from PostgreSQL queries located at `queries_path`,
@@ -48,14 +254,9 @@ pub fn generate_live<P: AsRef<Path>>(
     destination: Option<P>,
     settings: CodegenSettings,
 ) -> Result<String, Error> {
-    // Read
-    let modules = read_query_modules(queries_path.as_ref())?
-        .into_iter()
-        .map(parse_query_module)
-        .collect::<Result<_, parser::error::Error>>()?;
     // Generate
-    let prepared_modules = prepare(client, modules)?;
-    let generated_code = generate_internal(prepared_modules, settings);
+    let preparation = prepare(client, queries_path, &settings)?;
+    let generated_code = emit(preparation, &settings);
     // Write
     if let Some(d) = destination {
         write_generated_code(d.as_ref(), &generated_code)?;
@@ -69,8 +270,8 @@ pub fn generate_live<P: AsRef<Path>>(
 /// If some `destination` is given, the generated code will be written at that path.
 /// Code generation settings are set using the `settings` parameter.
 ///
-/// By default, the container manager is Docker, but Podman can be used by setting the
-/// `podman` parameter to `true`.
+/// By default, the container manager is Docker, or Podman when Docker isn't installed, but
+/// Podman can be used by setting the `podman` parameter to `true`.
 pub fn generate_managed<P: AsRef<Path>>(
     queries_path: P,
     schema_files: &[P],
@@ -79,15 +280,13 @@ pub fn generate_managed<P: AsRef<Path>>(
     settings: CodegenSettings,
 ) -> Result<String, Error> {
     // Read
-    let modules = read_query_modules(queries_path.as_ref())?
-        .into_iter()
-        .map(parse_query_module)
-        .collect::<Result<_, parser::error::Error>>()?;
+    let mut modules = read_modules(queries_path.as_ref(), &settings)?;
     container::setup(podman)?;
     let mut client = conn::cornucopia_conn()?;
     load_schema(&mut client, schema_files)?;
-    let prepared_modules = prepare(&mut client, modules)?;
-    let generated_code = generate_internal(prepared_modules, settings);
+    add_tables_module(&mut client, &mut modules, &settings)?;
+    let preparation = prepare_internal(&mut client, modules, &settings)?;
+    let generated_code = emit(preparation, &settings);
     container::cleanup(podman)?;
 
     if let Some(destination) = destination {
@@ -97,7 +296,189 @@ pub fn generate_managed<P: AsRef<Path>>(
     Ok(generated_code)
 }
 
+/// Generates the Rust queries of each database of `settings.databases` into its own module, named
+/// after the database. Databases given by their url are live databases managed by you, the others
+/// are created from their schema files in a container managed by cornucopia.
+/// If some `destination` is given, the generated code will be written at that path.
+///
+/// The module overrides of `settings` apply to the database whose queries declare that module.
+pub fn generate_databases<P: AsRef<Path>>(
+    destination: Option<P>,
+    podman: bool,
+    settings: CodegenSettings,
+) -> Result<String, Error> {
+    let managed = settings.databases.values().any(|it| it.url.is_none());
+    if managed {
+        container::setup(podman)?;
+    }
+    let mut generated_code = generate_each_database(&settings)?;
+    if settings.format {
+        generated_code = format(&header(&settings), generated_code);
+    }
+    if managed {
+        container::cleanup(podman)?;
+    }
+
+    if let Some(destination) = destination {
+        write_generated_code(destination.as_ref(), &generated_code)?;
+    };
+
+    Ok(generated_code)
+}
+
+fn generate_each_database(settings: &CodegenSettings) -> Result<String, Error> {
+    let mut generated_code = header(settings);
+    let mut overridden = Vec::new();
+    for (name, database) in &settings.databases {
+        let queries_path = database.queries_path(name);
+        let names: Vec<_> = read_query_modules(&queries_path)?
+            .into_iter()
+            .map(|it| it.name)
+            .collect();
+        // Only the overrides of this database's modules apply to it
+        let modules = settings
+            .modules
+            .iter()
+            .filter(|(module, _)| {
+                names.contains(module)
+                    || [MATERIALIZED_VIEWS_MODULE, TABLES_MODULE].contains(&module.as_str())
+            })
+            .map(|(module, config)| (module.clone(), config.clone()))
+            .collect();
+        // The extra query folders belong to the main queries, not to a database, and each
+        // database has its own cache
+        let settings = CodegenSettings {
+            modules,
+            extra_queries: Vec::new(),
+            cache: settings
+                .cache
+                .as_ref()
+                .map(|it| it.with_extension(format!("{name}.json"))),
+            ..settings.clone()
+        };
+        overridden.extend(settings.modules.keys().cloned());
+        let mut client = match &database.url {
+            Some(url) => conn::from_url(url)?,
+            None => {
+                let mut client = conn::cornucopia_database(name)?;
+                load_schema(&mut client, &database.schema)?;
+                client
+            }
+        };
+        // Formatted at once with the other databases
+        let code = generate_internal(prepare(&mut client, queries_path, &settings)?, &settings);
+        let code = code.strip_prefix(&header(&settings)).unwrap_or(&code);
+        generated_code.push_str(&format!("pub mod {name} {{\n{code}}}\n"));
+    }
+    // The modules overridden by the configuration must exist in some database
+    if let Some(name) = settings
+        .modules
+        .keys()
+        .find(|name| !overridden.contains(name))
+    {
+        return Err(config::error::Error::UnknownModule {
+            name: name.clone(),
+            help: "the module doesn't belong to any of the databases".to_string(),
+        }
+        .into());
+    }
+    Ok(generated_code)
+}
+
+/// Prepares the PostgreSQL queries located at `queries_path` against a live database managed
+/// by you, without generating their code yet. The [`Preparation`] can then be emitted by
+/// [`emit`] as many times as needed, with different settings, without reconnecting to the
+/// database. Only the settings reading the queries and describing them (e.g. the newtypes or the
+/// materialized views) are used here.
+pub fn prepare<P: AsRef<Path>>(
+    client: &mut Client,
+    queries_path: P,
+    settings: &CodegenSettings,
+) -> Result<Preparation, Error> {
+    let mut modules = read_modules(queries_path.as_ref(), settings)?;
+    add_tables_module(client, &mut modules, settings)?;
+    Ok(prepare_internal(client, modules, settings)?)
+}
+
+/// Generates the Rust code of a [`Preparation`]. Only the settings choosing what is generated
+/// (e.g. `gen_sync`, `gen_async` or the plugins) are used here.
+#[must_use]
+pub fn emit(preparation: Preparation, settings: &CodegenSettings) -> String {
+    log::info!("Generating the code");
+    let generated_code = generate_internal(preparation, settings);
+    if settings.format {
+        format(&header(settings), generated_code)
+    } else {
+        generated_code
+    }
+}
+
+/// Reads and parses the query modules of the main and extra query folders, along with the
+/// materialized views module, including their fragments
+fn read_modules(queries_path: &Path, settings: &CodegenSettings) -> Result<Vec<Module>, Error> {
+    let mut folders = Vec::new();
+    for dir_path in
+        std::iter::once(queries_path).chain(settings.extra_queries.iter().map(PathBuf::as_path))
+    {
+        let mut modules_info = read_query_modules(dir_path)?;
+        if dir_path != Path::new(STDIN_PATH) {
+            apply_sidecar(dir_path, &mut modules_info)?;
+            modules_info.retain(|it| {
+                let path = it.path.strip_prefix(dir_path).unwrap_or(&it.path);
+                settings.reads_query_file(&path.to_string_lossy())
+            });
+        }
+        folders.push((dir_path, modules_info));
+    }
+    let mut modules_info = merge_query_folders(folders)?;
+    for module in &modules_info {
+        log::debug!("Read the query module `{}`", module.path.display());
+    }
+    log::info!("Read {} query modules", modules_info.len());
+    if let Some(views) = materialized_views_module(&settings.materialized_views) {
+        modules_info.push(views);
+        modules_info.sort_by(|a, b| a.name.cmp(&b.name));
+    }
+    // The modules overridden by the configuration must exist, the tables module being
+    // only built once connected
+    if let Some(name) = settings.modules.keys().find(|name| {
+        modules_info.iter().all(|it| &it.name != *name)
+            && (*name != TABLES_MODULE || settings.tables.is_empty())
+    }) {
+        let names: Vec<_> = modules_info.iter().map(|it| it.name.as_str()).collect();
+        return Err(config::error::Error::UnknownModule {
+            name: name.clone(),
+            help: format!("the query modules are `{}`", names.join("`, `")),
+        }
+        .into());
+    }
+    let mut modules = modules_info
+        .into_iter()
+        .map(parse_query_module)
+        .collect::<Result<Vec<_>, parser::error::Error>>()?;
+    include_fragments(&mut modules)?;
+    resolve_imports(&mut modules)?;
+    // Files only declaring fragments for other modules aren't generated
+    modules.retain(|it| !it.queries.is_empty() || !it.types.is_empty() || it.fragments.is_empty());
+    Ok(modules)
+}
+
+/// Adds the module of the configured tables, whose columns are read from the database catalog
+fn add_tables_module(
+    client: &mut Client,
+    modules: &mut Vec<Module>,
+    settings: &CodegenSettings,
+) -> Result<(), Error> {
+    let tables = resolve_tables(client, &settings.tables)?;
+    if let Some(info) = tables_module(&tables) {
+        modules.push(parse_query_module(info)?);
+        modules.sort_by(|a, b| a.info.name.cmp(&b.info.name));
+    }
+    Ok(())
+}
+
 fn write_generated_code(destination: &Path, generated_code: &str) -> Result<(), Error> {
+    log::info!("Writing the generated code to `{}`", destination.display());
     Ok(
         std::fs::write(destination, generated_code).map_err(|err| WriteOutputError {
             err,
@@ -0,0 +1,433 @@
+use std::{fmt::Display, path::Path};
+
+use miette::{Diagnostic, LabeledSpan, NamedSource, Severity, SourceCode, SourceSpan};
+use thiserror::Error as ThisError;
+
+use crate::{
+    config::{LintConfig, LintLevel},
+    error::Error,
+    parser::{Attribute, Module, Query},
+    read_modules,
+    read_queries::MATERIALIZED_VIEWS_MODULE,
+    sql_analysis::{output_columns, OutputColumn},
+    CodegenSettings,
+};
+
+/// Risky pattern of the query files
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Lint {
+    SelectStar,
+    UnusedParam,
+    UnusedType,
+    DuplicateQuery,
+    MissingCardinality,
+    ShadowedColumn,
+}
+
+impl Lint {
+    /// Name of the lint, as configured in the `[lint]` section
+    fn name(self) -> &'static str {
+        match self {
+            Self::SelectStar => "select_star",
+            Self::UnusedParam => "unused_param",
+            Self::UnusedType => "unused_type",
+            Self::DuplicateQuery => "duplicate_query",
+            Self::MissingCardinality => "missing_cardinality",
+            Self::ShadowedColumn => "shadowed_column",
+        }
+    }
+
+    fn level(self, config: &LintConfig) -> LintLevel {
+        match self {
+            Self::SelectStar => config.select_star,
+            Self::UnusedParam => config.unused_param,
+            Self::UnusedType => config.unused_type,
+            Self::DuplicateQuery => config.duplicate_query,
+            Self::MissingCardinality => config.missing_cardinality,
+            Self::ShadowedColumn => config.shadowed_column,
+        }
+    }
+}
+
+/// Lint found in a query file, warned about or denied
+#[derive(Debug, ThisError)]
+#[error("{message}")]
+pub struct Finding {
+    lint: Lint,
+    pub(crate) level: LintLevel,
+    message: String,
+    src: NamedSource,
+    span: SourceSpan,
+    label: &'static str,
+    help: String,
+}
+
+impl Diagnostic for Finding {
+    fn code<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        Some(Box::new(format!("lint::{}", self.lint.name())))
+    }
+
+    fn severity(&self) -> Option<Severity> {
+        Some(match self.level {
+            LintLevel::Deny => Severity::Error,
+            _ => Severity::Warning,
+        })
+    }
+
+    fn help<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        Some(Box::new(&self.help))
+    }
+
+    fn source_code(&self) -> Option<&dyn SourceCode> {
+        Some(&self.src)
+    }
+
+    fn labels(&self) -> Option<Box<dyn Iterator<Item = LabeledSpan> + '_>> {
+        Some(Box::new(std::iter::once(LabeledSpan::new_with_span(
+            Some(self.label.to_string()),
+            self.span,
+        ))))
+    }
+}
+
+/// Lints the query files at `queries_path` without a database, returning what the levels of
+/// `settings` don't allow: queries selecting `*`, declared params left unbound, named types
+/// left unused, queries named like another one, queries returning rows without a cardinality
+/// and output columns named like a previous one.
+pub(crate) fn lint(queries_path: &Path, settings: &CodegenSettings) -> Result<Vec<Finding>, Error> {
+    let mut modules = read_modules(queries_path, settings)?;
+    // The queries generated for the materialized views select `*` on purpose
+    modules.retain(|it| it.info.name != MATERIALIZED_VIEWS_MODULE);
+    let mut findings = Vec::new();
+    let mut report = |lint: Lint,
+                      module: &Module,
+                      span: SourceSpan,
+                      label: &'static str,
+                      message: String,
+                      help: String| {
+        let level = lint.level(&settings.lint);
+        if level != LintLevel::Allow {
+            findings.push(Finding {
+                lint,
+                level,
+                message,
+                src: (&module.info).into(),
+                span,
+                label,
+                help,
+            });
+        }
+    };
+    for (module_idx, module) in modules.iter().enumerate() {
+        for (query_idx, query) in module.queries.iter().enumerate() {
+            let name = &query.name.value;
+            let previous = modules[..=module_idx].iter().find(|it| {
+                let queries = if it.info.name == module.info.name {
+                    &it.queries[..query_idx]
+                } else {
+                    &it.queries[..]
+                };
+                queries.iter().any(|it| it.name == query.name)
+            });
+            if let Some(previous) = previous {
+                report(
+                    Lint::DuplicateQuery,
+                    module,
+                    query.name.span,
+                    "declared again here",
+                    format!(
+                        "the query `{name}` is also declared in the module `{}`",
+                        previous.info.name
+                    ),
+                    "rename one of them, so that they aren't mistaken for each other".to_string(),
+                );
+            }
+            for (span, param) in unbound_params(module, query) {
+                report(
+                    Lint::UnusedParam,
+                    module,
+                    span,
+                    "declared here",
+                    format!("the param `{param}` of the query `{name}` isn't bound by its SQL"),
+                    format!("bind it with `:{param}`, or remove it from the annotation"),
+                );
+            }
+            let columns = if query.is_script || Attribute::find(&query.attributes, "copy").is_some()
+            {
+                None
+            } else {
+                output_columns(&query.sql_str)
+            };
+            let Some(columns) = columns.filter(|it| !it.is_empty()) else {
+                continue;
+            };
+            if columns.contains(&OutputColumn::Star) {
+                report(
+                    Lint::SelectStar,
+                    module,
+                    query.sql_span,
+                    "its columns change along with the schema",
+                    format!("the query `{name}` returns `*`"),
+                    "list the columns its rows need".to_string(),
+                );
+            }
+            let mut named = Vec::new();
+            for column in &columns {
+                let OutputColumn::Named(column) = column else {
+                    continue;
+                };
+                if !named.contains(&column) {
+                    named.push(column);
+                    continue;
+                }
+                report(
+                    Lint::ShadowedColumn,
+                    module,
+                    query.sql_span,
+                    "returns it several times",
+                    format!("the column `{column}` of the query `{name}` shadows a previous one"),
+                    "rename one of them with an `AS` clause".to_string(),
+                );
+            }
+            if Attribute::find(&query.attributes, "cardinality").is_none() {
+                report(
+                    Lint::MissingCardinality,
+                    module,
+                    query.name.span,
+                    "returns rows",
+                    format!("the query `{name}` doesn't declare how many rows it returns"),
+                    "declare it with `--# cardinality = one`, `opt` or `all`".to_string(),
+                );
+            }
+        }
+        for ty in &module.types {
+            let used = module.queries.iter().any(|query| {
+                [&query.param.name, &query.row.name]
+                    .into_iter()
+                    .flatten()
+                    .any(|it| *it == ty.name)
+            });
+            // Declared types can also be imported by other modules
+            let imported = ty.from.is_none()
+                && modules.iter().any(|other| {
+                    other.types.iter().any(|it| {
+                        it.name == ty.name
+                            && it
+                                .from
+                                .as_ref()
+                                .is_some_and(|it| it.value == module.info.name)
+                    })
+                });
+            if !used && !imported {
+                report(
+                    Lint::UnusedType,
+                    module,
+                    ty.name.span,
+                    "declared here",
+                    format!("the type `{}` isn't used by any query", ty.name.value),
+                    "use it in the annotation of a query, or remove it".to_string(),
+                );
+            }
+        }
+    }
+    Ok(findings)
+}
+
+/// Declared params of `query` which its SQL doesn't bind, with the span of their declaration
+/// in `module`
+fn unbound_params(module: &Module, query: &Query) -> Vec<(SourceSpan, String)> {
+    let (fields, _) = query
+        .param
+        .name_and_fields(&module.types, &query.name, Some("Params"));
+    // The fields of imported types are declared in another module
+    let imported = query.param.idents.is_none()
+        && module
+            .types
+            .iter()
+            .any(|it| Some(&it.name) == query.param.name.as_ref() && it.from.is_some());
+    fields
+        .iter()
+        .filter(|field| query.bind_params.iter().all(|it| *it != field.name))
+        .map(|field| {
+            let span = match (&query.param.name, imported) {
+                (Some(name), true) => name.span,
+                _ => field.name.span,
+            };
+            (span, field.name.value.clone())
+        })
+        .collect()
+}
+
+pub(crate) mod error {
+    use miette::Diagnostic;
+    use thiserror::Error as ThisError;
+
+    use super::Finding;
+
+    #[derive(Debug, ThisError, Diagnostic)]
+    pub enum Error {
+        #[error("{} of the lints are denied", denied.len())]
+        #[diagnostic(help(
+            "fix the queries, or lower the level of those lints in the `[lint]` section of the configuration file"
+        ))]
+        Denied {
+            #[related]
+            denied: Vec<Finding>,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        config::{LintConfig, LintLevel},
+        CodegenSettings,
+    };
+
+    use super::{lint, Lint};
+
+    /// Lints and messages of the findings in the query modules `files`, at the levels of `config`
+    fn findings(files: &[(&str, &str)], config: LintConfig) -> Vec<(Lint, String)> {
+        let dir = tempfile::tempdir().unwrap();
+        for (name, content) in files {
+            std::fs::write(dir.path().join(format!("{name}.sql")), content).unwrap();
+        }
+        let settings = CodegenSettings {
+            lint: config,
+            ..Default::default()
+        };
+        lint(dir.path(), &settings)
+            .unwrap()
+            .into_iter()
+            .map(|it| (it.lint, it.message))
+            .collect()
+    }
+
+    fn default_findings(content: &str) -> Vec<(Lint, String)> {
+        findings(&[("module", content)], LintConfig::default())
+    }
+
+    #[test]
+    fn select_star() {
+        assert_eq!(
+            default_findings("--! books\nSELECT * FROM book;"),
+            [(
+                Lint::SelectStar,
+                "the query `books` returns `*`".to_string()
+            )]
+        );
+        assert_eq!(
+            default_findings("--! books\nSELECT id, name FROM book;"),
+            []
+        );
+    }
+
+    #[test]
+    fn unused_param() {
+        assert_eq!(
+            default_findings(
+                "--! insert_book (author?, title?)\nINSERT INTO book (author) VALUES (:author);"
+            ),
+            [(
+                Lint::UnusedParam,
+                "the param `title` of the query `insert_book` isn't bound by its SQL".to_string()
+            )]
+        );
+        assert_eq!(
+            default_findings(
+                "--! insert_book (author?)\nINSERT INTO book (author) VALUES (:author);"
+            ),
+            []
+        );
+    }
+
+    #[test]
+    fn unused_type() {
+        let sql = "--: Id()\n--: Unused()\n--! insert_book : Id\nINSERT INTO book (name) VALUES (:name) RETURNING id;";
+        assert_eq!(
+            default_findings(sql),
+            [(
+                Lint::UnusedType,
+                "the type `Unused` isn't used by any query".to_string()
+            )]
+        );
+        // As a row or params type
+        let used = "--: Id()\n--: BookParams()\n--! insert_book BookParams : Id\nINSERT INTO book (name) VALUES (:name) RETURNING id;";
+        assert_eq!(default_findings(used), []);
+    }
+
+    #[test]
+    fn duplicate_query() {
+        let books = "--! books\nSELECT id FROM book;";
+        assert_eq!(
+            default_findings(&format!("{books}\n{books}")),
+            [(
+                Lint::DuplicateQuery,
+                "the query `books` is also declared in the module `module`".to_string()
+            )]
+        );
+        // Across modules, reported on the later one
+        assert_eq!(
+            findings(&[("a", books), ("b", books)], LintConfig::default()),
+            [(
+                Lint::DuplicateQuery,
+                "the query `books` is also declared in the module `a`".to_string()
+            )]
+        );
+        assert_eq!(
+            findings(
+                &[("a", books), ("b", "--! authors\nSELECT id FROM author;")],
+                LintConfig::default()
+            ),
+            []
+        );
+    }
+
+    #[test]
+    fn missing_cardinality() {
+        let books = "--! books\nSELECT id FROM book;";
+        // Allowed by default
+        assert_eq!(default_findings(books), []);
+        let config = LintConfig {
+            missing_cardinality: LintLevel::Warn,
+            ..LintConfig::default()
+        };
+        assert_eq!(
+            findings(&[("module", books)], config.clone()),
+            [(
+                Lint::MissingCardinality,
+                "the query `books` doesn't declare how many rows it returns".to_string()
+            )]
+        );
+        assert_eq!(
+            findings(
+                &[(
+                    "module",
+                    "--! books\n--# cardinality = all\nSELECT id FROM book;"
+                )],
+                config.clone()
+            ),
+            []
+        );
+        // Queries not returning rows have no cardinality
+        assert_eq!(
+            findings(&[("module", "--! delete_books\nDELETE FROM book;")], config),
+            []
+        );
+    }
+
+    #[test]
+    fn shadowed_column() {
+        assert_eq!(
+            default_findings("--! books\nSELECT book.id, author.id FROM book JOIN author ON book.author = author.id;"),
+            [(
+                Lint::ShadowedColumn,
+                "the column `id` of the query `books` shadows a previous one".to_string()
+            )]
+        );
+        assert_eq!(
+            default_findings("--! books\nSELECT book.id, author.id AS author_id FROM book JOIN author ON book.author = author.id;"),
+            []
+        );
+    }
+}
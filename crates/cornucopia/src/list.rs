@@ -0,0 +1,198 @@
+use std::{fmt::Write, path::Path};
+
+use clap::ValueEnum;
+use serde::Serialize;
+
+use crate::{
+    error::Error,
+    parser::{Module, NullableIdent, Query},
+    read_modules, CodegenSettings,
+};
+
+/// Output format of the `list` command
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub(crate) enum ListFormat {
+    /// One line per query, grouped by module
+    Text,
+    /// A JSON array of the modules
+    Json,
+}
+
+/// Query module, as listed from its file
+#[derive(Debug, Serialize)]
+struct ModuleEntry {
+    name: String,
+    path: String,
+    queries: Vec<QueryEntry>,
+}
+
+/// Query, as parsed from its annotation and SQL
+#[derive(Debug, Serialize)]
+struct QueryEntry {
+    name: String,
+    sql: String,
+    /// Made of several statements run as one batch
+    script: bool,
+    params: StructEntry,
+    row: StructEntry,
+}
+
+/// Params or row struct of a query. Only the declared parts are known without a database:
+/// the explicit struct name and the fields annotated in the query files.
+#[derive(Debug, Serialize)]
+struct StructEntry {
+    #[serde(rename = "struct")]
+    name: Option<String>,
+    fields: Vec<FieldEntry>,
+}
+
+#[derive(Debug, Serialize)]
+struct FieldEntry {
+    name: String,
+    nullable: bool,
+    inner_nullable: bool,
+    non_null: bool,
+    /// Rust type declared for the field, if any
+    #[serde(skip_serializing_if = "Option::is_none")]
+    rust_type: Option<String>,
+}
+
+impl From<&NullableIdent> for FieldEntry {
+    fn from(ident: &NullableIdent) -> Self {
+        Self {
+            name: ident.name.value.clone(),
+            nullable: ident.nullable,
+            inner_nullable: ident.inner_nullable,
+            non_null: ident.non_null,
+            rust_type: ident.rust_type.as_ref().map(|it| it.value.clone()),
+        }
+    }
+}
+
+impl QueryEntry {
+    fn new(module: &Module, query: &Query) -> Self {
+        let (param_fields, _) =
+            query
+                .param
+                .name_and_fields(&module.types, &query.name, Some("Params"));
+        let (row_fields, _) = query.row.name_and_fields(&module.types, &query.name, None);
+        // The params are the bind parameters, with the nullability declared for them
+        let mut params: Vec<FieldEntry> = Vec::new();
+        for bind in &query.bind_params {
+            if params.iter().any(|it| it.name == bind.value) {
+                continue;
+            }
+            let declared = param_fields.iter().find(|it| it.name == *bind);
+            let mut field = declared.map_or_else(
+                || FieldEntry {
+                    name: bind.value.clone(),
+                    nullable: false,
+                    inner_nullable: false,
+                    non_null: false,
+                    rust_type: None,
+                },
+                FieldEntry::from,
+            );
+            field.nullable |= query.nullable_bind_params.contains(bind);
+            params.push(field);
+        }
+        Self {
+            name: query.name.value.clone(),
+            sql: query.sql_str.clone(),
+            script: query.is_script,
+            params: StructEntry {
+                name: query.param.name.as_ref().map(|it| it.value.clone()),
+                fields: params,
+            },
+            row: StructEntry {
+                name: query.row.name.as_ref().map(|it| it.value.clone()),
+                fields: row_fields.iter().map(FieldEntry::from).collect(),
+            },
+        }
+    }
+
+    /// Single line summary, e.g. `author_by_id(id) : Author(name?)`
+    fn summary(&self) -> String {
+        let mut line = format!("{}(", self.name);
+        for (i, field) in self.params.fields.iter().enumerate() {
+            if i > 0 {
+                line.push_str(", ");
+            }
+            line.push_str(&field.annotation());
+        }
+        line.push(')');
+        if let Some(name) = &self.params.name {
+            write!(line, " {name}").unwrap();
+        }
+        let row = &self.row;
+        if row.name.is_some() || !row.fields.is_empty() {
+            line.push_str(" : ");
+            line.push_str(row.name.as_deref().unwrap_or_default());
+            if !row.fields.is_empty() {
+                let fields: Vec<_> = row.fields.iter().map(FieldEntry::annotation).collect();
+                write!(line, "({})", fields.join(", ")).unwrap();
+            }
+        }
+        if self.script {
+            line.push_str(" [script]");
+        }
+        line
+    }
+}
+
+impl FieldEntry {
+    /// Field as written in a query annotation, e.g. `name?[?]` or `price: crate::Money`
+    fn annotation(&self) -> String {
+        let mut annotation = self.name.clone();
+        if self.nullable {
+            annotation.push('?');
+        } else if self.non_null {
+            annotation.push('!');
+        }
+        if self.inner_nullable {
+            annotation.push_str("[?]");
+        }
+        if let Some(rust_type) = &self.rust_type {
+            write!(annotation, ": {rust_type}").unwrap();
+        }
+        annotation
+    }
+}
+
+/// Lists the modules at `queries_path` along with their queries, their params and their rows,
+/// without preparing them against a database.
+pub(crate) fn list(
+    queries_path: &Path,
+    settings: &CodegenSettings,
+    format: ListFormat,
+) -> Result<String, Error> {
+    let modules: Vec<_> = read_modules(queries_path, settings)?
+        .iter()
+        .map(|module| ModuleEntry {
+            name: module.info.name.clone(),
+            path: module.info.path.to_string_lossy().into_owned(),
+            queries: module
+                .queries
+                .iter()
+                .map(|query| QueryEntry::new(module, query))
+                .collect(),
+        })
+        .collect();
+    Ok(match format {
+        ListFormat::Text => {
+            let mut out = String::new();
+            for module in &modules {
+                writeln!(out, "{}", module.name).unwrap();
+                for query in &module.queries {
+                    writeln!(out, "  {}", query.summary()).unwrap();
+                }
+            }
+            out
+        }
+        ListFormat::Json => {
+            let mut out = serde_json::to_string_pretty(&modules).expect("listing is serializable");
+            out.push('\n');
+            out
+        }
+    })
+}
@@ -1,45 +1,190 @@
-use std::path::Path;
+use std::{io::Write, path::Path};
 
 use miette::NamedSource;
 use postgres::Client;
 
-use crate::utils::db_err;
+use crate::{
+    log,
+    sql_analysis::{self, CopyTarget},
+    utils::db_err,
+};
 
 use self::error::Error;
 
+/// Part of a schema file, split around its psql meta-commands
+enum Chunk<'a> {
+    /// Plain SQL, starting at a byte offset of the file
+    Sql { sql: &'a str, offset: usize },
+    /// `COPY ... FROM stdin` statement, followed by its inline data in the file
+    CopyIn {
+        statement: &'a str,
+        offset: usize,
+        data: &'a str,
+    },
+}
+
 /// Loads PostgreSQL schemas into a database.
 ///
 /// Takes a list of file paths as parameter and loads them in their given order.
+/// Schema dumps are supported: the psql meta-commands only configuring psql, such as
+/// `\connect` or `\set ON_ERROR_STOP on`, are skipped and the inline data of
+/// `COPY ... FROM stdin` statements is copied. Other meta-commands are rejected.
 pub fn load_schema<P: AsRef<Path>>(client: &mut Client, paths: &[P]) -> Result<(), Error> {
     for path in paths {
         let path = path.as_ref();
+        log::info!("Loading the schema file `{}`", path.display());
         let sql = std::fs::read_to_string(path).map_err(|err| Error::Io {
             path: path.to_string_lossy().to_string(),
             err,
         })?;
-        client.batch_execute(&sql).map_err(|err| {
-            let msg = format!("{err:#}");
-            let src = NamedSource::new(path.to_string_lossy(), sql);
-            if let Some((position, msg, help)) = db_err(&err) {
-                Error::Postgres {
-                    msg,
-                    help,
-                    src,
-                    err_span: Some((position as usize..position as usize).into()),
-                }
-            } else {
-                Error::Postgres {
-                    msg,
-                    help: None,
-                    src,
-                    err_span: None,
+        let src = || NamedSource::new(path.to_string_lossy(), sql.clone());
+        for chunk in split_schema(&sql, src)? {
+            match chunk {
+                Chunk::Sql { sql, offset } => client
+                    .batch_execute(sql)
+                    .map_err(|err| postgres_err(&err, src(), offset))?,
+                Chunk::CopyIn {
+                    statement,
+                    offset,
+                    data,
+                } => {
+                    // Errors of the copied data have no position, so they point to the statement
+                    let offset = offset + sql_analysis::statement_starts(statement)[0];
+                    let copy_err = |msg: String| Error::Postgres {
+                        msg,
+                        help: None,
+                        src: src(),
+                        err_span: Some((offset..offset).into()),
+                    };
+                    let mut writer = client
+                        .copy_in(statement)
+                        .map_err(|err| copy_err(format!("{err:#}")))?;
+                    writer
+                        .write_all(data.as_bytes())
+                        .map_err(|err| copy_err(err.to_string()))?;
+                    writer
+                        .finish()
+                        .map_err(|err| copy_err(format!("{err:#}")))?;
                 }
             }
-        })?;
+        }
     }
     Ok(())
 }
 
+/// Error of the statements of a schema file executed from the byte `offset` of the file
+fn postgres_err(err: &postgres::Error, src: NamedSource, offset: usize) -> Error {
+    let msg = format!("{err:#}");
+    if let Some((position, msg, help)) = db_err(err) {
+        let position = offset + position as usize;
+        Error::Postgres {
+            msg,
+            help,
+            src,
+            err_span: Some((position..position).into()),
+        }
+    } else {
+        Error::Postgres {
+            msg,
+            help: None,
+            src,
+            err_span: None,
+        }
+    }
+}
+
+/// Splits `sql` around the psql meta-commands starting a line between two statements, like psql
+/// would run it. The inline data of the `COPY ... FROM stdin` statements, ending with a `\.`
+/// line, is kept along with them.
+fn split_schema(sql: &str, src: impl Fn() -> NamedSource) -> Result<Vec<Chunk<'_>>, Error> {
+    let mut chunks = Vec::new();
+    // Start of the SQL not split yet, and of the statement being read
+    let mut start = 0;
+    let mut statement = 0;
+    let mut lines = sql.split_inclusive('\n').scan(0, |offset, line| {
+        let start = *offset;
+        *offset += line.len();
+        Some((start, line))
+    });
+    while let Some((line_start, line)) = lines.next() {
+        let pending = &sql[statement..line_start];
+        if !sql_analysis::ends_statement(pending) {
+            continue;
+        }
+        if let Some(CopyTarget::In(_)) = sql_analysis::copy(pending) {
+            let (data_end, end) = std::iter::once((line_start, line))
+                .chain(lines.by_ref())
+                .find(|(_, line)| line.trim_end() == "\\.")
+                .map_or((sql.len(), sql.len()), |(start, line)| {
+                    (start, start + line.len())
+                });
+            chunks.push(Chunk::Sql {
+                sql: &sql[start..statement],
+                offset: start,
+            });
+            chunks.push(Chunk::CopyIn {
+                statement: pending,
+                offset: statement,
+                data: &sql[line_start..data_end],
+            });
+            start = end;
+            statement = end;
+        } else if let Some(command) = line.trim_start().strip_prefix('\\') {
+            let command = command.trim_end();
+            if let Some(help) = unsupported_meta_command(command) {
+                let span = line_start + (line.len() - line.trim_start().len());
+                return Err(Error::MetaCommand {
+                    command: command.to_string(),
+                    src: src(),
+                    span: (span..span + command.len() + 1).into(),
+                    help,
+                });
+            }
+            chunks.push(Chunk::Sql {
+                sql: &sql[start..line_start],
+                offset: start,
+            });
+            start = line_start + line.len();
+            statement = start;
+        } else {
+            statement = line_start;
+        }
+    }
+    chunks.push(Chunk::Sql {
+        sql: &sql[start..],
+        offset: start,
+    });
+    Ok(chunks)
+}
+
+/// Why the psql meta-command `command` can't be loaded, unless it can be skipped as it only
+/// configures psql or selects the database the schema is loaded into
+fn unsupported_meta_command(command: &str) -> Option<String> {
+    let mut args = command.split_whitespace();
+    let name = args.next().unwrap_or_default();
+    match name {
+        "c" | "connect" | "echo" | "encoding" | "pset" | "qecho" | "restrict" | "timing"
+        | "unrestrict" => None,
+        // Only psql's own variables are uppercase, the others would be interpolated
+        "set" | "unset" => match args.next() {
+            Some(var) if !var.chars().all(|c| c.is_ascii_uppercase() || c == '_') => Some(format!(
+                "psql variables aren't interpolated, use the value of `{var}` in place of `:{var}`"
+            )),
+            _ => None,
+        },
+        "i" | "ir" | "include" | "include_relative" => {
+            Some("list the included file as a schema of its own instead".to_string())
+        }
+        "." => Some(
+            "inline data must follow a `COPY ... FROM stdin` statement on its own line".to_string(),
+        ),
+        _ => Some(
+            "schemas are loaded as plain SQL, with the inline data of `COPY ... FROM stdin` statements: remove this meta-command"
+                .to_string(),
+        ),
+    }
+}
+
 pub(crate) mod error {
     use miette::{Diagnostic, NamedSource, SourceSpan};
     use thiserror::Error as ThisError;
@@ -58,5 +203,15 @@ pub(crate) mod error {
             #[label("error occurs near this location")]
             err_span: Option<SourceSpan>,
         },
+        #[error("Unsupported psql meta-command `\\{command}`")]
+        MetaCommand {
+            command: String,
+            #[source_code]
+            src: NamedSource,
+            #[label("not plain SQL")]
+            span: SourceSpan,
+            #[help]
+            help: String,
+        },
     }
 }
@@ -0,0 +1,51 @@
+use std::{
+    fmt::Arguments,
+    sync::{
+        atomic::{AtomicU8, Ordering},
+        OnceLock,
+    },
+    time::Instant,
+};
+
+/// Verbosity of the messages written on stderr, none being written at 0
+static VERBOSITY: AtomicU8 = AtomicU8::new(0);
+
+/// Instant the messages are timed from
+static START: OnceLock<Instant> = OnceLock::new();
+
+/// Writes the messages up to `verbosity`: the phases at 1, e.g. preparing a module or
+/// starting the container, and their steps at 2, e.g. reading a file or preparing a query
+pub(crate) fn set_verbosity(verbosity: u8) {
+    START.get_or_init(Instant::now);
+    VERBOSITY.store(verbosity, Ordering::Relaxed);
+}
+
+pub(crate) fn enabled(verbosity: u8) -> bool {
+    VERBOSITY.load(Ordering::Relaxed) >= verbosity
+}
+
+/// Writes a message, prefixed by the seconds elapsed since the verbosity was set
+pub(crate) fn write(args: Arguments) {
+    let elapsed = START.get_or_init(Instant::now).elapsed();
+    eprintln!("[{:>8.3}s] {args}", elapsed.as_secs_f64());
+}
+
+/// Logs a phase of the generation
+macro_rules! info {
+    ($($arg:tt)*) => {
+        if $crate::log::enabled(1) {
+            $crate::log::write(format_args!($($arg)*));
+        }
+    };
+}
+
+/// Logs a step of a phase of the generation
+macro_rules! debug {
+    ($($arg:tt)*) => {
+        if $crate::log::enabled(2) {
+            $crate::log::write(format_args!($($arg)*));
+        }
+    };
+}
+
+pub(crate) use {debug, info};
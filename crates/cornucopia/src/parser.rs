@@ -4,13 +4,15 @@ use chumsky::prelude::*;
 use error::Error;
 use heck::ToUpperCamelCase;
 use miette::SourceSpan;
+use serde::{Deserialize, Serialize};
 
 use crate::read_queries::ModuleInfo;
 
 /// Th    if is data structure holds a value and the context in which it was parsed.
 /// This context is used for error reporting.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Span<T> {
+    #[serde(with = "crate::serialize::source_span")]
     pub(crate) span: SourceSpan,
     pub(crate) value: T,
 }
@@ -94,9 +96,9 @@ fn space() -> impl Parser<char, (), Error = Simple<char>> {
 }
 
 fn blank() -> impl Parser<char, (), Error = Simple<char>> {
-    // We want to escape valid SQL comment beginning with -- while not escaping our syntax --: or --!
+    // We want to escape valid SQL comment beginning with -- while not escaping our syntax --:, --! or --#
     let comment = just("--")
-        .then(none_of(":!").rewind())
+        .then(none_of(":!#").rewind())
         .then(none_of('\n').repeated());
     filter(|c: &char| c.is_whitespace())
         .ignored()
@@ -109,18 +111,69 @@ fn blank() -> impl Parser<char, (), Error = Simple<char>> {
 pub struct NullableIdent {
     pub name: Span<String>,
     pub nullable: bool,
+    /// Declared `name!`, never null even when inferred nullable
+    pub non_null: bool,
     pub inner_nullable: bool,
+    /// Rust type declared as `name: crate::Money`, used in place of the inferred one
+    pub rust_type: Option<Span<String>>,
+}
+
+/// Path of a Rust type along with its generic arguments, e.g. `Vec<crate::Tag>`, or a tuple
+/// of types, e.g. `(i32, String)`
+fn rust_type() -> impl Parser<char, Span<String>, Error = Simple<char>> {
+    recursive(|ty| {
+        let path = filter(|c: &char| c.is_ascii_alphanumeric() || "_:".contains(*c))
+            .repeated()
+            .at_least(1)
+            .collect::<String>();
+        let types = || {
+            space()
+                .ignore_then(ty.clone().map(|it: Span<String>| it.value))
+                .then_ignore(space())
+                .separated_by(just(','))
+                .at_least(1)
+        };
+        let generics = types().delimited_by(just('<'), just('>'));
+        let tuple = types()
+            .allow_trailing()
+            .delimited_by(just('('), just(')'))
+            .map(|types| match types.as_slice() {
+                [ty] => format!("({ty},)"),
+                _ => format!("({})", types.join(", ")),
+            });
+        let path = path
+            .then(generics.or_not())
+            .map(|(path, generics)| match generics {
+                Some(generics) => format!("{path}<{}>", generics.join(", ")),
+                None => path,
+            });
+        tuple
+            .or(path)
+            .map_with_span(|value, span: Range<usize>| Span {
+                value,
+                span: span.into(),
+            })
+    })
 }
 
 fn parse_nullable_ident() -> impl Parser<char, Vec<NullableIdent>, Error = Simple<char>> {
     space()
         .ignore_then(ident())
-        .then(just('?').or_not())
+        .then(one_of("?!").or_not())
         .then(just("[?]").or_not())
-        .map(|((name, null), inner_null)| NullableIdent {
+        .then(
+            space()
+                .ignore_then(just(':'))
+                .ignore_then(space())
+                .ignore_then(rust_type())
+                .or_not(),
+        )
+        .map(|(((name, null), inner_null), rust_type)| NullableIdent {
             name,
-            nullable: null.is_some(),
+            nullable: null == Some('?'),
+            non_null: null == Some('!'),
             inner_nullable: inner_null.is_some(),
+            rust_type,
         })
         .then_ignore(space())
         .separated_by(just(','))
@@ -132,16 +185,205 @@ fn parse_nullable_ident() -> impl Parser<char, Vec<NullableIdent>, Error = Simpl
 pub struct TypeAnnotation {
     pub name: Span<String>,
     pub fields: Vec<NullableIdent>,
+    /// Module declaring this row or params struct, for the ones imported with
+    /// `--: Name from module`. Its fields are the ones of the declaration.
+    pub from: Option<Span<String>>,
 }
 
 impl TypeAnnotation {
     fn parser() -> impl Parser<char, Self, Error = Simple<char>> {
+        let from = just("from")
+            .ignore_then(
+                filter(|c: &char| c.is_whitespace() && *c != '\n')
+                    .repeated()
+                    .at_least(1),
+            )
+            .ignore_then(plain_ident());
         just("--:")
             .ignore_then(space())
             .ignore_then(ident())
             .then_ignore(space())
-            .then(parse_nullable_ident())
-            .map(|(name, fields)| Self { name, fields })
+            .then(
+                parse_nullable_ident()
+                    .map(|fields| (fields, None))
+                    .or(from.map(|module| (Vec::new(), Some(module)))),
+            )
+            .map(|(name, (fields, from))| Self { name, fields, from })
+    }
+}
+
+/// A piece of SQL declared with `--: fragment name` and spliced into the queries
+/// including it with `--: include(name)`
+#[derive(Debug, Clone)]
+pub(crate) struct Fragment {
+    pub(crate) name: Span<String>,
+    /// Raw SQL, with its bindings left as is
+    pub(crate) sql: String,
+    pub(crate) sql_span: SourceSpan,
+}
+
+impl Fragment {
+    fn parser() -> impl Parser<char, Self, Error = Simple<char>> {
+        just("--:")
+            .ignore_then(space())
+            .ignore_then(just("fragment"))
+            .ignore_then(
+                filter(|c: &char| c.is_whitespace() && *c != '\n')
+                    .repeated()
+                    .at_least(1),
+            )
+            .ignore_then(plain_ident())
+            .then_ignore(space())
+            .then_ignore(ln())
+            .then(
+                none_of(";")
+                    .repeated()
+                    .collect::<String>()
+                    .map_with_span(|sql, span: Range<usize>| (sql, span))
+                    .then_ignore(just(';')),
+            )
+            .map(|(name, (sql, span))| Self {
+                name,
+                sql: sql.trim().to_string(),
+                sql_span: span.into(),
+            })
+    }
+}
+
+/// Part of a query substituted by one of its variants when creating its statement, declared with
+/// `--# variants(slot = "a|b")` and included like a fragment with `--: include(slot)`
+#[derive(Debug, Clone)]
+pub(crate) struct Slot {
+    pub(crate) name: Span<String>,
+    /// Fragments, or identifiers substituted as is, the first one being the default
+    pub(crate) variants: Vec<String>,
+    /// Position of the variants
+    pub(crate) span: SourceSpan,
+}
+
+impl Slot {
+    /// Slots declared by a valid `variants` attribute
+    pub(crate) fn from_attribute(attribute: &Attribute) -> Vec<Self> {
+        let AttributeValue::List(nested) = &attribute.value else {
+            return Vec::new();
+        };
+        nested
+            .iter()
+            .filter_map(|slot| match &slot.value {
+                AttributeValue::Value(value) => Some(Self {
+                    name: slot.name.clone(),
+                    variants: value
+                        .value
+                        .split('|')
+                        .map(|it| it.trim().to_string())
+                        .collect(),
+                    span: value.span,
+                }),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+/// An attribute refining the code generated for a query or a module,
+/// e.g. `--# trace(level = "info")`
+#[derive(Debug, Clone)]
+pub(crate) struct Attribute {
+    pub(crate) name: Span<String>,
+    pub(crate) value: AttributeValue,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) enum AttributeValue {
+    /// `name`
+    Flag,
+    /// `name = value` or `name = "value"`
+    Value(Span<String>),
+    /// `name(nested, ...)`
+    List(Vec<Attribute>),
+}
+
+impl Attribute {
+    /// Find the attribute named `name` in `attributes`
+    pub(crate) fn find<'a>(attributes: &'a [Self], name: &str) -> Option<&'a Self> {
+        attributes.iter().find(|it| it.name.value == name)
+    }
+
+    /// Value of the nested attribute named `name`, if any
+    pub(crate) fn nested_value(&self, name: &str) -> Option<&str> {
+        self.nested_span(name).map(|it| it.value.as_str())
+    }
+
+    /// Value of the nested attribute named `name` along with its position, if any
+    pub(crate) fn nested_span(&self, name: &str) -> Option<&Span<String>> {
+        match &self.value {
+            AttributeValue::List(nested) => match Self::find(nested, name).map(|it| &it.value) {
+                Some(AttributeValue::Value(value)) => Some(value),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    fn parser() -> impl Parser<char, Self, Error = Simple<char>> {
+        recursive(|attribute| {
+            let string = none_of('"').repeated().delimited_by(just('"'), just('"'));
+            let token = filter(|c: &char| c.is_ascii_alphanumeric() || "_.:-".contains(*c))
+                .repeated()
+                .at_least(1);
+            let value =
+                string
+                    .or(token)
+                    .collect()
+                    .map_with_span(|value: String, span: Range<usize>| Span {
+                        value,
+                        span: span.into(),
+                    });
+            let list = space()
+                .ignore_then(attribute)
+                .then_ignore(space())
+                .separated_by(just(','))
+                .allow_trailing()
+                .delimited_by(just('('), just(')'));
+            // Names can be paths, e.g. the traits of `derive(Hash, serde::Deserialize)`
+            let name = plain_ident()
+                .separated_by(just("::"))
+                .at_least(1)
+                .map_with_span(|parts: Vec<Span<String>>, span: Range<usize>| Span {
+                    value: parts
+                        .into_iter()
+                        .map(|it| it.value)
+                        .collect::<Vec<_>>()
+                        .join("::"),
+                    span: span.into(),
+                });
+            name.then_ignore(space())
+                .then(
+                    just('=')
+                        .ignore_then(space())
+                        .ignore_then(value)
+                        .map(AttributeValue::Value)
+                        .or(list.map(AttributeValue::List))
+                        .or_not(),
+                )
+                .map(|(name, value)| Self {
+                    name,
+                    value: value.unwrap_or(AttributeValue::Flag),
+                })
+        })
+    }
+
+    /// Parse a line of comma separated attributes following `prefix`
+    fn line(prefix: &'static str) -> impl Parser<char, Vec<Self>, Error = Simple<char>> {
+        just(prefix)
+            .then(none_of('!').rewind())
+            .ignore_then(space())
+            .ignore_then(
+                Self::parser()
+                    .then_ignore(space())
+                    .separated_by(just(',').then(space()))
+                    .at_least(1),
+            )
     }
 }
 
@@ -153,6 +395,14 @@ pub(crate) struct Query {
     pub(crate) sql_span: SourceSpan,
     pub(crate) sql_str: String,
     pub(crate) bind_params: Vec<Span<String>>,
+    /// Bind parameters declared nullable in the SQL, as in `:title?`
+    pub(crate) nullable_bind_params: Vec<Span<String>>,
+    pub(crate) attributes: Vec<Attribute>,
+    /// Made of several `;` separated statements
+    pub(crate) is_script: bool,
+    pub(crate) slots: Vec<Slot>,
+    /// SQL of each combination of the variants of the slots, the first being `sql_str`
+    pub(crate) sql_variants: Vec<String>,
 }
 
 impl Query {
@@ -211,33 +461,109 @@ impl Query {
     }
 
     /// Parse sql query, normalizing named parameters
-    fn parse_sql_query(
-    ) -> impl Parser<char, (String, SourceSpan, Vec<Span<String>>), Error = Simple<char>> {
-        none_of(";")
+    #[allow(clippy::type_complexity)]
+    fn parse_sql_query() -> impl Parser<
+        char,
+        (
+            String,
+            SourceSpan,
+            Vec<Span<String>>,
+            Vec<Span<String>>,
+            bool,
+        ),
+        Error = Simple<char>,
+    > {
+        let statement = || {
+            none_of(";")
+                .repeated()
+                .collect::<String>()
+                .then_ignore(just(';'))
+        };
+        // Blanks and comments between the statements of a script, kept so that the
+        // positions in the script match the ones in the file
+        let comment = just("--")
+            .then(none_of(":!#").rewind())
+            .then(none_of('\n').repeated().collect::<String>())
+            .map(|((dashes, _), comment)| format!("{dashes}{comment}"));
+        let gap = filter(|c: &char| c.is_whitespace())
+            .map(String::from)
+            .or(comment)
             .repeated()
-            .then_ignore(just(';'))
-            .collect::<String>()
-            .map_with_span(|mut sql_str, span: Range<usize>| {
-                let bind_params: Vec<_> = Self::parse_bind().parse(sql_str.clone()).unwrap();
-                // Remove duplicate
-                let dedup_params: Vec<_> = bind_params
-                    .iter()
-                    .enumerate()
-                    .rev()
-                    .filter(|(i, u)| !bind_params[..*i].contains(u))
-                    .map(|(_, u)| u.clone())
-                    .rev()
-                    .collect();
-
-                for bind_param in bind_params.iter().rev() {
-                    let index = dedup_params.iter().position(|bp| bp == bind_param).unwrap();
-                    let start = bind_param.span.offset() - 1;
-                    let end = start + bind_param.span.len();
-                    sql_str.replace_range(start..=end, &format!("${}", index + 1));
-                }
+            .map(|it| it.concat());
+        // Following statements, until the next annotation
+        let next = gap
+            .then_ignore(
+                none_of("-;")
+                    .ignored()
+                    .or(just("--:").then(space()).then(just("include")).ignored())
+                    .rewind(),
+            )
+            .then(statement());
+        statement()
+            .then(next.repeated())
+            .map(|(first, next)| {
+                let is_script = !next.is_empty();
+                let sql_str = next.into_iter().fold(first, |sql, (gap, statement)| {
+                    format!("{sql};{gap}{statement}")
+                });
+                (sql_str, is_script)
+            })
+            .map_with_span(|(sql_str, is_script), span: Range<usize>| {
+                let (sql_str, bind_params, nullable_params) = Self::normalize(sql_str);
+                (
+                    sql_str,
+                    span.into(),
+                    bind_params,
+                    nullable_params,
+                    is_script,
+                )
+            })
+    }
 
-                (sql_str, span.into(), dedup_params)
+    /// Replace the named parameters of `sql_str` by positional ones, returning them
+    /// along with the ones declared nullable
+    pub(crate) fn normalize(mut sql_str: String) -> (String, Vec<Span<String>>, Vec<Span<String>>) {
+        let bind_params: Vec<_> = Self::parse_bind().parse(sql_str.clone()).unwrap();
+        // A `?` right after a bind marks it nullable, unless it starts the `?|` or
+        // `?&` jsonb operators
+        let nullable: Vec<_> = bind_params
+            .iter()
+            .map(|it| {
+                let rest = &sql_str[it.span.offset() + it.span.len()..];
+                rest.starts_with('?') && !rest.starts_with("?|") && !rest.starts_with("?&")
             })
+            .collect();
+        let mut nullable_params: Vec<Span<String>> = Vec::new();
+        for (bind_param, _) in bind_params.iter().zip(&nullable).filter(|(_, it)| **it) {
+            if !nullable_params.contains(bind_param) {
+                nullable_params.push(bind_param.clone());
+            }
+        }
+        // Remove duplicate
+        let dedup_params: Vec<_> = bind_params
+            .iter()
+            .enumerate()
+            .rev()
+            .filter(|(i, u)| !bind_params[..*i].contains(u))
+            .map(|(_, u)| u.clone())
+            .rev()
+            .collect();
+
+        for (bind_param, nullable) in bind_params.iter().zip(nullable).rev() {
+            let index = dedup_params.iter().position(|bp| bp == bind_param).unwrap();
+            let start = bind_param.span.offset() - 1;
+            let end = start + bind_param.span.len() + usize::from(nullable);
+            sql_str.replace_range(start..=end, &format!("${}", index + 1));
+        }
+        (sql_str, dedup_params, nullable_params)
+    }
+
+    /// Replace the SQL of the query, e.g. once its fragments are included
+    pub(crate) fn set_sql(&mut self, sql: String) {
+        let (sql_str, bind_params, nullable_bind_params) = Self::normalize(sql);
+        self.sql_str = sql_str;
+        self.bind_params = bind_params;
+        self.nullable_bind_params = nullable_bind_params;
     }
 
     fn parse_query_annotation(
@@ -262,15 +588,30 @@ impl Query {
         Self::parse_query_annotation()
             .then_ignore(space())
             .then_ignore(ln())
+            .then(
+                Attribute::line("--#")
+                    .then_ignore(space())
+                    .then_ignore(ln())
+                    .repeated()
+                    .flatten(),
+            )
             .then(Self::parse_sql_query())
             .map(
-                |((name, param, row), (sql_str, sql_span, bind_params))| Self {
+                |(
+                    ((name, param, row), attributes),
+                    (sql_str, sql_span, bind_params, nullable_bind_params, is_script),
+                )| Self {
                     name,
                     param,
                     row,
                     sql_span,
                     sql_str,
                     bind_params,
+                    nullable_bind_params,
+                    attributes,
+                    is_script,
+                    slots: Vec::new(),
+                    sql_variants: Vec::new(),
                 },
             )
     }
@@ -357,7 +698,9 @@ impl QueryDataStruct {
 #[derive(Debug)]
 enum Statement {
     Type(TypeAnnotation),
-    Query(Query),
+    Fragment(Fragment),
+    Query(Box<Query>),
+    Attributes(Vec<Attribute>),
 }
 
 #[derive(Debug)]
@@ -365,12 +708,17 @@ pub(crate) struct Module {
     pub(crate) info: ModuleInfo,
     pub(crate) types: Vec<TypeAnnotation>,
     pub(crate) queries: Vec<Query>,
+    pub(crate) fragments: Vec<Fragment>,
+    /// Module-wide attributes, declared with `--#!`
+    pub(crate) attributes: Vec<Attribute>,
 }
 
 pub(crate) fn parse_query_module(info: ModuleInfo) -> Result<Module, Error> {
-    match TypeAnnotation::parser()
-        .map(Statement::Type)
-        .or(Query::parser().map(Statement::Query))
+    match Fragment::parser()
+        .map(Statement::Fragment)
+        .or(TypeAnnotation::parser().map(Statement::Type))
+        .or(Query::parser().map(|it| Statement::Query(Box::new(it))))
+        .or(Attribute::line("--#!").map(Statement::Attributes))
         .separated_by(blank())
         .allow_leading()
         .allow_trailing()
@@ -380,16 +728,22 @@ pub(crate) fn parse_query_module(info: ModuleInfo) -> Result<Module, Error> {
         Ok(statements) => {
             let mut types = Vec::new();
             let mut queries = Vec::new();
+            let mut fragments = Vec::new();
+            let mut attributes = Vec::new();
             for item in statements {
                 match item {
                     Statement::Type(it) => types.push(it),
-                    Statement::Query(it) => queries.push(it),
+                    Statement::Query(it) => queries.push(*it),
+                    Statement::Fragment(it) => fragments.push(it),
+                    Statement::Attributes(it) => attributes.extend(it),
                 }
             }
             Ok(Module {
                 info,
                 types,
                 queries,
+                fragments,
+                attributes,
             })
         }
         Err(e) => Err(Error {
@@ -0,0 +1,72 @@
+use std::fmt::Debug;
+
+/// Extends the generated code without forking the generator, e.g. with custom derives,
+/// conversions or ORM glue. Register plugins in [`CodegenSettings::plugins`](crate::CodegenSettings).
+///
+/// The returned code is appended right after the generated item, in the same module.
+pub trait Plugin: Debug {
+    /// Extra code for a custom type of the database
+    fn custom_type(&self, _ty: &TypeInfo<'_>) -> Option<String> {
+        None
+    }
+
+    /// Extra code for a params or row struct of a query module
+    fn item(&self, _item: &ItemInfo<'_>) -> Option<String> {
+        None
+    }
+}
+
+/// Custom type of the database, as seen by a [`Plugin`]
+#[derive(Debug, Clone)]
+pub struct TypeInfo<'a> {
+    /// Schema of the type
+    pub schema: &'a str,
+    /// Name of the type in the database
+    pub name: &'a str,
+    /// Name of the generated Rust type
+    pub struct_name: &'a str,
+    pub content: TypeContent<'a>,
+}
+
+/// Content of a custom type
+#[derive(Debug, Clone)]
+pub enum TypeContent<'a> {
+    /// Enum, with the Rust names of its variants
+    Enum(Vec<&'a str>),
+    /// Composite, with its fields
+    Composite(Vec<FieldInfo<'a>>),
+    /// Newtype around a Rust type
+    Newtype(String),
+}
+
+/// Kind of a generated query struct
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ItemKind {
+    Params,
+    Row,
+}
+
+/// Params or row struct of a query module, as seen by a [`Plugin`]
+#[derive(Debug, Clone)]
+pub struct ItemInfo<'a> {
+    /// Name of the query module
+    pub module: &'a str,
+    /// Name of the generated struct
+    pub name: &'a str,
+    pub kind: ItemKind,
+    /// Generic parameters of the struct, e.g. `'a, T1: cornucopia_async::StringSql`
+    pub generics: String,
+    pub fields: Vec<FieldInfo<'a>>,
+}
+
+/// Field of a composite type or of a query struct
+#[derive(Debug, Clone)]
+pub struct FieldInfo<'a> {
+    /// Name of the column or attribute in the database
+    pub db_name: &'a str,
+    /// Name of the Rust field
+    pub name: &'a str,
+    /// Rust type of the field
+    pub ty: String,
+    pub is_nullable: bool,
+}
@@ -1,35 +1,234 @@
-use std::rc::Rc;
+use std::{collections::BTreeMap, rc::Rc, time::Duration};
 
 use heck::ToUpperCamelCase;
 use indexmap::{map::Entry, IndexMap};
-use postgres::Client;
-use postgres_types::{Kind, Type};
+use miette::SourceSpan;
+use postgres::{Client, Statement};
+use postgres_types::{FromSql, Kind, Type};
+use serde::{Deserialize, Serialize};
 
 use crate::{
+    cache::ModuleCache,
     codegen::GenCtx,
-    parser::{Module, NullableIdent, Query, Span, TypeAnnotation},
-    read_queries::ModuleInfo,
+    config::{Codec, InlineType, TablesConfig},
+    log,
+    parser::{Attribute, AttributeValue, Module, NullableIdent, Query, Span, TypeAnnotation},
+    read_queries::{ModuleInfo, Table},
+    sql_analysis::{self, CopyIn, CopyTarget},
     type_registrar::CornucopiaType,
     type_registrar::TypeRegistrar,
-    utils::KEYWORD,
-    validation,
+    utils::{parse_duration, KEYWORD},
+    validation, CodegenSettings,
 };
 
 use self::error::Error;
 
 /// This data structure is used by Cornucopia to generate
 /// all constructs related to this particular query.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub(crate) struct PreparedQuery {
     pub(crate) ident: Ident,
     pub(crate) param: Option<(usize, Vec<usize>)>,
     pub(crate) row: Option<(usize, Vec<usize>)>,
     pub(crate) sql: String,
+    /// Position of the array parameter matched with `= ANY(...)`, which can be split into chunks
+    pub(crate) any_param: Option<usize>,
+    pub(crate) trace: Trace,
+    /// Operation of the statement (e.g. `SELECT`)
+    pub(crate) operation: Option<String>,
+    /// Table mainly worked on by the statement
+    pub(crate) table: Option<String>,
+    pub(crate) audit: Option<Audit>,
+    pub(crate) copy: Option<CopyStatement>,
+    pub(crate) paginate: Option<Paginate>,
+    pub(crate) renamed: Option<Renamed>,
+    pub(crate) cardinality: Option<Cardinality>,
+    /// Note of the `deprecated` attribute, empty when the query is deprecated without one
+    pub(crate) deprecated: Option<String>,
+    /// Latency budget of the query, set by its `budget` attribute
+    pub(crate) budget: Option<Duration>,
+    /// Checks of the parameters, set by its `validate` attribute
+    pub(crate) validate: Vec<ParamCheck>,
+    /// Made of several statements, run together without parameters
+    pub(crate) is_script: bool,
+    /// Slots substituted by one of their variants when creating the statement
+    pub(crate) slots: Vec<PreparedSlot>,
+    /// SQL of each combination of the variants of the slots, the first being `sql`
+    pub(crate) sql_variants: Vec<String>,
+}
+
+/// Slot of a query set by its `variants` attribute
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct PreparedSlot {
+    /// Position of the slot in the attribute
+    #[serde(with = "crate::serialize::source_span")]
+    pub(crate) span: SourceSpan,
+    /// Generated enum choosing the variant
+    pub(crate) enum_name: String,
+    /// Argument of the enum
+    pub(crate) arg: String,
+    /// Variants of the enum, the first one being the default
+    pub(crate) variants: Vec<String>,
+}
+
+/// Keyset pagination of a query annotated with `paginate`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct Paginate {
+    /// Query keeping the rows after a cursor, up to a limit
+    pub(crate) sql: String,
+    /// Row fields the pages are ordered by
+    pub(crate) by: Vec<usize>,
+}
+
+/// `COPY` statement of a query annotated with `copy`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) enum CopyStatement {
+    /// Binary `COPY ... FROM STDIN`, whose query `sql` is the equivalent `INSERT`
+    /// only prepared to get the type of the columns
+    In(String),
+    /// `COPY ... TO STDOUT`, whose query `sql` is the copied query
+    Out { sql: String, csv: bool },
+}
+
+/// Audit records emitted by a query, set by its `audit` attribute
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct Audit {
+    /// Parameter identifying who runs the query
+    pub(crate) actor: Option<String>,
+}
+
+/// Check of a parameter run before binding its query, e.g. `len(email) <= 255`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct ParamCheck {
+    /// Check as written in the `validate` attribute
+    pub(crate) text: String,
+    pub(crate) param: String,
+    /// Compares the length of the parameter instead of its value
+    pub(crate) len: bool,
+    pub(crate) op: String,
+    pub(crate) value: String,
+}
+
+impl ParamCheck {
+    /// Comparison operators, the longest first
+    const OPS: [&'static str; 6] = ["<=", ">=", "==", "!=", "<", ">"];
+
+    /// Checks of the comma separated `checks`, if they are all valid
+    pub(crate) fn parse_all(checks: &str) -> Option<Vec<Self>> {
+        checks.split(',').map(|it| Self::parse(it.trim())).collect()
+    }
+
+    /// Parses `param <op> number` or `len(param) <op> integer`
+    fn parse(text: &str) -> Option<Self> {
+        let op = Self::OPS.into_iter().find(|op| text.contains(op))?;
+        let (lhs, value) = text.split_once(op)?;
+        let (lhs, value) = (lhs.trim(), value.trim());
+        let (param, len) = match lhs.strip_prefix("len(").and_then(|it| it.strip_suffix(')')) {
+            Some(param) => (param.trim(), true),
+            None => (lhs, false),
+        };
+        let is_ident = param
+            .chars()
+            .next()
+            .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+            && param.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+        let digits = if len {
+            value
+        } else {
+            value.strip_prefix('-').unwrap_or(value)
+        };
+        let (int, fract) = digits.split_once('.').unwrap_or((digits, "0"));
+        let is_number = [int, fract]
+            .iter()
+            .all(|it| !it.is_empty() && it.chars().all(|c| c.is_ascii_digit()))
+            && (!len || !digits.contains('.'));
+        (is_ident && is_number).then(|| Self {
+            text: text.to_string(),
+            param: param.to_string(),
+            len,
+            op: op.to_string(),
+            value: value.to_string(),
+        })
+    }
+}
+
+/// Previous names of a query and of its row, set by its `renamed` attribute
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct Renamed {
+    pub(crate) query: Option<Span<String>>,
+    pub(crate) row: Option<Span<String>>,
+}
+
+/// Number of rows a query returns, set by its `cardinality` attribute. Binding the query
+/// then directly fetches them instead of returning a query to fetch them with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) enum Cardinality {
+    One,
+    Opt,
+    All,
+}
+
+impl Cardinality {
+    fn new(attribute: &Attribute) -> Option<Self> {
+        match &attribute.value {
+            AttributeValue::Value(value) => match value.value.as_str() {
+                "one" => Some(Self::One),
+                "opt" => Some(Self::Opt),
+                "all" => Some(Self::All),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// Method of the query struct fetching the rows
+    pub(crate) fn method(self) -> &'static str {
+        match self {
+            Self::One => "one",
+            Self::Opt => "opt",
+            Self::All => "all",
+        }
+    }
+
+    /// Type of the fetched rows, given the type of a row
+    pub(crate) fn ty(self, row: &str) -> String {
+        match self {
+            Self::One => row.to_string(),
+            Self::Opt => format!("Option<{row}>"),
+            Self::All => format!("Vec<{row}>"),
+        }
+    }
+}
+
+/// Tracing span settings of a query, set by its own `trace` attribute or its module's
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct Trace {
+    pub(crate) level: String,
+    /// Defaults to the path of the generated module
+    pub(crate) target: Option<String>,
+}
+
+impl Trace {
+    /// The first `trace` attribute defining a setting wins, so the query's
+    /// attributes must come before the ones inherited from its module.
+    fn new(attributes: &[Attribute]) -> Self {
+        let setting = |name| {
+            attributes
+                .iter()
+                .filter(|it| it.name.value == "trace")
+                .find_map(|it| it.nested_value(name))
+                .map(str::to_string)
+        };
+        Self {
+            level: setting("level").unwrap_or_else(|| "debug".to_string()),
+            target: setting("target"),
+        }
+    }
 }
 
 /// A normalized ident replacing all non-alphanumeric characters with an underscore (`_`)
 /// and escaping it with a raw identifier prefix (`r#`) if it clashes with a keyword reserved in Rust.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Ident {
     /// Database original ident
     pub(crate) db: String,
@@ -63,7 +262,7 @@ impl Ident {
 }
 
 /// A row or params field
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct PreparedField {
     pub(crate) ident: Ident,
     pub(crate) ty: Rc<CornucopiaType>,
@@ -88,19 +287,33 @@ impl PreparedField {
 
 impl PreparedField {
     pub fn unwrapped_name(&self) -> String {
+        if self.is_void() {
+            return "Void".to_string();
+        }
         self.own_struct(&GenCtx::new(0, false, false))
             .replace(['<', '>', '_'], "")
             .to_upper_camel_case()
     }
+
+    /// Is this field a `void` column, which carries no value
+    pub fn is_void(&self) -> bool {
+        *self.ty.pg_ty() == Type::VOID
+    }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub(crate) struct PreparedItem {
     pub(crate) name: Span<String>,
     pub(crate) fields: Vec<PreparedField>,
     pub(crate) is_copy: bool,
     pub(crate) is_named: bool,
     pub(crate) is_ref: bool,
+    /// Module declaring this struct, when imported from it with `--: Name from module`
+    pub(crate) imported: Option<Span<String>>,
+    /// Note of the `deprecated` attribute of the queries using this struct, when all of them are
+    pub(crate) deprecated: Option<String>,
+    /// Extra traits derived by the row struct, from the `derive` attribute of its queries
+    pub(crate) derives: Vec<String>,
 }
 
 impl PreparedItem {
@@ -110,6 +323,9 @@ impl PreparedItem {
             is_copy: fields.iter().all(|f| f.ty.is_copy()),
             is_ref: fields.iter().any(|f| f.ty.is_ref()),
             is_named: !is_implicit || fields.len() > 1,
+            imported: None,
+            deprecated: None,
+            derives: Vec::new(),
             fields,
         }
     }
@@ -119,33 +335,106 @@ impl PreparedItem {
     }
 }
 
-#[derive(PartialEq, Eq, Debug, Clone)]
+#[derive(PartialEq, Eq, Debug, Clone, Serialize, Deserialize)]
 pub(crate) struct PreparedType {
     pub(crate) name: String,
     pub(crate) struct_name: String,
+    /// Postgres type it is decoded from
+    #[serde(with = "crate::serialize::pg_type")]
+    pub(crate) pg_ty: Type,
     pub(crate) content: PreparedContent,
     pub(crate) is_copy: bool,
     pub(crate) is_params: bool,
 }
 
-#[derive(PartialEq, Eq, Debug, Clone)]
+#[derive(PartialEq, Eq, Debug, Clone, Serialize, Deserialize)]
 pub(crate) enum PreparedContent {
     Enum(Vec<Ident>),
     Composite(Vec<PreparedField>),
+    Newtype(Rc<CornucopiaType>),
+}
+
+/// A column configured to be wrapped in a newtype
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct NewtypeColumn {
+    schema: String,
+    table: String,
+    column: String,
+    table_oid: u32,
+    column_id: i16,
+    type_oid: u32,
+    struct_name: String,
+}
+
+/// A column configured to be held inline in owned rows
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct InlineColumn {
+    table_oid: u32,
+    column_id: i16,
+    capacity: usize,
+}
+
+/// A column configured to be decoded and encoded by a codec, whatever the mapping of its type
+#[derive(Debug, Clone)]
+struct CodecColumn {
+    schema: String,
+    table: String,
+    column: String,
+    table_oid: u32,
+    column_id: i16,
+    type_oid: u32,
+    codec: Codec,
+}
+
+impl CodecColumn {
+    /// Type of the column, decoded and encoded by the codec
+    fn mapped(&self, inner: Rc<CornucopiaType>) -> Rc<CornucopiaType> {
+        Rc::new(CornucopiaType::Mapped {
+            rust_path: self.codec.owned.clone(),
+            borrowed: self.codec.borrowed.clone(),
+            inner,
+        })
+    }
+}
+
+/// Columns whose generated types are configured
+#[derive(Debug, Clone, Default)]
+struct ConfiguredColumns {
+    newtypes: Vec<NewtypeColumn>,
+    inline: Vec<InlineColumn>,
+    codecs: Vec<CodecColumn>,
 }
 
 /// A struct containing the module name and the list of all
 /// the queries it contains.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub(crate) struct PreparedModule {
     pub(crate) info: ModuleInfo,
+    #[serde(with = "indexmap::map::serde_seq")]
     pub(crate) queries: IndexMap<Span<String>, PreparedQuery>,
+    #[serde(with = "indexmap::map::serde_seq")]
     pub(crate) params: IndexMap<Span<String>, PreparedItem>,
+    #[serde(with = "indexmap::map::serde_seq")]
     pub(crate) rows: IndexMap<Span<String>, PreparedItem>,
+    /// Session settings of the module's `session` attribute, as names and values
+    pub(crate) session: Vec<(String, String)>,
+    /// Constraints of the module's `constraints` attribute
+    pub(crate) constraints: Vec<ModuleConstraint>,
 }
 
-#[derive(Debug, Clone)]
-pub(crate) struct Preparation {
+/// Constraint whose violations are decoded as a variant of the module's `ConstraintError`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct ModuleConstraint {
+    pub(crate) name: String,
+    pub(crate) variant: String,
+    /// `SqlState` of its violations, e.g. `UNIQUE_VIOLATION`
+    pub(crate) code: String,
+}
+
+/// The queries and types of a set of query modules, as described by the database they were
+/// prepared against. It can be serialized, and emitted with [`emit`](crate::emit) later on.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Preparation {
     pub(crate) modules: Vec<PreparedModule>,
     pub(crate) types: IndexMap<String, Vec<PreparedType>>,
 }
@@ -212,22 +501,99 @@ impl PreparedModule {
         param_idx: Option<(usize, Vec<usize>)>,
         row_idx: Option<(usize, Vec<usize>)>,
         sql: String,
-    ) {
-        self.queries.insert(
+        attributes: &[Attribute],
+        copy: Option<CopyStatement>,
+    ) -> &mut PreparedQuery {
+        let (operation, table) = sql_analysis::operation(&sql);
+        let audit = Attribute::find(attributes, "audit").map(|it| Audit {
+            actor: it
+                .nested_value("actor")
+                .map(|actor| Ident::new(actor.to_string()).rs),
+        });
+        let renamed = Attribute::find(attributes, "renamed").map(|it| Renamed {
+            query: it.nested_span("query").cloned(),
+            row: it.nested_span("row").cloned(),
+        });
+        let (idx, _) = self.queries.insert_full(
             name.clone(),
             PreparedQuery {
                 ident: Ident::new(name.value),
                 row: row_idx,
+                any_param: sql_analysis::any_param(&sql),
                 sql,
                 param: param_idx,
+                trace: Trace::new(attributes),
+                operation,
+                table,
+                audit,
+                copy,
+                paginate: None,
+                renamed,
+                cardinality: Attribute::find(attributes, "cardinality").and_then(Cardinality::new),
+                deprecated: Attribute::find(attributes, "deprecated").map(|it| match &it.value {
+                    AttributeValue::Value(note) => note.value.clone(),
+                    _ => String::new(),
+                }),
+                budget: Attribute::find(attributes, "budget").and_then(|it| match &it.value {
+                    AttributeValue::Value(value) => parse_duration(&value.value),
+                    _ => None,
+                }),
+                validate: Attribute::find(attributes, "validate")
+                    .and_then(|it| match &it.value {
+                        AttributeValue::Value(checks) => ParamCheck::parse_all(&checks.value),
+                        _ => None,
+                    })
+                    .unwrap_or_default(),
+                is_script: false,
+                slots: Vec::new(),
+                sql_variants: Vec::new(),
             },
         );
+        &mut self.queries[idx]
     }
 }
 
 /// Prepares all modules
-pub(crate) fn prepare(client: &mut Client, modules: Vec<Module>) -> Result<Preparation, Error> {
-    let mut registrar = TypeRegistrar::default();
+pub(crate) fn prepare(
+    client: &mut Client,
+    modules: Vec<Module>,
+    settings: &CodegenSettings,
+) -> Result<Preparation, Error> {
+    prepare_modules(client, modules, settings, None)
+}
+
+/// Prepares the modules which can be, returning the names of the others along with their
+/// error instead of failing. The structs imported from other modules aren't checked, as their
+/// module could have failed.
+pub(crate) fn prepare_available(
+    client: &mut Client,
+    modules: Vec<Module>,
+    settings: &CodegenSettings,
+) -> Result<(Preparation, Vec<(String, Error)>), Error> {
+    let mut failed = Vec::new();
+    let preparation = prepare_modules(client, modules, settings, Some(&mut failed))?;
+    Ok((preparation, failed))
+}
+
+fn prepare_modules(
+    client: &mut Client,
+    modules: Vec<Module>,
+    settings: &CodegenSettings,
+    mut failed: Option<&mut Vec<(String, Error)>>,
+) -> Result<Preparation, Error> {
+    let mut registrar = TypeRegistrar {
+        extension_types: settings.extension_types.clone(),
+        codecs: settings.codecs.clone(),
+        money: settings.money,
+        interval: settings.interval,
+        bit: settings.bit,
+        ..Default::default()
+    };
+    let configured = ConfiguredColumns {
+        newtypes: resolve_newtypes(client, &settings.newtypes)?,
+        inline: resolve_inline_columns(client, &settings.inline_columns)?,
+        codecs: resolve_codec_columns(client, &settings.codecs)?,
+    };
     let mut tmp = Preparation {
         modules: Vec::new(),
         types: IndexMap::new(),
@@ -238,25 +604,471 @@ pub(crate) fn prepare(client: &mut Client, modules: Vec<Module>) -> Result<Prepa
         .map(|ty| (*ty).clone())
         .collect();
 
+    // Checking the nullability reads the data, which the cache doesn't follow
+    let mut cache = settings
+        .cache
+        .as_deref()
+        .filter(|_| !settings.verify_nullability)
+        .map(ModuleCache::load);
     for module in modules {
-        tmp.modules
-            .push(prepare_module(client, module, &mut registrar)?);
+        let Some(cache) = &mut cache else {
+            let name = module.info.name.clone();
+            match prepare_module(client, module, &mut registrar, &configured, settings) {
+                Ok(prepared) => tmp.modules.push(prepared),
+                Err(err) => match &mut failed {
+                    Some(failed) => failed.push((name, err)),
+                    None => return Err(err),
+                },
+            }
+            continue;
+        };
+        let key = ModuleCache::key(&module, settings);
+        if let Some(prepared) = cache.reuse(client, &module.info.name, &key, &mut registrar)? {
+            tmp.modules.push(prepared);
+            continue;
+        }
+        registrar.used.clear();
+        registrar.used_newtypes.clear();
+        let prepared = prepare_module(client, module, &mut registrar, &configured, settings)?;
+        cache.store(client, key, &prepared, &registrar)?;
+        tmp.modules.push(prepared);
+    }
+    if failed.is_none() {
+        check_imports(&tmp.modules)?;
+    }
+    if let Some(cache) = cache {
+        cache.save();
     }
 
     // Prepare types grouped by schema
-    for ((schema, name), ty) in &registrar.types {
-        if let Some(ty) = prepare_type(&registrar, name, ty, &declared) {
-            match tmp.types.entry(schema.clone()) {
-                Entry::Occupied(mut entry) => {
-                    entry.get_mut().push(ty);
+    let custom_types = registrar.types.iter().filter_map(|((schema, name), ty)| {
+        prepare_type(&registrar, name, ty, &declared).map(|ty| (schema, ty))
+    });
+    let newtypes = registrar
+        .newtypes
+        .values()
+        .filter_map(|ty| prepare_newtype(ty));
+    for (schema, ty) in custom_types.chain(newtypes) {
+        match tmp.types.entry(schema.clone()) {
+            Entry::Occupied(mut entry) => {
+                entry.get_mut().push(ty);
+            }
+            Entry::Vacant(entry) => {
+                entry.insert(vec![ty]);
+            }
+        }
+    }
+    Ok(tmp)
+}
+
+/// Checks the structs imported from other modules are the same in both modules, as their
+/// code is only generated in the module declaring them
+fn check_imports(modules: &[PreparedModule]) -> Result<(), Error> {
+    for module in modules {
+        let items = [(&module.params, "params"), (&module.rows, "row")];
+        for (items, kind) in items {
+            for item in items.values() {
+                let Some(from) = &item.imported else {
+                    continue;
+                };
+                let source = modules
+                    .iter()
+                    .find(|it| it.info.name == from.value)
+                    .and_then(|source| {
+                        if kind == "row" {
+                            source.rows.get(&item.name)
+                        } else {
+                            source.params.get(&item.name)
+                        }
+                    })
+                    .filter(|it| it.is_named);
+                let Some(source) = source else {
+                    return Err(Error::ImportedKind {
+                        src: (&module.info).into(),
+                        name: item.name.value.clone(),
+                        module: from.value.clone(),
+                        kind,
+                        pos: item.name.span,
+                    });
+                };
+                if source.fields != item.fields {
+                    let fields: Vec<_> = source
+                        .fields
+                        .iter()
+                        .map(|it| {
+                            let null = if it.is_nullable { "?" } else { "" };
+                            format!("{}{null}: {}", it.ident.db, it.ty.pg_ty())
+                        })
+                        .collect();
+                    return Err(Error::ImportedFields {
+                        src: (&module.info).into(),
+                        module: from.value.clone(),
+                        pos: item.name.span,
+                        help: format!(
+                            "`{}` has the fields `{}` in `{}`",
+                            item.name.value,
+                            fields.join("`, `"),
+                            from.value
+                        ),
+                    });
                 }
-                Entry::Vacant(entry) => {
-                    entry.insert(vec![ty]);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Finds the configured newtype columns in the database catalog
+fn resolve_newtypes(
+    client: &mut Client,
+    newtypes: &BTreeMap<String, String>,
+) -> Result<Vec<NewtypeColumn>, Error> {
+    let mut resolved = Vec::new();
+    for (path, struct_name) in newtypes {
+        let unknown = || Error::UnknownNewtypeColumn {
+            column: path.clone(),
+        };
+        let [schema, table, column] = path.split('.').collect::<Vec<_>>()[..] else {
+            return Err(unknown());
+        };
+        let row = client
+            .query_opt(
+                "SELECT c.oid, a.attnum, a.atttypid FROM pg_attribute a
+                JOIN pg_class c ON c.oid = a.attrelid
+                JOIN pg_namespace n ON n.oid = c.relnamespace
+                WHERE n.nspname = $1 AND c.relname = $2 AND a.attname = $3 AND NOT a.attisdropped",
+                &[&schema, &table, &column],
+            )?
+            .ok_or_else(unknown)?;
+        resolved.push(NewtypeColumn {
+            schema: schema.to_string(),
+            table: table.to_string(),
+            column: column.to_string(),
+            table_oid: row.get(0),
+            column_id: row.get(1),
+            type_oid: row.get(2),
+            struct_name: struct_name.clone(),
+        });
+    }
+    Ok(resolved)
+}
+
+/// Finds the columns of the configured codecs in the database catalog, those keyed by a
+/// `schema.table.column` path rather than by the `schema.name` path of a type
+fn resolve_codec_columns(
+    client: &mut Client,
+    codecs: &BTreeMap<String, Codec>,
+) -> Result<Vec<CodecColumn>, Error> {
+    let mut resolved = Vec::new();
+    for (path, codec) in codecs {
+        let [schema, table, column] = path.split('.').collect::<Vec<_>>()[..] else {
+            continue;
+        };
+        let row = client
+            .query_opt(
+                "SELECT c.oid, a.attnum, a.atttypid FROM pg_attribute a
+                JOIN pg_class c ON c.oid = a.attrelid
+                JOIN pg_namespace n ON n.oid = c.relnamespace
+                WHERE n.nspname = $1 AND c.relname = $2 AND a.attname = $3 AND NOT a.attisdropped",
+                &[&schema, &table, &column],
+            )?
+            .ok_or_else(|| Error::UnknownCodecColumn {
+                column: path.clone(),
+            })?;
+        resolved.push(CodecColumn {
+            schema: schema.to_string(),
+            table: table.to_string(),
+            column: column.to_string(),
+            table_oid: row.get(0),
+            column_id: row.get(1),
+            type_oid: row.get(2),
+            codec: codec.clone(),
+        });
+    }
+    Ok(resolved)
+}
+
+/// Finds the configured tables in the database catalog, along with their nullable columns
+pub(crate) fn resolve_tables(
+    client: &mut Client,
+    tables: &TablesConfig,
+) -> Result<Vec<Table>, Error> {
+    if tables.is_empty() {
+        return Ok(Vec::new());
+    }
+    let rows = client.query(
+        "SELECT n.nspname, c.relname,
+            array_agg(a.attname ORDER BY a.attnum) FILTER (WHERE NOT a.attnotnull)
+        FROM pg_class c
+        JOIN pg_namespace n ON n.oid = c.relnamespace
+        JOIN pg_attribute a ON a.attrelid = c.oid AND a.attnum > 0 AND NOT a.attisdropped
+        WHERE c.relkind IN ('r', 'p') AND NOT c.relispartition
+        AND (n.nspname = ANY($1) OR n.nspname || '.' || c.relname = ANY($2))
+        AND NOT n.nspname || '.' || c.relname = ANY($3)
+        GROUP BY n.nspname, c.relname
+        ORDER BY n.nspname, c.relname",
+        &[&tables.schemas, &tables.include, &tables.exclude],
+    )?;
+    let resolved: Vec<_> = rows
+        .iter()
+        .map(|row| Table {
+            schema: row.get(0),
+            name: row.get(1),
+            nullable: row.get::<_, Option<Vec<String>>>(2).unwrap_or_default(),
+        })
+        .collect();
+    if let Some(table) = tables.include.iter().find(|path| {
+        resolved
+            .iter()
+            .all(|it| format!("{}.{}", it.schema, it.name) != **path)
+    }) {
+        return Err(Error::UnknownTable {
+            table: table.clone(),
+        });
+    }
+    Ok(resolved)
+}
+
+/// Finds the configured inline columns in the database catalog, checking their values fit
+fn resolve_inline_columns(
+    client: &mut Client,
+    inline_columns: &BTreeMap<String, String>,
+) -> Result<Vec<InlineColumn>, Error> {
+    let mut resolved = Vec::new();
+    for (path, ty) in inline_columns {
+        let unknown = || Error::UnknownInlineColumn {
+            column: path.clone(),
+        };
+        let invalid = |msg: String, help: &str| Error::InvalidInlineColumn {
+            column: path.clone(),
+            msg,
+            help: help.to_string(),
+        };
+        let [schema, table, column] = path.split('.').collect::<Vec<_>>()[..] else {
+            return Err(unknown());
+        };
+        let inline = InlineType::parse(ty).ok_or_else(unknown)?;
+        let row = client
+            .query_opt(
+                "SELECT c.oid, a.attnum, a.atttypid, a.atttypmod, format_type(a.atttypid, a.atttypmod)
+                FROM pg_attribute a
+                JOIN pg_class c ON c.oid = a.attrelid
+                JOIN pg_namespace n ON n.oid = c.relnamespace
+                WHERE n.nspname = $1 AND c.relname = $2 AND a.attname = $3 AND NOT a.attisdropped",
+                &[&schema, &table, &column],
+            )?
+            .ok_or_else(unknown)?;
+        let (type_oid, typmod, type_name): (u32, i32, String) =
+            (row.get(2), row.get(3), row.get(4));
+        match inline {
+            InlineType::String(capacity) => {
+                // The length of a `varchar(n)` is stored offset by the size of its header
+                let len = usize::try_from(typmod - 4).ok();
+                if type_oid != Type::VARCHAR.oid() || len.is_none_or(|len| len > capacity) {
+                    return Err(invalid(
+                        format!("a `{type_name}` doesn't fit in `{ty}`"),
+                        "inline `varchar(n)` columns whose length is at most the capacity",
+                    ));
+                }
+            }
+            InlineType::Bytes(_) => {
+                if type_oid != Type::BYTEA.oid() {
+                    return Err(invalid(
+                        format!("a `{type_name}` isn't held in `{ty}`"),
+                        "inline `bytea` columns as fixed size byte arrays",
+                    ));
                 }
             }
         }
+        resolved.push(InlineColumn {
+            table_oid: row.get(0),
+            column_id: row.get(1),
+            capacity: inline.capacity(),
+        });
+    }
+    Ok(resolved)
+}
+
+/// Finds the composite type of the columns compared to the parameters of `stmt` typed as an
+/// anonymous `record` or `record[]`, as casts for [`sql_analysis::cast_params`]. Columns holding
+/// arrays of a composite type are compared to it too, e.g. `$1 = ANY(col)`.
+fn record_param_casts(
+    client: &mut Client,
+    sql: &str,
+    stmt: &Statement,
+) -> Result<Vec<(usize, String)>, Error> {
+    let params = stmt.params();
+    let mut casts = Vec::new();
+    if !params
+        .iter()
+        .any(|it| *it == Type::RECORD || *it == Type::RECORD_ARRAY)
+    {
+        return Ok(casts);
+    }
+    let columns = sql_analysis::param_columns(sql, params.len());
+    for ((idx, ty), column) in params.iter().enumerate().zip(columns) {
+        let suffix = match *ty {
+            Type::RECORD => "",
+            Type::RECORD_ARRAY => "[]",
+            _ => continue,
+        };
+        let Some(column) = column else {
+            continue;
+        };
+        for table in &column.tables {
+            let relation = match &table.schema {
+                Some(schema) => format!(
+                    "{}.{}",
+                    sql_analysis::quote_ident(schema),
+                    sql_analysis::quote_ident(&table.name)
+                ),
+                None => sql_analysis::quote_ident(&table.name),
+            };
+            let row = client.query_opt(
+                "SELECT n.nspname, t.typname FROM pg_attribute a
+                JOIN pg_type c ON c.oid = a.atttypid
+                JOIN pg_type t ON t.oid = c.oid OR t.oid = c.typelem
+                JOIN pg_namespace n ON n.oid = t.typnamespace
+                WHERE a.attrelid = to_regclass($1) AND a.attname = $2 AND t.typtype = 'c'
+                AND NOT a.attisdropped",
+                &[&relation, &column.column],
+            )?;
+            if let Some(row) = row {
+                let (schema, name): (&str, &str) = (row.get(0), row.get(1));
+                casts.push((
+                    idx + 1,
+                    format!(
+                        "{}.{}{suffix}",
+                        sql_analysis::quote_ident(schema),
+                        sql_analysis::quote_ident(name)
+                    ),
+                ));
+                break;
+            }
+        }
+    }
+    Ok(casts)
+}
+
+/// Names the positional `$n` parameters of `sql` after the column they are compared to
+/// or inserted in, falling back to `param_n`
+fn positional_params(sql: &str, nb_params: usize, span: SourceSpan) -> Vec<Span<String>> {
+    let mut names: Vec<String> = Vec::new();
+    for (idx, column) in sql_analysis::param_columns(sql, nb_params)
+        .into_iter()
+        .enumerate()
+    {
+        let name = column
+            .map(|it| it.column)
+            .filter(|it| !names.contains(it))
+            .unwrap_or_else(|| format!("param_{}", idx + 1));
+        names.push(name);
+    }
+    names
+        .into_iter()
+        .map(|value| Span { span, value })
+        .collect()
+}
+
+/// Names the positional parameters passed as arguments of the procedure called by a `CALL`
+/// statement after those arguments, as declared by the procedure when it isn't overloaded
+fn name_call_params(
+    client: &mut Client,
+    call: &sql_analysis::Call,
+    params: &mut [Span<String>],
+) -> Result<(), Error> {
+    // Output arguments are passed too, and the last input ones can be defaulted
+    let procs = client.query(
+        "SELECT p.proargnames FROM pg_proc p
+        WHERE p.prokind = 'p' AND p.proname = $1
+        AND CASE WHEN $2::text IS NULL THEN pg_function_is_visible(p.oid)
+            ELSE p.pronamespace = to_regnamespace($2) END
+        AND $3 BETWEEN cardinality(coalesce(p.proallargtypes, p.proargtypes::oid[])) - p.pronargdefaults
+            AND cardinality(coalesce(p.proallargtypes, p.proargtypes::oid[]))",
+        &[&call.name, &call.schema, &(call.args.len() as i32)],
+    )?;
+    let declared: Vec<String> = match &procs[..] {
+        [proc] => proc.get::<_, Option<_>>(0).unwrap_or_default(),
+        _ => Vec::new(),
+    };
+    let mut named = Vec::new();
+    for (position, arg) in call.args.iter().enumerate() {
+        let Some(idx) = arg
+            .param
+            .filter(|idx| *idx < params.len() && !named.contains(idx))
+        else {
+            continue;
+        };
+        let name = arg
+            .name
+            .clone()
+            .or_else(|| declared.get(position).cloned())
+            .filter(|name| !name.is_empty() && params.iter().all(|it| it.value != *name));
+        if let Some(name) = name {
+            params[idx].value = name;
+            named.push(idx);
+        }
+    }
+    Ok(())
+}
+
+/// Row type of a function
+struct FunctionRow {
+    /// Name of its composite type, or of the function when its rows are its output arguments
+    name: String,
+    /// Columns declared `NOT NULL` by the table the composite type is the row type of
+    not_null: Vec<String>,
+}
+
+/// Finds the row type of the function a query selects all the columns of, when it returns
+/// composite values and isn't overloaded
+fn function_row(
+    client: &mut Client,
+    call: &sql_analysis::Call,
+) -> Result<Option<FunctionRow>, Error> {
+    let functions = client.query(
+        "SELECT CASE WHEN t.typtype = 'c' THEN t.typname ELSE p.proname END,
+            ARRAY(SELECT a.attname::text FROM pg_attribute a
+                WHERE a.attrelid = t.typrelid AND a.attnotnull AND NOT a.attisdropped)
+        FROM pg_proc p JOIN pg_type t ON t.oid = p.prorettype
+        WHERE p.prokind = 'f' AND p.proname = $1
+        AND CASE WHEN $2::text IS NULL THEN pg_function_is_visible(p.oid)
+            ELSE p.pronamespace = to_regnamespace($2) END
+        AND $3 BETWEEN p.pronargs - p.pronargdefaults AND p.pronargs
+        AND (t.typtype = 'c' OR t.oid = 'record'::regtype AND p.proallargtypes IS NOT NULL)",
+        &[&call.name, &call.schema, &(call.args.len() as i16)],
+    )?;
+    Ok(match &functions[..] {
+        [function] => Some(FunctionRow {
+            name: function.get(0),
+            not_null: function.get(1),
+        }),
+        _ => None,
+    })
+}
+
+/// Prepares a generated newtype
+fn prepare_newtype(ty: &CornucopiaType) -> Option<(&String, PreparedType)> {
+    if let CornucopiaType::Newtype {
+        schema,
+        struct_name,
+        inner,
+    } = ty
+    {
+        Some((
+            schema,
+            PreparedType {
+                name: struct_name.clone(),
+                struct_name: struct_name.clone(),
+                pg_ty: inner.pg_ty().clone(),
+                content: PreparedContent::Newtype(inner.clone()),
+                is_copy: inner.is_copy(),
+                is_params: true,
+            },
+        ))
+    } else {
+        None
     }
-    Ok(tmp)
 }
 
 fn normalize_rust_name(name: &str) -> String {
@@ -306,6 +1118,7 @@ fn prepare_type(
         Some(PreparedType {
             name: name.to_string(),
             struct_name: struct_name.clone(),
+            pg_ty: pg_ty.clone(),
             content,
             is_copy: *is_copy,
             is_params: *is_params,
@@ -320,55 +1133,350 @@ fn prepare_module(
     client: &mut Client,
     module: Module,
     registrar: &mut TypeRegistrar,
+    configured: &ConfiguredColumns,
+    settings: &CodegenSettings,
 ) -> Result<PreparedModule, Error> {
+    log::info!("Preparing the module `{}`", module.info.name);
     validation::validate_module(&module)?;
 
+    // The queries are prepared with the session settings they rely on
+    let session = session_settings(&module.attributes);
+    let previous = apply_session(client, &module.info, &session)?;
+    let constraints = module_constraints(client, &module.info, &module.attributes)?;
+
     let mut tmp_prepared_module = PreparedModule {
         info: module.info.clone(),
         queries: IndexMap::new(),
         params: IndexMap::new(),
         rows: IndexMap::new(),
+        session: session
+            .iter()
+            .map(|(name, value)| (name.value.clone(), value.value.clone()))
+            .collect(),
+        constraints,
     };
 
-    for query in module.queries {
+    let prepared = module.queries.into_iter().try_for_each(|mut query| {
+        // Queries inherit the module's attributes
+        query.attributes.extend(module.attributes.iter().cloned());
         prepare_query(
             client,
             &mut tmp_prepared_module,
             registrar,
+            configured,
             &module.types,
             query,
-            &module.info,
-        )?;
+            settings,
+        )
+    });
+    // Restored even if a query failed, for the next modules
+    for (name, value) in previous {
+        client.execute("SELECT set_config($1, $2, false)", &[&name, &value])?;
     }
+    prepared?;
 
+    deprecate_structs(&mut tmp_prepared_module);
     validation::validate_preparation(&tmp_prepared_module)?;
 
     Ok(tmp_prepared_module)
 }
 
+/// Settings of the `session` attribute of a module
+fn session_settings(attributes: &[Attribute]) -> Vec<(&Span<String>, &Span<String>)> {
+    match Attribute::find(attributes, "session").map(|it| &it.value) {
+        Some(AttributeValue::List(nested)) => nested
+            .iter()
+            .filter_map(|it| match &it.value {
+                AttributeValue::Value(value) => Some((&it.name, value)),
+                _ => None,
+            })
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Applies the session settings of a module, returning the previous values to restore
+fn apply_session(
+    client: &mut Client,
+    info: &ModuleInfo,
+    session: &[(&Span<String>, &Span<String>)],
+) -> Result<Vec<(String, String)>, Error> {
+    let mut previous = Vec::new();
+    for (name, value) in session {
+        let current = client
+            .query_one(
+                "SELECT coalesce(current_setting($1, true), ''), set_config($1, $2, false)",
+                &[&name.value, &value.value],
+            )
+            .map_err(|err| Error::Db {
+                msg: err
+                    .as_db_error()
+                    .map_or_else(|| format!("{err:#}"), |it| it.message().to_string()),
+                help: None,
+                src: info.into(),
+                err_span: Some(name.span),
+            })?;
+        previous.push((name.value.clone(), current.get(0)));
+    }
+    Ok(previous)
+}
+
+/// Constraints of the `constraints` attribute of a module, along with the `SqlState` of their
+/// violations. Unique indexes are reported as constraints by their violations.
+fn module_constraints(
+    client: &mut Client,
+    info: &ModuleInfo,
+    attributes: &[Attribute],
+) -> Result<Vec<ModuleConstraint>, Error> {
+    let Some(AttributeValue::List(nested)) =
+        Attribute::find(attributes, "constraints").map(|it| &it.value)
+    else {
+        return Ok(Vec::new());
+    };
+    let mut constraints = Vec::new();
+    for constraint in nested {
+        let AttributeValue::Value(variant) = &constraint.value else {
+            continue;
+        };
+        let kind: Option<String> = client
+            .query_opt(
+                "SELECT contype::text FROM pg_constraint WHERE conname = $1
+                UNION ALL
+                SELECT 'u' FROM pg_index JOIN pg_class ON pg_class.oid = pg_index.indexrelid
+                WHERE pg_index.indisunique AND pg_class.relname = $1
+                LIMIT 1",
+                &[&constraint.name.value],
+            )?
+            .map(|row| row.get(0));
+        let code = match kind.as_deref() {
+            Some("p" | "u") => "UNIQUE_VIOLATION",
+            Some("f") => "FOREIGN_KEY_VIOLATION",
+            Some("c") => "CHECK_VIOLATION",
+            Some("x") => "EXCLUSION_VIOLATION",
+            Some("n") => "NOT_NULL_VIOLATION",
+            _ => {
+                return Err(Error::UnknownConstraint {
+                    src: info.into(),
+                    name: constraint.name.value.clone(),
+                    pos: constraint.name.span,
+                })
+            }
+        };
+        constraints.push(ModuleConstraint {
+            name: constraint.name.value.clone(),
+            variant: variant.value.clone(),
+            code: code.to_string(),
+        });
+    }
+    Ok(constraints)
+}
+
+/// User type `rust_type` declared for a field of type `inner`
+fn mapped_type(rust_type: &Span<String>, inner: Rc<CornucopiaType>) -> Rc<CornucopiaType> {
+    Rc::new(CornucopiaType::Mapped {
+        rust_path: rust_type.value.clone(),
+        borrowed: None,
+        inner,
+    })
+}
+
+/// Deprecates the params and row structs only used by deprecated queries
+fn deprecate_structs(module: &mut PreparedModule) {
+    let queries: Vec<_> = module.queries.values().collect();
+    let deprecation = |used: &dyn Fn(&PreparedQuery) -> bool| {
+        let mut users = queries.iter().filter(|it| used(it)).peekable();
+        let note = users.peek()?.deprecated.clone();
+        users
+            .all(|it| it.deprecated.is_some())
+            .then_some(note)
+            .flatten()
+    };
+    let params: Vec<_> = (0..module.params.len())
+        .map(|idx| deprecation(&|query| matches!(&query.param, Some((it, _)) if *it == idx)))
+        .collect();
+    let rows: Vec<_> = (0..module.rows.len())
+        .map(|idx| deprecation(&|query| matches!(&query.row, Some((it, _)) if *it == idx)))
+        .collect();
+    for (item, deprecated) in module.params.values_mut().zip(params) {
+        item.deprecated = deprecated;
+    }
+    for (item, deprecated) in module.rows.values_mut().zip(rows) {
+        item.deprecated = deprecated;
+    }
+}
+
 /// Prepares a query
 fn prepare_query(
     client: &mut Client,
     module: &mut PreparedModule,
     registrar: &mut TypeRegistrar,
+    configured: &ConfiguredColumns,
     types: &[TypeAnnotation],
     Query {
         name,
         param,
         bind_params,
+        nullable_bind_params,
         row,
         sql_str,
         sql_span,
+        attributes,
+        is_script,
+        slots,
+        sql_variants,
     }: Query,
-    module_info: &ModuleInfo,
+    settings: &CodegenSettings,
 ) -> Result<(), Error> {
+    log::debug!("Preparing the query `{}`", name.value);
+    let module_info = &module.info.clone();
+    if settings.upstream_compat {
+        validation::upstream_compat(
+            module_info,
+            &name,
+            &attributes,
+            is_script,
+            std::iter::empty(),
+        )?;
+    }
+    if is_script {
+        validation::script_query(
+            &module.info,
+            &name,
+            &sql_span,
+            &sql_str,
+            &param,
+            &row,
+            &attributes,
+            &bind_params,
+        )?;
+        // Scripts are never run, so that generating against a live database leaves its data
+        // untouched: each of their statements is prepared instead
+        for (offset, statement) in validation::script_statements(&sql_str) {
+            client.prepare(statement).map_err(|e| {
+                let span = (sql_span.offset() + offset, statement.len()).into();
+                Error::new_db_err(&e, module_info, &span, &name)
+            })?;
+        }
+        module
+            .add_query(name, None, None, sql_str, &attributes, None)
+            .is_script = true;
+        return Ok(());
+    }
+    if settings.validate_hints {
+        validation::plan_hints(&module.info, &name, &sql_span, &sql_str)?;
+    }
+    let copy = validation::copy_query(
+        &module.info,
+        &name,
+        &sql_span,
+        &attributes,
+        &sql_str,
+        &bind_params,
+    )?;
+    let (sql_str, bind_params, copy) = match copy {
+        // Copies in are prepared as the equivalent insert, binding each column
+        Some(CopyTarget::In(target)) => {
+            let (insert, copy, columns) =
+                prepare_copy_in(client, target, module_info, &sql_span, &name)?;
+            let bind_params = columns
+                .into_iter()
+                .map(|value| Span {
+                    span: sql_span,
+                    value,
+                })
+                .collect();
+            (insert, bind_params, Some(CopyStatement::In(copy)))
+        }
+        // Copies out are prepared as the copied query, to get the type of the rows
+        Some(CopyTarget::Out(query)) => {
+            let csv = Attribute::find(&attributes, "copy").and_then(|it| it.nested_value("format"))
+                == Some("csv");
+            // pg_hint_plan only reads the hint starting the statement
+            let hint = sql_analysis::split_hint(&sql_str)
+                .0
+                .map(|it| format!("{it} "))
+                .unwrap_or_default();
+            let sql = if csv {
+                format!("{hint}COPY ({query}) TO STDOUT (FORMAT csv, HEADER)")
+            } else {
+                format!("{hint}COPY ({query}) TO STDOUT (FORMAT binary)")
+            };
+            (query, bind_params, Some(CopyStatement::Out { sql, csv }))
+        }
+        None => (sql_str, bind_params, None),
+    };
+
     // Prepare the statement
     let stmt = client
         .prepare(&sql_str)
         .map_err(|e| Error::new_db_err(&e, module_info, &sql_span, &name))?;
+    // Composites compared to a parameter only type it as a `record`, so it is cast to the
+    // type of their column, e.g. `col = ANY($1::item[])`
+    let casts = record_param_casts(client, &sql_str, &stmt)?;
+    let (sql_str, sql_variants, stmt) = if casts.is_empty() {
+        (sql_str, sql_variants, stmt)
+    } else {
+        let sql_str = sql_analysis::cast_params(&sql_str, &casts);
+        let sql_variants = sql_variants
+            .iter()
+            .map(|sql| sql_analysis::cast_params(sql, &casts))
+            .collect();
+        let stmt = client
+            .prepare(&sql_str)
+            .map_err(|e| Error::new_db_err(&e, module_info, &sql_span, &name))?;
+        (sql_str, sql_variants, stmt)
+    };
+    // Text search queries are bound from text, parsed and normalized by `to_tsquery`
+    let text_queries: Vec<_> = (1..=stmt.params().len())
+        .filter(|idx| stmt.params()[idx - 1] == Type::TSQUERY)
+        .collect();
+    let (sql_str, sql_variants, stmt) = if text_queries.is_empty() {
+        (sql_str, sql_variants, stmt)
+    } else {
+        let sql_str = sql_analysis::wrap_params(&sql_str, &text_queries, "to_tsquery");
+        let sql_variants = sql_variants
+            .iter()
+            .map(|sql| sql_analysis::wrap_params(sql, &text_queries, "to_tsquery"))
+            .collect();
+        let stmt = client
+            .prepare(&sql_str)
+            .map_err(|e| Error::new_db_err(&e, module_info, &sql_span, &name))?;
+        (sql_str, sql_variants, stmt)
+    };
+    // Along with the other variants, which must be interchangeable with it
+    validation::variants_query(&module.info, &attributes, &stmt, None)?;
+    for sql in sql_variants.iter().skip(1) {
+        let variant = client
+            .prepare(sql)
+            .map_err(|e| Error::new_db_err(&e, module_info, &sql_span, &name))?;
+        validation::variants_query(&module.info, &attributes, &stmt, Some(&variant))?;
+    }
+
+    // Positional parameters are only supported on their own, as named ones are numbered too
+    let bind_params = if bind_params.is_empty() && copy.is_none() {
+        let mut params = positional_params(&sql_str, stmt.params().len(), sql_span);
+        if let Some(call) = sql_analysis::call(&sql_str) {
+            name_call_params(client, &call, &mut params)?;
+        }
+        params
+    } else {
+        bind_params
+    };
 
     let (nullable_params_fields, params_name) = param.name_and_fields(types, &name, Some("Params"));
     let (nullable_row_fields, row_name) = row.name_and_fields(types, &name, None);
+    // Rows of a function are named after its composite type, and can hold nulls in all the
+    // columns the table they are the rows of doesn't declare `NOT NULL`
+    let function_row = match sql_analysis::selected_function(&sql_str) {
+        Some(call) if copy.is_none() => function_row(client, &call)?,
+        _ => None,
+    };
+    let row_name = match &function_row {
+        Some(function) if row.is_implicit() => {
+            row_name.map(|_| function.name.to_upper_camel_case())
+        }
+        _ => row_name,
+    };
     let params_fields = {
         let stmt_params = stmt.params();
         let params = bind_params
@@ -378,26 +1486,95 @@ fn prepare_query(
             .collect::<Vec<(Span<String>, Type)>>();
         // Check for param declaration on simple query
         validation::param_on_simple_query(&module.info, &name, &sql_span, &param, &params)?;
+        validation::audited_query(&module.info, &name, &attributes, stmt.columns(), &params)?;
+        validation::cardinality_query(&module.info, &attributes, stmt.columns())?;
         for nullable_col in nullable_params_fields {
             // If none of the row's columns match the nullable column
             validation::nullable_param_name(&module.info, nullable_col, &params)
                 .map_err(Error::from)?;
         }
 
+        let columns = sql_analysis::param_columns(&sql_str, params.len());
         let mut param_fields = Vec::new();
-        for (col_name, col_ty) in params {
-            let nullity = nullable_params_fields
+        for ((col_name, col_ty), column) in params.into_iter().zip(columns) {
+            let mut nullity = nullable_params_fields
                 .iter()
-                .find(|x| x.name.value == col_name.value);
+                .find(|x| x.name.value == col_name.value)
+                .cloned()
+                .unwrap_or_else(|| NullableIdent {
+                    name: col_name.clone(),
+                    nullable: false,
+                    non_null: false,
+                    inner_nullable: false,
+                    rust_type: None,
+                });
+            // Binds marked `:name?` in the SQL are nullable too
+            nullity.nullable |= nullable_bind_params.contains(&col_name);
             // Register type
+            let mut ty = registrar
+                .register(&col_name.value, &col_ty, &name, module_info)?
+                .clone();
+            // Wrap parameters compared to or assigned to a newtype column
+            let newtype = column.as_ref().and_then(|column| {
+                configured
+                    .newtypes
+                    .iter()
+                    .find(|n| column.matches(&n.schema, &n.table, &n.column))
+            });
+            if let Some(newtype) = newtype {
+                let register = |registrar: &mut TypeRegistrar, inner| {
+                    registrar.register_newtype(
+                        &newtype.struct_name,
+                        &newtype.schema,
+                        inner,
+                        &col_name.value,
+                        &name,
+                        module_info,
+                    )
+                };
+                if col_ty.oid() == newtype.type_oid {
+                    ty = register(registrar, ty)?;
+                } else if let (CornucopiaType::Array { inner }, Kind::Array(inner_ty)) =
+                    (ty.clone().as_ref(), col_ty.kind())
+                {
+                    // `col = ANY(:ids)` takes an array of newtypes
+                    if inner_ty.oid() == newtype.type_oid {
+                        ty = Rc::new(CornucopiaType::Array {
+                            inner: register(registrar, inner.clone())?,
+                        });
+                    }
+                }
+            }
+            // Parameters compared to or assigned to a codec column are encoded by its codec
+            let codec = column.as_ref().and_then(|column| {
+                configured
+                    .codecs
+                    .iter()
+                    .find(|it| column.matches(&it.schema, &it.table, &it.column))
+            });
+            if let Some(codec) = codec {
+                if col_ty.oid() == codec.type_oid {
+                    ty = codec.mapped(ty);
+                } else if let (CornucopiaType::Array { inner }, Kind::Array(inner_ty)) =
+                    (ty.clone().as_ref(), col_ty.kind())
+                {
+                    if inner_ty.oid() == codec.type_oid {
+                        ty = Rc::new(CornucopiaType::Array {
+                            inner: codec.mapped(inner.clone()),
+                        });
+                    }
+                }
+            }
+            if let Some(rust_type) = &nullity.rust_type {
+                ty = mapped_type(rust_type, ty);
+            }
             param_fields.push(PreparedField::new(
                 col_name.value.clone(),
-                registrar
-                    .register(&col_name.value, &col_ty, &name, module_info)?
-                    .clone(),
-                nullity,
+                ty,
+                Some(&nullity),
             ));
         }
+        validation::validated_params(&module.info, &attributes, &param_fields)?;
         param_fields
     };
 
@@ -413,41 +1590,351 @@ fn prepare_query(
                 .map_err(Error::from)?;
         }
 
+        let rolled_up = sql_analysis::rolled_up_columns(&sql_str);
+        let outer_joins = (sql_analysis::operation(&sql_str).0.as_deref() == Some("SELECT"))
+            .then(|| sql_analysis::outer_joins(&sql_str))
+            .flatten();
         let mut row_fields = Vec::new();
-        for (col_name, col_ty) in stmt_cols.iter().map(|c| (c.name().to_owned(), c.type_())) {
+        for col in stmt_cols {
+            let col_name = col.name().to_owned();
             let nullity = nullable_row_fields
                 .iter()
                 .find(|x| x.name.value == col_name);
             // Register type
-            let ty = registrar
-                .register(&col_name, col_ty, &name, module_info)?
+            let mut ty = registrar
+                .register(&col_name, col.type_(), &name, module_info)?
                 .clone();
-            row_fields.push(PreparedField::new(
-                normalize_rust_name(&col_name),
-                ty,
-                nullity,
-            ));
+            // Anonymous records are decoded as the tuple declared as their Rust type
+            if *ty.pg_ty() == Type::RECORD {
+                validation::record_column(module_info, &name, col, nullity)?;
+            }
+            // Arrays don't record their dimensions, only the columns declaring them do
+            if let (CornucopiaType::Array { inner }, Some(table_oid), Some(column_id)) =
+                (ty.as_ref(), col.table_oid(), col.column_id())
+            {
+                let dims: i32 = client
+                    .query_one(
+                        "SELECT attndims::int4 FROM pg_attribute WHERE attrelid = $1 AND attnum = $2",
+                        &[&table_oid, &column_id],
+                    )?
+                    .get(0);
+                match dims {
+                    0 | 1 => {}
+                    2 => {
+                        ty = Rc::new(CornucopiaType::Matrix {
+                            pg_ty: col.type_().clone(),
+                            inner: inner.clone(),
+                        })
+                    }
+                    _ => {
+                        return Err(Error::UnsupportedArrayDims {
+                            src: module_info.into(),
+                            name: name.value.clone(),
+                            column: col_name,
+                            dims,
+                            query: name.span,
+                        })
+                    }
+                }
+            }
+            // Wrap columns coming straight from a newtype column
+            let is_col = |table_oid, column_id| {
+                col.table_oid() == Some(table_oid) && col.column_id() == Some(column_id)
+            };
+            let newtype = configured
+                .newtypes
+                .iter()
+                .find(|n| is_col(n.table_oid, n.column_id));
+            if let Some(newtype) = newtype {
+                ty = registrar.register_newtype(
+                    &newtype.struct_name,
+                    &newtype.schema,
+                    ty,
+                    &col_name,
+                    &name,
+                    module_info,
+                )?;
+            }
+            // Decode the columns coming straight from a codec column with its codec
+            if let Some(codec) = configured
+                .codecs
+                .iter()
+                .find(|it| is_col(it.table_oid, it.column_id))
+            {
+                ty = codec.mapped(ty);
+            }
+            // Hold the values of inline columns in the owned row itself
+            let inline = configured
+                .inline
+                .iter()
+                .find(|it| is_col(it.table_oid, it.column_id));
+            if let Some(inline) = inline {
+                if matches!(ty.as_ref(), CornucopiaType::Simple { .. }) {
+                    ty = Rc::new(CornucopiaType::Inline {
+                        inner: ty,
+                        capacity: inline.capacity,
+                    });
+                }
+            }
+            if let Some(rust_type) = nullity.and_then(|it| it.rust_type.as_ref()) {
+                ty = mapped_type(rust_type, ty);
+            }
+            let mut field = PreparedField::new(normalize_rust_name(&col_name), ty, nullity);
+            // Is it a column of a table on the nullable side of an outer join
+            let outer_joined = match (&outer_joins, col.table_oid()) {
+                (Some(joins), Some(table_oid)) => {
+                    let row = client.query_one(
+                        "SELECT n.nspname, c.relname FROM pg_class c
+                        JOIN pg_namespace n ON n.oid = c.relnamespace WHERE c.oid = $1",
+                        &[&table_oid],
+                    )?;
+                    joins.is_nullable(&col_name, Some((row.get(0), row.get(1))))
+                }
+                (Some(joins), None) => joins.is_nullable(&col_name, None),
+                (None, _) => false,
+            };
+            // Grouping sets null the columns they aggregate over, and outer joins those of the
+            // tables without matching rows, unless declared non-null
+            let nullable = outer_joined
+                || rolled_up.contains(&col_name)
+                || function_row
+                    .as_ref()
+                    .is_some_and(|function| !function.not_null.contains(&col_name));
+            field.is_nullable |= nullable && !nullity.is_some_and(|it| it.non_null);
+            row_fields.push(field);
         }
         row_fields
     };
 
-    let row_idx = if row_fields.is_empty() {
+    let paginate = validation::paginated_query(
+        &module.info,
+        &name,
+        &attributes,
+        stmt.columns(),
+        &row_fields,
+    )?
+    .map(|by| {
+        let columns: Vec<_> = by.iter().map(|idx| stmt.columns()[*idx].name()).collect();
+        let sql = paginated_sql(&sql_str, params_fields.len(), &columns);
+        // Check the filtered query is still valid
+        client
+            .prepare(&sql)
+            .map_err(|e| Error::new_db_err(&e, module_info, &sql_span, &name))?;
+        Ok::<_, Error>(Paginate { sql, by })
+    })
+    .transpose()?;
+
+    // Only selects are executed, as they can't have side effects
+    if settings.verify_nullability
+        && params_fields.is_empty()
+        && !row_fields.is_empty()
+        && copy.is_none()
+        && sql_analysis::operation(&sql_str).0.as_deref() == Some("SELECT")
+    {
+        verify_row_nullability(client, &sql_str, &row_fields, module_info, &sql_span, &name)?;
+    }
+
+    // Every type needed by the query is registered by now, those of the previous queries
+    // having already been checked
+    if settings.upstream_compat {
+        validation::upstream_compat(
+            module_info,
+            &name,
+            &attributes,
+            false,
+            registrar
+                .types
+                .values()
+                .chain(row_fields.iter().map(|it| &it.ty))
+                .map(AsRef::as_ref),
+        )?;
+    }
+    // Rows copied out as csv are left to the caller to decode
+    let csv = matches!(copy, Some(CopyStatement::Out { csv: true, .. }));
+    let imported = |name: &Span<String>| {
+        types
+            .iter()
+            .find(|it| it.name == *name)
+            .and_then(|it| it.from.clone())
+    };
+    let row_idx = if row_fields.is_empty() || csv {
         None
     } else {
-        Some(module.add_row(row_name, row_fields, row.is_implicit())?)
+        let imported = imported(&row_name);
+        let row_idx = module.add_row(row_name, row_fields, row.is_implicit())?;
+        module.rows[row_idx.0].imported = imported;
+        Some(row_idx)
     };
+    let has_row_struct = row_idx
+        .as_ref()
+        .is_some_and(|(idx, _)| module.rows[*idx].is_named);
+    validation::renamed_query(&module.info, &attributes, has_row_struct)?;
+    validation::derive_query(&module.info, &attributes, has_row_struct)?;
+    if let (Some((idx, _)), Some(AttributeValue::List(traits))) = (
+        &row_idx,
+        Attribute::find(&attributes, "derive").map(|it| &it.value),
+    ) {
+        let derives = &mut module.rows[*idx].derives;
+        for name in traits.iter().map(|it| &it.name.value) {
+            if !derives.contains(name) {
+                derives.push(name.clone());
+            }
+        }
+    }
     let param_idx = if params_fields.is_empty() {
         None
     } else {
-        Some(module.add_param(params_name, params_fields, param.is_implicit())?)
+        let imported = imported(&params_name);
+        let param_idx = module.add_param(params_name, params_fields, param.is_implicit())?;
+        module.params[param_idx.0].imported = imported;
+        Some(param_idx)
     };
-    module.add_query(name.clone(), param_idx, row_idx, sql_str);
+    let struct_name = Ident::new(name.value.clone()).type_ident();
+    let query = module.add_query(name.clone(), param_idx, row_idx, sql_str, &attributes, copy);
+    query.paginate = paginate;
+    query.slots = slots
+        .into_iter()
+        .map(|slot| PreparedSlot {
+            span: slot.name.span,
+            enum_name: format!("{struct_name}{}", slot.name.value.to_upper_camel_case()),
+            arg: Ident::new(slot.name.value).rs,
+            variants: slot
+                .variants
+                .iter()
+                .map(|it| it.rsplit('.').next().unwrap().to_upper_camel_case())
+                .collect(),
+        })
+        .collect();
+    query.sql_variants = sql_variants;
 
     Ok(())
 }
 
+/// Wraps `sql` to only keep the rows ordered after the values of `columns`, bound after the
+/// query's own `nb_params` parameters, up to a limit bound last. Null values keep all the rows.
+/// The hint comment starting the query is kept first, where pg_hint_plan reads it.
+fn paginated_sql(sql: &str, nb_params: usize, columns: &[&str]) -> String {
+    let (hint, sql) = sql_analysis::split_hint(sql);
+    let hint = hint.map(|it| format!("{it} ")).unwrap_or_default();
+    let nb_by = columns.len();
+    let columns = columns
+        .iter()
+        .map(|it| sql_analysis::quote_ident(it))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let after = (1..=nb_by)
+        .map(|idx| format!("${}", nb_params + idx))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let first = nb_params + 1;
+    let limit = nb_params + nb_by + 1;
+    format!(
+        "{hint}SELECT * FROM ({sql}) AS page WHERE ({columns}) > ({after}) OR ${first} IS NULL ORDER BY {columns} LIMIT ${limit}"
+    )
+}
+
+/// Decodes any value, only recording whether it is null
+struct Nullity(bool);
+
+impl<'a> FromSql<'a> for Nullity {
+    fn from_sql(_: &Type, _: &'a [u8]) -> Result<Self, Box<dyn std::error::Error + Sync + Send>> {
+        Ok(Self(false))
+    }
+
+    fn from_sql_null(_: &Type) -> Result<Self, Box<dyn std::error::Error + Sync + Send>> {
+        Ok(Self(true))
+    }
+
+    fn accepts(_: &Type) -> bool {
+        true
+    }
+}
+
+/// Number of rows of a query whose nullability is verified, bounding the time spent on large tables
+const VERIFIED_ROWS: usize = 10_000;
+
+/// Executes a query against the database's data, checking that none of its non-nullable columns
+/// is null in its first [`VERIFIED_ROWS`] rows
+fn verify_row_nullability(
+    client: &mut Client,
+    sql: &str,
+    fields: &[PreparedField],
+    module_info: &ModuleInfo,
+    sql_span: &SourceSpan,
+    name: &Span<String>,
+) -> Result<(), Error> {
+    let db_err = |e| Error::new_db_err(&e, module_info, sql_span, name);
+    let (hint, sql) = sql_analysis::split_hint(sql);
+    let hint = hint.map(|it| format!("{it} ")).unwrap_or_default();
+    let sql = format!("{hint}SELECT * FROM ({sql}) AS verified LIMIT {VERIFIED_ROWS}");
+    // The transaction is rolled back when dropped, leaving the data untouched
+    let mut transaction = client.transaction().map_err(db_err)?;
+    let stmt = transaction.prepare(&sql).map_err(db_err)?;
+    let mut nulls = vec![false; fields.len()];
+    for row in transaction.query(&stmt, &[]).map_err(db_err)? {
+        for (idx, null) in nulls.iter_mut().enumerate() {
+            *null |= row.try_get::<_, Nullity>(idx).map_err(db_err)?.0;
+        }
+    }
+    let columns = stmt
+        .columns()
+        .iter()
+        .zip(fields)
+        .zip(nulls)
+        .filter(|((_, field), null)| *null && !field.is_nullable)
+        .map(|((column, _), _)| format!("`{}`", column.name()))
+        .collect::<Vec<_>>();
+    if columns.is_empty() {
+        Ok(())
+    } else {
+        Err(Error::NullInNonNullable {
+            src: module_info.into(),
+            name: name.value.clone(),
+            columns: columns.join(", "),
+            query: name.span,
+        })
+    }
+}
+
+/// Builds the `INSERT` equivalent to a copy in and its binary `COPY` statement, along with the copied columns
+fn prepare_copy_in(
+    client: &mut Client,
+    target: CopyIn,
+    module_info: &ModuleInfo,
+    sql_span: &SourceSpan,
+    name: &Span<String>,
+) -> Result<(String, String, Vec<String>), Error> {
+    let CopyIn { table, columns } = target;
+    let columns = match columns {
+        Some(columns) => columns,
+        // All the columns of the table are copied, in order
+        None => client
+            .prepare(&format!("SELECT * FROM {table}"))
+            .map_err(|e| Error::new_db_err(&e, module_info, sql_span, name))?
+            .columns()
+            .iter()
+            .map(|it| it.name().to_string())
+            .collect(),
+    };
+    let list = columns
+        .iter()
+        .map(|it| sql_analysis::quote_ident(it))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let values = (1..=columns.len())
+        .map(|idx| format!("${idx}"))
+        .collect::<Vec<_>>()
+        .join(", ");
+    Ok((
+        format!("INSERT INTO {table} ({list}) VALUES ({values})"),
+        format!("COPY {table} ({list}) FROM STDIN (FORMAT binary)"),
+        columns,
+    ))
+}
+
 pub(crate) mod error {
     use miette::{Diagnostic, NamedSource, SourceSpan};
+    use postgres::error::SqlState;
     use thiserror::Error as ThisError;
 
     use crate::{
@@ -467,6 +1954,86 @@ pub(crate) mod error {
             #[label("error occurs near this location")]
             err_span: Option<SourceSpan>,
         },
+        #[error("the query `{name}` returned null in the non-nullable columns {columns}")]
+        #[diagnostic(help(
+            "declare those columns as nullable in the row, e.g. `--! name : (column?)`"
+        ))]
+        NullInNonNullable {
+            #[source_code]
+            src: NamedSource,
+            name: String,
+            columns: String,
+            #[label("this query returned null in non-nullable columns")]
+            query: SourceSpan,
+        },
+        #[error("`{name}` isn't a {kind} struct of `{module}`")]
+        #[diagnostic(help("import the struct as the same kind of struct it is declared as"))]
+        ImportedKind {
+            #[source_code]
+            src: NamedSource,
+            name: String,
+            module: String,
+            kind: &'static str,
+            #[label("used as a {kind} struct here")]
+            pos: SourceSpan,
+        },
+        #[error("imported struct with other fields than in `{module}`")]
+        ImportedFields {
+            #[source_code]
+            src: NamedSource,
+            module: String,
+            #[label("its fields differ here")]
+            pos: SourceSpan,
+            #[help]
+            help: String,
+        },
+        #[error("Couldn't find newtype column `{column}` in the database")]
+        #[diagnostic(help("newtype columns are declared as `schema.table.column`"))]
+        UnknownNewtypeColumn { column: String },
+        #[error("Couldn't find table `{table}` in the database")]
+        #[diagnostic(help("tables are included as `schema.table`, and excluding them wins"))]
+        UnknownTable { table: String },
+        #[error("Couldn't find codec column `{column}` in the database")]
+        #[diagnostic(help(
+            "codecs are declared for a type as `schema.name`, or for a column as `schema.table.column`"
+        ))]
+        UnknownCodecColumn { column: String },
+        #[error("Couldn't find inline column `{column}` in the database")]
+        #[diagnostic(help("inline columns are declared as `schema.table.column`"))]
+        UnknownInlineColumn { column: String },
+        #[error("Couldn't hold column `{column}` inline: {msg}")]
+        InvalidInlineColumn {
+            column: String,
+            msg: String,
+            #[help]
+            help: String,
+        },
+        #[error("the column `{column}` of the query `{name}` is declared with {dims} dimensions")]
+        #[diagnostic(help(
+            "arrays of one or two dimensions are decoded, select the others as json, e.g. `array_to_json({column})`"
+        ))]
+        UnsupportedArrayDims {
+            #[source_code]
+            src: NamedSource,
+            name: String,
+            column: String,
+            dims: i32,
+            #[label("its rows hold arrays of at most two dimensions")]
+            query: SourceSpan,
+        },
+        #[error("Couldn't find constraint `{name}` in the database")]
+        #[diagnostic(help(
+            "declare unique, primary key, foreign key, check or exclusion constraints, or unique indexes"
+        ))]
+        UnknownConstraint {
+            #[source_code]
+            src: NamedSource,
+            name: String,
+            #[label("no such constraint")]
+            pos: SourceSpan,
+        },
+        #[error("Couldn't query the database catalog: {0}")]
+        Catalog(#[from] postgres::Error),
         #[error(transparent)]
         #[diagnostic(transparent)]
         PostgresType(#[from] PostgresTypeError),
@@ -482,18 +2049,32 @@ pub(crate) mod error {
             query_span: &SourceSpan,
             query_name: &Span<String>,
         ) -> Self {
-            let msg = format!("{err:#}");
+            // The display of client errors varies between versions, unlike the database's message
+            let msg = err
+                .as_db_error()
+                .map_or_else(|| format!("{err:#}"), |it| it.message().to_string());
+            // Parameters only passed to polymorphic arguments have no type to deduce
+            let polymorphic_help = || {
+                err.as_db_error()
+                    .filter(|it| {
+                        *it.code() == SqlState::DATATYPE_MISMATCH
+                            && it.message().contains("polymorphic")
+                    })
+                    .map(|_| {
+                        "cast the parameter to the type it holds, e.g. `:value::int4`".to_string()
+                    })
+            };
             if let Some((position, msg, help)) = db_err(err) {
                 Self::Db {
+                    help: help.or_else(polymorphic_help),
                     msg,
-                    help,
                     src: module_info.into(),
                     err_span: Some((query_span.offset() + position as usize - 1).into()),
                 }
             } else {
                 Self::Db {
+                    help: polymorphic_help(),
                     msg,
-                    help: None,
                     src: module_info.into(),
                     err_span: Some(query_name.span),
                 }
@@ -1,13 +1,18 @@
 use std::{
+    io::Read,
     path::{Path, PathBuf},
     sync::Arc,
 };
 
+use heck::{ToSnakeCase, ToUpperCamelCase};
 use miette::NamedSource;
+use serde::{Deserialize, Serialize};
+
+use crate::sql_analysis::quote_ident;
 
 use self::error::Error;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub(crate) struct ModuleInfo {
     pub(crate) path: PathBuf,
     pub(crate) name: String,
@@ -26,18 +31,28 @@ impl From<&ModuleInfo> for NamedSource {
     }
 }
 
+/// Path reading the queries of a single module from the standard input
+pub(crate) const STDIN_PATH: &str = "-";
+
+/// Name of the module read from the standard input
+const STDIN_MODULE: &str = "stdin";
+
 /// Reads queries in the directory. Only .sql files are considered.
+/// If `dir_path` is `-`, a single module is read from the standard input instead.
 ///
 /// # Error
 /// Returns an error if `dir_path` does not point to a valid directory or if a query file cannot be parsed.
 pub(crate) fn read_query_modules(dir_path: &Path) -> Result<Vec<ModuleInfo>, Error> {
+    if dir_path == Path::new(STDIN_PATH) {
+        return read_stdin_module().map(|module| vec![module]);
+    }
     let mut modules_info = Vec::new();
-    for entry_result in std::fs::read_dir(dir_path).map_err(|err| Error {
+    for entry_result in std::fs::read_dir(dir_path).map_err(|err| Error::Io {
         err,
         path: dir_path.to_owned(),
     })? {
         // Directory entry
-        let entry = entry_result.map_err(|err| Error {
+        let entry = entry_result.map_err(|err| Error::Io {
             err,
             path: dir_path.to_owned(),
         })?;
@@ -56,7 +71,7 @@ pub(crate) fn read_query_modules(dir_path: &Path) -> Result<Vec<ModuleInfo>, Err
                 .expect("file name is valid utf8")
                 .to_string();
 
-            let file_contents = std::fs::read_to_string(&path_buf).map_err(|err| Error {
+            let file_contents = std::fs::read_to_string(&path_buf).map_err(|err| Error::Io {
                 err,
                 path: dir_path.to_owned(),
             })?;
@@ -73,6 +88,131 @@ pub(crate) fn read_query_modules(dir_path: &Path) -> Result<Vec<ModuleInfo>, Err
     Ok(modules_info)
 }
 
+/// Merges the modules of several query folders, the first one being the main folder. The
+/// modules of the other folders named like a previous one are named after their folder too,
+/// e.g. `billing_invoices` for `crates/billing/queries/invoices.sql`.
+pub(crate) fn merge_query_folders(
+    folders: Vec<(&Path, Vec<ModuleInfo>)>,
+) -> Result<Vec<ModuleInfo>, Error> {
+    let mut merged: Vec<ModuleInfo> = Vec::new();
+    for (idx, (dir_path, modules_info)) in folders.into_iter().enumerate() {
+        let taken: Vec<_> = merged.iter().map(|it| it.name.clone()).collect();
+        for mut module in modules_info {
+            if idx > 0 && taken.contains(&module.name) {
+                // The nearest folder telling the modules apart
+                let folder = dir_path
+                    .components()
+                    .rev()
+                    .filter_map(|it| it.as_os_str().to_str())
+                    .find(|it| !matches!(*it, "queries" | "." | ".." | "/"));
+                if let Some(folder) = folder {
+                    module.name = format!("{}_{}", folder.to_snake_case(), module.name);
+                }
+            }
+            if let Some(first) = merged.iter().find(|it| it.name == module.name) {
+                return Err(Error::DuplicateModule {
+                    name: module.name,
+                    first: first.path.to_string_lossy().into_owned(),
+                    second: module.path.to_string_lossy().into_owned(),
+                });
+            }
+            merged.push(module);
+        }
+    }
+    merged.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(merged)
+}
+
+/// Reads the queries of the `stdin` module from the standard input
+fn read_stdin_module() -> Result<ModuleInfo, Error> {
+    let mut content = String::new();
+    std::io::stdin()
+        .read_to_string(&mut content)
+        .map_err(|err| Error::Io {
+            err,
+            path: PathBuf::from(STDIN_PATH),
+        })?;
+    Ok(ModuleInfo {
+        path: PathBuf::from("<stdin>"),
+        name: STDIN_MODULE.to_string(),
+        content: Arc::new(content),
+    })
+}
+
+/// Name of the module generated for the configured materialized views
+pub(crate) const MATERIALIZED_VIEWS_MODULE: &str = "materialized_views";
+
+/// Name of the view, without its schema
+pub(crate) fn materialized_view_name(view: &str) -> &str {
+    view.rsplit('.').next().unwrap_or(view).trim_matches('"')
+}
+
+/// Builds a module selecting every row of each materialized view, giving them typed rows.
+/// Their refresh helpers are generated alongside.
+pub(crate) fn materialized_views_module(views: &[String]) -> Option<ModuleInfo> {
+    if views.is_empty() {
+        return None;
+    }
+    let content = views
+        .iter()
+        .map(|view| {
+            let name = materialized_view_name(view);
+            format!(
+                "--! {name} : {}()\nSELECT * FROM {view};\n",
+                name.to_upper_camel_case()
+            )
+        })
+        .collect();
+    Some(ModuleInfo {
+        path: PathBuf::from(format!("{MATERIALIZED_VIEWS_MODULE}.sql")),
+        name: MATERIALIZED_VIEWS_MODULE.to_string(),
+        content: Arc::new(content),
+    })
+}
+
+/// Name of the module generated for the configured tables
+pub(crate) const TABLES_MODULE: &str = "tables";
+
+/// Table getting a row struct, as found in the database catalog
+#[derive(Debug, Clone)]
+pub(crate) struct Table {
+    pub(crate) schema: String,
+    pub(crate) name: String,
+    /// Columns which aren't `NOT NULL`
+    pub(crate) nullable: Vec<String>,
+}
+
+/// Builds a module selecting every row of each table, giving them row structs holding all
+/// their columns. The tables outside of `public` are prefixed by their schema.
+pub(crate) fn tables_module(tables: &[Table]) -> Option<ModuleInfo> {
+    if tables.is_empty() {
+        return None;
+    }
+    let content = tables
+        .iter()
+        .map(|table| {
+            let name = if table.schema == "public" {
+                table.name.to_snake_case()
+            } else {
+                format!("{}_{}", table.schema, table.name).to_snake_case()
+            };
+            let nullable: Vec<_> = table.nullable.iter().map(|it| format!("{it}?")).collect();
+            format!(
+                "--! {name} : {}({})\nSELECT * FROM {}.{};\n",
+                name.to_upper_camel_case(),
+                nullable.join(", "),
+                quote_ident(&table.schema),
+                quote_ident(&table.name)
+            )
+        })
+        .collect();
+    Some(ModuleInfo {
+        path: PathBuf::from(format!("{TABLES_MODULE}.sql")),
+        name: TABLES_MODULE.to_string(),
+        content: Arc::new(content),
+    })
+}
+
 pub(crate) mod error {
     use std::path::PathBuf;
 
@@ -80,9 +220,15 @@ pub(crate) mod error {
     use thiserror::Error as ThisError;
 
     #[derive(Debug, ThisError, Diagnostic)]
-    #[error("[{path}] : {err:#}")]
-    pub struct Error {
-        pub(crate) err: std::io::Error,
-        pub(crate) path: PathBuf,
+    pub enum Error {
+        #[error("[{path}] : {err:#}")]
+        Io { err: std::io::Error, path: PathBuf },
+        #[error("the query modules `{first}` and `{second}` are both named `{name}`")]
+        #[diagnostic(help("rename one of their files, as their folders don't tell them apart"))]
+        DuplicateModule {
+            name: String,
+            first: String,
+            second: String,
+        },
     }
 }
@@ -0,0 +1,108 @@
+//! Serialization of the foreign types held by a [`Preparation`](crate::Preparation).
+
+/// Serializes a postgres [`Type`](postgres_types::Type) as its name, oid, schema and kind,
+/// recursively. Built-in types are deserialized from their oid.
+pub(crate) mod pg_type {
+    use postgres_types::{Field, Kind, Type};
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    #[derive(Serialize, Deserialize)]
+    struct PgType {
+        name: String,
+        oid: u32,
+        schema: String,
+        kind: PgKind,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    enum PgKind {
+        Simple,
+        Enum(Vec<String>),
+        Pseudo,
+        Array(Box<PgType>),
+        Range(Box<PgType>),
+        Multirange(Box<PgType>),
+        Domain(Box<PgType>),
+        Composite(Vec<(String, PgType)>),
+    }
+
+    impl From<&Type> for PgType {
+        fn from(ty: &Type) -> Self {
+            let inner = |ty: &Type| Box::new(PgType::from(ty));
+            let kind = match ty.kind() {
+                Kind::Enum(variants) => PgKind::Enum(variants.clone()),
+                Kind::Pseudo => PgKind::Pseudo,
+                Kind::Array(ty) => PgKind::Array(inner(ty)),
+                Kind::Range(ty) => PgKind::Range(inner(ty)),
+                Kind::Multirange(ty) => PgKind::Multirange(inner(ty)),
+                Kind::Domain(ty) => PgKind::Domain(inner(ty)),
+                Kind::Composite(fields) => PgKind::Composite(
+                    fields
+                        .iter()
+                        .map(|it| (it.name().to_string(), PgType::from(it.type_())))
+                        .collect(),
+                ),
+                _ => PgKind::Simple,
+            };
+            Self {
+                name: ty.name().to_string(),
+                oid: ty.oid(),
+                schema: ty.schema().to_string(),
+                kind,
+            }
+        }
+    }
+
+    impl From<PgType> for Type {
+        fn from(ty: PgType) -> Self {
+            if let Some(ty) = Type::from_oid(ty.oid) {
+                return ty;
+            }
+            let kind = match ty.kind {
+                PgKind::Simple => Kind::Simple,
+                PgKind::Enum(variants) => Kind::Enum(variants),
+                PgKind::Pseudo => Kind::Pseudo,
+                PgKind::Array(ty) => Kind::Array((*ty).into()),
+                PgKind::Range(ty) => Kind::Range((*ty).into()),
+                PgKind::Multirange(ty) => Kind::Multirange((*ty).into()),
+                PgKind::Domain(ty) => Kind::Domain((*ty).into()),
+                PgKind::Composite(fields) => Kind::Composite(
+                    fields
+                        .into_iter()
+                        .map(|(name, ty)| Field::new(name, ty.into()))
+                        .collect(),
+                ),
+            };
+            Type::new(ty.name, ty.oid, kind, ty.schema)
+        }
+    }
+
+    pub(crate) fn serialize<S: Serializer>(ty: &Type, serializer: S) -> Result<S::Ok, S::Error> {
+        PgType::from(ty).serialize(serializer)
+    }
+
+    pub(crate) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Type, D::Error> {
+        PgType::deserialize(deserializer).map(Type::from)
+    }
+}
+
+/// Serializes a [`SourceSpan`](miette::SourceSpan) as its offset and length
+pub(crate) mod source_span {
+    use miette::SourceSpan;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub(crate) fn serialize<S: Serializer>(
+        span: &SourceSpan,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        (span.offset(), span.len()).serialize(serializer)
+    }
+
+    pub(crate) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<SourceSpan, D::Error> {
+        <(usize, usize)>::deserialize(deserializer).map(Into::into)
+    }
+}
@@ -0,0 +1,178 @@
+use std::{collections::BTreeMap, fmt::Write, path::Path, sync::Arc};
+
+use miette::NamedSource;
+use serde::Deserialize;
+use toml::Spanned;
+
+use crate::{read_queries::ModuleInfo, sql_analysis};
+
+use self::error::Error;
+
+/// Name of the file annotating the plain SQL files of a queries directory
+pub(crate) const SIDECAR_FILE: &str = "queries.toml";
+
+/// Annotations of a query module, kept out of its SQL file
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ModuleAnnotations {
+    /// Type annotations, e.g. `Author(name?)`
+    #[serde(default)]
+    types: Vec<String>,
+    /// Module attributes, inherited by its queries
+    #[serde(default)]
+    attributes: Vec<String>,
+    /// Annotations of the statements of the file, in order
+    #[serde(default)]
+    queries: Vec<QueryAnnotation>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct QueryAnnotation {
+    name: String,
+    /// Params struct, e.g. `AuthorParams(name?)` or `(name?)`
+    params: Option<String>,
+    /// Row struct, e.g. `Author(name?)` or `(name?)`
+    row: Option<String>,
+    #[serde(default)]
+    attributes: Vec<String>,
+}
+
+impl QueryAnnotation {
+    /// Annotation lines, as written in a query file
+    fn lines(&self) -> String {
+        let mut lines = format!("--! {}", self.name);
+        if let Some(params) = &self.params {
+            write!(lines, " {params}").unwrap();
+        }
+        if let Some(row) = &self.row {
+            write!(lines, " : {row}").unwrap();
+        }
+        lines.push('\n');
+        for attribute in &self.attributes {
+            writeln!(lines, "--# {attribute}").unwrap();
+        }
+        lines
+    }
+}
+
+/// Annotates the plain SQL files of `modules` with the `queries.toml` file of `dir_path`, if
+/// any. Each query of a module annotates the statement of its file at the same position, and
+/// the modules are then read as if they had been annotated in place.
+pub(crate) fn apply_sidecar(dir_path: &Path, modules: &mut [ModuleInfo]) -> Result<(), Error> {
+    let path = dir_path.join(SIDECAR_FILE);
+    let content = match std::fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+        Err(err) => {
+            return Err(Error::Io {
+                path: path.to_string_lossy().to_string(),
+                err,
+            })
+        }
+    };
+    let src = || NamedSource::new(path.to_string_lossy(), content.clone());
+    let sidecar: BTreeMap<Spanned<String>, ModuleAnnotations> =
+        toml::from_str(&content).map_err(|err| Error::Parse {
+            msg: err.message().to_string(),
+            err_span: err.span().map(Into::into),
+            src: src(),
+        })?;
+    for (name, annotations) in sidecar {
+        let Some(module) = modules.iter_mut().find(|it| it.name == *name.get_ref()) else {
+            let names: Vec<_> = modules.iter().map(|it| it.name.as_str()).collect();
+            return Err(Error::UnknownModule {
+                src: src(),
+                span: name.span().into(),
+                name: name.into_inner(),
+                help: format!("the query modules are `{}`", names.join("`, `")),
+            });
+        };
+        if module
+            .content
+            .lines()
+            .any(|line| line.trim_start().starts_with("--!"))
+        {
+            return Err(Error::Annotated {
+                src: src(),
+                span: name.span().into(),
+                name: name.into_inner(),
+            });
+        }
+        let starts = sql_analysis::statement_starts(&module.content);
+        if starts.len() != annotations.queries.len() {
+            return Err(Error::QueryCount {
+                src: src(),
+                span: name.span().into(),
+                name: name.into_inner(),
+                declared: annotations.queries.len(),
+                found: starts.len(),
+            });
+        }
+        let mut annotated = String::new();
+        for ty in &annotations.types {
+            writeln!(annotated, "--: {ty}").unwrap();
+        }
+        for attribute in &annotations.attributes {
+            writeln!(annotated, "--#! {attribute}").unwrap();
+        }
+        let mut copied = 0;
+        for (start, query) in starts.into_iter().zip(&annotations.queries) {
+            annotated.push_str(&module.content[copied..start]);
+            annotated.push_str(&query.lines());
+            copied = start;
+        }
+        annotated.push_str(&module.content[copied..]);
+        module.content = Arc::new(annotated);
+    }
+    Ok(())
+}
+
+pub(crate) mod error {
+    use miette::{Diagnostic, NamedSource, SourceSpan};
+    use thiserror::Error as ThisError;
+
+    #[derive(Debug, ThisError, Diagnostic)]
+    pub enum Error {
+        #[error("Could not read annotations file `{path}`: ({err})")]
+        Io { path: String, err: std::io::Error },
+        #[error("Could not parse annotations file: {msg}")]
+        Parse {
+            msg: String,
+            #[source_code]
+            src: NamedSource,
+            #[label("error occurs near this location")]
+            err_span: Option<SourceSpan>,
+        },
+        #[error("annotations of the unknown module `{name}`")]
+        UnknownModule {
+            name: String,
+            #[source_code]
+            src: NamedSource,
+            #[label("no `{name}.sql` query file")]
+            span: SourceSpan,
+            #[help]
+            help: String,
+        },
+        #[error("the module `{name}` is already annotated in its query file")]
+        #[diagnostic(help("keep the annotations of a module either in its file or here"))]
+        Annotated {
+            name: String,
+            #[source_code]
+            src: NamedSource,
+            #[label("annotated here too")]
+            span: SourceSpan,
+        },
+        #[error("the module `{name}` has {found} statements, but {declared} annotated queries")]
+        #[diagnostic(help("annotate each `;` separated statement of the file, in order"))]
+        QueryCount {
+            name: String,
+            declared: usize,
+            found: usize,
+            #[source_code]
+            src: NamedSource,
+            #[label("annotated here")]
+            span: SourceSpan,
+        },
+    }
+}
@@ -0,0 +1,1492 @@
+//! Lightweight lexical analysis of query SQL.
+//!
+//! Postgres only describes the type of bind parameters, not where they are used. This module
+//! recovers, on a best effort basis, which column a parameter is compared to or assigned to
+//! (`col = $1`, `col = ANY($1)`, `$1 = ANY(col)`, `col IN ($1, $2)`, `SET col = $1` and `INSERT`
+//! value lists, including the ones of `MERGE` statements).
+//! It also splits statements, finds the operation of a statement and the table it mainly
+//! works on, parses the target of `COPY` statements and the arguments of `CALL` statements
+//! or of the function a query selects from, finds plan hint comments and the outer joins of
+//! queries, lists their output columns, and casts parameters in place.
+
+use std::ops::Range;
+
+/// A table referenced in a query, with its optional alias
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct TableRef {
+    pub(crate) schema: Option<String>,
+    pub(crate) name: String,
+    pub(crate) alias: Option<String>,
+}
+
+/// A column a bind parameter is related to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct ParamColumn {
+    pub(crate) column: String,
+    /// Tables that could own this column. An unqualified column could belong to any table of the query.
+    pub(crate) tables: Vec<TableRef>,
+}
+
+impl ParamColumn {
+    /// Could this column be `schema.table.column`
+    pub(crate) fn matches(&self, schema: &str, table: &str, column: &str) -> bool {
+        self.column == column
+            && self
+                .tables
+                .iter()
+                .any(|t| t.name == table && t.schema.as_deref().unwrap_or(schema) == schema)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    /// Identifier, lowercased unless quoted
+    Ident { name: String, quoted: bool },
+    /// Bind parameter (`$1`)
+    Param(usize),
+    /// Operators and punctuation
+    Punct(String),
+    /// Literals
+    Other,
+}
+
+impl Token {
+    fn is_punct(&self, s: &str) -> bool {
+        matches!(self, Token::Punct(p) if p == s)
+    }
+
+    fn is_keyword(&self, kw: &str) -> bool {
+        matches!(self, Token::Ident { name, quoted: false } if name == kw)
+    }
+
+    fn ident(&self) -> Option<&str> {
+        match self {
+            Token::Ident { name, quoted } if *quoted || !is_reserved(name) => Some(name),
+            _ => None,
+        }
+    }
+}
+
+/// Keywords that can't be used as an alias or a column name without quoting
+fn is_reserved(name: &str) -> bool {
+    const RESERVED: &[&str] = &[
+        "all",
+        "and",
+        "any",
+        "as",
+        "asc",
+        "between",
+        "by",
+        "case",
+        "cross",
+        "default",
+        "delete",
+        "desc",
+        "distinct",
+        "do",
+        "else",
+        "end",
+        "except",
+        "fetch",
+        "for",
+        "from",
+        "full",
+        "group",
+        "having",
+        "in",
+        "inner",
+        "insert",
+        "intersect",
+        "into",
+        "is",
+        "join",
+        "lateral",
+        "left",
+        "limit",
+        "natural",
+        "not",
+        "offset",
+        "on",
+        "or",
+        "order",
+        "returning",
+        "right",
+        "select",
+        "set",
+        "then",
+        "union",
+        "using",
+        "values",
+        "when",
+        "where",
+        "window",
+        "with",
+    ];
+    RESERVED.contains(&name)
+}
+
+fn tokenize(sql: &str) -> Vec<Token> {
+    lex(sql).into_iter().map(|(token, _)| token).collect()
+}
+
+/// Tokens of `sql`, along with the char offset they end at
+fn lex(sql: &str) -> Vec<(Token, usize)> {
+    scan(sql).0
+}
+
+/// Tokens of `sql` along with the char offset they end at, and the char ranges of its
+/// `/*+ ... */` hint comments
+fn scan(sql: &str) -> (Vec<(Token, usize)>, Vec<Range<usize>>) {
+    let chars: Vec<char> = sql.chars().collect();
+    let mut tokens = Vec::new();
+    let mut hints = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let next = chars.get(i + 1).copied();
+        if c.is_whitespace() {
+            i += 1;
+        } else if c == '-' && next == Some('-') {
+            while i < chars.len() && chars[i] != '\n' {
+                i += 1;
+            }
+        } else if c == '/' && next == Some('*') {
+            let start = i;
+            i += 2;
+            while i < chars.len() && !(chars[i] == '*' && chars.get(i + 1) == Some(&'/')) {
+                i += 1;
+            }
+            i += 2;
+            if chars.get(start + 2) == Some(&'+') {
+                hints.push(start..i.min(chars.len()));
+            }
+        } else if c == '\'' || (matches!(c, 'e' | 'E') && next == Some('\'')) {
+            // String literal, with backslash escapes for E'' strings
+            let escapes = c != '\'';
+            i += if escapes { 2 } else { 1 };
+            while i < chars.len() {
+                if escapes && chars[i] == '\\' {
+                    i += 2;
+                } else if chars[i] == '\'' {
+                    if chars.get(i + 1) == Some(&'\'') {
+                        i += 2;
+                    } else {
+                        i += 1;
+                        break;
+                    }
+                } else {
+                    i += 1;
+                }
+            }
+            tokens.push((Token::Other, i));
+        } else if c == '"' {
+            let mut name = String::new();
+            i += 1;
+            while i < chars.len() {
+                if chars[i] == '"' {
+                    if chars.get(i + 1) == Some(&'"') {
+                        name.push('"');
+                        i += 2;
+                    } else {
+                        i += 1;
+                        break;
+                    }
+                } else {
+                    name.push(chars[i]);
+                    i += 1;
+                }
+            }
+            tokens.push((Token::Ident { name, quoted: true }, i));
+        } else if c == '$' && matches!(next, Some(c) if c.is_ascii_digit()) {
+            let start = i + 1;
+            i += 1;
+            while i < chars.len() && chars[i].is_ascii_digit() {
+                i += 1;
+            }
+            let idx: String = chars[start..i].iter().collect();
+            tokens.push((Token::Param(idx.parse().unwrap_or(0)), i));
+        } else if c == '$' {
+            // Dollar quoted string
+            let start = i;
+            i += 1;
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            if chars.get(i) == Some(&'$') {
+                let tag: Vec<char> = chars[start..=i].to_vec();
+                i += 1;
+                while i < chars.len() && !chars[i..].starts_with(&tag) {
+                    i += 1;
+                }
+                i += tag.len();
+            }
+            tokens.push((Token::Other, i));
+        } else if c.is_alphabetic() || c == '_' {
+            let start = i;
+            while i < chars.len()
+                && (chars[i].is_alphanumeric() || chars[i] == '_' || chars[i] == '$')
+            {
+                i += 1;
+            }
+            let name: String = chars[start..i].iter().collect();
+            tokens.push((
+                Token::Ident {
+                    name: name.to_lowercase(),
+                    quoted: false,
+                },
+                i,
+            ));
+        } else if c.is_ascii_digit() {
+            while i < chars.len() && (chars[i].is_ascii_alphanumeric() || chars[i] == '.') {
+                i += 1;
+            }
+            tokens.push((Token::Other, i));
+        } else if c == ':' && next == Some(':') {
+            i += 2;
+            tokens.push((Token::Punct("::".into()), i));
+        } else if "+-*/<>=~!@#%^&|`?".contains(c) {
+            let start = i;
+            while i < chars.len() && "+-*/<>=~!@#%^&|`?".contains(chars[i]) {
+                i += 1;
+            }
+            tokens.push((Token::Punct(chars[start..i].iter().collect()), i));
+        } else {
+            i += 1;
+            tokens.push((Token::Punct(c.to_string()), i));
+        }
+    }
+    (tokens, hints)
+}
+
+/// Token at `i`, or a placeholder past the end
+fn at(tokens: &[Token], i: usize) -> &Token {
+    tokens.get(i).unwrap_or(&Token::Other)
+}
+
+/// Parses a possibly qualified name starting at `i`, returning its parts and the index after it.
+fn qualified_name(tokens: &[Token], mut i: usize) -> Option<(Vec<String>, usize)> {
+    let mut parts = vec![tokens.get(i)?.ident()?.to_string()];
+    i += 1;
+    while at(tokens, i).is_punct(".") {
+        parts.push(tokens.get(i + 1)?.ident()?.to_string());
+        i += 2;
+    }
+    Some((parts, i))
+}
+
+/// Parses a possibly qualified name ending right before `end`, returning its parts
+fn qualified_name_before(tokens: &[Token], end: usize) -> Option<Vec<String>> {
+    let mut i = end.checked_sub(1)?;
+    let mut parts = vec![tokens[i].ident()?.to_string()];
+    while i >= 2 && tokens[i - 1].is_punct(".") {
+        parts.insert(0, tokens[i - 2].ident()?.to_string());
+        i -= 2;
+    }
+    // A name preceded by a dot is only the end of a longer expression
+    if i >= 1 && (tokens[i - 1].is_punct(".") || tokens[i - 1].is_punct("::")) {
+        return None;
+    }
+    Some(parts)
+}
+
+/// Collects all the tables referenced in `FROM`, `JOIN`, `UPDATE`, `USING` and `INTO` clauses
+fn tables(tokens: &[Token]) -> Vec<TableRef> {
+    let mut tables = Vec::new();
+    for (i, token) in tokens.iter().enumerate() {
+        let is_into = token.is_keyword("into");
+        if !(is_into
+            || ["from", "join", "update", "using"]
+                .iter()
+                .any(|kw| token.is_keyword(kw)))
+        {
+            continue;
+        }
+        let mut i = i + 1;
+        while let Some((mut parts, end)) = qualified_name(tokens, i) {
+            i = end;
+            // Function calls are not tables
+            if !is_into && at(tokens, i).is_punct("(") {
+                break;
+            }
+            if at(tokens, i).is_keyword("as") {
+                i += 1;
+            }
+            let alias = tokens.get(i).and_then(Token::ident).map(|it| {
+                i += 1;
+                it.to_string()
+            });
+            let name = parts.pop().unwrap();
+            tables.push(TableRef {
+                schema: parts.pop(),
+                name,
+                alias,
+            });
+            // Comma separated tables
+            if !is_into && at(tokens, i).is_punct(",") {
+                i += 1;
+            } else {
+                break;
+            }
+        }
+    }
+    tables
+}
+
+/// Resolves a possibly qualified column reference against the query's tables
+fn resolve(mut parts: Vec<String>, tables: &[TableRef]) -> ParamColumn {
+    let column = parts.pop().unwrap();
+    let tables = match parts.pop() {
+        Some(qualifier) => {
+            let schema = parts.pop();
+            tables
+                .iter()
+                .filter(|t| {
+                    t.alias.as_ref() == Some(&qualifier)
+                        || (t.name == qualifier && (schema.is_none() || t.schema == schema))
+                })
+                .map(|t| TableRef {
+                    schema: t.schema.clone().or_else(|| schema.clone()),
+                    ..t.clone()
+                })
+                .collect()
+        }
+        None => tables.to_vec(),
+    };
+    ParamColumn { column, tables }
+}
+
+fn is_comparison(token: &Token) -> bool {
+    ["=", "<>", "!=", "<", ">", "<=", ">="]
+        .iter()
+        .any(|op| token.is_punct(op))
+}
+
+/// Index after a param and its optional cast (`$1::int4[]`)
+fn skip_cast(tokens: &[Token], mut i: usize) -> usize {
+    if at(tokens, i).is_punct("::") {
+        i += 2;
+        while at(tokens, i).is_punct("[") || at(tokens, i).is_punct("]") {
+            i += 1;
+        }
+    }
+    i
+}
+
+/// Column a parameter at position `i` is compared to
+fn compared_column(tokens: &[Token], i: usize) -> Option<Vec<String>> {
+    // col = $1
+    if i >= 2 && is_comparison(&tokens[i - 1]) {
+        if let Some(parts) = qualified_name_before(tokens, i - 1) {
+            return Some(parts);
+        }
+    }
+    // col = ANY($1)
+    if i >= 4
+        && tokens[i - 1].is_punct("(")
+        && (tokens[i - 2].is_keyword("any") || tokens[i - 2].is_keyword("all"))
+        && is_comparison(&tokens[i - 3])
+    {
+        if let Some(parts) = qualified_name_before(tokens, i - 3) {
+            return Some(parts);
+        }
+    }
+    // col IN ($1, $2)
+    let mut j = i;
+    while j >= 2 && tokens[j - 1].is_punct(",") && matches!(tokens[j - 2], Token::Param(_)) {
+        j -= 2;
+    }
+    if j >= 3 && tokens[j - 1].is_punct("(") && tokens[j - 2].is_keyword("in") {
+        if let Some(parts) = qualified_name_before(tokens, j - 2) {
+            return Some(parts);
+        }
+    }
+    // $1 = col
+    let j = skip_cast(tokens, i + 1);
+    if is_comparison(at(tokens, j)) {
+        if let Some((parts, end)) = qualified_name(tokens, j + 1) {
+            if !at(tokens, end).is_punct("(") {
+                return Some(parts);
+            }
+        }
+        // $1 = ANY(col), compared to the elements of an array column
+        if (at(tokens, j + 1).is_keyword("any") || at(tokens, j + 1).is_keyword("all"))
+            && at(tokens, j + 2).is_punct("(")
+        {
+            if let Some((parts, end)) = qualified_name(tokens, j + 3) {
+                if at(tokens, end).is_punct(")") {
+                    return Some(parts);
+                }
+            }
+        }
+    }
+    None
+}
+
+/// Columns assigned by `INSERT INTO table (columns) VALUES (params)`, or by the
+/// `INSERT (columns) VALUES (params)` action of a `MERGE INTO table` statement
+fn inserted_columns(tokens: &[Token], columns: &mut [Option<Vec<String>>]) {
+    let merge_target = tokens
+        .iter()
+        .position(|it| it.is_keyword("merge"))
+        .filter(|i| at(tokens, i + 1).is_keyword("into"))
+        .and_then(|i| qualified_name(tokens, i + 2));
+    for (i, token) in tokens.iter().enumerate() {
+        if !token.is_keyword("insert") {
+            continue;
+        }
+        let target = if at(tokens, i + 1).is_keyword("into") {
+            qualified_name(tokens, i + 2)
+        } else if at(tokens, i + 1).is_punct("(") {
+            merge_target.clone().map(|(table, _)| (table, i + 1))
+        } else {
+            None
+        };
+        let Some((mut table, mut i)) = target else {
+            continue;
+        };
+        if at(tokens, i).is_keyword("as") {
+            table = vec![tokens
+                .get(i + 1)
+                .and_then(Token::ident)
+                .unwrap_or_default()
+                .to_string()];
+            i += 2;
+        }
+        // Column list
+        if !at(tokens, i).is_punct("(") {
+            continue;
+        }
+        let mut cols = Vec::new();
+        i += 1;
+        while let Some(name) = tokens.get(i).and_then(Token::ident) {
+            let mut col = table.clone();
+            col.push(name.to_string());
+            cols.push(col);
+            i += 1;
+            if at(tokens, i).is_punct(",") {
+                i += 1;
+            }
+        }
+        if !at(tokens, i).is_punct(")") || !at(tokens, i + 1).is_keyword("values") {
+            continue;
+        }
+        i += 2;
+        // Value tuples
+        while at(tokens, i).is_punct("(") {
+            i += 1;
+            let mut depth = 0;
+            let mut col = 0;
+            let mut start = i;
+            while let Some(token) = tokens.get(i) {
+                let at_end = depth == 0 && (token.is_punct(",") || token.is_punct(")"));
+                if at_end {
+                    if let Token::Param(idx) = tokens[start] {
+                        if skip_cast(tokens, start + 1) == i {
+                            if let (Some(slot), Some(c)) =
+                                (columns.get_mut(idx.wrapping_sub(1)), cols.get(col))
+                            {
+                                slot.get_or_insert_with(|| c.clone());
+                            }
+                        }
+                    }
+                    col += 1;
+                    start = i + 1;
+                    if token.is_punct(")") {
+                        i += 1;
+                        break;
+                    }
+                } else if token.is_punct("(") || token.is_punct("[") {
+                    depth += 1;
+                } else if token.is_punct(")") || token.is_punct("]") {
+                    depth -= 1;
+                }
+                i += 1;
+            }
+            if at(tokens, i).is_punct(",") {
+                i += 1;
+            }
+        }
+    }
+}
+
+/// Finds, for each of the `nb_params` parameters of `sql`, the column it relates to if any.
+pub(crate) fn param_columns(sql: &str, nb_params: usize) -> Vec<Option<ParamColumn>> {
+    let tokens = tokenize(sql);
+    let tables = tables(&tokens);
+    let mut columns = vec![None; nb_params];
+    inserted_columns(&tokens, &mut columns);
+    for (i, token) in tokens.iter().enumerate() {
+        if let Token::Param(idx) = token {
+            if let Some(slot) = columns.get_mut(idx.wrapping_sub(1)) {
+                if slot.is_none() {
+                    *slot = compared_column(&tokens, i);
+                }
+            }
+        }
+    }
+    columns
+        .into_iter()
+        .map(|it| it.map(|parts| resolve(parts, &tables)))
+        .collect()
+}
+
+/// Finds the only parameter of `sql` matched with `= ANY($n)`, if it is used only once.
+pub(crate) fn any_param(sql: &str) -> Option<usize> {
+    let tokens = tokenize(sql);
+    let mut found = None;
+    for (i, token) in tokens.iter().enumerate() {
+        if let Token::Param(idx) = token {
+            let in_any = i >= 3
+                && tokens[i - 1].is_punct("(")
+                && tokens[i - 2].is_keyword("any")
+                && tokens[i - 3].is_punct("=")
+                && at(&tokens, skip_cast(&tokens, i + 1)).is_punct(")");
+            let uses = tokens.iter().filter(|t| **t == Token::Param(*idx)).count();
+            if in_any && uses == 1 {
+                if found.is_some() {
+                    return None;
+                }
+                found = Some(idx - 1);
+            }
+        }
+    }
+    found
+}
+
+/// Statements whose main table follows them directly, or after `FROM` or `INTO`
+const OPERATIONS: [&str; 5] = ["select", "insert", "update", "delete", "merge"];
+
+/// Finds the operation of `sql` (e.g. `SELECT`), skipping common table expressions,
+/// and the table it mainly works on.
+pub(crate) fn operation(sql: &str) -> (Option<String>, Option<String>) {
+    let tokens = tokenize(sql);
+    let mut depth = 0;
+    let mut main = None;
+    for (i, token) in tokens.iter().enumerate() {
+        if token.is_punct("(") {
+            depth += 1;
+        } else if token.is_punct(")") {
+            depth -= 1;
+        } else if let Some(kw) = OPERATIONS.iter().find(|kw| token.is_keyword(kw)) {
+            if depth == 0 {
+                main = Some((i, *kw));
+                break;
+            }
+        }
+    }
+    let Some((i, name)) = main else {
+        // Other statements are named by their first keyword (e.g. `CALL`)
+        let operation = match tokens.first() {
+            Some(Token::Ident {
+                name,
+                quoted: false,
+            }) => Some(name.to_uppercase()),
+            _ => None,
+        };
+        return (operation, None);
+    };
+    // The table follows `FROM` or `INTO` at the same depth
+    let mut depth = 0;
+    let mut j = i + 1;
+    if name != "update" {
+        while let Some(token) = tokens.get(j) {
+            if token.is_punct("(") {
+                depth += 1;
+            } else if token.is_punct(")") {
+                depth -= 1;
+            } else if depth == 0 && (token.is_keyword("from") || token.is_keyword("into")) {
+                break;
+            }
+            j += 1;
+        }
+        j += 1;
+    }
+    if at(&tokens, j).is_keyword("only") {
+        j += 1;
+    }
+    // Selecting from a function call is not selecting from a table
+    let table = qualified_name(&tokens, j)
+        .filter(|(_, end)| name != "select" || !at(&tokens, *end).is_punct("("))
+        .map(|(parts, _)| parts.join("."));
+    (Some(name.to_uppercase()), table)
+}
+
+/// A `/*+ ... */` plan hint comment, as read by pg_hint_plan
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Hint {
+    /// Text between `/*+` and `*/`
+    pub(crate) body: String,
+    /// Whether the comment starts the query, the only place pg_hint_plan reads hints from
+    pub(crate) leading: bool,
+}
+
+/// Hint comments of `sql`, outside of strings and quoted identifiers
+pub(crate) fn hints(sql: &str) -> Vec<Hint> {
+    let chars: Vec<char> = sql.chars().collect();
+    let blank = chars.iter().take_while(|c| c.is_whitespace()).count();
+    scan(sql)
+        .1
+        .into_iter()
+        .map(|range| Hint {
+            body: chars[range.start + 3..range.end.saturating_sub(2).max(range.start + 3)]
+                .iter()
+                .collect(),
+            leading: range.start == blank,
+        })
+        .collect()
+}
+
+/// Splits the hint comment starting `sql` off the statement following it
+pub(crate) fn split_hint(sql: &str) -> (Option<&str>, &str) {
+    let trimmed = sql.trim_start();
+    if !trimmed.starts_with("/*+") {
+        return (None, sql);
+    }
+    match trimmed.find("*/") {
+        Some(end) => (Some(&trimmed[..end + 2]), trimmed[end + 2..].trim_start()),
+        None => (None, sql),
+    }
+}
+
+/// Target of a `COPY` statement
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum CopyTarget {
+    /// `COPY table [(columns)] FROM STDIN`
+    In(CopyIn),
+    /// `COPY (query) TO STDOUT` or `COPY table [(columns)] TO STDOUT`, holding the copied query
+    Out(String),
+}
+
+/// Target of a `COPY table [(columns)] FROM STDIN` statement
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct CopyIn {
+    /// Table name, quoted where needed
+    pub(crate) table: String,
+    /// Listed columns, if any
+    pub(crate) columns: Option<Vec<String>>,
+}
+
+/// Quotes an identifier unless it can be written as is
+pub(crate) fn quote_ident(name: &str) -> String {
+    let plain = name.starts_with(|c: char| c.is_ascii_lowercase() || c == '_')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+        && !is_reserved(name);
+    if plain {
+        name.to_string()
+    } else {
+        format!("\"{}\"", name.replace('"', "\"\""))
+    }
+}
+
+/// Names of `sql`, quoted where needed and qualified by at most a schema, which could refer to
+/// relations or functions. Column references like `alias.column` are kept too.
+pub(crate) fn names(sql: &str) -> Vec<String> {
+    let tokens = tokenize(sql);
+    let mut names = Vec::new();
+    let mut i = 0;
+    while i < tokens.len() {
+        let Some((parts, end)) = qualified_name(&tokens, i) else {
+            i += 1;
+            continue;
+        };
+        i = end;
+        // `schema.table.column` refers to `schema.table`
+        let name = parts
+            .iter()
+            .take(2)
+            .map(|it| quote_ident(it))
+            .collect::<Vec<_>>()
+            .join(".");
+        if !names.contains(&name) {
+            names.push(name);
+        }
+    }
+    names
+}
+
+/// Casts every use of the parameters of `casts`, e.g. `(1, "public.item[]")` turns `$1` into
+/// `$1::public.item[]`.
+pub(crate) fn cast_params(sql: &str, casts: &[(usize, String)]) -> String {
+    let chars: Vec<char> = sql.chars().collect();
+    let mut out = String::with_capacity(sql.len());
+    let mut copied = 0;
+    for (token, end) in lex(sql) {
+        let Token::Param(idx) = token else {
+            continue;
+        };
+        if let Some((_, ty)) = casts.iter().find(|(it, _)| *it == idx) {
+            out.extend(&chars[copied..end]);
+            out.push_str("::");
+            out.push_str(ty);
+            copied = end;
+        }
+    }
+    out.extend(&chars[copied..]);
+    out
+}
+
+/// Wraps every use of the parameters `params` in a call to `function`, e.g. `[1]` and
+/// `"to_tsquery"` turn `$1` into `to_tsquery($1)`.
+pub(crate) fn wrap_params(sql: &str, params: &[usize], function: &str) -> String {
+    let chars: Vec<char> = sql.chars().collect();
+    let mut out = String::with_capacity(sql.len());
+    let mut copied = 0;
+    for (token, end) in lex(sql) {
+        let Token::Param(idx) = token else {
+            continue;
+        };
+        if params.contains(&idx) {
+            let start = chars[..end]
+                .iter()
+                .rposition(|c| *c == '$')
+                .expect("parameters start with `$`");
+            out.extend(&chars[copied..start]);
+            out.push_str(function);
+            out.push('(');
+            out.extend(&chars[start..end]);
+            out.push(')');
+            copied = end;
+        }
+    }
+    out.extend(&chars[copied..]);
+    out
+}
+
+/// Byte offsets of the start of each `;` separated statement of `sql`, skipping the blank
+/// space and comments before them
+pub(crate) fn statement_starts(sql: &str) -> Vec<usize> {
+    let bytes: Vec<usize> = sql
+        .char_indices()
+        .map(|(i, _)| i)
+        .chain(std::iter::once(sql.len()))
+        .collect();
+    let mut starts = Vec::new();
+    let mut segment = 0;
+    let mut pending = true;
+    for (token, end) in lex(sql) {
+        if pending {
+            pending = false;
+            let rest = &sql[bytes[segment]..];
+            let mut start = bytes[segment] + (rest.len() - rest.trim_start().len());
+            // Comments before a statement are kept out of it
+            while sql[start..].starts_with("--") || sql[start..].starts_with("/*") {
+                let skipped = if sql[start..].starts_with("--") {
+                    sql[start..].find('\n').map_or(sql.len(), |it| start + it)
+                } else {
+                    sql[start..]
+                        .find("*/")
+                        .map_or(sql.len(), |it| start + it + 2)
+                };
+                let rest = &sql[skipped..];
+                start = skipped + (rest.len() - rest.trim_start().len());
+            }
+            starts.push(start);
+        }
+        if token.is_punct(";") {
+            segment = end;
+            pending = true;
+        }
+    }
+    starts
+}
+
+/// Whether `sql` only holds complete statements, its last token being a `;` outside of any
+/// string or quoted identifier
+pub(crate) fn ends_statement(sql: &str) -> bool {
+    lex(sql).last().is_none_or(|(token, _)| token.is_punct(";"))
+}
+
+/// Parses a `COPY ... FROM STDIN` or `COPY ... TO STDOUT` statement, without options
+pub(crate) fn copy(sql: &str) -> Option<CopyTarget> {
+    let tokens = tokenize(sql);
+    if !at(&tokens, 0).is_keyword("copy") {
+        return None;
+    }
+    // Index right after `TO STDOUT` or `FROM STDIN` and their optional semicolon, which must end the statement
+    let ends = |i: usize, kw: &str, stream: &str| {
+        let mut end = i + 2;
+        if at(&tokens, end).is_punct(";") {
+            end += 1;
+        }
+        at(&tokens, i).is_keyword(kw)
+            && at(&tokens, i + 1).is_keyword(stream)
+            && end == tokens.len()
+    };
+    if at(&tokens, 1).is_punct("(") {
+        // The query is the text between the outer parentheses, the only ones after it being `TO STDOUT`
+        let mut depth = 0;
+        let close = tokens.iter().enumerate().skip(1).find_map(|(i, token)| {
+            if token.is_punct("(") {
+                depth += 1;
+            } else if token.is_punct(")") {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(i);
+                }
+            }
+            None
+        })?;
+        if !ends(close + 1, "to", "stdout") {
+            return None;
+        }
+        let (_, sql) = split_hint(sql);
+        let query = sql.get(sql.find('(')? + 1..sql.rfind(')')?)?.trim();
+        return Some(CopyTarget::Out(query.to_string()));
+    }
+    let (parts, mut i) = qualified_name(&tokens, 1)?;
+    let columns = if at(&tokens, i).is_punct("(") {
+        let mut columns = Vec::new();
+        i += 1;
+        while let Some(name) = tokens.get(i).and_then(Token::ident) {
+            columns.push(name.to_string());
+            i += 1;
+            if !at(&tokens, i).is_punct(",") {
+                break;
+            }
+            i += 1;
+        }
+        if !at(&tokens, i).is_punct(")") || columns.is_empty() {
+            return None;
+        }
+        i += 1;
+        Some(columns)
+    } else {
+        None
+    };
+    let table = parts
+        .iter()
+        .map(|it| quote_ident(it))
+        .collect::<Vec<_>>()
+        .join(".");
+    if ends(i, "from", "stdin") {
+        Some(CopyTarget::In(CopyIn { table, columns }))
+    } else if ends(i, "to", "stdout") {
+        let columns = columns.map_or("*".to_string(), |columns| {
+            columns
+                .iter()
+                .map(|it| quote_ident(it))
+                .collect::<Vec<_>>()
+                .join(", ")
+        });
+        Some(CopyTarget::Out(format!("SELECT {columns} FROM {table}")))
+    } else {
+        None
+    }
+}
+
+/// Routine called by a `CALL` statement, or selected from by a query
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Call {
+    pub(crate) schema: Option<String>,
+    pub(crate) name: String,
+    /// Arguments, in order
+    pub(crate) args: Vec<CallArg>,
+}
+
+/// Argument of a routine call
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct CallArg {
+    /// Name of the argument, when passed in named notation (`name => value`)
+    pub(crate) name: Option<String>,
+    /// Index of the parameter passed as is, possibly cast (`$1::int4`)
+    pub(crate) param: Option<usize>,
+}
+
+/// Parses a `CALL procedure(args)` statement
+pub(crate) fn call(sql: &str) -> Option<Call> {
+    let tokens = tokenize(sql);
+    if !at(&tokens, 0).is_keyword("call") {
+        return None;
+    }
+    routine_call(&tokens, 1).map(|(call, _)| call)
+}
+
+/// Parses a `SELECT * FROM function(args)` query, selecting all the columns of a single function
+pub(crate) fn selected_function(sql: &str) -> Option<Call> {
+    let tokens = tokenize(sql);
+    if !(at(&tokens, 0).is_keyword("select")
+        && at(&tokens, 1).is_punct("*")
+        && at(&tokens, 2).is_keyword("from"))
+    {
+        return None;
+    }
+    let (call, mut i) = routine_call(&tokens, 3)?;
+    if at(&tokens, i).is_keyword("as") {
+        i += 1;
+    }
+    if tokens.get(i).and_then(Token::ident).is_some() {
+        i += 1;
+    }
+    // Joined or filtered through other tables, the columns could come from elsewhere
+    let ends = tokens.get(i).is_none_or(|token| {
+        token.is_punct(";")
+            || ["where", "order", "limit", "offset", "fetch"]
+                .iter()
+                .any(|kw| token.is_keyword(kw))
+    });
+    ends.then_some(call)
+}
+
+/// Parses the call of a possibly qualified routine starting at `i`, returning it and the index
+/// after its arguments
+fn routine_call(tokens: &[Token], i: usize) -> Option<(Call, usize)> {
+    let (mut parts, mut i) = qualified_name(tokens, i)?;
+    if !at(tokens, i).is_punct("(") || parts.len() > 2 {
+        return None;
+    }
+    let name = parts.pop()?;
+    let schema = parts.pop();
+    // Split the arguments at the commas between the outer parentheses
+    let mut args = Vec::new();
+    let mut depth = 0;
+    i += 1;
+    let mut start = i;
+    while let Some(token) = tokens.get(i) {
+        if token.is_punct("(") || token.is_punct("[") {
+            depth += 1;
+        } else if (token.is_punct(")") || token.is_punct(",")) && depth == 0 {
+            if i > start {
+                args.push(call_arg(&tokens[start..i]));
+            }
+            if token.is_punct(")") {
+                return Some((Call { schema, name, args }, i + 1));
+            }
+            start = i + 1;
+        } else if token.is_punct(")") || token.is_punct("]") {
+            depth -= 1;
+        }
+        i += 1;
+    }
+    None
+}
+
+fn call_arg(tokens: &[Token]) -> CallArg {
+    let (name, value) = match tokens {
+        [Token::Ident { name, .. }, arrow, value @ ..] if arrow.is_punct("=>") => {
+            (Some(name.clone()), value)
+        }
+        _ => (None, tokens),
+    };
+    let param = match value.first() {
+        Some(Token::Param(idx)) if skip_cast(value, 1) == value.len() => idx.checked_sub(1),
+        _ => None,
+    };
+    CallArg { name, param }
+}
+
+/// Finds the output columns of `sql` that are grouped by `ROLLUP`, `CUBE` or `GROUPING SETS`.
+/// Those are NULL in the rows aggregating over them, even when coming from a NOT NULL column.
+pub(crate) fn rolled_up_columns(sql: &str) -> Vec<String> {
+    let tokens = tokenize(sql);
+    // Clauses of the main query, at depth 0
+    let mut depth = 0;
+    let mut select = None;
+    let mut from = None;
+    let mut group_by = None;
+    for (i, token) in tokens.iter().enumerate() {
+        if token.is_punct("(") {
+            depth += 1;
+        } else if token.is_punct(")") {
+            depth -= 1;
+        } else if depth == 0 {
+            if token.is_keyword("select") && select.is_none() {
+                select = Some(i + 1);
+            } else if token.is_keyword("from") && select.is_some() && from.is_none() {
+                from = Some(i);
+            } else if token.is_keyword("group") && at(&tokens, i + 1).is_keyword("by") {
+                group_by = Some(i + 2);
+                break;
+            }
+        }
+    }
+    let (Some(select), Some(from), Some(group_by)) = (select, from, group_by) else {
+        return Vec::new();
+    };
+    // Columns listed in grouping sets
+    let mut rolled_up = Vec::new();
+    let mut i = group_by;
+    while let Some(token) = tokens.get(i) {
+        let start = if token.is_keyword("rollup") || token.is_keyword("cube") {
+            i + 1
+        } else if token.is_keyword("grouping") && at(&tokens, i + 1).is_keyword("sets") {
+            i + 2
+        } else if [
+            "having",
+            "order",
+            "limit",
+            "offset",
+            "window",
+            "union",
+            "intersect",
+            "except",
+        ]
+        .iter()
+        .any(|kw| token.is_keyword(kw))
+        {
+            break;
+        } else {
+            i += 1;
+            continue;
+        };
+        if !at(&tokens, start).is_punct("(") {
+            i = start;
+            continue;
+        }
+        let mut depth = 0;
+        i = start;
+        while let Some(token) = tokens.get(i) {
+            if token.is_punct("(") {
+                depth += 1;
+            } else if token.is_punct(")") {
+                depth -= 1;
+                if depth == 0 {
+                    break;
+                }
+            } else if let Some((parts, end)) = qualified_name(&tokens, i) {
+                if !at(&tokens, end).is_punct("(") {
+                    rolled_up.push(parts.last().unwrap().clone());
+                }
+                i = end;
+                continue;
+            }
+            i += 1;
+        }
+    }
+    // Select list items that are a plain grouped column, named by their alias or column
+    let mut columns = Vec::new();
+    let mut item = Vec::new();
+    let mut depth = 0;
+    for token in &tokens[select..=from] {
+        let at_end = depth == 0 && (token.is_punct(",") || token.is_keyword("from"));
+        if !at_end {
+            if token.is_punct("(") {
+                depth += 1;
+            } else if token.is_punct(")") {
+                depth -= 1;
+            }
+            item.push(token.clone());
+            continue;
+        }
+        if let Some((parts, end)) = qualified_name(&item, 0) {
+            let alias = match &item[end..] {
+                [] => parts.last().map(String::as_str),
+                [alias] => alias.ident(),
+                [as_, alias] if as_.is_keyword("as") => alias.ident(),
+                _ => None,
+            };
+            if let Some(alias) = alias {
+                if rolled_up.contains(parts.last().unwrap()) {
+                    columns.push(alias.to_string());
+                }
+            }
+        }
+        item.clear();
+    }
+    columns
+}
+
+/// Clauses ending the `FROM` clause of a query
+const FROM_END: [&str; 13] = [
+    "where",
+    "group",
+    "having",
+    "window",
+    "order",
+    "limit",
+    "offset",
+    "fetch",
+    "for",
+    "union",
+    "intersect",
+    "except",
+    "returning",
+];
+
+/// A table, subquery or function call of the `FROM` clause of a query
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct FromItem {
+    schema: Option<String>,
+    /// Name of the table, unless a subquery or a function call
+    name: Option<String>,
+    alias: Option<String>,
+    /// Is it on the nullable side of an outer join
+    is_nullable: bool,
+}
+
+impl FromItem {
+    /// Is it the item qualifying the columns named `qualifier.column`
+    fn is_named(&self, qualifier: &str) -> bool {
+        match &self.alias {
+            Some(alias) => alias == qualifier,
+            None => self.name.as_deref() == Some(qualifier),
+        }
+    }
+}
+
+/// Kind of the join of an item of a `FROM` clause with the previous ones
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Join {
+    Inner,
+    Left,
+    Right,
+    Full,
+}
+
+/// Outer joins of the main query of a statement. They null the columns of the items on their
+/// nullable side, e.g. those of `b` in `a LEFT JOIN b`, in the rows where none of them match.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct OuterJoins {
+    items: Vec<FromItem>,
+    /// Qualifiers of the output columns selecting a qualified column, e.g. `b` for `b.name AS title`
+    qualifiers: Vec<(String, String)>,
+}
+
+impl OuterJoins {
+    /// Is the output column `column` nulled by an outer join. `origin` is the schema and the
+    /// name of the table it comes straight from, if any.
+    pub(crate) fn is_nullable(&self, column: &str, origin: Option<(&str, &str)>) -> bool {
+        let mut qualifiers = self.qualifiers.iter().filter(|(name, _)| name == column);
+        if let (Some((_, qualifier)), None) = (qualifiers.next(), qualifiers.next()) {
+            if let Some(item) = self.items.iter().find(|it| it.is_named(qualifier)) {
+                return item.is_nullable;
+            }
+        }
+        // Otherwise by its table, unless also read on the non-nullable side of a join
+        let Some((schema, table)) = origin else {
+            return false;
+        };
+        let mut items = self
+            .items
+            .iter()
+            .filter(|it| {
+                it.name.as_deref() == Some(table)
+                    && it.schema.as_deref().is_none_or(|it| it == schema)
+            })
+            .peekable();
+        items.peek().is_some() && items.all(|it| it.is_nullable)
+    }
+}
+
+/// Finds the outer joins of the `FROM` clause of the main query of `sql`, if it has any
+pub(crate) fn outer_joins(sql: &str) -> Option<OuterJoins> {
+    let tokens = tokenize(sql);
+    // Clauses of the main query, at depth 0
+    let mut depth = 0;
+    let mut select = None;
+    let mut from = None;
+    let mut end = tokens.len();
+    for (i, token) in tokens.iter().enumerate() {
+        if token.is_punct("(") {
+            depth += 1;
+        } else if token.is_punct(")") {
+            depth -= 1;
+        } else if depth == 0 {
+            if token.is_keyword("select") && select.is_none() {
+                select = Some(i + 1);
+            } else if token.is_keyword("from") && select.is_some() && from.is_none() {
+                from = Some(i);
+            } else if from.is_some() && FROM_END.iter().any(|kw| token.is_keyword(kw)) {
+                end = i;
+                break;
+            }
+        }
+    }
+    let (select, from) = (select?, from?);
+    let items = from_items(&tokens[from + 1..end]);
+    if !items.iter().any(|it| it.is_nullable) {
+        return None;
+    }
+    // Select list items that are a qualified column, named by their alias or column
+    let mut qualifiers = Vec::new();
+    let mut start = select;
+    let mut depth = 0;
+    for (i, token) in tokens.iter().enumerate().take(from + 1).skip(select) {
+        if token.is_punct("(") {
+            depth += 1;
+        } else if token.is_punct(")") {
+            depth -= 1;
+        }
+        if depth != 0 || !(token.is_punct(",") || i == from) {
+            continue;
+        }
+        let mut item = &tokens[start..i];
+        start = i + 1;
+        if at(item, 0).is_keyword("distinct") || at(item, 0).is_keyword("all") {
+            item = &item[1..];
+        }
+        let Some((parts, end)) = qualified_name(item, 0).filter(|(it, _)| it.len() > 1) else {
+            continue;
+        };
+        let alias = match &item[end..] {
+            [] => parts.last().map(String::as_str),
+            [alias] => alias.ident(),
+            [as_, alias] if as_.is_keyword("as") => alias.ident(),
+            _ => None,
+        };
+        if let Some(alias) = alias {
+            qualifiers.push((alias.to_string(), parts[parts.len() - 2].clone()));
+        }
+    }
+    Some(OuterJoins { items, qualifiers })
+}
+
+/// Index of the parenthesis closing the one at `i`
+fn closing(tokens: &[Token], i: usize) -> usize {
+    let mut depth = 0;
+    for (j, token) in tokens.iter().enumerate().skip(i) {
+        if token.is_punct("(") {
+            depth += 1;
+        } else if token.is_punct(")") {
+            depth -= 1;
+            if depth == 0 {
+                return j;
+            }
+        }
+    }
+    tokens.len()
+}
+
+/// Parses the optional alias of a `FROM` item at `i`, skipping the column aliases following it
+fn from_alias(tokens: &[Token], i: &mut usize) -> Option<String> {
+    if at(tokens, *i).is_keyword("as") {
+        *i += 1;
+    }
+    let alias = tokens.get(*i).and_then(Token::ident).map(str::to_string);
+    if alias.is_some() {
+        *i += 1;
+    }
+    if at(tokens, *i).is_punct("(") {
+        *i = closing(tokens, *i) + 1;
+    }
+    alias
+}
+
+/// Parses the items of a `FROM` clause, marking those on the nullable side of outer joins
+fn from_items(tokens: &[Token]) -> Vec<FromItem> {
+    let mut items: Vec<FromItem> = Vec::new();
+    // First item since the last comma, as joins bind tighter
+    let mut group = 0;
+    let mut join = None;
+    let mut i = 0;
+    while i < tokens.len() {
+        if at(tokens, i).is_keyword("lateral") || at(tokens, i).is_keyword("only") {
+            i += 1;
+        }
+        let start = items.len();
+        if at(tokens, i).is_punct("(") {
+            let end = closing(tokens, i);
+            let inner = &tokens[i + 1..end];
+            i = end + 1;
+            let alias = from_alias(tokens, &mut i);
+            if ["select", "values", "with", "table"]
+                .iter()
+                .any(|kw| at(inner, 0).is_keyword(kw))
+            {
+                items.push(FromItem {
+                    schema: None,
+                    name: None,
+                    alias,
+                    is_nullable: false,
+                });
+            } else {
+                // Items joined in parentheses
+                items.extend(from_items(inner));
+            }
+        } else if let Some((mut parts, end)) = qualified_name(tokens, i) {
+            i = end;
+            let is_call = at(tokens, i).is_punct("(");
+            if is_call {
+                i = closing(tokens, i) + 1;
+            }
+            let name = parts.pop().unwrap();
+            items.push(FromItem {
+                schema: parts.pop().filter(|_| !is_call),
+                name: (!is_call).then_some(name),
+                alias: from_alias(tokens, &mut i),
+                is_nullable: false,
+            });
+        }
+        let nulled = match join {
+            Some(Join::Left) => start..items.len(),
+            Some(Join::Right) => group..start,
+            Some(Join::Full) => group..items.len(),
+            _ => 0..0,
+        };
+        for item in &mut items[nulled] {
+            item.is_nullable = true;
+        }
+        // Skips the join condition, up to the next item
+        join = None;
+        let mut depth = 0;
+        while let Some(token) = tokens.get(i) {
+            i += 1;
+            if token.is_punct("(") {
+                depth += 1;
+            } else if token.is_punct(")") {
+                depth -= 1;
+            } else if depth == 0 && token.is_punct(",") {
+                group = items.len();
+                break;
+            } else if depth == 0 && token.is_keyword("join") {
+                let mut kind = i - 1;
+                if kind > 0 && tokens[kind - 1].is_keyword("outer") {
+                    kind -= 1;
+                }
+                let kind = kind.checked_sub(1).map(|it| &tokens[it]);
+                join = Some(match kind {
+                    Some(kind) if kind.is_keyword("left") => Join::Left,
+                    Some(kind) if kind.is_keyword("right") => Join::Right,
+                    Some(kind) if kind.is_keyword("full") => Join::Full,
+                    _ => Join::Inner,
+                });
+                break;
+            }
+        }
+    }
+    items
+}
+
+/// Output column of a query, as listed in its `SELECT` or `RETURNING` clause
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum OutputColumn {
+    /// `*` or `alias.*`, selecting all the columns of its relations
+    Star,
+    /// Column named by its alias, the column it selects or the function it calls
+    Named(String),
+    /// Other expression, named by Postgres
+    Unnamed,
+}
+
+/// Output columns of the main query of `sql`: those of its `RETURNING` clause if it has one,
+/// else those of its `SELECT` list. `None` if it returns no rows.
+pub(crate) fn output_columns(sql: &str) -> Option<Vec<OutputColumn>> {
+    let tokens = tokenize(sql);
+    let mut depth = 0;
+    let mut list = None;
+    let mut operation = None;
+    for (i, token) in tokens.iter().enumerate() {
+        if token.is_punct("(") {
+            depth += 1;
+        } else if token.is_punct(")") {
+            depth -= 1;
+        } else if depth == 0 && token.is_punct(";") {
+            break;
+        } else if depth == 0 && token.is_keyword("returning") {
+            list = Some(i + 1);
+        } else if let Some(kw) = OPERATIONS.iter().find(|kw| token.is_keyword(kw)) {
+            if depth == 0 && operation.is_none() {
+                operation = Some(*kw);
+                if *kw == "select" {
+                    list = Some(i + 1);
+                }
+            }
+        }
+    }
+    let mut i = list?;
+    if at(&tokens, i).is_keyword("all") {
+        i += 1;
+    } else if at(&tokens, i).is_keyword("distinct") {
+        i += 1;
+        if at(&tokens, i).is_keyword("on") {
+            // Skips the expressions of `DISTINCT ON (...)`
+            let mut depth = 0;
+            while let Some(token) = tokens.get(i) {
+                i += 1;
+                if token.is_punct("(") {
+                    depth += 1;
+                } else if token.is_punct(")") {
+                    depth -= 1;
+                    if depth == 0 {
+                        break;
+                    }
+                }
+            }
+        }
+    }
+    let mut columns = Vec::new();
+    let mut item: Vec<Token> = Vec::new();
+    let mut depth = 0;
+    loop {
+        let token = tokens.get(i);
+        let at_end = depth == 0
+            && token.is_none_or(|token| {
+                token.is_punct(",")
+                    || token.is_punct(";")
+                    || token.is_keyword("from")
+                    || token.is_keyword("into")
+                    || FROM_END.iter().any(|kw| token.is_keyword(kw))
+            });
+        if !at_end {
+            let token = token.expect("the list continues");
+            if token.is_punct("(") {
+                depth += 1;
+            } else if token.is_punct(")") {
+                depth -= 1;
+            }
+            item.push(token.clone());
+            i += 1;
+            continue;
+        }
+        if !item.is_empty() {
+            columns.push(output_column(&item));
+        }
+        item.clear();
+        if !token.is_some_and(|it| it.is_punct(",")) {
+            break;
+        }
+        i += 1;
+    }
+    Some(columns)
+}
+
+/// Output column of the item `tokens` of a `SELECT` or `RETURNING` list
+fn output_column(tokens: &[Token]) -> OutputColumn {
+    match tokens {
+        [.., star] if star.is_punct("*") => return OutputColumn::Star,
+        [.., as_, alias] if as_.is_keyword("as") => {
+            return alias.ident().map_or(OutputColumn::Unnamed, |it| {
+                OutputColumn::Named(it.to_string())
+            })
+        }
+        _ => {}
+    }
+    // A cast keeps the name of its expression
+    let mut depth = 0;
+    let cast = tokens.iter().position(|token| {
+        if token.is_punct("(") {
+            depth += 1;
+        } else if token.is_punct(")") {
+            depth -= 1;
+        }
+        depth == 0 && token.is_punct("::")
+    });
+    if let Some(cast) = cast.filter(|it| *it > 0) {
+        return output_column(&tokens[..cast]);
+    }
+    if let Some((parts, end)) = qualified_name(tokens, 0) {
+        match &tokens[end..] {
+            [] => return OutputColumn::Named(parts.last().unwrap().clone()),
+            [open, .., close] if open.is_punct("(") && close.is_punct(")") => {
+                return OutputColumn::Named(parts.last().unwrap().clone())
+            }
+            _ => {}
+        }
+    }
+    // An alias can follow its expression without `AS`, unlike an operand of an operator
+    match tokens {
+        [.., before, alias]
+            if matches!(before, Token::Other | Token::Param(_))
+                || before.is_punct(")")
+                || before.is_punct("]")
+                || before.is_keyword("end")
+                || before.ident().is_some() =>
+        {
+            alias.ident().map_or(OutputColumn::Unnamed, |it| {
+                OutputColumn::Named(it.to_string())
+            })
+        }
+        _ => OutputColumn::Unnamed,
+    }
+}
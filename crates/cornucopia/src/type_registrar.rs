@@ -1,11 +1,13 @@
-use std::rc::Rc;
+use std::{borrow::Cow, collections::BTreeMap, rc::Rc};
 
 use heck::ToUpperCamelCase;
 use indexmap::{map::Entry, IndexMap};
 use postgres_types::{Kind, Type};
+use serde::{Deserialize, Serialize};
 
 use crate::{
     codegen::{idx_char, GenCtx},
+    config::{BitType, Codec, IntervalType, MoneyType},
     parser::Span,
     read_queries::ModuleInfo,
     utils::SchemaKey,
@@ -14,39 +16,105 @@ use crate::{
 use self::error::Error;
 
 /// A struct containing a postgres type and its Rust-equivalent.
-#[derive(PartialEq, Eq, Debug, Clone)]
+#[derive(PartialEq, Eq, Debug, Clone, Serialize, Deserialize)]
 pub(crate) enum CornucopiaType {
     Simple {
+        #[serde(with = "crate::serialize::pg_type")]
         pg_ty: Type,
-        rust_name: &'static str,
+        rust_name: Cow<'static, str>,
         is_copy: bool,
     },
     Array {
         inner: Rc<CornucopiaType>,
     },
+    /// An array column declared with two dimensions, e.g. `int[][]`, decoded as the `Vec` of its
+    /// rows. The type of arrays doesn't record their dimensions, only the columns declaring them.
+    Matrix {
+        #[serde(with = "crate::serialize::pg_type")]
+        pg_ty: Type,
+        inner: Rc<CornucopiaType>,
+    },
     Domain {
+        #[serde(with = "crate::serialize::pg_type")]
+        pg_ty: Type,
+        inner: Rc<CornucopiaType>,
+    },
+    /// A range type, e.g. `int4range`, decoded as a `Range` of its bounds' type
+    Range {
+        #[serde(with = "crate::serialize::pg_type")]
+        pg_ty: Type,
+        inner: Rc<CornucopiaType>,
+    },
+    /// A multirange type, e.g. `int4multirange`, decoded as the `Range`s of its bounds' type
+    Multirange {
+        #[serde(with = "crate::serialize::pg_type")]
         pg_ty: Type,
         inner: Rc<CornucopiaType>,
     },
     Custom {
+        #[serde(with = "crate::serialize::pg_type")]
         pg_ty: Type,
         struct_name: String,
         is_copy: bool,
         is_params: bool,
     },
+    /// A generated wrapper around the type of configured columns
+    Newtype {
+        schema: String,
+        struct_name: String,
+        inner: Rc<CornucopiaType>,
+    },
+    /// A text or `bytea` column configured to be held inline in owned rows, as an
+    /// `arrayvec::ArrayString` or a byte array of `capacity`
+    Inline {
+        inner: Rc<CornucopiaType>,
+        capacity: usize,
+    },
+    /// A user type declared by a field annotation, e.g. `price: crate::Money`, or by a codec,
+    /// decoded and encoded with its own `FromSql` and `ToSql` implementations
+    Mapped {
+        rust_path: String,
+        /// Type borrowing the values from the rows, declared by a codec
+        borrowed: Option<String>,
+        inner: Rc<CornucopiaType>,
+    },
+}
+
+/// Kind of the parameter values compared by the checks of the `validate` attribute
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum CheckedKind {
+    Text,
+    Bytes,
+    Integer,
+    Float,
 }
 
 impl CornucopiaType {
     /// Is this type need a generic lifetime
     pub fn is_ref(&self) -> bool {
         match self {
-            CornucopiaType::Simple { pg_ty, .. } => match *pg_ty {
-                Type::BYTEA | Type::TEXT | Type::VARCHAR | Type::JSON | Type::JSONB => false,
+            CornucopiaType::Simple {
+                pg_ty, rust_name, ..
+            } => match *pg_ty {
+                Type::BYTEA | Type::JSON | Type::JSONB => false,
+                _ if rust_name == "String"
+                    || is_hstore(pg_ty)
+                    || is_text_search(pg_ty)
+                    || is_geometric(pg_ty)
+                    || is_client_decoded(pg_ty) =>
+                {
+                    false
+                }
                 _ => !self.is_copy(),
             },
-            CornucopiaType::Domain { inner, .. } | CornucopiaType::Array { inner } => {
-                inner.is_ref()
-            }
+            CornucopiaType::Domain { inner, .. }
+            | CornucopiaType::Array { inner }
+            | CornucopiaType::Matrix { inner, .. }
+            | CornucopiaType::Inline { inner, .. } => inner.is_ref(),
+            CornucopiaType::Mapped { borrowed, .. } => borrowed.is_some(),
+            CornucopiaType::Newtype { .. } => false,
+            // Bound as a slice of its ranges
+            CornucopiaType::Multirange { .. } => true,
             _ => !self.is_copy(),
         }
     }
@@ -57,21 +125,94 @@ impl CornucopiaType {
             CornucopiaType::Simple { is_copy, .. } | CornucopiaType::Custom { is_copy, .. } => {
                 *is_copy
             }
-            CornucopiaType::Domain { inner, .. } => inner.is_copy(),
-            CornucopiaType::Array { .. } => false,
+            CornucopiaType::Domain { inner, .. }
+            | CornucopiaType::Range { inner, .. }
+            | CornucopiaType::Newtype { inner, .. } => inner.is_copy(),
+            // User types are only assumed to be `Clone`, and inline values are borrowed as slices
+            CornucopiaType::Array { .. }
+            | CornucopiaType::Matrix { .. }
+            | CornucopiaType::Multirange { .. }
+            | CornucopiaType::Inline { .. }
+            | CornucopiaType::Mapped { .. } => false,
+        }
+    }
+
+    /// Does this type implement `Eq`, `Hash` and `Ord`
+    pub fn is_ord(&self) -> bool {
+        match self {
+            CornucopiaType::Simple { pg_ty, .. } => {
+                !matches!(
+                    *pg_ty,
+                    Type::FLOAT4 | Type::FLOAT8 | Type::JSON | Type::JSONB
+                ) && !is_hstore(pg_ty)
+                    && !is_geometric(pg_ty)
+            }
+            CornucopiaType::Array { inner }
+            | CornucopiaType::Matrix { inner, .. }
+            | CornucopiaType::Domain { inner, .. }
+            | CornucopiaType::Newtype { inner, .. }
+            | CornucopiaType::Inline { inner, .. } => inner.is_ord(),
+            // Ranges are only partially ordered
+            CornucopiaType::Range { .. }
+            | CornucopiaType::Multirange { .. }
+            | CornucopiaType::Custom { .. }
+            | CornucopiaType::Mapped { .. } => false,
         }
     }
 
     /// Can this used in parameters as it is
     pub fn is_params(&self) -> bool {
         match self {
-            CornucopiaType::Simple { .. } => true,
-            CornucopiaType::Array { .. } => false,
-            CornucopiaType::Domain { inner, .. } => inner.is_params(),
+            CornucopiaType::Simple { .. }
+            | CornucopiaType::Newtype { .. }
+            | CornucopiaType::Mapped { .. } => true,
+            CornucopiaType::Array { .. } | CornucopiaType::Matrix { .. } => false,
+            CornucopiaType::Domain { inner, .. }
+            | CornucopiaType::Range { inner, .. }
+            | CornucopiaType::Multirange { inner, .. }
+            | CornucopiaType::Inline { inner, .. } => inner.is_params(),
             CornucopiaType::Custom { is_params, .. } => *is_params,
         }
     }
 
+    /// Is this type decoded by items of this fork's client crates, missing from the upstream ones
+    pub(crate) fn needs_fork_client(&self) -> bool {
+        match self {
+            CornucopiaType::Simple {
+                pg_ty, rust_name, ..
+            } => {
+                is_hstore(pg_ty)
+                    || is_text_search(pg_ty)
+                    || is_geometric(pg_ty)
+                    || (is_client_decoded(pg_ty) && rust_name != "bit_vec::BitVec")
+            }
+            CornucopiaType::Range { .. }
+            | CornucopiaType::Multirange { .. }
+            | CornucopiaType::Matrix { .. } => true,
+            CornucopiaType::Mapped { inner, .. } => *inner.pg_ty() == Type::RECORD,
+            _ => false,
+        }
+    }
+
+    /// Kind of the values of this parameter type, if the `validate` attribute can check them
+    pub(crate) fn checked_kind(&self) -> Option<CheckedKind> {
+        match self {
+            CornucopiaType::Simple {
+                pg_ty, rust_name, ..
+            } => match *pg_ty {
+                Type::BYTEA => Some(CheckedKind::Bytes),
+                Type::INT2 | Type::INT4 | Type::INT8 => Some(CheckedKind::Integer),
+                Type::FLOAT4 | Type::FLOAT8 => Some(CheckedKind::Float),
+                _ if rust_name == "String" => Some(CheckedKind::Text),
+                _ => None,
+            },
+            CornucopiaType::Domain { inner, .. } | CornucopiaType::Inline { inner, .. } => {
+                inner.checked_kind()
+            }
+            _ => None,
+        }
+    }
+
     /// Wrap type to escape domains in parameters
     pub(crate) fn sql_wrapped(&self, name: &str, ctx: &GenCtx) -> String {
         let client_name = ctx.client_name();
@@ -91,6 +232,9 @@ impl CornucopiaType {
                 }
                 _ => name.to_string(),
             },
+            CornucopiaType::Multirange { .. } => {
+                format!("&{client_name}::private::Multirange({name})")
+            }
             _ => name.to_string(),
         }
     }
@@ -110,6 +254,10 @@ impl CornucopiaType {
                 }
                 _ => self.param_ty(false, ctx),
             },
+            CornucopiaType::Multirange { inner, .. } => format!(
+                "{client_name}::private::Multirange::<&[{client_name}::Range<{}>]>",
+                inner.param_ty(false, ctx)
+            ),
             _ => self.param_ty(false, ctx),
         }
     }
@@ -119,8 +267,14 @@ impl CornucopiaType {
         match self {
             CornucopiaType::Simple { pg_ty, .. }
             | CornucopiaType::Custom { pg_ty, .. }
-            | CornucopiaType::Domain { pg_ty, .. } => pg_ty,
-            CornucopiaType::Array { inner } => inner.pg_ty(),
+            | CornucopiaType::Domain { pg_ty, .. }
+            | CornucopiaType::Range { pg_ty, .. }
+            | CornucopiaType::Multirange { pg_ty, .. }
+            | CornucopiaType::Matrix { pg_ty, .. } => pg_ty,
+            CornucopiaType::Array { inner }
+            | CornucopiaType::Newtype { inner, .. }
+            | CornucopiaType::Inline { inner, .. }
+            | CornucopiaType::Mapped { inner, .. } => inner.pg_ty(),
         }
     }
 
@@ -144,11 +298,36 @@ impl CornucopiaType {
             CornucopiaType::Simple { pg_ty, .. } if matches!(*pg_ty, Type::JSON | Type::JSONB) => {
                 format!("serde_json::from_str({name}.0.get()).unwrap()")
             }
+            CornucopiaType::Simple { pg_ty, .. } if is_hstore(pg_ty) => {
+                format!("{name}.map(|(k, v)| (k.to_string(), v.map(str::to_string))).collect()")
+            }
             CornucopiaType::Array { inner, .. } => {
                 let inner = inner.owning_call("v", is_inner_nullable, false);
                 format!("{name}.map(|v| {inner}).collect()")
             }
+            CornucopiaType::Matrix { inner, .. } => {
+                let inner = inner.owning_call("v", is_inner_nullable, false);
+                format!("{name}.map(|v| v.into_iter().map(|v| {inner}).collect()).collect()")
+            }
             CornucopiaType::Domain { inner, .. } => inner.owning_call(name, is_nullable, false),
+            CornucopiaType::Range { inner, .. } => {
+                let inner = inner.owning_call("v", false, false);
+                format!("{name}.map(|v| {inner})")
+            }
+            CornucopiaType::Multirange { inner, .. } => {
+                let inner = inner.owning_call("v", false, false);
+                format!("{name}.map(|v| v.map(|v| {inner})).collect()")
+            }
+            CornucopiaType::Mapped { inner, .. } if *inner.pg_ty() == Type::RECORD => {
+                format!("{name}.0")
+            }
+            CornucopiaType::Inline { inner, .. } => {
+                if *inner.pg_ty() == Type::BYTEA {
+                    format!("{name}.try_into().expect(\"value doesn't fit its inline column\")")
+                } else {
+                    format!("arrayvec::ArrayString::from({name}).expect(\"value doesn't fit its inline column\")")
+                }
+            }
             _ => {
                 format!("{name}.into()")
             }
@@ -158,7 +337,16 @@ impl CornucopiaType {
     /// Corresponding owned type
     pub(crate) fn own_ty(&self, is_inner_nullable: bool, ctx: &GenCtx) -> String {
         match self {
-            CornucopiaType::Simple { rust_name, .. } => (*rust_name).to_string(),
+            CornucopiaType::Simple {
+                pg_ty, rust_name, ..
+            } if is_text_search(pg_ty)
+                || is_geometric(pg_ty)
+                || (is_client_decoded(pg_ty)
+                    && matches!(&**rust_name, "Money" | "Interval" | "BitString")) =>
+            {
+                format!("{}::{rust_name}", ctx.client_name())
+            }
+            CornucopiaType::Simple { rust_name, .. } => rust_name.to_string(),
             CornucopiaType::Array { inner, .. } => {
                 let own_inner = inner.own_ty(false, ctx);
                 if is_inner_nullable {
@@ -167,10 +355,39 @@ impl CornucopiaType {
                     format!("Vec<{own_inner}>")
                 }
             }
+            CornucopiaType::Matrix { inner, .. } => {
+                let own_inner = inner.own_ty(false, ctx);
+                if is_inner_nullable {
+                    format!("Vec<Vec<Option<{own_inner}>>>")
+                } else {
+                    format!("Vec<Vec<{own_inner}>>")
+                }
+            }
             CornucopiaType::Domain { inner, .. } => inner.own_ty(false, ctx),
+            CornucopiaType::Range { inner, .. } => {
+                let client_name = ctx.client_name();
+                format!("{client_name}::Range<{}>", inner.own_ty(false, ctx))
+            }
+            CornucopiaType::Multirange { inner, .. } => {
+                let client_name = ctx.client_name();
+                format!("Vec<{client_name}::Range<{}>>", inner.own_ty(false, ctx))
+            }
             CornucopiaType::Custom {
                 struct_name, pg_ty, ..
             } => custom_ty_path(pg_ty.schema(), struct_name, ctx),
+            CornucopiaType::Newtype {
+                schema,
+                struct_name,
+                ..
+            } => custom_ty_path(schema, struct_name, ctx),
+            CornucopiaType::Inline { inner, capacity } => {
+                if *inner.pg_ty() == Type::BYTEA {
+                    format!("[u8; {capacity}]")
+                } else {
+                    format!("arrayvec::ArrayString<{capacity}>")
+                }
+            }
+            CornucopiaType::Mapped { rust_path, .. } => rust_path.clone(),
         }
     }
 
@@ -183,12 +400,14 @@ impl CornucopiaType {
     ) -> String {
         let client_name = ctx.client_name();
         match self {
-            CornucopiaType::Simple { pg_ty, .. } => match *pg_ty {
+            CornucopiaType::Simple {
+                pg_ty, rust_name, ..
+            } => match *pg_ty {
                 Type::BYTEA => {
                     traits.push(format!("{client_name}::BytesSql"));
                     idx_char(traits.len())
                 }
-                Type::TEXT | Type::VARCHAR => {
+                _ if rust_name == "String" => {
                     traits.push(format!("{client_name}::StringSql"));
                     idx_char(traits.len())
                 }
@@ -196,6 +415,10 @@ impl CornucopiaType {
                     traits.push(format!("{client_name}::JsonSql"));
                     idx_char(traits.len())
                 }
+                _ if is_hstore(pg_ty) => {
+                    traits.push(format!("{client_name}::HstoreSql"));
+                    idx_char(traits.len())
+                }
                 _ => self.param_ty(is_inner_nullable, ctx),
             },
             CornucopiaType::Array { inner, .. } => {
@@ -208,10 +431,15 @@ impl CornucopiaType {
                 traits.push(format!("{client_name}::ArraySql<Item = {inner}>"));
                 idx_char(traits.len())
             }
-            CornucopiaType::Domain { inner, .. } => {
+            CornucopiaType::Domain { inner, .. } | CornucopiaType::Inline { inner, .. } => {
                 inner.param_ergo_ty(is_inner_nullable, traits, ctx)
             }
-            CornucopiaType::Custom { .. } => self.param_ty(is_inner_nullable, ctx),
+            CornucopiaType::Range { .. }
+            | CornucopiaType::Multirange { .. }
+            | CornucopiaType::Matrix { .. }
+            | CornucopiaType::Custom { .. }
+            | CornucopiaType::Newtype { .. }
+            | CornucopiaType::Mapped { .. } => self.param_ty(is_inner_nullable, ctx),
         }
     }
 
@@ -220,6 +448,8 @@ impl CornucopiaType {
         match self {
             CornucopiaType::Simple { pg_ty, .. } => match *pg_ty {
                 Type::JSON | Type::JSONB => "&'a serde_json::value::Value".to_string(),
+                _ if is_hstore(pg_ty) => format!("&'a {}", self.own_ty(false, ctx)),
+                _ if is_text_search(pg_ty) || is_geometric(pg_ty) => self.own_ty(false, ctx),
                 _ => self.brw_ty(is_inner_nullable, true, ctx),
             },
             CornucopiaType::Array { inner, .. } => {
@@ -232,7 +462,20 @@ impl CornucopiaType {
                 // Its more practical for users to use a slice
                 format!("&'a [{inner}]")
             }
-            CornucopiaType::Domain { inner, .. } => inner.param_ty(false, ctx),
+            CornucopiaType::Matrix { .. } => {
+                unreachable!("two dimensional arrays are only decoded from columns")
+            }
+            CornucopiaType::Domain { inner, .. } | CornucopiaType::Inline { inner, .. } => {
+                inner.param_ty(false, ctx)
+            }
+            CornucopiaType::Range { inner, .. } => {
+                let client_name = ctx.client_name();
+                format!("{client_name}::Range<{}>", inner.param_ty(false, ctx))
+            }
+            CornucopiaType::Multirange { inner, .. } => {
+                let client_name = ctx.client_name();
+                format!("&'a [{client_name}::Range<{}>]", inner.param_ty(false, ctx))
+            }
             CornucopiaType::Custom {
                 is_params,
                 is_copy,
@@ -247,6 +490,13 @@ impl CornucopiaType {
                     self.brw_ty(is_inner_nullable, true, ctx)
                 }
             }
+            // Bound by reference like `hstore` maps, rows borrowing their values as another type
+            CornucopiaType::Mapped {
+                borrowed: Some(_), ..
+            } => format!("&'a {}", self.own_ty(false, ctx)),
+            CornucopiaType::Newtype { .. } | CornucopiaType::Mapped { .. } => {
+                self.own_ty(false, ctx)
+            }
         }
     }
 
@@ -264,11 +514,26 @@ impl CornucopiaType {
                 pg_ty, rust_name, ..
             } => match *pg_ty {
                 Type::BYTEA => format!("&{lifetime} [u8]"),
-                Type::TEXT | Type::VARCHAR => format!("&{lifetime} str"),
+                // Text types, along with the extension types decoded as `String`
+                _ if rust_name == "String" => format!("&{lifetime} str"),
                 Type::JSON | Type::JSONB => {
                     format!("postgres_types::Json<&{lifetime} serde_json::value::RawValue>")
                 }
-                _ => (*rust_name).to_string(),
+                _ if is_hstore(pg_ty) => {
+                    let lifetime = if has_lifetime { lifetime } else { "'_" };
+                    format!("{}::HstoreIterator<{lifetime}>", ctx.client_name())
+                }
+                // Decoded as owned values, whatever the lifetime of the row
+                _ if is_text_search(pg_ty) || is_geometric(pg_ty) => self.own_ty(false, ctx),
+                // Converted from the types of the client when owned
+                Type::MONEY => format!("{}::Money", ctx.client_name()),
+                Type::INTERVAL if rust_name == "Interval" => self.own_ty(false, ctx),
+                Type::INTERVAL => format!("{}::ExactInterval", ctx.client_name()),
+                Type::BIT | Type::VARBIT if rust_name == "Vec<bool>" => {
+                    format!("{}::BitString", ctx.client_name())
+                }
+                Type::BIT | Type::VARBIT => self.own_ty(false, ctx),
+                _ => rust_name.to_string(),
             },
             CornucopiaType::Array { inner, .. } => {
                 let inner = inner.brw_ty(is_inner_nullable, has_lifetime, ctx);
@@ -282,7 +547,31 @@ impl CornucopiaType {
                 let client_name = ctx.client_name();
                 format!("{client_name}::ArrayIterator<{lifetime}, {inner}>")
             }
-            CornucopiaType::Domain { inner, .. } => inner.brw_ty(false, has_lifetime, ctx),
+            CornucopiaType::Matrix { inner, .. } => {
+                let inner = inner.brw_ty(is_inner_nullable, has_lifetime, ctx);
+                let inner = if is_inner_nullable {
+                    format!("Option<{inner}>")
+                } else {
+                    inner
+                };
+                let lifetime = if has_lifetime { lifetime } else { "'_" };
+                let client_name = ctx.client_name();
+                format!("{client_name}::MatrixIterator<{lifetime}, {inner}>")
+            }
+            CornucopiaType::Domain { inner, .. } | CornucopiaType::Inline { inner, .. } => {
+                inner.brw_ty(false, has_lifetime, ctx)
+            }
+            CornucopiaType::Range { inner, .. } => {
+                let client_name = ctx.client_name();
+                let inner = inner.brw_ty(false, has_lifetime, ctx);
+                format!("{client_name}::Range<{inner}>")
+            }
+            CornucopiaType::Multirange { inner, .. } => {
+                let inner = inner.brw_ty(false, has_lifetime, ctx);
+                let lifetime = if has_lifetime { lifetime } else { "'_" };
+                let client_name = ctx.client_name();
+                format!("{client_name}::MultirangeIterator<{lifetime}, {inner}>")
+            }
             CornucopiaType::Custom {
                 is_copy,
                 pg_ty,
@@ -296,10 +585,61 @@ impl CornucopiaType {
                     format!("{path}Borrowed<{lifetime}>")
                 }
             }
+            CornucopiaType::Newtype { inner, .. } => {
+                if inner.is_copy() {
+                    self.own_ty(false, ctx)
+                } else {
+                    inner.brw_ty(false, has_lifetime, ctx)
+                }
+            }
+            // Anonymous records are decoded as the tuple of their fields
+            CornucopiaType::Mapped {
+                rust_path, inner, ..
+            } if *inner.pg_ty() == Type::RECORD => {
+                format!("{}::Record<{rust_path}>", ctx.client_name())
+            }
+            CornucopiaType::Mapped {
+                borrowed: Some(borrowed),
+                ..
+            } => {
+                let lifetime = if has_lifetime { lifetime } else { "'_" };
+                format!("{borrowed}<{lifetime}>")
+            }
+            // Decoded as an owned value, whatever the lifetime of the row
+            CornucopiaType::Mapped { rust_path, .. } => rust_path.clone(),
         }
     }
 }
 
+/// Is `ty` a `tsvector` or a `tsquery`, decoded by the client crates
+fn is_text_search(ty: &Type) -> bool {
+    matches!(*ty, Type::TS_VECTOR | Type::TSQUERY)
+}
+
+/// Is `ty` a geometric type, decoded by the client crates
+fn is_geometric(ty: &Type) -> bool {
+    matches!(
+        *ty,
+        Type::POINT
+            | Type::LINE
+            | Type::LSEG
+            | Type::BOX
+            | Type::PATH
+            | Type::POLYGON
+            | Type::CIRCLE
+    )
+}
+
+/// Is `ty` decoded by a type of the client crates, converted to its own Rust type when owned
+fn is_client_decoded(ty: &Type) -> bool {
+    matches!(*ty, Type::MONEY | Type::INTERVAL | Type::BIT | Type::VARBIT)
+}
+
+/// Is `ty` the `hstore` type of the extension of the same name, whose oid differs between databases
+fn is_hstore(ty: &Type) -> bool {
+    ty.name() == "hstore" && matches!(ty.kind(), Kind::Simple)
+}
+
 pub fn custom_ty_path(schema: &str, struct_name: &str, ctx: &GenCtx) -> String {
     if ctx.depth == 0 {
         format!("{schema}::{struct_name}")
@@ -314,6 +654,22 @@ pub fn custom_ty_path(schema: &str, struct_name: &str, ctx: &GenCtx) -> String {
 #[derive(Debug, Clone, Default)]
 pub(crate) struct TypeRegistrar {
     pub types: IndexMap<(String, String), Rc<CornucopiaType>>,
+    pub newtypes: IndexMap<String, Rc<CornucopiaType>>,
+    /// Rust types of the configured extension types, keyed by their `schema.name` path
+    pub extension_types: BTreeMap<String, String>,
+    /// User types of the configured codecs, keyed by the `schema.name` path of their type
+    pub codecs: BTreeMap<String, Codec>,
+    /// Rust type of the `money` values
+    pub money: MoneyType,
+    /// Rust type of the `interval` values
+    pub interval: IntervalType,
+    /// Rust type of the `bit(n)` and `bit varying` values
+    pub bit: BitType,
+    /// Keys of the types registered or looked up, along with the types they depend on, in the
+    /// order a new registrar would register them in
+    pub used: Vec<(String, String)>,
+    /// Names of the newtypes registered or looked up
+    pub used_newtypes: Vec<String>,
 }
 
 impl TypeRegistrar {
@@ -334,6 +690,35 @@ impl TypeRegistrar {
             }
         }
 
+        fn extension(ty: &Type, rust_type: &str) -> CornucopiaType {
+            let simple = |rust_name: &str| CornucopiaType::Simple {
+                pg_ty: ty.clone(),
+                rust_name: Cow::Owned(rust_name.to_string()),
+                is_copy: false,
+            };
+            if rust_type == "String" {
+                simple(rust_type)
+            } else {
+                CornucopiaType::Mapped {
+                    rust_path: rust_type.to_string(),
+                    borrowed: None,
+                    inner: Rc::new(simple(rust_type)),
+                }
+            }
+        }
+
+        fn codec(ty: &Type, codec: &Codec) -> CornucopiaType {
+            CornucopiaType::Mapped {
+                rust_path: codec.owned.clone(),
+                borrowed: codec.borrowed.clone(),
+                inner: Rc::new(CornucopiaType::Simple {
+                    pg_ty: ty.clone(),
+                    rust_name: Cow::Owned(codec.owned.clone()),
+                    is_copy: false,
+                }),
+            }
+        }
+
         fn domain(ty: &Type, inner: Rc<CornucopiaType>) -> CornucopiaType {
             CornucopiaType::Domain {
                 pg_ty: ty.clone(),
@@ -342,9 +727,16 @@ impl TypeRegistrar {
         }
 
         if let Some(idx) = self.types.get_index_of(&SchemaKey::from(ty)) {
+            self.use_registered(ty);
             return Ok(&self.types[idx]);
         }
 
+        // Passed through untouched, whatever the kind of the type
+        let path = format!("{}.{}", ty.schema(), ty.name());
+        if let Some(user_codec) = self.codecs.get(&path).cloned() {
+            return Ok(self.insert(ty, || codec(ty, &user_codec)));
+        }
+
         Ok(match ty.kind() {
             Kind::Enum(_) => self.insert(ty, || custom(ty, true, true)),
             Kind::Array(inner_ty) => {
@@ -361,6 +753,24 @@ impl TypeRegistrar {
                     .clone();
                 self.insert(ty, || domain(ty, inner.clone()))
             }
+            Kind::Range(inner_ty) => {
+                let inner = self
+                    .register(name, inner_ty, query_name, module_info)?
+                    .clone();
+                self.insert(ty, || CornucopiaType::Range {
+                    pg_ty: ty.clone(),
+                    inner: inner.clone(),
+                })
+            }
+            Kind::Multirange(inner_ty) => {
+                let inner = self
+                    .register(name, inner_ty, query_name, module_info)?
+                    .clone();
+                self.insert(ty, || CornucopiaType::Multirange {
+                    pg_ty: ty.clone(),
+                    inner: inner.clone(),
+                })
+            }
             Kind::Composite(composite_fields) => {
                 let mut is_copy = true;
                 let mut is_params = true;
@@ -371,6 +781,17 @@ impl TypeRegistrar {
                 }
                 self.insert(ty, || custom(ty, is_copy, is_params))
             }
+            Kind::Pseudo if *ty == Type::VOID => self.insert(ty, || CornucopiaType::Simple {
+                pg_ty: ty.clone(),
+                rust_name: Cow::Borrowed("()"),
+                is_copy: true,
+            }),
+            // Only decoded as the tuple declared as the Rust type of its column
+            Kind::Pseudo if *ty == Type::RECORD => self.insert(ty, || CornucopiaType::Simple {
+                pg_ty: ty.clone(),
+                rust_name: Cow::Borrowed("record"),
+                is_copy: false,
+            }),
             Kind::Simple => {
                 let (rust_name, is_copy) = match *ty {
                     Type::BOOL => ("bool", true),
@@ -378,6 +799,7 @@ impl TypeRegistrar {
                     Type::INT2 => ("i16", true),
                     Type::INT4 => ("i32", true),
                     Type::INT8 => ("i64", true),
+                    Type::OID => ("u32", true),
                     Type::FLOAT4 => ("f32", true),
                     Type::FLOAT8 => ("f64", true),
                     Type::TEXT | Type::VARCHAR => ("String", false),
@@ -391,18 +813,49 @@ impl TypeRegistrar {
                     Type::INET => ("std::net::IpAddr", true),
                     Type::MACADDR => ("eui48::MacAddress", true),
                     Type::NUMERIC => ("rust_decimal::Decimal", true),
+                    Type::TS_VECTOR => ("TsVector", false),
+                    Type::TSQUERY => ("TsQuery", false),
+                    Type::POINT => ("Point", true),
+                    Type::LINE => ("Line", true),
+                    Type::LSEG => ("LineSegment", true),
+                    Type::BOX => ("BoundingBox", true),
+                    Type::PATH => ("Path", false),
+                    Type::POLYGON => ("Polygon", false),
+                    Type::CIRCLE => ("Circle", true),
+                    // Only the client's newtype is decoded as is, the others are converted from it
+                    Type::MONEY => match self.money {
+                        MoneyType::Newtype => ("Money", true),
+                        MoneyType::Cents => ("i64", false),
+                        MoneyType::Decimal => ("rust_decimal::Decimal", false),
+                    },
+                    Type::INTERVAL => match self.interval {
+                        IntervalType::Struct => ("Interval", true),
+                        IntervalType::Duration => ("time::Duration", false),
+                    },
+                    Type::BIT | Type::VARBIT => match self.bit {
+                        BitType::BitString => ("BitString", false),
+                        BitType::Bools => ("Vec<bool>", false),
+                        // Decoded as is by `postgres-types`
+                        BitType::BitVec => ("bit_vec::BitVec", false),
+                    },
+                    _ if is_hstore(ty) => {
+                        ("std::collections::HashMap<String, Option<String>>", false)
+                    }
                     _ => {
+                        if let Some(rust_type) = self.extension_types.get(&path).cloned() {
+                            return Ok(self.insert(ty, || extension(ty, &rust_type)));
+                        }
                         return Err(Error::UnsupportedPostgresType {
                             src: module_info.clone().into(),
                             query: query_name.span,
                             col_name: name.to_string(),
                             col_ty: ty.to_string(),
-                        })
+                        });
                     }
                 };
                 self.insert(ty, || CornucopiaType::Simple {
                     pg_ty: ty.clone(),
-                    rust_name,
+                    rust_name: Cow::Borrowed(rust_name),
                     is_copy,
                 })
             }
@@ -417,6 +870,48 @@ impl TypeRegistrar {
         })
     }
 
+    /// Registers the newtype `struct_name` wrapping the type of a configured column
+    pub(crate) fn register_newtype(
+        &mut self,
+        struct_name: &str,
+        schema: &str,
+        inner: Rc<CornucopiaType>,
+        col_name: &str,
+        query_name: &Span<String>,
+        module_info: &ModuleInfo,
+    ) -> Result<Rc<CornucopiaType>, Error> {
+        let unsupported = || Error::UnsupportedNewtype {
+            src: module_info.clone().into(),
+            query: query_name.span,
+            col_name: col_name.to_string(),
+            col_ty: inner.pg_ty().to_string(),
+        };
+        let is_simple = matches!(
+            inner.as_ref(),
+            CornucopiaType::Simple { pg_ty, .. } if !matches!(*pg_ty, Type::JSON | Type::JSONB | Type::VOID)
+        );
+        if !is_simple {
+            return Err(unsupported());
+        }
+        self.used_newtypes.push(struct_name.to_string());
+        match self.newtypes.entry(struct_name.to_string()) {
+            Entry::Occupied(o) => match o.get().as_ref() {
+                // Every column sharing a newtype must have the same type
+                CornucopiaType::Newtype { inner: prev, .. } if *prev == inner => {
+                    Ok(o.get().clone())
+                }
+                _ => Err(unsupported()),
+            },
+            Entry::Vacant(v) => Ok(v
+                .insert(Rc::new(CornucopiaType::Newtype {
+                    schema: schema.to_string(),
+                    struct_name: struct_name.to_string(),
+                    inner,
+                }))
+                .clone()),
+        }
+    }
+
     pub(crate) fn ref_of(&self, ty: &Type) -> Rc<CornucopiaType> {
         self.types
             .get(&SchemaKey::from(ty))
@@ -424,7 +919,31 @@ impl TypeRegistrar {
             .clone()
     }
 
+    /// Records the use of the registered type `ty` and of the types it depends on, following
+    /// the order of their registration
+    fn use_registered(&mut self, ty: &Type) {
+        let path = format!("{}.{}", ty.schema(), ty.name());
+        if !self.codecs.contains_key(&path) {
+            match ty.kind() {
+                Kind::Array(inner)
+                | Kind::Domain(inner)
+                | Kind::Range(inner)
+                | Kind::Multirange(inner) => self.use_registered(inner),
+                Kind::Composite(fields) => {
+                    for field in fields {
+                        self.use_registered(field.type_());
+                    }
+                }
+                _ => {}
+            }
+        }
+        self.used
+            .push((ty.schema().to_owned(), ty.name().to_owned()));
+    }
+
     fn insert(&mut self, ty: &Type, call: impl Fn() -> CornucopiaType) -> &Rc<CornucopiaType> {
+        self.used
+            .push((ty.schema().to_owned(), ty.name().to_owned()));
         let index = match self
             .types
             .entry((ty.schema().to_owned(), ty.name().to_owned()))
@@ -464,5 +983,18 @@ pub(crate) mod error {
             col_name: String,
             col_ty: String,
         },
+        #[diagnostic(help(
+            "newtypes wrap a single scalar type, shared by all the columns mapped to them"
+        ))]
+        UnsupportedNewtype {
+            #[source_code]
+            src: NamedSource,
+            #[label(
+                "column `{col_name}` of type `{col_ty}` can't be wrapped in its configured newtype"
+            )]
+            query: SourceSpan,
+            col_name: String,
+            col_ty: String,
+        },
     }
 }
@@ -1,3 +1,5 @@
+use std::time::Duration;
+
 use indexmap::Equivalent;
 use postgres::error::ErrorPosition;
 use postgres_types::Type;
@@ -33,6 +35,39 @@ pub fn find_duplicate<T>(slice: &[T], eq: fn(&T, &T) -> bool) -> Option<(&T, &T)
     None
 }
 
+/// Parses a positive duration in microseconds, milliseconds or seconds, e.g. `50ms`
+pub(crate) fn parse_duration(value: &str) -> Option<Duration> {
+    let (amount, unit) = value.split_at(value.find(|c: char| !c.is_ascii_digit())?);
+    let amount: u64 = amount.parse().ok().filter(|it| *it > 0)?;
+    match unit {
+        "us" => Some(Duration::from_micros(amount)),
+        "ms" => Some(Duration::from_millis(amount)),
+        "s" => Some(Duration::from_secs(amount)),
+        _ => None,
+    }
+}
+
+/// Whether `path` matches the glob `pattern`, where `*` matches any characters but `/`, `?` one of
+/// them and `**/` any number of folders
+pub(crate) fn glob_match(pattern: &str, path: &str) -> bool {
+    fn matches(pattern: &[u8], path: &[u8]) -> bool {
+        match pattern {
+            [] => path.is_empty(),
+            [b'*', b'*', b'/', rest @ ..] => {
+                matches(rest, path)
+                    || (0..path.len()).any(|i| path[i] == b'/' && matches(rest, &path[i + 1..]))
+            }
+            [b'*', b'*', rest @ ..] => (0..=path.len()).any(|i| matches(rest, &path[i..])),
+            [b'*', rest @ ..] => (0..=path.len())
+                .take_while(|&i| i == 0 || path[i - 1] != b'/')
+                .any(|i| matches(rest, &path[i..])),
+            [b'?', rest @ ..] => matches!(path, [c, ..] if *c != b'/') && matches(rest, &path[1..]),
+            [c, rest @ ..] => path.first() == Some(c) && matches(rest, &path[1..]),
+        }
+    }
+    matches(pattern.as_bytes(), path.as_bytes())
+}
+
 /// Extracts useful info from a `postgres`-generated error.
 pub(crate) fn db_err(err: &postgres::Error) -> Option<(u32, String, Option<String>)> {
     if let Some(db_err) = err.as_db_error() {
@@ -61,3 +96,78 @@ pub(crate) const KEYWORD: [&str; 53] = [
     "return", "self", "static", "struct", "super", "trait", "true", "try", "type", "typeof",
     "union", "unsafe", "unsized", "use", "virtual", "where", "while", "yield",
 ];
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{glob_match, parse_duration};
+
+    #[test]
+    fn glob() {
+        let cases = [
+            // Literals
+            ("queries/a.sql", "queries/a.sql", true),
+            ("queries/a.sql", "queries/b.sql", false),
+            ("queries/a.sql", "queries/a.sql.bak", false),
+            // `*` matches any characters but `/`
+            ("*.sql", "a.sql", true),
+            ("*.sql", ".sql", true),
+            ("*.sql", "a.toml", false),
+            ("*.sql", "queries/a.sql", false),
+            ("queries/*", "queries/a.sql", true),
+            ("queries/*", "queries/nested/a.sql", false),
+            ("a*b*c", "abbbc", true),
+            ("a*b*c", "acb", false),
+            // `?` matches one character but `/`
+            ("?.sql", "a.sql", true),
+            ("?.sql", "ab.sql", false),
+            ("?.sql", ".sql", false),
+            ("a?b", "a/b", false),
+            // `**/` matches any number of folders
+            ("**/*.sql", "a.sql", true),
+            ("**/*.sql", "queries/nested/a.sql", true),
+            ("queries/**/a.sql", "queries/a.sql", true),
+            ("queries/**/a.sql", "queries/x/y/a.sql", true),
+            ("queries/**/a.sql", "other/x/a.sql", false),
+            // `**` alone crosses folders
+            ("queries/**", "queries/x/y/a.sql", true),
+            // Empty patterns only match empty paths
+            ("", "", true),
+            ("", "a.sql", false),
+            ("*", "", true),
+            ("?", "", false),
+        ];
+        for (pattern, path, expected) in cases {
+            assert_eq!(
+                glob_match(pattern, path),
+                expected,
+                "`{pattern}` matching `{path}`"
+            );
+        }
+    }
+
+    #[test]
+    fn duration() {
+        let cases = [
+            ("250us", Some(Duration::from_micros(250))),
+            ("50ms", Some(Duration::from_millis(50))),
+            ("3s", Some(Duration::from_secs(3))),
+            // Zero, missing or unknown units, and other invalid input
+            ("0ms", None),
+            ("50", None),
+            ("ms", None),
+            ("", None),
+            ("50m", None),
+            ("5min", None),
+            ("50 ms", None),
+            ("-5s", None),
+            ("1.5s", None),
+            ("50MS", None),
+            ("99999999999999999999s", None),
+        ];
+        for (value, expected) in cases {
+            assert_eq!(parse_duration(value), expected, "`{value}`");
+        }
+    }
+}
@@ -1,15 +1,22 @@
 use std::collections::BTreeMap;
 
 use crate::{
-    parser::{Module, NullableIdent, Query, QueryDataStruct, Span, TypeAnnotation},
+    parser::{
+        Attribute, AttributeValue, Module, NullableIdent, Query, QueryDataStruct, Span,
+        TypeAnnotation,
+    },
+    prepare_queries::ParamCheck,
     prepare_queries::{PreparedField, PreparedModule},
     read_queries::ModuleInfo,
-    utils::{find_duplicate, STRICT_KEYWORD},
+    sql_analysis::{self, CopyTarget},
+    type_registrar::{CheckedKind, CornucopiaType},
+    utils::{find_duplicate, parse_duration, STRICT_KEYWORD},
 };
 
 use error::Error;
+use heck::ToUpperCamelCase;
 use miette::SourceSpan;
-use postgres::Column;
+use postgres::{Column, Statement};
 use postgres_types::Type;
 
 pub(crate) fn duplicate_nullable_ident(
@@ -169,6 +176,25 @@ pub(crate) fn row_on_execute(
     Ok(())
 }
 
+/// Anonymous records can only be decoded as the tuple declared as the Rust type of their column
+pub(crate) fn record_column(
+    info: &ModuleInfo,
+    name: &Span<String>,
+    column: &Column,
+    nullity: Option<&NullableIdent>,
+) -> Result<(), Box<Error>> {
+    let declared = nullity.and_then(|it| it.rust_type.as_ref());
+    if *column.type_() == Type::RECORD && declared.is_some_and(|it| it.value.starts_with('(')) {
+        return Ok(());
+    }
+    Err(Box::new(Error::UndeclaredRecord {
+        src: info.into(),
+        name: name.value.clone(),
+        column: column.name().to_string(),
+        pos: declared.map_or(name.span, |it| it.span),
+    }))
+}
+
 pub(crate) fn param_on_simple_query(
     info: &ModuleInfo,
     name: &Span<String>,
@@ -187,6 +213,111 @@ pub(crate) fn param_on_simple_query(
     Ok(())
 }
 
+/// Statements ending the transaction a script runs in, or unable to run in one
+const TRANSACTION_CONTROL: [&str; 9] = [
+    "ABORT",
+    "BEGIN",
+    "COMMIT",
+    "END",
+    "RELEASE",
+    "ROLLBACK",
+    "SAVEPOINT",
+    "START",
+    "VACUUM",
+];
+
+/// Statements of the script `sql`, with their offset in it, including their leading blanks and
+/// comments
+pub(crate) fn script_statements(sql: &str) -> impl Iterator<Item = (usize, &str)> {
+    sql.split(';').scan(0, |offset, statement| {
+        let start = *offset;
+        *offset += statement.len() + 1;
+        Some((start, statement))
+    })
+}
+
+/// Offset of the first word of `statement`, after its leading blanks and comments, along with
+/// the uppercase first two words
+fn leading_words(statement: &str) -> (usize, String, String) {
+    let mut rest = statement.trim_start();
+    while rest.starts_with("--") {
+        rest = rest.split_once('\n').map_or("", |(_, it)| it).trim_start();
+    }
+    let mut words = rest
+        .split(|c: char| !c.is_ascii_alphabetic())
+        .filter(|it| !it.is_empty())
+        .map(str::to_ascii_uppercase);
+    let first = words.next().unwrap_or_default();
+    let second = words.next().unwrap_or_default();
+    (statement.len() - rest.len(), first, second)
+}
+
+/// Scripts are run with the simple query protocol, so they can't have bindings nor return rows.
+/// They run in the transaction of their client, which they must not control.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn script_query(
+    info: &ModuleInfo,
+    name: &Span<String>,
+    query: &SourceSpan,
+    sql_str: &str,
+    param: &QueryDataStruct,
+    row: &QueryDataStruct,
+    attributes: &[Attribute],
+    bind_params: &[Span<String>],
+) -> Result<(), Box<Error>> {
+    if let Some(bind_param) = bind_params.first() {
+        return Err(Box::new(Error::ParamsOnScript {
+            src: info.into(),
+            name: name.value.clone(),
+            // Binding spans are relative to the query, and don't include the colon
+            param: (
+                query.offset() + bind_param.span.offset() - 1,
+                bind_param.span.len() + 1,
+            )
+                .into(),
+        }));
+    }
+    for (offset, statement) in script_statements(sql_str) {
+        let (start, first, second) = leading_words(statement);
+        let keyword = if first == "PREPARE" && second == "TRANSACTION" {
+            format!("{first} {second}")
+        } else if TRANSACTION_CONTROL.contains(&first.as_str()) {
+            first
+        } else {
+            continue;
+        };
+        return Err(Box::new(Error::TransactionControlOnScript {
+            src: info.into(),
+            name: name.value.clone(),
+            statement: (query.offset() + offset + start, keyword.len()).into(),
+            keyword,
+        }));
+    }
+    param_on_simple_query(info, name, query, param, &[])?;
+    row_on_execute(info, name, query, row, &[])?;
+    if let Some(attribute) = attributes.iter().find(|it| {
+        [
+            "audit",
+            "cardinality",
+            "copy",
+            "derive",
+            "paginate",
+            "validate",
+            "variants",
+        ]
+        .contains(&it.name.value.as_str())
+    }) {
+        return Err(invalid_attribute(
+            info,
+            attribute,
+            attribute.name.span,
+            "not supported on scripts",
+            "split the script into one query per statement",
+        ));
+    }
+    Ok(())
+}
+
 fn reserved_type_keyword(info: &ModuleInfo, s: &Span<String>) -> Result<(), Box<Error>> {
     if let Ok(it) = STRICT_KEYWORD.binary_search(&s.value.as_str()) {
         return Err(Box::new(Error::TypeRustKeyword {
@@ -266,6 +397,1031 @@ pub(crate) fn named_struct_field(
     Ok(())
 }
 
+/// Attributes accepted on queries (`--#`) and modules (`--#!`)
+const ATTRIBUTES: [&str; 13] = [
+    "audit",
+    "budget",
+    "cardinality",
+    "constraints",
+    "copy",
+    "deprecated",
+    "derive",
+    "paginate",
+    "renamed",
+    "session",
+    "trace",
+    "validate",
+    "variants",
+];
+/// Attributes only accepted on queries
+const QUERY_ATTRIBUTES: [&str; 6] = [
+    "cardinality",
+    "deprecated",
+    "derive",
+    "renamed",
+    "validate",
+    "variants",
+];
+/// Attributes only accepted on modules
+const MODULE_ATTRIBUTES: [&str; 2] = ["constraints", "session"];
+/// Levels accepted by the `trace` attribute
+pub(crate) const TRACE_LEVELS: [&str; 5] = ["trace", "debug", "info", "warn", "error"];
+
+fn invalid_attribute(
+    info: &ModuleInfo,
+    attribute: &Attribute,
+    pos: SourceSpan,
+    msg: &str,
+    help: &str,
+) -> Box<Error> {
+    Box::new(Error::InvalidAttribute {
+        src: info.into(),
+        name: attribute.name.value.clone(),
+        msg: msg.to_string(),
+        help: help.to_string(),
+        pos,
+    })
+}
+
+pub(crate) fn attributes(info: &ModuleInfo, attributes: &[Attribute]) -> Result<(), Box<Error>> {
+    find_duplicate(attributes, |a, b| a.name == b.name).map_or(Ok(()), |(first, second)| {
+        Err(Box::new(Error::DuplicateType {
+            src: info.into(),
+            ty: "attribute",
+            name: first.name.value.clone(),
+            first: first.name.span,
+            second: second.name.span,
+        }))
+    })?;
+    for attribute in attributes {
+        match attribute.name.value.as_str() {
+            "audit" => audit_attribute(info, attribute)?,
+            "budget" => budget_attribute(info, attribute)?,
+            "cardinality" => cardinality_attribute(info, attribute)?,
+            "constraints" => constraints_attribute(info, attribute)?,
+            "copy" => copy_attribute(info, attribute)?,
+            "deprecated" => deprecated_attribute(info, attribute)?,
+            "derive" => derive_attribute(info, attribute)?,
+            "paginate" => paginate_attribute(info, attribute)?,
+            "renamed" => renamed_attribute(info, attribute)?,
+            "session" => session_attribute(info, attribute)?,
+            "trace" => trace_attribute(info, attribute)?,
+            "validate" => validate_attribute(info, attribute)?,
+            "variants" => variants_attribute(info, attribute)?,
+            _ => {
+                return Err(Box::new(Error::UnknownAttribute {
+                    src: info.into(),
+                    name: attribute.name.value.clone(),
+                    pos: attribute.name.span,
+                    known: ATTRIBUTES.join(", "),
+                }))
+            }
+        }
+    }
+    Ok(())
+}
+
+fn audit_attribute(info: &ModuleInfo, attribute: &Attribute) -> Result<(), Box<Error>> {
+    let help = "use `audit` or `audit(actor = param)`";
+    match &attribute.value {
+        AttributeValue::Flag => Ok(()),
+        AttributeValue::Value(value) => Err(invalid_attribute(
+            info,
+            attribute,
+            value.span,
+            "expected a list of settings",
+            help,
+        )),
+        AttributeValue::List(nested) => {
+            for setting in nested {
+                if setting.name.value != "actor" {
+                    return Err(invalid_attribute(
+                        info,
+                        attribute,
+                        setting.name.span,
+                        "unknown setting",
+                        help,
+                    ));
+                }
+                if !matches!(setting.value, AttributeValue::Value(_)) {
+                    return Err(invalid_attribute(
+                        info,
+                        attribute,
+                        setting.name.span,
+                        "expected a parameter name",
+                        help,
+                    ));
+                }
+            }
+            Ok(())
+        }
+    }
+}
+
+fn cardinality_attribute(info: &ModuleInfo, attribute: &Attribute) -> Result<(), Box<Error>> {
+    match &attribute.value {
+        AttributeValue::Value(value) if matches!(value.value.as_str(), "one" | "opt" | "all") => {
+            Ok(())
+        }
+        AttributeValue::Value(value) => Err(invalid_attribute(
+            info,
+            attribute,
+            value.span,
+            "unknown cardinality",
+            "use one of those cardinalities: one, opt, all",
+        )),
+        _ => Err(invalid_attribute(
+            info,
+            attribute,
+            attribute.name.span,
+            "expected a cardinality",
+            "use `cardinality = one`, `cardinality = opt` or `cardinality = all`",
+        )),
+    }
+}
+
+/// Queries with a cardinality fetch their rows when bound, so they must return rows
+pub(crate) fn cardinality_query(
+    info: &ModuleInfo,
+    attributes: &[Attribute],
+    columns: &[Column],
+) -> Result<(), Box<Error>> {
+    let Some(cardinality) = Attribute::find(attributes, "cardinality") else {
+        return Ok(());
+    };
+    if columns.is_empty() {
+        return Err(invalid_attribute(
+            info,
+            cardinality,
+            cardinality.name.span,
+            "the query returns no rows",
+            "only declare the cardinality of queries returning rows",
+        ));
+    }
+    if Attribute::find(attributes, "copy").is_some() {
+        return Err(invalid_attribute(
+            info,
+            cardinality,
+            cardinality.name.span,
+            "copied rows are streamed",
+            "remove the cardinality of this copy",
+        ));
+    }
+    Ok(())
+}
+
+/// Variants must be fragments or identifiers, so that no SQL is written at runtime
+pub(crate) fn variants_attribute(
+    info: &ModuleInfo,
+    attribute: &Attribute,
+) -> Result<(), Box<Error>> {
+    let help = "use `variants(slot = \"variant|other\")`";
+    let AttributeValue::List(nested) = &attribute.value else {
+        return Err(invalid_attribute(
+            info,
+            attribute,
+            attribute.name.span,
+            "expected a list of slots",
+            help,
+        ));
+    };
+    if let Some((first, second)) = find_duplicate(nested, |a, b| a.name == b.name) {
+        return Err(Box::new(Error::DuplicateType {
+            src: info.into(),
+            ty: "slot",
+            name: first.name.value.clone(),
+            first: first.name.span,
+            second: second.name.span,
+        }));
+    }
+    for slot in nested {
+        let AttributeValue::Value(value) = &slot.value else {
+            return Err(invalid_attribute(
+                info,
+                attribute,
+                slot.name.span,
+                "expected the variants of the slot",
+                help,
+            ));
+        };
+        let variants: Vec<_> = value.value.split('|').map(str::trim).collect();
+        let is_ident = |it: &str| {
+            it.chars().next().is_some_and(|c| !c.is_ascii_digit())
+                && it.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        };
+        if let Some(variant) = variants.iter().find(|variant| {
+            let mut parts = variant.split('.');
+            !parts.next().is_some_and(is_ident) || !parts.all(is_ident)
+        }) {
+            return Err(invalid_attribute(
+                info,
+                attribute,
+                value.span,
+                &format!("`{variant}` is neither a fragment nor an identifier"),
+                "declare the SQL of the variant as a fragment, and use its name",
+            ));
+        }
+        if variants.len() < 2 {
+            return Err(invalid_attribute(
+                info,
+                attribute,
+                value.span,
+                "expected several variants",
+                help,
+            ));
+        }
+        // Variants are named after their fragment or identifier
+        let names: Vec<_> = variants
+            .iter()
+            .map(|it| it.rsplit('.').next().unwrap().to_upper_camel_case())
+            .collect();
+        if let Some((name, _)) = find_duplicate(&names, |a, b| a == b) {
+            return Err(invalid_attribute(
+                info,
+                attribute,
+                value.span,
+                &format!("several variants are named `{name}`"),
+                "use distinct variants",
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Each variant of a query is prepared as a statement of its own, which must take the same
+/// parameters and return the same columns as the default one
+pub(crate) fn variants_query(
+    info: &ModuleInfo,
+    attributes: &[Attribute],
+    stmt: &Statement,
+    variant: Option<&Statement>,
+) -> Result<(), Box<Error>> {
+    let Some(attribute) = Attribute::find(attributes, "variants") else {
+        return Ok(());
+    };
+    if let Some(other) = ["copy", "paginate"]
+        .into_iter()
+        .find(|it| Attribute::find(attributes, it).is_some())
+    {
+        return Err(invalid_attribute(
+            info,
+            attribute,
+            attribute.name.span,
+            &format!("not supported with `{other}`"),
+            "use a query for each variant",
+        ));
+    }
+    let Some(variant) = variant else {
+        return Ok(());
+    };
+    let columns = |stmt: &Statement| -> Vec<_> {
+        stmt.columns()
+            .iter()
+            .map(|it| (it.name().to_string(), it.type_().clone()))
+            .collect()
+    };
+    let msg = if stmt.params() != variant.params() {
+        "a variant changes the parameters"
+    } else if columns(stmt) != columns(variant) {
+        "a variant changes the columns"
+    } else {
+        return Ok(());
+    };
+    Err(invalid_attribute(
+        info,
+        attribute,
+        attribute.name.span,
+        msg,
+        "variants are substituted in the same statement, so they must keep its parameters and columns",
+    ))
+}
+
+/// Audited queries report the rows they affect, so they can't return rows
+pub(crate) fn audited_query(
+    info: &ModuleInfo,
+    name: &Span<String>,
+    attributes: &[Attribute],
+    columns: &[Column],
+    params: &[(Span<String>, Type)],
+) -> Result<(), Box<Error>> {
+    let Some(audit) = Attribute::find(attributes, "audit") else {
+        return Ok(());
+    };
+    if !columns.is_empty() {
+        return Err(Box::new(Error::AuditOnRows {
+            src: info.into(),
+            name: name.value.clone(),
+            attribute: audit.name.span,
+            query: name.span,
+        }));
+    }
+    if let AttributeValue::List(nested) = &audit.value {
+        for setting in nested {
+            if let AttributeValue::Value(actor) = &setting.value {
+                if params.iter().all(|(name, _)| name.value != actor.value) {
+                    return Err(Box::new(Error::UnknownFieldName {
+                        src: info.into(),
+                        pos: actor.span,
+                        known: params
+                            .iter()
+                            .map(|it| it.0.value.to_string())
+                            .collect::<Vec<_>>()
+                            .join(", "),
+                    }));
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+fn copy_attribute(info: &ModuleInfo, attribute: &Attribute) -> Result<(), Box<Error>> {
+    let help = "use `copy` or `copy(format = csv)`";
+    match &attribute.value {
+        AttributeValue::Flag => Ok(()),
+        AttributeValue::Value(value) => Err(invalid_attribute(
+            info,
+            attribute,
+            value.span,
+            "expected a list of settings",
+            help,
+        )),
+        AttributeValue::List(nested) => {
+            for setting in nested {
+                if setting.name.value != "format" {
+                    return Err(invalid_attribute(
+                        info,
+                        attribute,
+                        setting.name.span,
+                        "unknown setting",
+                        help,
+                    ));
+                }
+                match &setting.value {
+                    AttributeValue::Value(format)
+                        if matches!(format.value.as_str(), "binary" | "csv") => {}
+                    _ => {
+                        return Err(invalid_attribute(
+                            info,
+                            attribute,
+                            setting.name.span,
+                            "expected `binary` or `csv`",
+                            help,
+                        ))
+                    }
+                }
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Hints of pg_hint_plan, whose names are case insensitive
+const PLAN_HINTS: [&str; 25] = [
+    "SeqScan",
+    "TidScan",
+    "IndexScan",
+    "IndexOnlyScan",
+    "BitmapScan",
+    "IndexScanRegexp",
+    "IndexOnlyScanRegexp",
+    "BitmapScanRegexp",
+    "NoSeqScan",
+    "NoTidScan",
+    "NoIndexScan",
+    "NoIndexOnlyScan",
+    "NoBitmapScan",
+    "NestLoop",
+    "HashJoin",
+    "MergeJoin",
+    "NoNestLoop",
+    "NoHashJoin",
+    "NoMergeJoin",
+    "Leading",
+    "Memoize",
+    "NoMemoize",
+    "Rows",
+    "Parallel",
+    "Set",
+];
+
+/// Queries generated for the upstream client crates can't be scripts, nor use the attributes
+/// or the types needing this fork's client crates, e.g. ranges
+pub(crate) fn upstream_compat<'a>(
+    info: &ModuleInfo,
+    name: &Span<String>,
+    attributes: &[Attribute],
+    is_script: bool,
+    mut types: impl Iterator<Item = &'a CornucopiaType>,
+) -> Result<(), Box<Error>> {
+    let incompatible = |feature: String, pos: SourceSpan| {
+        Err(Box::new(Error::UpstreamIncompatible {
+            src: info.into(),
+            name: name.value.clone(),
+            feature,
+            pos,
+        }))
+    };
+    if is_script {
+        return incompatible("scripts".to_string(), name.span);
+    }
+    if let Some(attribute) = attributes
+        .iter()
+        .find(|it| ["audit", "budget", "paginate", "validate"].contains(&it.name.value.as_str()))
+    {
+        return incompatible(
+            format!("the `{}` attribute", attribute.name.value),
+            attribute.name.span,
+        );
+    }
+    if let Some(ty) = types.find(|it| it.needs_fork_client()) {
+        return incompatible(format!("the `{}` type", ty.pg_ty()), name.span);
+    }
+    Ok(())
+}
+
+/// Plan hint comments must start their query, the only place pg_hint_plan reads them from,
+/// and only hold its hints, written `Name(arguments)`
+pub(crate) fn plan_hints(
+    info: &ModuleInfo,
+    name: &Span<String>,
+    sql_span: &SourceSpan,
+    sql: &str,
+) -> Result<(), Box<Error>> {
+    for hint in sql_analysis::hints(sql) {
+        let invalid = |msg: String, help: String| {
+            Box::new(Error::InvalidHint {
+                src: info.into(),
+                name: name.value.clone(),
+                msg,
+                query: *sql_span,
+                help,
+            })
+        };
+        if !hint.leading {
+            return Err(invalid(
+                "hint comment not starting the query".to_string(),
+                "pg_hint_plan only reads the hints of the comment starting the query, move it before the statement".to_string(),
+            ));
+        }
+        let names = hint_names(&hint.body).ok_or_else(|| {
+            invalid(
+                format!("malformed hints `{}`", hint.body.trim()),
+                "write hints as `Name(arguments)`, e.g. `/*+ SeqScan(t) */`".to_string(),
+            )
+        })?;
+        if let Some(unknown) = names
+            .iter()
+            .find(|it| !PLAN_HINTS.iter().any(|hint| hint.eq_ignore_ascii_case(it)))
+        {
+            return Err(invalid(
+                format!("unknown hint `{unknown}`"),
+                format!("use one of those hints: `{}`", PLAN_HINTS.join("`, `")),
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Names of the hints of the body of a hint comment, if their arguments are parenthesized
+fn hint_names(body: &str) -> Option<Vec<&str>> {
+    let mut names = Vec::new();
+    let mut rest = body.trim_start();
+    while !rest.is_empty() {
+        let end = rest
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+            .unwrap_or(rest.len());
+        if end == 0 {
+            return None;
+        }
+        names.push(&rest[..end]);
+        rest = rest[end..].trim_start().strip_prefix('(')?;
+        // Arguments end at the matching parenthesis, outside of quoted names
+        let mut depth = 1;
+        let mut quoted = false;
+        let close = rest.char_indices().find_map(|(i, c)| {
+            match c {
+                '"' => quoted = !quoted,
+                '(' if !quoted => depth += 1,
+                ')' if !quoted => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return Some(i);
+                    }
+                }
+                _ => {}
+            }
+            None
+        })?;
+        rest = rest[close + 1..].trim_start();
+    }
+    Some(names)
+}
+
+/// Copy queries must be plain `COPY ... FROM STDIN` or `COPY ... TO STDOUT` statements,
+/// returning their target. Only typed binary copies can be written.
+pub(crate) fn copy_query(
+    info: &ModuleInfo,
+    name: &Span<String>,
+    sql_span: &SourceSpan,
+    attributes: &[Attribute],
+    sql: &str,
+    bind_params: &[Span<String>],
+) -> Result<Option<CopyTarget>, Box<Error>> {
+    let Some(copy) = Attribute::find(attributes, "copy") else {
+        return Ok(None);
+    };
+    let target = sql_analysis::copy(sql).ok_or_else(|| {
+        Box::new(Error::InvalidCopy {
+            src: info.into(),
+            name: name.value.clone(),
+            attribute: copy.name.span,
+            query: *sql_span,
+        })
+    })?;
+    match &target {
+        CopyTarget::In(_) if copy.nested_value("format") == Some("csv") => Err(invalid_attribute(
+            info,
+            copy,
+            copy.name.span,
+            "csv is only supported when copying out",
+            "use `copy` to write rows",
+        )),
+        CopyTarget::Out(_) if !bind_params.is_empty() => Err(Box::new(Error::ParamsOnCopyOut {
+            src: info.into(),
+            name: name.value.clone(),
+            // Binding spans are relative to the query, and don't include the colon
+            param: (
+                sql_span.offset() + bind_params[0].span.offset() - 1,
+                bind_params[0].span.len() + 1,
+            )
+                .into(),
+        })),
+        _ => Ok(Some(target)),
+    }
+}
+
+fn paginate_attribute(info: &ModuleInfo, attribute: &Attribute) -> Result<(), Box<Error>> {
+    let help = "use `paginate(by(column, ...))`";
+    let AttributeValue::List(nested) = &attribute.value else {
+        return Err(invalid_attribute(
+            info,
+            attribute,
+            attribute.name.span,
+            "expected a list of settings",
+            help,
+        ));
+    };
+    for setting in nested {
+        if setting.name.value != "by" {
+            return Err(invalid_attribute(
+                info,
+                attribute,
+                setting.name.span,
+                "unknown setting",
+                help,
+            ));
+        }
+        match &setting.value {
+            AttributeValue::List(columns)
+                if !columns.is_empty()
+                    && columns
+                        .iter()
+                        .all(|it| matches!(it.value, AttributeValue::Flag)) => {}
+            _ => {
+                return Err(invalid_attribute(
+                    info,
+                    attribute,
+                    setting.name.span,
+                    "expected a list of columns",
+                    help,
+                ))
+            }
+        }
+    }
+    if Attribute::find(nested, "by").is_none() {
+        return Err(invalid_attribute(
+            info,
+            attribute,
+            attribute.name.span,
+            "missing `by` columns",
+            help,
+        ));
+    }
+    Ok(())
+}
+
+/// Finds the position of the row columns a query annotated with `paginate` is ordered by
+pub(crate) fn paginated_query(
+    info: &ModuleInfo,
+    name: &Span<String>,
+    attributes: &[Attribute],
+    columns: &[Column],
+    fields: &[PreparedField],
+) -> Result<Option<Vec<usize>>, Box<Error>> {
+    let Some(paginate) = Attribute::find(attributes, "paginate") else {
+        return Ok(None);
+    };
+    if columns.is_empty() {
+        return Err(Box::new(Error::PaginateWithoutRows {
+            src: info.into(),
+            name: name.value.clone(),
+            attribute: paginate.name.span,
+            query: name.span,
+        }));
+    }
+    let AttributeValue::List(nested) = &paginate.value else {
+        return Ok(None);
+    };
+    let Some(AttributeValue::List(by)) = Attribute::find(nested, "by").map(|it| &it.value) else {
+        return Ok(None);
+    };
+    let mut positions = Vec::new();
+    for column in by {
+        let Some(idx) = columns.iter().position(|it| it.name() == column.name.value) else {
+            return Err(Box::new(Error::UnknownFieldName {
+                src: info.into(),
+                pos: column.name.span,
+                known: columns
+                    .iter()
+                    .map(|it| it.name().to_string())
+                    .collect::<Vec<_>>()
+                    .join(", "),
+            }));
+        };
+        if fields[idx].is_nullable {
+            return Err(Box::new(Error::NullablePaginateColumn {
+                src: info.into(),
+                name: name.value.clone(),
+                column: column.name.span,
+            }));
+        }
+        positions.push(idx);
+    }
+    Ok(Some(positions))
+}
+
+fn renamed_attribute(info: &ModuleInfo, attribute: &Attribute) -> Result<(), Box<Error>> {
+    let help = "use `renamed(query = old_name, row = OldRow)`";
+    let AttributeValue::List(nested) = &attribute.value else {
+        return Err(invalid_attribute(
+            info,
+            attribute,
+            attribute.name.span,
+            "expected a list of previous names",
+            help,
+        ));
+    };
+    if nested.is_empty() {
+        return Err(invalid_attribute(
+            info,
+            attribute,
+            attribute.name.span,
+            "missing previous names",
+            help,
+        ));
+    }
+    for setting in nested {
+        if !matches!(setting.name.value.as_str(), "query" | "row") {
+            return Err(invalid_attribute(
+                info,
+                attribute,
+                setting.name.span,
+                "unknown setting",
+                help,
+            ));
+        }
+        if !matches!(setting.value, AttributeValue::Value(_)) {
+            return Err(invalid_attribute(
+                info,
+                attribute,
+                setting.name.span,
+                "expected a previous name",
+                help,
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// A renamed row is aliased by its previous name, so the query must return a row struct
+pub(crate) fn renamed_query(
+    info: &ModuleInfo,
+    attributes: &[Attribute],
+    has_row_struct: bool,
+) -> Result<(), Box<Error>> {
+    let Some(renamed) = Attribute::find(attributes, "renamed") else {
+        return Ok(());
+    };
+    match renamed.nested_span("row") {
+        Some(row) if !has_row_struct => Err(invalid_attribute(
+            info,
+            renamed,
+            row.span,
+            "this query doesn't return a row struct",
+            "only rename the query with `renamed(query = old_name)`",
+        )),
+        _ => Ok(()),
+    }
+}
+
+fn derive_attribute(info: &ModuleInfo, attribute: &Attribute) -> Result<(), Box<Error>> {
+    let help = "derive traits by name or path, e.g. `derive(Hash, serde::Deserialize)`";
+    let AttributeValue::List(nested) = &attribute.value else {
+        return Err(invalid_attribute(
+            info,
+            attribute,
+            attribute.name.span,
+            "expected a list of traits",
+            help,
+        ));
+    };
+    if let Some(setting) = nested
+        .iter()
+        .find(|it| !matches!(it.value, AttributeValue::Flag))
+    {
+        return Err(invalid_attribute(
+            info,
+            attribute,
+            setting.name.span,
+            "expected the path of a trait",
+            help,
+        ));
+    }
+    Ok(())
+}
+
+/// Traits can only be derived by the row struct of a query
+pub(crate) fn derive_query(
+    info: &ModuleInfo,
+    attributes: &[Attribute],
+    has_row_struct: bool,
+) -> Result<(), Box<Error>> {
+    match Attribute::find(attributes, "derive") {
+        Some(derive) if !has_row_struct => Err(invalid_attribute(
+            info,
+            derive,
+            derive.name.span,
+            "this query doesn't return a row struct",
+            "only derive traits for the named rows of queries",
+        )),
+        _ => Ok(()),
+    }
+}
+
+fn budget_attribute(info: &ModuleInfo, attribute: &Attribute) -> Result<(), Box<Error>> {
+    let help = "use `budget = 50ms`, in `us`, `ms` or `s`";
+    match &attribute.value {
+        AttributeValue::Value(value) if parse_duration(&value.value).is_some() => Ok(()),
+        AttributeValue::Value(value) => Err(invalid_attribute(
+            info,
+            attribute,
+            value.span,
+            "invalid duration",
+            help,
+        )),
+        _ => Err(invalid_attribute(
+            info,
+            attribute,
+            attribute.name.span,
+            "expected a duration",
+            help,
+        )),
+    }
+}
+
+fn constraints_attribute(info: &ModuleInfo, attribute: &Attribute) -> Result<(), Box<Error>> {
+    let help =
+        "use `constraints(author_name_key = DuplicateName, book_author_id_fkey = UnknownAuthor)`";
+    let nested = match &attribute.value {
+        AttributeValue::List(nested) if !nested.is_empty() => nested,
+        _ => {
+            return Err(invalid_attribute(
+                info,
+                attribute,
+                attribute.name.span,
+                "expected a list of constraints",
+                help,
+            ))
+        }
+    };
+    let mut variants = Vec::new();
+    for constraint in nested {
+        let AttributeValue::Value(variant) = &constraint.value else {
+            return Err(invalid_attribute(
+                info,
+                attribute,
+                constraint.name.span,
+                "expected the name of its variant",
+                help,
+            ));
+        };
+        let is_variant = variant
+            .value
+            .chars()
+            .next()
+            .is_some_and(|c| c.is_ascii_uppercase())
+            && variant
+                .value
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_');
+        if !is_variant || variant.value == "Other" {
+            return Err(invalid_attribute(
+                info,
+                attribute,
+                variant.span,
+                &format!("`{}` isn't a valid variant name", variant.value),
+                "use an UpperCamelCase identifier other than `Other`, which holds the remaining errors",
+            ));
+        }
+        variants.push(variant);
+    }
+    find_duplicate(nested, |a, b| a.name == b.name)
+        .map(|(first, second)| ("constraint", &first.name, &second.name))
+        .or_else(|| {
+            find_duplicate(&variants, |a, b| a.value == b.value)
+                .map(|(first, second)| ("variant", *first, *second))
+        })
+        .map_or(Ok(()), |(ty, first, second)| {
+            Err(Box::new(Error::DuplicateType {
+                src: info.into(),
+                ty,
+                name: first.value.clone(),
+                first: first.span,
+                second: second.span,
+            }))
+        })
+}
+
+fn validate_attribute(info: &ModuleInfo, attribute: &Attribute) -> Result<(), Box<Error>> {
+    let help = "use `validate = \"len(email) <= 255, age >= 18\"`, comparing parameters or their length to numbers";
+    match &attribute.value {
+        AttributeValue::Value(checks) if ParamCheck::parse_all(&checks.value).is_some() => Ok(()),
+        AttributeValue::Value(checks) => Err(invalid_attribute(
+            info,
+            attribute,
+            checks.span,
+            "invalid checks",
+            help,
+        )),
+        _ => Err(invalid_attribute(
+            info,
+            attribute,
+            attribute.name.span,
+            "expected checks",
+            help,
+        )),
+    }
+}
+
+/// The checks of the `validate` attribute compare the length of text and `bytea` parameters,
+/// or the value of numeric ones
+pub(crate) fn validated_params(
+    info: &ModuleInfo,
+    attributes: &[Attribute],
+    params: &[PreparedField],
+) -> Result<(), Box<Error>> {
+    let Some(attribute) = Attribute::find(attributes, "validate") else {
+        return Ok(());
+    };
+    let AttributeValue::Value(checks) = &attribute.value else {
+        return Ok(());
+    };
+    for check in ParamCheck::parse_all(&checks.value).unwrap_or_default() {
+        let Some(param) = params.iter().find(|it| it.ident.db == check.param) else {
+            return Err(Box::new(Error::UnknownFieldName {
+                src: info.into(),
+                pos: checks.span,
+                known: params
+                    .iter()
+                    .map(|it| it.ident.db.clone())
+                    .collect::<Vec<_>>()
+                    .join(", "),
+            }));
+        };
+        let kind = param.ty.checked_kind();
+        let msg = match kind {
+            Some(CheckedKind::Text | CheckedKind::Bytes) if check.len => continue,
+            Some(CheckedKind::Integer) if !check.len && !check.value.contains('.') => continue,
+            Some(CheckedKind::Float) if !check.len => continue,
+            Some(CheckedKind::Integer) if !check.len => "integers can't be compared to decimals",
+            Some(CheckedKind::Text | CheckedKind::Bytes) => {
+                "text and `bytea` parameters are checked by their length"
+            }
+            _ if check.len => "only text and `bytea` parameters have a length",
+            _ => "only numeric parameters can be compared",
+        };
+        return Err(invalid_attribute(
+            info,
+            attribute,
+            checks.span,
+            &format!(
+                "parameter `{}` can't be checked by `{}`, {msg}",
+                check.param, check.text
+            ),
+            "use `len(param)` for text and `bytea` parameters, and `param` for integers and floats",
+        ));
+    }
+    Ok(())
+}
+
+fn deprecated_attribute(info: &ModuleInfo, attribute: &Attribute) -> Result<(), Box<Error>> {
+    match &attribute.value {
+        AttributeValue::Flag | AttributeValue::Value(_) => Ok(()),
+        AttributeValue::List(_) => Err(invalid_attribute(
+            info,
+            attribute,
+            attribute.name.span,
+            "expected a note",
+            "use `deprecated` or `deprecated = \"use new_query\"`",
+        )),
+    }
+}
+
+fn session_attribute(info: &ModuleInfo, attribute: &Attribute) -> Result<(), Box<Error>> {
+    let help = "use `session(timezone = \"UTC\", search_path = app)`";
+    let nested = match &attribute.value {
+        AttributeValue::List(nested) if !nested.is_empty() => nested,
+        _ => {
+            return Err(invalid_attribute(
+                info,
+                attribute,
+                attribute.name.span,
+                "expected a list of settings",
+                help,
+            ))
+        }
+    };
+    for setting in nested {
+        if !matches!(setting.value, AttributeValue::Value(_)) {
+            return Err(invalid_attribute(
+                info,
+                attribute,
+                setting.name.span,
+                "expected a value",
+                help,
+            ));
+        }
+    }
+    find_duplicate(nested, |a, b| a.name == b.name).map_or(Ok(()), |(first, second)| {
+        Err(Box::new(Error::DuplicateType {
+            src: info.into(),
+            ty: "setting",
+            name: first.name.value.clone(),
+            first: first.name.span,
+            second: second.name.span,
+        }))
+    })
+}
+
+fn trace_attribute(info: &ModuleInfo, attribute: &Attribute) -> Result<(), Box<Error>> {
+    let help = "use `trace(level = \"info\", target = \"my_app::sql\")`";
+    let AttributeValue::List(nested) = &attribute.value else {
+        return Err(invalid_attribute(
+            info,
+            attribute,
+            attribute.name.span,
+            "expected a list of settings",
+            help,
+        ));
+    };
+    for setting in nested {
+        let AttributeValue::Value(value) = &setting.value else {
+            return Err(invalid_attribute(
+                info,
+                attribute,
+                setting.name.span,
+                "expected a value",
+                help,
+            ));
+        };
+        match setting.name.value.as_str() {
+            "level" => {
+                if !TRACE_LEVELS.contains(&value.value.as_str()) {
+                    return Err(invalid_attribute(
+                        info,
+                        attribute,
+                        value.span,
+                        "unknown level",
+                        &format!("use one of those levels: {}", TRACE_LEVELS.join(", ")),
+                    ));
+                }
+            }
+            "target" => {}
+            _ => {
+                return Err(invalid_attribute(
+                    info,
+                    attribute,
+                    setting.name.span,
+                    "unknown setting",
+                    help,
+                ))
+            }
+        }
+    }
+    Ok(())
+}
+
 pub(crate) fn validate_preparation(module: &PreparedModule) -> Result<(), Box<Error>> {
     // Check generated name clash
     let mut name_registrar = BTreeMap::new();
@@ -298,6 +1454,31 @@ pub(crate) fn validate_preparation(module: &PreparedModule) -> Result<(), Box<Er
             origin.span,
             "statement",
         )?;
+        for slot in &query.slots {
+            check_name(slot.enum_name.clone(), slot.span, "variants")?;
+        }
+        let Some(renamed) = &query.renamed else {
+            continue;
+        };
+        if let Some(old) = &renamed.query {
+            check_name(
+                format!("{}Stmt", old.value.to_upper_camel_case()),
+                old.span,
+                "renamed statement",
+            )?;
+        }
+        if let (Some(old), Some((idx, _))) = (&renamed.row, &query.row) {
+            let row = &module.rows[*idx];
+            check_name(old.value.clone(), old.span, "renamed row")?;
+            if !row.is_copy {
+                check_name(
+                    format!("{}Borrowed", old.value),
+                    old.span,
+                    "renamed borrowed row",
+                )?;
+            }
+            check_name(format!("{}Query", old.value), old.span, "renamed query")?;
+        }
     }
     for (origin, row) in &module.rows {
         reserved_type_keyword(&module.info, origin)?;
@@ -330,14 +1511,43 @@ pub(crate) fn validate_module(
         info,
         types,
         queries,
+        attributes: module_attributes,
+        ..
     }: &Module,
 ) -> Result<(), Box<Error>> {
     query_name_already_used(info, queries)?;
     named_type_already_used(info, types)?;
+    attributes(info, module_attributes)?;
+    if let Some(attribute) = module_attributes
+        .iter()
+        .find(|it| QUERY_ATTRIBUTES.contains(&it.name.value.as_str()))
+    {
+        return Err(invalid_attribute(
+            info,
+            attribute,
+            attribute.name.span,
+            "only accepted on queries",
+            "move this attribute to the queries it applies to",
+        ));
+    }
     for ty in types {
         duplicate_nullable_ident(info, &ty.fields)?;
     }
     for query in queries {
+        attributes(info, &query.attributes)?;
+        if let Some(attribute) = query
+            .attributes
+            .iter()
+            .find(|it| MODULE_ATTRIBUTES.contains(&it.name.value.as_str()))
+        {
+            return Err(invalid_attribute(
+                info,
+                attribute,
+                attribute.name.span,
+                "only accepted on modules",
+                "move this attribute to a `--#!` line of the module",
+            ));
+        }
         for (it, ty) in [(&query.param, "param"), (&query.row, "row")] {
             if let Some(idents) = &it.idents {
                 duplicate_nullable_ident(info, idents)?;
@@ -448,6 +1658,142 @@ pub mod error {
             #[label("but query has no binding")]
             query: SourceSpan,
         },
+        #[error("unknown attribute `{name}`")]
+        #[diagnostic(help("use one of those attributes: {known}"))]
+        UnknownAttribute {
+            #[source_code]
+            src: NamedSource,
+            name: String,
+            #[label("unknown attribute")]
+            pos: SourceSpan,
+            known: String,
+        },
+        #[error("the column `{column}` of the query `{name}` is an anonymous record")]
+        #[diagnostic(help(
+            "declare the Rust types of its fields as a tuple, e.g. `--! {name} : ({column}: (i32, String))`"
+        ))]
+        UndeclaredRecord {
+            #[source_code]
+            src: NamedSource,
+            name: String,
+            column: String,
+            #[label("decoded as a tuple of the types of its fields only")]
+            pos: SourceSpan,
+        },
+        #[error("invalid `{name}` attribute")]
+        InvalidAttribute {
+            #[source_code]
+            src: NamedSource,
+            name: String,
+            msg: String,
+            #[label("{msg}")]
+            pos: SourceSpan,
+            #[help]
+            help: String,
+        },
+        #[error("the query `{name}` is audited but returns rows")]
+        #[diagnostic(help(
+            "only audit queries that return nothing, like `UPDATE` without `RETURNING`"
+        ))]
+        AuditOnRows {
+            #[source_code]
+            src: NamedSource,
+            name: String,
+            #[label("audit declared here")]
+            attribute: SourceSpan,
+            #[label("but query returns rows")]
+            query: SourceSpan,
+        },
+        #[error("the query `{name}` is paginated but returns no rows")]
+        #[diagnostic(help("only paginate queries that return rows, like `SELECT`"))]
+        PaginateWithoutRows {
+            #[source_code]
+            src: NamedSource,
+            name: String,
+            #[label("paginate declared here")]
+            attribute: SourceSpan,
+            #[label("but query returns nothing")]
+            query: SourceSpan,
+        },
+        #[error("the query `{name}` is paginated by a nullable column")]
+        #[diagnostic(help(
+            "keyset pagination needs non-nullable columns, use `COALESCE` or another column"
+        ))]
+        NullablePaginateColumn {
+            #[source_code]
+            src: NamedSource,
+            name: String,
+            #[label("this column is nullable")]
+            column: SourceSpan,
+        },
+        #[error("the query `{name}` is a copy but isn't a `COPY ... FROM STDIN` or `COPY ... TO STDOUT` statement")]
+        #[diagnostic(help(
+            "use `COPY table (columns) FROM STDIN` or `COPY (query) TO STDOUT`, without options as the format is set for you"
+        ))]
+        InvalidCopy {
+            #[source_code]
+            src: NamedSource,
+            name: String,
+            #[label("copy declared here")]
+            attribute: SourceSpan,
+            #[label("unsupported statement")]
+            query: SourceSpan,
+        },
+        #[error("the query `{name}` has invalid plan hints")]
+        InvalidHint {
+            #[source_code]
+            src: NamedSource,
+            name: String,
+            msg: String,
+            #[label("{msg}")]
+            query: SourceSpan,
+            #[help]
+            help: String,
+        },
+        #[error("the query `{name}` copies out but has a binding")]
+        #[diagnostic(help("`COPY` doesn't support bindings, use a regular query instead"))]
+        ParamsOnCopyOut {
+            #[source_code]
+            src: NamedSource,
+            name: String,
+            #[label("binding used here")]
+            param: SourceSpan,
+        },
+        #[error("the query `{name}` is a script but has a binding")]
+        #[diagnostic(help(
+            "scripts don't support bindings, use a query per statement needing them instead"
+        ))]
+        ParamsOnScript {
+            #[source_code]
+            src: NamedSource,
+            name: String,
+            #[label("binding used here")]
+            param: SourceSpan,
+        },
+        #[error("the script `{name}` controls its transaction with `{keyword}`")]
+        #[diagnostic(help(
+            "scripts run in the transaction of their client, start and end it from Rust instead"
+        ))]
+        TransactionControlOnScript {
+            #[source_code]
+            src: NamedSource,
+            name: String,
+            keyword: String,
+            #[label("statement used here")]
+            statement: SourceSpan,
+        },
+        #[error("the query `{name}` can't be generated for the upstream client crates")]
+        #[diagnostic(help(
+            "only this fork's client crates support {feature}, generate the query without upstream compatibility"
+        ))]
+        UpstreamIncompatible {
+            #[source_code]
+            src: NamedSource,
+            name: String,
+            feature: String,
+            #[label("needs this fork's client crates")]
+            pos: SourceSpan,
+        },
         #[error("`{name}` is used multiple time")]
         #[diagnostic(help("use a different name for one of those"))]
         DuplicateName {
@@ -0,0 +1,75 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+    thread,
+    time::{Duration, SystemTime},
+};
+
+use crate::error::Error;
+
+/// Delay between two looks at the watched files
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Modification times of the watched files
+type Snapshot = BTreeMap<PathBuf, SystemTime>;
+
+/// Modification times of the files at `paths`, walking the folders. Missing files are
+/// skipped, so that creating them counts as a change.
+fn snapshot(paths: &[PathBuf]) -> Snapshot {
+    let mut snapshot = Snapshot::new();
+    for path in paths {
+        visit(path, &mut snapshot);
+    }
+    snapshot
+}
+
+fn visit(path: &Path, snapshot: &mut Snapshot) {
+    let Ok(metadata) = fs::metadata(path) else {
+        return;
+    };
+    if metadata.is_dir() {
+        for entry in fs::read_dir(path).into_iter().flatten().flatten() {
+            visit(&entry.path(), snapshot);
+        }
+    } else if let Ok(modified) = metadata.modified() {
+        snapshot.insert(path.to_owned(), modified);
+    }
+}
+
+/// Calls `generate`, then calls it again whenever the files at `paths` change, once they
+/// stopped changing for a poll interval so that saving several files regenerates once.
+/// Errors are reported, also by `report`, without stopping to watch.
+pub(crate) fn watch(
+    paths: &[PathBuf],
+    mut generate: impl FnMut() -> Result<(), Error>,
+    report: impl Fn(&Error),
+) -> ! {
+    let mut last = snapshot(paths);
+    loop {
+        match generate() {
+            Ok(()) => println!("Generated the queries, watching for changes"),
+            Err(err) => {
+                report(&err);
+                eprintln!("{:?}", miette::Report::new(err));
+            }
+        }
+        // Wait for some change, then for the files to settle
+        loop {
+            thread::sleep(POLL_INTERVAL);
+            let current = snapshot(paths);
+            if current != last {
+                last = current;
+                break;
+            }
+        }
+        loop {
+            thread::sleep(POLL_INTERVAL);
+            let current = snapshot(paths);
+            if current == last {
+                break;
+            }
+            last = current;
+        }
+    }
+}
@@ -20,6 +20,8 @@ pub mod queries {
             stmt: &'a mut cornucopia_async::private::Stmt,
             extractor: fn(&tokio_postgres::Row) -> &str,
             mapper: fn(&str) -> T,
+            info: cornucopia_async::QueryInfo,
+            timeout: Option<(std::time::Duration, tokio_postgres::CancelToken)>,
         }
         impl<'a, C, T: 'a, const N: usize> StringQuery<'a, C, T, N>
         where
@@ -32,23 +34,64 @@ pub mod queries {
                     stmt: self.stmt,
                     extractor: self.extractor,
                     mapper,
+                    info: self.info,
+                    timeout: self.timeout,
                 }
             }
             pub async fn one(self) -> Result<T, tokio_postgres::Error> {
-                let stmt = self.stmt.prepare(self.client).await?;
-                let row = self.client.query_one(stmt, &self.params).await?;
-                Ok((self.mapper)((self.extractor)(&row)))
+                let info = self.info;
+                cornucopia_async::private::observe(&info, |_| Some(1), async move {
+                    cornucopia_async::private::timeout(self.timeout.clone(), async move {
+                        let row = self
+                            .stmt
+                            .run(self.client, |client, stmt| async move {
+                                let stmt = &stmt;
+                                let params: &[&(dyn postgres_types::ToSql + Sync)] = &self.params;
+                                client.query_one(stmt, params).await
+                            })
+                            .await?;
+                        Ok((self.mapper)((self.extractor)(&row)))
+                    })
+                    .await
+                })
+                .await
             }
             pub async fn all(self) -> Result<Vec<T>, tokio_postgres::Error> {
-                self.iter().await?.try_collect().await
+                let info = self.info;
+                cornucopia_async::private::observe(
+                    &info,
+                    |it: &Vec<T>| Some(it.len() as u64),
+                    async move {
+                        cornucopia_async::private::timeout(self.timeout.clone(), async move {
+                            self.raw_iter().await?.try_collect().await
+                        })
+                        .await
+                    },
+                )
+                .await
             }
             pub async fn opt(self) -> Result<Option<T>, tokio_postgres::Error> {
-                let stmt = self.stmt.prepare(self.client).await?;
-                Ok(self
-                    .client
-                    .query_opt(stmt, &self.params)
-                    .await?
-                    .map(|row| (self.mapper)((self.extractor)(&row))))
+                let info = self.info;
+                cornucopia_async::private::observe(
+                    &info,
+                    |it: &Option<T>| Some(it.is_some().into()),
+                    async move {
+                        cornucopia_async::private::timeout(self.timeout.clone(), async move {
+                            Ok(self
+                                .stmt
+                                .run(self.client, |client, stmt| async move {
+                                    let stmt = &stmt;
+                                    let params: &[&(dyn postgres_types::ToSql + Sync)] =
+                                        &self.params;
+                                    client.query_opt(stmt, params).await
+                                })
+                                .await?
+                                .map(|row| (self.mapper)((self.extractor)(&row))))
+                        })
+                        .await
+                    },
+                )
+                .await
             }
             pub async fn iter(
                 self,
@@ -56,16 +99,110 @@ pub mod queries {
                 impl futures::Stream<Item = Result<T, tokio_postgres::Error>> + 'a,
                 tokio_postgres::Error,
             > {
-                let stmt = self.stmt.prepare(self.client).await?;
+                let info = self.info;
+                cornucopia_async::private::observe(&info, |_| None, async move {
+                    cornucopia_async::private::timeout(self.timeout.clone(), async move {
+                        self.raw_iter().await
+                    })
+                    .await
+                })
+                .await
+            }
+            /// Passes each row to `visitor` as it is read, without building owned values,
+            /// returning the number of rows.
+            pub async fn for_each(
+                self,
+                mut visitor: impl FnMut(&str),
+            ) -> Result<u64, tokio_postgres::Error> {
+                let info = self.info;
+                cornucopia_async::private::observe(&info, |it: &u64| Some(*it), async move {
+                    cornucopia_async::private::timeout(self.timeout.clone(), async move {
+                        let rows = self
+                            .stmt
+                            .run(self.client, |client, stmt| async move {
+                                let stmt = &stmt;
+                                let params: &[&(dyn postgres_types::ToSql + Sync)] = &self.params;
+                                client
+                                    .query_raw(stmt, cornucopia_async::private::slice_iter(params))
+                                    .await
+                            })
+                            .await?;
+                        let mut count = 0;
+                        let mut rows = std::pin::pin!(rows);
+                        while let Some(row) = rows.try_next().await? {
+                            visitor((self.extractor)(&row));
+                            count += 1;
+                        }
+                        Ok(count)
+                    })
+                    .await
+                })
+                .await
+            }
+            async fn raw_iter(
+                self,
+            ) -> Result<
+                impl futures::Stream<Item = Result<T, tokio_postgres::Error>> + 'a,
+                tokio_postgres::Error,
+            > {
                 let it = self
-                    .client
-                    .query_raw(stmt, cornucopia_async::private::slice_iter(&self.params))
+                    .stmt
+                    .run(self.client, |client, stmt| async move {
+                        let stmt = &stmt;
+                        let params: &[&(dyn postgres_types::ToSql + Sync)] = &self.params;
+                        client
+                            .query_raw(stmt, cornucopia_async::private::slice_iter(params))
+                            .await
+                    })
                     .await?
                     .map(move |res| res.map(|row| (self.mapper)((self.extractor)(&row))))
                     .into_stream();
                 Ok(it)
             }
         }
+        impl<'a, C, T: 'a, const N: usize> StringQuery<'a, C, T, N>
+        where
+            C: cornucopia_async::CancelClient,
+        {
+            /// Cancels the query on the server if it runs longer than `timeout`, failing with
+            /// the cancellation error. `iter` and `cursor` only bound starting the query, not
+            /// reading its rows.
+            pub fn timeout(mut self, timeout: std::time::Duration) -> Self {
+                self.timeout = Some((timeout, self.client.cancel_token()));
+                self
+            }
+        }
+        impl<'a, C, T: 'a, const N: usize> StringQuery<'a, C, T, N>
+        where
+            C: cornucopia_async::CursorClient,
+        {
+            /// Fetch rows through a portal, `batch_size` rows at a time.
+            pub async fn cursor(
+                self,
+                batch_size: i32,
+            ) -> Result<
+                impl futures::Stream<Item = Result<T, tokio_postgres::Error>> + 'a,
+                tokio_postgres::Error,
+            > {
+                let info = self.info;
+                cornucopia_async::private::observe(&info, |_| None, async move {
+                    cornucopia_async::private::timeout(self.timeout.clone(), async move {
+                        let stmt = self.stmt.prepare(self.client).await?;
+                        let it = cornucopia_async::private::cursor(
+                            self.client,
+                            stmt,
+                            &self.params,
+                            batch_size,
+                        )
+                        .await?
+                        .map(move |res| res.map(|row| (self.mapper)((self.extractor)(&row))));
+                        Ok(it)
+                    })
+                    .await
+                })
+                .await
+            }
+        }
         pub fn example_query() -> ExampleQueryStmt {
             ExampleQueryStmt(cornucopia_async::private::Stmt::new(
                 "SELECT
@@ -76,6 +213,11 @@ FROM
         }
         pub struct ExampleQueryStmt(cornucopia_async::private::Stmt);
         impl ExampleQueryStmt {
+            /// Uses `sql`, e.g. composed at runtime, instead of the annotated query.
+            /// It is prepared as is, so it must take the same parameters and return the same columns.
+            pub fn with_sql(sql: impl Into<std::borrow::Cow<'static, str>>) -> Self {
+                Self(cornucopia_async::private::Stmt::new(sql))
+            }
             pub fn bind<'a, C: GenericClient>(
                 &'a mut self,
                 client: &'a C,
@@ -86,8 +228,18 @@ FROM
                     stmt: &mut self.0,
                     extractor: |row| row.get(0),
                     mapper: |it| it.into(),
+                    info: cornucopia_async::QueryInfo {
+                        module: "module_1",
+                        query: "example_query",
+                    },
+                    timeout: None,
                 }
             }
         }
     }
 }
+/// Flat re-exports of the generated items, to import them all with `use prelude::*`
+#[allow(unused_imports)]
+pub mod prelude {
+    pub use cornucopia_async::{GenericClient, Params as _};
+}
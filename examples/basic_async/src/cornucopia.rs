@@ -88,7 +88,7 @@ pub mod types {
                 }
             }
         }
-        #[derive(Debug, postgres_types :: FromSql, Clone, PartialEq)]
+        #[derive(Debug, postgres_types::FromSql, Clone, PartialEq)]
         #[postgres(name = "voiceactor")]
         pub struct Voiceactor {
             #[postgres(name = "name")]
@@ -117,6 +117,9 @@ pub mod types {
             {
                 let fields = match *ty.kind() {
                     postgres_types::Kind::Composite(ref fields) => fields,
+                    postgres_types::Kind::Domain(ref inner) => {
+                        return Self::from_sql(inner, out);
+                    }
                     _ => unreachable!(),
                 };
                 let mut out = out;
@@ -135,7 +138,10 @@ pub mod types {
                 Ok(VoiceactorBorrowed { name, age })
             }
             fn accepts(ty: &postgres_types::Type) -> bool {
-                ty.name() == "voiceactor" && ty.schema() == "public"
+                match *ty.kind() {
+                    postgres_types::Kind::Domain(ref inner) => Self::accepts(inner),
+                    _ => ty.name() == "voiceactor" && ty.schema() == "public",
+                }
             }
         }
         impl<'a> postgres_types::ToSql for VoiceactorBorrowed<'a> {
@@ -220,13 +226,71 @@ pub mod queries {
         }
         pub struct InsertBookStmt(cornucopia_async::private::Stmt);
         impl InsertBookStmt {
+            /// Uses `sql`, e.g. composed at runtime, instead of the annotated query.
+            /// It is prepared as is, so it must take the same parameters and return the same columns.
+            pub fn with_sql(sql: impl Into<std::borrow::Cow<'static, str>>) -> Self {
+                Self(cornucopia_async::private::Stmt::new(sql))
+            }
+            /// Shares the statements prepared on each connection through `cache`
+            pub(crate) fn with_cache(
+                self,
+                cache: &std::sync::Arc<cornucopia_async::private::StmtCache>,
+            ) -> Self {
+                Self(self.0.with_cache(cache))
+            }
             pub async fn bind<'a, C: GenericClient, T1: cornucopia_async::StringSql>(
                 &'a mut self,
                 client: &'a C,
                 title: &'a T1,
             ) -> Result<u64, tokio_postgres::Error> {
-                let stmt = self.0.prepare(client).await?;
-                client.execute(stmt, &[title]).await
+                cornucopia_async::private::observe(
+                    &cornucopia_async::QueryInfo {
+                        module: "module_1",
+                        query: "insert_book",
+                    },
+                    |rows| Some(*rows),
+                    async move {
+                        self.0
+                            .run(client, |client, stmt| async move {
+                                let stmt = &stmt;
+                                let params: &[&(dyn postgres_types::ToSql + Sync)] = &[title];
+                                client.execute(stmt, params).await
+                            })
+                            .await
+                    },
+                )
+                .await
+            }
+        }
+        /// Every statement of this module. Statements are prepared on the connection
+        /// they are first run on, so keep one bundle per connection.
+        pub struct Statements {
+            pub insert_book: InsertBookStmt,
+        }
+        impl Statements {
+            pub fn new() -> Self {
+                Self {
+                    insert_book: insert_book(),
+                }
+            }
+            /// Prepares every statement on `client`
+            pub async fn prepare_all<C: GenericClient>(
+                &mut self,
+                client: &C,
+            ) -> Result<(), tokio_postgres::Error> {
+                futures::future::try_join_all([self.insert_book.0.prepare(client)]).await?;
+                Ok(())
+            }
+        }
+        /// Statements of this module, prepared lazily once per connection and shared by the
+        /// clones of `Queries`, which are cheap
+        #[derive(Debug, Clone, Default)]
+        pub struct Queries {
+            cache: std::sync::Arc<cornucopia_async::private::StmtCache>,
+        }
+        impl Queries {
+            pub fn insert_book(&self) -> InsertBookStmt {
+                insert_book().with_cache(&self.cache)
             }
         }
     }
@@ -235,7 +299,7 @@ pub mod queries {
         pub struct AuthorNameStartingWithParams<T1: cornucopia_async::StringSql> {
             pub start_str: T1,
         }
-        #[derive(Debug, Clone, PartialEq)]
+        #[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
         pub struct Authors {
             pub id: i32,
             pub name: String,
@@ -255,7 +319,7 @@ pub mod queries {
                 }
             }
         }
-        #[derive(Debug, Clone, PartialEq)]
+        #[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
         pub struct AuthorNameStartingWith {
             pub authorid: i32,
             pub name: String,
@@ -285,7 +349,7 @@ pub mod queries {
                 }
             }
         }
-        #[derive(Debug, Clone, PartialEq)]
+        #[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
         pub struct SelectTranslations {
             pub title: String,
             pub translations: Vec<String>,
@@ -316,6 +380,8 @@ pub mod queries {
             stmt: &'a mut cornucopia_async::private::Stmt,
             extractor: fn(&tokio_postgres::Row) -> AuthorsBorrowed,
             mapper: fn(AuthorsBorrowed) -> T,
+            info: cornucopia_async::QueryInfo,
+            timeout: Option<(std::time::Duration, tokio_postgres::CancelToken)>,
         }
         impl<'a, C, T: 'a, const N: usize> AuthorsQuery<'a, C, T, N>
         where
@@ -328,23 +394,64 @@ pub mod queries {
                     stmt: self.stmt,
                     extractor: self.extractor,
                     mapper,
+                    info: self.info,
+                    timeout: self.timeout,
                 }
             }
             pub async fn one(self) -> Result<T, tokio_postgres::Error> {
-                let stmt = self.stmt.prepare(self.client).await?;
-                let row = self.client.query_one(stmt, &self.params).await?;
-                Ok((self.mapper)((self.extractor)(&row)))
+                let info = self.info;
+                cornucopia_async::private::observe(&info, |_| Some(1), async move {
+                    cornucopia_async::private::timeout(self.timeout.clone(), async move {
+                        let row = self
+                            .stmt
+                            .run(self.client, |client, stmt| async move {
+                                let stmt = &stmt;
+                                let params: &[&(dyn postgres_types::ToSql + Sync)] = &self.params;
+                                client.query_one(stmt, params).await
+                            })
+                            .await?;
+                        Ok((self.mapper)((self.extractor)(&row)))
+                    })
+                    .await
+                })
+                .await
             }
             pub async fn all(self) -> Result<Vec<T>, tokio_postgres::Error> {
-                self.iter().await?.try_collect().await
+                let info = self.info;
+                cornucopia_async::private::observe(
+                    &info,
+                    |it: &Vec<T>| Some(it.len() as u64),
+                    async move {
+                        cornucopia_async::private::timeout(self.timeout.clone(), async move {
+                            self.raw_iter().await?.try_collect().await
+                        })
+                        .await
+                    },
+                )
+                .await
             }
             pub async fn opt(self) -> Result<Option<T>, tokio_postgres::Error> {
-                let stmt = self.stmt.prepare(self.client).await?;
-                Ok(self
-                    .client
-                    .query_opt(stmt, &self.params)
-                    .await?
-                    .map(|row| (self.mapper)((self.extractor)(&row))))
+                let info = self.info;
+                cornucopia_async::private::observe(
+                    &info,
+                    |it: &Option<T>| Some(it.is_some().into()),
+                    async move {
+                        cornucopia_async::private::timeout(self.timeout.clone(), async move {
+                            Ok(self
+                                .stmt
+                                .run(self.client, |client, stmt| async move {
+                                    let stmt = &stmt;
+                                    let params: &[&(dyn postgres_types::ToSql + Sync)] =
+                                        &self.params;
+                                    client.query_opt(stmt, params).await
+                                })
+                                .await?
+                                .map(|row| (self.mapper)((self.extractor)(&row))))
+                        })
+                        .await
+                    },
+                )
+                .await
             }
             pub async fn iter(
                 self,
@@ -352,22 +459,118 @@ pub mod queries {
                 impl futures::Stream<Item = Result<T, tokio_postgres::Error>> + 'a,
                 tokio_postgres::Error,
             > {
-                let stmt = self.stmt.prepare(self.client).await?;
+                let info = self.info;
+                cornucopia_async::private::observe(&info, |_| None, async move {
+                    cornucopia_async::private::timeout(self.timeout.clone(), async move {
+                        self.raw_iter().await
+                    })
+                    .await
+                })
+                .await
+            }
+            /// Passes each row to `visitor` as it is read, without building owned values,
+            /// returning the number of rows.
+            pub async fn for_each(
+                self,
+                mut visitor: impl FnMut(AuthorsBorrowed),
+            ) -> Result<u64, tokio_postgres::Error> {
+                let info = self.info;
+                cornucopia_async::private::observe(&info, |it: &u64| Some(*it), async move {
+                    cornucopia_async::private::timeout(self.timeout.clone(), async move {
+                        let rows = self
+                            .stmt
+                            .run(self.client, |client, stmt| async move {
+                                let stmt = &stmt;
+                                let params: &[&(dyn postgres_types::ToSql + Sync)] = &self.params;
+                                client
+                                    .query_raw(stmt, cornucopia_async::private::slice_iter(params))
+                                    .await
+                            })
+                            .await?;
+                        let mut count = 0;
+                        let mut rows = std::pin::pin!(rows);
+                        while let Some(row) = rows.try_next().await? {
+                            visitor((self.extractor)(&row));
+                            count += 1;
+                        }
+                        Ok(count)
+                    })
+                    .await
+                })
+                .await
+            }
+            async fn raw_iter(
+                self,
+            ) -> Result<
+                impl futures::Stream<Item = Result<T, tokio_postgres::Error>> + 'a,
+                tokio_postgres::Error,
+            > {
                 let it = self
-                    .client
-                    .query_raw(stmt, cornucopia_async::private::slice_iter(&self.params))
+                    .stmt
+                    .run(self.client, |client, stmt| async move {
+                        let stmt = &stmt;
+                        let params: &[&(dyn postgres_types::ToSql + Sync)] = &self.params;
+                        client
+                            .query_raw(stmt, cornucopia_async::private::slice_iter(params))
+                            .await
+                    })
                     .await?
                     .map(move |res| res.map(|row| (self.mapper)((self.extractor)(&row))))
                     .into_stream();
                 Ok(it)
             }
         }
+        impl<'a, C, T: 'a, const N: usize> AuthorsQuery<'a, C, T, N>
+        where
+            C: cornucopia_async::CancelClient,
+        {
+            /// Cancels the query on the server if it runs longer than `timeout`, failing with
+            /// the cancellation error. `iter` and `cursor` only bound starting the query, not
+            /// reading its rows.
+            pub fn timeout(mut self, timeout: std::time::Duration) -> Self {
+                self.timeout = Some((timeout, self.client.cancel_token()));
+                self
+            }
+        }
+        impl<'a, C, T: 'a, const N: usize> AuthorsQuery<'a, C, T, N>
+        where
+            C: cornucopia_async::CursorClient,
+        {
+            /// Fetch rows through a portal, `batch_size` rows at a time.
+            pub async fn cursor(
+                self,
+                batch_size: i32,
+            ) -> Result<
+                impl futures::Stream<Item = Result<T, tokio_postgres::Error>> + 'a,
+                tokio_postgres::Error,
+            > {
+                let info = self.info;
+                cornucopia_async::private::observe(&info, |_| None, async move {
+                    cornucopia_async::private::timeout(self.timeout.clone(), async move {
+                        let stmt = self.stmt.prepare(self.client).await?;
+                        let it = cornucopia_async::private::cursor(
+                            self.client,
+                            stmt,
+                            &self.params,
+                            batch_size,
+                        )
+                        .await?
+                        .map(move |res| res.map(|row| (self.mapper)((self.extractor)(&row))));
+                        Ok(it)
+                    })
+                    .await
+                })
+                .await
+            }
+        }
         pub struct StringQuery<'a, C: GenericClient, T, const N: usize> {
             client: &'a C,
             params: [&'a (dyn postgres_types::ToSql + Sync); N],
             stmt: &'a mut cornucopia_async::private::Stmt,
             extractor: fn(&tokio_postgres::Row) -> &str,
             mapper: fn(&str) -> T,
+            info: cornucopia_async::QueryInfo,
+            timeout: Option<(std::time::Duration, tokio_postgres::CancelToken)>,
         }
         impl<'a, C, T: 'a, const N: usize> StringQuery<'a, C, T, N>
         where
@@ -380,23 +583,64 @@ pub mod queries {
                     stmt: self.stmt,
                     extractor: self.extractor,
                     mapper,
+                    info: self.info,
+                    timeout: self.timeout,
                 }
             }
             pub async fn one(self) -> Result<T, tokio_postgres::Error> {
-                let stmt = self.stmt.prepare(self.client).await?;
-                let row = self.client.query_one(stmt, &self.params).await?;
-                Ok((self.mapper)((self.extractor)(&row)))
+                let info = self.info;
+                cornucopia_async::private::observe(&info, |_| Some(1), async move {
+                    cornucopia_async::private::timeout(self.timeout.clone(), async move {
+                        let row = self
+                            .stmt
+                            .run(self.client, |client, stmt| async move {
+                                let stmt = &stmt;
+                                let params: &[&(dyn postgres_types::ToSql + Sync)] = &self.params;
+                                client.query_one(stmt, params).await
+                            })
+                            .await?;
+                        Ok((self.mapper)((self.extractor)(&row)))
+                    })
+                    .await
+                })
+                .await
             }
             pub async fn all(self) -> Result<Vec<T>, tokio_postgres::Error> {
-                self.iter().await?.try_collect().await
+                let info = self.info;
+                cornucopia_async::private::observe(
+                    &info,
+                    |it: &Vec<T>| Some(it.len() as u64),
+                    async move {
+                        cornucopia_async::private::timeout(self.timeout.clone(), async move {
+                            self.raw_iter().await?.try_collect().await
+                        })
+                        .await
+                    },
+                )
+                .await
             }
             pub async fn opt(self) -> Result<Option<T>, tokio_postgres::Error> {
-                let stmt = self.stmt.prepare(self.client).await?;
-                Ok(self
-                    .client
-                    .query_opt(stmt, &self.params)
-                    .await?
-                    .map(|row| (self.mapper)((self.extractor)(&row))))
+                let info = self.info;
+                cornucopia_async::private::observe(
+                    &info,
+                    |it: &Option<T>| Some(it.is_some().into()),
+                    async move {
+                        cornucopia_async::private::timeout(self.timeout.clone(), async move {
+                            Ok(self
+                                .stmt
+                                .run(self.client, |client, stmt| async move {
+                                    let stmt = &stmt;
+                                    let params: &[&(dyn postgres_types::ToSql + Sync)] =
+                                        &self.params;
+                                    client.query_opt(stmt, params).await
+                                })
+                                .await?
+                                .map(|row| (self.mapper)((self.extractor)(&row))))
+                        })
+                        .await
+                    },
+                )
+                .await
             }
             pub async fn iter(
                 self,
@@ -404,22 +648,118 @@ pub mod queries {
                 impl futures::Stream<Item = Result<T, tokio_postgres::Error>> + 'a,
                 tokio_postgres::Error,
             > {
-                let stmt = self.stmt.prepare(self.client).await?;
+                let info = self.info;
+                cornucopia_async::private::observe(&info, |_| None, async move {
+                    cornucopia_async::private::timeout(self.timeout.clone(), async move {
+                        self.raw_iter().await
+                    })
+                    .await
+                })
+                .await
+            }
+            /// Passes each row to `visitor` as it is read, without building owned values,
+            /// returning the number of rows.
+            pub async fn for_each(
+                self,
+                mut visitor: impl FnMut(&str),
+            ) -> Result<u64, tokio_postgres::Error> {
+                let info = self.info;
+                cornucopia_async::private::observe(&info, |it: &u64| Some(*it), async move {
+                    cornucopia_async::private::timeout(self.timeout.clone(), async move {
+                        let rows = self
+                            .stmt
+                            .run(self.client, |client, stmt| async move {
+                                let stmt = &stmt;
+                                let params: &[&(dyn postgres_types::ToSql + Sync)] = &self.params;
+                                client
+                                    .query_raw(stmt, cornucopia_async::private::slice_iter(params))
+                                    .await
+                            })
+                            .await?;
+                        let mut count = 0;
+                        let mut rows = std::pin::pin!(rows);
+                        while let Some(row) = rows.try_next().await? {
+                            visitor((self.extractor)(&row));
+                            count += 1;
+                        }
+                        Ok(count)
+                    })
+                    .await
+                })
+                .await
+            }
+            async fn raw_iter(
+                self,
+            ) -> Result<
+                impl futures::Stream<Item = Result<T, tokio_postgres::Error>> + 'a,
+                tokio_postgres::Error,
+            > {
                 let it = self
-                    .client
-                    .query_raw(stmt, cornucopia_async::private::slice_iter(&self.params))
+                    .stmt
+                    .run(self.client, |client, stmt| async move {
+                        let stmt = &stmt;
+                        let params: &[&(dyn postgres_types::ToSql + Sync)] = &self.params;
+                        client
+                            .query_raw(stmt, cornucopia_async::private::slice_iter(params))
+                            .await
+                    })
                     .await?
                     .map(move |res| res.map(|row| (self.mapper)((self.extractor)(&row))))
                     .into_stream();
                 Ok(it)
             }
         }
+        impl<'a, C, T: 'a, const N: usize> StringQuery<'a, C, T, N>
+        where
+            C: cornucopia_async::CancelClient,
+        {
+            /// Cancels the query on the server if it runs longer than `timeout`, failing with
+            /// the cancellation error. `iter` and `cursor` only bound starting the query, not
+            /// reading its rows.
+            pub fn timeout(mut self, timeout: std::time::Duration) -> Self {
+                self.timeout = Some((timeout, self.client.cancel_token()));
+                self
+            }
+        }
+        impl<'a, C, T: 'a, const N: usize> StringQuery<'a, C, T, N>
+        where
+            C: cornucopia_async::CursorClient,
+        {
+            /// Fetch rows through a portal, `batch_size` rows at a time.
+            pub async fn cursor(
+                self,
+                batch_size: i32,
+            ) -> Result<
+                impl futures::Stream<Item = Result<T, tokio_postgres::Error>> + 'a,
+                tokio_postgres::Error,
+            > {
+                let info = self.info;
+                cornucopia_async::private::observe(&info, |_| None, async move {
+                    cornucopia_async::private::timeout(self.timeout.clone(), async move {
+                        let stmt = self.stmt.prepare(self.client).await?;
+                        let it = cornucopia_async::private::cursor(
+                            self.client,
+                            stmt,
+                            &self.params,
+                            batch_size,
+                        )
+                        .await?
+                        .map(move |res| res.map(|row| (self.mapper)((self.extractor)(&row))));
+                        Ok(it)
+                    })
+                    .await
+                })
+                .await
+            }
+        }
         pub struct AuthorNameStartingWithQuery<'a, C: GenericClient, T, const N: usize> {
             client: &'a C,
             params: [&'a (dyn postgres_types::ToSql + Sync); N],
             stmt: &'a mut cornucopia_async::private::Stmt,
             extractor: fn(&tokio_postgres::Row) -> AuthorNameStartingWithBorrowed,
             mapper: fn(AuthorNameStartingWithBorrowed) -> T,
+            info: cornucopia_async::QueryInfo,
+            timeout: Option<(std::time::Duration, tokio_postgres::CancelToken)>,
         }
         impl<'a, C, T: 'a, const N: usize> AuthorNameStartingWithQuery<'a, C, T, N>
         where
@@ -435,23 +775,64 @@ pub mod queries {
                     stmt: self.stmt,
                     extractor: self.extractor,
                     mapper,
+                    info: self.info,
+                    timeout: self.timeout,
                 }
             }
             pub async fn one(self) -> Result<T, tokio_postgres::Error> {
-                let stmt = self.stmt.prepare(self.client).await?;
-                let row = self.client.query_one(stmt, &self.params).await?;
-                Ok((self.mapper)((self.extractor)(&row)))
+                let info = self.info;
+                cornucopia_async::private::observe(&info, |_| Some(1), async move {
+                    cornucopia_async::private::timeout(self.timeout.clone(), async move {
+                        let row = self
+                            .stmt
+                            .run(self.client, |client, stmt| async move {
+                                let stmt = &stmt;
+                                let params: &[&(dyn postgres_types::ToSql + Sync)] = &self.params;
+                                client.query_one(stmt, params).await
+                            })
+                            .await?;
+                        Ok((self.mapper)((self.extractor)(&row)))
+                    })
+                    .await
+                })
+                .await
             }
             pub async fn all(self) -> Result<Vec<T>, tokio_postgres::Error> {
-                self.iter().await?.try_collect().await
+                let info = self.info;
+                cornucopia_async::private::observe(
+                    &info,
+                    |it: &Vec<T>| Some(it.len() as u64),
+                    async move {
+                        cornucopia_async::private::timeout(self.timeout.clone(), async move {
+                            self.raw_iter().await?.try_collect().await
+                        })
+                        .await
+                    },
+                )
+                .await
             }
             pub async fn opt(self) -> Result<Option<T>, tokio_postgres::Error> {
-                let stmt = self.stmt.prepare(self.client).await?;
-                Ok(self
-                    .client
-                    .query_opt(stmt, &self.params)
-                    .await?
-                    .map(|row| (self.mapper)((self.extractor)(&row))))
+                let info = self.info;
+                cornucopia_async::private::observe(
+                    &info,
+                    |it: &Option<T>| Some(it.is_some().into()),
+                    async move {
+                        cornucopia_async::private::timeout(self.timeout.clone(), async move {
+                            Ok(self
+                                .stmt
+                                .run(self.client, |client, stmt| async move {
+                                    let stmt = &stmt;
+                                    let params: &[&(dyn postgres_types::ToSql + Sync)] =
+                                        &self.params;
+                                    client.query_opt(stmt, params).await
+                                })
+                                .await?
+                                .map(|row| (self.mapper)((self.extractor)(&row))))
+                        })
+                        .await
+                    },
+                )
+                .await
             }
             pub async fn iter(
                 self,
@@ -459,22 +840,118 @@ pub mod queries {
                 impl futures::Stream<Item = Result<T, tokio_postgres::Error>> + 'a,
                 tokio_postgres::Error,
             > {
-                let stmt = self.stmt.prepare(self.client).await?;
+                let info = self.info;
+                cornucopia_async::private::observe(&info, |_| None, async move {
+                    cornucopia_async::private::timeout(self.timeout.clone(), async move {
+                        self.raw_iter().await
+                    })
+                    .await
+                })
+                .await
+            }
+            /// Passes each row to `visitor` as it is read, without building owned values,
+            /// returning the number of rows.
+            pub async fn for_each(
+                self,
+                mut visitor: impl FnMut(AuthorNameStartingWithBorrowed),
+            ) -> Result<u64, tokio_postgres::Error> {
+                let info = self.info;
+                cornucopia_async::private::observe(&info, |it: &u64| Some(*it), async move {
+                    cornucopia_async::private::timeout(self.timeout.clone(), async move {
+                        let rows = self
+                            .stmt
+                            .run(self.client, |client, stmt| async move {
+                                let stmt = &stmt;
+                                let params: &[&(dyn postgres_types::ToSql + Sync)] = &self.params;
+                                client
+                                    .query_raw(stmt, cornucopia_async::private::slice_iter(params))
+                                    .await
+                            })
+                            .await?;
+                        let mut count = 0;
+                        let mut rows = std::pin::pin!(rows);
+                        while let Some(row) = rows.try_next().await? {
+                            visitor((self.extractor)(&row));
+                            count += 1;
+                        }
+                        Ok(count)
+                    })
+                    .await
+                })
+                .await
+            }
+            async fn raw_iter(
+                self,
+            ) -> Result<
+                impl futures::Stream<Item = Result<T, tokio_postgres::Error>> + 'a,
+                tokio_postgres::Error,
+            > {
                 let it = self
-                    .client
-                    .query_raw(stmt, cornucopia_async::private::slice_iter(&self.params))
+                    .stmt
+                    .run(self.client, |client, stmt| async move {
+                        let stmt = &stmt;
+                        let params: &[&(dyn postgres_types::ToSql + Sync)] = &self.params;
+                        client
+                            .query_raw(stmt, cornucopia_async::private::slice_iter(params))
+                            .await
+                    })
                     .await?
                     .map(move |res| res.map(|row| (self.mapper)((self.extractor)(&row))))
                     .into_stream();
                 Ok(it)
             }
         }
+        impl<'a, C, T: 'a, const N: usize> AuthorNameStartingWithQuery<'a, C, T, N>
+        where
+            C: cornucopia_async::CancelClient,
+        {
+            /// Cancels the query on the server if it runs longer than `timeout`, failing with
+            /// the cancellation error. `iter` and `cursor` only bound starting the query, not
+            /// reading its rows.
+            pub fn timeout(mut self, timeout: std::time::Duration) -> Self {
+                self.timeout = Some((timeout, self.client.cancel_token()));
+                self
+            }
+        }
+        impl<'a, C, T: 'a, const N: usize> AuthorNameStartingWithQuery<'a, C, T, N>
+        where
+            C: cornucopia_async::CursorClient,
+        {
+            /// Fetch rows through a portal, `batch_size` rows at a time.
+            pub async fn cursor(
+                self,
+                batch_size: i32,
+            ) -> Result<
+                impl futures::Stream<Item = Result<T, tokio_postgres::Error>> + 'a,
+                tokio_postgres::Error,
+            > {
+                let info = self.info;
+                cornucopia_async::private::observe(&info, |_| None, async move {
+                    cornucopia_async::private::timeout(self.timeout.clone(), async move {
+                        let stmt = self.stmt.prepare(self.client).await?;
+                        let it = cornucopia_async::private::cursor(
+                            self.client,
+                            stmt,
+                            &self.params,
+                            batch_size,
+                        )
+                        .await?
+                        .map(move |res| res.map(|row| (self.mapper)((self.extractor)(&row))));
+                        Ok(it)
+                    })
+                    .await
+                })
+                .await
+            }
+        }
         pub struct PublicVoiceactorQuery<'a, C: GenericClient, T, const N: usize> {
             client: &'a C,
             params: [&'a (dyn postgres_types::ToSql + Sync); N],
             stmt: &'a mut cornucopia_async::private::Stmt,
             extractor: fn(&tokio_postgres::Row) -> super::super::types::public::VoiceactorBorrowed,
             mapper: fn(super::super::types::public::VoiceactorBorrowed) -> T,
+            info: cornucopia_async::QueryInfo,
+            timeout: Option<(std::time::Duration, tokio_postgres::CancelToken)>,
         }
         impl<'a, C, T: 'a, const N: usize> PublicVoiceactorQuery<'a, C, T, N>
         where
@@ -490,23 +967,64 @@ pub mod queries {
                     stmt: self.stmt,
                     extractor: self.extractor,
                     mapper,
+                    info: self.info,
+                    timeout: self.timeout,
                 }
             }
             pub async fn one(self) -> Result<T, tokio_postgres::Error> {
-                let stmt = self.stmt.prepare(self.client).await?;
-                let row = self.client.query_one(stmt, &self.params).await?;
-                Ok((self.mapper)((self.extractor)(&row)))
+                let info = self.info;
+                cornucopia_async::private::observe(&info, |_| Some(1), async move {
+                    cornucopia_async::private::timeout(self.timeout.clone(), async move {
+                        let row = self
+                            .stmt
+                            .run(self.client, |client, stmt| async move {
+                                let stmt = &stmt;
+                                let params: &[&(dyn postgres_types::ToSql + Sync)] = &self.params;
+                                client.query_one(stmt, params).await
+                            })
+                            .await?;
+                        Ok((self.mapper)((self.extractor)(&row)))
+                    })
+                    .await
+                })
+                .await
             }
             pub async fn all(self) -> Result<Vec<T>, tokio_postgres::Error> {
-                self.iter().await?.try_collect().await
+                let info = self.info;
+                cornucopia_async::private::observe(
+                    &info,
+                    |it: &Vec<T>| Some(it.len() as u64),
+                    async move {
+                        cornucopia_async::private::timeout(self.timeout.clone(), async move {
+                            self.raw_iter().await?.try_collect().await
+                        })
+                        .await
+                    },
+                )
+                .await
             }
             pub async fn opt(self) -> Result<Option<T>, tokio_postgres::Error> {
-                let stmt = self.stmt.prepare(self.client).await?;
-                Ok(self
-                    .client
-                    .query_opt(stmt, &self.params)
-                    .await?
-                    .map(|row| (self.mapper)((self.extractor)(&row))))
+                let info = self.info;
+                cornucopia_async::private::observe(
+                    &info,
+                    |it: &Option<T>| Some(it.is_some().into()),
+                    async move {
+                        cornucopia_async::private::timeout(self.timeout.clone(), async move {
+                            Ok(self
+                                .stmt
+                                .run(self.client, |client, stmt| async move {
+                                    let stmt = &stmt;
+                                    let params: &[&(dyn postgres_types::ToSql + Sync)] =
+                                        &self.params;
+                                    client.query_opt(stmt, params).await
+                                })
+                                .await?
+                                .map(|row| (self.mapper)((self.extractor)(&row))))
+                        })
+                        .await
+                    },
+                )
+                .await
             }
             pub async fn iter(
                 self,
@@ -514,22 +1032,118 @@ pub mod queries {
                 impl futures::Stream<Item = Result<T, tokio_postgres::Error>> + 'a,
                 tokio_postgres::Error,
             > {
-                let stmt = self.stmt.prepare(self.client).await?;
+                let info = self.info;
+                cornucopia_async::private::observe(&info, |_| None, async move {
+                    cornucopia_async::private::timeout(self.timeout.clone(), async move {
+                        self.raw_iter().await
+                    })
+                    .await
+                })
+                .await
+            }
+            /// Passes each row to `visitor` as it is read, without building owned values,
+            /// returning the number of rows.
+            pub async fn for_each(
+                self,
+                mut visitor: impl FnMut(super::super::types::public::VoiceactorBorrowed),
+            ) -> Result<u64, tokio_postgres::Error> {
+                let info = self.info;
+                cornucopia_async::private::observe(&info, |it: &u64| Some(*it), async move {
+                    cornucopia_async::private::timeout(self.timeout.clone(), async move {
+                        let rows = self
+                            .stmt
+                            .run(self.client, |client, stmt| async move {
+                                let stmt = &stmt;
+                                let params: &[&(dyn postgres_types::ToSql + Sync)] = &self.params;
+                                client
+                                    .query_raw(stmt, cornucopia_async::private::slice_iter(params))
+                                    .await
+                            })
+                            .await?;
+                        let mut count = 0;
+                        let mut rows = std::pin::pin!(rows);
+                        while let Some(row) = rows.try_next().await? {
+                            visitor((self.extractor)(&row));
+                            count += 1;
+                        }
+                        Ok(count)
+                    })
+                    .await
+                })
+                .await
+            }
+            async fn raw_iter(
+                self,
+            ) -> Result<
+                impl futures::Stream<Item = Result<T, tokio_postgres::Error>> + 'a,
+                tokio_postgres::Error,
+            > {
                 let it = self
-                    .client
-                    .query_raw(stmt, cornucopia_async::private::slice_iter(&self.params))
+                    .stmt
+                    .run(self.client, |client, stmt| async move {
+                        let stmt = &stmt;
+                        let params: &[&(dyn postgres_types::ToSql + Sync)] = &self.params;
+                        client
+                            .query_raw(stmt, cornucopia_async::private::slice_iter(params))
+                            .await
+                    })
                     .await?
                     .map(move |res| res.map(|row| (self.mapper)((self.extractor)(&row))))
                     .into_stream();
                 Ok(it)
             }
         }
+        impl<'a, C, T: 'a, const N: usize> PublicVoiceactorQuery<'a, C, T, N>
+        where
+            C: cornucopia_async::CancelClient,
+        {
+            /// Cancels the query on the server if it runs longer than `timeout`, failing with
+            /// the cancellation error. `iter` and `cursor` only bound starting the query, not
+            /// reading its rows.
+            pub fn timeout(mut self, timeout: std::time::Duration) -> Self {
+                self.timeout = Some((timeout, self.client.cancel_token()));
+                self
+            }
+        }
+        impl<'a, C, T: 'a, const N: usize> PublicVoiceactorQuery<'a, C, T, N>
+        where
+            C: cornucopia_async::CursorClient,
+        {
+            /// Fetch rows through a portal, `batch_size` rows at a time.
+            pub async fn cursor(
+                self,
+                batch_size: i32,
+            ) -> Result<
+                impl futures::Stream<Item = Result<T, tokio_postgres::Error>> + 'a,
+                tokio_postgres::Error,
+            > {
+                let info = self.info;
+                cornucopia_async::private::observe(&info, |_| None, async move {
+                    cornucopia_async::private::timeout(self.timeout.clone(), async move {
+                        let stmt = self.stmt.prepare(self.client).await?;
+                        let it = cornucopia_async::private::cursor(
+                            self.client,
+                            stmt,
+                            &self.params,
+                            batch_size,
+                        )
+                        .await?
+                        .map(move |res| res.map(|row| (self.mapper)((self.extractor)(&row))));
+                        Ok(it)
+                    })
+                    .await
+                })
+                .await
+            }
+        }
         pub struct SelectTranslationsQuery<'a, C: GenericClient, T, const N: usize> {
             client: &'a C,
             params: [&'a (dyn postgres_types::ToSql + Sync); N],
             stmt: &'a mut cornucopia_async::private::Stmt,
             extractor: fn(&tokio_postgres::Row) -> SelectTranslationsBorrowed,
             mapper: fn(SelectTranslationsBorrowed) -> T,
+            info: cornucopia_async::QueryInfo,
+            timeout: Option<(std::time::Duration, tokio_postgres::CancelToken)>,
         }
         impl<'a, C, T: 'a, const N: usize> SelectTranslationsQuery<'a, C, T, N>
         where
@@ -545,23 +1159,64 @@ pub mod queries {
                     stmt: self.stmt,
                     extractor: self.extractor,
                     mapper,
+                    info: self.info,
+                    timeout: self.timeout,
                 }
             }
             pub async fn one(self) -> Result<T, tokio_postgres::Error> {
-                let stmt = self.stmt.prepare(self.client).await?;
-                let row = self.client.query_one(stmt, &self.params).await?;
-                Ok((self.mapper)((self.extractor)(&row)))
+                let info = self.info;
+                cornucopia_async::private::observe(&info, |_| Some(1), async move {
+                    cornucopia_async::private::timeout(self.timeout.clone(), async move {
+                        let row = self
+                            .stmt
+                            .run(self.client, |client, stmt| async move {
+                                let stmt = &stmt;
+                                let params: &[&(dyn postgres_types::ToSql + Sync)] = &self.params;
+                                client.query_one(stmt, params).await
+                            })
+                            .await?;
+                        Ok((self.mapper)((self.extractor)(&row)))
+                    })
+                    .await
+                })
+                .await
             }
             pub async fn all(self) -> Result<Vec<T>, tokio_postgres::Error> {
-                self.iter().await?.try_collect().await
+                let info = self.info;
+                cornucopia_async::private::observe(
+                    &info,
+                    |it: &Vec<T>| Some(it.len() as u64),
+                    async move {
+                        cornucopia_async::private::timeout(self.timeout.clone(), async move {
+                            self.raw_iter().await?.try_collect().await
+                        })
+                        .await
+                    },
+                )
+                .await
             }
             pub async fn opt(self) -> Result<Option<T>, tokio_postgres::Error> {
-                let stmt = self.stmt.prepare(self.client).await?;
-                Ok(self
-                    .client
-                    .query_opt(stmt, &self.params)
-                    .await?
-                    .map(|row| (self.mapper)((self.extractor)(&row))))
+                let info = self.info;
+                cornucopia_async::private::observe(
+                    &info,
+                    |it: &Option<T>| Some(it.is_some().into()),
+                    async move {
+                        cornucopia_async::private::timeout(self.timeout.clone(), async move {
+                            Ok(self
+                                .stmt
+                                .run(self.client, |client, stmt| async move {
+                                    let stmt = &stmt;
+                                    let params: &[&(dyn postgres_types::ToSql + Sync)] =
+                                        &self.params;
+                                    client.query_opt(stmt, params).await
+                                })
+                                .await?
+                                .map(|row| (self.mapper)((self.extractor)(&row))))
+                        })
+                        .await
+                    },
+                )
+                .await
             }
             pub async fn iter(
                 self,
@@ -569,16 +1224,110 @@ pub mod queries {
                 impl futures::Stream<Item = Result<T, tokio_postgres::Error>> + 'a,
                 tokio_postgres::Error,
             > {
-                let stmt = self.stmt.prepare(self.client).await?;
+                let info = self.info;
+                cornucopia_async::private::observe(&info, |_| None, async move {
+                    cornucopia_async::private::timeout(self.timeout.clone(), async move {
+                        self.raw_iter().await
+                    })
+                    .await
+                })
+                .await
+            }
+            /// Passes each row to `visitor` as it is read, without building owned values,
+            /// returning the number of rows.
+            pub async fn for_each(
+                self,
+                mut visitor: impl FnMut(SelectTranslationsBorrowed),
+            ) -> Result<u64, tokio_postgres::Error> {
+                let info = self.info;
+                cornucopia_async::private::observe(&info, |it: &u64| Some(*it), async move {
+                    cornucopia_async::private::timeout(self.timeout.clone(), async move {
+                        let rows = self
+                            .stmt
+                            .run(self.client, |client, stmt| async move {
+                                let stmt = &stmt;
+                                let params: &[&(dyn postgres_types::ToSql + Sync)] = &self.params;
+                                client
+                                    .query_raw(stmt, cornucopia_async::private::slice_iter(params))
+                                    .await
+                            })
+                            .await?;
+                        let mut count = 0;
+                        let mut rows = std::pin::pin!(rows);
+                        while let Some(row) = rows.try_next().await? {
+                            visitor((self.extractor)(&row));
+                            count += 1;
+                        }
+                        Ok(count)
+                    })
+                    .await
+                })
+                .await
+            }
+            async fn raw_iter(
+                self,
+            ) -> Result<
+                impl futures::Stream<Item = Result<T, tokio_postgres::Error>> + 'a,
+                tokio_postgres::Error,
+            > {
                 let it = self
-                    .client
-                    .query_raw(stmt, cornucopia_async::private::slice_iter(&self.params))
+                    .stmt
+                    .run(self.client, |client, stmt| async move {
+                        let stmt = &stmt;
+                        let params: &[&(dyn postgres_types::ToSql + Sync)] = &self.params;
+                        client
+                            .query_raw(stmt, cornucopia_async::private::slice_iter(params))
+                            .await
+                    })
                     .await?
                     .map(move |res| res.map(|row| (self.mapper)((self.extractor)(&row))))
                     .into_stream();
                 Ok(it)
             }
         }
+        impl<'a, C, T: 'a, const N: usize> SelectTranslationsQuery<'a, C, T, N>
+        where
+            C: cornucopia_async::CancelClient,
+        {
+            /// Cancels the query on the server if it runs longer than `timeout`, failing with
+            /// the cancellation error. `iter` and `cursor` only bound starting the query, not
+            /// reading its rows.
+            pub fn timeout(mut self, timeout: std::time::Duration) -> Self {
+                self.timeout = Some((timeout, self.client.cancel_token()));
+                self
+            }
+        }
+        impl<'a, C, T: 'a, const N: usize> SelectTranslationsQuery<'a, C, T, N>
+        where
+            C: cornucopia_async::CursorClient,
+        {
+            /// Fetch rows through a portal, `batch_size` rows at a time.
+            pub async fn cursor(
+                self,
+                batch_size: i32,
+            ) -> Result<
+                impl futures::Stream<Item = Result<T, tokio_postgres::Error>> + 'a,
+                tokio_postgres::Error,
+            > {
+                let info = self.info;
+                cornucopia_async::private::observe(&info, |_| None, async move {
+                    cornucopia_async::private::timeout(self.timeout.clone(), async move {
+                        let stmt = self.stmt.prepare(self.client).await?;
+                        let it = cornucopia_async::private::cursor(
+                            self.client,
+                            stmt,
+                            &self.params,
+                            batch_size,
+                        )
+                        .await?
+                        .map(move |res| res.map(|row| (self.mapper)((self.extractor)(&row))));
+                        Ok(it)
+                    })
+                    .await
+                })
+                .await
+            }
+        }
         pub fn authors() -> AuthorsStmt {
             AuthorsStmt(cornucopia_async::private::Stmt::new(
                 "SELECT
@@ -589,6 +1338,18 @@ FROM
         }
         pub struct AuthorsStmt(cornucopia_async::private::Stmt);
         impl AuthorsStmt {
+            /// Uses `sql`, e.g. composed at runtime, instead of the annotated query.
+            /// It is prepared as is, so it must take the same parameters and return the same columns.
+            pub fn with_sql(sql: impl Into<std::borrow::Cow<'static, str>>) -> Self {
+                Self(cornucopia_async::private::Stmt::new(sql))
+            }
+            /// Shares the statements prepared on each connection through `cache`
+            pub(crate) fn with_cache(
+                self,
+                cache: &std::sync::Arc<cornucopia_async::private::StmtCache>,
+            ) -> Self {
+                Self(self.0.with_cache(cache))
+            }
             pub fn bind<'a, C: GenericClient>(
                 &'a mut self,
                 client: &'a C,
@@ -603,6 +1364,11 @@ FROM
                         country: row.get(2),
                     },
                     mapper: |it| <Authors>::from(it),
+                    info: cornucopia_async::QueryInfo {
+                        module: "module_2",
+                        query: "authors",
+                    },
+                    timeout: None,
                 }
             }
         }
@@ -616,6 +1382,18 @@ FROM
         }
         pub struct BooksStmt(cornucopia_async::private::Stmt);
         impl BooksStmt {
+            /// Uses `sql`, e.g. composed at runtime, instead of the annotated query.
+            /// It is prepared as is, so it must take the same parameters and return the same columns.
+            pub fn with_sql(sql: impl Into<std::borrow::Cow<'static, str>>) -> Self {
+                Self(cornucopia_async::private::Stmt::new(sql))
+            }
+            /// Shares the statements prepared on each connection through `cache`
+            pub(crate) fn with_cache(
+                self,
+                cache: &std::sync::Arc<cornucopia_async::private::StmtCache>,
+            ) -> Self {
+                Self(self.0.with_cache(cache))
+            }
             pub fn bind<'a, C: GenericClient>(
                 &'a mut self,
                 client: &'a C,
@@ -626,6 +1404,11 @@ FROM
                     stmt: &mut self.0,
                     extractor: |row| row.get(0),
                     mapper: |it| it.into(),
+                    info: cornucopia_async::QueryInfo {
+                        module: "module_2",
+                        query: "books",
+                    },
+                    timeout: None,
                 }
             }
         }
@@ -641,6 +1424,18 @@ WHERE
         }
         pub struct AuthorNameByIdStmt(cornucopia_async::private::Stmt);
         impl AuthorNameByIdStmt {
+            /// Uses `sql`, e.g. composed at runtime, instead of the annotated query.
+            /// It is prepared as is, so it must take the same parameters and return the same columns.
+            pub fn with_sql(sql: impl Into<std::borrow::Cow<'static, str>>) -> Self {
+                Self(cornucopia_async::private::Stmt::new(sql))
+            }
+            /// Shares the statements prepared on each connection through `cache`
+            pub(crate) fn with_cache(
+                self,
+                cache: &std::sync::Arc<cornucopia_async::private::StmtCache>,
+            ) -> Self {
+                Self(self.0.with_cache(cache))
+            }
             pub fn bind<'a, C: GenericClient>(
                 &'a mut self,
                 client: &'a C,
@@ -652,6 +1447,11 @@ WHERE
                     stmt: &mut self.0,
                     extractor: |row| row.get(0),
                     mapper: |it| it.into(),
+                    info: cornucopia_async::QueryInfo {
+                        module: "module_2",
+                        query: "author_name_by_id",
+                    },
+                    timeout: None,
                 }
             }
         }
@@ -672,6 +1472,18 @@ WHERE
         }
         pub struct AuthorNameStartingWithStmt(cornucopia_async::private::Stmt);
         impl AuthorNameStartingWithStmt {
+            /// Uses `sql`, e.g. composed at runtime, instead of the annotated query.
+            /// It is prepared as is, so it must take the same parameters and return the same columns.
+            pub fn with_sql(sql: impl Into<std::borrow::Cow<'static, str>>) -> Self {
+                Self(cornucopia_async::private::Stmt::new(sql))
+            }
+            /// Shares the statements prepared on each connection through `cache`
+            pub(crate) fn with_cache(
+                self,
+                cache: &std::sync::Arc<cornucopia_async::private::StmtCache>,
+            ) -> Self {
+                Self(self.0.with_cache(cache))
+            }
             pub fn bind<'a, C: GenericClient, T1: cornucopia_async::StringSql>(
                 &'a mut self,
                 client: &'a C,
@@ -688,6 +1500,11 @@ WHERE
                         title: row.get(3),
                     },
                     mapper: |it| <AuthorNameStartingWith>::from(it),
+                    info: cornucopia_async::QueryInfo {
+                        module: "module_2",
+                        query: "author_name_starting_with",
+                    },
+                    timeout: None,
                 }
             }
         }
@@ -719,6 +1536,18 @@ WHERE
         }
         pub struct SelectVoiceActorWithCharacterStmt(cornucopia_async::private::Stmt);
         impl SelectVoiceActorWithCharacterStmt {
+            /// Uses `sql`, e.g. composed at runtime, instead of the annotated query.
+            /// It is prepared as is, so it must take the same parameters and return the same columns.
+            pub fn with_sql(sql: impl Into<std::borrow::Cow<'static, str>>) -> Self {
+                Self(cornucopia_async::private::Stmt::new(sql))
+            }
+            /// Shares the statements prepared on each connection through `cache`
+            pub(crate) fn with_cache(
+                self,
+                cache: &std::sync::Arc<cornucopia_async::private::StmtCache>,
+            ) -> Self {
+                Self(self.0.with_cache(cache))
+            }
             pub fn bind<'a, C: GenericClient>(
                 &'a mut self,
                 client: &'a C,
@@ -731,6 +1560,11 @@ WHERE
                     stmt: &mut self.0,
                     extractor: |row| row.get(0),
                     mapper: |it| it.into(),
+                    info: cornucopia_async::QueryInfo {
+                        module: "module_2",
+                        query: "select_voice_actor_with_character",
+                    },
+                    timeout: None,
                 }
             }
         }
@@ -745,6 +1579,18 @@ FROM
         }
         pub struct SelectTranslationsStmt(cornucopia_async::private::Stmt);
         impl SelectTranslationsStmt {
+            /// Uses `sql`, e.g. composed at runtime, instead of the annotated query.
+            /// It is prepared as is, so it must take the same parameters and return the same columns.
+            pub fn with_sql(sql: impl Into<std::borrow::Cow<'static, str>>) -> Self {
+                Self(cornucopia_async::private::Stmt::new(sql))
+            }
+            /// Shares the statements prepared on each connection through `cache`
+            pub(crate) fn with_cache(
+                self,
+                cache: &std::sync::Arc<cornucopia_async::private::StmtCache>,
+            ) -> Self {
+                Self(self.0.with_cache(cache))
+            }
             pub fn bind<'a, C: GenericClient>(
                 &'a mut self,
                 client: &'a C,
@@ -758,8 +1604,131 @@ FROM
                         translations: row.get(1),
                     },
                     mapper: |it| <SelectTranslations>::from(it),
+                    info: cornucopia_async::QueryInfo {
+                        module: "module_2",
+                        query: "select_translations",
+                    },
+                    timeout: None,
+                }
+            }
+        }
+        /// Every statement of this module. Statements are prepared on the connection
+        /// they are first run on, so keep one bundle per connection.
+        pub struct Statements {
+            pub authors: AuthorsStmt,
+            pub books: BooksStmt,
+            pub author_name_by_id: AuthorNameByIdStmt,
+            pub author_name_starting_with: AuthorNameStartingWithStmt,
+            pub select_voice_actor_with_character: SelectVoiceActorWithCharacterStmt,
+            pub select_translations: SelectTranslationsStmt,
+        }
+        impl Statements {
+            pub fn new() -> Self {
+                Self {
+                    authors: authors(),
+                    books: books(),
+                    author_name_by_id: author_name_by_id(),
+                    author_name_starting_with: author_name_starting_with(),
+                    select_voice_actor_with_character: select_voice_actor_with_character(),
+                    select_translations: select_translations(),
                 }
             }
+            /// Prepares every statement on `client`
+            pub async fn prepare_all<C: GenericClient>(
+                &mut self,
+                client: &C,
+            ) -> Result<(), tokio_postgres::Error> {
+                futures::future::try_join_all([
+                    self.authors.0.prepare(client),
+                    self.books.0.prepare(client),
+                    self.author_name_by_id.0.prepare(client),
+                    self.author_name_starting_with.0.prepare(client),
+                    self.select_voice_actor_with_character.0.prepare(client),
+                    self.select_translations.0.prepare(client),
+                ])
+                .await?;
+                Ok(())
+            }
+        }
+        /// Statements of this module, prepared lazily once per connection and shared by the
+        /// clones of `Queries`, which are cheap
+        #[derive(Debug, Clone, Default)]
+        pub struct Queries {
+            cache: std::sync::Arc<cornucopia_async::private::StmtCache>,
         }
+        impl Queries {
+            pub fn authors(&self) -> AuthorsStmt {
+                authors().with_cache(&self.cache)
+            }
+            pub fn books(&self) -> BooksStmt {
+                books().with_cache(&self.cache)
+            }
+            pub fn author_name_by_id(&self) -> AuthorNameByIdStmt {
+                author_name_by_id().with_cache(&self.cache)
+            }
+            pub fn author_name_starting_with(&self) -> AuthorNameStartingWithStmt {
+                author_name_starting_with().with_cache(&self.cache)
+            }
+            pub fn select_voice_actor_with_character(&self) -> SelectVoiceActorWithCharacterStmt {
+                select_voice_actor_with_character().with_cache(&self.cache)
+            }
+            pub fn select_translations(&self) -> SelectTranslationsStmt {
+                select_translations().with_cache(&self.cache)
+            }
+        }
+    }
+    /// Statements of every module, to share between threads (e.g. as axum's `State`
+    /// or actix's `Data`). They are prepared lazily once per connection, and shared by
+    /// the clones of `Queries`, which are cheap. The clients with their own statement
+    /// cache, like deadpool's, use it instead.
+    #[derive(Debug, Clone, Default)]
+    pub struct Queries {
+        module_1: module_1::Queries,
+        module_2: module_2::Queries,
+    }
+    impl Queries {
+        pub fn module_1(&self) -> module_1::Queries {
+            self.module_1.clone()
+        }
+        pub fn module_2(&self) -> module_2::Queries {
+            self.module_2.clone()
+        }
+    }
+    /// Every statement, to prepare them all up front with `prepare_all`. This gives a predictable
+    /// latency on first use and surfaces schema drift at startup. Prepared statements belong to
+    /// the connection they were prepared on, so keep one bundle per connection.
+    pub struct Statements {
+        pub module_1: module_1::Statements,
+        pub module_2: module_2::Statements,
     }
+    impl Statements {
+        pub fn new() -> Self {
+            Self {
+                module_1: module_1::Statements::new(),
+                module_2: module_2::Statements::new(),
+            }
+        }
+        /// Prepares every statement on `client`
+        pub async fn prepare_all<C: cornucopia_async::GenericClient>(
+            &mut self,
+            client: &C,
+        ) -> Result<(), tokio_postgres::Error> {
+            futures::try_join!(
+                self.module_1.prepare_all(client),
+                self.module_2.prepare_all(client),
+            )?;
+            Ok(())
+        }
+    }
+}
+/// Flat re-exports of the generated items, to import them all with `use prelude::*`
+#[allow(unused_imports)]
+pub mod prelude {
+    pub use super::queries::module_2::AuthorNameStartingWith;
+    pub use super::queries::module_2::AuthorNameStartingWithParams;
+    pub use super::queries::module_2::Authors;
+    pub use super::queries::module_2::SelectTranslations;
+    pub use super::types::public::SpongeBobCharacter;
+    pub use super::types::public::Voiceactor;
+    pub use cornucopia_async::{GenericClient, Params as _};
 }
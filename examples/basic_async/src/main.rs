@@ -8,6 +8,7 @@ use crate::cornucopia::{
             author_name_by_id, author_name_starting_with, authors, books, select_translations,
             select_voice_actor_with_character, AuthorNameStartingWithParams,
         },
+        Queries, Statements,
     },
     types::public::SpongeBobCharacter,
 };
@@ -104,6 +105,47 @@ pub async fn main() {
         .await
         .unwrap();
     dbg!(translations);
+
+    // Bound how long a query may run with `timeout`. A slow query is cancelled on the
+    // server, and fails with the cancellation error instead of holding the connection.
+    let books = books()
+        .bind(&client)
+        .timeout(std::time::Duration::from_secs(5))
+        .all()
+        .await
+        .unwrap();
+    dbg!(books);
+
+    // When generated with `--prepare-all`, the `Statements` struct owns every statement.
+    // Preparing them all up front surfaces schema drift at startup instead of on first use.
+    // Prepared statements belong to a connection, so keep one `Statements` per connection.
+    let mut statements = Statements::new();
+    statements.prepare_all(&client).await.unwrap();
+    let authors = statements
+        .module_2
+        .authors
+        .bind(&client)
+        .all()
+        .await
+        .unwrap();
+    dbg!(authors);
+
+    // When generated with `--facade`, the `Queries` struct holds all statements, prepared
+    // lazily once per connection. Its clones are cheap and share them, so it can be stored in
+    // your web framework's state (e.g. axum's `State`). Deadpool's clients cache the statements
+    // themselves, so only the clients without a cache of their own use the facade's.
+    let queries = Queries::default();
+    let handle = tokio::spawn(async move {
+        let client = pool.get().await.unwrap();
+        queries
+            .module_2()
+            .books()
+            .bind(&client)
+            .all()
+            .await
+            .unwrap()
+    });
+    dbg!(handle.await.unwrap());
 }
 
 /// Connection pool configuration.
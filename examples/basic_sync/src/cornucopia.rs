@@ -88,7 +88,7 @@ pub mod types {
                 }
             }
         }
-        #[derive(Debug, postgres_types :: FromSql, Clone, PartialEq)]
+        #[derive(Debug, postgres_types::FromSql, Clone, PartialEq)]
         #[postgres(name = "voiceactor")]
         pub struct Voiceactor {
             #[postgres(name = "name")]
@@ -117,6 +117,9 @@ pub mod types {
             {
                 let fields = match *ty.kind() {
                     postgres_types::Kind::Composite(ref fields) => fields,
+                    postgres_types::Kind::Domain(ref inner) => {
+                        return Self::from_sql(inner, out);
+                    }
                     _ => unreachable!(),
                 };
                 let mut out = out;
@@ -135,7 +138,10 @@ pub mod types {
                 Ok(VoiceactorBorrowed { name, age })
             }
             fn accepts(ty: &postgres_types::Type) -> bool {
-                ty.name() == "voiceactor" && ty.schema() == "public"
+                match *ty.kind() {
+                    postgres_types::Kind::Domain(ref inner) => Self::accepts(inner),
+                    _ => ty.name() == "voiceactor" && ty.schema() == "public",
+                }
             }
         }
         impl<'a> postgres_types::ToSql for VoiceactorBorrowed<'a> {
@@ -209,7 +215,8 @@ pub mod types {
 #[allow(dead_code)]
 pub mod queries {
     pub mod module_1 {
-        use postgres::{fallible_iterator::FallibleIterator, GenericClient};
+        use cornucopia_sync::GenericClient;
+        use postgres::fallible_iterator::FallibleIterator;
         pub fn insert_book() -> InsertBookStmt {
             InsertBookStmt(cornucopia_sync::private::Stmt::new(
                 "INSERT INTO Book (title)
@@ -218,13 +225,29 @@ pub mod queries {
         }
         pub struct InsertBookStmt(cornucopia_sync::private::Stmt);
         impl InsertBookStmt {
+            /// Uses `sql`, e.g. composed at runtime, instead of the annotated query.
+            /// It is prepared as is, so it must take the same parameters and return the same columns.
+            pub fn with_sql(sql: impl Into<std::borrow::Cow<'static, str>>) -> Self {
+                Self(cornucopia_sync::private::Stmt::new(sql))
+            }
             pub fn bind<'a, C: GenericClient, T1: cornucopia_sync::StringSql>(
                 &'a mut self,
                 client: &'a mut C,
                 title: &'a T1,
             ) -> Result<u64, postgres::Error> {
-                let stmt = self.0.prepare(client)?;
-                client.execute(stmt, &[title])
+                cornucopia_sync::private::observe(
+                    &cornucopia_sync::QueryInfo {
+                        module: "module_1",
+                        query: "insert_book",
+                    },
+                    |rows| Some(*rows),
+                    move || {
+                        self.0.run(client, |client, stmt| {
+                            let params: &[&(dyn postgres_types::ToSql + Sync)] = &[title];
+                            client.execute(stmt, params)
+                        })
+                    },
+                )
             }
         }
     }
@@ -233,7 +256,7 @@ pub mod queries {
         pub struct AuthorNameStartingWithParams<T1: cornucopia_sync::StringSql> {
             pub start_str: T1,
         }
-        #[derive(Debug, Clone, PartialEq)]
+        #[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
         pub struct Authors {
             pub id: i32,
             pub name: String,
@@ -253,7 +276,7 @@ pub mod queries {
                 }
             }
         }
-        #[derive(Debug, Clone, PartialEq)]
+        #[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
         pub struct AuthorNameStartingWith {
             pub authorid: i32,
             pub name: String,
@@ -283,7 +306,7 @@ pub mod queries {
                 }
             }
         }
-        #[derive(Debug, Clone, PartialEq)]
+        #[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
         pub struct SelectTranslations {
             pub title: String,
             pub translations: Vec<String>,
@@ -305,13 +328,15 @@ pub mod queries {
                 }
             }
         }
-        use postgres::{fallible_iterator::FallibleIterator, GenericClient};
+        use cornucopia_sync::GenericClient;
+        use postgres::fallible_iterator::FallibleIterator;
         pub struct AuthorsQuery<'a, C: GenericClient, T, const N: usize> {
             client: &'a mut C,
             params: [&'a (dyn postgres_types::ToSql + Sync); N],
             stmt: &'a mut cornucopia_sync::private::Stmt,
             extractor: fn(&postgres::Row) -> AuthorsBorrowed,
             mapper: fn(AuthorsBorrowed) -> T,
+            info: cornucopia_sync::QueryInfo,
         }
         impl<'a, C, T: 'a, const N: usize> AuthorsQuery<'a, C, T, N>
         where
@@ -324,42 +349,131 @@ pub mod queries {
                     stmt: self.stmt,
                     extractor: self.extractor,
                     mapper,
+                    info: self.info,
                 }
             }
             pub fn one(self) -> Result<T, postgres::Error> {
-                let stmt = self.stmt.prepare(self.client)?;
-                let row = self.client.query_one(stmt, &self.params)?;
-                Ok((self.mapper)((self.extractor)(&row)))
+                let info = self.info;
+                cornucopia_sync::private::observe(
+                    &info,
+                    |_| Some(1),
+                    move || {
+                        let row = self.stmt.run(self.client, |client, stmt| {
+                            let params: &[&(dyn postgres_types::ToSql + Sync)] = &self.params;
+                            client.query_one(stmt, params)
+                        })?;
+                        Ok((self.mapper)((self.extractor)(&row)))
+                    },
+                )
             }
             pub fn all(self) -> Result<Vec<T>, postgres::Error> {
-                self.iter()?.collect()
+                let info = self.info;
+                cornucopia_sync::private::observe(
+                    &info,
+                    |it: &Vec<T>| Some(it.len() as u64),
+                    move || self.raw_iter()?.collect(),
+                )
             }
             pub fn opt(self) -> Result<Option<T>, postgres::Error> {
-                let stmt = self.stmt.prepare(self.client)?;
-                Ok(self
-                    .client
-                    .query_opt(stmt, &self.params)?
-                    .map(|row| (self.mapper)((self.extractor)(&row))))
+                let info = self.info;
+                cornucopia_sync::private::observe(
+                    &info,
+                    |it: &Option<T>| Some(it.is_some().into()),
+                    move || {
+                        Ok(self
+                            .stmt
+                            .run(self.client, |client, stmt| {
+                                let params: &[&(dyn postgres_types::ToSql + Sync)] = &self.params;
+                                client.query_opt(stmt, params)
+                            })?
+                            .map(|row| (self.mapper)((self.extractor)(&row))))
+                    },
+                )
             }
             pub fn iter(
                 self,
             ) -> Result<impl Iterator<Item = Result<T, postgres::Error>> + 'a, postgres::Error>
             {
-                let stmt = self.stmt.prepare(self.client)?;
+                let info = self.info;
+                cornucopia_sync::private::observe(&info, |_| None, move || self.raw_iter())
+            }
+            /// Passes each row to `visitor` as it is read, without building owned values,
+            /// returning the number of rows.
+            pub fn for_each(
+                self,
+                mut visitor: impl FnMut(AuthorsBorrowed),
+            ) -> Result<u64, postgres::Error> {
+                let info = self.info;
+                cornucopia_sync::private::observe(
+                    &info,
+                    |it: &u64| Some(*it),
+                    move || {
+                        let rows = self.stmt.run_borrowed(
+                            self.client,
+                            &self.params,
+                            |client, stmt, params| {
+                                client.query_raw(stmt, cornucopia_sync::private::slice_iter(params))
+                            },
+                        )?;
+                        let mut count = 0;
+                        let mut rows = rows;
+                        while let Some(row) = rows.next()? {
+                            visitor((self.extractor)(&row));
+                            count += 1;
+                        }
+                        Ok(count)
+                    },
+                )
+            }
+            fn raw_iter(
+                self,
+            ) -> Result<impl Iterator<Item = Result<T, postgres::Error>> + 'a, postgres::Error>
+            {
                 let it = self
-                    .client
-                    .query_raw(stmt, cornucopia_sync::private::slice_iter(&self.params))?
+                    .stmt
+                    .run_borrowed(self.client, &self.params, |client, stmt, params| {
+                        client.query_raw(stmt, cornucopia_sync::private::slice_iter(params))
+                    })?
                     .iterator()
                     .map(move |res| res.map(|row| (self.mapper)((self.extractor)(&row))));
                 Ok(it)
             }
         }
+        impl<'a, C, T: 'a, const N: usize> AuthorsQuery<'a, C, T, N>
+        where
+            C: cornucopia_sync::CursorClient,
+        {
+            /// Fetch rows through a portal, `batch_size` rows at a time.
+            pub fn cursor(
+                self,
+                batch_size: i32,
+            ) -> Result<impl Iterator<Item = Result<T, postgres::Error>> + 'a, postgres::Error>
+            {
+                let info = self.info;
+                cornucopia_sync::private::observe(
+                    &info,
+                    |_| None,
+                    move || {
+                        let stmt = self.stmt.prepare(self.client)?;
+                        let it = cornucopia_sync::private::cursor(
+                            self.client,
+                            stmt,
+                            &self.params,
+                            batch_size,
+                        )?
+                        .map(move |res| res.map(|row| (self.mapper)((self.extractor)(&row))));
+                        Ok(it)
+                    },
+                )
+            }
+        }
         pub struct StringQuery<'a, C: GenericClient, T, const N: usize> {
             client: &'a mut C,
             params: [&'a (dyn postgres_types::ToSql + Sync); N],
             stmt: &'a mut cornucopia_sync::private::Stmt,
             extractor: fn(&postgres::Row) -> &str,
             mapper: fn(&str) -> T,
+            info: cornucopia_sync::QueryInfo,
         }
         impl<'a, C, T: 'a, const N: usize> StringQuery<'a, C, T, N>
         where
@@ -372,42 +486,128 @@ pub mod queries {
                     stmt: self.stmt,
                     extractor: self.extractor,
                     mapper,
+                    info: self.info,
                 }
             }
             pub fn one(self) -> Result<T, postgres::Error> {
-                let stmt = self.stmt.prepare(self.client)?;
-                let row = self.client.query_one(stmt, &self.params)?;
-                Ok((self.mapper)((self.extractor)(&row)))
+                let info = self.info;
+                cornucopia_sync::private::observe(
+                    &info,
+                    |_| Some(1),
+                    move || {
+                        let row = self.stmt.run(self.client, |client, stmt| {
+                            let params: &[&(dyn postgres_types::ToSql + Sync)] = &self.params;
+                            client.query_one(stmt, params)
+                        })?;
+                        Ok((self.mapper)((self.extractor)(&row)))
+                    },
+                )
             }
             pub fn all(self) -> Result<Vec<T>, postgres::Error> {
-                self.iter()?.collect()
+                let info = self.info;
+                cornucopia_sync::private::observe(
+                    &info,
+                    |it: &Vec<T>| Some(it.len() as u64),
+                    move || self.raw_iter()?.collect(),
+                )
             }
             pub fn opt(self) -> Result<Option<T>, postgres::Error> {
-                let stmt = self.stmt.prepare(self.client)?;
-                Ok(self
-                    .client
-                    .query_opt(stmt, &self.params)?
-                    .map(|row| (self.mapper)((self.extractor)(&row))))
+                let info = self.info;
+                cornucopia_sync::private::observe(
+                    &info,
+                    |it: &Option<T>| Some(it.is_some().into()),
+                    move || {
+                        Ok(self
+                            .stmt
+                            .run(self.client, |client, stmt| {
+                                let params: &[&(dyn postgres_types::ToSql + Sync)] = &self.params;
+                                client.query_opt(stmt, params)
+                            })?
+                            .map(|row| (self.mapper)((self.extractor)(&row))))
+                    },
+                )
             }
             pub fn iter(
                 self,
             ) -> Result<impl Iterator<Item = Result<T, postgres::Error>> + 'a, postgres::Error>
             {
-                let stmt = self.stmt.prepare(self.client)?;
+                let info = self.info;
+                cornucopia_sync::private::observe(&info, |_| None, move || self.raw_iter())
+            }
+            /// Passes each row to `visitor` as it is read, without building owned values,
+            /// returning the number of rows.
+            pub fn for_each(self, mut visitor: impl FnMut(&str)) -> Result<u64, postgres::Error> {
+                let info = self.info;
+                cornucopia_sync::private::observe(
+                    &info,
+                    |it: &u64| Some(*it),
+                    move || {
+                        let rows = self.stmt.run_borrowed(
+                            self.client,
+                            &self.params,
+                            |client, stmt, params| {
+                                client.query_raw(stmt, cornucopia_sync::private::slice_iter(params))
+                            },
+                        )?;
+                        let mut count = 0;
+                        let mut rows = rows;
+                        while let Some(row) = rows.next()? {
+                            visitor((self.extractor)(&row));
+                            count += 1;
+                        }
+                        Ok(count)
+                    },
+                )
+            }
+            fn raw_iter(
+                self,
+            ) -> Result<impl Iterator<Item = Result<T, postgres::Error>> + 'a, postgres::Error>
+            {
                 let it = self
-                    .client
-                    .query_raw(stmt, cornucopia_sync::private::slice_iter(&self.params))?
+                    .stmt
+                    .run_borrowed(self.client, &self.params, |client, stmt, params| {
+                        client.query_raw(stmt, cornucopia_sync::private::slice_iter(params))
+                    })?
                     .iterator()
                     .map(move |res| res.map(|row| (self.mapper)((self.extractor)(&row))));
                 Ok(it)
             }
         }
+        impl<'a, C, T: 'a, const N: usize> StringQuery<'a, C, T, N>
+        where
+            C: cornucopia_sync::CursorClient,
+        {
+            /// Fetch rows through a portal, `batch_size` rows at a time.
+            pub fn cursor(
+                self,
+                batch_size: i32,
+            ) -> Result<impl Iterator<Item = Result<T, postgres::Error>> + 'a, postgres::Error>
+            {
+                let info = self.info;
+                cornucopia_sync::private::observe(
+                    &info,
+                    |_| None,
+                    move || {
+                        let stmt = self.stmt.prepare(self.client)?;
+                        let it = cornucopia_sync::private::cursor(
+                            self.client,
+                            stmt,
+                            &self.params,
+                            batch_size,
+                        )?
+                        .map(move |res| res.map(|row| (self.mapper)((self.extractor)(&row))));
+                        Ok(it)
+                    },
+                )
+            }
+        }
         pub struct AuthorNameStartingWithQuery<'a, C: GenericClient, T, const N: usize> {
             client: &'a mut C,
             params: [&'a (dyn postgres_types::ToSql + Sync); N],
             stmt: &'a mut cornucopia_sync::private::Stmt,
             extractor: fn(&postgres::Row) -> AuthorNameStartingWithBorrowed,
             mapper: fn(AuthorNameStartingWithBorrowed) -> T,
+            info: cornucopia_sync::QueryInfo,
         }
         impl<'a, C, T: 'a, const N: usize> AuthorNameStartingWithQuery<'a, C, T, N>
         where
@@ -423,42 +623,131 @@ pub mod queries {
                     stmt: self.stmt,
                     extractor: self.extractor,
                     mapper,
+                    info: self.info,
                 }
             }
             pub fn one(self) -> Result<T, postgres::Error> {
-                let stmt = self.stmt.prepare(self.client)?;
-                let row = self.client.query_one(stmt, &self.params)?;
-                Ok((self.mapper)((self.extractor)(&row)))
+                let info = self.info;
+                cornucopia_sync::private::observe(
+                    &info,
+                    |_| Some(1),
+                    move || {
+                        let row = self.stmt.run(self.client, |client, stmt| {
+                            let params: &[&(dyn postgres_types::ToSql + Sync)] = &self.params;
+                            client.query_one(stmt, params)
+                        })?;
+                        Ok((self.mapper)((self.extractor)(&row)))
+                    },
+                )
             }
             pub fn all(self) -> Result<Vec<T>, postgres::Error> {
-                self.iter()?.collect()
+                let info = self.info;
+                cornucopia_sync::private::observe(
+                    &info,
+                    |it: &Vec<T>| Some(it.len() as u64),
+                    move || self.raw_iter()?.collect(),
+                )
             }
             pub fn opt(self) -> Result<Option<T>, postgres::Error> {
-                let stmt = self.stmt.prepare(self.client)?;
-                Ok(self
-                    .client
-                    .query_opt(stmt, &self.params)?
-                    .map(|row| (self.mapper)((self.extractor)(&row))))
+                let info = self.info;
+                cornucopia_sync::private::observe(
+                    &info,
+                    |it: &Option<T>| Some(it.is_some().into()),
+                    move || {
+                        Ok(self
+                            .stmt
+                            .run(self.client, |client, stmt| {
+                                let params: &[&(dyn postgres_types::ToSql + Sync)] = &self.params;
+                                client.query_opt(stmt, params)
+                            })?
+                            .map(|row| (self.mapper)((self.extractor)(&row))))
+                    },
+                )
             }
             pub fn iter(
                 self,
             ) -> Result<impl Iterator<Item = Result<T, postgres::Error>> + 'a, postgres::Error>
             {
-                let stmt = self.stmt.prepare(self.client)?;
+                let info = self.info;
+                cornucopia_sync::private::observe(&info, |_| None, move || self.raw_iter())
+            }
+            /// Passes each row to `visitor` as it is read, without building owned values,
+            /// returning the number of rows.
+            pub fn for_each(
+                self,
+                mut visitor: impl FnMut(AuthorNameStartingWithBorrowed),
+            ) -> Result<u64, postgres::Error> {
+                let info = self.info;
+                cornucopia_sync::private::observe(
+                    &info,
+                    |it: &u64| Some(*it),
+                    move || {
+                        let rows = self.stmt.run_borrowed(
+                            self.client,
+                            &self.params,
+                            |client, stmt, params| {
+                                client.query_raw(stmt, cornucopia_sync::private::slice_iter(params))
+                            },
+                        )?;
+                        let mut count = 0;
+                        let mut rows = rows;
+                        while let Some(row) = rows.next()? {
+                            visitor((self.extractor)(&row));
+                            count += 1;
+                        }
+                        Ok(count)
+                    },
+                )
+            }
+            fn raw_iter(
+                self,
+            ) -> Result<impl Iterator<Item = Result<T, postgres::Error>> + 'a, postgres::Error>
+            {
                 let it = self
-                    .client
-                    .query_raw(stmt, cornucopia_sync::private::slice_iter(&self.params))?
+                    .stmt
+                    .run_borrowed(self.client, &self.params, |client, stmt, params| {
+                        client.query_raw(stmt, cornucopia_sync::private::slice_iter(params))
+                    })?
                     .iterator()
                     .map(move |res| res.map(|row| (self.mapper)((self.extractor)(&row))));
                 Ok(it)
             }
         }
+        impl<'a, C, T: 'a, const N: usize> AuthorNameStartingWithQuery<'a, C, T, N>
+        where
+            C: cornucopia_sync::CursorClient,
+        {
+            /// Fetch rows through a portal, `batch_size` rows at a time.
+            pub fn cursor(
+                self,
+                batch_size: i32,
+            ) -> Result<impl Iterator<Item = Result<T, postgres::Error>> + 'a, postgres::Error>
+            {
+                let info = self.info;
+                cornucopia_sync::private::observe(
+                    &info,
+                    |_| None,
+                    move || {
+                        let stmt = self.stmt.prepare(self.client)?;
+                        let it = cornucopia_sync::private::cursor(
+                            self.client,
+                            stmt,
+                            &self.params,
+                            batch_size,
+                        )?
+                        .map(move |res| res.map(|row| (self.mapper)((self.extractor)(&row))));
+                        Ok(it)
+                    },
+                )
+            }
+        }
         pub struct PublicVoiceactorQuery<'a, C: GenericClient, T, const N: usize> {
             client: &'a mut C,
             params: [&'a (dyn postgres_types::ToSql + Sync); N],
             stmt: &'a mut cornucopia_sync::private::Stmt,
             extractor: fn(&postgres::Row) -> super::super::types::public::VoiceactorBorrowed,
             mapper: fn(super::super::types::public::VoiceactorBorrowed) -> T,
+            info: cornucopia_sync::QueryInfo,
         }
         impl<'a, C, T: 'a, const N: usize> PublicVoiceactorQuery<'a, C, T, N>
         where
@@ -474,42 +763,131 @@ pub mod queries {
                     stmt: self.stmt,
                     extractor: self.extractor,
                     mapper,
+                    info: self.info,
                 }
             }
             pub fn one(self) -> Result<T, postgres::Error> {
-                let stmt = self.stmt.prepare(self.client)?;
-                let row = self.client.query_one(stmt, &self.params)?;
-                Ok((self.mapper)((self.extractor)(&row)))
+                let info = self.info;
+                cornucopia_sync::private::observe(
+                    &info,
+                    |_| Some(1),
+                    move || {
+                        let row = self.stmt.run(self.client, |client, stmt| {
+                            let params: &[&(dyn postgres_types::ToSql + Sync)] = &self.params;
+                            client.query_one(stmt, params)
+                        })?;
+                        Ok((self.mapper)((self.extractor)(&row)))
+                    },
+                )
             }
             pub fn all(self) -> Result<Vec<T>, postgres::Error> {
-                self.iter()?.collect()
+                let info = self.info;
+                cornucopia_sync::private::observe(
+                    &info,
+                    |it: &Vec<T>| Some(it.len() as u64),
+                    move || self.raw_iter()?.collect(),
+                )
             }
             pub fn opt(self) -> Result<Option<T>, postgres::Error> {
-                let stmt = self.stmt.prepare(self.client)?;
-                Ok(self
-                    .client
-                    .query_opt(stmt, &self.params)?
-                    .map(|row| (self.mapper)((self.extractor)(&row))))
+                let info = self.info;
+                cornucopia_sync::private::observe(
+                    &info,
+                    |it: &Option<T>| Some(it.is_some().into()),
+                    move || {
+                        Ok(self
+                            .stmt
+                            .run(self.client, |client, stmt| {
+                                let params: &[&(dyn postgres_types::ToSql + Sync)] = &self.params;
+                                client.query_opt(stmt, params)
+                            })?
+                            .map(|row| (self.mapper)((self.extractor)(&row))))
+                    },
+                )
             }
             pub fn iter(
                 self,
             ) -> Result<impl Iterator<Item = Result<T, postgres::Error>> + 'a, postgres::Error>
             {
-                let stmt = self.stmt.prepare(self.client)?;
+                let info = self.info;
+                cornucopia_sync::private::observe(&info, |_| None, move || self.raw_iter())
+            }
+            /// Passes each row to `visitor` as it is read, without building owned values,
+            /// returning the number of rows.
+            pub fn for_each(
+                self,
+                mut visitor: impl FnMut(super::super::types::public::VoiceactorBorrowed),
+            ) -> Result<u64, postgres::Error> {
+                let info = self.info;
+                cornucopia_sync::private::observe(
+                    &info,
+                    |it: &u64| Some(*it),
+                    move || {
+                        let rows = self.stmt.run_borrowed(
+                            self.client,
+                            &self.params,
+                            |client, stmt, params| {
+                                client.query_raw(stmt, cornucopia_sync::private::slice_iter(params))
+                            },
+                        )?;
+                        let mut count = 0;
+                        let mut rows = rows;
+                        while let Some(row) = rows.next()? {
+                            visitor((self.extractor)(&row));
+                            count += 1;
+                        }
+                        Ok(count)
+                    },
+                )
+            }
+            fn raw_iter(
+                self,
+            ) -> Result<impl Iterator<Item = Result<T, postgres::Error>> + 'a, postgres::Error>
+            {
                 let it = self
-                    .client
-                    .query_raw(stmt, cornucopia_sync::private::slice_iter(&self.params))?
+                    .stmt
+                    .run_borrowed(self.client, &self.params, |client, stmt, params| {
+                        client.query_raw(stmt, cornucopia_sync::private::slice_iter(params))
+                    })?
                     .iterator()
                     .map(move |res| res.map(|row| (self.mapper)((self.extractor)(&row))));
                 Ok(it)
             }
         }
+        impl<'a, C, T: 'a, const N: usize> PublicVoiceactorQuery<'a, C, T, N>
+        where
+            C: cornucopia_sync::CursorClient,
+        {
+            /// Fetch rows through a portal, `batch_size` rows at a time.
+            pub fn cursor(
+                self,
+                batch_size: i32,
+            ) -> Result<impl Iterator<Item = Result<T, postgres::Error>> + 'a, postgres::Error>
+            {
+                let info = self.info;
+                cornucopia_sync::private::observe(
+                    &info,
+                    |_| None,
+                    move || {
+                        let stmt = self.stmt.prepare(self.client)?;
+                        let it = cornucopia_sync::private::cursor(
+                            self.client,
+                            stmt,
+                            &self.params,
+                            batch_size,
+                        )?
+                        .map(move |res| res.map(|row| (self.mapper)((self.extractor)(&row))));
+                        Ok(it)
+                    },
+                )
+            }
+        }
         pub struct SelectTranslationsQuery<'a, C: GenericClient, T, const N: usize> {
             client: &'a mut C,
             params: [&'a (dyn postgres_types::ToSql + Sync); N],
             stmt: &'a mut cornucopia_sync::private::Stmt,
             extractor: fn(&postgres::Row) -> SelectTranslationsBorrowed,
             mapper: fn(SelectTranslationsBorrowed) -> T,
+            info: cornucopia_sync::QueryInfo,
         }
         impl<'a, C, T: 'a, const N: usize> SelectTranslationsQuery<'a, C, T, N>
         where
@@ -525,36 +903,124 @@ pub mod queries {
                     stmt: self.stmt,
                     extractor: self.extractor,
                     mapper,
+                    info: self.info,
                 }
             }
             pub fn one(self) -> Result<T, postgres::Error> {
-                let stmt = self.stmt.prepare(self.client)?;
-                let row = self.client.query_one(stmt, &self.params)?;
-                Ok((self.mapper)((self.extractor)(&row)))
+                let info = self.info;
+                cornucopia_sync::private::observe(
+                    &info,
+                    |_| Some(1),
+                    move || {
+                        let row = self.stmt.run(self.client, |client, stmt| {
+                            let params: &[&(dyn postgres_types::ToSql + Sync)] = &self.params;
+                            client.query_one(stmt, params)
+                        })?;
+                        Ok((self.mapper)((self.extractor)(&row)))
+                    },
+                )
             }
             pub fn all(self) -> Result<Vec<T>, postgres::Error> {
-                self.iter()?.collect()
+                let info = self.info;
+                cornucopia_sync::private::observe(
+                    &info,
+                    |it: &Vec<T>| Some(it.len() as u64),
+                    move || self.raw_iter()?.collect(),
+                )
             }
             pub fn opt(self) -> Result<Option<T>, postgres::Error> {
-                let stmt = self.stmt.prepare(self.client)?;
-                Ok(self
-                    .client
-                    .query_opt(stmt, &self.params)?
-                    .map(|row| (self.mapper)((self.extractor)(&row))))
+                let info = self.info;
+                cornucopia_sync::private::observe(
+                    &info,
+                    |it: &Option<T>| Some(it.is_some().into()),
+                    move || {
+                        Ok(self
+                            .stmt
+                            .run(self.client, |client, stmt| {
+                                let params: &[&(dyn postgres_types::ToSql + Sync)] = &self.params;
+                                client.query_opt(stmt, params)
+                            })?
+                            .map(|row| (self.mapper)((self.extractor)(&row))))
+                    },
+                )
             }
             pub fn iter(
                 self,
             ) -> Result<impl Iterator<Item = Result<T, postgres::Error>> + 'a, postgres::Error>
             {
-                let stmt = self.stmt.prepare(self.client)?;
+                let info = self.info;
+                cornucopia_sync::private::observe(&info, |_| None, move || self.raw_iter())
+            }
+            /// Passes each row to `visitor` as it is read, without building owned values,
+            /// returning the number of rows.
+            pub fn for_each(
+                self,
+                mut visitor: impl FnMut(SelectTranslationsBorrowed),
+            ) -> Result<u64, postgres::Error> {
+                let info = self.info;
+                cornucopia_sync::private::observe(
+                    &info,
+                    |it: &u64| Some(*it),
+                    move || {
+                        let rows = self.stmt.run_borrowed(
+                            self.client,
+                            &self.params,
+                            |client, stmt, params| {
+                                client.query_raw(stmt, cornucopia_sync::private::slice_iter(params))
+                            },
+                        )?;
+                        let mut count = 0;
+                        let mut rows = rows;
+                        while let Some(row) = rows.next()? {
+                            visitor((self.extractor)(&row));
+                            count += 1;
+                        }
+                        Ok(count)
+                    },
+                )
+            }
+            fn raw_iter(
+                self,
+            ) -> Result<impl Iterator<Item = Result<T, postgres::Error>> + 'a, postgres::Error>
+            {
                 let it = self
-                    .client
-                    .query_raw(stmt, cornucopia_sync::private::slice_iter(&self.params))?
+                    .stmt
+                    .run_borrowed(self.client, &self.params, |client, stmt, params| {
+                        client.query_raw(stmt, cornucopia_sync::private::slice_iter(params))
+                    })?
                     .iterator()
                     .map(move |res| res.map(|row| (self.mapper)((self.extractor)(&row))));
                 Ok(it)
             }
         }
+        impl<'a, C, T: 'a, const N: usize> SelectTranslationsQuery<'a, C, T, N>
+        where
+            C: cornucopia_sync::CursorClient,
+        {
+            /// Fetch rows through a portal, `batch_size` rows at a time.
+            pub fn cursor(
+                self,
+                batch_size: i32,
+            ) -> Result<impl Iterator<Item = Result<T, postgres::Error>> + 'a, postgres::Error>
+            {
+                let info = self.info;
+                cornucopia_sync::private::observe(
+                    &info,
+                    |_| None,
+                    move || {
+                        let stmt = self.stmt.prepare(self.client)?;
+                        let it = cornucopia_sync::private::cursor(
+                            self.client,
+                            stmt,
+                            &self.params,
+                            batch_size,
+                        )?
+                        .map(move |res| res.map(|row| (self.mapper)((self.extractor)(&row))));
+                        Ok(it)
+                    },
+                )
+            }
+        }
         pub fn authors() -> AuthorsStmt {
             AuthorsStmt(cornucopia_sync::private::Stmt::new(
                 "SELECT
@@ -565,6 +1031,11 @@ FROM
         }
         pub struct AuthorsStmt(cornucopia_sync::private::Stmt);
         impl AuthorsStmt {
+            /// Uses `sql`, e.g. composed at runtime, instead of the annotated query.
+            /// It is prepared as is, so it must take the same parameters and return the same columns.
+            pub fn with_sql(sql: impl Into<std::borrow::Cow<'static, str>>) -> Self {
+                Self(cornucopia_sync::private::Stmt::new(sql))
+            }
             pub fn bind<'a, C: GenericClient>(
                 &'a mut self,
                 client: &'a mut C,
@@ -579,6 +1050,10 @@ FROM
                         country: row.get(2),
                     },
                     mapper: |it| <Authors>::from(it),
+                    info: cornucopia_sync::QueryInfo {
+                        module: "module_2",
+                        query: "authors",
+                    },
                 }
             }
         }
@@ -592,6 +1067,11 @@ FROM
         }
         pub struct BooksStmt(cornucopia_sync::private::Stmt);
         impl BooksStmt {
+            /// Uses `sql`, e.g. composed at runtime, instead of the annotated query.
+            /// It is prepared as is, so it must take the same parameters and return the same columns.
+            pub fn with_sql(sql: impl Into<std::borrow::Cow<'static, str>>) -> Self {
+                Self(cornucopia_sync::private::Stmt::new(sql))
+            }
             pub fn bind<'a, C: GenericClient>(
                 &'a mut self,
                 client: &'a mut C,
@@ -602,6 +1082,10 @@ FROM
                     stmt: &mut self.0,
                     extractor: |row| row.get(0),
                     mapper: |it| it.into(),
+                    info: cornucopia_sync::QueryInfo {
+                        module: "module_2",
+                        query: "books",
+                    },
                 }
             }
         }
@@ -617,6 +1101,11 @@ WHERE
         }
         pub struct AuthorNameByIdStmt(cornucopia_sync::private::Stmt);
         impl AuthorNameByIdStmt {
+            /// Uses `sql`, e.g. composed at runtime, instead of the annotated query.
+            /// It is prepared as is, so it must take the same parameters and return the same columns.
+            pub fn with_sql(sql: impl Into<std::borrow::Cow<'static, str>>) -> Self {
+                Self(cornucopia_sync::private::Stmt::new(sql))
+            }
             pub fn bind<'a, C: GenericClient>(
                 &'a mut self,
                 client: &'a mut C,
@@ -628,6 +1117,10 @@ WHERE
                     stmt: &mut self.0,
                     extractor: |row| row.get(0),
                     mapper: |it| it.into(),
+                    info: cornucopia_sync::QueryInfo {
+                        module: "module_2",
+                        query: "author_name_by_id",
+                    },
                 }
             }
         }
@@ -648,6 +1141,11 @@ WHERE
         }
         pub struct AuthorNameStartingWithStmt(cornucopia_sync::private::Stmt);
         impl AuthorNameStartingWithStmt {
+            /// Uses `sql`, e.g. composed at runtime, instead of the annotated query.
+            /// It is prepared as is, so it must take the same parameters and return the same columns.
+            pub fn with_sql(sql: impl Into<std::borrow::Cow<'static, str>>) -> Self {
+                Self(cornucopia_sync::private::Stmt::new(sql))
+            }
             pub fn bind<'a, C: GenericClient, T1: cornucopia_sync::StringSql>(
                 &'a mut self,
                 client: &'a mut C,
@@ -664,6 +1162,10 @@ WHERE
                         title: row.get(3),
                     },
                     mapper: |it| <AuthorNameStartingWith>::from(it),
+                    info: cornucopia_sync::QueryInfo {
+                        module: "module_2",
+                        query: "author_name_starting_with",
+                    },
                 }
             }
         }
@@ -695,6 +1197,11 @@ WHERE
         }
         pub struct SelectVoiceActorWithCharacterStmt(cornucopia_sync::private::Stmt);
         impl SelectVoiceActorWithCharacterStmt {
+            /// Uses `sql`, e.g. composed at runtime, instead of the annotated query.
+            /// It is prepared as is, so it must take the same parameters and return the same columns.
+            pub fn with_sql(sql: impl Into<std::borrow::Cow<'static, str>>) -> Self {
+                Self(cornucopia_sync::private::Stmt::new(sql))
+            }
             pub fn bind<'a, C: GenericClient>(
                 &'a mut self,
                 client: &'a mut C,
@@ -707,6 +1214,10 @@ WHERE
                     stmt: &mut self.0,
                     extractor: |row| row.get(0),
                     mapper: |it| it.into(),
+                    info: cornucopia_sync::QueryInfo {
+                        module: "module_2",
+                        query: "select_voice_actor_with_character",
+                    },
                 }
             }
         }
@@ -721,6 +1232,11 @@ FROM
         }
         pub struct SelectTranslationsStmt(cornucopia_sync::private::Stmt);
         impl SelectTranslationsStmt {
+            /// Uses `sql`, e.g. composed at runtime, instead of the annotated query.
+            /// It is prepared as is, so it must take the same parameters and return the same columns.
+            pub fn with_sql(sql: impl Into<std::borrow::Cow<'static, str>>) -> Self {
+                Self(cornucopia_sync::private::Stmt::new(sql))
+            }
             pub fn bind<'a, C: GenericClient>(
                 &'a mut self,
                 client: &'a mut C,
@@ -734,8 +1250,23 @@ FROM
                         translations: row.get(1),
                     },
                     mapper: |it| <SelectTranslations>::from(it),
+                    info: cornucopia_sync::QueryInfo {
+                        module: "module_2",
+                        query: "select_translations",
+                    },
                 }
             }
         }
     }
 }
+/// Flat re-exports of the generated items, to import them all with `use prelude::*`
+#[allow(unused_imports)]
+pub mod prelude {
+    pub use super::queries::module_2::AuthorNameStartingWith;
+    pub use super::queries::module_2::AuthorNameStartingWithParams;
+    pub use super::queries::module_2::Authors;
+    pub use super::queries::module_2::SelectTranslations;
+    pub use super::types::public::SpongeBobCharacter;
+    pub use super::types::public::Voiceactor;
+    pub use cornucopia_sync::{GenericClient, Params as _};
+}
@@ -0,0 +1,2 @@
+--! team_names
+SELECT name FROM team ORDER BY id;
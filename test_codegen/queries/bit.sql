@@ -0,0 +1,8 @@
+--! insert_permission (mask?)
+INSERT INTO permission (id, flags, mask, history) VALUES (:id, :flags, :mask, :history);
+
+--! permissions : (mask?)
+SELECT * FROM permission ORDER BY id;
+
+--! granted
+SELECT flags & B'0110' AS granted, flags::text AS text FROM permission WHERE id = :id;
@@ -0,0 +1,14 @@
+--! insert_region (samples?)
+INSERT INTO region (id, bounds, samples) VALUES (:id, :bounds, :samples);
+
+--! regions : (samples?)
+SELECT * FROM region ORDER BY id;
+
+--! region_dims
+SELECT id, cube_dim(bounds) AS dim FROM region WHERE bounds <@ :container ORDER BY id;
+
+--! insert_labelled
+INSERT INTO labelled (id, label, note) VALUES (:id, :label, :note);
+
+--! labelled
+SELECT id, label, note FROM labelled WHERE label = ANY(:labels) ORDER BY id;
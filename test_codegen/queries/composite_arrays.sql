@@ -0,0 +1,14 @@
+--! insert_shipment
+INSERT INTO shipment (id, items) VALUES (:id, :items);
+
+--! shipments
+SELECT id, items FROM shipment ORDER BY id;
+
+--! shipments_with_item
+SELECT id FROM shipment WHERE :item = ANY(items) ORDER BY id;
+
+--! shipments_containing
+SELECT id FROM shipment WHERE items @> :items ORDER BY id;
+
+--! shipment_items : (items?)
+SELECT items FROM shipment WHERE id = :id;
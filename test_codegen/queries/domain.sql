@@ -5,4 +5,10 @@ SELECT txt, json, nb, arr FROM nightmare_domain;
 INSERT INTO nightmare_domain (txt, json, nb, arr, composite) VALUES (:txt, :json, :nb, :arr, :composite);
 
 --! select_nightmare_domain_null: (txt?, json?, nb?, arr?[?], composite?)
-SELECT * FROM nightmare_domain;
\ No newline at end of file
+SELECT * FROM nightmare_domain;
+
+--! insert_checked
+INSERT INTO checked (item, ints, items, holder) VALUES (:item, :ints, :items, :holder);
+
+--! select_checked
+SELECT item, ints, items, holder FROM checked;
@@ -0,0 +1,20 @@
+--! insert_preference (extra?)
+INSERT INTO preference (id, attrs, extra) VALUES (:id, :attrs, :extra);
+
+--! preferences : (extra?)
+SELECT * FROM preference ORDER BY id;
+
+--! preference_keys
+SELECT id FROM preference WHERE attrs ?& :keys::text[] ORDER BY id;
+
+--! insert_category (aliases?)
+INSERT INTO category (id, name, path, aliases) VALUES (:id, :name, :path, :aliases);
+
+--! categories : (aliases?)
+SELECT * FROM category ORDER BY id;
+
+--! category_by_name
+SELECT id FROM category WHERE name = :name;
+
+--! subcategories
+SELECT path FROM category WHERE path <@ :root ORDER BY path;
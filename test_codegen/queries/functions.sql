@@ -0,0 +1,8 @@
+--! shelves_from
+SELECT * FROM shelves_from(:first);
+
+--! shelf_labels : (id!)
+SELECT * FROM shelf_labels(:first);
+
+--! repeated
+SELECT * FROM repeated(:value::text, :times);
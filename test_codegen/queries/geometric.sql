@@ -0,0 +1,9 @@
+--! insert_shape (edge?)
+INSERT INTO shape (id, at, edge, segment, bounds, route, area, zone)
+VALUES (:id, :at, :edge, :segment, :bounds, :route, :area, :zone);
+
+--! shapes : (edge?)
+SELECT * FROM shape ORDER BY id;
+
+--! contained
+SELECT id FROM shape WHERE :point::point <@ area AND :point <@ zone ORDER BY id;
@@ -0,0 +1,5 @@
+--: SelectBook from params
+--: FindBookByNameParams from params
+
+--! books_named FindBookByNameParams: SelectBook
+SELECT * FROM book WHERE name = :name;
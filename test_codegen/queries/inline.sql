@@ -0,0 +1,8 @@
+--! insert_listing (exchange?)
+INSERT INTO listing (id, symbol, exchange, digest) VALUES (:id, :symbol, :exchange, :digest);
+
+--! listings : (exchange?)
+SELECT * FROM listing ORDER BY id;
+
+--! listing_symbols
+SELECT symbol, upper(symbol) AS upper_symbol FROM listing WHERE id = :id;
@@ -0,0 +1,8 @@
+--! insert_subscription (grace?)
+INSERT INTO subscription (id, period, grace) VALUES (:id, :period, :grace);
+
+--! subscriptions : (grace?)
+SELECT * FROM subscription ORDER BY id;
+
+--! renewal
+SELECT period + '1 day 2 hours'::interval AS renewal FROM subscription WHERE id = :id;
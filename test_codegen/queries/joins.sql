@@ -0,0 +1,29 @@
+--! insert_teams
+INSERT INTO team (id, name) VALUES (1, 'Core'), (2, 'Docs');
+
+--! insert_members
+INSERT INTO member (id, team_id, name) VALUES (1, 1, 'Ada'), (2, 1, 'Grace'), (3, NULL, 'Linus');
+
+--! team_members
+SELECT t.name AS team, m.name AS member FROM team t LEFT JOIN member m ON m.team_id = t.id ORDER BY t.id, m.id;
+
+--! member_teams
+SELECT m.name, t.name AS team FROM team t RIGHT OUTER JOIN member m ON m.team_id = t.id ORDER BY m.id;
+
+--! all_members
+SELECT team.name AS team, member.name AS member
+FROM team FULL JOIN member ON member.team_id = team.id
+ORDER BY team.id, member.id;
+
+--! teammates
+SELECT m.name, other.name AS teammate
+FROM member m LEFT JOIN member other ON other.team_id = m.team_id AND other.id <> m.id
+ORDER BY m.id, other.id;
+
+--! team_leads : (lead!)
+SELECT t.name, m.name AS lead FROM team t LEFT JOIN member m ON m.team_id = t.id AND m.id = 1 WHERE t.id = 1;
+
+--! member_counts
+SELECT t.name, c.members
+FROM team t LEFT JOIN (SELECT team_id, count(*) AS members FROM member GROUP BY team_id) c ON c.team_id = t.id
+ORDER BY t.id;
@@ -0,0 +1,9 @@
+--! insert_grids
+INSERT INTO grid (id, cells, labels)
+VALUES (1, '{{1,2,3},{4,5,6}}', '{{a,NULL},{c,d}}'), (2, '{}', NULL);
+
+--! grids : (labels?[?])
+SELECT * FROM grid ORDER BY id;
+
+--! grid_cells
+SELECT cells FROM grid WHERE id = :id;
@@ -0,0 +1,6 @@
+--! insert_media
+INSERT INTO media (content) VALUES (:content) RETURNING id;
+
+--! media_content
+--# cardinality = one
+SELECT content FROM media WHERE id = :id;
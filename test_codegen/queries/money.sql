@@ -0,0 +1,8 @@
+--! insert_invoice (discount?)
+INSERT INTO invoice (id, total, discount, installments) VALUES (:id, :total, :discount, :installments);
+
+--! invoices : (discount?)
+SELECT * FROM invoice ORDER BY id;
+
+--! invoice_totals
+SELECT id, total + 0.5::money AS total FROM invoice ORDER BY id;
@@ -9,7 +9,10 @@ INSERT INTO named (price, name, show) VALUES (:price, :name, false) RETURNING id
 --! named: Named
 SELECT * FROM named;
 --! named_by_id: Named
+--# renamed(query = find_named, row = NamedRow)
 SELECT * FROM named WHERE id = :id;
+--! named_priced_above (price: crate::Price) : NamedPriced(price?: crate::Price)
+SELECT name, price FROM named WHERE price > :price ORDER BY price;
 
 --: named_composite(wow?,such_cool?)
 --: "named_composite.with_dot"("this.is.inconceivable"?)
@@ -18,3 +21,5 @@ SELECT * FROM named WHERE id = :id;
 INSERT INTO named_complex (named, "named.with_dot") VALUES (:named, :named_with_dot);
 --! named_complex: NamedComplex("named.with_dot"?)
 SELECT * FROM named_complex;
+--! named_complex_with_dot_among: NamedComplex("named.with_dot"?)
+SELECT * FROM named_complex WHERE "named.with_dot" = ANY (:with_dot);
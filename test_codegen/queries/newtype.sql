@@ -0,0 +1,53 @@
+--#! trace(target = "test_codegen::newtype")
+--#! constraints(newtype_book_author_id_fkey = UnknownAuthor, newtype_author_pkey = DuplicateAuthor)
+
+--! new_author
+INSERT INTO newtype_author (name) VALUES (:name) RETURNING id;
+--! new_book
+INSERT INTO newtype_book (author_id, slug) VALUES (:author_id, :slug) RETURNING id;
+--! books_by_author
+SELECT b.id, b.slug, a.name FROM newtype_book b JOIN newtype_author a ON a.id = b.author_id WHERE a.id = :author_id;
+--! books_by_authors
+--# trace(level = "trace")
+SELECT id, author_id, slug FROM newtype_book WHERE author_id = ANY(:author_ids) ORDER BY id;
+--! book_by_slug
+SELECT id FROM newtype_book WHERE slug = :slug;
+--! touch_books
+--# trace(level = info, target = "test_codegen::admin")
+UPDATE newtype_book SET slug = slug WHERE id = ANY(:ids);
+--! rename_book
+--# audit(actor = author_id)
+UPDATE newtype_book SET slug = :slug WHERE id = :id AND author_id = :author_id;
+
+--! copy_books
+--# copy
+COPY newtype_book (author_id, slug) FROM STDIN;
+
+--! toggle_book
+MERGE INTO newtype_book b
+USING (SELECT 1) s ON b.id = :id
+WHEN MATCHED THEN DELETE
+WHEN NOT MATCHED THEN INSERT (author_id, slug) VALUES (:author_id, :slug);
+
+--! export_books : ExportedBook()
+--# copy
+COPY (SELECT id, author_id, slug FROM newtype_book ORDER BY id) TO STDOUT;
+
+--! export_books_csv
+--# copy(format = csv)
+COPY newtype_book (id, slug) TO STDOUT;
+
+--! books_per_author
+SELECT author_id, count(*) AS books FROM newtype_book GROUP BY ROLLUP (author_id) ORDER BY author_id;
+--! books_per_known_author: (author_id!)
+SELECT author_id, count(*) AS books FROM newtype_book GROUP BY ROLLUP (author_id) HAVING GROUPING(author_id) = 0 ORDER BY author_id;
+
+--! list_books
+--# paginate(by(author_id, id))
+SELECT id, author_id, slug FROM newtype_book WHERE slug <> :excluded;
+--! list_hinted_books
+--# paginate(by(id))
+/*+ SeqScan(newtype_book) */ SELECT id, slug FROM newtype_book WHERE slug <> :excluded;
+--! export_hinted_books : ExportedBook()
+--# copy
+/*+ IndexScan(newtype_book) */ COPY (SELECT id, author_id, slug FROM newtype_book ORDER BY id) TO STDOUT;
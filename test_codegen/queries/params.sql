@@ -4,9 +4,53 @@ INSERT INTO book (author, name) VALUES (:author, :name);
 --! select_book: (author?)
 SELECT * FROM book;
 
+--! count_books_by_author
+--# cardinality = one
+SELECT count(*) FROM book WHERE author IS NOT DISTINCT FROM :author?;
+
+--! find_book_by_name FindBookByNameParams(): (author?)
+--# cardinality = opt
+SELECT * FROM book WHERE name = :name;
+
+--! titles_by_author TitlesByAuthorParams()
+SELECT name FROM book WHERE author = $1 AND length(name) > $2 ORDER BY name;
+
+--: fragment book_columns
+author, name;
+
+--: fragment name_starts_with
+name LIKE :prefix || '%';
+
+--! books_starting_with: (author?)
+SELECT
+  --: include(book_columns)
+FROM book
+WHERE --: include(name_starts_with)
+--: include(shared.ordered_by_name)
+;
+
+--: fragment all_books
+TRUE;
+
+--: fragment with_author
+author IS NOT NULL;
+
+--: fragment by_author_then_name
+author, name;
+
+--! books_ordered: (author?)
+--# variants(order = "name|by_author_then_name", filter = "all_books|with_author")
+SELECT * FROM book
+WHERE --: include(filter)
+ORDER BY --: include(order)
+;
+
 --! find_books: (author?)
 SELECT * FROM book WHERE name = ANY (:title);
 
+--! characters_among
+SELECT c FROM unnest(enum_range(NULL::spongebob_character)) AS c WHERE c = ANY (:characters) ORDER BY c;
+
 --! params_use_twice
 UPDATE book SET name = :name WHERE length(name) > 42 AND length(:name) < 42;
 
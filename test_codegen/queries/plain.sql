@@ -0,0 +1,6 @@
+-- Plain SQL, annotated in `queries.toml`
+INSERT INTO book (author, name) VALUES (:author, :name);
+
+SELECT * FROM book WHERE name LIKE :prefix || '%' ORDER BY name;
+
+SELECT count(*) FROM book WHERE author IS NOT DISTINCT FROM :author;
@@ -0,0 +1,5 @@
+--! bump_counter : (value?, previous?)
+CALL bump_counter($1, $2, NULL);
+
+--! reset_counter
+CALL reset_counter(id => $1);
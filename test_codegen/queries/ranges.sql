@@ -0,0 +1,24 @@
+--! insert_reservation (days?, amounts?)
+INSERT INTO reservation (id, seats, during, days, amounts, names, slots)
+VALUES (:id, :seats, :during, :days, :amounts, :names, :slots);
+
+--! reservations : (days?, amounts?)
+SELECT * FROM reservation ORDER BY id;
+
+--! reservations_during
+SELECT id FROM reservation WHERE during @> :at::timestamptz ORDER BY id;
+
+--! overlapping_reservations
+SELECT id FROM reservation WHERE seats && :seats ORDER BY id;
+
+--! reservation_bounds : (first_seat?)
+SELECT lower(seats) AS first_seat, upper_inc(seats) AS last_included, isempty(seats) AS empty FROM reservation WHERE id = :id;
+
+--! insert_availability (days?)
+INSERT INTO availability (id, seats, days) VALUES (:id, :seats, :days);
+
+--! availabilities : (days?)
+SELECT * FROM availability ORDER BY id;
+
+--! available_for
+SELECT id FROM availability WHERE seats @> :seats::int4range ORDER BY id;
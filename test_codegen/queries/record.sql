@@ -0,0 +1,13 @@
+--! items : (item: (i32, String, Option<bool>))
+SELECT ROW(n, 'item ' || n, NULLIF(n > 1, false)) AS item FROM generate_series(1, 2) n ORDER BY n;
+
+--! latest_per_series : (latest: (i32, f64))
+SELECT s.series, l.latest
+FROM generate_series(1, 2) s(series)
+CROSS JOIN LATERAL (
+    SELECT ROW(v, v / 2.0::float8) AS latest FROM generate_series(s.series, 3) v ORDER BY v DESC LIMIT 1
+) l
+ORDER BY s.series;
+
+--! record_columns
+SELECT * FROM json_to_record('{"a": 1, "b": "x"}') AS t(a int, b text);
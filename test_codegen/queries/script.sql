@@ -0,0 +1,8 @@
+--! reset_scripted
+-- Statements run in order, and all or none of them are applied
+DELETE FROM scripted;
+INSERT INTO scripted VALUES (1), (2);
+UPDATE scripted SET id = id * 10;
+
+--! scripted_ids
+SELECT id FROM scripted ORDER BY id;
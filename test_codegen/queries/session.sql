@@ -0,0 +1,4 @@
+--#! session(timezone = "UTC", intervalstyle = iso_8601)
+
+--! session_settings : (timezone!, day!)
+SELECT current_setting('TimeZone') AS timezone, '1 day'::interval::text AS day;
@@ -0,0 +1,4 @@
+-- Fragments included by the other query modules
+
+--: fragment ordered_by_name
+ORDER BY name;
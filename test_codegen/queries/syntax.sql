@@ -50,6 +50,33 @@ INSERT INTO syntax ("trick:y", async, enum) VALUES ('this is just a cast'::text,
 --! typeof
 SELECT * FROM syntax;
 
+--! backslashes
+SELECT '\d+' AS pattern, -- sent as written
+  E'\x5C' AS backslash;
+
+--! legacy_pattern : LegacyPattern(pattern)
+--# deprecated = "use `backslashes`"
+SELECT '\d+' AS pattern;
+
+--! dumped : (label?)
+--# derive(Default, serde::Deserialize)
+SELECT * FROM dumped ORDER BY id;
+
+--! within_budget
+--# budget = 5s
+SELECT 42 AS answer;
+
+--! over_budget
+--# budget = 1ms
+SELECT pg_sleep(0.05);
+
+--! sleep
+SELECT pg_sleep(:seconds);
+
+--! validated (score?)
+--# validate = "len(email) <= 16, len(avatar) < 4, age >= 18, score <= 10"
+SELECT :email::TEXT AS email, :avatar::BYTEA AS avatar, :age::INT AS age, :score::FLOAT8 AS score;
+
 -- Multi
 
 -- Comment
\ No newline at end of file
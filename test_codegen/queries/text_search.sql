@@ -0,0 +1,14 @@
+--! insert_article
+INSERT INTO article (id, title, body) VALUES (:id, :title, to_tsvector('english', :title));
+
+--! insert_article_lexemes
+INSERT INTO article (id, title, body) VALUES (:id, :title, :body);
+
+--! articles
+SELECT * FROM article ORDER BY id;
+
+--! search_articles
+SELECT id FROM article WHERE body @@ :query ORDER BY id;
+
+--! normalize_query
+SELECT :words::tsquery AS query;
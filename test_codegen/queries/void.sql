@@ -0,0 +1,5 @@
+--! void_fn
+SELECT pg_sleep(0);
+
+--! void_with_value
+SELECT pg_sleep(0) AS sleep, 42 AS answer;
@@ -189,6 +189,103 @@ CREATE TABLE nightmare (
     composite nightmare_composite NOT NULL
 );
 
+CREATE DOMAIN checked_composite AS custom_composite CHECK ((VALUE).such_cool > 0);
+CREATE DOMAIN int_list AS INT[] CHECK (cardinality(VALUE) > 0);
+CREATE TYPE checked_holder AS (
+    item checked_composite,
+    ints int_list
+);
+
+CREATE TABLE checked (
+    item checked_composite NOT NULL,
+    ints int_list NOT NULL,
+    items checked_composite[] NOT NULL,
+    holder checked_holder NOT NULL
+);
+
+CREATE TABLE shipment (
+    id INT NOT NULL,
+    items custom_composite[] NOT NULL
+);
+
+-- Ranges
+
+CREATE TYPE text_range AS RANGE (subtype = text);
+
+CREATE TABLE reservation (
+    id INT NOT NULL,
+    seats int4range NOT NULL,
+    during tstzrange NOT NULL,
+    days daterange,
+    amounts numrange,
+    names text_range NOT NULL,
+    slots int8range[] NOT NULL
+);
+
+CREATE TABLE availability (
+    id INT NOT NULL,
+    seats int4multirange NOT NULL,
+    days datemultirange
+);
+
+-- Extension types
+
+CREATE EXTENSION hstore;
+CREATE EXTENSION citext;
+CREATE EXTENSION ltree;
+CREATE EXTENSION cube;
+
+CREATE TABLE preference (
+    id INT NOT NULL,
+    attrs hstore NOT NULL,
+    extra hstore
+);
+
+CREATE TABLE category (
+    id INT NOT NULL,
+    name citext NOT NULL,
+    path ltree NOT NULL,
+    aliases citext[]
+);
+
+CREATE TABLE region (
+    id INT NOT NULL,
+    bounds cube NOT NULL,
+    samples cube[]
+);
+
+CREATE TABLE labelled (
+    id INT NOT NULL,
+    label TEXT NOT NULL,
+    note TEXT NOT NULL
+);
+
+-- Outer joins
+
+CREATE TABLE team (id INT PRIMARY KEY, name TEXT NOT NULL);
+CREATE TABLE member (
+    id INT PRIMARY KEY,
+    team_id INT REFERENCES team,
+    name TEXT NOT NULL
+);
+
+-- Multidimensional arrays
+
+CREATE TABLE grid (
+    id INT NOT NULL,
+    cells INT[][] NOT NULL,
+    labels TEXT[][]
+);
+
+-- Inline columns
+
+CREATE TABLE listing (
+    id INT NOT NULL,
+    symbol VARCHAR(8) NOT NULL,
+    exchange VARCHAR(4),
+    digest BYTEA NOT NULL
+);
+
 -- Syntax
 
 CREATE TYPE syntax_composite AS (
@@ -200,3 +297,111 @@ CREATE TABLE Syntax (
     async syntax_composite,
     enum syntax_enum
 );
+
+-- Newtype
+
+CREATE TABLE newtype_author (
+    id SERIAL PRIMARY KEY,
+    name TEXT NOT NULL
+);
+CREATE TABLE newtype_book (
+    id SERIAL PRIMARY KEY,
+    author_id INT NOT NULL REFERENCES newtype_author(id),
+    slug TEXT NOT NULL
+);
+
+-- Materialized view
+
+CREATE MATERIALIZED VIEW author_book_count AS
+    SELECT author_id, count(*) AS books FROM newtype_book GROUP BY author_id;
+CREATE UNIQUE INDEX ON author_book_count(author_id);
+
+-- Script
+
+CREATE TABLE scripted (id INT NOT NULL);
+
+-- Schema dump, with psql meta-commands
+
+\set ON_ERROR_STOP on
+\connect postgres
+
+CREATE TABLE dumped (id INT NOT NULL, label TEXT);
+
+COPY public.dumped (id, label) FROM stdin;
+1	it's; quoted
+2	\N
+\.
+
+-- Large objects
+
+CREATE TABLE media (id SERIAL PRIMARY KEY, content OID NOT NULL);
+
+-- Procedures
+
+CREATE TABLE counter (id INT PRIMARY KEY, value INT NOT NULL);
+INSERT INTO counter VALUES (1, 10);
+
+CREATE PROCEDURE bump_counter(id INT, INOUT value INT, OUT previous INT) LANGUAGE plpgsql AS $$
+BEGIN
+  SELECT c.value INTO previous FROM counter c WHERE c.id = bump_counter.id;
+  UPDATE counter c SET value = c.value + bump_counter.value WHERE c.id = bump_counter.id
+  RETURNING c.value INTO value;
+END $$;
+
+CREATE PROCEDURE reset_counter(id INT) LANGUAGE sql AS $$
+  UPDATE counter SET value = 0 WHERE counter.id = reset_counter.id
+$$;
+
+-- Functions
+
+CREATE TABLE shelf (id SERIAL PRIMARY KEY, label TEXT NOT NULL, note TEXT);
+INSERT INTO shelf (label, note) VALUES ('fiction', NULL), ('poetry', 'top shelf');
+
+CREATE FUNCTION shelves_from(first INT) RETURNS SETOF shelf LANGUAGE sql STABLE AS $$
+  SELECT * FROM shelf WHERE id >= first ORDER BY id
+$$;
+
+CREATE FUNCTION shelf_labels(first INT) RETURNS TABLE (id INT, label TEXT) LANGUAGE sql STABLE AS $$
+  SELECT s.id, s.label FROM shelf s WHERE s.id >= first ORDER BY s.id
+$$;
+
+CREATE FUNCTION repeated(value anyelement, times INT) RETURNS SETOF anyelement LANGUAGE sql STABLE AS $$
+  SELECT value FROM generate_series(1, times)
+$$;
+
+CREATE TABLE article (
+    id INT NOT NULL,
+    title TEXT NOT NULL,
+    body tsvector NOT NULL
+);
+
+CREATE TABLE invoice (
+    id INT NOT NULL,
+    total money NOT NULL,
+    discount money,
+    installments money[] NOT NULL
+);
+
+CREATE TABLE subscription (
+    id INT NOT NULL,
+    period interval NOT NULL,
+    grace interval
+);
+
+CREATE TABLE permission (
+    id INT NOT NULL,
+    flags bit(4) NOT NULL,
+    mask varbit,
+    history varbit[] NOT NULL
+);
+
+CREATE TABLE shape (
+    id INT NOT NULL,
+    at point NOT NULL,
+    edge line,
+    segment lseg NOT NULL,
+    bounds box NOT NULL,
+    route path NOT NULL,
+    area polygon NOT NULL,
+    zone circle NOT NULL
+);
@@ -8,33 +8,58 @@ use rust_decimal::Decimal;
 use serde_json::Value;
 use std::{
     borrow::Cow,
-    collections::HashMap,
+    collections::{BTreeSet, HashMap, HashSet},
+    io::Read,
     net::{IpAddr, Ipv4Addr},
+    sync::Mutex,
+    time::Duration,
 };
 use time::{OffsetDateTime, PrimitiveDateTime};
 use uuid::Uuid;
 
 use crate::cornucopia::{
+    errors::QueryError,
     queries::{
         copy::sync::{insert_clone, insert_copy, select_copy},
         domain::{
             sync::{
-                insert_nightmare_domain, select_nightmare_domain, select_nightmare_domain_null,
+                insert_checked, insert_nightmare_domain, select_checked, select_nightmare_domain,
+                select_nightmare_domain_null,
             },
-            InsertNightmareDomainParams, SelectNightmareDomain, SelectNightmareDomainNull,
+            InsertCheckedParams, InsertNightmareDomainParams, SelectChecked, SelectNightmareDomain,
+            SelectNightmareDomainNull,
+        },
+        materialized_views::{
+            sync::{author_book_count, refresh_author_book_count},
+            AuthorBookCount,
         },
         named::sync::{
-            named, named_by_id, named_complex, new_named_complex, new_named_hidden,
-            new_named_visible,
+            named, named_by_id, named_complex, named_complex_with_dot_among, new_named_complex,
+            new_named_hidden, new_named_visible,
         },
         named::{Named, NamedComplex, NamedComplexParams, NamedParams},
+        newtype::{
+            sync::{
+                book_by_slug, books_by_author, books_by_authors, books_per_author,
+                books_per_known_author, copy_books, export_books, export_books_csv,
+                export_hinted_books, list_books_page, list_hinted_books_page, new_author, new_book,
+                rename_book, toggle_book, touch_books, BooksPerAuthorStmt, Statements,
+            },
+            BooksByAuthor, BooksByAuthors, CopyBooksParams, ExportedBook, ListBooks, NewBookParams,
+        },
         nullity::sync::{new_nullity, nullity},
         nullity::{Nullity, NullityParams},
         params::sync::insert_book,
         params::{
-            sync::{find_books, params_use_twice, select_book},
-            SelectBook,
+            sync::{
+                books_ordered, books_ordered_with, books_starting_with, characters_among,
+                count_books_by_author, find_book_by_name, find_books, params_use_twice,
+                select_book, titles_by_author,
+            },
+            BooksOrdered, BooksOrderedFilter, BooksOrderedOrder, BooksStartingWith, FindBookByName,
+            FindBookByNameParams, SelectBook, TitlesByAuthorParams,
         },
+        script::sync::{reset_scripted, scripted_ids},
         stress::{
             sync::{
                 insert_everything, insert_everything_array, insert_nightmare, select_everything,
@@ -43,11 +68,13 @@ use crate::cornucopia::{
             Everything, EverythingArray, EverythingArrayParams, EverythingParams,
         },
         syntax::{
-            sync::{r#typeof, tricky_sql10},
+            sync::{backslashes, r#typeof, tricky_sql10},
             TrickySql10Params,
         },
+        void::{void_fn, void_with_value},
     },
     types::public::{
+        AuthorId, BookSlug, CheckedHolder, CheckedHolderParams, CloneComposite,
         CloneCompositeBorrowed, CopyComposite, CustomComposite, CustomCompositeBorrowed,
         DomainComposite, DomainCompositeParams, EnumWithDot, NamedComposite,
         NamedCompositeBorrowed, NamedCompositeWithDot, NightmareComposite,
@@ -55,25 +82,194 @@ use crate::cornucopia::{
         SyntaxComposite, SyntaxEnum,
     },
 };
-use cornucopia_sync::Params;
+use cornucopia_sync::{Params, QueryInfo, QueryObserver, QueryOutcome};
+
+/// Price of the `named` table, decoded by its own `FromSql` implementation
+#[derive(
+    Debug, Clone, PartialEq, serde::Serialize, postgres_types::FromSql, postgres_types::ToSql,
+)]
+#[postgres(transparent)]
+pub struct Price(pub f64);
+
+/// Path of the `ltree` extension, decoded by its own `FromSql` implementation
+#[derive(
+    Debug, Clone, PartialEq, serde::Serialize, postgres_types::FromSql, postgres_types::ToSql,
+)]
+#[postgres(transparent)]
+pub struct Ltree(pub String);
+
+/// Label of the `labelled` table, decoded by the codec configured for this column only
+#[derive(
+    Debug, Clone, PartialEq, serde::Serialize, postgres_types::FromSql, postgres_types::ToSql,
+)]
+#[postgres(transparent)]
+pub struct Label(pub String);
+
+/// Value of the `cube` extension, decoded by the codec configured for it
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct Cube {
+    pub lower: Vec<f64>,
+    pub upper: Vec<f64>,
+}
+
+/// Value of the `cube` extension borrowed from a row, converted into a `Cube` when owned
+pub struct CubeRef<'a>(&'a [u8]);
+
+impl CubeRef<'_> {
+    /// Its header, with the bit of points cleared
+    pub fn dim(&self) -> usize {
+        (u32::from_be_bytes(self.0[..4].try_into().unwrap()) & 0x7fff_ffff) as usize
+    }
+
+    fn coords(&self) -> impl Iterator<Item = f64> + '_ {
+        self.0[4..]
+            .chunks_exact(8)
+            .map(|it| f64::from_be_bytes(it.try_into().unwrap()))
+    }
+}
+
+impl<'a> postgres_types::FromSql<'a> for CubeRef<'a> {
+    fn from_sql(
+        _: &postgres_types::Type,
+        raw: &'a [u8],
+    ) -> Result<Self, Box<dyn std::error::Error + Sync + Send>> {
+        if raw.len() < 4 {
+            return Err("invalid cube message size".into());
+        }
+        Ok(Self(raw))
+    }
+
+    fn accepts(ty: &postgres_types::Type) -> bool {
+        ty.name() == "cube"
+    }
+}
+
+impl From<CubeRef<'_>> for Cube {
+    fn from(cube: CubeRef<'_>) -> Self {
+        let coords: Vec<_> = cube.coords().collect();
+        // Points only store their coordinates once
+        let (lower, upper) = coords.split_at(cube.dim());
+        let upper = if upper.is_empty() { lower } else { upper };
+        Self {
+            lower: lower.to_vec(),
+            upper: upper.to_vec(),
+        }
+    }
+}
+
+impl<'a> postgres_types::FromSql<'a> for Cube {
+    fn from_sql(
+        ty: &postgres_types::Type,
+        raw: &'a [u8],
+    ) -> Result<Self, Box<dyn std::error::Error + Sync + Send>> {
+        CubeRef::from_sql(ty, raw).map(Self::from)
+    }
+
+    fn accepts(ty: &postgres_types::Type) -> bool {
+        ty.name() == "cube"
+    }
+}
+
+impl postgres_types::ToSql for Cube {
+    fn to_sql(
+        &self,
+        _: &postgres_types::Type,
+        out: &mut postgres_types::private::BytesMut,
+    ) -> Result<postgres_types::IsNull, Box<dyn std::error::Error + Sync + Send>> {
+        out.extend_from_slice(&u32::try_from(self.lower.len())?.to_be_bytes());
+        for coord in self.lower.iter().chain(&self.upper) {
+            out.extend_from_slice(&coord.to_be_bytes());
+        }
+        Ok(postgres_types::IsNull::No)
+    }
+
+    fn accepts(ty: &postgres_types::Type) -> bool {
+        ty.name() == "cube"
+    }
+
+    postgres_types::to_sql_checked!();
+}
 
 pub fn main() {
-    let client = &mut Config::new()
+    let mut config = Config::new();
+    config
         .user("postgres")
         .password("postgres")
         .host("127.0.0.1")
         .port(5435)
-        .dbname("postgres")
-        .connect(NoTls)
-        .unwrap();
+        .dbname("postgres");
+    let client = &mut config.connect(NoTls).unwrap();
     test_copy(client);
     test_params(client);
+    test_prelude(client);
+    test_imports(client);
     test_named(client);
     test_nullity(client);
     test_stress(client);
     test_domain(client);
     test_trait_sql(client);
     test_keyword_escaping(client);
+    test_deprecated(client);
+    test_budget(client);
+    test_validate(client);
+    test_timeout();
+    test_void(client);
+    test_script(client);
+    test_schema_dump(client);
+    test_large_object(client);
+    test_call(client);
+    test_functions(client);
+    test_composite_arrays(client);
+    test_newtype(client);
+    test_materialized_view(client);
+    test_copy_in(client);
+    test_copy_out(client);
+    test_paginate(client);
+    test_plugin();
+    test_rollup(client);
+    test_cursor(client);
+    test_stmt_cache(client, &mut config.connect(NoTls).unwrap());
+    test_stmt_cache_reconnect(&config);
+    test_stmt_cache_async();
+    test_facade();
+    test_db_errors(client);
+    test_constraint_errors(client);
+    test_sidecar(client);
+    test_observer(client);
+    test_session(client);
+    test_ranges(client);
+    test_multiranges(client);
+    test_inline_columns(client);
+    test_fuzz(client);
+    test_hstore(client);
+    test_for_each(client);
+    test_extension_types(client);
+    test_codecs(client);
+    test_text_search(client);
+    test_money(client);
+    test_interval(client);
+    test_bit(client);
+    test_geometric(client);
+    test_record(client);
+    test_matrix(client);
+    test_outer_joins(client);
+    test_tables(client);
+    test_extra_queries(client);
+}
+
+pub fn test_prelude(client: &mut Client) {
+    use crate::cornucopia::prelude::*;
+    let books: Vec<FindBooks> = find_books()
+        .bind(client, &["Necronomicon"].as_slice())
+        .all()
+        .unwrap();
+    assert_eq!(
+        books,
+        [FindBooks {
+            author: None,
+            name: "Necronomicon".to_string()
+        }]
+    );
 }
 
 pub fn test_params(client: &mut Client) {
@@ -102,7 +298,88 @@ pub fn test_params(client: &mut Client) {
             }
         ]
     );
+    // `:author?` accepts NULL
+    assert_eq!(
+        count_books_by_author().bind(client, &None::<&str>).unwrap(),
+        1
+    );
+    assert_eq!(
+        count_books_by_author()
+            .bind(client, &Some("Marcel Proust"))
+            .unwrap(),
+        1
+    );
+    // Positional parameters are named after their column, or by position
+    assert_eq!(
+        titles_by_author()
+            .params(
+                client,
+                &TitlesByAuthorParams {
+                    author: "Marcel Proust",
+                    param_2: 3,
+                }
+            )
+            .all()
+            .unwrap(),
+        ["In Search of Lost Time"]
+    );
+    // Bound queries with a cardinality directly fetch their rows
+    assert_eq!(
+        find_book_by_name()
+            .params(
+                client,
+                &FindBookByNameParams {
+                    name: "Necronomicon"
+                }
+            )
+            .unwrap(),
+        Some(FindBookByName {
+            author: None,
+            name: "Necronomicon".into()
+        })
+    );
+    assert_eq!(find_book_by_name().bind(client, &"Dune").unwrap(), None);
+    // Variants are chosen when creating the statement, the first ones by default
+    assert_eq!(
+        books_ordered().bind(client).all().unwrap(),
+        books_ordered_with(BooksOrderedOrder::Name, BooksOrderedFilter::AllBooks)
+            .bind(client)
+            .all()
+            .unwrap()
+    );
+    assert_eq!(
+        books_ordered_with(BooksOrderedOrder::Name, BooksOrderedFilter::WithAuthor)
+            .bind(client)
+            .all()
+            .unwrap(),
+        &[BooksOrdered {
+            author: Some("Marcel Proust".into()),
+            name: "In Search of Lost Time".into()
+        }]
+    );
+    // Fragments are spliced into the queries including them
+    assert_eq!(
+        books_starting_with().bind(client, &"").all().unwrap(),
+        &[
+            BooksStartingWith {
+                author: Some("Marcel Proust".into()),
+                name: "In Search of Lost Time".into()
+            },
+            BooksStartingWith {
+                author: None,
+                name: "Necronomicon".into()
+            }
+        ]
+    );
     params_use_twice().bind(client, &"name").unwrap();
+    // Derived through the module's settings
+    assert_eq!(
+        SelectBook::default(),
+        SelectBook {
+            author: None,
+            name: String::new()
+        }
+    );
 }
 
 pub fn test_trait_sql(client: &mut Client) {
@@ -143,6 +420,23 @@ pub fn test_trait_sql(client: &mut Client) {
         .bind(client, &IterSql(|| map.values()))
         .all()
         .unwrap();
+
+    // Enum lists, from a slice or a boxed slice
+    assert_eq!(
+        characters_among()
+            .bind(
+                client,
+                &[SpongebobCharacter::Squidward, SpongebobCharacter::Bob].as_slice()
+            )
+            .all()
+            .unwrap(),
+        [SpongebobCharacter::Bob, SpongebobCharacter::Squidward]
+    );
+    let boxed: Box<[_]> = vec![SpongebobCharacter::Patrick].into_boxed_slice();
+    assert_eq!(
+        characters_among().bind(client, &boxed).all().unwrap(),
+        [SpongebobCharacter::Patrick]
+    );
 }
 
 pub fn test_nullity(client: &mut Client) {
@@ -172,6 +466,27 @@ pub fn test_nullity(client: &mut Client) {
     );
 }
 
+pub fn test_imports(client: &mut Client) {
+    use crate::cornucopia::queries::imports::sync::books_named;
+    // The structs imported from `params` are the same types as there
+    let books: Vec<SelectBook> = books_named()
+        .params(
+            client,
+            &FindBookByNameParams {
+                name: "Necronomicon",
+            },
+        )
+        .all()
+        .unwrap();
+    assert_eq!(
+        books,
+        [SelectBook {
+            author: None,
+            name: "Necronomicon".to_string()
+        }]
+    );
+}
+
 pub fn test_named(client: &mut Client) {
     let hidden_id = new_named_hidden()
         .params(
@@ -245,6 +560,27 @@ pub fn test_named(client: &mut Client) {
         named().bind(client).map(|it| it.id).all().unwrap(),
         &[hidden_id, visible_id, last_id]
     );
+    // Annotated fields use the user type
+    {
+        use crate::cornucopia::queries::named::{sync::named_priced_above, NamedPriced};
+        assert_eq!(
+            named_priced_above()
+                .bind(client, &Price(50.0))
+                .all()
+                .unwrap(),
+            [NamedPriced {
+                name: "stuff".into(),
+                price: Some(Price(84.0))
+            }]
+        );
+    }
+    // Deprecated aliases still work after a rename
+    #[allow(deprecated)]
+    {
+        use crate::cornucopia::queries::named::{sync::find_named, NamedRow};
+        let row: NamedRow = find_named().bind(client, &hidden_id).one().unwrap();
+        assert_eq!(row.name, "secret");
+    }
 
     new_named_complex()
         .params(
@@ -295,6 +631,18 @@ pub fn test_named(client: &mut Client) {
             }
         ],
     );
+    // Composite lists
+    let with_dot = NamedCompositeWithDot {
+        this_is_inconceivable: Some(EnumWithDot::variant_with_dot),
+    };
+    assert_eq!(
+        named_complex_with_dot_among()
+            .bind(client, &vec![with_dot])
+            .map(|it| it.named.wow.map(str::to_string))
+            .all()
+            .unwrap(),
+        [Some("Hello world".to_string())]
+    );
 }
 
 // Test we correctly implement borrowed version and copy derive
@@ -364,6 +712,35 @@ pub fn test_domain(client: &mut Client) {
     };
     let actual = select_nightmare_domain_null().bind(client).one().unwrap();
     assert_eq!(expected, actual);
+
+    // Domains over composites and arrays
+    let item = || CustomCompositeBorrowed {
+        wow: "wow",
+        such_cool: 3,
+        nice: SpongebobCharacter::Squidward,
+    };
+    let params = InsertCheckedParams {
+        item: item(),
+        ints: vec![1, 2],
+        items: vec![item()],
+        holder: CheckedHolderParams {
+            item: item(),
+            ints: &[3],
+        },
+    };
+    insert_checked().params(client, &params).unwrap();
+    assert_eq!(
+        select_checked().bind(client).one().unwrap(),
+        SelectChecked {
+            item: item().into(),
+            ints: vec![1, 2],
+            items: vec![item().into()],
+            holder: CheckedHolder {
+                item: item().into(),
+                ints: vec![3],
+            },
+        }
+    );
 }
 
 // Test hard cases
@@ -569,4 +946,1701 @@ pub fn test_keyword_escaping(client: &mut Client) {
     };
     tricky_sql10().params(client, &params).unwrap();
     r#typeof().bind(client).all().unwrap();
+    // The SQL is sent as written, backslashes included
+    let row = backslashes().bind(client).one().unwrap();
+    assert_eq!(
+        (row.pattern.as_str(), row.backslash.as_str()),
+        ("\\d+", "\\")
+    );
+}
+
+// Deprecated queries still work, but warn their users
+#[allow(deprecated)]
+pub fn test_deprecated(client: &mut Client) {
+    use crate::cornucopia::queries::syntax::{sync::legacy_pattern, LegacyPattern};
+    assert_eq!(
+        legacy_pattern().bind(client).one().unwrap(),
+        LegacyPattern {
+            pattern: "\\d+".to_string()
+        }
+    );
+}
+
+// Test the latency budget checks of the queries with a `budget` attribute
+pub fn test_budget(client: &mut Client) {
+    use crate::cornucopia::queries::syntax::sync::{
+        over_budget, within_budget, OverBudgetStmt, WithinBudgetStmt,
+    };
+    assert_eq!(WithinBudgetStmt::BUDGET, std::time::Duration::from_secs(5));
+    let answer = WithinBudgetStmt::check_budget(|| within_budget().bind(client).one().unwrap());
+    assert_eq!(answer, Ok(42));
+    let err =
+        OverBudgetStmt::check_budget(|| over_budget().bind(client).one().unwrap()).unwrap_err();
+    assert_eq!((err.module, err.query), ("syntax", "over_budget"));
+    assert!(err.elapsed > err.budget);
+}
+
+// Test the parameter checks of the queries with a `validate` attribute
+// Test queries running longer than their timeout
+pub fn test_timeout() {
+    use crate::cornucopia::queries::syntax::async_::sleep;
+    use std::time::{Duration, Instant};
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap();
+    runtime.block_on(async {
+        let (client, connection) = tokio_postgres::connect(
+            "host=127.0.0.1 port=5435 user=postgres password=postgres dbname=postgres",
+            tokio_postgres::NoTls,
+        )
+        .await
+        .unwrap();
+        tokio::spawn(connection);
+        let start = Instant::now();
+        let err = sleep()
+            .bind(&client, &10.0)
+            .timeout(Duration::from_millis(100))
+            .one()
+            .await
+            .unwrap_err();
+        // Cancelled on the server, rather than left running
+        assert_eq!(
+            err.code(),
+            Some(&tokio_postgres::error::SqlState::QUERY_CANCELED)
+        );
+        assert!(start.elapsed() < Duration::from_secs(5));
+        // The connection is free for the next query
+        sleep()
+            .bind(&client, &0.0)
+            .timeout(Duration::from_secs(5))
+            .one()
+            .await
+            .unwrap();
+    });
+}
+
+pub fn test_validate(client: &mut Client) {
+    use crate::cornucopia::queries::syntax::sync::validated;
+    let mut stmt = validated();
+    let avatar: &[u8] = &[1, 2];
+    assert_eq!(stmt.validate(&"ana", &avatar, &18, &None), Ok(()));
+    let row = stmt
+        .bind(client, &"ana", &avatar, &18, &Some(9.5))
+        .one()
+        .unwrap();
+    assert_eq!((row.email.as_str(), row.age), ("ana", 18));
+    let err = stmt.validate(&"ana", &avatar, &17, &Some(9.5)).unwrap_err();
+    assert_eq!(err.param, "age");
+    assert_eq!(
+        err.to_string(),
+        "parameter `age` of query `syntax::validated` fails `age >= 18`"
+    );
+    let err = stmt
+        .validate(&"ana", &[0u8; 4].as_slice(), &18, &None)
+        .unwrap_err();
+    assert_eq!(err.check, "len(avatar) < 4");
+    let err = stmt
+        .validate(&"é".repeat(17), &avatar, &18, &Some(10.5))
+        .unwrap_err();
+    assert_eq!(err.param, "email");
+}
+
+// Test void columns
+pub fn test_void(client: &mut Client) {
+    let () = void_fn().bind(client).one().unwrap();
+    assert_eq!(void_with_value().bind(client).one().unwrap().answer, 42);
+}
+
+pub fn test_script(client: &mut Client) {
+    reset_scripted().bind(client).unwrap();
+    assert_eq!(scripted_ids().bind(client).all().unwrap(), [10, 20]);
+    // Running it again starts over
+    reset_scripted().bind(client).unwrap();
+    assert_eq!(scripted_ids().bind(client).all().unwrap(), [10, 20]);
+}
+
+// Test the data copied by the schema dump
+pub fn test_schema_dump(client: &mut Client) {
+    use crate::cornucopia::queries::syntax::{sync::dumped, Dumped};
+    assert_eq!(
+        dumped().bind(client).all().unwrap(),
+        [
+            Dumped {
+                id: 1,
+                label: Some("it's; quoted".to_string())
+            },
+            Dumped { id: 2, label: None }
+        ]
+    );
+    // The row derives the traits of its query's `derive` attribute
+    let row: Dumped = serde_json::from_str(r#"{"id": 3, "label": null}"#).unwrap();
+    assert_eq!(
+        row,
+        Dumped {
+            id: 3,
+            ..Default::default()
+        }
+    );
+}
+
+// Test the `oid` columns of large objects, streamed with `LargeObject`
+pub fn test_large_object(client: &mut Client) {
+    use crate::cornucopia::queries::media::sync::{insert_media, media_content};
+    use cornucopia_sync::{LargeObject, LargeObjectMode};
+    use std::io::{Seek, SeekFrom, Write};
+
+    let mut transaction = client.transaction().unwrap();
+    let oid = LargeObject::create(&mut transaction).unwrap();
+    let mut object = LargeObject::open(&mut transaction, oid, LargeObjectMode::Write).unwrap();
+    object.write_all(b"Hello, large world").unwrap();
+    object.seek(SeekFrom::Start(7)).unwrap();
+    object.write_all(b"LARGE").unwrap();
+    object.close().unwrap();
+    let id = insert_media().bind(&mut transaction, &oid).one().unwrap();
+    transaction.commit().unwrap();
+
+    let mut transaction = client.transaction().unwrap();
+    let oid = media_content().bind(&mut transaction, &id).unwrap();
+    let mut content = String::new();
+    LargeObject::open(&mut transaction, oid, LargeObjectMode::Read)
+        .unwrap()
+        .read_to_string(&mut content)
+        .unwrap();
+    assert_eq!(content, "Hello, LARGE world");
+    LargeObject::unlink(&mut transaction, oid).unwrap();
+    transaction.commit().unwrap();
+}
+
+// Test `CALL` statements, returning the INOUT and OUT arguments of the procedure
+pub fn test_call(client: &mut Client) {
+    use crate::cornucopia::queries::procedures::{
+        sync::{bump_counter, reset_counter},
+        BumpCounter, BumpCounterParams,
+    };
+    // Positional parameters are named after the arguments of the procedure
+    let params = BumpCounterParams { id: 1, value: 5 };
+    assert_eq!(
+        bump_counter().params(client, &params).one().unwrap(),
+        BumpCounter {
+            value: Some(15),
+            previous: Some(10)
+        }
+    );
+    reset_counter().bind(client, &1).unwrap();
+    assert_eq!(
+        bump_counter().bind(client, &2, &1).one().unwrap(),
+        BumpCounter {
+            value: None,
+            previous: None
+        }
+    );
+}
+
+// Test the rows of functions
+pub fn test_functions(client: &mut Client) {
+    use crate::cornucopia::queries::functions::{
+        sync::{repeated, shelf_labels, shelves_from},
+        Shelf, ShelfLabels,
+    };
+    // Named after the table, with its nullability
+    assert_eq!(
+        shelves_from().bind(client, &1).all().unwrap(),
+        vec![
+            Shelf {
+                id: 1,
+                label: "fiction".to_string(),
+                note: None
+            },
+            Shelf {
+                id: 2,
+                label: "poetry".to_string(),
+                note: Some("top shelf".to_string())
+            }
+        ]
+    );
+    // Named after the function, nullable unless declared otherwise
+    assert_eq!(
+        shelf_labels().bind(client, &2).all().unwrap(),
+        vec![ShelfLabels {
+            id: 2,
+            label: Some("poetry".to_string())
+        }]
+    );
+    // Polymorphic arguments are cast
+    assert_eq!(
+        repeated().bind(client, &"echo", &2).all().unwrap(),
+        vec!["echo".to_string(), "echo".to_string()]
+    );
+}
+
+// Test arrays of composites, as params and columns
+pub fn test_composite_arrays(client: &mut Client) {
+    use crate::cornucopia::queries::composite_arrays::{
+        sync::{
+            insert_shipment, shipment_items, shipments, shipments_containing, shipments_with_item,
+        },
+        Shipments,
+    };
+    let bob = || CustomCompositeBorrowed {
+        wow: "wow",
+        such_cool: 1,
+        nice: SpongebobCharacter::Bob,
+    };
+    let patrick = || CustomCompositeBorrowed {
+        wow: "such",
+        such_cool: 2,
+        nice: SpongebobCharacter::Patrick,
+    };
+    insert_shipment().bind(client, &1, &vec![bob()]).unwrap();
+    insert_shipment()
+        .bind(client, &2, &vec![bob(), patrick()])
+        .unwrap();
+    assert_eq!(
+        shipments().bind(client).all().unwrap(),
+        vec![
+            Shipments {
+                id: 1,
+                items: vec![bob().into()],
+            },
+            Shipments {
+                id: 2,
+                items: vec![bob().into(), patrick().into()],
+            }
+        ]
+    );
+    // Composites compared to the elements of an array column
+    assert_eq!(
+        shipments_with_item()
+            .bind(client, &patrick())
+            .all()
+            .unwrap(),
+        vec![2]
+    );
+    assert_eq!(
+        shipments_containing()
+            .bind(client, &vec![bob(), patrick()])
+            .all()
+            .unwrap(),
+        vec![2]
+    );
+    let items: Vec<CustomComposite> = shipment_items().bind(client, &2).one().unwrap().unwrap();
+    assert_eq!(items, vec![bob().into(), patrick().into()]);
+}
+
+// Test newtype columns
+pub fn test_newtype(client: &mut Client) {
+    let author: AuthorId = new_author().bind(client, &"Herman").one().unwrap();
+    let other: AuthorId = new_author().bind(client, &"Jorge").one().unwrap();
+    let book = new_book()
+        .params(
+            client,
+            &NewBookParams {
+                author_id: author,
+                slug: BookSlug("moby-dick".to_string()),
+            },
+        )
+        .one()
+        .unwrap();
+    let other_book = new_book()
+        .bind(client, &other, &BookSlug("ficciones".to_string()))
+        .one()
+        .unwrap();
+    assert_eq!(
+        books_by_author().bind(client, &author).all().unwrap(),
+        &[BooksByAuthor {
+            id: book,
+            slug: BookSlug("moby-dick".to_string()),
+            name: "Herman".to_string(),
+        }]
+    );
+    assert_eq!(
+        books_by_authors()
+            .bind(client, &[author, other].as_slice())
+            .all()
+            .unwrap(),
+        &[
+            BooksByAuthors {
+                id: book,
+                author_id: author,
+                slug: BookSlug("moby-dick".to_string()),
+            },
+            BooksByAuthors {
+                id: other_book,
+                author_id: other,
+                slug: BookSlug("ficciones".to_string()),
+            }
+        ]
+    );
+    // Rows without floats or json can be used as keys
+    let books: HashSet<BooksByAuthors> = books_by_authors()
+        .bind(client, &[author, other, author].as_slice())
+        .iter()
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    let sorted: BTreeSet<_> = books.into_iter().map(|it| it.id).collect();
+    assert_eq!(sorted.into_iter().collect::<Vec<_>>(), [book, other_book]);
+    // Huge `= ANY(...)` arrays can be split into chunks
+    let ids = [author, other, author];
+    assert_eq!(
+        books_by_authors().bind_chunked(client, &ids).unwrap().len(),
+        3
+    );
+    assert_eq!(
+        touch_books()
+            .bind_chunked(client, &[book, other_book, book])
+            .unwrap(),
+        3
+    );
+    assert_eq!(
+        book_by_slug()
+            .bind(client, &BookSlug("ficciones".to_string()))
+            .one()
+            .unwrap(),
+        other_book
+    );
+    // Statements can all be prepared up front
+    let mut statements = Statements::new();
+    statements.prepare_all(client).unwrap();
+    assert_eq!(
+        statements
+            .book_by_slug
+            .bind(client, &BookSlug("moby-dick".to_string()))
+            .one()
+            .unwrap(),
+        book
+    );
+    // Audited queries report who changed how many rows
+    static RECORDS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+    let auditor = |record: &cornucopia_sync::AuditRecord| {
+        RECORDS.lock().unwrap().push(format!(
+            "{}::{} by {:?}: {}",
+            record.module, record.query, record.actor, record.rows
+        ))
+    };
+    assert!(cornucopia_sync::set_auditor(auditor).is_ok());
+    let slug = BookSlug("the-whale".to_string());
+    assert_eq!(rename_book().bind(client, &slug, &book, &other).unwrap(), 0);
+    assert_eq!(
+        rename_book().bind(client, &slug, &book, &author).unwrap(),
+        1
+    );
+    assert_eq!(
+        *RECORDS.lock().unwrap(),
+        [
+            format!("newtype::rename_book by Some({other:?}): 0"),
+            format!("newtype::rename_book by Some({author:?}): 1"),
+        ]
+    );
+}
+
+// Test materialized view helpers
+pub fn test_materialized_view(client: &mut Client) {
+    refresh_author_book_count(client, false).unwrap();
+    let counts = author_book_count().bind(client).all().unwrap();
+    assert_eq!(counts.iter().map(|it| it.books).sum::<i64>(), 2);
+    refresh_author_book_count(client, true).unwrap();
+    assert_eq!(author_book_count().bind(client).all().unwrap(), counts);
+    assert!(counts.contains(&AuthorBookCount {
+        author_id: counts[0].author_id,
+        books: 1,
+    }));
+}
+
+// Test binary copy
+pub fn test_copy_in(client: &mut Client) {
+    let author: AuthorId = new_author().bind(client, &"Italo").one().unwrap();
+    let mut stmt = copy_books();
+    let mut writer = stmt.writer(client).unwrap();
+    writer
+        .write(&author, &BookSlug("if-on-a-winters-night".to_string()))
+        .unwrap();
+    writer
+        .write_params(&CopyBooksParams {
+            author_id: author,
+            slug: BookSlug("invisible-cities".to_string()),
+        })
+        .unwrap();
+    assert_eq!(writer.finish().unwrap(), 2);
+    let books = books_by_author().bind(client, &author).all().unwrap();
+    assert_eq!(
+        books
+            .iter()
+            .map(|it| it.slug.0.as_str())
+            .collect::<Vec<_>>(),
+        ["if-on-a-winters-night", "invisible-cities"]
+    );
+    // `MERGE` removes the book, or adds it back
+    let slug = BookSlug("if-on-a-winters-night".to_string());
+    let book = book_by_slug().bind(client, &slug).one().unwrap();
+    assert_eq!(
+        toggle_book().bind(client, &book, &author, &slug).unwrap(),
+        1
+    );
+    assert!(book_by_slug().bind(client, &slug).opt().unwrap().is_none());
+    assert_eq!(
+        toggle_book().bind(client, &book, &author, &slug).unwrap(),
+        1
+    );
+    assert!(book_by_slug().bind(client, &slug).opt().unwrap().is_some());
+}
+
+// Test keyset pagination
+pub fn test_paginate(client: &mut Client) {
+    let excluded = BookSlug("invisible-cities".to_string());
+    let mut stmt = list_books_page();
+    let all = stmt.page(client, &excluded, None, i64::MAX).unwrap();
+    assert!(all.next.is_none());
+    assert!(all.rows.iter().all(|it| it.slug != excluded));
+    let mut rows = Vec::new();
+    let mut after = None;
+    loop {
+        let page = stmt.page(client, &excluded, after.as_ref(), 2).unwrap();
+        assert!(page.rows.len() <= 2);
+        rows.extend(page.rows);
+        match page.next {
+            Some(next) => after = Some(next),
+            None => break,
+        }
+    }
+    assert_eq!(rows, all.rows);
+    // The plan hint is kept first when wrapping the query
+    let hinted = list_hinted_books_page()
+        .page(client, &excluded, None, i64::MAX)
+        .unwrap();
+    let mut ids: Vec<_> = all.rows.iter().map(|it| it.id).collect();
+    ids.sort_by_key(|it| it.0);
+    assert_eq!(hinted.rows.iter().map(|it| it.id).collect::<Vec<_>>(), ids);
+}
+
+// Test the code appended by the `columns` test plugin
+pub fn test_plugin() {
+    assert_eq!(CloneComposite::COLUMNS, ["first", "second"]);
+    assert_eq!(ListBooks::COLUMNS, ["id", "author_id", "slug"]);
+}
+
+// Test copy to stdout
+pub fn test_copy_out(client: &mut Client) {
+    let books: Vec<ExportedBook> = export_books()
+        .reader(client)
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    let slug = BookSlug("invisible-cities".to_string());
+    let book = book_by_slug().bind(client, &slug).one().unwrap();
+    assert!(books.iter().any(|it| it.id == book && it.slug == slug));
+    let hinted: Vec<ExportedBook> = export_hinted_books()
+        .reader(client)
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(hinted, books);
+    let mut csv = String::new();
+    export_books_csv()
+        .reader(client)
+        .unwrap()
+        .read_to_string(&mut csv)
+        .unwrap();
+    let mut lines = csv.lines();
+    assert_eq!(lines.next(), Some("id,slug"));
+    assert_eq!(lines.count(), books.len());
+}
+
+// Test columns nulled by grouping sets
+pub fn test_rollup(client: &mut Client) {
+    let rows = books_per_author().bind(client).all().unwrap();
+    let (total, per_author) = rows.split_last().unwrap();
+    assert_eq!(total.author_id, None);
+    assert!(per_author.iter().all(|it| it.author_id.is_some()));
+    assert_eq!(
+        total.books,
+        per_author.iter().map(|it| it.books).sum::<i64>()
+    );
+    // Declared non-null, as the total row is filtered out
+    let known = books_per_known_author().bind(client).all().unwrap();
+    assert_eq!(
+        known.iter().map(|it| it.author_id).collect::<Vec<_>>(),
+        per_author
+            .iter()
+            .map(|it| it.author_id.unwrap())
+            .collect::<Vec<_>>()
+    );
+    // Same columns, with a runtime composed order
+    let direction = "DESC NULLS FIRST";
+    let sql = format!("SELECT author_id, count(*) AS books FROM newtype_book GROUP BY ROLLUP (author_id) ORDER BY author_id {direction}");
+    let reversed = BooksPerAuthorStmt::with_sql(sql)
+        .bind(client)
+        .all()
+        .unwrap();
+    assert_eq!(reversed, rows.into_iter().rev().collect::<Vec<_>>());
+}
+
+pub fn test_cursor(client: &mut Client) {
+    let mut transaction = client.transaction().unwrap();
+    let all = books_per_author().bind(&mut transaction).all().unwrap();
+    // A batch size that doesn't divide the row count exercises the short last batch
+    let streamed = books_per_author()
+        .bind(&mut transaction)
+        .cursor(2)
+        .unwrap()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(streamed, all);
+    let exact = books_per_author()
+        .bind(&mut transaction)
+        .cursor(all.len() as i32)
+        .unwrap()
+        .count();
+    assert_eq!(exact, all.len());
+    transaction.rollback().unwrap();
+}
+
+// Test statements shared between connections
+pub fn test_stmt_cache(client: &mut Client, other: &mut Client) {
+    let mut stmt = select_book();
+    let books = stmt.bind(client).all().unwrap();
+    assert_eq!(stmt.bind(other).all().unwrap(), books);
+    assert_eq!(stmt.bind(client).all().unwrap(), books);
+}
+
+// Test statements cached for a connection whose address is reused by another one
+pub fn test_stmt_cache_reconnect(config: &Config) {
+    let mut select = select_book();
+    let mut insert = insert_book();
+    let mut conn = config.connect(NoTls).unwrap();
+    let books = select.bind(&mut conn).all().unwrap();
+    insert.bind(&mut conn, &None::<&str>, &"Kindred").unwrap();
+    let address = std::ptr::addr_of!(conn);
+    // The previous connection is closed, and the new one lives at its address
+    conn = config.connect(NoTls).unwrap();
+    assert_eq!(std::ptr::addr_of!(conn), address);
+    assert_eq!(
+        insert.bind(&mut conn, &None::<&str>, &"Kindred").unwrap(),
+        1
+    );
+    conn.execute("DELETE FROM book WHERE name = 'Kindred'", &[])
+        .unwrap();
+    assert_eq!(select.bind(&mut conn).all().unwrap(), books);
+    conn = config.connect(NoTls).unwrap();
+    let iterated = select
+        .bind(&mut conn)
+        .iter()
+        .unwrap()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(iterated, books);
+    // Transactions never reuse the statements of a previous one
+    let mut transaction = conn.transaction().unwrap();
+    assert_eq!(select.bind(&mut transaction).all().unwrap(), books);
+    transaction.rollback().unwrap();
+    conn = config.connect(NoTls).unwrap();
+    let mut transaction = conn.transaction().unwrap();
+    assert_eq!(select.bind(&mut transaction).all().unwrap(), books);
+    transaction.rollback().unwrap();
+}
+
+// Test statements cached for a connection are reused by its transactions, and on no other
+pub fn test_stmt_cache_async() {
+    use crate::cornucopia::queries::params::async_::select_book;
+    use cornucopia_async::GenericClient;
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap();
+    runtime.block_on(async {
+        let mut client = connect().await;
+        let other = connect().await;
+        let key = client.cache_key();
+        assert!(key.is_some());
+        assert_ne!(key, other.cache_key());
+        let mut stmt = select_book();
+        let books = stmt.bind(&client).all().await.unwrap();
+        let before = prepared(&client).await;
+        let transaction = client.transaction().await.unwrap();
+        assert_eq!(transaction.cache_key(), key);
+        for _ in 0..3 {
+            assert_eq!(stmt.bind(&transaction).all().await.unwrap(), books);
+        }
+        transaction.rollback().await.unwrap();
+        assert_eq!(stmt.bind(&other).all().await.unwrap(), books);
+        assert_eq!(prepared(&client).await, before);
+        assert_eq!(stmt.bind(&client).all().await.unwrap(), books);
+        assert_eq!(prepared(&client).await, before);
+    });
+}
+
+// Test the statements of the facade are prepared once per connection, and shared by its clones
+pub fn test_facade() {
+    use crate::cornucopia::queries::Queries;
+    fn shared<T: Clone + Send + Sync + 'static>(it: &T) -> T {
+        it.clone()
+    }
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap();
+    runtime.block_on(async {
+        let queries = Queries::default();
+        let client = connect().await;
+        let books = queries
+            .params()
+            .select_book()
+            .bind(&client)
+            .all()
+            .await
+            .unwrap();
+        let before = prepared(&client).await;
+        let queries = shared(&queries);
+        let handle = tokio::spawn(async move {
+            let books = queries
+                .params()
+                .select_book()
+                .bind(&client)
+                .all()
+                .await
+                .unwrap();
+            (books, prepared(&client).await)
+        });
+        assert_eq!(handle.await.unwrap(), (books, before));
+    });
+}
+
+async fn connect() -> tokio_postgres::Client {
+    let (client, connection) = tokio_postgres::connect(
+        "host=127.0.0.1 port=5435 user=postgres password=postgres dbname=postgres",
+        tokio_postgres::NoTls,
+    )
+    .await
+    .unwrap();
+    tokio::spawn(connection);
+    client
+}
+
+/// Number of statements prepared on the connection of `client`
+async fn prepared(client: &tokio_postgres::Client) -> i64 {
+    let row = client
+        .query_one("SELECT count(*) FROM pg_prepared_statements", &[])
+        .await
+        .unwrap();
+    row.get(0)
+}
+
+pub fn test_db_errors(client: &mut Client) {
+    let err = new_book()
+        .bind(client, &AuthorId(-1), &BookSlug("nowhere".to_string()))
+        .one()
+        .map_err(QueryError::from)
+        .unwrap_err();
+    assert!(matches!(err, QueryError::ForeignKeyViolation { .. }));
+    assert_eq!(err.constraint(), Some("newtype_book_author_id_fkey"));
+    assert!(!err.is_retryable());
+}
+
+// Test the violations decoded by the `constraints` attribute of a module
+pub fn test_constraint_errors(client: &mut Client) {
+    use crate::cornucopia::queries::newtype::ConstraintError;
+    let err = new_book()
+        .bind(client, &AuthorId(-1), &BookSlug("nowhere".to_string()))
+        .one()
+        .map_err(ConstraintError::from)
+        .unwrap_err();
+    assert!(matches!(err, ConstraintError::UnknownAuthor(_)));
+    let mut transaction = client.transaction().unwrap();
+    transaction
+        .execute(
+            "INSERT INTO newtype_author (id, name) VALUES (-2, 'Twin'), (-2, 'Twin')",
+            &[],
+        )
+        .map_err(ConstraintError::from)
+        .map(|_| panic!("the author is duplicated"))
+        .unwrap_or_else(|err| assert!(matches!(err, ConstraintError::DuplicateAuthor(_))));
+    transaction.rollback().unwrap();
+    let err = ConstraintError::from(client.execute("SELECT 1 / 0", &[]).unwrap_err());
+    assert!(matches!(err, ConstraintError::Other(_)));
+}
+
+// Test the query observer
+pub fn test_sidecar(client: &mut Client) {
+    use crate::cornucopia::queries::plain::{
+        sync::{count_plain_books, insert_plain_book, plain_books_starting_with},
+        PlainBook,
+    };
+    insert_plain_book()
+        .bind(client, &Some("Sidecar"), &"Plainly annotated")
+        .unwrap();
+    assert_eq!(
+        plain_books_starting_with()
+            .bind(client, &"Plainly")
+            .all()
+            .unwrap(),
+        [PlainBook {
+            author: Some("Sidecar".to_string()),
+            name: "Plainly annotated".to_string(),
+        }]
+    );
+    assert_eq!(
+        count_plain_books().bind(client, &Some("Sidecar")).unwrap(),
+        1
+    );
+}
+
+pub fn test_observer(client: &mut Client) {
+    static EVENTS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+    struct Recorder;
+    impl QueryObserver for Recorder {
+        fn on_start(&self, query: &QueryInfo) {
+            let event = format!("start {}::{}", query.module, query.query);
+            EVENTS.lock().unwrap().push(event);
+        }
+
+        fn on_end(&self, query: &QueryInfo, _elapsed: Duration, outcome: QueryOutcome<'_>) {
+            let outcome = match outcome {
+                QueryOutcome::Success { rows } => format!("{rows:?} rows"),
+                QueryOutcome::Failure(_) => "failed".to_string(),
+            };
+            let event = format!("end {}::{}: {outcome}", query.module, query.query);
+            EVENTS.lock().unwrap().push(event);
+        }
+    }
+    assert!(cornucopia_sync::set_observer(Recorder).is_ok());
+    let books = select_book().bind(client).all().unwrap();
+    new_book()
+        .bind(client, &AuthorId(-1), &BookSlug("nowhere".to_string()))
+        .one()
+        .unwrap_err();
+    assert_eq!(
+        *EVENTS.lock().unwrap(),
+        [
+            "start params::select_book".to_string(),
+            format!("end params::select_book: Some({}) rows", books.len()),
+            "start newtype::new_book".to_string(),
+            "end newtype::new_book: failed".to_string(),
+        ]
+    );
+}
+
+// Test the session settings of a module
+pub fn test_session(client: &mut Client) {
+    use crate::cornucopia::queries::session::{
+        sync::{configure, session_settings},
+        SessionSettings,
+    };
+    configure(client).unwrap();
+    assert_eq!(
+        session_settings().bind(client).one().unwrap(),
+        SessionSettings {
+            timezone: "UTC".to_string(),
+            day: "P1D".to_string(),
+        }
+    );
+}
+
+// Test range types
+pub fn test_ranges(client: &mut Client) {
+    use crate::cornucopia::queries::ranges::{
+        sync::{
+            insert_reservation, overlapping_reservations, reservation_bounds, reservations,
+            reservations_during,
+        },
+        ReservationBounds, Reservations,
+    };
+    use cornucopia_sync::Range;
+    use std::ops::Bound;
+
+    let start = OffsetDateTime::from_unix_timestamp(1_700_000_000).unwrap();
+    let end = start + time::Duration::hours(2);
+    let during = Range::new(Bound::Included(start), Bound::Excluded(end));
+    let days = Range::new(Bound::Included(start.date()), Bound::Unbounded);
+    let amounts = Range::new(
+        Bound::Excluded(Decimal::new(150, 2)),
+        Bound::Included(Decimal::new(300, 2)),
+    );
+    insert_reservation()
+        .bind(
+            client,
+            &1,
+            &Range::new(Bound::Included(1), Bound::Included(5)),
+            &during,
+            &Some(days),
+            &Some(amounts),
+            &Range::new(Bound::Included("a"), Bound::Excluded("m")),
+            &vec![
+                Range::new(Bound::Included(1), Bound::Excluded(3)),
+                Range::Empty,
+            ],
+        )
+        .unwrap();
+    insert_reservation()
+        .bind(
+            client,
+            &2,
+            &Range::Empty,
+            &Range::new(Bound::Unbounded, Bound::Excluded(start)),
+            &None,
+            &None,
+            &Range::new(Bound::Included("m"), Bound::Unbounded),
+            &vec![],
+        )
+        .unwrap();
+    assert_eq!(
+        reservations().bind(client).all().unwrap(),
+        [
+            Reservations {
+                id: 1,
+                // Discrete ranges are normalized with an exclusive upper bound
+                seats: Range::new(Bound::Included(1), Bound::Excluded(6)),
+                during,
+                days: Some(days),
+                amounts: Some(amounts),
+                names: Range::new(
+                    Bound::Included("a".to_string()),
+                    Bound::Excluded("m".to_string())
+                ),
+                slots: vec![
+                    Range::new(Bound::Included(1), Bound::Excluded(3)),
+                    Range::Empty
+                ],
+            },
+            Reservations {
+                id: 2,
+                seats: Range::Empty,
+                during: Range::new(Bound::Unbounded, Bound::Excluded(start)),
+                days: None,
+                amounts: None,
+                names: Range::new(Bound::Included("m".to_string()), Bound::Unbounded),
+                slots: vec![],
+            },
+        ]
+    );
+    assert_eq!(
+        reservations_during()
+            .bind(client, &(start + time::Duration::hours(1)))
+            .all()
+            .unwrap(),
+        [1]
+    );
+    assert_eq!(
+        overlapping_reservations()
+            .bind(client, &Range::new(Bound::Excluded(4), Bound::Unbounded))
+            .all()
+            .unwrap(),
+        [1]
+    );
+    assert_eq!(
+        reservation_bounds().bind(client, &1).one().unwrap(),
+        ReservationBounds {
+            first_seat: Some(1),
+            last_included: false,
+            empty: false,
+        }
+    );
+    assert_eq!(
+        reservation_bounds().bind(client, &2).one().unwrap(),
+        ReservationBounds {
+            first_seat: None,
+            last_included: false,
+            empty: true,
+        }
+    );
+    assert!(during.contains(&start) && !during.contains(&end));
+}
+
+// Test multirange types
+pub fn test_multiranges(client: &mut Client) {
+    use crate::cornucopia::queries::ranges::{
+        sync::{availabilities, available_for, insert_availability},
+        Availabilities,
+    };
+    use cornucopia_sync::Range;
+    use std::ops::Bound;
+
+    let day = time::Date::from_calendar_date(2024, time::Month::March, 1).unwrap();
+    let days = [Range::new(
+        Bound::Included(day),
+        Bound::Excluded(day.next_day().unwrap()),
+    )];
+    insert_availability()
+        .bind(
+            client,
+            &1,
+            &[
+                Range::new(Bound::Included(1), Bound::Excluded(4)),
+                Range::new(Bound::Included(10), Bound::Unbounded),
+            ]
+            .as_slice(),
+            &Some(days.as_slice()),
+        )
+        .unwrap();
+    insert_availability()
+        .bind(
+            client,
+            &2,
+            // Overlapping ranges are merged
+            &[
+                Range::new(Bound::Included(1), Bound::Excluded(3)),
+                Range::new(Bound::Included(2), Bound::Excluded(5)),
+            ]
+            .as_slice(),
+            &None,
+        )
+        .unwrap();
+    assert_eq!(
+        availabilities().bind(client).all().unwrap(),
+        [
+            Availabilities {
+                id: 1,
+                seats: vec![
+                    Range::new(Bound::Included(1), Bound::Excluded(4)),
+                    Range::new(Bound::Included(10), Bound::Unbounded),
+                ],
+                days: Some(days.to_vec()),
+            },
+            Availabilities {
+                id: 2,
+                seats: vec![Range::new(Bound::Included(1), Bound::Excluded(5))],
+                days: None,
+            },
+        ]
+    );
+    assert_eq!(
+        available_for()
+            .bind(
+                client,
+                &Range::new(Bound::Included(11), Bound::Excluded(20))
+            )
+            .all()
+            .unwrap(),
+        [1]
+    );
+}
+
+pub fn test_inline_columns(client: &mut Client) {
+    use crate::cornucopia::queries::inline::{
+        sync::{insert_listing, listing_symbols, listings},
+        ListingSymbols, Listings,
+    };
+    use arrayvec::ArrayString;
+
+    insert_listing()
+        .bind(client, &1, &"ACME", &Some("NYSE"), &[1, 2, 3, 4].as_slice())
+        .unwrap();
+    insert_listing()
+        .bind(
+            client,
+            &2,
+            &"INITECH",
+            &None::<&str>,
+            &[5, 6, 7, 8].as_slice(),
+        )
+        .unwrap();
+    assert_eq!(
+        listings().bind(client).all().unwrap(),
+        [
+            Listings {
+                id: 1,
+                symbol: ArrayString::from("ACME").unwrap(),
+                exchange: Some(ArrayString::from("NYSE").unwrap()),
+                digest: [1, 2, 3, 4],
+            },
+            Listings {
+                id: 2,
+                symbol: ArrayString::from("INITECH").unwrap(),
+                exchange: None,
+                digest: [5, 6, 7, 8],
+            },
+        ]
+    );
+    // Only the columns read straight from the table are held inline
+    assert_eq!(
+        listing_symbols().bind(client, &1).one().unwrap(),
+        ListingSymbols {
+            symbol: ArrayString::from("ACME").unwrap(),
+            upper_symbol: "ACME".to_string(),
+        }
+    );
+}
+
+pub fn test_fuzz(client: &mut Client) {
+    use crate::cornucopia::fuzz::public::{custom_composite, nightmare_composite};
+
+    // Arbitrary bytes are rejected without panicking
+    for data in [&[][..], &[0, 0, 0, 3], &[0xff; 64]] {
+        custom_composite(data);
+        nightmare_composite(data);
+    }
+    // As well as truncated values
+    let data: Vec<u8> = client
+        .query_one(
+            "SELECT record_send(ROW('wow', 42, 'Bob')::custom_composite)",
+            &[],
+        )
+        .unwrap()
+        .get(0);
+    for len in 0..=data.len() {
+        custom_composite(&data[..len]);
+    }
+}
+
+pub fn test_hstore(client: &mut Client) {
+    use crate::cornucopia::queries::extensions::{
+        sync::{insert_preference, preference_keys, preferences},
+        Preferences,
+    };
+    use std::collections::HashMap;
+
+    let attrs = HashMap::from([
+        ("theme".to_string(), Some("dark".to_string())),
+        ("font".to_string(), None),
+    ]);
+    let extra = HashMap::from([("lang".to_string(), Some("fr".to_string()))]);
+    insert_preference()
+        .bind(client, &1, &attrs, &Some(&extra))
+        .unwrap();
+    insert_preference()
+        .bind(client, &2, &HashMap::new(), &None::<&HashMap<_, _>>)
+        .unwrap();
+    assert_eq!(
+        preferences().bind(client).all().unwrap(),
+        [
+            Preferences {
+                id: 1,
+                attrs: attrs.clone(),
+                extra: Some(extra),
+            },
+            Preferences {
+                id: 2,
+                attrs: HashMap::new(),
+                extra: None,
+            },
+        ]
+    );
+    // Entries are read without allocating
+    let keys: Vec<String> = preferences()
+        .bind(client)
+        .map(|row| {
+            let mut keys: Vec<_> = row.attrs.map(|(key, _)| key.to_string()).collect();
+            keys.sort();
+            keys.join(",")
+        })
+        .all()
+        .unwrap();
+    assert_eq!(keys, ["font,theme", ""]);
+    assert_eq!(
+        preference_keys()
+            .bind(client, &["theme", "font"].as_slice())
+            .all()
+            .unwrap(),
+        [1]
+    );
+}
+
+pub fn test_for_each(client: &mut Client) {
+    use crate::cornucopia::queries::{
+        inline::sync::listings, ranges::sync::overlapping_reservations,
+    };
+    use cornucopia_sync::Range;
+    use std::ops::Bound;
+
+    // Rows are visited borrowed, e.g. to aggregate some of their fields
+    let mut lengths = Vec::new();
+    let mut digest_sum = 0;
+    let rows = listings()
+        .bind(client)
+        .for_each(|row| {
+            lengths.push(row.symbol.len());
+            digest_sum += row.digest.iter().map(|it| u32::from(*it)).sum::<u32>();
+        })
+        .unwrap();
+    assert_eq!(rows, 2);
+    assert_eq!(lengths, [4, 7]);
+    assert_eq!(digest_sum, 36);
+    // As well as single column rows
+    let mut ids = Vec::new();
+    let rows = overlapping_reservations()
+        .bind(client, &Range::new(Bound::Unbounded, Bound::Unbounded))
+        .for_each(|id| ids.push(id))
+        .unwrap();
+    assert_eq!(rows, ids.len() as u64);
+    assert!(!ids.is_empty());
+}
+
+pub fn test_extension_types(client: &mut Client) {
+    use crate::cornucopia::queries::extensions::{
+        sync::{categories, category_by_name, insert_category, subcategories},
+        Categories,
+    };
+
+    let path = |it: &str| Ltree(it.to_string());
+    insert_category()
+        .bind(
+            client,
+            &1,
+            &"Books",
+            &path("shop.books"),
+            &Some(["Novels", "Reads"].as_slice()),
+        )
+        .unwrap();
+    insert_category()
+        .bind(
+            client,
+            &2,
+            &"Comics",
+            &path("shop.books.comics"),
+            &None::<&[&str]>,
+        )
+        .unwrap();
+    assert_eq!(
+        categories().bind(client).all().unwrap(),
+        [
+            Categories {
+                id: 1,
+                name: "Books".to_string(),
+                path: path("shop.books"),
+                aliases: Some(vec!["Novels".to_string(), "Reads".to_string()]),
+            },
+            Categories {
+                id: 2,
+                name: "Comics".to_string(),
+                path: path("shop.books.comics"),
+                aliases: None,
+            },
+        ]
+    );
+    // `citext` compares case insensitively
+    assert_eq!(category_by_name().bind(client, &"COMICS").one().unwrap(), 2);
+    assert_eq!(
+        subcategories()
+            .bind(client, &path("shop.books.comics"))
+            .all()
+            .unwrap(),
+        [path("shop.books.comics")]
+    );
+}
+
+pub fn test_text_search(client: &mut Client) {
+    use crate::cornucopia::queries::text_search::sync::{
+        articles, insert_article, insert_article_lexemes, normalize_query, search_articles,
+    };
+    use cornucopia_sync::{Lexeme, LexemePosition, TsVector, Weight};
+
+    insert_article()
+        .bind(client, &1, &"The fat rats ate the fat cat")
+        .unwrap();
+    let lexeme = |word: &str, positions: &[(u16, Weight)]| Lexeme {
+        word: word.to_string(),
+        positions: positions
+            .iter()
+            .map(|&(position, weight)| LexemePosition { position, weight })
+            .collect(),
+    };
+    let body = TsVector(vec![
+        lexeme("dog", &[(2, Weight::A)]),
+        lexeme("lazi", &[(1, Weight::D)]),
+    ]);
+    insert_article_lexemes()
+        .bind(client, &2, &"Lazy dog", &body)
+        .unwrap();
+    let rows = articles().bind(client).all().unwrap();
+    assert_eq!(
+        rows[0].body,
+        TsVector(vec![
+            lexeme("ate", &[(4, Weight::D)]),
+            lexeme("cat", &[(7, Weight::D)]),
+            lexeme("fat", &[(2, Weight::D), (6, Weight::D)]),
+            lexeme("rat", &[(3, Weight::D)]),
+        ])
+    );
+    assert!(rows[0].body.contains("rat"));
+    assert_eq!(rows[1].body, body);
+    // Queries are normalized by `to_tsquery`, so `rats` matches `rat`
+    assert_eq!(
+        search_articles().bind(client, &"Rats & fat").all().unwrap(),
+        [1]
+    );
+    assert_eq!(
+        search_articles()
+            .bind(client, &"dog:A | cat")
+            .all()
+            .unwrap(),
+        [1, 2]
+    );
+    assert_eq!(
+        normalize_query()
+            .bind(client, &"Fat & (Rats | cat:*AB) & !dog <2> bird")
+            .one()
+            .unwrap()
+            .to_string(),
+        "'fat' & ( 'rat' | 'cat':*AB ) & !'dog' <2> 'bird'"
+    );
+}
+
+pub fn test_money(client: &mut Client) {
+    use crate::cornucopia::queries::money::sync::{insert_invoice, invoice_totals, invoices};
+    use cornucopia_sync::Money;
+    use rust_decimal::Decimal;
+
+    insert_invoice()
+        .bind(
+            client,
+            &1,
+            &Money(12_34),
+            &Some(Money::try_from(Decimal::new(5, 1)).unwrap()),
+            &[Money(6_17), Money(6_17)].as_slice(),
+        )
+        .unwrap();
+    insert_invoice()
+        .bind(client, &2, &Money(-99), &None, &[].as_slice())
+        .unwrap();
+    assert!(Money::try_from(Decimal::new(1, 3)).is_err());
+    let rows = invoices().bind(client).all().unwrap();
+    assert_eq!(rows[0].total, Decimal::new(12_34, 2));
+    assert_eq!(rows[0].discount, Some(Decimal::new(50, 2)));
+    assert_eq!(
+        rows[0].installments,
+        [Decimal::new(6_17, 2), Decimal::new(6_17, 2)]
+    );
+    assert_eq!(rows[1].total, Decimal::new(-99, 2));
+    assert_eq!(rows[1].discount, None);
+    let totals = invoice_totals().bind(client).all().unwrap();
+    assert_eq!(totals[1].total, Decimal::new(-49, 2));
+}
+
+pub fn test_interval(client: &mut Client) {
+    use crate::cornucopia::queries::interval::sync::{insert_subscription, renewal, subscriptions};
+    use cornucopia_sync::ExactInterval;
+    use time::Duration;
+
+    insert_subscription()
+        .bind(
+            client,
+            &1,
+            &ExactInterval(Duration::days(30)),
+            &Some(Duration::hours(36).into()),
+        )
+        .unwrap();
+    insert_subscription()
+        .bind(client, &2, &Duration::microseconds(-1).into(), &None)
+        .unwrap();
+    let rows = subscriptions().bind(client).all().unwrap();
+    assert_eq!(rows[0].period, Duration::days(30));
+    assert_eq!(rows[0].grace, Some(Duration::hours(36)));
+    assert_eq!(rows[1].period, Duration::microseconds(-1));
+    assert_eq!(rows[1].grace, None);
+    assert_eq!(
+        renewal().bind(client, &1).one().unwrap(),
+        Duration::days(31) + Duration::hours(2)
+    );
+}
+
+// Test bit strings mapped to `Vec<bool>`
+pub fn test_bit(client: &mut Client) {
+    use crate::cornucopia::queries::bit::sync::{granted, insert_permission, permissions};
+    use cornucopia_sync::BitString;
+
+    let flags = BitString::from(vec![true, true, false, true]);
+    let history = [BitString::default(), vec![false; 9].into()];
+    insert_permission()
+        .bind(
+            client,
+            &1,
+            &flags,
+            &Some([true].as_slice().into()),
+            &history.as_slice(),
+        )
+        .unwrap();
+    assert_eq!(flags.to_string(), "1101");
+    assert_eq!(flags.as_bytes(), [0b1101_0000]);
+    let rows = permissions().bind(client).all().unwrap();
+    assert_eq!(rows[0].flags, [true, true, false, true]);
+    assert_eq!(rows[0].mask, Some(vec![true]));
+    assert_eq!(rows[0].history, [vec![], vec![false; 9]]);
+    let row = granted().bind(client, &1).one().unwrap();
+    assert_eq!(
+        (row.granted, row.text.as_str()),
+        (vec![false, true, false, false], "1101")
+    );
+}
+
+// Test the geometric types
+pub fn test_geometric(client: &mut Client) {
+    use crate::cornucopia::queries::geometric::sync::{contained, insert_shape, shapes};
+    use cornucopia_sync::{BoundingBox, Circle, Line, LineSegment, Path, Point, Polygon};
+
+    let square = vec![
+        Point::new(0., 0.),
+        Point::new(0., 2.),
+        Point::new(2., 2.),
+        Point::new(2., 0.),
+    ];
+    insert_shape()
+        .bind(
+            client,
+            &1,
+            &Point::new(1., 1.),
+            &Some(Line {
+                a: 1.,
+                b: -1.,
+                c: 0.,
+            }),
+            &LineSegment {
+                start: Point::new(0., 0.),
+                end: Point::new(1., 1.),
+            },
+            // Stored with the corners swapped
+            &BoundingBox {
+                high: Point::new(0., 0.),
+                low: Point::new(2., 2.),
+            },
+            &Path {
+                closed: false,
+                points: square.clone(),
+            },
+            &Polygon {
+                points: square.clone(),
+            },
+            &Circle {
+                center: Point::new(1., 1.),
+                radius: 1.5,
+            },
+        )
+        .unwrap();
+    let rows = shapes().bind(client).all().unwrap();
+    assert_eq!(rows[0].at, Point::new(1., 1.));
+    assert_eq!(
+        rows[0].edge,
+        Some(Line {
+            a: 1.,
+            b: -1.,
+            c: 0.
+        })
+    );
+    assert_eq!(rows[0].segment.end, Point::new(1., 1.));
+    assert_eq!(
+        rows[0].bounds,
+        BoundingBox {
+            high: Point::new(2., 2.),
+            low: Point::new(0., 0.),
+        }
+    );
+    assert!(!rows[0].route.closed);
+    assert_eq!(rows[0].route.points, square);
+    assert_eq!(rows[0].area.points, square);
+    assert_eq!(rows[0].zone.radius, 1.5);
+    assert_eq!(
+        contained()
+            .bind(client, &Point::new(0.5, 1.))
+            .all()
+            .unwrap(),
+        [1]
+    );
+    assert!(contained()
+        .bind(client, &Point::new(3., 3.))
+        .all()
+        .unwrap()
+        .is_empty());
+}
+
+// Test anonymous records decoded as tuples
+pub fn test_record(client: &mut Client) {
+    use crate::cornucopia::queries::record::{
+        sync::{items, latest_per_series, record_columns},
+        LatestPerSeries,
+    };
+
+    assert_eq!(
+        items().bind(client).all().unwrap(),
+        [
+            (1, "item 1".to_string(), None),
+            (2, "item 2".to_string(), Some(true))
+        ]
+    );
+    assert_eq!(
+        latest_per_series().bind(client).all().unwrap(),
+        [
+            LatestPerSeries {
+                series: 1,
+                latest: (3, 1.5)
+            },
+            LatestPerSeries {
+                series: 2,
+                latest: (3, 1.5)
+            }
+        ]
+    );
+    let row = record_columns().bind(client).one().unwrap();
+    assert_eq!((row.a, row.b.as_str()), (1, "x"));
+}
+
+pub fn test_codecs(client: &mut Client) {
+    use crate::cornucopia::queries::codecs::{
+        sync::{insert_labelled, insert_region, labelled, region_dims, regions},
+        Labelled, Regions,
+    };
+
+    let cube = |lower: &[f64], upper: &[f64]| Cube {
+        lower: lower.to_vec(),
+        upper: upper.to_vec(),
+    };
+    let unit = cube(&[0., 0.], &[1., 1.]);
+    let point = cube(&[2., 3., 4.], &[2., 3., 4.]);
+    insert_region()
+        .bind(client, &1, &&unit, &Some([&point, &unit].as_slice()))
+        .unwrap();
+    insert_region()
+        .bind(client, &2, &&point, &None::<&[&Cube]>)
+        .unwrap();
+    assert_eq!(
+        regions().bind(client).all().unwrap(),
+        [
+            Regions {
+                id: 1,
+                bounds: unit.clone(),
+                samples: Some(vec![point.clone(), unit.clone()]),
+            },
+            Regions {
+                id: 2,
+                bounds: point.clone(),
+                samples: None,
+            },
+        ]
+    );
+    // Borrowed rows decode the codec's borrowed type
+    assert_eq!(
+        regions()
+            .bind(client)
+            .map(|it| it.bounds.dim())
+            .all()
+            .unwrap(),
+        [2, 3]
+    );
+    assert_eq!(
+        region_dims()
+            .bind(client, &&unit)
+            .map(|it| (it.id, it.dim))
+            .all()
+            .unwrap(),
+        [(1, 2)]
+    );
+
+    // Only the configured column is decoded by its codec, the other text columns aren't
+    let (first, second) = (Label("first".into()), Label("second".into()));
+    insert_labelled()
+        .bind(client, &1, &first, &"a note")
+        .unwrap();
+    insert_labelled()
+        .bind(client, &2, &second, &"another note")
+        .unwrap();
+    let rows = labelled()
+        .bind(client, &[first.clone()].as_slice())
+        .all()
+        .unwrap();
+    assert_eq!(
+        rows,
+        [Labelled {
+            id: 1,
+            label: first,
+            note: "a note".to_string(),
+        }]
+    );
+}
+
+pub fn test_matrix(client: &mut Client) {
+    use crate::cornucopia::queries::matrix::{
+        sync::{grid_cells, grids, insert_grids},
+        Grids,
+    };
+
+    insert_grids().bind(client).unwrap();
+    assert_eq!(
+        grids().bind(client).all().unwrap(),
+        [
+            Grids {
+                id: 1,
+                cells: vec![vec![1, 2, 3], vec![4, 5, 6]],
+                labels: Some(vec![
+                    vec![Some("a".to_string()), None],
+                    vec![Some("c".to_string()), Some("d".to_string())],
+                ]),
+            },
+            Grids {
+                id: 2,
+                cells: vec![],
+                labels: None,
+            },
+        ]
+    );
+    // Borrowed rows iterate over the rows of the matrix
+    assert_eq!(
+        grid_cells()
+            .bind(client, &1)
+            .map(|cells| cells.map(|row| row.iter().sum::<i32>()).collect::<Vec<_>>())
+            .one()
+            .unwrap(),
+        [6, 15]
+    );
+}
+
+pub fn test_outer_joins(client: &mut Client) {
+    use crate::cornucopia::queries::joins::{
+        sync::{
+            all_members, insert_members, insert_teams, member_counts, member_teams, team_leads,
+            team_members, teammates,
+        },
+        AllMembers, MemberCounts, MemberTeams, TeamLeads, TeamMembers, Teammates,
+    };
+
+    insert_teams().bind(client).unwrap();
+    insert_members().bind(client).unwrap();
+    let some = |it: &str| Some(it.to_string());
+    // Columns of the table on the right of a left join are nullable
+    assert_eq!(
+        team_members().bind(client).all().unwrap(),
+        [
+            TeamMembers {
+                team: "Core".to_string(),
+                member: some("Ada"),
+            },
+            TeamMembers {
+                team: "Core".to_string(),
+                member: some("Grace"),
+            },
+            TeamMembers {
+                team: "Docs".to_string(),
+                member: None,
+            },
+        ]
+    );
+    // And those on the left of a right join
+    assert_eq!(
+        member_teams().bind(client).all().unwrap(),
+        [
+            MemberTeams {
+                name: "Ada".to_string(),
+                team: some("Core"),
+            },
+            MemberTeams {
+                name: "Grace".to_string(),
+                team: some("Core"),
+            },
+            MemberTeams {
+                name: "Linus".to_string(),
+                team: None,
+            },
+        ]
+    );
+    // Both sides of a full join
+    assert_eq!(
+        all_members().bind(client).all().unwrap(),
+        [
+            AllMembers {
+                team: some("Core"),
+                member: some("Ada"),
+            },
+            AllMembers {
+                team: some("Core"),
+                member: some("Grace"),
+            },
+            AllMembers {
+                team: some("Docs"),
+                member: None,
+            },
+            AllMembers {
+                team: None,
+                member: some("Linus"),
+            },
+        ]
+    );
+    // Tables joined to themselves are told apart by the qualifiers of the columns
+    assert_eq!(
+        teammates().bind(client).all().unwrap(),
+        [
+            Teammates {
+                name: "Ada".to_string(),
+                teammate: some("Grace"),
+            },
+            Teammates {
+                name: "Grace".to_string(),
+                teammate: some("Ada"),
+            },
+            Teammates {
+                name: "Linus".to_string(),
+                teammate: None,
+            },
+        ]
+    );
+    // Declared non-null columns aren't nulled by outer joins
+    assert_eq!(
+        team_leads().bind(client).one().unwrap(),
+        TeamLeads {
+            name: "Core".to_string(),
+            lead: "Ada".to_string(),
+        }
+    );
+    assert_eq!(
+        member_counts().bind(client).all().unwrap(),
+        [
+            MemberCounts {
+                name: "Core".to_string(),
+                members: Some(2),
+            },
+            MemberCounts {
+                name: "Docs".to_string(),
+                members: None,
+            },
+        ]
+    );
+}
+
+// Test the row structs of the configured tables
+pub fn test_tables(client: &mut Client) {
+    use crate::cornucopia::queries::tables::{
+        sync::{member, team},
+        Member, Team,
+    };
+
+    assert_eq!(
+        team().bind(client).all().unwrap(),
+        [
+            Team {
+                id: 1,
+                name: "Core".to_string(),
+            },
+            Team {
+                id: 2,
+                name: "Docs".to_string(),
+            },
+        ]
+    );
+    // Columns which aren't `NOT NULL` are nullable
+    assert_eq!(
+        member().bind(client).all().unwrap(),
+        [
+            Member {
+                id: 1,
+                team_id: Some(1),
+                name: "Ada".to_string(),
+            },
+            Member {
+                id: 2,
+                team_id: Some(1),
+                name: "Grace".to_string(),
+            },
+            Member {
+                id: 3,
+                team_id: None,
+                name: "Linus".to_string(),
+            },
+        ]
+    );
+}
+
+// Test the modules of the extra query folders, named after their folder when taken
+pub fn test_extra_queries(client: &mut Client) {
+    use crate::cornucopia::queries::billing_joins::sync::team_names;
+
+    assert_eq!(
+        team_names().bind(client).all().unwrap(),
+        ["Core".to_string(), "Docs".to_string()]
+    );
 }
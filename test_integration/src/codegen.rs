@@ -64,6 +64,45 @@ pub(crate) fn run_codegen_test(
                         test.destination.to_str().unwrap()
                     ))?;
                 }
+
+                // A serialized preparation must emit the same code
+                let preparation =
+                    cornucopia::prepare(client, &test.queries_path, &CodegenSettings::from(&test))
+                        .map_err(Error::report)?;
+                let preparation = serde_json::from_str(&serde_json::to_string(&preparation)?)?;
+                let emitted = cornucopia::emit(preparation, &CodegenSettings::from(&test));
+                if emitted != new_codegen {
+                    Err(format!(
+                        "\"{}\" differs once its preparation is serialized",
+                        test.destination.to_str().unwrap()
+                    ))?;
+                }
+
+                // Preparations reused from the cache must generate the same code, the
+                // nullability checks bypassing it
+                let cache = std::env::temp_dir().join("cornucopia_test_cache.json");
+                std::fs::remove_file(&cache).ok();
+                let settings = CodegenSettings {
+                    cache: Some(cache.clone()),
+                    verify_nullability: false,
+                    ..CodegenSettings::from(&test)
+                };
+                for _ in 0..2 {
+                    let cached = cornucopia::generate_live(
+                        client,
+                        &test.queries_path,
+                        None,
+                        settings.clone(),
+                    )
+                    .map_err(Error::report)?;
+                    if cached != new_codegen {
+                        Err(format!(
+                            "\"{}\" differs when generated with a cache",
+                            test.destination.to_str().unwrap()
+                        ))?;
+                    }
+                }
+                std::fs::remove_file(&cache).ok();
             }
             println!("(generate) {} {}", test.name, "OK".green());
 
@@ -43,6 +43,9 @@ pub(crate) fn run_errors_test(
                 "queries/test.sql",
                 test.query.as_deref().unwrap_or_default(),
             )?;
+            if let Some(sidecar) = &test.sidecar {
+                std::fs::write("queries/queries.toml", sidecar)?;
+            }
 
             // Run codegen
             let result = cornucopia::load_schema(client, &["schema.sql"])
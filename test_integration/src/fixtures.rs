@@ -3,9 +3,11 @@ use std::{
     path::{Path, PathBuf},
 };
 
-use cornucopia::CodegenSettings;
+use cornucopia::{CodegenSettings, Config};
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 
+use crate::plugins;
+
 #[derive(Serialize, Deserialize)]
 struct TestSuiteSerde<T> {
     #[serde(rename = "test")]
@@ -60,7 +62,24 @@ pub(crate) struct CodegenTest {
     #[serde(default)]
     pub(crate) derive_ser: bool,
     #[serde(default)]
+    pub(crate) facade: bool,
+    #[serde(default)]
+    pub(crate) prepare_all: bool,
+    #[serde(default)]
+    pub(crate) tracing: bool,
+    #[serde(default)]
+    pub(crate) db_errors: bool,
+    #[serde(default)]
+    pub(crate) fuzz: bool,
+    #[serde(default)]
+    pub(crate) upstream_compat: bool,
+    #[serde(default)]
     pub(crate) run: bool,
+    #[serde(default)]
+    pub(crate) config: Option<PathBuf>,
+    /// Names of the test plugins to run
+    #[serde(default)]
+    pub(crate) plugins: Vec<String>,
 }
 
 fn default_queries_path() -> PathBuf {
@@ -73,11 +92,30 @@ fn default_destination_path() -> PathBuf {
 
 impl From<&CodegenTest> for CodegenSettings {
     fn from(codegen_test: &CodegenTest) -> Self {
+        let config = codegen_test
+            .config
+            .as_ref()
+            .map(|path| Config::check(path).unwrap())
+            .unwrap_or_default();
         Self {
             gen_async: codegen_test.r#async || !codegen_test.sync,
             gen_sync: codegen_test.sync,
             derive_ser: codegen_test.derive_ser,
+            gen_facade: codegen_test.facade,
+            gen_prepare_all: codegen_test.prepare_all,
+            gen_tracing: codegen_test.tracing,
+            gen_db_errors: codegen_test.db_errors,
+            gen_fuzz: codegen_test.fuzz,
+            upstream_compat: codegen_test.upstream_compat,
+            format: true,
+            plugins: codegen_test
+                .plugins
+                .iter()
+                .map(|it| plugins::by_name(it))
+                .collect(),
+            ..Default::default()
         }
+        .with_config(config)
     }
 }
 
@@ -87,15 +125,31 @@ pub(crate) struct ErrorTest {
     pub(crate) name: String,
     pub(crate) query: Option<String>,
     pub(crate) schema: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) config: Option<String>,
+    /// Annotations of the queries, in a `queries.toml` sidecar file
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) sidecar: Option<String>,
+    /// Generate code compatible with the upstream client crates
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub(crate) upstream_compat: bool,
     pub(crate) error: String,
 }
 
 impl From<&ErrorTest> for CodegenSettings {
-    fn from(_error_test: &ErrorTest) -> Self {
+    fn from(error_test: &ErrorTest) -> Self {
+        let config = error_test
+            .config
+            .as_deref()
+            .map(|config| toml::from_str(config).unwrap())
+            .unwrap_or_default();
         Self {
             derive_ser: false,
             gen_async: false,
             gen_sync: true,
+            upstream_compat: error_test.upstream_compat,
+            ..Default::default()
         }
+        .with_config(config)
     }
 }
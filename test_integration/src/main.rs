@@ -7,6 +7,7 @@ use cornucopia::container;
 mod codegen;
 mod errors;
 mod fixtures;
+mod plugins;
 mod utils;
 
 /// Integration test CLI arguments
@@ -0,0 +1,41 @@
+use std::sync::Arc;
+
+use cornucopia::plugin::{ItemInfo, ItemKind, Plugin, TypeContent, TypeInfo};
+
+/// Plugin used by the codegen tests, by name
+pub(crate) fn by_name(name: &str) -> Arc<dyn Plugin> {
+    match name {
+        "columns" => Arc::new(ColumnsPlugin),
+        _ => panic!("unknown test plugin `{name}`"),
+    }
+}
+
+/// Lists the database names of the fields of composites and rows in a `COLUMNS` constant
+#[derive(Debug)]
+struct ColumnsPlugin;
+
+impl Plugin for ColumnsPlugin {
+    fn custom_type(&self, ty: &TypeInfo<'_>) -> Option<String> {
+        let TypeContent::Composite(fields) = &ty.content else {
+            return None;
+        };
+        Some(columns_impl(
+            ty.struct_name,
+            fields.iter().map(|it| it.db_name),
+        ))
+    }
+
+    fn item(&self, item: &ItemInfo<'_>) -> Option<String> {
+        (item.kind == ItemKind::Row)
+            .then(|| columns_impl(item.name, item.fields.iter().map(|it| it.db_name)))
+    }
+}
+
+fn columns_impl<'a>(name: &str, columns: impl Iterator<Item = &'a str>) -> String {
+    let columns: Vec<_> = columns.map(|it| format!("\"{it}\"")).collect();
+    format!(
+        "impl {name} {{ pub const COLUMNS: [&'static str; {}] = [{}]; }}",
+        columns.len(),
+        columns.join(", ")
+    )
+}